@@ -0,0 +1,51 @@
+//! Compares `strip_ansi` with the regex it replaced on typical terminal output.
+//!
+//! Run with `cargo bench -p clauset-core --bench strip_ansi`.
+
+use clauset_core::strip_ansi;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static ANSI_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"\x1b\[[0-9;?]*[A-Za-z]",
+        r"|\x1b\][^\x07]*\x07",
+        r"|\x1b\][^\x1b]*\x1b\\",
+        r"|\x1b[()][A-Z0-9]",
+        r"|\x1b[=>MNOP78]",
+        r"|\x1b",
+    ))
+    .unwrap()
+});
+
+fn strip_with_regex(text: &str) -> String {
+    ANSI_REGEX.replace_all(text, "").to_string()
+}
+
+/// About 8KB of styled Claude Code output, the size of the parse window.
+fn styled_window() -> String {
+    let screen = concat!(
+        "\x1b[2K\x1b[1G\x1b[38;5;174m✻\x1b[39m \x1b[1mThinking…\x1b[22m \x1b[2m(esc to interrupt)\x1b[22m\r\n",
+        "\x1b[38;5;246m⏺\x1b[39m \x1b[1mRead\x1b[22m(src/buffer.rs)\r\n",
+        "  ⎿  Read 412 lines\r\n",
+        "\x1b]0;clauset\x07\x1b[?25l\x1b[2mOpus 4 | $0.42 | 12.3K/4.1K | ctx:38%\x1b[22m\x1b[?25h\r\n",
+    );
+    screen.repeat(8192 / screen.len() + 1)
+}
+
+fn plain_window() -> String {
+    "test parser::tests::test_status_line ... ok\n".repeat(8192 / 44 + 1)
+}
+
+fn bench_strip_ansi(c: &mut Criterion) {
+    for (name, text) in [("styled", styled_window()), ("plain", plain_window())] {
+        let mut group = c.benchmark_group(format!("strip_ansi/{name}"));
+        group.bench_function("scanner", |b| b.iter(|| strip_ansi(black_box(&text)).len()));
+        group.bench_function("regex", |b| b.iter(|| strip_with_regex(black_box(&text)).len()));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_strip_ansi);
+criterion_main!(benches);
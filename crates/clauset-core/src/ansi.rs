@@ -0,0 +1,411 @@
+//! ANSI escape handling for terminal output.
+//!
+//! Consumers want different renderings of the same bytes: raw ANSI for a
+//! terminal widget, plain text for search, and HTML for the chat view. SGR
+//! styling (colors, bold, italic, underline) becomes `<span style=…>` runs;
+//! all other escape sequences (cursor movement, OSC titles, ...) are dropped.
+
+use crate::tui_menu_parser::strip_ansi_codes;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// How ANSI escape sequences are rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnsiMode {
+    /// Leave escape sequences as they are.
+    #[default]
+    Keep,
+    /// Remove all escape sequences.
+    Strip,
+    /// Convert styling to HTML spans and drop everything else.
+    Html,
+}
+
+/// Render terminal output according to `mode`.
+pub fn render_ansi(bytes: &[u8], mode: AnsiMode) -> String {
+    match mode {
+        AnsiMode::Keep => String::from_utf8_lossy(bytes).into_owned(),
+        AnsiMode::Strip => strip_ansi_codes(&String::from_utf8_lossy(bytes)),
+        AnsiMode::Html => ansi_to_html(bytes),
+    }
+}
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// Strip ANSI escape sequences from terminal output.
+///
+/// The activity parser calls this several times per chunk, so it scans bytes
+/// by hand instead of running a regex, and borrows `text` when there is
+/// nothing to strip. Removes, in order of precedence at each ESC:
+/// - CSI sequences: `ESC [` parameters (`0-9;?`) and a final letter
+/// - OSC sequences: `ESC ]` up to the next BEL, or else up to an `ESC \`
+/// - Character set selection: `ESC (` or `ESC )` and `A-Z`/`0-9`
+/// - Single-character escapes: `ESC` and one of `=>MNOP78`
+/// - Any other ESC on its own
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    let Some(first) = bytes.iter().position(|&b| b == ESC) else {
+        return Cow::Borrowed(text);
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut i = first;
+    // Last BEL search (start, result), so a run of unterminated OSC
+    // sequences doesn't rescan the rest of the text each time
+    let mut bel_cache: Option<(usize, Option<usize>)> = None;
+    while i < bytes.len() {
+        if bytes[i] != ESC {
+            i += 1;
+            continue;
+        }
+        let end = escape_end(bytes, i, &mut bel_cache);
+        // Sequences start at ESC and end after an ASCII byte, so these are
+        // always char boundaries
+        out.push_str(&text[copied..i]);
+        copied = end;
+        i = end;
+    }
+    out.push_str(&text[copied..]);
+    Cow::Owned(out)
+}
+
+/// End (exclusive) of the escape sequence starting with the ESC at `start`.
+pub(crate) fn escape_sequence_end(bytes: &[u8], start: usize) -> usize {
+    escape_end(bytes, start, &mut None)
+}
+
+/// End (exclusive) of the escape sequence starting with the ESC at `start`.
+fn escape_end(bytes: &[u8], start: usize, bel_cache: &mut Option<(usize, Option<usize>)>) -> usize {
+    match bytes.get(start + 1) {
+        Some(b'[') => {
+            let params = bytes[start + 2..]
+                .iter()
+                .take_while(|b| matches!(b, b'0'..=b'9' | b';' | b'?'))
+                .count();
+            let last = start + 2 + params;
+            if bytes.get(last).is_some_and(u8::is_ascii_alphabetic) {
+                return last + 1;
+            }
+        }
+        Some(b']') => {
+            let from = start + 2;
+            let bel = match *bel_cache {
+                Some((searched, found)) if searched <= from && found.is_none_or(|at| at >= from) => found,
+                _ => {
+                    let found = bytes[from..].iter().position(|&b| b == BEL).map(|at| from + at);
+                    *bel_cache = Some((from, found));
+                    found
+                }
+            };
+            if let Some(at) = bel {
+                return at + 1;
+            }
+            if let Some(at) = bytes[from..].iter().position(|&b| b == ESC)
+                && bytes.get(from + at + 1) == Some(&b'\\')
+            {
+                return from + at + 2;
+            }
+        }
+        Some(b'(' | b')') if bytes.get(start + 2).is_some_and(|b| b.is_ascii_uppercase() || b.is_ascii_digit()) => {
+            return start + 3;
+        }
+        Some(b'=' | b'>' | b'M' | b'N' | b'O' | b'P' | b'7' | b'8') => return start + 2,
+        _ => {}
+    }
+    start + 1
+}
+
+/// Standard and bright colors (xterm defaults).
+const PALETTE: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+/// Text style set by SGR codes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Style {
+    fg: Option<String>,
+    bg: Option<String>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    fn is_plain(&self) -> bool {
+        *self == Style::default()
+    }
+
+    fn css(&self) -> String {
+        let mut rules = Vec::new();
+        if let Some(fg) = &self.fg {
+            rules.push(format!("color:{fg}"));
+        }
+        if let Some(bg) = &self.bg {
+            rules.push(format!("background-color:{bg}"));
+        }
+        if self.bold {
+            rules.push("font-weight:bold".to_string());
+        }
+        if self.italic {
+            rules.push("font-style:italic".to_string());
+        }
+        if self.underline {
+            rules.push("text-decoration:underline".to_string());
+        }
+        rules.join(";")
+    }
+
+    /// Apply the parameters of one SGR sequence.
+    fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u16> = params
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                code @ 30..=37 => self.fg = Some(PALETTE[(code - 30) as usize].to_string()),
+                code @ 90..=97 => self.fg = Some(PALETTE[(code - 90 + 8) as usize].to_string()),
+                code @ 40..=47 => self.bg = Some(PALETTE[(code - 40) as usize].to_string()),
+                code @ 100..=107 => self.bg = Some(PALETTE[(code - 100 + 8) as usize].to_string()),
+                39 => self.fg = None,
+                49 => self.bg = None,
+                code @ (38 | 48) => {
+                    let (color, used) = extended_color(&codes[i + 1..]);
+                    if code == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                    i += used;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Parse a `5;n` or `2;r;g;b` extended color. Returns the color and parameters consumed.
+fn extended_color(params: &[u16]) -> (Option<String>, usize) {
+    match params {
+        [5, n, ..] => (Some(color_256(*n)), 2),
+        [2, r, g, b, ..] => (
+            Some(format!("#{:02x}{:02x}{:02x}", r.min(&255), g.min(&255), b.min(&255))),
+            4,
+        ),
+        _ => (None, params.len()),
+    }
+}
+
+/// CSS color for an xterm 256-color index.
+fn color_256(n: u16) -> String {
+    match n {
+        0..=15 => PALETTE[n as usize].to_string(),
+        16..=231 => {
+            const LEVELS: [u16; 6] = [0, 95, 135, 175, 215, 255];
+            let n = n - 16;
+            let (r, g, b) = (
+                LEVELS[(n / 36) as usize],
+                LEVELS[(n / 6 % 6) as usize],
+                LEVELS[(n % 6) as usize],
+            );
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        }
+        _ => {
+            let level = 8 + 10 * (n.min(255) - 232);
+            format!("#{:02x}{:02x}{:02x}", level, level, level)
+        }
+    }
+}
+
+/// Convert terminal output to HTML, turning SGR styling into `<span>` runs.
+///
+/// Text is HTML-escaped, a reset (`ESC[0m`) closes the open span, and
+/// non-styling escape sequences and control characters are dropped.
+pub fn ansi_to_html(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let mut html = String::with_capacity(text.len());
+    let mut style = Style::default();
+    let mut open: Option<Style> = None;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            match chars.next() {
+                // CSI: parameters, then a final byte in @..~
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            if c == 'm' {
+                                style.apply_sgr(&params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+                // OSC: terminated by BEL or ESC \
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Charset selection takes one more character
+                Some('(' | ')') => {
+                    chars.next();
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if c.is_control() && c != '\n' && c != '\t' {
+            continue;
+        }
+
+        if open.as_ref() != Some(&style) && !(open.is_none() && style.is_plain()) {
+            if open.take().is_some() {
+                html.push_str("</span>");
+            }
+            if !style.is_plain() {
+                html.push_str(&format!("<span style=\"{}\">", style.css()));
+                open = Some(style.clone());
+            }
+        }
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+
+    if open.is_some() {
+        html.push_str("</span>");
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use once_cell::sync::Lazy;
+    use proptest::prelude::*;
+    use regex::Regex;
+
+    /// The regex `strip_ansi` replaced, kept as its reference.
+    static ANSI_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(concat!(
+            r"\x1b\[[0-9;?]*[A-Za-z]",    // CSI sequences (colors, cursor, etc.)
+            r"|\x1b\][^\x07]*\x07",        // OSC sequences ending with BEL
+            r"|\x1b\][^\x1b]*\x1b\\",      // OSC sequences ending with ST
+            r"|\x1b[()][A-Z0-9]",          // Character set selection
+            r"|\x1b[=>MNOP78]",            // Other single-char escapes
+            r"|\x1b",                       // Catch any remaining bare ESC
+        ))
+        .unwrap()
+    });
+
+    fn strip_with_regex(text: &str) -> String {
+        ANSI_REGEX.replace_all(text, "").to_string()
+    }
+
+    #[test]
+    fn test_strip_ansi_sequences() {
+        let cases = [
+            ("\x1b[1;32mReady\x1b[0m", "Ready"),
+            ("\x1b[?25lhidden\x1b[?25h", "hidden"),
+            ("\x1b]0;title\x07text", "text"),
+            ("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\", "link"),
+            ("\x1b(Bascii\x1b)0", "ascii"),
+            ("\x1b7saved\x1b8\x1b=", "saved"),
+            ("bare \x1b escape \x1b[12", "bare  escape [12"),
+            ("\x1b]unterminated", "]unterminated"),
+            ("✻ Thinking… \x1b[2m(esc to interrupt)\x1b[22m", "✻ Thinking… (esc to interrupt)"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(strip_ansi(input), expected, "input {input:?}");
+            assert_eq!(strip_with_regex(input), expected, "input {input:?}");
+        }
+    }
+
+    #[test]
+    fn test_strip_ansi_borrows_plain_text() {
+        assert!(matches!(strip_ansi("plain output\n"), Cow::Borrowed("plain output\n")));
+        assert!(matches!(strip_ansi(""), Cow::Borrowed("")));
+        assert!(matches!(strip_ansi("\x1b[0m"), Cow::Owned(_)));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_strip_ansi_matches_regex(
+            parts in prop::collection::vec(
+                prop_oneof![
+                    Just("\x1b".to_string()),
+                    Just("\x07".to_string()),
+                    prop::sample::select(vec!["[", "]", "(", ")", "\\", ";", "?", "=", ">", "M", "7", "8", "B", "0", "m", "é", "\n"])
+                        .prop_map(str::to_string),
+                    "[a-zA-Z0-9;?\\[\\] ]{0,4}",
+                    any::<String>(),
+                ],
+                0..40,
+            )
+        ) {
+            let text = parts.concat();
+            prop_assert_eq!(strip_ansi(&text), strip_with_regex(&text));
+        }
+    }
+
+    #[test]
+    fn test_colored_text_becomes_spans_closed_on_reset() {
+        let html = ansi_to_html(b"\x1b[31mred\x1b[0m plain \x1b[1;4;32mgo\x1b[m");
+        assert_eq!(
+            html,
+            "<span style=\"color:#cd0000\">red</span> plain \
+             <span style=\"color:#00cd00;font-weight:bold;text-decoration:underline\">go</span>"
+        );
+    }
+
+    #[test]
+    fn test_style_changes_and_extended_colors() {
+        let html = ansi_to_html(b"\x1b[38;5;196;48;2;0;0;255mA\x1b[39mB\x1b[49mC");
+        assert_eq!(
+            html,
+            "<span style=\"color:#ff0000;background-color:#0000ff\">A</span>\
+             <span style=\"background-color:#0000ff\">B</span>C"
+        );
+    }
+
+    #[test]
+    fn test_cursor_movement_ignored_and_text_escaped() {
+        let html = ansi_to_html(b"\x1b[2K\x1b[1G\x1b]0;title\x07<b> & \"x\"\r\n\x1b[?25l");
+        assert_eq!(html, "&lt;b&gt; &amp; &quot;x&quot;\n");
+    }
+
+    #[test]
+    fn test_render_modes() {
+        let raw = b"\x1b[1mbold\x1b[0m";
+        assert_eq!(render_ansi(raw, AnsiMode::Keep), "\x1b[1mbold\x1b[0m");
+        assert_eq!(render_ansi(raw, AnsiMode::Strip), "bold");
+        assert_eq!(
+            render_ansi(raw, AnsiMode::Html),
+            "<span style=\"font-weight:bold\">bold</span>"
+        );
+    }
+}
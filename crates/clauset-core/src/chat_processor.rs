@@ -11,7 +11,7 @@
 //! - Messages are broadcast via ProcessEvent for WebSocket delivery
 
 use crate::{InteractionStore, TranscriptEvent, TranscriptWatcher, TranscriptWatcherHandle};
-use clauset_types::{ChatEvent, ChatMessage, ChatToolCall, HookEvent};
+use clauset_types::{ChatEvent, ChatMessage, ChatRole, ChatToolCall, HookEvent, SessionChatSnapshot};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::collections::HashMap;
@@ -49,6 +49,8 @@ struct SessionChatState {
     in_tool_output: bool,
     /// Current tool output being captured
     current_tool_output: String,
+    /// Prose extraction state carried across terminal output chunks
+    text_extractor: TextExtractor,
 }
 
 impl SessionChatState {
@@ -60,6 +62,74 @@ impl SessionChatState {
             text_buffer: String::new(),
             in_tool_output: false,
             current_tool_output: String::new(),
+            text_extractor: TextExtractor::default(),
+        }
+    }
+}
+
+/// How long tool output is shortened for chat tool calls and stored previews.
+///
+/// In TOML:
+/// ```toml
+/// [tool_output_preview]
+/// mode = "head_tail"
+/// head = 200
+/// tail = 300
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum OutputPreview {
+    /// Keep the first `max_len` chars, ending in `...` when cut.
+    Head { max_len: usize },
+    /// Keep the last `max_len` chars, starting with `...` when cut.
+    Tail { max_len: usize },
+    /// Keep the first `head` and last `tail` chars joined by an elision
+    /// marker, so errors at the end of long output stay visible.
+    HeadTail { head: usize, tail: usize },
+}
+
+impl Default for OutputPreview {
+    fn default() -> Self {
+        Self::Head { max_len: 500 }
+    }
+}
+
+impl OutputPreview {
+    /// Modes for tools whose useful output is not at the start: a test run
+    /// reports its failures last, so `Bash` keeps the tail.
+    pub fn tool_defaults() -> HashMap<String, OutputPreview> {
+        HashMap::from([("Bash".to_string(), Self::Tail { max_len: 500 })])
+    }
+
+    /// Shorten `s` according to this mode.
+    pub fn apply(&self, s: &str) -> String {
+        let len = s.chars().count();
+        match *self {
+            Self::Head { max_len } => {
+                if len <= max_len {
+                    s.to_string()
+                } else {
+                    let kept: String = s.chars().take(max_len.saturating_sub(3)).collect();
+                    format!("{kept}...")
+                }
+            }
+            Self::Tail { max_len } => {
+                if len <= max_len {
+                    s.to_string()
+                } else {
+                    let kept: String = s.chars().skip(len - max_len.saturating_sub(3)).collect();
+                    format!("...{kept}")
+                }
+            }
+            Self::HeadTail { head, tail } => {
+                let marker = |elided: usize| format!("\n[... {elided} chars elided ...]\n");
+                if len <= head + tail + marker(len).chars().count() {
+                    return s.to_string();
+                }
+                let start: String = s.chars().take(head).collect();
+                let end: String = s.chars().skip(len - tail).collect();
+                format!("{start}{}{end}", marker(len - head - tail))
+            }
         }
     }
 }
@@ -71,6 +141,8 @@ pub struct ChatProcessor {
     store: Option<Arc<InteractionStore>>,
     /// Active transcript watchers by session ID
     transcript_watchers: Arc<RwLock<HashMap<Uuid, TranscriptWatcherHandle>>>,
+    /// How tool call output is shortened
+    output_preview: OutputPreview,
 }
 
 impl Default for ChatProcessor {
@@ -85,6 +157,7 @@ impl ChatProcessor {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             store: None,
             transcript_watchers: Arc::new(RwLock::new(HashMap::new())),
+            output_preview: OutputPreview::default(),
         }
     }
 
@@ -94,9 +167,16 @@ impl ChatProcessor {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             store: Some(store),
             transcript_watchers: Arc::new(RwLock::new(HashMap::new())),
+            output_preview: OutputPreview::default(),
         }
     }
 
+    /// Set how tool call output is shortened.
+    pub fn with_output_preview(mut self, output_preview: OutputPreview) -> Self {
+        self.output_preview = output_preview;
+        self
+    }
+
     /// Helper to persist a message to the database.
     fn persist_message(&self, msg: &ChatMessage) {
         if let Some(store) = &self.store {
@@ -127,7 +207,7 @@ impl ChatProcessor {
                 session_id, prompt, ..
             } => {
                 let mut sessions = self.sessions.write().await;
-                let state = sessions.entry(*session_id).or_insert_with(SessionChatState::new);
+                let state = sessions.entry(*session_id).or_insert_with(|| self.stored_state(*session_id));
 
                 // Finalize any in-progress assistant message
                 if let Some(mut msg) = state.current_message.take() {
@@ -161,6 +241,7 @@ impl ChatProcessor {
                 // Transition state
                 state.state = ProcessorState::WaitingForResponse;
                 state.text_buffer.clear();
+                state.text_extractor = TextExtractor::default();
             }
 
             HookEvent::PreToolUse {
@@ -171,7 +252,7 @@ impl ChatProcessor {
                 ..
             } => {
                 let mut sessions = self.sessions.write().await;
-                let state = sessions.entry(*session_id).or_insert_with(SessionChatState::new);
+                let state = sessions.entry(*session_id).or_insert_with(|| self.stored_state(*session_id));
 
                 // Ensure we have an assistant message
                 if state.current_message.is_none() {
@@ -192,6 +273,9 @@ impl ChatProcessor {
                 );
 
                 if let Some(msg) = &mut state.current_message {
+                    if msg.mark_first_token() {
+                        self.persist_message(msg);
+                    }
                     msg.add_tool_call(tool_call.clone());
                     self.persist_tool_call(&msg.id, &tool_call);
                 }
@@ -216,10 +300,10 @@ impl ChatProcessor {
                 ..
             } => {
                 let mut sessions = self.sessions.write().await;
-                let state = sessions.entry(*session_id).or_insert_with(SessionChatState::new);
+                let state = sessions.entry(*session_id).or_insert_with(|| self.stored_state(*session_id));
 
                 // Extract output from tool response
-                let output = extract_tool_output(tool_response);
+                let output = extract_tool_output(tool_response, self.output_preview);
                 let is_error = tool_response.get("error").is_some()
                     || tool_response
                         .get("is_error")
@@ -263,7 +347,7 @@ impl ChatProcessor {
                 info!(target: "clauset::chat", "Transcript watcher active: {}", watcher_active);
 
                 let mut sessions = self.sessions.write().await;
-                let state = sessions.entry(*session_id).or_insert_with(SessionChatState::new);
+                let state = sessions.entry(*session_id).or_insert_with(|| self.stored_state(*session_id));
 
                 info!(target: "clauset::chat", "Current message exists: {}", state.current_message.is_some());
 
@@ -294,17 +378,34 @@ impl ChatProcessor {
                                     }
                                 }
 
-                                // Add text content
+                                // Reconcile streamed terminal text with the transcript text
                                 if !response.text.is_empty() {
-                                    msg.append_content(&response.text);
-                                    // Only emit delta if watcher wasn't handling streaming
-                                    if !watcher_active {
-                                        events.push(ChatEvent::ContentDelta {
+                                    let merged = reconcile_assistant_text(&msg.content, &response.text);
+                                    if let Some(delta) = merged.strip_prefix(msg.content.as_str()) {
+                                        let delta = delta.to_string();
+                                        if !delta.is_empty() {
+                                            msg.append_content(&delta);
+                                            // Only emit delta if watcher wasn't handling streaming
+                                            if !watcher_active {
+                                                events.push(ChatEvent::ContentDelta {
+                                                    session_id: *session_id,
+                                                    message_id: msg.id.clone(),
+                                                    delta,
+                                                });
+                                                info!(target: "clauset::chat", "Added ContentDelta event");
+                                            }
+                                        }
+                                    } else {
+                                        // Streamed text was a garbled subset; the persisted
+                                        // message takes the fuller transcript text.
+                                        info!(target: "clauset::chat", "Replacing streamed content with transcript text");
+                                        msg.content = merged;
+                                        // Clients hold the streamed text, so they need the whole content
+                                        events.push(ChatEvent::ContentReplaced {
                                             session_id: *session_id,
                                             message_id: msg.id.clone(),
-                                            delta: response.text,
+                                            content: msg.content.clone(),
                                         });
-                                        info!(target: "clauset::chat", "Added ContentDelta event");
                                     }
                                 }
 
@@ -355,7 +456,7 @@ impl ChatProcessor {
         let clean_text = strip_ansi_codes(&text);
 
         let mut sessions = self.sessions.write().await;
-        let state = sessions.entry(session_id).or_insert_with(SessionChatState::new);
+        let state = sessions.entry(session_id).or_insert_with(|| self.stored_state(session_id));
 
         // Only process if we're building a response
         match &state.state {
@@ -366,7 +467,7 @@ impl ChatProcessor {
                 }
 
                 // Extract meaningful text (filter out status lines, prompts, etc.)
-                let extracted = extract_claude_text(&clean_text);
+                let extracted = state.text_extractor.extract(&clean_text);
                 if extracted.is_empty() {
                     return None;
                 }
@@ -380,6 +481,9 @@ impl ChatProcessor {
                 // Update current message
                 if let Some(msg) = &mut state.current_message {
                     msg.append_content(&extracted);
+                    if msg.mark_first_token() {
+                        self.persist_message(msg);
+                    }
 
                     return Some(ChatEvent::ContentDelta {
                         session_id,
@@ -394,6 +498,25 @@ impl ChatProcessor {
         None
     }
 
+    /// Finalize and persist every in-progress assistant message.
+    ///
+    /// Called during shutdown so responses that never received a Stop hook
+    /// aren't lost. Returns the number of messages flushed.
+    pub async fn flush_all(&self) -> usize {
+        let mut sessions = self.sessions.write().await;
+        let mut flushed = 0;
+        for state in sessions.values_mut() {
+            if let Some(mut msg) = state.current_message.take() {
+                msg.complete();
+                self.persist_message(&msg);
+                state.messages.push(msg);
+                state.state = ProcessorState::Idle;
+                flushed += 1;
+            }
+        }
+        flushed
+    }
+
     /// Get all messages for a session.
     ///
     /// Returns messages from memory if available, otherwise loads from database.
@@ -412,6 +535,35 @@ impl ChatProcessor {
         self.get_chat_history(session_id)
     }
 
+    /// Get a session's finalized messages plus the message still streaming,
+    /// so a reconnecting client can render the exact current state.
+    ///
+    /// Falls back to the database when the session isn't in memory; a stored
+    /// message that never finished is reported as in progress.
+    pub async fn get_session_snapshot(&self, session_id: Uuid) -> SessionChatSnapshot {
+        let sessions = self.sessions.read().await;
+        if let Some(s) = sessions.get(&session_id) {
+            return SessionChatSnapshot {
+                session_id,
+                messages: s.messages.clone(),
+                in_progress: s.current_message.clone(),
+            };
+        }
+        drop(sessions);
+
+        let mut messages = self.get_chat_history(session_id);
+        let in_progress = if messages.last().is_some_and(|m| m.is_streaming && !m.is_complete) {
+            messages.pop()
+        } else {
+            None
+        };
+        SessionChatSnapshot {
+            session_id,
+            messages,
+            in_progress,
+        }
+    }
+
     /// Get chat history from the database.
     pub fn get_chat_history(&self, session_id: Uuid) -> Vec<ChatMessage> {
         if let Some(store) = &self.store {
@@ -427,23 +579,50 @@ impl ChatProcessor {
         }
     }
 
-    /// Load messages from database into memory for a session.
-    pub async fn load_session_history(&self, session_id: Uuid) {
-        if let Some(store) = &self.store {
-            match store.get_chat_messages(session_id) {
-                Ok(messages) => {
-                    if !messages.is_empty() {
-                        let mut sessions = self.sessions.write().await;
-                        let state = sessions.entry(session_id).or_insert_with(SessionChatState::new);
-                        state.messages = messages;
-                        info!(target: "clauset::chat", "Loaded {} messages from database for session {}", state.messages.len(), session_id);
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(target: "clauset::chat", "Failed to load chat history for session {}: {}", session_id, e);
-                }
+    /// Rebuild a session's state from the database, e.g. after a restart.
+    ///
+    /// An assistant message that was still incomplete when persisted becomes
+    /// the message being built again, so the next Stop finalizes it instead
+    /// of leaving it half-written.
+    fn stored_state(&self, session_id: Uuid) -> SessionChatState {
+        let mut state = SessionChatState::new();
+        let Some(store) = &self.store else {
+            return state;
+        };
+        match store.get_chat_messages(session_id) {
+            Ok(messages) => state.messages = messages,
+            Err(e) => {
+                tracing::warn!(target: "clauset::chat", "Failed to load chat history for session {}: {}", session_id, e);
+                return state;
             }
         }
+        if state
+            .messages
+            .last()
+            .is_some_and(|m| m.role == ChatRole::Assistant && !m.is_complete)
+        {
+            state.current_message = state.messages.pop();
+            state.state = ProcessorState::BuildingResponse;
+            info!(target: "clauset::chat", "Resuming in-progress assistant message for session {}", session_id);
+        }
+        state
+    }
+
+    /// Load messages from database into memory for a session, resuming an
+    /// assistant message that was still being built (see [`Self::stored_state`]).
+    pub async fn load_session_history(&self, session_id: Uuid) {
+        let stored = self.stored_state(session_id);
+        if stored.messages.is_empty() && stored.current_message.is_none() {
+            return;
+        }
+        let mut sessions = self.sessions.write().await;
+        let state = sessions.entry(session_id).or_insert_with(SessionChatState::new);
+        state.messages = stored.messages;
+        if state.current_message.is_none() {
+            state.current_message = stored.current_message;
+            state.state = stored.state;
+        }
+        info!(target: "clauset::chat", "Loaded {} messages from database for session {}", state.messages.len(), session_id);
     }
 
     /// Clear messages for a session.
@@ -567,82 +746,110 @@ static TOOL_HEADER_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^[●•\s]*(Read|Edit|Write|Bash|Grep|Glob|Task|Search|WebFetch|WebSearch)\s*[\(:]").unwrap()
 });
 
-/// Extract Claude's meaningful text from terminal output.
+/// Bullet Claude prints before each prose block and tool header.
+static PROSE_BULLET_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[●•]\s*\S").unwrap()
+});
+
+/// Stateful extraction of Claude's meaningful text from terminal output.
 ///
 /// Filters out:
 /// - Status lines (model | cost | tokens)
 /// - Prompts (> or $)
 /// - Thinking indicators
-/// - Tool headers
+/// - Tool headers, and the tool output echoed after them (file contents,
+///   command output) up to the next prose block or prompt; tool results come
+///   from the structured tool call instead
 /// - Empty lines
-fn extract_claude_text(text: &str) -> String {
-    let mut result = String::new();
+///
+/// A tool output region can span several chunks of terminal output, so the
+/// extractor keeps its state between calls.
+#[derive(Debug, Default)]
+struct TextExtractor {
+    /// Whether the last tool header's output region is still open
+    in_tool_output: bool,
+}
 
-    for line in text.lines() {
-        let trimmed = line.trim();
+impl TextExtractor {
+    fn extract(&mut self, text: &str) -> String {
+        let mut result = String::new();
 
-        // Skip empty lines
-        if trimmed.is_empty() {
-            continue;
-        }
+        for line in text.lines() {
+            let trimmed = line.trim();
 
-        // Skip status lines
-        if STATUS_LINE_REGEX.is_match(trimmed) {
-            continue;
-        }
+            // Skip empty lines
+            if trimmed.is_empty() {
+                continue;
+            }
 
-        // Skip prompts
-        if PROMPT_REGEX.is_match(trimmed) {
-            continue;
-        }
+            // Skip status lines
+            if STATUS_LINE_REGEX.is_match(trimmed) {
+                self.in_tool_output = false;
+                continue;
+            }
 
-        // Skip thinking indicators
-        if THINKING_REGEX.is_match(trimmed) {
-            continue;
-        }
+            // Skip prompts
+            if PROMPT_REGEX.is_match(trimmed) {
+                self.in_tool_output = false;
+                continue;
+            }
 
-        // Skip tool headers
-        if TOOL_HEADER_REGEX.is_match(trimmed) {
-            continue;
-        }
+            // Skip thinking indicators
+            if THINKING_REGEX.is_match(trimmed) {
+                self.in_tool_output = false;
+                continue;
+            }
 
-        // Skip very short lines that look like UI chrome
-        if trimmed.len() < 3 {
-            continue;
-        }
+            // Skip tool headers and the output that follows them
+            if TOOL_HEADER_REGEX.is_match(trimmed) {
+                self.in_tool_output = true;
+                continue;
+            }
+            if self.in_tool_output {
+                if !PROSE_BULLET_REGEX.is_match(trimmed) {
+                    continue;
+                }
+                self.in_tool_output = false;
+            }
 
-        // Skip lines that are mostly box-drawing characters
-        let box_chars: usize = trimmed
-            .chars()
-            .filter(|c| "─│┌┐└┘├┤┬┴┼━┃┏┓┗┛┣┫┳┻╋═║╔╗╚╝╠╣╦╩╬▀▄█▌▐░▒▓".contains(*c))
-            .count();
-        if box_chars > trimmed.len() / 2 {
-            continue;
-        }
+            // Skip very short lines that look like UI chrome
+            if trimmed.len() < 3 {
+                continue;
+            }
 
-        // Add the line
-        if !result.is_empty() {
-            result.push('\n');
+            // Skip lines that are mostly box-drawing characters
+            let box_chars: usize = trimmed
+                .chars()
+                .filter(|c| "─│┌┐└┘├┤┬┴┼━┃┏┓┗┛┣┫┳┻╋═║╔╗╚╝╠╣╦╩╬▀▄█▌▐░▒▓".contains(*c))
+                .count();
+            if box_chars > trimmed.len() / 2 {
+                continue;
+            }
+
+            // Add the line
+            if !result.is_empty() {
+                result.push('\n');
+            }
+            result.push_str(trimmed);
         }
-        result.push_str(trimmed);
-    }
 
-    result
+        result
+    }
 }
 
 /// Extract a readable output string from tool response JSON.
-fn extract_tool_output(response: &serde_json::Value) -> String {
+fn extract_tool_output(response: &serde_json::Value, preview: OutputPreview) -> String {
     // Try common output field names
     if let Some(output) = response.get("output").and_then(|v| v.as_str()) {
-        return truncate_output(output);
+        return preview.apply(output);
     }
 
     if let Some(content) = response.get("content").and_then(|v| v.as_str()) {
-        return truncate_output(content);
+        return preview.apply(content);
     }
 
     if let Some(result) = response.get("result").and_then(|v| v.as_str()) {
-        return truncate_output(result);
+        return preview.apply(result);
     }
 
     // For arrays (like search results), summarize
@@ -652,16 +859,46 @@ fn extract_tool_output(response: &serde_json::Value) -> String {
 
     // Fallback to JSON stringification (truncated)
     let json_str = response.to_string();
-    truncate_output(&json_str)
+    preview.apply(&json_str)
 }
 
-/// Truncate output to a reasonable length for display.
-fn truncate_output(s: &str) -> String {
-    const MAX_LEN: usize = 500;
-    if s.len() <= MAX_LEN {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..MAX_LEN - 3])
+/// Merge streamed terminal text with the transcript text for the same response.
+///
+/// The transcript is authoritative but may lag or be partial; the terminal
+/// capture may be missing lines. If the streamed text is contained in the
+/// transcript the transcript wins. If they diverge, the transcript tail after
+/// the last streamed line it shares is appended to the streamed text.
+fn reconcile_assistant_text(streamed: &str, transcript: &str) -> String {
+    if streamed.trim().is_empty() || transcript.contains(streamed) {
+        return transcript.to_string();
+    }
+    if transcript.trim().is_empty() || streamed.contains(transcript) {
+        return streamed.to_string();
+    }
+
+    // Anchor on the last streamed line that also appears in the transcript
+    let anchor = streamed
+        .lines()
+        .rev()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .find_map(|line| transcript.rfind(line).map(|pos| pos + line.len()));
+
+    match anchor {
+        Some(end) => {
+            let tail = transcript[end..].trim_start_matches([' ', '\t']);
+            if tail.trim().is_empty() {
+                return streamed.to_string();
+            }
+            let mut merged = streamed.trim_end().to_string();
+            if !tail.starts_with('\n') {
+                merged.push('\n');
+            }
+            merged.push_str(tail);
+            merged
+        }
+        // Nothing in common: the stream captured noise, not the response
+        None => transcript.to_string(),
     }
 }
 
@@ -700,66 +937,129 @@ fn read_last_assistant_response(path: &str) -> std::io::Result<TranscriptRespons
         }
 
         // Parse the JSONL line
-        if let Ok(entry) = serde_json::from_str::<serde_json::Value>(line) {
-            // Check if this is an assistant message (outer type field)
-            let entry_type = entry.get("type").and_then(|v| v.as_str());
-            if entry_type != Some("assistant") {
-                continue;
-            }
+        if let Ok(entry) = serde_json::from_str::<serde_json::Value>(line)
+            && let Some(response) = assistant_response(&entry)
+        {
+            return Ok(response);
+        }
+    }
 
-            // Get the nested message object
-            let message = match entry.get("message") {
-                Some(m) => m,
-                None => continue,
-            };
+    Ok(TranscriptResponse::default())
+}
 
-            // Extract text and thinking content from message.content array
-            if let Some(content) = message.get("content").and_then(|v| v.as_array()) {
-                let mut text_parts = Vec::new();
-                let mut thinking_parts = Vec::new();
+/// Extract text and thinking from a transcript entry if it is an assistant
+/// message with content.
+fn assistant_response(entry: &serde_json::Value) -> Option<TranscriptResponse> {
+    // Check if this is an assistant message (outer type field)
+    if entry.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+        return None;
+    }
 
-                for part in content {
-                    match part.get("type").and_then(|v| v.as_str()) {
-                        Some("text") => {
-                            if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
-                                text_parts.push(text.to_string());
-                            }
-                        }
-                        Some("thinking") => {
-                            if let Some(thinking) = part.get("thinking").and_then(|v| v.as_str()) {
-                                thinking_parts.push(thinking.to_string());
-                            }
-                        }
-                        _ => {}
+    // Get the nested message object
+    let message = entry.get("message")?;
+
+    // Extract text and thinking content from message.content array
+    if let Some(content) = message.get("content").and_then(|v| v.as_array()) {
+        let mut text_parts = Vec::new();
+        let mut thinking_parts = Vec::new();
+
+        for part in content {
+            match part.get("type").and_then(|v| v.as_str()) {
+                Some("text") => {
+                    if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
+                        text_parts.push(text.to_string());
                     }
                 }
-
-                if !text_parts.is_empty() || !thinking_parts.is_empty() {
-                    return Ok(TranscriptResponse {
-                        text: text_parts.join("\n"),
-                        thinking: thinking_parts.join("\n"),
-                    });
+                Some("thinking") => {
+                    if let Some(thinking) = part.get("thinking").and_then(|v| v.as_str()) {
+                        thinking_parts.push(thinking.to_string());
+                    }
                 }
+                _ => {}
             }
+        }
 
-            // Also handle simple string content format
-            if let Some(content) = message.get("content").and_then(|v| v.as_str()) {
-                return Ok(TranscriptResponse {
-                    text: content.to_string(),
-                    thinking: String::new(),
-                });
+        if !text_parts.is_empty() || !thinking_parts.is_empty() {
+            return Some(TranscriptResponse {
+                text: text_parts.join("\n"),
+                thinking: thinking_parts.join("\n"),
+            });
+        }
+    }
+
+    // Also handle simple string content format
+    message
+        .get("content")
+        .and_then(|v| v.as_str())
+        .map(|content| TranscriptResponse {
+            text: content.to_string(),
+            thinking: String::new(),
+        })
+}
+
+/// The prompt text of a user entry, or None for tool results and other
+/// non-prompt user entries.
+fn user_prompt(entry: &serde_json::Value) -> Option<String> {
+    if entry.get("type").and_then(|v| v.as_str()) != Some("user") {
+        return None;
+    }
+    let content = entry.get("message")?.get("content")?;
+    if let Some(text) = content.as_str() {
+        return Some(text.to_string());
+    }
+    let parts: Vec<&str> = content
+        .as_array()?
+        .iter()
+        .filter(|part| part.get("type").and_then(|v| v.as_str()) == Some("text"))
+        .filter_map(|part| part.get("text").and_then(|v| v.as_str()))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n"))
+}
+
+/// One user prompt and the assistant text that answered it.
+#[derive(Debug, Default)]
+pub(crate) struct TranscriptTurn {
+    pub prompt: String,
+    /// Assistant text across every message in the turn
+    pub response: String,
+}
+
+/// Read a transcript as a list of turns, one per user prompt, in order.
+pub(crate) fn read_transcript_turns(path: &std::path::Path) -> std::io::Result<Vec<TranscriptTurn>> {
+    use std::io::{BufRead, BufReader};
+
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut turns: Vec<TranscriptTurn> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if let Some(prompt) = user_prompt(&entry) {
+            turns.push(TranscriptTurn {
+                prompt,
+                response: String::new(),
+            });
+        } else if let Some(response) = assistant_response(&entry)
+            && let Some(turn) = turns.last_mut()
+            && !response.text.trim().is_empty()
+        {
+            if !turn.response.is_empty() {
+                turn.response.push('\n');
             }
+            turn.response.push_str(&response.text);
         }
     }
 
-    Ok(TranscriptResponse::default())
+    Ok(turns)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use clauset_types::ChatRole;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
     use std::io::Write;
 
     // ==================== ANSI Code Stripping Tests ====================
@@ -824,6 +1124,10 @@ mod tests {
 
     // ==================== Text Extraction Tests ====================
 
+    fn extract_claude_text(text: &str) -> String {
+        TextExtractor::default().extract(text)
+    }
+
     #[test]
     fn test_extract_claude_text() {
         let input = r#"
@@ -945,6 +1249,37 @@ Let me know if you need help.
         assert!(extracted.contains("Third one"));
     }
 
+    #[test]
+    fn test_extract_claude_text_skips_tool_output() {
+        let input = r#"
+● I'll check the config first.
+● Read(src/config.rs)
+  ⎿  use serde::Deserialize;
+
+     pub struct Config {
+         pub port: u16,
+     }
+● The port is read from the config file.
+"#;
+        let extracted = extract_claude_text(input);
+        assert_eq!(
+            extracted,
+            "● I'll check the config first.\n● The port is read from the config file."
+        );
+    }
+
+    #[test]
+    fn test_tool_output_region_spans_chunks() {
+        let mut extractor = TextExtractor::default();
+        assert_eq!(extractor.extract("● Bash(cargo test)\n  ⎿  running 3 tests\n"), "");
+        assert_eq!(extractor.extract("     test result: ok. 3 passed\n"), "");
+        assert_eq!(extractor.extract("● All tests pass.\n"), "● All tests pass.");
+
+        // A prompt also closes the region
+        extractor.extract("● Grep(TODO)\n  ⎿  Found 2 files\n>\n");
+        assert_eq!(extractor.extract("Plain prose again\n"), "Plain prose again");
+    }
+
     // ==================== Tool Output Extraction Tests ====================
 
     #[test]
@@ -952,10 +1287,10 @@ Let me know if you need help.
         let response = serde_json::json!({
             "output": "File contents here"
         });
-        assert_eq!(extract_tool_output(&response), "File contents here");
+        assert_eq!(extract_tool_output(&response, OutputPreview::default()), "File contents here");
 
         let response2 = serde_json::json!([1, 2, 3]);
-        assert_eq!(extract_tool_output(&response2), "[3 results]");
+        assert_eq!(extract_tool_output(&response2, OutputPreview::default()), "[3 results]");
     }
 
     #[test]
@@ -963,7 +1298,7 @@ Let me know if you need help.
         let response = serde_json::json!({
             "content": "Some content value"
         });
-        assert_eq!(extract_tool_output(&response), "Some content value");
+        assert_eq!(extract_tool_output(&response, OutputPreview::default()), "Some content value");
     }
 
     #[test]
@@ -971,7 +1306,7 @@ Let me know if you need help.
         let response = serde_json::json!({
             "result": "Operation result"
         });
-        assert_eq!(extract_tool_output(&response), "Operation result");
+        assert_eq!(extract_tool_output(&response, OutputPreview::default()), "Operation result");
     }
 
     #[test]
@@ -982,7 +1317,7 @@ Let me know if you need help.
             "content": "secondary",
             "result": "tertiary"
         });
-        assert_eq!(extract_tool_output(&response), "preferred");
+        assert_eq!(extract_tool_output(&response, OutputPreview::default()), "preferred");
     }
 
     #[test]
@@ -994,13 +1329,13 @@ Let me know if you need help.
             {"file": "d.txt"},
             {"file": "e.txt"}
         ]);
-        assert_eq!(extract_tool_output(&response), "[5 results]");
+        assert_eq!(extract_tool_output(&response, OutputPreview::default()), "[5 results]");
     }
 
     #[test]
     fn test_extract_tool_output_empty_array() {
         let response = serde_json::json!([]);
-        assert_eq!(extract_tool_output(&response), "[0 results]");
+        assert_eq!(extract_tool_output(&response, OutputPreview::default()), "[0 results]");
     }
 
     #[test]
@@ -1009,7 +1344,7 @@ Let me know if you need help.
             "some_field": "value",
             "another": 123
         });
-        let result = extract_tool_output(&response);
+        let result = extract_tool_output(&response, OutputPreview::default());
         assert!(result.contains("some_field"));
     }
 
@@ -1019,7 +1354,7 @@ Let me know if you need help.
         let response = serde_json::json!({
             "output": long_output
         });
-        let result = extract_tool_output(&response);
+        let result = extract_tool_output(&response, OutputPreview::default());
         assert_eq!(result.len(), 500);
         assert!(result.ends_with("..."));
     }
@@ -1028,27 +1363,178 @@ Let me know if you need help.
 
     #[test]
     fn test_truncate_output_short() {
-        assert_eq!(truncate_output("short"), "short");
-        assert_eq!(truncate_output(""), "");
+        assert_eq!(OutputPreview::default().apply("short"), "short");
+        assert_eq!(OutputPreview::default().apply(""), "");
     }
 
     #[test]
     fn test_truncate_output_exactly_max() {
         let s = "x".repeat(500);
-        assert_eq!(truncate_output(&s), s);
+        assert_eq!(OutputPreview::default().apply(&s), s);
     }
 
     #[test]
     fn test_truncate_output_over_max() {
         let s = "x".repeat(600);
-        let result = truncate_output(&s);
+        let result = OutputPreview::default().apply(&s);
         assert_eq!(result.len(), 500);
         assert!(result.ends_with("..."));
         assert!(result.starts_with("xxx"));
     }
 
+    #[test]
+    fn test_output_preview_head_tail_keeps_end() {
+        let build_log = format!(
+            "   Compiling clauset v0.1.0\n{}error[E0308]: mismatched types\n  --> src/main.rs:4:5",
+            "warning: unused variable\n".repeat(100)
+        );
+        let preview = OutputPreview::HeadTail { head: 40, tail: 60 };
+        let result = preview.apply(&build_log);
+
+        assert!(result.starts_with("   Compiling clauset v0.1.0\n"));
+        assert!(result.ends_with("error[E0308]: mismatched types\n  --> src/main.rs:4:5"));
+        assert!(result.contains("chars elided"));
+        assert!(result.len() < build_log.len());
+
+        let response = serde_json::json!({ "output": build_log });
+        assert_eq!(extract_tool_output(&response, preview), result);
+    }
+
+    #[test]
+    fn test_output_preview_tail_keeps_end() {
+        let preview = OutputPreview::Tail { max_len: 10 };
+        assert_eq!(preview.apply("short"), "short");
+        assert_eq!(preview.apply("running 3 tests\nFAILED"), "...\nFAILED");
+        assert_eq!(preview.apply(&"é".repeat(20)), format!("...{}", "é".repeat(7)));
+    }
+
+    #[test]
+    fn test_output_preview_head_tail_short_unchanged() {
+        let preview = OutputPreview::HeadTail { head: 10, tail: 10 };
+        assert_eq!(preview.apply("short output"), "short output");
+        assert_eq!(preview.apply(&"é".repeat(40)), "é".repeat(40));
+    }
+
     // ==================== Transcript Reading Tests ====================
 
+    #[test]
+    fn test_reconcile_streamed_prefix_of_transcript() {
+        let transcript = "Here is the fix.\nIt updates the parser.";
+        assert_eq!(reconcile_assistant_text("Here is the fix.", transcript), transcript);
+        // A substring from the middle (stream started late) also yields the transcript
+        assert_eq!(reconcile_assistant_text("the fix.\nIt upd", transcript), transcript);
+        assert_eq!(reconcile_assistant_text("", transcript), transcript);
+        // Stream already has everything
+        assert_eq!(reconcile_assistant_text(transcript, "It updates"), transcript);
+    }
+
+    #[test]
+    fn test_reconcile_divergent_tail_appended() {
+        // Terminal wrapped the first line differently and missed the last line
+        let streamed = "Here is the fix for the\nparser bug.\nStep one done.";
+        let transcript = "Here is the fix for the parser bug.\nStep one done.\nStep two done.";
+        assert_eq!(
+            reconcile_assistant_text(streamed, transcript),
+            "Here is the fix for the\nparser bug.\nStep one done.\nStep two done."
+        );
+
+        // No shared line: the transcript replaces the noise
+        assert_eq!(reconcile_assistant_text("@@@", "Real answer"), "Real answer");
+    }
+
+    #[tokio::test]
+    async fn test_processor_stop_merges_streamed_and_transcript_text() {
+        let processor = ChatProcessor::new();
+        let session_id = Uuid::new_v4();
+
+        processor
+            .process_hook_event(&HookEvent::UserPromptSubmit {
+                session_id,
+                claude_session_id: "test".to_string(),
+                prompt: "Hello".to_string(),
+                cwd: None,
+                context_window: None,
+            })
+            .await;
+        processor
+            .process_terminal_output(session_id, b"Hello there, this is the start\n")
+            .await;
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","message":{{"content":[{{"type":"text","text":"Hello there, this is the start\nand the rest."}}]}}}}"#
+        )
+        .unwrap();
+
+        let events = processor
+            .process_hook_event(&HookEvent::Stop {
+                session_id,
+                claude_session_id: "test".to_string(),
+                stop_hook_active: false,
+                transcript_path: Some(file.path().to_str().unwrap().to_string()),
+                context_window: None,
+            })
+            .await;
+
+        let deltas: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                ChatEvent::ContentDelta { delta, .. } => Some(delta.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deltas, vec!["\nand the rest."]);
+
+        let messages = processor.get_messages(session_id).await;
+        let assistant = messages.iter().find(|m| m.role == ChatRole::Assistant).unwrap();
+        assert_eq!(assistant.content, "Hello there, this is the start\nand the rest.");
+    }
+
+    #[tokio::test]
+    async fn test_processor_stop_broadcasts_replaced_content() {
+        let processor = ChatProcessor::new();
+        let session_id = Uuid::new_v4();
+
+        processor
+            .process_hook_event(&HookEvent::UserPromptSubmit {
+                session_id,
+                claude_session_id: "test".to_string(),
+                prompt: "Hello".to_string(),
+                cwd: None,
+                context_window: None,
+            })
+            .await;
+        processor.process_terminal_output(session_id, b"@@ garbled capture @@\n").await;
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"{{"type":"assistant","message":{{"content":[{{"type":"text","text":"The real answer."}}]}}}}"#
+        )
+        .unwrap();
+
+        let events = processor
+            .process_hook_event(&HookEvent::Stop {
+                session_id,
+                claude_session_id: "test".to_string(),
+                stop_hook_active: false,
+                transcript_path: Some(file.path().to_str().unwrap().to_string()),
+                context_window: None,
+            })
+            .await;
+
+        let replaced: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                ChatEvent::ContentReplaced { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(replaced, vec!["The real answer."]);
+        assert!(!events.iter().any(|e| matches!(e, ChatEvent::ContentDelta { .. })));
+    }
+
     #[test]
     fn test_read_last_assistant_response_basic() {
         let mut file = NamedTempFile::new().unwrap();
@@ -1157,6 +1643,169 @@ Let me know if you need help.
 
     // ==================== ChatProcessor State Machine Tests ====================
 
+    /// An InteractionStore in `dir` holding one session, for persistence tests.
+    fn store_with_session(dir: &TempDir, session_id: Uuid) -> Arc<InteractionStore> {
+        use crate::SessionStore;
+        use clauset_types::{Session, SessionMode, SessionStatus};
+
+        let db_path = dir.path().join("chat.db");
+        SessionStore::open(&db_path)
+            .unwrap()
+            .insert(&Session {
+                id: session_id,
+                claude_session_id: Uuid::new_v4(),
+                project_path: "/test/project".into(),
+                model: "sonnet".to_string(),
+                status: SessionStatus::Active,
+                mode: SessionMode::Terminal,
+                created_at: chrono::Utc::now(),
+                last_activity_at: chrono::Utc::now(),
+                total_cost_usd: 0.0,
+                input_tokens: 0,
+                output_tokens: 0,
+                context_percent: 0,
+                preview: "Hello".to_string(),
+                claude_version: None,
+            })
+            .unwrap();
+        Arc::new(InteractionStore::open(&db_path).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_response_latency_measures_time_to_first_token() {
+        let dir = TempDir::new().unwrap();
+        let session_id = Uuid::new_v4();
+        let store = store_with_session(&dir, session_id);
+        let processor = ChatProcessor::with_store(store.clone());
+
+        let interaction = clauset_types::Interaction::new(session_id, 1, "Hello".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        processor
+            .process_hook_event(&HookEvent::UserPromptSubmit {
+                session_id,
+                claude_session_id: "test".to_string(),
+                prompt: "Hello".to_string(),
+                cwd: None,
+                context_window: None,
+            })
+            .await;
+        assert_eq!(store.response_latency(interaction.id).unwrap(), None);
+
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        processor
+            .process_terminal_output(session_id, b"Here is the first part of the answer\n")
+            .await;
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        processor
+            .process_terminal_output(session_id, b"And a second part arriving later\n")
+            .await;
+
+        // Measured to the first delta, not the last
+        let latency = store.response_latency(interaction.id).unwrap().unwrap();
+        assert!(latency >= std::time::Duration::from_millis(150), "{latency:?}");
+        assert!(latency < std::time::Duration::from_millis(400), "{latency:?}");
+    }
+
+    #[tokio::test]
+    async fn test_flush_all_persists_in_progress_message() {
+        let dir = TempDir::new().unwrap();
+        let session_id = Uuid::new_v4();
+        let store = store_with_session(&dir, session_id);
+        let processor = ChatProcessor::with_store(store.clone());
+
+        processor
+            .process_hook_event(&HookEvent::UserPromptSubmit {
+                session_id,
+                claude_session_id: "test".to_string(),
+                prompt: "Hello".to_string(),
+                cwd: None,
+                context_window: None,
+            })
+            .await;
+        processor
+            .process_terminal_output(session_id, b"Partial answer before shutdown\n")
+            .await;
+
+        assert_eq!(processor.flush_all().await, 1);
+        assert_eq!(processor.flush_all().await, 0);
+
+        let persisted = store.get_chat_messages(session_id).unwrap();
+        let assistant = persisted.iter().find(|m| m.role == ChatRole::Assistant).unwrap();
+        assert!(assistant.is_complete);
+        assert!(!assistant.is_streaming);
+        assert!(assistant.content.contains("Partial answer before shutdown"));
+    }
+
+    #[tokio::test]
+    async fn test_restart_resumes_in_progress_message() {
+        let dir = TempDir::new().unwrap();
+        let session_id = Uuid::new_v4();
+        let store = store_with_session(&dir, session_id);
+
+        let before_restart = ChatProcessor::with_store(store.clone());
+        before_restart
+            .process_hook_event(&HookEvent::UserPromptSubmit {
+                session_id,
+                claude_session_id: "test".to_string(),
+                prompt: "Hello".to_string(),
+                cwd: None,
+                context_window: None,
+            })
+            .await;
+        let message_id = before_restart.get_current_assistant_message_id(session_id).await.unwrap();
+        drop(before_restart);
+
+        let processor = ChatProcessor::with_store(store.clone());
+        processor.load_session_history(session_id).await;
+        assert_eq!(processor.get_current_assistant_message_id(session_id).await, Some(message_id.clone()));
+
+        let events = processor
+            .process_hook_event(&HookEvent::Stop {
+                session_id,
+                claude_session_id: "test".to_string(),
+                stop_hook_active: false,
+                transcript_path: None,
+                context_window: None,
+            })
+            .await;
+        assert!(events.iter().any(|e| matches!(
+            e,
+            ChatEvent::MessageComplete { message_id: id, .. } if *id == message_id
+        )));
+
+        let persisted = store.get_chat_messages(session_id).unwrap();
+        assert_eq!(persisted.len(), 2);
+        assert_eq!(persisted[1].id, message_id);
+        assert!(persisted[1].is_complete);
+    }
+
+    #[tokio::test]
+    async fn test_first_access_after_restart_resumes_in_progress_message() {
+        let dir = TempDir::new().unwrap();
+        let session_id = Uuid::new_v4();
+        let store = store_with_session(&dir, session_id);
+        let incomplete = ChatMessage::assistant(session_id);
+        store.save_chat_message(&ChatMessage::user(session_id, "Hello".to_string())).unwrap();
+        store.save_chat_message(&incomplete).unwrap();
+
+        // No explicit history load: the Stop hook is the first access
+        let processor = ChatProcessor::with_store(store.clone());
+        processor
+            .process_hook_event(&HookEvent::Stop {
+                session_id,
+                claude_session_id: "test".to_string(),
+                stop_hook_active: false,
+                transcript_path: None,
+                context_window: None,
+            })
+            .await;
+
+        let messages = processor.get_messages(session_id).await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].id, incomplete.id);
+        assert!(messages[1].is_complete);
+    }
+
     #[tokio::test]
     async fn test_processor_user_prompt() {
         let processor = ChatProcessor::new();
@@ -1203,6 +1852,37 @@ Let me know if you need help.
         assert!(messages[1].is_streaming); // Should be streaming (waiting for response)
     }
 
+    #[tokio::test]
+    async fn test_session_snapshot_includes_streaming_message() {
+        let processor = ChatProcessor::new();
+        let session_id = Uuid::new_v4();
+
+        processor
+            .process_hook_event(&HookEvent::UserPromptSubmit {
+                session_id,
+                claude_session_id: "test".to_string(),
+                prompt: "Explain the parser".to_string(),
+                cwd: None,
+                context_window: None,
+            })
+            .await;
+        processor
+            .process_terminal_output(session_id, b"The parser reads tokens one at a time.\n")
+            .await;
+
+        let snapshot = processor.get_session_snapshot(session_id).await;
+        assert_eq!(snapshot.session_id, session_id);
+        assert_eq!(snapshot.messages.len(), 1);
+        assert_eq!(snapshot.messages[0].role, ChatRole::User);
+        assert!(snapshot.messages[0].is_complete);
+
+        let in_progress = snapshot.in_progress.expect("streaming message missing from snapshot");
+        assert_eq!(in_progress.role, ChatRole::Assistant);
+        assert!(in_progress.is_streaming);
+        assert!(!in_progress.is_complete);
+        assert!(in_progress.content.contains("reads tokens"), "{:?}", in_progress.content);
+    }
+
     #[tokio::test]
     async fn test_processor_pre_tool_use() {
         let processor = ChatProcessor::new();
@@ -1850,6 +2530,7 @@ Let me know if you need help.
             context_window: None,
             model: None,
             transcript_path: None,
+            version: None,
         }).await;
 
         assert!(events.is_empty()); // Should handle gracefully
@@ -6,6 +6,7 @@
 
 use crate::Result;
 use chrono::{DateTime, TimeZone, Utc};
+use clauset_types::TodoItem;
 use serde::Deserialize;
 use std::collections::HashSet;
 use std::fs::File;
@@ -352,6 +353,36 @@ impl ClaudeSessionReader {
         Ok(messages)
     }
 
+    /// Todo list from the latest `TodoWrite` call in a session's transcript.
+    /// Returns None if there is no transcript or it never called TodoWrite.
+    pub fn latest_todos(&self, session_id: &str, project_path: &Path) -> Result<Option<Vec<TodoItem>>> {
+        let transcript_path = self.get_transcript_path(session_id, project_path);
+        if !transcript_path.exists() {
+            return Ok(None);
+        }
+        read_latest_todos(&transcript_path)
+    }
+
+    /// Copy a session's transcript from `from`'s project folder into `to`'s,
+    /// so Claude finds it when the session is resumed in `to`.
+    ///
+    /// Claude keys transcripts by working directory. Returns false if there
+    /// is no transcript under `from`; one already under `to` is left alone.
+    pub fn carry_transcript(&self, session_id: &str, from: &Path, to: &Path) -> Result<bool> {
+        let source = self.get_transcript_path(session_id, from);
+        if !source.exists() {
+            return Ok(false);
+        }
+        let target = self.get_transcript_path(session_id, to);
+        if !target.exists() {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&source, &target)?;
+        }
+        Ok(true)
+    }
+
     /// Get the path to a transcript file.
     fn get_transcript_path(&self, session_id: &str, project_path: &Path) -> PathBuf {
         // Encode project path (replace / with -)
@@ -366,6 +397,44 @@ impl ClaudeSessionReader {
     }
 }
 
+/// Reconstruct the todo list from the latest `TodoWrite` tool_use in a
+/// transcript, for sessions whose hooks never reported it.
+///
+/// Malformed TodoWrite inputs are skipped in favor of the previous list.
+/// Returns None if the transcript never called TodoWrite.
+pub fn read_latest_todos(transcript_path: &Path) -> Result<Option<Vec<TodoItem>>> {
+    let reader = BufReader::new(File::open(transcript_path)?);
+    let mut latest = None;
+
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            continue;
+        };
+        let Ok(entry) = serde_json::from_str::<TranscriptEntry>(&line) else {
+            continue;
+        };
+        if entry.entry_type != "assistant" {
+            continue;
+        }
+        let Some(serde_json::Value::Array(blocks)) = entry.message.map(|m| m.content) else {
+            continue;
+        };
+        for block in blocks {
+            let is_todo_write = block.get("type").and_then(|t| t.as_str()) == Some("tool_use")
+                && block.get("name").and_then(|n| n.as_str()) == Some("TodoWrite");
+            if let Some(todos) = block
+                .get("input")
+                .filter(|_| is_todo_write)
+                .and_then(TodoItem::list_from_tool_input)
+            {
+                latest = Some(todos);
+            }
+        }
+    }
+
+    Ok(latest)
+}
+
 /// Extract text content from a message content value.
 /// Handles both string content and array of content blocks.
 fn extract_text_content(content: &serde_json::Value) -> String {
@@ -422,6 +491,72 @@ mod tests {
         assert_eq!(extract_text_content(&content), "Hello world");
     }
 
+    #[test]
+    fn test_read_latest_todos() {
+        use clauset_types::TodoStatus;
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let mut file = File::create(&path).unwrap();
+        let lines = [
+            serde_json::json!({"type": "user", "message": {"role": "user", "content": "Add retries"}}),
+            serde_json::json!({"type": "assistant", "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "name": "TodoWrite", "input": {"todos": [
+                    {"content": "Write retry loop", "status": "pending", "activeForm": "Writing retry loop"}
+                ]}}
+            ]}}),
+            serde_json::json!({"type": "assistant", "message": {"role": "assistant", "content": [
+                {"type": "text", "text": "Updating the plan"},
+                {"type": "tool_use", "name": "TodoWrite", "input": {"todos": [
+                    {"content": "Write retry loop", "status": "completed", "activeForm": "Writing retry loop"},
+                    {"content": "Add tests", "status": "in_progress", "activeForm": "Adding tests"}
+                ]}}
+            ]}}),
+            // A malformed call later on doesn't wipe out the list
+            serde_json::json!({"type": "assistant", "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "name": "TodoWrite", "input": {"todos": "oops"}}
+            ]}}),
+            serde_json::json!({"type": "assistant", "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "name": "Read", "input": {"file_path": "/p/src/lib.rs"}}
+            ]}}),
+        ];
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        writeln!(file, "not json").unwrap();
+
+        let todos = read_latest_todos(&path).unwrap().unwrap();
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0].content, "Write retry loop");
+        assert_eq!(todos[0].status, TodoStatus::Completed);
+        assert_eq!(todos[1].status, TodoStatus::InProgress);
+        assert_eq!(todos[1].active_form.as_deref(), Some("Adding tests"));
+
+        let empty = dir.path().join("empty.jsonl");
+        File::create(&empty).unwrap();
+        assert!(read_latest_todos(&empty).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_carry_transcript_to_new_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let reader = ClaudeSessionReader::with_dir(dir.path().to_path_buf());
+        let (old, new) = (Path::new("/work/old-checkout"), Path::new("/work/new-checkout"));
+
+        assert!(!reader.carry_transcript("s1", old, new).unwrap());
+
+        let source = dir.path().join("projects/-work-old-checkout/s1.jsonl");
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, "{\"type\":\"user\"}\n").unwrap();
+        assert!(reader.carry_transcript("s1", old, new).unwrap());
+
+        let carried = dir.path().join("projects/-work-new-checkout/s1.jsonl");
+        assert_eq!(std::fs::read_to_string(&carried).unwrap(), "{\"type\":\"user\"}\n");
+        // The original stays, so the session can still resume in the old directory
+        assert!(source.exists());
+    }
+
     #[test]
     fn test_extract_text_content_array() {
         let content = serde_json::json!([
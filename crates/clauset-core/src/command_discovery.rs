@@ -1,6 +1,6 @@
 //! Discovery of Claude Code slash commands from multiple sources.
 
-use crate::Result;
+use crate::{segments_match, wildcard_match, Result};
 use clauset_types::{Command, CommandCategory, CommandCounts, CommandFrontmatter, CommandsResponse};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -10,9 +10,16 @@ use tracing::debug;
 /// Cache TTL for discovered commands.
 const CACHE_TTL: Duration = Duration::from_secs(30);
 
+/// Default include pattern for command files.
+const DEFAULT_INCLUDE: &str = "**/*.md";
+
 /// Discovers Claude Code commands from all sources.
 pub struct CommandDiscovery {
     claude_dir: PathBuf,
+    /// Glob patterns a command file must match (relative to its commands dir).
+    include: Vec<String>,
+    /// Glob patterns that exclude a command file.
+    exclude: Vec<String>,
     cache: Option<CachedCommands>,
 }
 
@@ -25,12 +32,51 @@ impl CommandDiscovery {
     /// Create a new command discovery instance.
     pub fn new() -> Self {
         let claude_dir = dirs::home_dir().unwrap_or_default().join(".claude");
+        Self::with_claude_dir(claude_dir)
+    }
+
+    /// Create a discovery instance rooted at a custom Claude directory.
+    pub fn with_claude_dir(claude_dir: PathBuf) -> Self {
         Self {
             claude_dir,
+            include: vec![DEFAULT_INCLUDE.to_string()],
+            exclude: Vec::new(),
             cache: None,
         }
     }
 
+    /// Only discover command files matching one of these glob patterns.
+    ///
+    /// Patterns containing `/` match the path relative to the commands
+    /// directory (`**` spans directories); other patterns match the file name.
+    pub fn with_include<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.include = patterns.into_iter().map(Into::into).collect();
+        self.cache = None;
+        self
+    }
+
+    /// Skip command files matching any of these glob patterns (e.g. `_*`, `drafts/**`).
+    pub fn with_exclude<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude = patterns.into_iter().map(Into::into).collect();
+        self.cache = None;
+        self
+    }
+
+    /// Whether a command file (relative to its commands directory) passes the filters.
+    fn is_command_file(&self, relative: &Path) -> bool {
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let matches = |pattern: &String| path_matches(pattern, &relative);
+        self.include.iter().any(matches) && !self.exclude.iter().any(matches)
+    }
+
     /// Discover all commands, using cache if available and fresh.
     pub fn discover_all(&mut self) -> Result<CommandsResponse> {
         // Check cache freshness
@@ -184,7 +230,7 @@ impl CommandDiscovery {
         }
 
         let mut commands = Vec::new();
-        self.scan_commands_dir(&commands_dir, CommandCategory::User, None, &mut commands)?;
+        self.scan_commands_dir(&commands_dir, &commands_dir, CommandCategory::User, None, &mut commands)?;
         Ok(commands)
     }
 
@@ -310,6 +356,7 @@ impl CommandDiscovery {
         let commands_dir = plugin_path.join("commands");
         if commands_dir.exists() {
             let _ = self.scan_commands_dir(
+                &commands_dir,
                 &commands_dir,
                 CommandCategory::Plugin,
                 Some(plugin_name),
@@ -334,6 +381,7 @@ impl CommandDiscovery {
             let commands_dir = version_path.join("commands");
             if commands_dir.exists() {
                 let _ = self.scan_commands_dir(
+                    &commands_dir,
                     &commands_dir,
                     CommandCategory::Plugin,
                     Some(plugin_name),
@@ -413,6 +461,7 @@ impl CommandDiscovery {
 
     fn scan_commands_dir(
         &self,
+        root: &Path,
         dir: &Path,
         category: CommandCategory,
         plugin_name: Option<&str>,
@@ -432,8 +481,8 @@ impl CommandDiscovery {
 
             if path.is_dir() {
                 // Recurse into subdirectories (for namespaced commands)
-                self.scan_commands_dir(&path, category, plugin_name, commands)?;
-            } else if path.extension().map(|e| e == "md").unwrap_or(false) {
+                self.scan_commands_dir(root, &path, category, plugin_name, commands)?;
+            } else if self.is_command_file(path.strip_prefix(root).unwrap_or(&path)) {
                 match self.parse_markdown_file(&path) {
                     Ok((frontmatter, first_line)) => {
                         let name = frontmatter
@@ -511,6 +560,20 @@ impl Default for CommandDiscovery {
     }
 }
 
+/// Match a glob pattern against a `/`-separated relative path.
+///
+/// Patterns without a `/` are matched against the file name only.
+fn path_matches(pattern: &str, relative: &str) -> bool {
+    if !pattern.contains('/') {
+        let file_name = relative.rsplit('/').next().unwrap_or(relative);
+        return wildcard_match(pattern, file_name);
+    }
+
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = relative.split('/').collect();
+    segments_match(&pattern, &path)
+}
+
 /// Parse YAML frontmatter from markdown content.
 fn parse_frontmatter(content: &str) -> (CommandFrontmatter, &str) {
     let content = content.trim_start();
@@ -572,6 +635,51 @@ This is the body.
         assert_eq!(remaining, content);
     }
 
+    #[test]
+    fn test_path_matches() {
+        assert!(path_matches("**/*.md", "deploy.md"));
+        assert!(path_matches("**/*.md", "frontend/deploy.md"));
+        assert!(!path_matches("**/*.md", "deploy.txt"));
+        assert!(path_matches("_*", "_template.md"));
+        assert!(path_matches("_*", "nested/_template.md"));
+        assert!(!path_matches("_*", "deploy.md"));
+        assert!(path_matches("drafts/**", "drafts/wip.md"));
+        assert!(!path_matches("drafts/**", "ops/drafts.md"));
+        assert!(path_matches("?.md", "a.md"));
+    }
+
+    #[test]
+    fn test_discovery_include_exclude() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let commands_dir = temp.path().join("commands");
+        fs::create_dir_all(commands_dir.join("drafts")).unwrap();
+        fs::write(commands_dir.join("deploy.md"), "Deploy the app").unwrap();
+        fs::write(commands_dir.join("_template.md"), "Template for new commands").unwrap();
+        fs::write(commands_dir.join("notes.txt"), "Not a command").unwrap();
+        fs::write(commands_dir.join("drafts").join("wip.md"), "Work in progress").unwrap();
+
+        let user_names = |discovery: &mut CommandDiscovery| -> Vec<String> {
+            let mut names: Vec<String> = discovery
+                .discover_all()
+                .unwrap()
+                .commands
+                .into_iter()
+                .filter(|c| c.category == CommandCategory::User)
+                .map(|c| c.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Default: every markdown file, nothing excluded
+        let mut discovery = CommandDiscovery::with_claude_dir(temp.path().to_path_buf());
+        assert_eq!(user_names(&mut discovery), vec!["_template", "deploy", "wip"]);
+
+        let mut discovery = CommandDiscovery::with_claude_dir(temp.path().to_path_buf())
+            .with_exclude(["_*", "drafts/**"]);
+        assert_eq!(user_names(&mut discovery), vec!["deploy"]);
+    }
+
     #[test]
     fn test_built_in_commands() {
         let discovery = CommandDiscovery::new();
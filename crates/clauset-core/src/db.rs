@@ -1,15 +1,55 @@
 //! SQLite persistence for sessions.
 
 use crate::{ClausetError, Result};
-use clauset_types::{Session, SessionMode, SessionStatus, SessionSummary};
+use clauset_types::{PermissionMode, Session, SessionMode, SessionStatus, SessionSummary};
 use rusqlite::{params, Connection, OptionalExtension};
-use std::path::Path;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use uuid::Uuid;
 
 /// SQLite-based session store.
 pub struct SessionStore {
     conn: Mutex<Connection>,
+    /// zstd level for persisted terminal buffers; 0 stores them uncompressed
+    buffer_compression_level: i32,
+}
+
+/// Default zstd level for persisted terminal buffers.
+pub const DEFAULT_BUFFER_COMPRESSION_LEVEL: i32 = 3;
+
+/// Tables with a `session_id` column whose rows are deleted with their session.
+const SESSION_CHILD_TABLES: &[&str] = &[
+    "session_metadata",
+    "session_tags",
+    "session_name_history",
+    "permission_mode_events",
+];
+
+/// One entry in a session's rename history.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionNameChange {
+    pub old_name: String,
+    pub new_name: String,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One entry in a session's permission mode timeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionModeChange {
+    /// Mode the session switched to
+    pub mode: PermissionMode,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A project directory registered by path (outside the projects root scan).
+#[derive(Debug, Clone)]
+pub struct RegisteredProject {
+    pub name: String,
+    /// Canonical path to the project directory
+    pub path: PathBuf,
+    pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// Persisted terminal buffer data for session resume.
@@ -31,12 +71,20 @@ impl SessionStore {
         let conn = Connection::open(path)?;
         let store = Self {
             conn: Mutex::new(conn),
+            buffer_compression_level: DEFAULT_BUFFER_COMPRESSION_LEVEL,
         };
         store.init_schema()?;
         store.migrate()?;
         Ok(store)
     }
 
+    /// Compress persisted terminal buffers at zstd `level` (0 disables
+    /// compression). Buffers already stored are read either way.
+    pub fn with_buffer_compression_level(mut self, level: i32) -> Self {
+        self.buffer_compression_level = level;
+        self
+    }
+
     /// Initialize database schema.
     fn init_schema(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -64,8 +112,61 @@ impl SessionStore {
                 data BLOB NOT NULL,
                 start_seq INTEGER NOT NULL,
                 end_seq INTEGER NOT NULL,
-                updated_at TEXT NOT NULL
+                updated_at TEXT NOT NULL,
+                compressed INTEGER NOT NULL DEFAULT 0
+            );
+
+            -- Project directories registered by path, keyed by canonical path
+            CREATE TABLE IF NOT EXISTS projects (
+                path TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            -- Audit trail of session renames
+            CREATE TABLE IF NOT EXISTS session_name_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                old_name TEXT NOT NULL,
+                new_name TEXT NOT NULL,
+                changed_at TEXT NOT NULL
             );
+
+            CREATE INDEX IF NOT EXISTS idx_session_name_history_session
+                ON session_name_history(session_id);
+
+            -- Audit trail of permission mode changes
+            CREATE TABLE IF NOT EXISTS permission_mode_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                changed_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_permission_mode_events_session
+                ON permission_mode_events(session_id);
+
+            -- Free-form tags for organizing sessions
+            CREATE TABLE IF NOT EXISTS session_tags (
+                session_id TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (session_id, tag)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag);
+
+            -- Key-value metadata for integrating with external tools
+            CREATE TABLE IF NOT EXISTS session_metadata (
+                session_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (session_id, key)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_session_metadata_key_value
+                ON session_metadata(key, value);
             "#,
         )?;
         Ok(())
@@ -112,6 +213,51 @@ impl SessionStore {
             )?;
         }
 
+        // Buffers stored before compression existed are raw
+        let has_buffer_compression: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('terminal_buffers') WHERE name = 'compressed'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_buffer_compression {
+            conn.execute(
+                "ALTER TABLE terminal_buffers ADD COLUMN compressed INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
+
+        // Pinned sessions are exempt from automatic cleanup
+        let has_is_pinned: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'is_pinned'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_is_pinned {
+            conn.execute(
+                "ALTER TABLE sessions ADD COLUMN is_pinned INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
+
+        // Claude Code version that ran the session
+        let has_claude_version: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'claude_version'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_claude_version {
+            conn.execute("ALTER TABLE sessions ADD COLUMN claude_version TEXT", [])?;
+        }
+
         Ok(())
     }
 
@@ -169,6 +315,34 @@ impl SessionStore {
         Ok(sessions)
     }
 
+    /// List unpinned sessions with no recorded interactions whose last
+    /// activity is before `older_than`, oldest first.
+    pub fn list_empty_sessions(&self, older_than: chrono::DateTime<chrono::Utc>) -> Result<Vec<SessionSummary>> {
+        let conn = self.conn.lock().unwrap();
+        // The interactions table belongs to InteractionStore and may not exist yet
+        let has_interactions: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'interactions')",
+            [],
+            |row| row.get(0),
+        )?;
+        let sql = if has_interactions {
+            r#"
+            SELECT * FROM sessions s
+            WHERE s.last_activity_at < ?1
+              AND s.is_pinned = 0
+              AND NOT EXISTS (SELECT 1 FROM interactions i WHERE i.session_id = s.id)
+            ORDER BY s.last_activity_at ASC
+            "#
+        } else {
+            "SELECT * FROM sessions WHERE last_activity_at < ?1 AND is_pinned = 0 ORDER BY last_activity_at ASC"
+        };
+        let mut stmt = conn.prepare(sql)?;
+        let sessions = stmt
+            .query_map(params![older_than.to_rfc3339()], Self::row_to_session_summary)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
     /// List active sessions (not stopped/error).
     pub fn list_active(&self) -> Result<Vec<Session>> {
         let conn = self.conn.lock().unwrap();
@@ -211,6 +385,33 @@ impl SessionStore {
         Ok(())
     }
 
+    /// Correct a session's model and/or project path, leaving unset fields as they are.
+    pub fn update_metadata(&self, id: Uuid, model: Option<&str>, project_path: Option<&Path>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE sessions SET model = COALESCE(?1, model), project_path = COALESCE(?2, project_path) WHERE id = ?3",
+            params![
+                model,
+                project_path.map(|p| p.to_string_lossy().to_string()),
+                id.to_string()
+            ],
+        )?;
+        if updated == 0 {
+            return Err(ClausetError::SessionNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Update the session's project (working) directory.
+    pub fn update_project_path(&self, id: Uuid, project_path: &Path) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET project_path = ?1 WHERE id = ?2",
+            params![project_path.to_string_lossy(), id.to_string()],
+        )?;
+        Ok(())
+    }
+
     /// Update session preview.
     pub fn update_preview(&self, id: Uuid, preview: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -256,10 +457,19 @@ impl SessionStore {
         Ok(())
     }
 
-    /// Delete a session.
+    /// Delete a session along with the rows keyed by it.
     pub fn delete(&self, id: Uuid) -> Result<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute("DELETE FROM sessions WHERE id = ?1", params![id.to_string()])?;
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        // These tables have no foreign keys, so clean them up explicitly
+        for table in SESSION_CHILD_TABLES {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE session_id = ?1"),
+                params![id.to_string()],
+            )?;
+        }
+        tx.execute("DELETE FROM sessions WHERE id = ?1", params![id.to_string()])?;
+        tx.commit()?;
         Ok(())
     }
 
@@ -319,12 +529,22 @@ impl SessionStore {
         Ok(())
     }
 
+    /// Bump only `last_activity_at`, leaving the rest of the row untouched.
+    pub fn touch_activity(&self, id: Uuid, at: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET last_activity_at = ?1 WHERE id = ?2",
+            params![at.to_rfc3339(), id.to_string()],
+        )?;
+        Ok(())
+    }
+
     // ========================================================================
     // Terminal Buffer Persistence
     // ========================================================================
 
-    /// Save terminal buffer to database.
-    /// Replaces any existing buffer for this session.
+    /// Save terminal buffer to database, zstd-compressed unless compression
+    /// is disabled. Replaces any existing buffer for this session.
     pub fn save_terminal_buffer(
         &self,
         session_id: Uuid,
@@ -332,18 +552,25 @@ impl SessionStore {
         start_seq: u64,
         end_seq: u64,
     ) -> Result<()> {
+        let compressed = self.buffer_compression_level != 0;
+        let stored = if compressed {
+            std::borrow::Cow::Owned(zstd::encode_all(data, self.buffer_compression_level)?)
+        } else {
+            std::borrow::Cow::Borrowed(data)
+        };
         let conn = self.conn.lock().unwrap();
         conn.execute(
             r#"
-            INSERT OR REPLACE INTO terminal_buffers (session_id, data, start_seq, end_seq, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5)
+            INSERT OR REPLACE INTO terminal_buffers (session_id, data, start_seq, end_seq, updated_at, compressed)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             "#,
             params![
                 session_id.to_string(),
-                data,
+                stored.as_ref(),
                 start_seq as i64,
                 end_seq as i64,
-                chrono::Utc::now().to_rfc3339()
+                chrono::Utc::now().to_rfc3339(),
+                compressed
             ],
         )?;
         Ok(())
@@ -355,21 +582,33 @@ impl SessionStore {
         let conn = self.conn.lock().unwrap();
         let result = conn
             .query_row(
-                "SELECT data, start_seq, end_seq FROM terminal_buffers WHERE session_id = ?1",
+                "SELECT data, start_seq, end_seq, compressed FROM terminal_buffers WHERE session_id = ?1",
                 params![session_id.to_string()],
                 |row| {
                     let data: Vec<u8> = row.get(0)?;
                     let start_seq: i64 = row.get(1)?;
                     let end_seq: i64 = row.get(2)?;
-                    Ok(TerminalBufferData {
-                        data,
-                        start_seq: start_seq as u64,
-                        end_seq: end_seq as u64,
-                    })
+                    let compressed: bool = row.get(3)?;
+                    Ok((
+                        TerminalBufferData {
+                            data,
+                            start_seq: start_seq as u64,
+                            end_seq: end_seq as u64,
+                        },
+                        compressed,
+                    ))
                 },
             )
             .optional()?;
-        Ok(result)
+        drop(conn);
+        match result {
+            Some((mut buffer, true)) => {
+                buffer.data = zstd::decode_all(&buffer.data[..])?;
+                Ok(Some(buffer))
+            }
+            Some((buffer, false)) => Ok(Some(buffer)),
+            None => Ok(None),
+        }
     }
 
     /// Delete terminal buffer for a session.
@@ -382,6 +621,300 @@ impl SessionStore {
         Ok(())
     }
 
+    /// Rename a session, recording the old and new name in its history.
+    /// Renaming to the current name is not recorded.
+    pub fn rename(&self, id: Uuid, name: &str) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let old_name: String = tx
+            .query_row(
+                "SELECT preview FROM sessions WHERE id = ?1",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or(ClausetError::SessionNotFound(id))?;
+
+        if old_name != name {
+            let now = chrono::Utc::now().to_rfc3339();
+            tx.execute(
+                "UPDATE sessions SET preview = ?1, last_activity_at = ?2 WHERE id = ?3",
+                params![name, now, id.to_string()],
+            )?;
+            tx.execute(
+                r#"INSERT INTO session_name_history (session_id, old_name, new_name, changed_at)
+                   VALUES (?1, ?2, ?3, ?4)"#,
+                params![id.to_string(), old_name, name, now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Pin or unpin a session. Pinned sessions are never removed by the
+    /// empty-session cleanup and keep their interactions past retention.
+    pub fn set_pinned(&self, id: Uuid, pinned: bool) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE sessions SET is_pinned = ?1 WHERE id = ?2",
+            params![pinned, id.to_string()],
+        )?;
+        if updated == 0 {
+            return Err(ClausetError::SessionNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Record the Claude Code version that runs a session.
+    pub fn set_claude_version(&self, id: Uuid, version: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE sessions SET claude_version = ?1 WHERE id = ?2",
+            params![version, id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Record a session's Claude Code version unless one is already known.
+    /// Returns whether it was recorded.
+    pub fn fill_claude_version(&self, id: Uuid, version: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE sessions SET claude_version = ?1 WHERE id = ?2 AND claude_version IS NULL",
+            params![version, id.to_string()],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Get a session's rename history, oldest first.
+    pub fn session_name_history(&self, id: Uuid) -> Result<Vec<SessionNameChange>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"SELECT old_name, new_name, changed_at FROM session_name_history
+               WHERE session_id = ?1 ORDER BY id ASC"#,
+        )?;
+        let history = stmt
+            .query_map(params![id.to_string()], |row| {
+                let changed_at: String = row.get(2)?;
+                Ok(SessionNameChange {
+                    old_name: row.get(0)?,
+                    new_name: row.get(1)?,
+                    changed_at: chrono::DateTime::parse_from_rfc3339(&changed_at)
+                        .map(|dt| dt.with_timezone(&chrono::Utc))
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(history)
+    }
+
+    /// Record that a session switched permission mode.
+    pub fn record_permission_mode(&self, id: Uuid, mode: PermissionMode) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"INSERT INTO permission_mode_events (session_id, mode, changed_at)
+               VALUES (?1, ?2, ?3)"#,
+            params![id.to_string(), serde_json::to_string(&mode)?, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Get a session's permission mode changes, oldest first.
+    pub fn permission_mode_timeline(&self, id: Uuid) -> Result<Vec<PermissionModeChange>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"SELECT mode, changed_at FROM permission_mode_events
+               WHERE session_id = ?1 ORDER BY id ASC"#,
+        )?;
+        let timeline = stmt
+            .query_map(params![id.to_string()], |row| {
+                let mode: String = row.get(0)?;
+                let changed_at: String = row.get(1)?;
+                Ok(PermissionModeChange {
+                    mode: serde_json::from_str(&mode).unwrap_or(PermissionMode::Default),
+                    changed_at: chrono::DateTime::parse_from_rfc3339(&changed_at)
+                        .map(|dt| dt.with_timezone(&chrono::Utc))
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(timeline)
+    }
+
+    // ========================================================================
+    // Session Tags
+    // ========================================================================
+
+    /// Tag a session. Returns false if the session already had the tag.
+    pub fn add_session_tag(&self, id: Uuid, tag: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sessions WHERE id = ?1",
+            params![id.to_string()],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(ClausetError::SessionNotFound(id));
+        }
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO session_tags (session_id, tag, created_at) VALUES (?1, ?2, ?3)",
+            params![id.to_string(), tag, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Remove a tag from a session. Returns false if the session didn't have it.
+    pub fn remove_session_tag(&self, id: Uuid, tag: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute(
+            "DELETE FROM session_tags WHERE session_id = ?1 AND tag = ?2",
+            params![id.to_string(), tag],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// List a session's tags, alphabetically.
+    pub fn list_session_tags(&self, id: Uuid) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT tag FROM session_tags WHERE session_id = ?1 ORDER BY tag ASC")?;
+        let tags = stmt
+            .query_map(params![id.to_string()], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+        Ok(tags)
+    }
+
+    /// List sessions carrying a tag, ordered by last activity (most recent first).
+    pub fn list_sessions_by_tag(&self, tag: &str) -> Result<Vec<SessionSummary>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT s.* FROM sessions s
+            JOIN session_tags t ON t.session_id = s.id
+            WHERE t.tag = ?1
+            ORDER BY s.last_activity_at DESC
+            "#,
+        )?;
+        let sessions = stmt
+            .query_map(params![tag], Self::row_to_session_summary)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
+    // ========================================================================
+    // Session Metadata
+    // ========================================================================
+
+    /// Set a metadata key on a session, replacing any previous value.
+    pub fn set_session_metadata(&self, id: Uuid, key: &str, value: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sessions WHERE id = ?1",
+            params![id.to_string()],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(ClausetError::SessionNotFound(id));
+        }
+        conn.execute(
+            r#"
+            INSERT INTO session_metadata (session_id, key, value, updated_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT (session_id, key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = excluded.updated_at
+            "#,
+            params![id.to_string(), key, value, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Get a session's metadata, ordered by key.
+    pub fn get_session_metadata(&self, id: Uuid) -> Result<BTreeMap<String, String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT key, value FROM session_metadata WHERE session_id = ?1")?;
+        let metadata = stmt
+            .query_map(params![id.to_string()], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<BTreeMap<String, String>, _>>()?;
+        Ok(metadata)
+    }
+
+    /// Remove a metadata key from a session. Returns false if it wasn't set.
+    pub fn delete_session_metadata(&self, id: Uuid, key: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute(
+            "DELETE FROM session_metadata WHERE session_id = ?1 AND key = ?2",
+            params![id.to_string(), key],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// List the ids of sessions whose metadata has `key` set to `value`.
+    pub fn session_ids_with_metadata(&self, key: &str, value: &str) -> Result<Vec<Uuid>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT session_id FROM session_metadata WHERE key = ?1 AND value = ?2")?;
+        let ids = stmt
+            .query_map(params![key, value], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(ids.iter().filter_map(|id| Uuid::parse_str(id).ok()).collect())
+    }
+
+    // ========================================================================
+    // Registered Projects
+    // ========================================================================
+
+    /// Register a project directory. `path` should already be canonical.
+    /// Returns false if the path was already registered.
+    pub fn register_project(&self, path: &Path, name: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO projects (path, name, created_at) VALUES (?1, ?2, ?3)",
+            params![
+                path.to_string_lossy(),
+                name,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Get a registered project by canonical path.
+    pub fn get_project(&self, path: &Path) -> Result<Option<RegisteredProject>> {
+        let conn = self.conn.lock().unwrap();
+        let project = conn
+            .query_row(
+                "SELECT path, name, created_at FROM projects WHERE path = ?1",
+                params![path.to_string_lossy()],
+                Self::row_to_project,
+            )
+            .optional()?;
+        Ok(project)
+    }
+
+    /// List registered projects ordered by name.
+    pub fn list_projects(&self) -> Result<Vec<RegisteredProject>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT path, name, created_at FROM projects ORDER BY name COLLATE NOCASE")?;
+        let projects = stmt
+            .query_map([], Self::row_to_project)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(projects)
+    }
+
+    fn row_to_project(row: &rusqlite::Row) -> rusqlite::Result<RegisteredProject> {
+        let path: String = row.get(0)?;
+        let created_at: String = row.get(2)?;
+        Ok(RegisteredProject {
+            name: row.get(1)?,
+            path: path.into(),
+            created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_default(),
+        })
+    }
+
     fn row_to_session(row: &rusqlite::Row) -> rusqlite::Result<Session> {
         let id: String = row.get("id")?;
         let claude_session_id: String = row.get("claude_session_id")?;
@@ -396,6 +929,7 @@ impl SessionStore {
         let output_tokens: i64 = row.get("output_tokens").unwrap_or(0);
         let context_percent: i32 = row.get("context_percent").unwrap_or(0);
         let preview: String = row.get("preview")?;
+        let claude_version: Option<String> = row.get("claude_version").unwrap_or(None);
 
         Ok(Session {
             id: Uuid::parse_str(&id).unwrap_or_default(),
@@ -415,6 +949,7 @@ impl SessionStore {
             output_tokens: output_tokens as u64,
             context_percent: context_percent as u8,
             preview,
+            claude_version,
         })
     }
 
@@ -432,6 +967,7 @@ impl SessionStore {
         let output_tokens: i64 = row.get("output_tokens").unwrap_or(0);
         let context_percent: i32 = row.get("context_percent").unwrap_or(0);
         let preview: String = row.get("preview")?;
+        let is_pinned: bool = row.get("is_pinned").unwrap_or(false);
         let current_step: Option<String> = row.get("current_step").ok();
         let recent_actions_json: String = row.get("recent_actions").unwrap_or_else(|_| "[]".to_string());
         let recent_actions: Vec<clauset_types::RecentAction> =
@@ -455,8 +991,150 @@ impl SessionStore {
             output_tokens: output_tokens as u64,
             context_percent: context_percent as u8,
             preview,
+            subtitle: None,
             current_step,
             recent_actions,
+            is_pinned,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_terminal_buffer_is_stored_compressed() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::open(&dir.path().join("sessions.db")).unwrap();
+        let session_id = Uuid::new_v4();
+        let data = b"\x1b[2K\r\x1b[32m* Thinking...\x1b[0m Opus 4.5 | $1.23 | ctx:42%\r\n".repeat(5000);
+
+        store.save_terminal_buffer(session_id, &data, 10, 20).unwrap();
+
+        let stored_len: i64 = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT length(data) FROM terminal_buffers WHERE session_id = ?1",
+                params![session_id.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!((stored_len as usize) < data.len() / 20, "{stored_len} of {}", data.len());
+
+        let restored = store.get_terminal_buffer(session_id).unwrap().unwrap();
+        assert_eq!(restored.data, data);
+        assert_eq!((restored.start_seq, restored.end_seq), (10, 20));
+
+        // Buffers saved uncompressed are still read back as is
+        let raw = store.with_buffer_compression_level(0);
+        raw.save_terminal_buffer(session_id, b"plain", 0, 0).unwrap();
+        assert_eq!(raw.get_terminal_buffer(session_id).unwrap().unwrap().data, b"plain");
+    }
+
+    #[test]
+    fn test_delete_removes_session_metadata() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::open(&dir.path().join("sessions.db")).unwrap();
+        let now = chrono::Utc::now();
+        let session = Session {
+            id: Uuid::new_v4(),
+            claude_session_id: Uuid::new_v4(),
+            project_path: PathBuf::from("/test/project"),
+            model: "sonnet".to_string(),
+            status: SessionStatus::Active,
+            mode: SessionMode::Terminal,
+            created_at: now,
+            last_activity_at: now,
+            total_cost_usd: 0.0,
+            input_tokens: 0,
+            output_tokens: 0,
+            context_percent: 0,
+            preview: String::new(),
+            claude_version: None,
+        };
+        store.insert(&session).unwrap();
+        store.set_session_metadata(session.id, "jira", "PROJ-1").unwrap();
+
+        store.delete(session.id).unwrap();
+        assert!(store.get(session.id).unwrap().is_none());
+        assert!(store.get_session_metadata(session.id).unwrap().is_empty());
+        assert!(store.session_ids_with_metadata("jira", "PROJ-1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_removes_tags_renames_and_mode_events() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::open(&dir.path().join("sessions.db")).unwrap();
+        let now = chrono::Utc::now();
+        let session = Session {
+            id: Uuid::new_v4(),
+            claude_session_id: Uuid::new_v4(),
+            project_path: PathBuf::from("/test/project"),
+            model: "sonnet".to_string(),
+            status: SessionStatus::Active,
+            mode: SessionMode::Terminal,
+            created_at: now,
+            last_activity_at: now,
+            total_cost_usd: 0.0,
+            input_tokens: 0,
+            output_tokens: 0,
+            context_percent: 0,
+            preview: String::new(),
+            claude_version: None,
+        };
+        store.insert(&session).unwrap();
+        store.add_session_tag(session.id, "release").unwrap();
+        store.rename(session.id, "Release prep").unwrap();
+        store.record_permission_mode(session.id, PermissionMode::Plan).unwrap();
+
+        store.delete(session.id).unwrap();
+        let conn = store.conn.lock().unwrap();
+        for table in ["session_tags", "session_name_history", "permission_mode_events"] {
+            let rows: i64 = conn
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(rows, 0, "{table} kept rows of a deleted session");
+        }
+    }
+
+    #[test]
+    fn test_touch_activity_only_changes_last_activity_at() {
+        let dir = TempDir::new().unwrap();
+        let store = SessionStore::open(&dir.path().join("sessions.db")).unwrap();
+
+        let created = chrono::Utc::now() - chrono::Duration::hours(1);
+        let session = Session {
+            id: Uuid::new_v4(),
+            claude_session_id: Uuid::new_v4(),
+            project_path: PathBuf::from("/test/project"),
+            model: "sonnet".to_string(),
+            status: SessionStatus::Active,
+            mode: SessionMode::Terminal,
+            created_at: created,
+            last_activity_at: created,
+            total_cost_usd: 1.25,
+            input_tokens: 1000,
+            output_tokens: 200,
+            context_percent: 40,
+            preview: "Fix the parser".to_string(),
+            claude_version: None,
+        };
+        store.insert(&session).unwrap();
+        let before = store.get(session.id).unwrap().unwrap();
+
+        let at = chrono::Utc::now();
+        store.touch_activity(session.id, at).unwrap();
+        let after = store.get(session.id).unwrap().unwrap();
+
+        assert_eq!(after.last_activity_at.timestamp_micros(), at.timestamp_micros());
+        let mut before_json = serde_json::to_value(&before).unwrap();
+        let mut after_json = serde_json::to_value(&after).unwrap();
+        before_json["last_activity_at"] = serde_json::Value::Null;
+        after_json["last_activity_at"] = serde_json::Value::Null;
+        assert_eq!(before_json, after_json);
+    }
+}
@@ -4,7 +4,13 @@
 //! providing structured output suitable for display in the frontend.
 
 use serde::{Deserialize, Serialize};
-use similar::{ChangeTag, TextDiff};
+use similar::{Algorithm, DiffTag, TextDiff};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// A single line change in a diff.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +48,10 @@ pub struct DiffHunk {
     pub new_start: u32,
     /// Number of lines in new file
     pub new_count: u32,
+    /// Nearest line above the hunk that looks like a function, class or
+    /// other section header, as in git's `@@ ... @@ fn foo()` hunk headers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section_heading: Option<String>,
     /// The lines in this hunk
     pub lines: Vec<DiffLine>,
 }
@@ -59,6 +69,9 @@ pub struct FileDiff {
     pub is_identical: bool,
     /// Whether either file is binary
     pub is_binary: bool,
+    /// Whether a stored snapshot couldn't be decompressed (no hunks are available)
+    #[serde(default)]
+    pub decode_error: bool,
 }
 
 impl FileDiff {
@@ -70,6 +83,7 @@ impl FileDiff {
             hunks: Vec::new(),
             is_identical: true,
             is_binary: false,
+            decode_error: false,
         }
     }
 
@@ -81,8 +95,34 @@ impl FileDiff {
             hunks: Vec::new(),
             is_identical: false,
             is_binary: true,
+            decode_error: false,
         }
     }
+
+    /// Create a placeholder for a change whose snapshot content is corrupt.
+    pub fn decode_error() -> Self {
+        Self {
+            lines_added: 0,
+            lines_removed: 0,
+            hunks: Vec::new(),
+            is_identical: false,
+            is_binary: false,
+            decode_error: true,
+        }
+    }
+}
+
+/// Options controlling which differences count as changes.
+///
+/// Normalization only affects how lines are compared; the rendered lines
+/// keep their original content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiffOptions {
+    /// Ignore all whitespace when comparing lines (like `diff -w`)
+    pub ignore_whitespace: bool,
+    /// Leave blank lines out of the comparison
+    pub ignore_blank_lines: bool,
 }
 
 /// Compute a diff between two file contents.
@@ -98,6 +138,19 @@ pub fn compute_diff(
     old_content: Option<&[u8]>,
     new_content: Option<&[u8]>,
     context_lines: usize,
+) -> FileDiff {
+    compute_diff_with_options(old_content, new_content, context_lines, &DiffOptions::default())
+}
+
+/// Compute a diff between two file contents, comparing lines per `options`.
+///
+/// With `ignore_blank_lines`, blank lines are skipped entirely and so never
+/// appear in hunks, not even as context.
+pub fn compute_diff_with_options(
+    old_content: Option<&[u8]>,
+    new_content: Option<&[u8]>,
+    context_lines: usize,
+    options: &DiffOptions,
 ) -> FileDiff {
     // Handle edge cases
     match (old_content, new_content) {
@@ -121,15 +174,26 @@ pub fn compute_diff(
     let old_text = old_str.as_deref().unwrap_or("");
     let new_text = new_str.as_deref().unwrap_or("");
 
+    // Split into lines (keeping terminators, as `TextDiff::from_lines` does)
+    // and build the keys that are actually compared
+    let old_lines: Vec<&str> = old_text.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_text.split_inclusive('\n').collect();
+    let (old_index, old_keys) = comparison_keys(&old_lines, options);
+    let (new_index, new_keys) = comparison_keys(&new_lines, options);
+
     // Compute the diff using similar
-    let diff = TextDiff::from_lines(old_text, new_text);
+    let ops = similar::capture_diff_slices(Algorithm::Myers, &old_keys, &new_keys);
 
     let mut hunks = Vec::new();
     let mut lines_added = 0u32;
     let mut lines_removed = 0u32;
 
     // Group changes into hunks with context
-    for group in diff.grouped_ops(context_lines) {
+    for group in similar::group_diff_ops(ops, context_lines) {
+        if group.iter().all(|op| op.tag() == DiffTag::Equal) {
+            continue;
+        }
+
         let mut hunk_lines = Vec::new();
         let mut old_start = 0u32;
         let mut new_start = 0u32;
@@ -138,66 +202,73 @@ pub fn compute_diff(
         let mut first = true;
 
         for op in group {
-            for change in diff.iter_changes(&op) {
-                let (old_line, new_line) = match change.tag() {
-                    ChangeTag::Delete => {
-                        lines_removed += 1;
-                        old_count += 1;
-                        let old_idx = change.old_index().map(|i| i as u32 + 1);
-                        if first {
-                            old_start = old_idx.unwrap_or(1);
-                            first = false;
-                        }
-                        (old_idx, None)
-                    }
-                    ChangeTag::Insert => {
-                        lines_added += 1;
-                        new_count += 1;
-                        let new_idx = change.new_index().map(|i| i as u32 + 1);
-                        if first {
-                            new_start = new_idx.unwrap_or(1);
-                            first = false;
-                        }
-                        (None, new_idx)
-                    }
-                    ChangeTag::Equal => {
-                        old_count += 1;
-                        new_count += 1;
-                        let old_idx = change.old_index().map(|i| i as u32 + 1);
-                        let new_idx = change.new_index().map(|i| i as u32 + 1);
-                        if first {
-                            old_start = old_idx.unwrap_or(1);
-                            new_start = new_idx.unwrap_or(1);
-                            first = false;
-                        }
-                        (old_idx, new_idx)
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+
+            if tag == DiffTag::Equal {
+                for (old_i, new_i) in old_range.zip(new_range) {
+                    old_count += 1;
+                    new_count += 1;
+                    let old_idx = old_index[old_i] as u32 + 1;
+                    let new_idx = new_index[new_i] as u32 + 1;
+                    if first {
+                        old_start = old_idx;
+                        new_start = new_idx;
+                        first = false;
                     }
-                };
-
-                let change_type = match change.tag() {
-                    ChangeTag::Delete => DiffChangeType::Remove,
-                    ChangeTag::Insert => DiffChangeType::Add,
-                    ChangeTag::Equal => DiffChangeType::Context,
-                };
-
-                // Remove trailing newline from content for cleaner display
-                let content = change.value().trim_end_matches('\n').to_string();
+                    hunk_lines.push(DiffLine {
+                        change_type: DiffChangeType::Context,
+                        old_line_num: Some(old_idx),
+                        new_line_num: Some(new_idx),
+                        content: display_line(new_lines[new_index[new_i]]),
+                    });
+                }
+                continue;
+            }
 
+            // Replacements render as removals followed by additions
+            for old_i in old_range {
+                lines_removed += 1;
+                old_count += 1;
+                let old_idx = old_index[old_i] as u32 + 1;
+                if first {
+                    old_start = old_idx;
+                    first = false;
+                }
+                hunk_lines.push(DiffLine {
+                    change_type: DiffChangeType::Remove,
+                    old_line_num: Some(old_idx),
+                    new_line_num: None,
+                    content: display_line(old_lines[old_index[old_i]]),
+                });
+            }
+            for new_i in new_range {
+                lines_added += 1;
+                new_count += 1;
+                let new_idx = new_index[new_i] as u32 + 1;
+                if first {
+                    new_start = new_idx;
+                    first = false;
+                }
                 hunk_lines.push(DiffLine {
-                    change_type,
-                    old_line_num: old_line,
-                    new_line_num: new_line,
-                    content,
+                    change_type: DiffChangeType::Add,
+                    old_line_num: None,
+                    new_line_num: Some(new_idx),
+                    content: display_line(new_lines[new_index[new_i]]),
                 });
             }
         }
 
         if !hunk_lines.is_empty() {
+            // old_start is 1-based, so the lines above the hunk end at old_start - 1
+            let section_heading = (old_start > 0)
+                .then(|| section_heading(&old_lines, old_start as usize - 1))
+                .flatten();
             hunks.push(DiffHunk {
                 old_start,
                 old_count,
                 new_start,
                 new_count,
+                section_heading,
                 lines: hunk_lines,
             });
         }
@@ -209,7 +280,122 @@ pub fn compute_diff(
         hunks,
         is_identical: lines_added == 0 && lines_removed == 0,
         is_binary: false,
+        decode_error: false,
+    }
+}
+
+/// One element of a sequence diff, indexing into the old and/or new sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceStep {
+    /// `Context` for elements common to both sequences
+    pub change_type: DiffChangeType,
+    /// Index in the old sequence (None for additions)
+    pub old_index: Option<usize>,
+    /// Index in the new sequence (None for removals)
+    pub new_index: Option<usize>,
+}
+
+/// Diff two arbitrary sequences, keeping a longest common subsequence as
+/// context (Myers' algorithm, as for file diffs).
+///
+/// Every element of both sequences appears once, in order; replacements
+/// render as removals followed by additions, as in file diffs.
+pub fn diff_sequences<T: Hash + Eq + Ord>(old: &[T], new: &[T]) -> Vec<SequenceStep> {
+    let mut steps = Vec::with_capacity(old.len().max(new.len()));
+    for op in similar::capture_diff_slices(Algorithm::Myers, old, new) {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            steps.extend(old_range.zip(new_range).map(|(old_i, new_i)| SequenceStep {
+                change_type: DiffChangeType::Context,
+                old_index: Some(old_i),
+                new_index: Some(new_i),
+            }));
+            continue;
+        }
+        steps.extend(old_range.map(|old_i| SequenceStep {
+            change_type: DiffChangeType::Remove,
+            old_index: Some(old_i),
+            new_index: None,
+        }));
+        steps.extend(new_range.map(|new_i| SequenceStep {
+            change_type: DiffChangeType::Add,
+            old_index: None,
+            new_index: Some(new_i),
+        }));
     }
+    steps
+}
+
+/// Build the comparison keys for `lines`, along with each key's original line index.
+fn comparison_keys<'a>(
+    lines: &[&'a str],
+    options: &DiffOptions,
+) -> (Vec<usize>, Vec<Cow<'a, str>>) {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !(options.ignore_blank_lines && line.trim().is_empty()))
+        .map(|(i, line)| {
+            let key = if options.ignore_whitespace {
+                Cow::Owned(line.chars().filter(|c| !c.is_whitespace()).collect())
+            } else {
+                Cow::Borrowed(*line)
+            };
+            (i, key)
+        })
+        .unzip()
+}
+
+/// Longest section heading kept, in characters (git uses 80 bytes).
+const MAX_SECTION_HEADING_LEN: usize = 80;
+
+/// Find the section a hunk starting at line index `start` falls in: the
+/// nearest line above it that starts with a letter, `_` or `$`, which is
+/// git's default rule for hunk header function names.
+fn section_heading(old_lines: &[&str], start: usize) -> Option<String> {
+    old_lines[..start.min(old_lines.len())]
+        .iter()
+        .rev()
+        .map(|line| line.trim_end())
+        .find(|line| line.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$'))
+        .map(|line| line.chars().take(MAX_SECTION_HEADING_LEN).collect())
+}
+
+/// Guess a file's programming language from its extension.
+///
+/// Returns `None` for unrecognized or missing extensions.
+pub fn detect_language(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let language = match ext.as_str() {
+        "rs" => "Rust",
+        "py" | "pyi" => "Python",
+        "js" | "mjs" | "cjs" | "jsx" => "JavaScript",
+        "ts" | "mts" | "cts" | "tsx" => "TypeScript",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => "C++",
+        "cs" => "C#",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "sh" | "bash" | "zsh" => "Shell",
+        "sql" => "SQL",
+        "html" | "htm" => "HTML",
+        "css" | "scss" | "sass" => "CSS",
+        "json" => "JSON",
+        "toml" => "TOML",
+        "yaml" | "yml" => "YAML",
+        "md" | "markdown" => "Markdown",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Remove trailing newline from content for cleaner display
+fn display_line(line: &str) -> String {
+    line.trim_end_matches('\n').to_string()
 }
 
 /// Generate a unified diff string (like `diff -u` output).
@@ -227,11 +413,74 @@ pub fn generate_unified_diff(
     let new_text = new_str.as_deref().unwrap_or("");
 
     let diff = TextDiff::from_lines(old_text, new_text);
+    let old_lines: Vec<&str> = old_text.split_inclusive('\n').collect();
 
-    diff.unified_diff()
-        .context_radius(context_lines)
-        .header(old_path, new_path)
-        .to_string()
+    let mut out = String::new();
+    for hunk in diff.unified_diff().context_radius(context_lines).iter_hunks() {
+        if out.is_empty() {
+            out.push_str(&format!("--- {old_path}\n+++ {new_path}\n"));
+        }
+        let rendered = hunk.to_string();
+        let start = hunk.ops().first().map_or(0, |op| op.old_range().start);
+        match (section_heading(&old_lines, start), rendered.split_once('\n')) {
+            (Some(heading), Some((header, body))) => {
+                out.push_str(&format!("{header} {heading}\n{body}"));
+            }
+            _ => out.push_str(&rendered),
+        }
+    }
+    out
+}
+
+/// Identifies a computed diff. Snapshot content is addressed by hash, so
+/// the same key always yields the same diff.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct DiffCacheKey {
+    pub before_hash: Option<String>,
+    pub after_hash: Option<String>,
+    pub context_lines: usize,
+    pub options: DiffOptions,
+}
+
+/// Bounded LRU cache of computed diffs.
+pub(crate) struct DiffCache {
+    capacity: usize,
+    /// Use counter for recency
+    tick: AtomicU64,
+    /// Each diff with the tick it was last used at
+    entries: Mutex<HashMap<DiffCacheKey, (FileDiff, u64)>>,
+}
+
+impl DiffCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: AtomicU64::new(0),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &DiffCacheKey) -> Option<FileDiff> {
+        let mut entries = self.entries.lock().unwrap();
+        let (diff, last_used) = entries.get_mut(key)?;
+        *last_used = self.tick.fetch_add(1, Ordering::Relaxed);
+        Some(diff.clone())
+    }
+
+    /// Store a diff, evicting the least recently used one when full.
+    pub fn insert(&self, key: DiffCacheKey, diff: FileDiff) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity
+            && !entries.contains_key(&key)
+            && let Some(oldest) = entries.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| k.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(key, (diff, self.tick.fetch_add(1, Ordering::Relaxed)));
+    }
 }
 
 #[cfg(test)]
@@ -310,4 +559,111 @@ mod tests {
         assert!(unified.contains("-line2"));
         assert!(unified.contains("+modified"));
     }
+
+    #[test]
+    fn test_hunk_section_heading_names_enclosing_function() {
+        let old = b"use std::fmt;\n\npub fn parse(input: &str) -> Vec<String> {\n    let mut out = Vec::new();\n    for line in input.lines() {\n        let line = line.trim();\n        if line.is_empty() {\n            continue;\n        }\n        out.push(line.to_string());\n    }\n    out\n}\n";
+        let new = b"use std::fmt;\n\npub fn parse(input: &str) -> Vec<String> {\n    let mut out = Vec::new();\n    for line in input.lines() {\n        let line = line.trim();\n        if line.is_empty() || line.starts_with('#') {\n            continue;\n        }\n        out.push(line.to_string());\n    }\n    out\n}\n";
+
+        let diff = compute_diff(Some(old), Some(new), 2);
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].old_start, 5);
+        assert_eq!(
+            diff.hunks[0].section_heading.as_deref(),
+            Some("pub fn parse(input: &str) -> Vec<String> {")
+        );
+
+        let unified = generate_unified_diff(Some(old), Some(new), "a/parse.rs", "b/parse.rs", 2);
+        assert!(unified.contains("@@ -5,5 +5,5 @@ pub fn parse(input: &str) -> Vec<String> {\n"));
+
+        // A hunk at the top of the file has nothing above it
+        let diff = compute_diff(Some(b"a\nb\n"), Some(b"a\nc\n"), 3);
+        assert!(diff.hunks[0].section_heading.is_none());
+    }
+
+    #[test]
+    fn test_diff_sequences() {
+        let old = ["Read", "Edit", "Bash"];
+        let new = ["Read", "Grep", "Edit", "Write"];
+        let steps: Vec<_> = diff_sequences(&old, &new)
+            .into_iter()
+            .map(|s| (s.change_type, s.old_index, s.new_index))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (DiffChangeType::Context, Some(0), Some(0)),
+                (DiffChangeType::Add, None, Some(1)),
+                (DiffChangeType::Context, Some(1), Some(2)),
+                (DiffChangeType::Remove, Some(2), None),
+                (DiffChangeType::Add, None, Some(3)),
+            ]
+        );
+        assert!(diff_sequences::<&str>(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_ignore_whitespace_skips_reindentation() {
+        let old = b"fn main() {\n    run();\n}\n";
+        let new = b"fn main() {\n\trun();   \n}\n";
+
+        let diff = compute_diff(Some(old), Some(new), 3);
+        assert_eq!((diff.lines_added, diff.lines_removed), (1, 1));
+
+        let options = DiffOptions { ignore_whitespace: true, ..DiffOptions::default() };
+        let diff = compute_diff_with_options(Some(old), Some(new), 3, &options);
+        assert!(diff.is_identical);
+        assert!(diff.hunks.is_empty());
+    }
+
+    #[test]
+    fn test_ignore_options_keep_original_content() {
+        let old = b"a\n  b\nc\n";
+        let new = b"a\n\n    b\nc\nd\n";
+        let options = DiffOptions { ignore_whitespace: true, ignore_blank_lines: true };
+        let diff = compute_diff_with_options(Some(old), Some(new), 1, &options);
+
+        // Only the appended line is a change; the blank line and indent are ignored
+        assert_eq!((diff.lines_added, diff.lines_removed), (1, 0));
+        let lines = &diff.hunks[0].lines;
+        let context = lines.iter().find(|l| l.change_type == DiffChangeType::Context).unwrap();
+        assert_eq!(context.content, "c");
+        assert_eq!((context.old_line_num, context.new_line_num), (Some(3), Some(4)));
+        let added = lines.last().unwrap();
+        assert_eq!((added.change_type, added.content.as_str()), (DiffChangeType::Add, "d"));
+        assert_eq!(added.new_line_num, Some(5));
+
+        // Blank lines alone still count without the option
+        let diff = compute_diff(Some(b"a\nb\n"), Some(b"a\n\nb\n"), 3);
+        assert_eq!(diff.lines_added, 1);
+    }
+
+    #[test]
+    fn test_diff_cache_evicts_least_recently_used() {
+        let key = |after: &str| DiffCacheKey {
+            before_hash: None,
+            after_hash: Some(after.to_string()),
+            context_lines: 3,
+            options: DiffOptions::default(),
+        };
+        let cache = DiffCache::new(2);
+        cache.insert(key("a"), compute_diff(None, Some(b"a\n"), 3));
+        cache.insert(key("b"), compute_diff(None, Some(b"b\n"), 3));
+        // Touch "a" so "b" is the oldest
+        assert!(cache.get(&key("a")).is_some());
+        cache.insert(key("c"), compute_diff(None, Some(b"c\n"), 3));
+
+        assert!(cache.get(&key("a")).is_some());
+        assert!(cache.get(&key("b")).is_none());
+        assert_eq!(cache.get(&key("c")).unwrap().lines_added, 1);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(detect_language(Path::new("src/main.rs")), Some("Rust"));
+        assert_eq!(detect_language(Path::new("App.TSX")), Some("TypeScript"));
+        assert_eq!(detect_language(Path::new("script.py")), Some("Python"));
+        assert_eq!(detect_language(Path::new("Makefile")), None);
+        assert_eq!(detect_language(Path::new("data.xyz")), None);
+    }
 }
@@ -11,27 +11,45 @@ pub enum ClausetError {
     #[error("Session {0} has no resumable state (Claude session ID not captured)")]
     SessionNotResumable(Uuid),
 
+    #[error("Session {0} has no transcript to carry into a new working directory")]
+    TranscriptNotFound(Uuid),
+
     #[error("Session already exists: {0}")]
     SessionAlreadyExists(Uuid),
 
     #[error("Session limit exceeded: max {0} concurrent sessions")]
     SessionLimitExceeded(usize),
 
+    #[error("Interaction not found: {0}")]
+    InteractionNotFound(Uuid),
+
     #[error("Invalid session state: expected {expected}, got {actual}")]
     InvalidSessionState { expected: String, actual: String },
 
     #[error("Process spawn failed: {0}")]
     ProcessSpawnFailed(String),
 
+    #[error("Failed to start session {session_id}: {reason}")]
+    SessionSpawnFailed { session_id: Uuid, reason: String },
+
     #[error("PTY error: {0}")]
     PtyError(String),
 
     #[error("Claude CLI error: {0}")]
     ClaudeCliError(String),
 
+    #[error("Corrupt snapshot content: {content_hash}")]
+    CorruptSnapshot { content_hash: String },
+
     #[error("Parse error: {0}")]
     ParseError(String),
 
+    #[error("Query rejected: {0}")]
+    QueryRejected(String),
+
+    #[error("Query timed out after {0:?}")]
+    QueryTimeout(std::time::Duration),
+
     #[error("Database error: {0}")]
     DatabaseError(#[from] rusqlite::Error),
 
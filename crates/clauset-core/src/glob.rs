@@ -0,0 +1,65 @@
+//! Glob matching for path filters (`*`, `?` and `**`).
+
+/// Match `/`-separated pattern segments against path components. A `**`
+/// segment matches zero or more components; the whole path must be matched.
+pub fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| segments_match(rest, &path[skip..])),
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(name, tail)| wildcard_match(first, name) && segments_match(rest, tail)),
+    }
+}
+
+/// Match a single path component against `*` / `?` wildcards.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.rs", "main.rs"));
+        assert!(wildcard_match("?.md", "a.md"));
+        assert!(!wildcard_match("?.md", "ab.md"));
+        assert!(wildcard_match("a*b*c", "aXbYbc"));
+        assert!(!wildcard_match("*.rs", "main.rs.bak"));
+    }
+
+    #[test]
+    fn test_segments_match() {
+        assert!(segments_match(&["**", "*.md"], &["deploy.md"]));
+        assert!(segments_match(&["**", "*.md"], &["a", "b", "deploy.md"]));
+        assert!(segments_match(&["src", "**"], &["src"]));
+        assert!(!segments_match(&["src", "*.rs"], &["lib", "main.rs"]));
+        assert!(!segments_match(&["src"], &["src", "main.rs"]));
+    }
+}
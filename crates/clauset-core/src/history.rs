@@ -1,44 +1,171 @@
-//! History reader for ~/.claude/history.jsonl.
+//! History reader for ~/.claude/history.jsonl (and any other history files).
+//!
+//! Besides Claude's JSONL format, lines in zsh extended history
+//! (`: <epoch>:<duration>;<command>`) and plain shell history are understood.
+//! Fish history is multi-line and is read record by record; the watcher
+//! recognizes fish history files by their first record. Lines that aren't
+//! valid UTF-8 (zsh metafies non-ASCII bytes) are read lossily.
 
 use crate::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
 use uuid::Uuid;
 
-/// An entry from Claude's history.jsonl file.
+/// An entry from Claude's history.jsonl file (or a converted shell history line).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub display: String,
     #[serde(default)]
     pub pasted_contents: HashMap<String, serde_json::Value>,
+    /// Unix timestamp in milliseconds (0 when the source has no timestamp).
     pub timestamp: i64,
     pub project: PathBuf,
     #[serde(default)]
     pub session_id: Option<Uuid>,
+    /// How long the command ran, when recorded (zsh extended history).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
 }
 
-/// Watches and reads Claude's history file.
+/// A command parsed from any supported history format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryCommand {
+    pub command: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub duration: Option<Duration>,
+    /// Working directory, when the format records it (Claude history).
+    pub cwd: Option<PathBuf>,
+}
+
+impl HistoryCommand {
+    fn plain(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            timestamp: None,
+            duration: None,
+            cwd: None,
+        }
+    }
+}
+
+impl From<HistoryCommand> for HistoryEntry {
+    fn from(command: HistoryCommand) -> Self {
+        Self {
+            display: command.command,
+            pasted_contents: HashMap::new(),
+            timestamp: command.timestamp.map(|t| t.timestamp_millis()).unwrap_or(0),
+            project: command.cwd.unwrap_or_default(),
+            session_id: None,
+            duration_ms: command.duration.map(|d| d.as_millis() as u64),
+        }
+    }
+}
+
+/// Parse a single history line (Claude JSONL, zsh extended, or plain).
+///
+/// Returns None for blank lines.
+pub fn parse_history_line(line: &str) -> Option<HistoryCommand> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    if line.starts_with('{')
+        && let Ok(entry) = serde_json::from_str::<HistoryEntry>(line)
+    {
+        return Some(HistoryCommand {
+            command: entry.display,
+            timestamp: DateTime::from_timestamp_millis(entry.timestamp),
+            duration: entry.duration_ms.map(Duration::from_millis),
+            cwd: Some(entry.project),
+        });
+    }
+
+    Some(parse_zsh_extended(line).unwrap_or_else(|| HistoryCommand::plain(line)))
+}
+
+/// Parse a zsh extended history line: `: <epoch>:<duration>;<command>`.
+fn parse_zsh_extended(line: &str) -> Option<HistoryCommand> {
+    let rest = line.strip_prefix(": ")?;
+    let (epoch, rest) = rest.split_once(':')?;
+    let (duration, command) = rest.split_once(';')?;
+    let epoch: i64 = epoch.trim().parse().ok()?;
+    let duration: u64 = duration.trim().parse().ok()?;
+
+    Some(HistoryCommand {
+        command: command.to_string(),
+        timestamp: DateTime::from_timestamp(epoch, 0),
+        duration: Some(Duration::from_secs(duration)),
+        cwd: None,
+    })
+}
+
+/// The command of a fish `- cmd:` line.
+fn fish_command(cmd: &str) -> HistoryCommand {
+    // Fish escapes newlines and backslashes in commands
+    let command = cmd.trim_end_matches(['\r', '\n']).replace("\\n", "\n").replace("\\\\", "\\");
+    HistoryCommand::plain(&command)
+}
+
+/// The timestamp of a fish record's indented `when:` line.
+fn fish_when(line: &str) -> Option<Option<DateTime<Utc>>> {
+    let when = line.trim_start().strip_prefix("when: ")?;
+    Some(when.trim().parse().ok().and_then(|t| DateTime::from_timestamp(t, 0)))
+}
+
+/// A history entry tagged with the file it was read from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourcedHistoryEntry {
+    pub source: PathBuf,
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+}
+
+/// How far a history file has been read, and in which format.
+#[derive(Debug, Clone, Copy)]
+struct FileCursor {
+    /// Bytes already consumed
+    offset: u64,
+    /// Whether the file is fish history
+    fish: bool,
+}
+
+/// Watches and reads one or more history files.
 pub struct HistoryWatcher {
     entries: Arc<RwLock<Vec<HistoryEntry>>>,
-    history_path: PathBuf,
+    history_paths: Vec<PathBuf>,
+    /// Read position in each file
+    cursors: Mutex<HashMap<PathBuf, FileCursor>>,
+    /// Last command emitted by `poll`, for de-duplicating across sources
+    last_display: Mutex<Option<String>>,
 }
 
 impl HistoryWatcher {
-    /// Create a new history watcher.
+    /// Create a new history watcher for ~/.claude/history.jsonl.
     pub fn new() -> Result<Self> {
         let history_path = dirs::home_dir()
             .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No home directory"))?
             .join(".claude")
             .join("history.jsonl");
 
-        let entries = Arc::new(RwLock::new(Vec::new()));
+        Self::with_paths(vec![history_path])
+    }
 
+    /// Create a watcher over several history files. Files that don't exist yet
+    /// are picked up once they appear.
+    pub fn with_paths(history_paths: Vec<PathBuf>) -> Result<Self> {
         let watcher = Self {
-            entries,
-            history_path,
+            entries: Arc::new(RwLock::new(Vec::new())),
+            history_paths,
+            cursors: Mutex::new(HashMap::new()),
+            last_display: Mutex::new(None),
         };
 
         // Initial load
@@ -49,31 +176,130 @@ impl HistoryWatcher {
 
     /// Reload history from disk.
     pub fn reload(&self) -> Result<()> {
-        if !self.history_path.exists() {
-            return Ok(());
-        }
-
-        let file = std::fs::File::open(&self.history_path)?;
-        let reader = BufReader::new(file);
         let mut new_entries = Vec::new();
+        let mut cursors = HashMap::new();
 
-        for line in reader.lines() {
-            let line = line?;
-            if line.trim().is_empty() {
+        for path in &self.history_paths {
+            if !path.exists() {
                 continue;
             }
-            if let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line) {
-                new_entries.push(entry);
-            }
+            // Until its first line is complete the format can't be told
+            let Some(fish) = is_fish_history(path) else {
+                continue;
+            };
+
+            let file = std::fs::File::open(path)?;
+            let (entries, consumed) = read_complete_entries(&mut BufReader::new(file), fish)?;
+            new_entries.extend(entries);
+            cursors.insert(path.clone(), FileCursor { offset: consumed, fish });
         }
 
         // Sort by timestamp descending
-        new_entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        new_entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
 
         *self.entries.write().unwrap() = new_entries;
+        *self.cursors.lock().unwrap() = cursors;
         Ok(())
     }
 
+    /// Read entries appended to any history file since the last reload/poll.
+    ///
+    /// Returns a unified stream: entries found in this poll are ordered by
+    /// timestamp, and a command identical to the previously emitted one (from
+    /// any source) is dropped.
+    pub fn poll(&self) -> Result<Vec<SourcedHistoryEntry>> {
+        let mut appended = Vec::new();
+
+        {
+            let mut cursors = self.cursors.lock().unwrap();
+            for path in &self.history_paths {
+                let len = match std::fs::metadata(path) {
+                    Ok(meta) => meta.len(),
+                    Err(_) => continue,
+                };
+
+                // A new file, or one truncated or replaced, is read from the
+                // start and its format detected again
+                let cursor = match cursors.get(path) {
+                    Some(cursor) if cursor.offset <= len => *cursor,
+                    _ => match is_fish_history(path) {
+                        Some(fish) => FileCursor { offset: 0, fish },
+                        None => continue,
+                    },
+                };
+                if len == cursor.offset {
+                    continue;
+                }
+
+                let mut file = std::fs::File::open(path)?;
+                file.seek(SeekFrom::Start(cursor.offset))?;
+                let (entries, consumed) = read_complete_entries(&mut BufReader::new(file), cursor.fish)?;
+                cursors.insert(path.clone(), FileCursor {
+                    offset: cursor.offset + consumed,
+                    ..cursor
+                });
+
+                appended.extend(entries.into_iter().map(|entry| SourcedHistoryEntry {
+                    source: path.clone(),
+                    entry,
+                }));
+            }
+        }
+
+        appended.sort_by_key(|e| e.entry.timestamp);
+
+        let mut last_display = self.last_display.lock().unwrap();
+        appended.retain(|e| {
+            if last_display.as_deref() == Some(e.entry.display.as_str()) {
+                return false;
+            }
+            *last_display = Some(e.entry.display.clone());
+            true
+        });
+        drop(last_display);
+
+        if !appended.is_empty() {
+            let mut entries = self.entries.write().unwrap();
+            entries.extend(appended.iter().map(|e| e.entry.clone()));
+            entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        }
+
+        Ok(appended)
+    }
+
+    /// Poll all history files on an interval and stream new entries.
+    ///
+    /// The background task stops when the receiver is dropped.
+    pub fn spawn_stream(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> mpsc::UnboundedReceiver<SourcedHistoryEntry> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watcher = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if tx.is_closed() {
+                    break;
+                }
+                match watcher.poll() {
+                    Ok(entries) => {
+                        for entry in entries {
+                            if tx.send(entry).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => warn!(target: "clauset::history", "Failed to poll history files: {}", e),
+                }
+            }
+        });
+
+        rx
+    }
+
     /// Get recent history entries.
     pub fn get_entries(&self, limit: Option<usize>) -> Vec<HistoryEntry> {
         let entries = self.entries.read().unwrap();
@@ -83,6 +309,28 @@ impl HistoryWatcher {
         }
     }
 
+    /// Get entries with a timestamp in `[since, until)` (Unix milliseconds).
+    ///
+    /// Entries without a timestamp are excluded whenever a bound is given.
+    pub fn get_in_range(
+        &self,
+        since: Option<i64>,
+        until: Option<i64>,
+        limit: Option<usize>,
+    ) -> Vec<HistoryEntry> {
+        let entries = self.entries.read().unwrap();
+        let bounded = since.is_some() || until.is_some();
+        let matching = entries.iter().filter(|e| {
+            !(bounded && e.timestamp == 0)
+                && since.is_none_or(|s| e.timestamp >= s)
+                && until.is_none_or(|u| e.timestamp < u)
+        });
+        match limit {
+            Some(n) => matching.take(n).cloned().collect(),
+            None => matching.cloned().collect(),
+        }
+    }
+
     /// Get entries for a specific session.
     pub fn get_by_session(&self, session_id: Uuid) -> Vec<HistoryEntry> {
         self.entries
@@ -110,7 +358,325 @@ impl Default for HistoryWatcher {
     fn default() -> Self {
         Self::new().unwrap_or_else(|_| Self {
             entries: Arc::new(RwLock::new(Vec::new())),
-            history_path: PathBuf::new(),
+            history_paths: Vec::new(),
+            cursors: Mutex::new(HashMap::new()),
+            last_display: Mutex::new(None),
         })
     }
 }
+
+/// Whether a history file is in fish's format, judged by its first line.
+///
+/// None while the file has no complete first line.
+fn is_fish_history(path: &std::path::Path) -> Option<bool> {
+    let file = std::fs::File::open(path).ok()?;
+    let (first, _) = read_complete_line(&mut BufReader::new(file)).ok()??;
+    Some(first.starts_with("- cmd: "))
+}
+
+/// Read the next newline-terminated line, decoding it lossily. Returns the
+/// line and its length in bytes, or None at the end of the input or of its
+/// complete lines.
+fn read_complete_line(reader: &mut impl BufRead) -> std::io::Result<Option<(String, u64)>> {
+    let mut bytes = Vec::new();
+    let read = reader.read_until(b'\n', &mut bytes)?;
+    if read == 0 || !bytes.ends_with(b"\n") {
+        return Ok(None);
+    }
+    Ok(Some((String::from_utf8_lossy(&bytes).into_owned(), read as u64)))
+}
+
+/// Parse complete entries from a reader, as fish records with `fish` and
+/// one entry per line otherwise.
+///
+/// Input that may still be being written is left unconsumed, to be read again
+/// once complete. Returns the parsed entries and the number of bytes consumed.
+fn read_complete_entries(reader: &mut impl BufRead, fish: bool) -> Result<(Vec<HistoryEntry>, u64)> {
+    if fish {
+        read_fish_records(reader)
+    } else {
+        read_complete_lines(reader)
+    }
+}
+
+/// Parse newline-terminated history lines. A trailing line without a newline
+/// is left unconsumed.
+fn read_complete_lines(reader: &mut impl BufRead) -> Result<(Vec<HistoryEntry>, u64)> {
+    let mut entries = Vec::new();
+    let mut consumed = 0u64;
+
+    while let Some((line, read)) = read_complete_line(reader)? {
+        consumed += read;
+
+        if line.starts_with('{') {
+            // A JSONL line this version can't read is not a shell command
+            match serde_json::from_str::<HistoryEntry>(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => debug!(target: "clauset::history", "Skipping unreadable history line: {}", e),
+            }
+        } else if let Some(command) = parse_history_line(&line) {
+            entries.push(command.into());
+        }
+    }
+
+    Ok((entries, consumed))
+}
+
+/// Parse fish records. A record is complete once its `when:` line (or the
+/// next record) has been written; an incomplete one at the end is left
+/// unconsumed, so its timestamp is picked up by a later read.
+fn read_fish_records(reader: &mut impl BufRead) -> Result<(Vec<HistoryEntry>, u64)> {
+    let mut entries = Vec::new();
+    let mut offset = 0u64;
+    let mut consumed = 0u64;
+    let mut pending: Option<HistoryCommand> = None;
+
+    while let Some((line, read)) = read_complete_line(reader)? {
+        if let Some(cmd) = line.strip_prefix("- cmd: ") {
+            // A record without a `when:` ends where the next one starts
+            if let Some(command) = pending.take() {
+                entries.push(command.into());
+                consumed = offset;
+            }
+            pending = Some(fish_command(cmd));
+        } else if let Some(when) = fish_when(&line)
+            && let Some(mut command) = pending.take()
+        {
+            command.timestamp = when;
+            entries.push(command.into());
+            consumed = offset + read;
+        } else if pending.is_none() {
+            // `paths:` and other lines trailing a complete record
+            consumed = offset + read;
+        }
+        offset += read;
+    }
+
+    Ok((entries, consumed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn append(path: &std::path::Path, display: &str, timestamp: i64) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        let entry = serde_json::json!({
+            "display": display,
+            "timestamp": timestamp,
+            "project": "/tmp/project",
+        });
+        writeln!(file, "{}", entry).unwrap();
+    }
+
+    fn summary(entries: &[SourcedHistoryEntry]) -> Vec<(PathBuf, String)> {
+        entries
+            .iter()
+            .map(|e| (e.source.clone(), e.entry.display.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_poll_merges_multiple_files() {
+        let temp = TempDir::new().unwrap();
+        let first = temp.path().join("first.jsonl");
+        let second = temp.path().join("second.jsonl");
+        append(&first, "already there", 1);
+
+        // `second` does not exist yet
+        let watcher = HistoryWatcher::with_paths(vec![first.clone(), second.clone()]).unwrap();
+        assert_eq!(watcher.get_entries(None).len(), 1);
+        assert!(watcher.poll().unwrap().is_empty());
+
+        append(&first, "cargo build", 10);
+        assert_eq!(summary(&watcher.poll().unwrap()), vec![(first.clone(), "cargo build".to_string())]);
+
+        append(&second, "git status", 20);
+        append(&first, "cargo test", 30);
+        assert_eq!(
+            summary(&watcher.poll().unwrap()),
+            vec![
+                (second.clone(), "git status".to_string()),
+                (first.clone(), "cargo test".to_string()),
+            ]
+        );
+
+        // The same command run in another shell right after is collapsed
+        append(&second, "cargo test", 31);
+        append(&second, "cargo clippy", 40);
+        assert_eq!(summary(&watcher.poll().unwrap()), vec![(second.clone(), "cargo clippy".to_string())]);
+
+        let all: Vec<_> = watcher.get_entries(None).into_iter().map(|e| e.display).collect();
+        assert_eq!(all, vec!["cargo clippy", "cargo test", "git status", "cargo build", "already there"]);
+    }
+
+    #[test]
+    fn test_poll_skips_partial_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("history.jsonl");
+        let watcher = HistoryWatcher::with_paths(vec![path.clone()]).unwrap();
+
+        let mut file = std::fs::File::create(&path).unwrap();
+        write!(file, r#"{{"display":"half","timestamp":1,"project":"/tmp"}}"#).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+
+        writeln!(file).unwrap();
+        assert_eq!(watcher.poll().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_zsh_extended_line() {
+        let parsed = parse_history_line(": 1700000000:12;cargo test --workspace\n").unwrap();
+        assert_eq!(parsed.command, "cargo test --workspace");
+        assert_eq!(parsed.timestamp, DateTime::from_timestamp(1_700_000_000, 0));
+        assert_eq!(parsed.duration, Some(Duration::from_secs(12)));
+        assert_eq!(parsed.cwd, None);
+
+        let entry: HistoryEntry = parsed.into();
+        assert_eq!(entry.timestamp, 1_700_000_000_000);
+        assert_eq!(entry.duration_ms, Some(12_000));
+    }
+
+    #[test]
+    fn test_parse_plain_line() {
+        let parsed = parse_history_line("git log --oneline").unwrap();
+        assert_eq!(parsed, HistoryCommand::plain("git log --oneline"));
+        // Looks like zsh but isn't
+        let parsed = parse_history_line(": not-a-time:0;ls").unwrap();
+        assert_eq!(parsed.command, ": not-a-time:0;ls");
+        assert!(parsed.timestamp.is_none());
+        assert!(parse_history_line("   ").is_none());
+    }
+
+    #[test]
+    fn test_parse_claude_line() {
+        let parsed = parse_history_line(
+            r#"{"display":"fix the bug","timestamp":1700000000123,"project":"/work/app"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.command, "fix the bug");
+        assert_eq!(parsed.timestamp.unwrap().timestamp_millis(), 1_700_000_000_123);
+        assert_eq!(parsed.cwd, Some(PathBuf::from("/work/app")));
+    }
+
+    #[test]
+    fn test_parse_fish_history() {
+        let content = "- cmd: ls -la\n  when: 1700000000\n- cmd: echo a\\nb\n  when: 1700000100\n  paths:\n    - b\n";
+        let (entries, consumed) = read_fish_records(&mut content.as_bytes()).unwrap();
+        assert_eq!(consumed, content.len() as u64);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].display, "ls -la");
+        assert_eq!(entries[0].timestamp, 1_700_000_000_000);
+        assert_eq!(entries[1].display, "echo a\nb");
+        assert_eq!(entries[1].timestamp, 1_700_000_100_000);
+    }
+
+    #[test]
+    fn test_watcher_reads_zsh_history() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".zsh_history");
+        std::fs::write(&path, ": 1700000000:0;ls\n: 1700000500:3;make\nplain command\n").unwrap();
+
+        let watcher = HistoryWatcher::with_paths(vec![path]).unwrap();
+        let entries = watcher.get_entries(None);
+        let displays: Vec<_> = entries.iter().map(|e| e.display.as_str()).collect();
+        assert_eq!(displays, vec!["make", "ls", "plain command"]);
+        assert_eq!(entries[0].duration_ms, Some(3000));
+        assert_eq!(entries[2].timestamp, 0);
+
+        let ranged = watcher.get_in_range(Some(1_700_000_000_000), Some(1_700_000_500_000), None);
+        assert_eq!(ranged.len(), 1);
+        assert_eq!(ranged[0].display, "ls");
+        assert_eq!(watcher.get_in_range(None, None, Some(2)).len(), 2);
+    }
+
+    #[test]
+    fn test_watcher_reads_fish_history() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("fish_history");
+        std::fs::write(&path, "- cmd: ls -la\n  when: 1700000000\n- cmd: cat notes\n  when: 1700000100\n  paths:\n    - notes\n").unwrap();
+
+        let watcher = HistoryWatcher::with_paths(vec![path.clone()]).unwrap();
+        let displays: Vec<_> = watcher.get_entries(None).into_iter().map(|e| e.display).collect();
+        // The YAML `when:`/`paths:` lines are part of the records, not commands
+        assert_eq!(displays, vec!["cat notes", "ls -la"]);
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "- cmd: make\n  when: 1700000200\n").unwrap();
+        let appended = watcher.poll().unwrap();
+        assert_eq!(appended.len(), 1);
+        assert_eq!(appended[0].entry.display, "make");
+        assert_eq!(appended[0].entry.timestamp, 1_700_000_200_000);
+
+        // A record whose `when:` line lands in a later poll keeps its timestamp
+        writeln!(file, "- cmd: make test").unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+        writeln!(file, "  when: 1700000300").unwrap();
+        let appended = watcher.poll().unwrap();
+        assert_eq!(appended.len(), 1);
+        assert_eq!(appended[0].entry.display, "make test");
+        assert_eq!(appended[0].entry.timestamp, 1_700_000_300_000);
+        assert!(watcher.poll().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_poll_skips_unreadable_json_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("history.jsonl");
+        let watcher = HistoryWatcher::with_paths(vec![path.clone()]).unwrap();
+
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, r#"{{"text":"a newer schema","when":1}}"#).unwrap();
+        writeln!(file, r#"{{"display":"cargo test","timestamp":2,"project":"/tmp"}}"#).unwrap();
+        let appended = watcher.poll().unwrap();
+        assert_eq!(appended.len(), 1);
+        assert_eq!(appended[0].entry.display, "cargo test");
+    }
+
+    #[test]
+    fn test_watcher_reads_metafied_zsh_history() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".zsh_history");
+        // zsh metafies the 0x94 in "—" (E2 80 94) as 83 B4, which isn't UTF-8
+        let mut history = b": 1700000000:0;echo a\xe2\x80\x83\xb4b\n".to_vec();
+        history.extend_from_slice(b": 1700000100:0;ls\n");
+        std::fs::write(&path, &history).unwrap();
+
+        let watcher = HistoryWatcher::with_paths(vec![path.clone()]).unwrap();
+        let displays: Vec<_> = watcher.get_entries(None).into_iter().map(|e| e.display).collect();
+        assert_eq!(displays.len(), 2);
+        assert_eq!(displays[0], "ls");
+        assert!(displays[1].starts_with("echo a"));
+
+        // Polling moves past such lines too
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b": 1700000200:0;cd \x83\xa0\n: 1700000300:0;make\n").unwrap();
+        let appended: Vec<_> = watcher.poll().unwrap().into_iter().map(|e| e.entry.display).collect();
+        assert_eq!(appended.len(), 2);
+        assert_eq!(appended[1], "make");
+        assert!(watcher.poll().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_emits_appended_entries() {
+        let temp = TempDir::new().unwrap();
+        let first = temp.path().join("first.jsonl");
+        let second = temp.path().join("second.jsonl");
+        let watcher = Arc::new(HistoryWatcher::with_paths(vec![first.clone(), second.clone()]).unwrap());
+        let mut rx = watcher.spawn_stream(Duration::from_millis(10));
+
+        append(&first, "ls", 1);
+        let entry = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await.unwrap().unwrap();
+        assert_eq!((entry.source, entry.entry.display), (first, "ls".to_string()));
+
+        append(&second, "pwd", 2);
+        let entry = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await.unwrap().unwrap();
+        assert_eq!((entry.source, entry.entry.display), (second, "pwd".to_string()));
+    }
+}
@@ -3,23 +3,40 @@
 //! This module provides the database layer for the interaction tracking system.
 //! It uses the same SQLite database as SessionStore but manages separate tables.
 
-use crate::diff::FileDiff;
+use crate::diff::{detect_language, DiffCache, DiffChangeType, DiffCacheKey, DiffOptions, FileDiff};
 use crate::{ClausetError, Result};
 use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use clauset_types::{
-    FileChange, FileChangeType, FileSnapshot, Interaction, InteractionStatus, InteractionSummary,
-    SnapshotType, ToolInvocation,
+    FileChange, FileChangeType, FileSnapshot, HookEventPayload, Interaction, InteractionContext,
+    InteractionStatus, InteractionSummary, SnapshotType, TimelineEntry, ToolInvocation, TurnOutcome,
 };
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Batch, Connection, OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 /// Maximum file size for snapshots (1 MB).
 pub const MAX_SNAPSHOT_SIZE: u64 = 1_048_576;
 
+/// Characters of the first prompt kept in a generated session summary.
+const SUMMARY_PROMPT_CHARS: usize = 60;
+
+/// Maximum length of an assistant summary backfilled from a transcript.
+const SUMMARY_RESPONSE_CHARS: usize = 200;
+
+/// How long after an interaction starts a same-prompt interaction may still
+/// be merged into it as a duplicate.
+const DUPLICATE_INTERACTION_WINDOW: chrono::Duration = chrono::Duration::seconds(60);
+
 /// A file change with its computed diff.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileChangeWithDiff {
@@ -71,6 +88,89 @@ pub struct FilePathMatch {
     pub snapshot_count: u32,
 }
 
+/// A file touched anywhere in the store, across all sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalFileEntry {
+    pub file_path: PathBuf,
+    /// Snapshots of this file across all interactions.
+    pub snapshot_count: u32,
+    /// Distinct sessions that touched this file.
+    pub session_count: u32,
+    /// When the most recent snapshot was taken.
+    pub last_modified_at: DateTime<Utc>,
+}
+
+/// Rows returned by an ad-hoc read-only query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// Whether rows beyond the requested maximum were dropped.
+    pub truncated: bool,
+}
+
+/// A file snapshot referencing a stored content blob.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentRef {
+    /// The referencing snapshot.
+    pub snapshot_id: Uuid,
+    /// Interaction the snapshot belongs to.
+    pub interaction_id: Uuid,
+    /// Session the interaction belongs to.
+    pub session_id: Uuid,
+    /// Path of the snapshotted file.
+    pub file_path: PathBuf,
+    /// Whether this is a before or after snapshot.
+    pub snapshot_type: SnapshotType,
+    /// When the snapshot was taken.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Per-category result limits for [`InteractionStore::global_search`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalSearchOptions {
+    pub interaction_limit: usize,
+    pub tool_limit: usize,
+    pub file_limit: usize,
+}
+
+impl GlobalSearchOptions {
+    /// The same limit for every category.
+    pub fn uniform(limit: usize) -> Self {
+        Self {
+            interaction_limit: limit,
+            tool_limit: limit,
+            file_limit: limit,
+        }
+    }
+}
+
+impl Default for GlobalSearchOptions {
+    fn default() -> Self {
+        Self::uniform(50)
+    }
+}
+
+/// Result category of a global search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlobalSearchKind {
+    Interaction,
+    ToolInvocation,
+    File,
+}
+
+/// Position of one result in the merged, relevance-ordered view of a
+/// global search.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RankedMatch {
+    pub kind: GlobalSearchKind,
+    /// Index into the category's result list.
+    pub index: usize,
+    /// Merged relevance (higher = more relevant).
+    pub score: f64,
+}
+
 /// Results from a global search.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalSearchResults {
@@ -80,6 +180,103 @@ pub struct GlobalSearchResults {
     pub tool_invocations: Vec<ToolInvocation>,
     /// Matching file paths.
     pub file_matches: Vec<FilePathMatch>,
+    /// Every result across categories, most relevant first.
+    #[serde(default)]
+    pub ranking: Vec<RankedMatch>,
+}
+
+impl GlobalSearchResults {
+    /// Results grouped by category, with `ranking` left empty.
+    pub fn grouped(
+        interactions: Vec<SearchResult>,
+        tool_invocations: Vec<ToolInvocation>,
+        file_matches: Vec<FilePathMatch>,
+    ) -> Self {
+        Self {
+            interactions,
+            tool_invocations,
+            file_matches,
+            ranking: Vec::new(),
+        }
+    }
+
+    /// Merge the categories by reciprocal rank into `ranking`.
+    ///
+    /// Scores from different FTS indexes aren't comparable (and file path
+    /// matches have none), so each result scores `1 / (k + rank)` within its
+    /// category. Equal ranks interleave as interaction, tool, file.
+    fn rank(&mut self) {
+        let ranked = |kind: GlobalSearchKind, len: usize| {
+            (0..len).map(move |index| RankedMatch {
+                kind,
+                index,
+                score: 1.0 / (RANK_FUSION_K + index as f64 + 1.0),
+            })
+        };
+        let mut ranking: Vec<RankedMatch> = ranked(GlobalSearchKind::Interaction, self.interactions.len())
+            .chain(ranked(GlobalSearchKind::ToolInvocation, self.tool_invocations.len()))
+            .chain(ranked(GlobalSearchKind::File, self.file_matches.len()))
+            .collect();
+        ranking.sort_by(|a, b| b.score.total_cmp(&a.score));
+        self.ranking = ranking;
+    }
+
+    /// The results in `ranking` order.
+    pub fn merged(&self) -> Vec<GlobalSearchItem> {
+        self.ranking
+            .iter()
+            .map(|ranked| match ranked.kind {
+                GlobalSearchKind::Interaction => GlobalSearchItem::Interaction(self.interactions[ranked.index].clone()),
+                GlobalSearchKind::ToolInvocation => {
+                    GlobalSearchItem::ToolInvocation(self.tool_invocations[ranked.index].clone())
+                }
+                GlobalSearchKind::File => GlobalSearchItem::File(self.file_matches[ranked.index].clone()),
+            })
+            .collect()
+    }
+}
+
+/// Smoothing constant for reciprocal rank fusion of search categories.
+const RANK_FUSION_K: f64 = 60.0;
+
+/// A single result from a streaming global search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GlobalSearchItem {
+    Interaction(SearchResult),
+    ToolInvocation(ToolInvocation),
+    File(FilePathMatch),
+}
+
+/// Session usage when a hook event arrived, replayed alongside the event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct HookUsage {
+    pub cost_usd: f64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub thinking_tokens: u64,
+    pub context_percent: u8,
+}
+
+/// A hook event from the session's hook log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedHookEvent {
+    /// Position in the log (increases with arrival order).
+    pub sequence: i64,
+    pub payload: HookEventPayload,
+    pub usage: HookUsage,
+    pub received_at: DateTime<Utc>,
+}
+
+/// A hook event with a name Clauset doesn't recognize (from a user's custom
+/// hook), kept verbatim so custom automation stays observable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomHookEvent {
+    pub id: i64,
+    pub session_id: Uuid,
+    pub event_name: String,
+    pub payload: serde_json::Value,
+    pub received_at: DateTime<Utc>,
 }
 
 /// Analytics for a single session.
@@ -95,6 +292,8 @@ pub struct SessionAnalytics {
     pub total_input_tokens: u64,
     /// Total output tokens.
     pub total_output_tokens: u64,
+    /// Total extended thinking tokens (estimated from thinking blocks).
+    pub total_thinking_tokens: u64,
     /// First interaction timestamp.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub first_interaction_at: Option<DateTime<Utc>>,
@@ -103,6 +302,26 @@ pub struct SessionAnalytics {
     pub last_interaction_at: Option<DateTime<Utc>>,
 }
 
+/// Pace of work in a session over its active span, from the first
+/// interaction's start to the last one's end.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionVelocity {
+    /// Session ID.
+    pub session_id: Uuid,
+    /// Number of interactions.
+    pub interaction_count: u32,
+    /// File changes, counting each file once per interaction.
+    pub files_changed: u32,
+    /// Length of the active span in seconds.
+    pub active_span_secs: f64,
+    /// Interactions per hour; `None` when the span is empty.
+    pub interactions_per_hour: Option<f64>,
+    /// File changes per hour; `None` when the span is empty.
+    pub files_changed_per_hour: Option<f64>,
+    /// Mean duration of finished interactions; `None` if none has finished.
+    pub avg_turn_duration_secs: Option<f64>,
+}
+
 /// Daily cost breakdown entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailyCostEntry {
@@ -118,6 +337,56 @@ pub struct DailyCostEntry {
     pub output_tokens: u64,
 }
 
+/// Bucket size for cost timeseries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Hour,
+    #[default]
+    Day,
+    /// ISO weeks, starting on Monday.
+    Week,
+    Month,
+}
+
+impl Granularity {
+    /// SQLite expression mapping `started_at` to the UTC start of its bucket.
+    fn bucket_start_sql(self) -> &'static str {
+        match self {
+            Granularity::Hour => "strftime('%Y-%m-%dT%H:00:00Z', started_at)",
+            Granularity::Day => "strftime('%Y-%m-%dT00:00:00Z', started_at)",
+            Granularity::Week => "strftime('%Y-%m-%dT00:00:00Z', started_at, 'weekday 0', '-6 days')",
+            Granularity::Month => "strftime('%Y-%m-01T00:00:00Z', started_at)",
+        }
+    }
+}
+
+/// Number of calls to one tool within one time bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolBucket {
+    /// Start of the bucket (UTC).
+    pub bucket_start: DateTime<Utc>,
+    /// Tool name.
+    pub tool_name: String,
+    /// Invocations of the tool started in the bucket.
+    pub count: u32,
+}
+
+/// Aggregated cost for one timeseries bucket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostBucket {
+    /// Start of the bucket (UTC).
+    pub bucket_start: DateTime<Utc>,
+    /// Number of interactions started in the bucket.
+    pub interaction_count: u32,
+    /// Total cost in USD.
+    pub total_cost_usd: f64,
+    /// Total input tokens.
+    pub input_tokens: u64,
+    /// Total output tokens.
+    pub output_tokens: u64,
+}
+
 /// Cost breakdown by tool type.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCostEntry {
@@ -130,6 +399,22 @@ pub struct ToolCostEntry {
     pub avg_duration_ms: Option<f64>,
 }
 
+/// Success and failure counts for one tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolReliability {
+    /// Tool name.
+    pub tool_name: String,
+    /// Number of invocations.
+    pub invocation_count: u32,
+    /// Number of invocations that returned an error.
+    pub error_count: u32,
+    /// `error_count / invocation_count`, from 0.0 to 1.0.
+    pub error_rate: f64,
+    /// Average duration in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_duration_ms: Option<f64>,
+}
+
 /// Overall analytics summary.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalyticsSummary {
@@ -143,6 +428,8 @@ pub struct AnalyticsSummary {
     pub total_input_tokens: u64,
     /// Total output tokens.
     pub total_output_tokens: u64,
+    /// Total extended thinking tokens (estimated from thinking blocks).
+    pub total_thinking_tokens: u64,
     /// Average cost per interaction.
     pub avg_cost_per_interaction: f64,
     /// Total tool invocations.
@@ -151,12 +438,144 @@ pub struct AnalyticsSummary {
     pub total_file_changes: u32,
 }
 
+/// Change in one metric between two analytics summaries.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricDelta {
+    /// Value in the baseline period
+    pub a: f64,
+    /// Value in the compared period
+    pub b: f64,
+    /// `b - a`
+    pub absolute: f64,
+    /// Change relative to `a`, in percent; None when `a` is zero
+    pub percent: Option<f64>,
+}
+
+impl MetricDelta {
+    fn new(a: f64, b: f64) -> Self {
+        Self {
+            a,
+            b,
+            absolute: b - a,
+            percent: (a != 0.0).then(|| (b - a) / a * 100.0),
+        }
+    }
+}
+
+/// Per-metric changes from one analytics summary (period A) to another (period B).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalyticsDelta {
+    pub cost_usd: MetricDelta,
+    pub interactions: MetricDelta,
+    pub input_tokens: MetricDelta,
+    pub output_tokens: MetricDelta,
+    pub tool_invocations: MetricDelta,
+}
+
+impl AnalyticsDelta {
+    /// Compare `b` against the baseline `a`.
+    pub fn between(a: &AnalyticsSummary, b: &AnalyticsSummary) -> Self {
+        Self {
+            cost_usd: MetricDelta::new(a.total_cost_usd, b.total_cost_usd),
+            interactions: MetricDelta::new(a.interaction_count as f64, b.interaction_count as f64),
+            input_tokens: MetricDelta::new(a.total_input_tokens as f64, b.total_input_tokens as f64),
+            output_tokens: MetricDelta::new(a.total_output_tokens as f64, b.total_output_tokens as f64),
+            tool_invocations: MetricDelta::new(
+                a.total_tool_invocations as f64,
+                b.total_tool_invocations as f64,
+            ),
+        }
+    }
+}
+
 /// Default retention period in days.
 pub const DEFAULT_RETENTION_DAYS: i64 = 30;
 
+/// `@path` references in a prompt: an `@` at the start or after whitespace.
+static FILE_REF_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|\s)@([^\s@]+)").unwrap());
+
+/// File paths referenced with `@` in a prompt, in order of first mention.
+///
+/// Only references that look like paths (containing `/` or `.`) count, so
+/// `@someone` mentions are ignored; trailing punctuation is dropped.
+pub fn extract_file_refs(prompt: &str) -> Vec<String> {
+    let mut refs: Vec<String> = Vec::new();
+    for caps in FILE_REF_RE.captures_iter(prompt) {
+        let path = caps[1].trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"', '`']);
+        if (path.contains('/') || path.contains('.')) && !refs.iter().any(|r| r == path) {
+            refs.push(path.to_string());
+        }
+    }
+    refs
+}
+
+/// Read-only connections opened alongside the writer.
+const READER_POOL_SIZE: usize = 4;
+
+/// How long a connection waits on a locked database before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// VM instructions between deadline checks while an ad-hoc query runs.
+const QUERY_PROGRESS_OPS: i32 = 10_000;
+
+/// Computed diffs kept in memory for repeat viewing.
+const DIFF_CACHE_CAPACITY: usize = 256;
+
+/// Bounds on full-text search queries, so pathological input can't build a
+/// huge FTS5 `MATCH` expression. Anything past either limit is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FtsQueryLimits {
+    /// Search terms kept from a query
+    pub max_tokens: usize,
+    /// Characters of search terms kept from a query
+    pub max_chars: usize,
+}
+
+impl Default for FtsQueryLimits {
+    fn default() -> Self {
+        Self {
+            max_tokens: 32,
+            max_chars: 512,
+        }
+    }
+}
+
+/// Size cap on stored tool inputs. A larger input is saved with its longest
+/// string fields cut down (marked with how much was dropped), so the row and
+/// its search index stay small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ToolInputLimits {
+    /// Largest serialized tool input stored as-is, in bytes
+    pub max_bytes: usize,
+    /// Also keep the untruncated input as a compressed content blob
+    pub store_full: bool,
+}
+
+impl Default for ToolInputLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 256 * 1024,
+            store_full: true,
+        }
+    }
+}
+
 /// SQLite-based store for interaction tracking.
+///
+/// Writes go through a single connection. Searches, listings and analytics
+/// use a small pool of read-only connections so that, with WAL, a slow query
+/// doesn't hold up inserts from hook processing.
 pub struct InteractionStore {
     conn: Mutex<Connection>,
+    /// Read-only connections; empty when wrapping an existing connection,
+    /// in which case reads share the writer
+    readers: Vec<Mutex<Connection>>,
+    next_reader: AtomicUsize,
+    diff_cache: DiffCache,
+    fts_query_limits: FtsQueryLimits,
+    tool_input_limits: ToolInputLimits,
 }
 
 impl InteractionStore {
@@ -170,11 +589,29 @@ impl InteractionStore {
         }
 
         let conn = Connection::open(path)?;
-        let store = Self {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // WAL lets the readers run alongside the writer
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        let mut store = Self {
             conn: Mutex::new(conn),
+            readers: Vec::new(),
+            next_reader: AtomicUsize::new(0),
+            diff_cache: DiffCache::new(DIFF_CACHE_CAPACITY),
+            fts_query_limits: FtsQueryLimits::default(),
+            tool_input_limits: ToolInputLimits::default(),
         };
         store.init_schema()?;
         store.migrate()?;
+
+        // Opened after the schema exists, since they can't create it
+        for _ in 0..READER_POOL_SIZE {
+            let reader = Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+            )?;
+            reader.busy_timeout(BUSY_TIMEOUT)?;
+            store.readers.push(Mutex::new(reader));
+        }
         Ok(store)
     }
 
@@ -184,12 +621,41 @@ impl InteractionStore {
     pub fn from_connection(conn: Connection) -> Result<Self> {
         let store = Self {
             conn: Mutex::new(conn),
+            readers: Vec::new(),
+            next_reader: AtomicUsize::new(0),
+            diff_cache: DiffCache::new(DIFF_CACHE_CAPACITY),
+            fts_query_limits: FtsQueryLimits::default(),
+            tool_input_limits: ToolInputLimits::default(),
         };
         store.init_schema()?;
         store.migrate()?;
         Ok(store)
     }
 
+    /// Set the bounds applied to full-text search queries.
+    pub fn with_fts_query_limits(mut self, limits: FtsQueryLimits) -> Self {
+        self.fts_query_limits = limits;
+        self
+    }
+
+    /// Set the size cap applied to stored tool inputs.
+    pub fn with_tool_input_limits(mut self, limits: ToolInputLimits) -> Self {
+        self.tool_input_limits = limits;
+        self
+    }
+
+    /// A connection for read-only queries: the first idle reader, starting
+    /// from a rotating index, or the writer when there is no pool.
+    fn reader(&self) -> MutexGuard<'_, Connection> {
+        if self.readers.is_empty() {
+            return self.conn.lock().unwrap();
+        }
+        let start = self.next_reader.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+        (0..self.readers.len())
+            .find_map(|offset| self.readers[(start + offset) % self.readers.len()].try_lock().ok())
+            .unwrap_or_else(|| self.readers[start].lock().unwrap())
+    }
+
     /// Initialize the schema for interaction tracking tables.
     fn init_schema(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -202,14 +668,18 @@ impl InteractionStore {
                 session_id TEXT NOT NULL,
                 sequence_number INTEGER NOT NULL,
                 user_prompt TEXT NOT NULL,
+                prompt_truncated INTEGER NOT NULL DEFAULT 0,
                 assistant_summary TEXT,
                 started_at TEXT NOT NULL,
                 ended_at TEXT,
                 cost_usd_delta REAL NOT NULL DEFAULT 0.0,
                 input_tokens_delta INTEGER NOT NULL DEFAULT 0,
                 output_tokens_delta INTEGER NOT NULL DEFAULT 0,
+                thinking_tokens_delta INTEGER NOT NULL DEFAULT 0,
+                context_percent INTEGER,
                 status TEXT NOT NULL DEFAULT 'active',
                 error_message TEXT,
+                context TEXT,
                 FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
             );
 
@@ -236,6 +706,8 @@ impl InteractionStore {
                 file_path TEXT,
                 is_error INTEGER NOT NULL DEFAULT 0,
                 error_message TEXT,
+                exit_code INTEGER,
+                tool_input_hash TEXT,
                 started_at TEXT NOT NULL,
                 ended_at TEXT,
                 duration_ms INTEGER,
@@ -310,6 +782,7 @@ impl InteractionStore {
                 is_streaming INTEGER NOT NULL DEFAULT 0,
                 is_complete INTEGER NOT NULL DEFAULT 1,
                 timestamp INTEGER NOT NULL,
+                first_token_at INTEGER,
                 FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
             );
 
@@ -352,7 +825,8 @@ impl InteractionStore {
                 timestamp INTEGER NOT NULL,
                 word_count INTEGER NOT NULL,
                 char_count INTEGER NOT NULL,
-                content_hash TEXT NOT NULL
+                content_hash TEXT NOT NULL,
+                use_count INTEGER NOT NULL DEFAULT 1
             );
 
             CREATE INDEX IF NOT EXISTS idx_prompts_timestamp
@@ -361,6 +835,68 @@ impl InteractionStore {
                 ON prompts(claude_session_id);
             CREATE UNIQUE INDEX IF NOT EXISTS idx_prompts_dedup
                 ON prompts(claude_session_id, content_hash);
+
+            CREATE TABLE IF NOT EXISTS prompt_templates (
+                id TEXT PRIMARY KEY,
+                interaction_id TEXT NOT NULL,
+                template TEXT NOT NULL,
+                slots TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_prompt_templates_interaction
+                ON prompt_templates(interaction_id);
+
+            CREATE TABLE IF NOT EXISTS prompt_file_refs (
+                interaction_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                PRIMARY KEY (interaction_id, path),
+                FOREIGN KEY (interaction_id) REFERENCES interactions(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_prompt_file_refs_path
+                ON prompt_file_refs(path);
+            "#,
+        )?;
+
+        // Create hook_events table (raw hook log for replay)
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS hook_events (
+                sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                event_name TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                cost_usd REAL NOT NULL DEFAULT 0.0,
+                input_tokens INTEGER NOT NULL DEFAULT 0,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                thinking_tokens INTEGER NOT NULL DEFAULT 0,
+                context_percent INTEGER NOT NULL DEFAULT 0,
+                received_at TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_hook_events_session
+                ON hook_events(session_id, sequence);
+            CREATE INDEX IF NOT EXISTS idx_hook_events_received_at
+                ON hook_events(received_at);
+            "#,
+        )?;
+
+        // Create custom_hook_events table (passthrough for unrecognized events)
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS custom_hook_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                event_name TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                received_at TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_custom_hook_events_session
+                ON custom_hook_events(session_id, id);
             "#,
         )?;
 
@@ -371,6 +907,109 @@ impl InteractionStore {
     fn migrate(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
+        // Add thinking token tracking to databases created before it existed
+        let has_thinking_tokens: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('interactions') WHERE name = 'thinking_tokens_delta'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_thinking_tokens {
+            conn.execute(
+                "ALTER TABLE interactions ADD COLUMN thinking_tokens_delta INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
+
+        // Add context usage snapshots to databases created before they existed
+        let has_context_percent: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('interactions') WHERE name = 'context_percent'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_context_percent {
+            conn.execute(
+                "ALTER TABLE interactions ADD COLUMN context_percent INTEGER",
+                [],
+            )?;
+        }
+
+        // Count repeat submissions of library prompts
+        let has_use_count: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('prompts') WHERE name = 'use_count'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_use_count {
+            conn.execute(
+                "ALTER TABLE prompts ADD COLUMN use_count INTEGER NOT NULL DEFAULT 1",
+                [],
+            )?;
+        }
+
+        // Flag prompts stored truncated
+        let has_prompt_truncated: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('interactions') WHERE name = 'prompt_truncated'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_prompt_truncated {
+            conn.execute(
+                "ALTER TABLE interactions ADD COLUMN prompt_truncated INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
+
+        // Session settings captured at interaction start (JSON)
+        let has_context: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('interactions') WHERE name = 'context'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_context {
+            conn.execute("ALTER TABLE interactions ADD COLUMN context TEXT", [])?;
+        }
+
+        // Bash exit codes parsed from tool responses
+        let has_exit_code: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('tool_invocations') WHERE name = 'exit_code'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_exit_code {
+            conn.execute("ALTER TABLE tool_invocations ADD COLUMN exit_code INTEGER", [])?;
+        }
+
+        // Full copies of oversized tool inputs, kept as content blobs
+        let has_tool_input_hash: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('tool_invocations') WHERE name = 'tool_input_hash'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !has_tool_input_hash {
+            conn.execute("ALTER TABLE tool_invocations ADD COLUMN tool_input_hash TEXT", [])?;
+        }
+
         // Check if FTS tables exist and create them if not
         let has_fts: bool = conn
             .query_row(
@@ -387,7 +1026,7 @@ impl InteractionStore {
         // Check if reference count triggers exist
         let has_triggers: bool = conn
             .query_row(
-                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='trigger' AND name='file_snapshots_insert_ref'",
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='trigger' AND name='tool_invocations_insert_ref'",
                 [],
                 |row| row.get(0),
             )
@@ -397,33 +1036,113 @@ impl InteractionStore {
             self.create_reference_triggers(&conn)?;
         }
 
-        Ok(())
-    }
-
-    /// Check if FTS tables need migration (e.g., missing prefix indexes).
-    /// Returns true if tables exist but need to be recreated with new options.
-    fn check_fts_needs_migration(&self, conn: &Connection) -> Result<bool> {
-        // Check if interactions_fts exists
-        let table_exists: bool = conn
+        // Enforce one invocation per tool_use_id, dropping duplicates recorded
+        // by replayed hooks before the index existed
+        let has_tool_use_id_index: bool = conn
             .query_row(
-                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='interactions_fts'",
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='index' AND name='idx_tool_invocations_tool_use_id'",
                 [],
                 |row| row.get(0),
             )
             .unwrap_or(false);
 
-        if !table_exists {
-            return Ok(false); // No migration needed, tables will be created fresh
+        if !has_tool_use_id_index {
+            let removed = Self::dedupe_tool_invocations(&conn)?;
+            if removed > 0 {
+                tracing::info!(target: "clauset::db", "Removed {} duplicate tool invocations", removed);
+            }
+            conn.execute_batch(
+                r#"
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_tool_invocations_tool_use_id
+                    ON tool_invocations(tool_use_id) WHERE tool_use_id IS NOT NULL;
+                "#,
+            )?;
         }
 
-        // Simple heuristic: check the row count of _config to infer prefix support.
-        let config_count: i64 = conn
+        // Add response start times to databases created before they existed
+        let has_first_token_at: bool = conn
             .query_row(
-                "SELECT COUNT(*) FROM interactions_fts_config",
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('chat_messages') WHERE name = 'first_token_at'",
                 [],
                 |row| row.get(0),
             )
-            .unwrap_or(0);
+            .unwrap_or(false);
+
+        if !has_first_token_at {
+            conn.execute("ALTER TABLE chat_messages ADD COLUMN first_token_at INTEGER", [])?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete all but the earliest invocation for each tool_use_id.
+    ///
+    /// Snapshots pointing at a removed duplicate are moved to the kept row.
+    fn dedupe_tool_invocations(conn: &Connection) -> Result<usize> {
+        conn.execute(
+            r#"
+            UPDATE file_snapshots
+            SET tool_invocation_id = (
+                SELECT kept.id FROM tool_invocations kept
+                WHERE kept.rowid = (
+                    SELECT MIN(t.rowid) FROM tool_invocations t
+                    WHERE t.tool_use_id = (
+                        SELECT d.tool_use_id FROM tool_invocations d
+                        WHERE d.id = file_snapshots.tool_invocation_id
+                    )
+                )
+            )
+            WHERE tool_invocation_id IN (
+                SELECT id FROM tool_invocations
+                WHERE tool_use_id IS NOT NULL
+                  AND rowid NOT IN (
+                      SELECT MIN(rowid) FROM tool_invocations
+                      WHERE tool_use_id IS NOT NULL
+                      GROUP BY tool_use_id
+                  )
+            )
+            "#,
+            [],
+        )?;
+        let removed = conn.execute(
+            r#"
+            DELETE FROM tool_invocations
+            WHERE tool_use_id IS NOT NULL
+              AND rowid NOT IN (
+                  SELECT MIN(rowid) FROM tool_invocations
+                  WHERE tool_use_id IS NOT NULL
+                  GROUP BY tool_use_id
+              )
+            "#,
+            [],
+        )?;
+        Ok(removed)
+    }
+
+    /// Check if FTS tables need migration (e.g., missing prefix indexes).
+    /// Returns true if tables exist but need to be recreated with new options.
+    fn check_fts_needs_migration(&self, conn: &Connection) -> Result<bool> {
+        // Check if interactions_fts exists
+        let table_exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name='interactions_fts'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+
+        if !table_exists {
+            return Ok(false); // No migration needed, tables will be created fresh
+        }
+
+        // Simple heuristic: check the row count of _config to infer prefix support.
+        let config_count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM interactions_fts_config",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(0);
 
         // Tables with prefix='2 3' have more config entries (one for each prefix size)
         // Without prefix, typically 2-3 entries; with prefix='2 3', typically 4-5 entries
@@ -563,6 +1282,25 @@ impl InteractionStore {
         Ok(())
     }
 
+    /// Clear both FTS indexes and repopulate them from the base tables.
+    ///
+    /// Repairs search after the indexes drift from the data (manual edits,
+    /// imports, a crash mid-write). Runs in a single transaction, so searches
+    /// never see a half-built index.
+    pub fn rebuild_all_fts(&self) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute_batch(
+            r#"
+            INSERT INTO interactions_fts(interactions_fts) VALUES('delete-all');
+            INSERT INTO tool_invocations_fts(tool_invocations_fts) VALUES('delete-all');
+            "#,
+        )?;
+        self.rebuild_fts_index(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Create reference count triggers for file_contents deduplication.
     fn create_reference_triggers(&self, conn: &Connection) -> Result<()> {
         tracing::info!(target: "clauset::db", "Creating reference count triggers");
@@ -584,6 +1322,21 @@ impl InteractionStore {
                 SET reference_count = reference_count - 1
                 WHERE content_hash = OLD.content_hash;
             END;
+
+            -- Same for full tool inputs stored as content
+            CREATE TRIGGER IF NOT EXISTS tool_invocations_insert_ref
+            AFTER INSERT ON tool_invocations WHEN NEW.tool_input_hash IS NOT NULL BEGIN
+                UPDATE file_contents
+                SET reference_count = reference_count + 1
+                WHERE content_hash = NEW.tool_input_hash;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS tool_invocations_delete_ref
+            AFTER DELETE ON tool_invocations WHEN OLD.tool_input_hash IS NOT NULL BEGIN
+                UPDATE file_contents
+                SET reference_count = reference_count - 1
+                WHERE content_hash = OLD.tool_input_hash;
+            END;
             "#,
         )?;
 
@@ -600,22 +1353,26 @@ impl InteractionStore {
         conn.execute(
             r#"
             INSERT INTO interactions (
-                id, session_id, sequence_number, user_prompt, assistant_summary,
-                started_at, ended_at, cost_usd_delta, input_tokens_delta,
-                output_tokens_delta, status, error_message
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                id, session_id, sequence_number, user_prompt, prompt_truncated,
+                assistant_summary, started_at, ended_at, cost_usd_delta, input_tokens_delta,
+                output_tokens_delta, thinking_tokens_delta, context_percent, status,
+                error_message
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
             "#,
             params![
                 interaction.id.to_string(),
                 interaction.session_id.to_string(),
                 interaction.sequence_number,
                 interaction.user_prompt,
+                interaction.prompt_truncated,
                 interaction.assistant_summary,
                 interaction.started_at.to_rfc3339(),
                 interaction.ended_at.map(|t| t.to_rfc3339()),
                 interaction.cost_usd_delta,
                 interaction.input_tokens_delta as i64,
                 interaction.output_tokens_delta as i64,
+                interaction.thinking_tokens_delta as i64,
+                interaction.context_percent,
                 status_to_string(interaction.status),
                 interaction.error_message,
             ],
@@ -636,6 +1393,149 @@ impl InteractionStore {
         Ok(interaction)
     }
 
+    /// Merge an interaction's prompt, tool starts/ends and assistant response
+    /// into one feed ordered by time, for replaying a turn.
+    pub fn turn_timeline(&self, interaction_id: Uuid) -> Result<Vec<TimelineEntry>> {
+        let interaction = self
+            .get_interaction(interaction_id)?
+            .ok_or(ClausetError::InteractionNotFound(interaction_id))?;
+        let invocations = self.list_tool_invocations(interaction_id)?;
+
+        let mut entries = vec![TimelineEntry::Prompt {
+            timestamp: interaction.started_at,
+            text: interaction.user_prompt,
+        }];
+        for invocation in invocations {
+            entries.push(TimelineEntry::ToolStart {
+                timestamp: invocation.started_at,
+                tool_invocation_id: invocation.id,
+                tool_name: invocation.tool_name.clone(),
+                file_path: invocation.file_path,
+            });
+            if let Some(ended_at) = invocation.ended_at {
+                entries.push(TimelineEntry::ToolEnd {
+                    timestamp: ended_at,
+                    tool_invocation_id: invocation.id,
+                    tool_name: invocation.tool_name,
+                    duration_ms: invocation.duration_ms,
+                    is_error: invocation.is_error,
+                });
+            }
+        }
+        // Stable sort keeps the prompt first and each start before its end on ties
+        entries.sort_by_key(TimelineEntry::timestamp);
+
+        if interaction.ended_at.is_some() || interaction.assistant_summary.is_some() {
+            let last = entries.last().map(TimelineEntry::timestamp);
+            let timestamp = interaction.ended_at.into_iter().chain(last).max().unwrap_or(interaction.started_at);
+            entries.push(TimelineEntry::AssistantResponse {
+                timestamp,
+                summary: interaction.assistant_summary,
+                cost_usd: interaction.cost_usd_delta,
+                input_tokens: interaction.input_tokens_delta,
+                output_tokens: interaction.output_tokens_delta,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// The interactions immediately before and after `id` in its session, by
+    /// sequence number.
+    pub fn neighbor_interactions(&self, id: Uuid) -> Result<(Option<Interaction>, Option<Interaction>)> {
+        let conn = self.conn.lock().unwrap();
+        let neighbor = |sql: &str| {
+            conn.query_row(sql, params![id.to_string()], |row| self.row_to_interaction(row))
+                .optional()
+        };
+        let prev = neighbor(
+            r#"
+            SELECT n.* FROM interactions n
+            JOIN interactions c ON n.session_id = c.session_id
+            WHERE c.id = ?1 AND n.sequence_number < c.sequence_number
+            ORDER BY n.sequence_number DESC
+            LIMIT 1
+            "#,
+        )?;
+        let next = neighbor(
+            r#"
+            SELECT n.* FROM interactions n
+            JOIN interactions c ON n.session_id = c.session_id
+            WHERE c.id = ?1 AND n.sequence_number > c.sequence_number
+            ORDER BY n.sequence_number ASC
+            LIMIT 1
+            "#,
+        )?;
+        Ok((prev, next))
+    }
+
+    /// Record the `@file` references made in an interaction's prompt.
+    pub fn insert_prompt_file_refs(&self, interaction_id: Uuid, paths: &[String]) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("INSERT OR IGNORE INTO prompt_file_refs (interaction_id, path) VALUES (?1, ?2)")?;
+        for path in paths {
+            stmt.execute(params![interaction_id.to_string(), path])?;
+        }
+        Ok(())
+    }
+
+    /// Paths referenced with `@` in an interaction's prompt.
+    pub fn prompt_file_refs(&self, interaction_id: Uuid) -> Result<Vec<String>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare("SELECT path FROM prompt_file_refs WHERE interaction_id = ?1 ORDER BY rowid")?;
+        let paths = stmt
+            .query_map(params![interaction_id.to_string()], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(paths)
+    }
+
+    /// Interactions whose prompt referenced `path`, newest first. A reference
+    /// matches the exact path or any path ending in `/<path>`, so `config.rs`
+    /// finds `@src/config.rs`.
+    pub fn interactions_referencing_file(&self, path: &str, limit: u32) -> Result<Vec<Interaction>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT * FROM interactions
+            WHERE id IN (
+                SELECT interaction_id FROM prompt_file_refs
+                WHERE path = ?1 OR substr(path, -length(?1) - 1) = '/' || ?1
+            )
+            ORDER BY started_at DESC
+            LIMIT ?2
+            "#,
+        )?;
+        let interactions = stmt
+            .query_map(params![path, limit], |row| self.row_to_interaction(row))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(interactions)
+    }
+
+    /// Record the session settings an interaction started under.
+    pub fn set_interaction_context(&self, id: Uuid, context: &InteractionContext) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE interactions SET context = ?2 WHERE id = ?1",
+            params![id.to_string(), serde_json::to_string(context)?],
+        )?;
+        Ok(())
+    }
+
+    /// Get the session settings an interaction started under.
+    ///
+    /// None if the interaction doesn't exist or no context was recorded.
+    pub fn interaction_context(&self, id: Uuid) -> Result<Option<InteractionContext>> {
+        let conn = self.conn.lock().unwrap();
+        let context: Option<String> = conn
+            .query_row(
+                "SELECT context FROM interactions WHERE id = ?1",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(context.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
     /// Get the active (in-progress) interaction for a session.
     pub fn get_active_interaction(&self, session_id: Uuid) -> Result<Option<Interaction>> {
         let conn = self.conn.lock().unwrap();
@@ -649,6 +1549,28 @@ impl InteractionStore {
         Ok(interaction)
     }
 
+    /// Get the interaction that was in flight at `at`.
+    ///
+    /// That is the session's latest interaction started at or before `at`,
+    /// provided it hadn't ended yet (or is still open). Returns None for a
+    /// timestamp between interactions.
+    pub fn interaction_at(&self, session_id: Uuid, at: DateTime<Utc>) -> Result<Option<Interaction>> {
+        let conn = self.conn.lock().unwrap();
+        let latest = conn
+            .query_row(
+                r#"
+                SELECT * FROM interactions
+                WHERE session_id = ?1 AND started_at <= ?2
+                ORDER BY started_at DESC
+                LIMIT 1
+                "#,
+                params![session_id.to_string(), at.to_rfc3339()],
+                |row| self.row_to_interaction(row),
+            )
+            .optional()?;
+        Ok(latest.filter(|i| i.ended_at.is_none_or(|ended| ended >= at)))
+    }
+
     /// Get the next sequence number for a session.
     pub fn next_sequence_number(&self, session_id: Uuid) -> Result<u32> {
         let conn = self.conn.lock().unwrap();
@@ -670,7 +1592,7 @@ impl InteractionStore {
         limit: u32,
         offset: u32,
     ) -> Result<Vec<Interaction>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             r#"
             SELECT * FROM interactions
@@ -688,6 +1610,99 @@ impl InteractionStore {
         Ok(interactions)
     }
 
+    /// A session's interactions as plain text, oldest first: each turn's
+    /// prompt, the tools it called and the assistant's summary.
+    ///
+    /// ```text
+    /// User: Fix the login bug
+    /// Tool: Read src/auth.rs
+    /// Assistant: Fixed the redirect check.
+    /// ```
+    ///
+    /// Turns are separated by a blank line. Empty when the session has no
+    /// interactions.
+    pub fn session_transcript_text(&self, session_id: Uuid) -> Result<String> {
+        let interactions = {
+            let conn = self.reader();
+            let mut stmt = conn.prepare(
+                "SELECT * FROM interactions WHERE session_id = ?1 ORDER BY sequence_number ASC",
+            )?;
+            stmt.query_map(params![session_id.to_string()], |row| self.row_to_interaction(row))?
+                .collect::<std::result::Result<Vec<_>, _>>()?
+        };
+
+        let mut turns = Vec::with_capacity(interactions.len());
+        for interaction in interactions {
+            let mut turn = format!("User: {}\n", interaction.user_prompt.trim());
+            for invocation in self.list_tool_invocations(interaction.id)? {
+                match invocation.key_input() {
+                    Some(input) => turn.push_str(&format!("Tool: {} {}\n", invocation.tool_name, input)),
+                    None => turn.push_str(&format!("Tool: {}\n", invocation.tool_name)),
+                }
+            }
+            if let Some(summary) = &interaction.assistant_summary {
+                turn.push_str(&format!("Assistant: {}\n", summary.trim()));
+            }
+            turns.push(turn);
+        }
+        Ok(turns.join("\n"))
+    }
+
+    /// List failed or interrupted interactions across all sessions (newest first).
+    pub fn list_failed_interactions(
+        &self,
+        limit: u32,
+        offset: u32,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Interaction>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT * FROM interactions
+            WHERE status IN ('failed', 'interrupted')
+              AND (?1 IS NULL OR started_at >= ?1)
+            ORDER BY started_at DESC
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )?;
+        let interactions = stmt
+            .query_map(
+                params![since.map(|t| t.to_rfc3339()), limit, offset],
+                |row| self.row_to_interaction(row),
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(interactions)
+    }
+
+    /// List interactions with at least one errored tool invocation (newest first).
+    pub fn list_interactions_with_tool_errors(
+        &self,
+        limit: u32,
+        offset: u32,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Interaction>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT * FROM interactions i
+            WHERE EXISTS (
+                SELECT 1 FROM tool_invocations t
+                WHERE t.interaction_id = i.id AND t.is_error = 1
+            )
+              AND (?1 IS NULL OR i.started_at >= ?1)
+            ORDER BY i.started_at DESC
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )?;
+        let interactions = stmt
+            .query_map(
+                params![since.map(|t| t.to_rfc3339()), limit, offset],
+                |row| self.row_to_interaction(row),
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(interactions)
+    }
+
     /// List interaction summaries for a session.
     pub fn list_interaction_summaries(
         &self,
@@ -695,7 +1710,7 @@ impl InteractionStore {
         limit: u32,
         offset: u32,
     ) -> Result<Vec<InteractionSummary>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             r#"
             SELECT
@@ -723,6 +1738,44 @@ impl InteractionStore {
         Ok(summaries)
     }
 
+    /// List interaction summaries for a session that did (`changed`) or did
+    /// not (pure Q&A turns) snapshot any file.
+    pub fn list_interactions_by_change(
+        &self,
+        session_id: Uuid,
+        changed: bool,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<InteractionSummary>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                i.*,
+                (SELECT COUNT(*) FROM tool_invocations WHERE interaction_id = i.id) as tool_count,
+                (SELECT COUNT(DISTINCT file_path) FROM file_snapshots WHERE interaction_id = i.id AND snapshot_type = 'after') as files_changed
+            FROM interactions i
+            WHERE i.session_id = ?1
+              AND EXISTS (SELECT 1 FROM file_snapshots f WHERE f.interaction_id = i.id) = ?2
+            ORDER BY i.sequence_number DESC
+            LIMIT ?3 OFFSET ?4
+            "#,
+        )?;
+        let summaries = stmt
+            .query_map(params![session_id.to_string(), changed, limit, offset], |row| {
+                let interaction = self.row_to_interaction(row)?;
+                let tool_count: i64 = row.get("tool_count")?;
+                let files_changed: i64 = row.get("files_changed")?;
+                Ok(InteractionSummary::from_interaction(
+                    &interaction,
+                    tool_count as u32,
+                    files_changed as u32,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(summaries)
+    }
+
     /// Update an interaction.
     pub fn update_interaction(&self, interaction: &Interaction) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -734,9 +1787,11 @@ impl InteractionStore {
                 cost_usd_delta = ?3,
                 input_tokens_delta = ?4,
                 output_tokens_delta = ?5,
-                status = ?6,
-                error_message = ?7
-            WHERE id = ?8
+                thinking_tokens_delta = ?6,
+                context_percent = ?7,
+                status = ?8,
+                error_message = ?9
+            WHERE id = ?10
             "#,
             params![
                 interaction.assistant_summary,
@@ -744,6 +1799,8 @@ impl InteractionStore {
                 interaction.cost_usd_delta,
                 interaction.input_tokens_delta as i64,
                 interaction.output_tokens_delta as i64,
+                interaction.thinking_tokens_delta as i64,
+                interaction.context_percent,
                 status_to_string(interaction.status),
                 interaction.error_message,
                 interaction.id.to_string(),
@@ -752,23 +1809,25 @@ impl InteractionStore {
         Ok(())
     }
 
-    /// Mark an interaction as completed.
+    /// Mark an interaction as completed. Interactions that already ended
+    /// (e.g. interrupted) keep their status.
     pub fn complete_interaction(&self, id: Uuid) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "UPDATE interactions SET status = 'completed', ended_at = ?1 WHERE id = ?2",
+            "UPDATE interactions SET status = 'completed', ended_at = ?1 WHERE id = ?2 AND status = 'active'",
             params![Utc::now().to_rfc3339(), id.to_string()],
         )?;
         Ok(())
     }
 
-    /// Mark an interaction as completed with cost/token deltas.
+    /// Mark an active interaction as completed with cost/token deltas.
     pub fn complete_interaction_with_costs(
         &self,
         id: Uuid,
         cost_usd_delta: f64,
         input_tokens_delta: u64,
         output_tokens_delta: u64,
+        thinking_tokens_delta: u64,
     ) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute(
@@ -777,19 +1836,107 @@ impl InteractionStore {
                    ended_at = ?1,
                    cost_usd_delta = ?2,
                    input_tokens_delta = ?3,
-                   output_tokens_delta = ?4
-               WHERE id = ?5"#,
+                   output_tokens_delta = ?4,
+                   thinking_tokens_delta = ?5
+               WHERE id = ?6 AND status = 'active'"#,
             params![
                 Utc::now().to_rfc3339(),
                 cost_usd_delta,
                 input_tokens_delta as i64,
                 output_tokens_delta as i64,
+                thinking_tokens_delta as i64,
                 id.to_string()
             ],
         )?;
         Ok(())
     }
 
+    /// Back-fill a session's interaction costs from its Claude transcript and
+    /// mark them completed, for sessions imported without live hooks.
+    ///
+    /// Transcript prompt turns are matched to interactions by order. Token
+    /// deltas count all input including the prompt cache, and cost uses the
+    /// default pricing table. Returns the number of interactions updated.
+    pub fn reconcile_costs_from_transcript(&self, session_id: Uuid, transcript_path: &Path) -> Result<u32> {
+        let turns = crate::compute_turn_usage(transcript_path)?;
+        let pricing = crate::PricingTable::default();
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let interaction_ids = {
+            let mut stmt = tx.prepare(
+                "SELECT id FROM interactions WHERE session_id = ?1 ORDER BY sequence_number ASC",
+            )?;
+            stmt.query_map(params![session_id.to_string()], |row| row.get::<_, String>(0))?
+                .collect::<std::result::Result<Vec<_>, _>>()?
+        };
+
+        let now = Utc::now().to_rfc3339();
+        let mut updated = 0;
+        for (interaction_id, turn) in interaction_ids.iter().zip(&turns) {
+            let cost = pricing.compute_cost(
+                &turn.model,
+                turn.total_input_tokens,
+                turn.total_output_tokens,
+                turn.total_cache_read_tokens,
+                turn.total_cache_creation_tokens,
+            );
+            tx.execute(
+                r#"UPDATE interactions
+                   SET status = 'completed',
+                       ended_at = COALESCE(ended_at, ?1),
+                       cost_usd_delta = ?2,
+                       input_tokens_delta = ?3,
+                       output_tokens_delta = ?4,
+                       thinking_tokens_delta = ?5
+                   WHERE id = ?6"#,
+                params![
+                    now,
+                    cost,
+                    turn.context_tokens() as i64,
+                    turn.total_output_tokens as i64,
+                    turn.total_thinking_tokens as i64,
+                    interaction_id
+                ],
+            )?;
+            updated += 1;
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    /// Record the context window usage snapshot for an interaction.
+    pub fn set_interaction_context_percent(&self, id: Uuid, context_percent: u8) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE interactions SET context_percent = ?1 WHERE id = ?2",
+            params![context_percent, id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Get the context usage at each interaction of a session, as
+    /// `(sequence_number, context_percent)` in sequence order.
+    ///
+    /// Interactions without a snapshot are skipped. Drops in the series show
+    /// where the conversation was compacted.
+    pub fn context_history(&self, session_id: Uuid) -> Result<Vec<(u32, u8)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"SELECT sequence_number, context_percent FROM interactions
+               WHERE session_id = ?1 AND context_percent IS NOT NULL
+               ORDER BY sequence_number ASC"#,
+        )?;
+        let history = stmt
+            .query_map(params![session_id.to_string()], |row| {
+                let sequence: i64 = row.get(0)?;
+                let percent: i64 = row.get(1)?;
+                Ok((sequence as u32, percent.clamp(0, 100) as u8))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(history)
+    }
+
     /// Update costs for the most recent interaction in a session.
     /// Called when costs are updated after the interaction was marked complete.
     pub fn update_latest_interaction_costs(
@@ -798,6 +1945,21 @@ impl InteractionStore {
         cost_usd_delta: f64,
         input_tokens_delta: u64,
         output_tokens_delta: u64,
+    ) -> Result<bool> {
+        self.update_interaction_costs(session_id, None, cost_usd_delta, input_tokens_delta, output_tokens_delta)
+    }
+
+    /// Update costs for a given interaction of a session, or the most recent
+    /// one when `interaction_id` is None or not part of the session. Late
+    /// costs for an older turn then stay on that turn even after a newer one
+    /// has started.
+    pub fn update_interaction_costs(
+        &self,
+        session_id: Uuid,
+        interaction_id: Option<Uuid>,
+        cost_usd_delta: f64,
+        input_tokens_delta: u64,
+        output_tokens_delta: u64,
     ) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
         // Only update if the new values are greater (costs only increase)
@@ -806,15 +1968,17 @@ impl InteractionStore {
                SET cost_usd_delta = MAX(cost_usd_delta, ?1),
                    input_tokens_delta = MAX(input_tokens_delta, ?2),
                    output_tokens_delta = MAX(output_tokens_delta, ?3)
-               WHERE session_id = ?4
-                 AND sequence_number = (
-                     SELECT MAX(sequence_number) FROM interactions WHERE session_id = ?4
-                 )"#,
+               WHERE id = COALESCE(
+                   (SELECT id FROM interactions WHERE id = ?5 AND session_id = ?4),
+                   (SELECT id FROM interactions WHERE session_id = ?4
+                    ORDER BY sequence_number DESC LIMIT 1)
+               )"#,
             params![
                 cost_usd_delta,
                 input_tokens_delta as i64,
                 output_tokens_delta as i64,
-                session_id.to_string()
+                session_id.to_string(),
+                interaction_id.map(|id| id.to_string()),
             ],
         )?;
         Ok(updated > 0)
@@ -840,28 +2004,285 @@ impl InteractionStore {
         Ok(count as u32)
     }
 
-    // =========================================================================
-    // Tool Invocation CRUD
-    // =========================================================================
+    /// Fail interactions left `active` by a crash: those that started more
+    /// than `grace` ago are marked failed with a "Recovered after restart"
+    /// message. Returns how many were recovered.
+    pub fn recover_stale_active(&self, grace: std::time::Duration) -> Result<u32> {
+        let grace = chrono::Duration::from_std(grace).unwrap_or(chrono::Duration::MAX);
+        let cutoff = Utc::now()
+            .checked_sub_signed(grace)
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        let conn = self.conn.lock().unwrap();
+        let count = conn.execute(
+            r#"UPDATE interactions SET status = 'failed', ended_at = ?1, error_message = ?2
+               WHERE status = 'active' AND started_at < ?3"#,
+            params![Utc::now().to_rfc3339(), "Recovered after restart", cutoff.to_rfc3339()],
+        )?;
+        Ok(count as u32)
+    }
 
-    /// Insert a new tool invocation.
-    pub fn insert_tool_invocation(&self, invocation: &ToolInvocation) -> Result<()> {
+    /// Mark all active interactions for a session as interrupted by the user.
+    pub fn interrupt_active_interactions(&self, session_id: Uuid) -> Result<u32> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            r#"
-            INSERT INTO tool_invocations (
-                id, interaction_id, tool_use_id, sequence_number, tool_name,
-                tool_input, tool_output_preview, file_path, is_error,
-                error_message, started_at, ended_at, duration_ms
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
-            "#,
-            params![
-                invocation.id.to_string(),
-                invocation.interaction_id.to_string(),
-                invocation.tool_use_id,
-                invocation.sequence_number,
-                invocation.tool_name,
-                invocation.tool_input.to_string(),
+        let count = conn.execute(
+            "UPDATE interactions SET status = 'interrupted', ended_at = ?1 WHERE session_id = ?2 AND status = 'active'",
+            params![Utc::now().to_rfc3339(), session_id.to_string()],
+        )?;
+        Ok(count as u32)
+    }
+
+    /// Merge duplicate consecutive interactions in a session.
+    ///
+    /// Two adjacent interactions are duplicates when they share the same
+    /// `user_prompt` and the later one started before the earlier one ended
+    /// (or the earlier one never ended), within [`DUPLICATE_INTERACTION_WINDOW`]
+    /// of the earlier one's start. The duplicate's tool invocations and
+    /// file snapshots are moved onto the earlier interaction, its costs are
+    /// added, and the duplicate is deleted.
+    ///
+    /// Returns the number of interactions merged away.
+    pub fn dedupe_session_interactions(&self, session_id: Uuid) -> Result<u32> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let interactions = {
+            let mut stmt = tx.prepare(
+                "SELECT * FROM interactions WHERE session_id = ?1 ORDER BY sequence_number ASC",
+            )?;
+            stmt.query_map(params![session_id.to_string()], |row| {
+                self.row_to_interaction(row)
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?
+        };
+
+        let mut merged = 0u32;
+        let mut iter = interactions.into_iter();
+        let Some(mut keeper) = iter.next() else {
+            return Ok(0);
+        };
+
+        for candidate in iter {
+            let overlaps = keeper
+                .ended_at
+                .map(|ended| candidate.started_at <= ended)
+                .unwrap_or(true)
+                && candidate.started_at - keeper.started_at <= DUPLICATE_INTERACTION_WINDOW;
+
+            if candidate.user_prompt != keeper.user_prompt || !overlaps {
+                keeper = candidate;
+                continue;
+            }
+
+            // Append the duplicate's tools after the keeper's existing ones
+            let max_tool_seq: i64 = tx.query_row(
+                "SELECT COALESCE(MAX(sequence_number), 0) FROM tool_invocations WHERE interaction_id = ?1",
+                params![keeper.id.to_string()],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                r#"UPDATE tool_invocations
+                   SET interaction_id = ?1, sequence_number = sequence_number + ?2
+                   WHERE interaction_id = ?3"#,
+                params![keeper.id.to_string(), max_tool_seq, candidate.id.to_string()],
+            )?;
+            tx.execute(
+                "UPDATE file_snapshots SET interaction_id = ?1 WHERE interaction_id = ?2",
+                params![keeper.id.to_string(), candidate.id.to_string()],
+            )?;
+
+            keeper.cost_usd_delta += candidate.cost_usd_delta;
+            keeper.input_tokens_delta += candidate.input_tokens_delta;
+            keeper.output_tokens_delta += candidate.output_tokens_delta;
+            keeper.thinking_tokens_delta += candidate.thinking_tokens_delta;
+            keeper.ended_at = match (keeper.ended_at, candidate.ended_at) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            };
+            if keeper.assistant_summary.is_none() {
+                keeper.assistant_summary = candidate.assistant_summary;
+            }
+            if keeper.status == InteractionStatus::Active {
+                keeper.status = candidate.status;
+                keeper.error_message = candidate.error_message;
+            }
+
+            tx.execute(
+                r#"UPDATE interactions SET
+                       assistant_summary = ?1,
+                       ended_at = ?2,
+                       cost_usd_delta = ?3,
+                       input_tokens_delta = ?4,
+                       output_tokens_delta = ?5,
+                       thinking_tokens_delta = ?6,
+                       status = ?7,
+                       error_message = ?8
+                   WHERE id = ?9"#,
+                params![
+                    keeper.assistant_summary,
+                    keeper.ended_at.map(|t| t.to_rfc3339()),
+                    keeper.cost_usd_delta,
+                    keeper.input_tokens_delta as i64,
+                    keeper.output_tokens_delta as i64,
+                    keeper.thinking_tokens_delta as i64,
+                    status_to_string(keeper.status),
+                    keeper.error_message,
+                    keeper.id.to_string(),
+                ],
+            )?;
+            tx.execute(
+                "DELETE FROM interactions WHERE id = ?1",
+                params![candidate.id.to_string()],
+            )?;
+            merged += 1;
+        }
+
+        tx.commit()?;
+        Ok(merged)
+    }
+
+    /// Copy an interaction into another session's timeline for reference.
+    ///
+    /// The copy gets a new ID and the next sequence number in the target
+    /// session, along with copies of its tool invocations and file snapshots.
+    /// Snapshots reference the existing content blobs, so no file content is
+    /// duplicated. Cost and token deltas are not copied since the target
+    /// session never incurred them, and copied tools drop their `tool_use_id`
+    /// so hook events can't be matched to them.
+    ///
+    /// Returns the ID of the new interaction.
+    pub fn copy_interaction(&self, interaction_id: Uuid, target_session_id: Uuid) -> Result<Uuid> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let max_seq: Option<i64> = tx.query_row(
+            "SELECT MAX(sequence_number) FROM interactions WHERE session_id = ?1",
+            params![target_session_id.to_string()],
+            |row| row.get(0),
+        )?;
+        let sequence_number = max_seq.map(|n| n + 1).unwrap_or(1);
+
+        let new_id = Uuid::new_v4();
+        let copied = tx.execute(
+            r#"INSERT INTO interactions (
+                   id, session_id, sequence_number, user_prompt, prompt_truncated,
+                   assistant_summary, started_at, ended_at, status, error_message,
+                   context_percent
+               )
+               SELECT ?1, ?2, ?3, user_prompt, prompt_truncated,
+                      assistant_summary, started_at, ended_at, status, error_message,
+                      context_percent
+               FROM interactions WHERE id = ?4"#,
+            params![
+                new_id.to_string(),
+                target_session_id.to_string(),
+                sequence_number,
+                interaction_id.to_string(),
+            ],
+        )?;
+        if copied == 0 {
+            return Err(ClausetError::InteractionNotFound(interaction_id));
+        }
+
+        // Copy tool invocations, remembering old -> new IDs for the snapshots
+        let tool_ids: Vec<String> = {
+            let mut stmt = tx.prepare("SELECT id FROM tool_invocations WHERE interaction_id = ?1")?;
+            stmt.query_map(params![interaction_id.to_string()], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?
+        };
+        let mut tool_id_map = HashMap::new();
+        for old_tool_id in tool_ids {
+            let new_tool_id = Uuid::new_v4().to_string();
+            tx.execute(
+                r#"INSERT INTO tool_invocations (
+                       id, interaction_id, tool_use_id, sequence_number, tool_name, tool_input,
+                       tool_input_hash, tool_output_preview, file_path, is_error, error_message,
+                       started_at, ended_at, duration_ms
+                   )
+                   SELECT ?1, ?2, NULL, sequence_number, tool_name, tool_input,
+                          tool_input_hash, tool_output_preview, file_path, is_error, error_message,
+                          started_at, ended_at, duration_ms
+                   FROM tool_invocations WHERE id = ?3"#,
+                params![new_tool_id, new_id.to_string(), old_tool_id],
+            )?;
+            tool_id_map.insert(old_tool_id, new_tool_id);
+        }
+
+        // Copy snapshots; the insert trigger bumps each blob's reference count
+        let snapshots: Vec<(String, Option<String>)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, tool_invocation_id FROM file_snapshots WHERE interaction_id = ?1",
+            )?;
+            stmt.query_map(params![interaction_id.to_string()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<rusqlite::Result<_>>()?
+        };
+        for (old_snapshot_id, old_tool_id) in snapshots {
+            let new_tool_id = old_tool_id.and_then(|id| tool_id_map.get(&id).cloned());
+            tx.execute(
+                r#"INSERT INTO file_snapshots (
+                       id, interaction_id, tool_invocation_id, file_path,
+                       content_hash, snapshot_type, file_size, created_at
+                   )
+                   SELECT ?1, ?2, ?3, file_path, content_hash, snapshot_type, file_size, created_at
+                   FROM file_snapshots WHERE id = ?4"#,
+                params![
+                    Uuid::new_v4().to_string(),
+                    new_id.to_string(),
+                    new_tool_id,
+                    old_snapshot_id,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(new_id)
+    }
+
+    // =========================================================================
+    // Tool Invocation CRUD
+    // =========================================================================
+
+    /// Insert a new tool invocation.
+    ///
+    /// Inputs over the configured size cap are stored truncated; the full
+    /// input is kept as content (see [`Self::full_tool_input`]) unless
+    /// disabled.
+    ///
+    /// Returns false without writing anything when an invocation with the
+    /// same `tool_use_id` was already recorded (e.g. a replayed hook).
+    pub fn insert_tool_invocation(&self, invocation: &ToolInvocation) -> Result<bool> {
+        let full_input = invocation.tool_input.to_string();
+        let (tool_input, tool_input_hash) = if full_input.len() > self.tool_input_limits.max_bytes {
+            let truncated = truncate_tool_input(&invocation.tool_input, self.tool_input_limits.max_bytes);
+            let hash = if self.tool_input_limits.store_full {
+                Some(self.store_file_content(full_input.as_bytes())?.0)
+            } else {
+                None
+            };
+            (truncated, hash)
+        } else {
+            (full_input, None)
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let inserted = conn.execute(
+            r#"
+            INSERT INTO tool_invocations (
+                id, interaction_id, tool_use_id, sequence_number, tool_name,
+                tool_input, tool_output_preview, file_path, is_error,
+                error_message, started_at, ended_at, duration_ms, exit_code,
+                tool_input_hash
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+            ON CONFLICT(tool_use_id) WHERE tool_use_id IS NOT NULL DO NOTHING
+            "#,
+            params![
+                invocation.id.to_string(),
+                invocation.interaction_id.to_string(),
+                invocation.tool_use_id,
+                invocation.sequence_number,
+                invocation.tool_name,
+                tool_input,
                 invocation.tool_output_preview,
                 invocation.file_path.as_ref().map(|p| p.to_string_lossy().to_string()),
                 invocation.is_error as i32,
@@ -869,9 +2290,37 @@ impl InteractionStore {
                 invocation.started_at.to_rfc3339(),
                 invocation.ended_at.map(|t| t.to_rfc3339()),
                 invocation.duration_ms,
+                invocation.exit_code,
+                tool_input_hash,
             ],
         )?;
-        Ok(())
+        Ok(inserted > 0)
+    }
+
+    /// The untruncated input of a tool invocation: the stored full copy when
+    /// the input was over the size cap, otherwise the input as recorded.
+    pub fn full_tool_input(&self, id: Uuid) -> Result<Option<serde_json::Value>> {
+        let row: Option<(String, Option<String>)> = {
+            let conn = self.reader();
+            conn.query_row(
+                "SELECT tool_input, tool_input_hash FROM tool_invocations WHERE id = ?1",
+                params![id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+        };
+        let Some((tool_input, hash)) = row else {
+            return Ok(None);
+        };
+
+        let full = match hash {
+            Some(hash) => match self.get_file_content(&hash)? {
+                Some(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                None => tool_input,
+            },
+            None => tool_input,
+        };
+        Ok(Some(serde_json::from_str(&full).unwrap_or(serde_json::Value::Null)))
     }
 
     /// Get a tool invocation by ID.
@@ -905,7 +2354,7 @@ impl InteractionStore {
 
     /// List tool invocations for an interaction (in order).
     pub fn list_tool_invocations(&self, interaction_id: Uuid) -> Result<Vec<ToolInvocation>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             r#"
             SELECT * FROM tool_invocations
@@ -945,8 +2394,9 @@ impl InteractionStore {
                 is_error = ?2,
                 error_message = ?3,
                 ended_at = ?4,
-                duration_ms = ?5
-            WHERE id = ?6
+                duration_ms = ?5,
+                exit_code = ?6
+            WHERE id = ?7
             "#,
             params![
                 invocation.tool_output_preview,
@@ -954,19 +2404,22 @@ impl InteractionStore {
                 invocation.error_message,
                 invocation.ended_at.map(|t| t.to_rfc3339()),
                 invocation.duration_ms,
+                invocation.exit_code,
                 invocation.id.to_string(),
             ],
         )?;
         Ok(())
     }
 
-    /// Complete a tool invocation with output, error status, and timing.
+    /// Complete a tool invocation with output, error status, exit code
+    /// (Bash only), and timing.
     pub fn complete_tool_invocation(
         &self,
         id: Uuid,
         output_preview: Option<String>,
         is_error: bool,
         error_message: Option<String>,
+        exit_code: Option<i32>,
     ) -> Result<()> {
         let now = Utc::now();
         let conn = self.conn.lock().unwrap();
@@ -994,8 +2447,9 @@ impl InteractionStore {
                 is_error = ?2,
                 error_message = ?3,
                 ended_at = ?4,
-                duration_ms = ?5
-            WHERE id = ?6
+                duration_ms = ?5,
+                exit_code = ?6
+            WHERE id = ?7
             "#,
             params![
                 output_preview,
@@ -1003,12 +2457,80 @@ impl InteractionStore {
                 error_message,
                 now.to_rfc3339(),
                 duration_ms,
+                exit_code,
                 id.to_string(),
             ],
         )?;
         Ok(())
     }
 
+    /// List a session's Bash invocations that exited non-zero, oldest first.
+    pub fn list_failed_commands(&self, session_id: Uuid) -> Result<Vec<ToolInvocation>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT t.* FROM tool_invocations t
+            JOIN interactions i ON t.interaction_id = i.id
+            WHERE i.session_id = ?1 AND t.tool_name = 'Bash'
+              AND t.exit_code IS NOT NULL AND t.exit_code != 0
+            ORDER BY i.sequence_number ASC, t.sequence_number ASC
+            "#,
+        )?;
+        let invocations = stmt
+            .query_map(params![session_id.to_string()], |row| self.row_to_tool_invocation(row))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(invocations)
+    }
+
+    /// Shell script replaying a session's Bash commands in order, for bug
+    /// reports. Commands that errored without running (no exit code) are
+    /// skipped; each command is preceded by a comment naming its interaction.
+    pub fn extract_bash_script(&self, session_id: Uuid) -> Result<String> {
+        let conn = self.reader();
+        let project_path: Option<String> = conn
+            .query_row(
+                "SELECT project_path FROM sessions WHERE id = ?1",
+                params![session_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT i.id, i.sequence_number, t.tool_input FROM tool_invocations t
+            JOIN interactions i ON t.interaction_id = i.id
+            WHERE i.session_id = ?1 AND t.tool_name = 'Bash'
+              AND NOT (t.is_error = 1 AND t.exit_code IS NULL)
+            ORDER BY i.sequence_number ASC, t.sequence_number ASC
+            "#,
+        )?;
+        let commands = stmt
+            .query_map(params![session_id.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut script = format!("#!/bin/sh\n# Bash commands from session {}\n", session_id);
+        if let Some(path) = project_path {
+            script.push_str(&format!("cd '{}' || exit 1\n", path.replace('\'', r"'\''")));
+        }
+        let mut last_interaction = None;
+        for (interaction_id, sequence_number, tool_input) in commands {
+            let Some(command) = serde_json::from_str::<serde_json::Value>(&tool_input)
+                .ok()
+                .and_then(|input| input.get("command")?.as_str().map(str::to_string))
+            else {
+                continue;
+            };
+            if last_interaction.as_ref() != Some(&interaction_id) {
+                script.push_str(&format!("\n# Interaction {} ({})\n", sequence_number, interaction_id));
+                last_interaction = Some(interaction_id);
+            }
+            script.push_str(command.trim_end());
+            script.push('\n');
+        }
+        Ok(script)
+    }
+
     // =========================================================================
     // File Content & Snapshot CRUD
     // =========================================================================
@@ -1079,15 +2601,67 @@ impl InteractionStore {
 
         match result {
             Some(compressed) => {
-                let decompressed = zstd::decode_all(&compressed[..]).map_err(|e| {
-                    ClausetError::IoError(std::io::Error::new(std::io::ErrorKind::Other, e))
-                })?;
+                let decompressed =
+                    zstd::decode_all(&compressed[..]).map_err(|_| ClausetError::CorruptSnapshot {
+                        content_hash: content_hash.to_string(),
+                    })?;
                 Ok(Some(decompressed))
             }
             None => Ok(None),
         }
     }
 
+    /// List every file snapshot referencing a content hash, oldest first.
+    pub fn content_references(&self, content_hash: &str) -> Result<Vec<ContentRef>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT fs.id, fs.interaction_id, i.session_id, fs.file_path,
+                   fs.snapshot_type, fs.created_at
+            FROM file_snapshots fs
+            JOIN interactions i ON i.id = fs.interaction_id
+            WHERE fs.content_hash = ?1
+            ORDER BY fs.created_at ASC
+            "#,
+        )?;
+
+        let refs = stmt
+            .query_map(params![content_hash], |row| {
+                let snapshot_id: String = row.get(0)?;
+                let interaction_id: String = row.get(1)?;
+                let session_id: String = row.get(2)?;
+                let file_path: String = row.get(3)?;
+                let snapshot_type: String = row.get(4)?;
+                let created_at: String = row.get(5)?;
+                Ok(ContentRef {
+                    snapshot_id: Uuid::parse_str(&snapshot_id).unwrap_or_default(),
+                    interaction_id: Uuid::parse_str(&interaction_id).unwrap_or_default(),
+                    session_id: Uuid::parse_str(&session_id).unwrap_or_default(),
+                    file_path: file_path.into(),
+                    snapshot_type: string_to_snapshot_type(&snapshot_type),
+                    created_at: DateTime::parse_from_rfc3339(&created_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(refs)
+    }
+
+    /// Get the maintained reference count of a content blob, or None if it isn't stored.
+    pub fn content_reference_count(&self, content_hash: &str) -> Result<Option<u32>> {
+        let conn = self.conn.lock().unwrap();
+        let count: Option<i64> = conn
+            .query_row(
+                "SELECT reference_count FROM file_contents WHERE content_hash = ?1",
+                params![content_hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(count.map(|c| c as u32))
+    }
+
     /// Insert a file snapshot.
     pub fn insert_file_snapshot(&self, snapshot: &FileSnapshot) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -1158,16 +2732,16 @@ impl InteractionStore {
         file_path: &str,
         snapshot_type: SnapshotType,
     ) -> Result<Option<Vec<u8>>> {
-        let conn = self.conn.lock().unwrap();
-
         let type_str = match snapshot_type {
             SnapshotType::Before => "before",
             SnapshotType::After => "after",
         };
 
-        // First get the content hash from file_snapshots
-        let content_hash: Option<String> = conn
-            .query_row(
+        // First get the content hash from file_snapshots (releasing the
+        // connection before get_file_content locks it again)
+        let content_hash: Option<String> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
                 r#"
                 SELECT content_hash
                 FROM file_snapshots
@@ -1176,7 +2750,8 @@ impl InteractionStore {
                 params![interaction_id.to_string(), file_path, type_str],
                 |row| row.get(0),
             )
-            .optional()?;
+            .optional()?
+        };
 
         // If we have a content hash, fetch the actual content
         if let Some(hash) = content_hash {
@@ -1186,18 +2761,249 @@ impl InteractionStore {
         }
     }
 
+    /// Diff the latest "after" snapshot of a file in an interaction against
+    /// the file currently on disk.
+    ///
+    /// Returns None if the interaction has no "after" snapshot for the file.
+    /// A file missing from disk diffs as a deletion; at most
+    /// `MAX_SNAPSHOT_SIZE` bytes of the disk file are read.
+    pub fn diff_against_disk(
+        &self,
+        interaction_id: Uuid,
+        file_path: &Path,
+        context_lines: usize,
+    ) -> Result<Option<FileDiff>> {
+        let content_hash: Option<String> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                r#"
+                SELECT content_hash
+                FROM file_snapshots
+                WHERE interaction_id = ?1 AND file_path = ?2 AND snapshot_type = 'after'
+                ORDER BY created_at DESC
+                LIMIT 1
+                "#,
+                params![interaction_id.to_string(), file_path.to_string_lossy()],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+        let Some(hash) = content_hash else {
+            return Ok(None);
+        };
+        let Some(snapshot) = self.get_file_content(&hash)? else {
+            return Ok(None);
+        };
+
+        let on_disk = match std::fs::File::open(file_path) {
+            Ok(file) => {
+                use std::io::Read;
+                let mut content = Vec::new();
+                file.take(MAX_SNAPSHOT_SIZE).read_to_end(&mut content)?;
+                Some(content)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Some(crate::diff::compute_diff(
+            Some(&snapshot),
+            on_disk.as_deref(),
+            context_lines,
+        )))
+    }
+
+    /// Diff the latest "after" snapshot of a file in one session against the
+    /// latest in another.
+    ///
+    /// A file absent from one session diffs as a creation or deletion.
+    /// Returns None if neither session has a snapshot of the file.
+    pub fn diff_file_across_sessions(
+        &self,
+        session_a: Uuid,
+        session_b: Uuid,
+        file_path: &str,
+        context_lines: usize,
+    ) -> Result<Option<FileDiff>> {
+        let content_a = self.latest_session_snapshot(session_a, file_path)?;
+        let content_b = self.latest_session_snapshot(session_b, file_path)?;
+        if content_a.is_none() && content_b.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(crate::diff::compute_diff(
+            content_a.as_deref(),
+            content_b.as_deref(),
+            context_lines,
+        )))
+    }
+
+    /// Compare the tool calls of two interactions, e.g. two attempts at the
+    /// same task. Calls match when both the tool and its key input (file
+    /// path, command or pattern) are the same.
+    pub fn diff_tool_sequences(&self, interaction_a: Uuid, interaction_b: Uuid) -> Result<Vec<ToolSeqDiff>> {
+        let tools_a = self.tool_sequence(interaction_a)?;
+        let tools_b = self.tool_sequence(interaction_b)?;
+        let keys = |tools: &[ToolInvocation]| -> Vec<(String, Option<String>)> {
+            tools.iter().map(|t| (t.tool_name.clone(), t.key_input())).collect()
+        };
+
+        Ok(crate::diff::diff_sequences(&keys(&tools_a), &keys(&tools_b))
+            .into_iter()
+            .map(|step| {
+                let a = step.old_index.map(|i| &tools_a[i]);
+                let b = step.new_index.map(|i| &tools_b[i]);
+                let tool = a.or(b).expect("every diff step is on at least one side");
+                ToolSeqDiff {
+                    change_type: step.change_type,
+                    tool_name: tool.tool_name.clone(),
+                    key_input: tool.key_input(),
+                    sequence_a: a.map(|t| t.sequence_number),
+                    sequence_b: b.map(|t| t.sequence_number),
+                }
+            })
+            .collect())
+    }
+
+    /// Tool invocations of an interaction that must exist.
+    fn tool_sequence(&self, interaction_id: Uuid) -> Result<Vec<ToolInvocation>> {
+        if self.get_interaction(interaction_id)?.is_none() {
+            return Err(ClausetError::InteractionNotFound(interaction_id));
+        }
+        self.list_tool_invocations(interaction_id)
+    }
+
+    /// Content of the most recent "after" snapshot of a file within a session.
+    fn latest_session_snapshot(&self, session_id: Uuid, file_path: &str) -> Result<Option<Vec<u8>>> {
+        let content_hash: Option<String> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                r#"
+                SELECT fs.content_hash
+                FROM file_snapshots fs
+                JOIN interactions i ON i.id = fs.interaction_id
+                WHERE i.session_id = ?1 AND fs.file_path = ?2 AND fs.snapshot_type = 'after'
+                ORDER BY i.sequence_number DESC, fs.created_at DESC
+                LIMIT 1
+                "#,
+                params![session_id.to_string(), file_path],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+        match content_hash {
+            Some(hash) => self.get_file_content(&hash),
+            None => Ok(None),
+        }
+    }
+
+    /// Content of a file as it was before Claude first touched it within a
+    /// session. `None` if the first interaction touching the file has no
+    /// "before" snapshot, i.e. Claude created it.
+    fn earliest_session_snapshot(&self, session_id: Uuid, file_path: &str) -> Result<Option<Vec<u8>>> {
+        let first: Option<(String, String)> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                r#"
+                SELECT fs.snapshot_type, fs.content_hash
+                FROM file_snapshots fs
+                JOIN interactions i ON i.id = fs.interaction_id
+                WHERE i.session_id = ?1 AND fs.file_path = ?2
+                ORDER BY i.sequence_number ASC, fs.snapshot_type = 'before' DESC, fs.created_at ASC
+                LIMIT 1
+                "#,
+                params![session_id.to_string(), file_path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+        };
+        match first {
+            Some((snapshot_type, hash)) if snapshot_type == "before" => self.get_file_content(&hash),
+            _ => Ok(None),
+        }
+    }
+
+    /// Attribute each line of a file's final version in a session to Claude
+    /// or to the pre-existing file.
+    ///
+    /// The file as it was before Claude first touched it is diffed against
+    /// the latest "after" snapshot; lines added along the way are Claude's.
+    /// A file whose first snapshot is an "after" was created by Claude, even
+    /// if later interactions recorded "before" snapshots of it. Returns no lines if the
+    /// session never wrote the file or it is binary.
+    pub fn file_authorship(&self, session_id: Uuid, file_path: &str) -> Result<Vec<LineAuthorship>> {
+        let Some(after) = self.latest_session_snapshot(session_id, file_path)? else {
+            return Ok(Vec::new());
+        };
+        let before = self.earliest_session_snapshot(session_id, file_path)?;
+
+        let diff = crate::diff::compute_diff(before.as_deref(), Some(&after), 0);
+        if diff.is_binary {
+            return Ok(Vec::new());
+        }
+        let added: std::collections::HashSet<u32> = diff
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .filter(|line| line.change_type == crate::diff::DiffChangeType::Add)
+            .filter_map(|line| line.new_line_num)
+            .collect();
+
+        Ok(String::from_utf8_lossy(&after)
+            .lines()
+            .enumerate()
+            .map(|(i, content)| {
+                let line_number = i as u32 + 1;
+                LineAuthorship {
+                    line_number,
+                    content: content.to_string(),
+                    author: if added.contains(&line_number) {
+                        LineAuthor::Claude
+                    } else {
+                        LineAuthor::PreExisting
+                    },
+                }
+            })
+            .collect())
+    }
+
+    /// Input of the most recent invocation of a tool in a session.
+    pub fn latest_tool_input(&self, session_id: Uuid, tool_name: &str) -> Result<Option<serde_json::Value>> {
+        let conn = self.conn.lock().unwrap();
+        let input: Option<String> = conn
+            .query_row(
+                r#"
+                SELECT t.tool_input
+                FROM tool_invocations t
+                JOIN interactions i ON i.id = t.interaction_id
+                WHERE i.session_id = ?1 AND t.tool_name = ?2
+                ORDER BY i.sequence_number DESC, t.sequence_number DESC
+                LIMIT 1
+                "#,
+                params![session_id.to_string(), tool_name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(input.and_then(|s| serde_json::from_str(&s).ok()))
+    }
+
     /// List file changes for an interaction.
     pub fn list_file_changes(&self, interaction_id: Uuid) -> Result<Vec<FileChange>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             r#"
             SELECT
-                file_path,
-                MAX(CASE WHEN snapshot_type = 'before' THEN id END) as before_id,
-                MAX(CASE WHEN snapshot_type = 'after' THEN id END) as after_id
-            FROM file_snapshots
-            WHERE interaction_id = ?1
-            GROUP BY file_path
+                f.file_path,
+                (SELECT b.id FROM file_snapshots b
+                 WHERE b.interaction_id = f.interaction_id AND b.file_path = f.file_path
+                   AND b.snapshot_type = 'before'
+                 ORDER BY b.created_at ASC, b.rowid ASC LIMIT 1) as before_id,
+                (SELECT a.id FROM file_snapshots a
+                 WHERE a.interaction_id = f.interaction_id AND a.file_path = f.file_path
+                   AND a.snapshot_type = 'after'
+                 ORDER BY a.created_at DESC, a.rowid DESC LIMIT 1) as after_id
+            FROM file_snapshots f
+            WHERE f.interaction_id = ?1
+            GROUP BY f.file_path
             "#,
         )?;
 
@@ -1232,26 +3038,54 @@ impl InteractionStore {
         &self,
         interaction_id: Uuid,
         context_lines: usize,
+        options: &DiffOptions,
+    ) -> Result<Vec<FileChangeWithDiff>> {
+        self.file_changes_with_diffs_using(interaction_id, context_lines, options, crate::diff::compute_diff_with_options)
+    }
+
+    /// [`Self::get_file_changes_with_diffs`] with the diff function injected,
+    /// so tests can observe cache hits.
+    fn file_changes_with_diffs_using(
+        &self,
+        interaction_id: Uuid,
+        context_lines: usize,
+        options: &DiffOptions,
+        compute: impl Fn(Option<&[u8]>, Option<&[u8]>, usize, &DiffOptions) -> FileDiff,
     ) -> Result<Vec<FileChangeWithDiff>> {
         let changes = self.list_file_changes(interaction_id)?;
         let mut results = Vec::new();
 
         for change in changes {
-            let before_content = change
-                .before_snapshot_id
-                .and_then(|id| self.get_file_snapshot(id).ok().flatten())
-                .and_then(|snap| self.get_file_content(&snap.content_hash).ok().flatten());
-
-            let after_content = change
-                .after_snapshot_id
-                .and_then(|id| self.get_file_snapshot(id).ok().flatten())
-                .and_then(|snap| self.get_file_content(&snap.content_hash).ok().flatten());
-
-            let diff = crate::diff::compute_diff(
-                before_content.as_deref(),
-                after_content.as_deref(),
+            let key = DiffCacheKey {
+                before_hash: self.snapshot_hash(change.before_snapshot_id),
+                after_hash: self.snapshot_hash(change.after_snapshot_id),
                 context_lines,
-            );
+                options: *options,
+            };
+            let diff = match self.diff_cache.get(&key) {
+                Some(diff) => diff,
+                // A corrupt blob only costs this file its diff, not the whole listing
+                None => match (
+                    self.snapshot_content(key.before_hash.as_deref()),
+                    self.snapshot_content(key.after_hash.as_deref()),
+                ) {
+                    (Ok(before_content), Ok(after_content)) => {
+                        let diff = compute(before_content.as_deref(), after_content.as_deref(), context_lines, options);
+                        // Content pruned from under its snapshot could be stored
+                        // again later, so only cache diffs of complete content
+                        if before_content.is_some() == key.before_hash.is_some()
+                            && after_content.is_some() == key.after_hash.is_some()
+                        {
+                            self.diff_cache.insert(key, diff.clone());
+                        }
+                        diff
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        tracing::warn!(target: "clauset::db", "Skipping diff for {}: {}", change.file_path.display(), e);
+                        FileDiff::decode_error()
+                    }
+                },
+            };
 
             results.push(FileChangeWithDiff {
                 file_path: change.file_path,
@@ -1263,6 +3097,25 @@ impl InteractionStore {
         Ok(results)
     }
 
+    /// Content hash of a snapshot, `None` if there is no such snapshot.
+    fn snapshot_hash(&self, snapshot_id: Option<Uuid>) -> Option<String> {
+        snapshot_id
+            .and_then(|id| self.get_file_snapshot(id).ok().flatten())
+            .map(|snapshot| snapshot.content_hash)
+    }
+
+    /// Decompressed content for a snapshot's hash, `None` if there is no
+    /// snapshot or its content is missing. Only corrupt content is an error.
+    fn snapshot_content(&self, content_hash: Option<&str>) -> Result<Option<Vec<u8>>> {
+        let Some(content_hash) = content_hash else {
+            return Ok(None);
+        };
+        match self.get_file_content(content_hash) {
+            Err(e @ ClausetError::CorruptSnapshot { .. }) => Err(e),
+            result => Ok(result.ok().flatten()),
+        }
+    }
+
     /// Get unified diff string for a tool invocation's file changes.
     pub fn get_unified_diff(
         &self,
@@ -1301,22 +3154,315 @@ impl InteractionStore {
         Ok(Some(unified))
     }
 
-    // =========================================================================
-    // Cleanup & Retention
-    // =========================================================================
-
-    /// Clean up data older than the specified number of days.
+    /// Bundle one interaction into a self-contained JSON document for sharing:
+    /// the interaction with its context, every tool invocation, and each
+    /// changed file's before/after snapshot content inlined as base64.
     ///
-    /// Returns the number of interactions deleted.
-    pub fn cleanup_old_data(&self, retention_days: i64) -> Result<CleanupStats> {
-        let cutoff = Utc::now() - chrono::Duration::days(retention_days);
-        let cutoff_str = cutoff.to_rfc3339();
-
-        let conn = self.conn.lock().unwrap();
+    /// Snapshots larger than `MAX_SNAPSHOT_SIZE` are listed without content
+    /// (`content_base64` is null).
+    pub fn export_interaction_full(&self, id: Uuid) -> Result<serde_json::Value> {
+        use base64::Engine;
+
+        let interaction = self
+            .get_interaction(id)?
+            .ok_or(ClausetError::InteractionNotFound(id))?;
+        let context = self.interaction_context(id)?;
+        let tool_invocations = self.list_tool_invocations(id)?;
+
+        let snapshot_json = |snapshot_id: Option<Uuid>| -> Result<serde_json::Value> {
+            let Some(snapshot) = snapshot_id.map(|sid| self.get_file_snapshot(sid)).transpose()?.flatten() else {
+                return Ok(serde_json::Value::Null);
+            };
+            let content = if snapshot.file_size <= MAX_SNAPSHOT_SIZE {
+                self.get_file_content(&snapshot.content_hash)?
+                    .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+            } else {
+                None
+            };
+            Ok(serde_json::json!({
+                "content_hash": snapshot.content_hash,
+                "file_size": snapshot.file_size,
+                "created_at": snapshot.created_at,
+                "content_base64": content,
+            }))
+        };
+
+        let mut changes = self.list_file_changes(id)?;
+        changes.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        let file_changes = changes
+            .into_iter()
+            .map(|change| {
+                Ok(serde_json::json!({
+                    "file_path": change.file_path,
+                    "change_type": change.change_type,
+                    "before": snapshot_json(change.before_snapshot_id)?,
+                    "after": snapshot_json(change.after_snapshot_id)?,
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(serde_json::json!({
+            "version": 1,
+            "exported_at": Utc::now(),
+            "interaction": interaction,
+            "context": context,
+            "tool_invocations": tool_invocations,
+            "file_changes": file_changes,
+        }))
+    }
+
+    /// Build a git-style multi-file patch for every file changed in an interaction.
+    ///
+    /// Paths are made relative to the session's project directory so the output
+    /// can be applied with `git apply` from the project root. A file whose
+    /// snapshots can't be read is left out with a `# skipped <path>: <reason>`
+    /// line in its place. Returns an empty string if the interaction changed
+    /// no files.
+    pub fn interaction_patch(&self, interaction_id: Uuid, context_lines: usize) -> Result<String> {
+        let project_path: Option<String> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                r#"SELECT s.project_path FROM sessions s
+                   JOIN interactions i ON i.session_id = s.id
+                   WHERE i.id = ?1"#,
+                params![interaction_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+
+        let mut changes = self.list_file_changes(interaction_id)?;
+        changes.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        let read = |snapshot_id: Option<Uuid>| -> Result<Option<Vec<u8>>> {
+            match snapshot_id.map(|id| self.get_file_snapshot(id)).transpose()?.flatten() {
+                Some(snapshot) => self.get_file_content(&snapshot.content_hash),
+                None => Ok(None),
+            }
+        };
+
+        let mut patch = String::new();
+        for change in changes {
+            let path = project_path
+                .as_deref()
+                .and_then(|root| change.file_path.strip_prefix(root).ok())
+                .unwrap_or(&change.file_path)
+                .to_string_lossy()
+                .trim_start_matches('/')
+                .to_string();
+
+            let (before_content, after_content) =
+                match (read(change.before_snapshot_id), read(change.after_snapshot_id)) {
+                    (Ok(before), Ok(after)) => (before, after),
+                    (Err(e), _) | (_, Err(e)) => {
+                        patch.push_str(&format!("# skipped {}: {}\n", path, e));
+                        continue;
+                    }
+                };
+
+            if before_content == after_content {
+                continue;
+            }
+
+            let (old_path, new_path) = match change.change_type {
+                FileChangeType::Created => ("/dev/null".to_string(), format!("b/{}", path)),
+                FileChangeType::Deleted => (format!("a/{}", path), "/dev/null".to_string()),
+                FileChangeType::Modified => (format!("a/{}", path), format!("b/{}", path)),
+            };
+
+            patch.push_str(&format!("diff --git a/{} b/{}\n", path, path));
+            match change.change_type {
+                FileChangeType::Created => patch.push_str("new file mode 100644\n"),
+                FileChangeType::Deleted => patch.push_str("deleted file mode 100644\n"),
+                FileChangeType::Modified => {}
+            }
+            patch.push_str(&crate::diff::generate_unified_diff(
+                before_content.as_deref(),
+                after_content.as_deref(),
+                &old_path,
+                &new_path,
+                context_lines,
+            ));
+        }
+
+        Ok(patch)
+    }
+
+    // =========================================================================
+    // Hook Event Log
+    // =========================================================================
+
+    /// Append a hook event as received, with the session usage at that moment.
+    ///
+    /// Returns the event's sequence number.
+    pub fn record_hook_event(&self, payload: &HookEventPayload, usage: &HookUsage) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            INSERT INTO hook_events (
+                session_id, event_name, payload, cost_usd, input_tokens,
+                output_tokens, thinking_tokens, context_percent, received_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+            params![
+                payload.clauset_session_id.to_string(),
+                payload.hook_event_name,
+                serde_json::to_string(payload)?,
+                usage.cost_usd,
+                usage.input_tokens as i64,
+                usage.output_tokens as i64,
+                usage.thinking_tokens as i64,
+                usage.context_percent,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// List a session's hook events in the order they were received.
+    pub fn list_hook_events(&self, session_id: Uuid) -> Result<Vec<RecordedHookEvent>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT sequence, payload, cost_usd, input_tokens, output_tokens,
+                   thinking_tokens, context_percent, received_at
+            FROM hook_events
+            WHERE session_id = ?1
+            ORDER BY sequence
+            "#,
+        )?;
+        let rows = stmt
+            .query_map(params![session_id.to_string()], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    HookUsage {
+                        cost_usd: row.get(2)?,
+                        input_tokens: row.get::<_, i64>(3)? as u64,
+                        output_tokens: row.get::<_, i64>(4)? as u64,
+                        thinking_tokens: row.get::<_, i64>(5)? as u64,
+                        context_percent: row.get(6)?,
+                    },
+                    row.get::<_, String>(7)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(sequence, payload, usage, received_at)| {
+                Ok(RecordedHookEvent {
+                    sequence,
+                    payload: serde_json::from_str(&payload)?,
+                    usage,
+                    received_at: DateTime::parse_from_rfc3339(&received_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })
+            .collect()
+    }
+
+    /// Store a hook event with an unrecognized name, payload as received.
+    ///
+    /// Returns the event's id.
+    pub fn record_custom_hook_event(
+        &self,
+        session_id: Uuid,
+        event_name: &str,
+        payload: &serde_json::Value,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            INSERT INTO custom_hook_events (session_id, event_name, payload, received_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![
+                session_id.to_string(),
+                event_name,
+                serde_json::to_string(payload)?,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// List a session's custom hook events in the order they were received.
+    pub fn list_custom_hook_events(&self, session_id: Uuid) -> Result<Vec<CustomHookEvent>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, event_name, payload, received_at
+            FROM custom_hook_events
+            WHERE session_id = ?1
+            ORDER BY id
+            "#,
+        )?;
+        let rows = stmt
+            .query_map(params![session_id.to_string()], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, event_name, payload, received_at)| {
+                Ok(CustomHookEvent {
+                    id,
+                    session_id,
+                    event_name,
+                    payload: serde_json::from_str(&payload)?,
+                    received_at: DateTime::parse_from_rfc3339(&received_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                })
+            })
+            .collect()
+    }
+
+    // =========================================================================
+    // Cleanup & Retention
+    // =========================================================================
+
+    /// Clean up data older than the specified number of days.
+    ///
+    /// Interactions of pinned sessions are kept regardless of age.
+    /// Returns the number of interactions deleted.
+    pub fn cleanup_old_data(&self, retention_days: i64) -> Result<CleanupStats> {
+        let cutoff = Utc::now() - chrono::Duration::days(retention_days);
+        let cutoff_str = cutoff.to_rfc3339();
+
+        let conn = self.conn.lock().unwrap();
+
+        // The sessions table belongs to SessionStore and may predate pinning
+        let has_pinning: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('sessions') WHERE name = 'is_pinned'",
+            [],
+            |row| row.get(0),
+        )?;
+        let sql = if has_pinning {
+            r#"
+            DELETE FROM interactions
+            WHERE started_at < ?1
+              AND session_id NOT IN (SELECT id FROM sessions WHERE is_pinned = 1)
+            "#
+        } else {
+            "DELETE FROM interactions WHERE started_at < ?1"
+        };
 
         // Delete old interactions (cascades to tool_invocations and file_snapshots)
-        let interactions_deleted = conn.execute(
-            "DELETE FROM interactions WHERE started_at < ?1",
+        let interactions_deleted = conn.execute(sql, params![&cutoff_str])?;
+
+        conn.execute(
+            "DELETE FROM hook_events WHERE received_at < ?1",
+            params![&cutoff_str],
+        )?;
+        conn.execute(
+            "DELETE FROM custom_hook_events WHERE received_at < ?1",
             params![&cutoff_str],
         )?;
 
@@ -1342,6 +3488,104 @@ impl InteractionStore {
         })
     }
 
+    /// Delete all but the newest `keep_last` interactions of a session.
+    ///
+    /// Tool invocations and file snapshots go with their interactions; file
+    /// contents no longer referenced by any snapshot are removed as well.
+    pub fn truncate_session_history(&self, session_id: Uuid, keep_last: u32) -> Result<CleanupStats> {
+        let conn = self.conn.lock().unwrap();
+
+        let interactions_deleted = conn.execute(
+            r#"
+            DELETE FROM interactions
+            WHERE session_id = ?1 AND id NOT IN (
+                SELECT id FROM interactions
+                WHERE session_id = ?1
+                ORDER BY sequence_number DESC
+                LIMIT ?2
+            )
+            "#,
+            params![session_id.to_string(), keep_last],
+        )?;
+
+        let contents_deleted = conn.execute(
+            "DELETE FROM file_contents WHERE reference_count <= 0",
+            [],
+        )?;
+
+        Ok(CleanupStats {
+            interactions_deleted: interactions_deleted as u32,
+            contents_deleted: contents_deleted as u32,
+        })
+    }
+
+    /// Count rows whose parent row is missing, which can happen after imports
+    /// or deletes made while foreign keys weren't enforced.
+    pub fn check_integrity(&self) -> Result<IntegrityReport> {
+        let conn = self.conn.lock().unwrap();
+        Self::integrity_report(&conn)
+    }
+
+    /// Remove the orphans reported by [`Self::check_integrity`] and turn on
+    /// foreign key enforcement. Snapshots pointing at a missing tool
+    /// invocation keep their interaction and lose the tool link.
+    ///
+    /// Returns the report from before the repair.
+    pub fn repair_integrity(&self) -> Result<IntegrityReport> {
+        let mut conn = self.conn.lock().unwrap();
+        let report = Self::integrity_report(&conn)?;
+
+        // Can't be changed inside a transaction
+        conn.execute_batch("PRAGMA foreign_keys = ON")?;
+        let tx = conn.transaction()?;
+        tx.execute_batch(
+            r#"
+            DELETE FROM interactions
+            WHERE session_id NOT IN (SELECT id FROM sessions);
+
+            DELETE FROM tool_invocations
+            WHERE interaction_id NOT IN (SELECT id FROM interactions);
+
+            DELETE FROM file_snapshots
+            WHERE interaction_id NOT IN (SELECT id FROM interactions);
+
+            UPDATE file_snapshots SET tool_invocation_id = NULL
+            WHERE tool_invocation_id IS NOT NULL
+              AND tool_invocation_id NOT IN (SELECT id FROM tool_invocations);
+
+            DELETE FROM file_contents WHERE reference_count <= 0;
+            "#,
+        )?;
+        tx.commit()?;
+
+        Ok(report)
+    }
+
+    fn integrity_report(conn: &Connection) -> Result<IntegrityReport> {
+        let count = |sql: &str| -> Result<u64> {
+            Ok(conn.query_row(sql, [], |row| row.get::<_, i64>(0))? as u64)
+        };
+        Ok(IntegrityReport {
+            foreign_keys_enabled: count("PRAGMA foreign_keys")? == 1,
+            orphaned_interactions: count(
+                "SELECT COUNT(*) FROM interactions WHERE session_id NOT IN (SELECT id FROM sessions)",
+            )?,
+            orphaned_tool_invocations: count(
+                "SELECT COUNT(*) FROM tool_invocations WHERE interaction_id NOT IN (SELECT id FROM interactions)",
+            )?,
+            orphaned_snapshots: count(
+                "SELECT COUNT(*) FROM file_snapshots WHERE interaction_id NOT IN (SELECT id FROM interactions)",
+            )?,
+            dangling_snapshot_tool_links: count(
+                r#"
+                SELECT COUNT(*) FROM file_snapshots
+                WHERE tool_invocation_id IS NOT NULL
+                  AND tool_invocation_id NOT IN (SELECT id FROM tool_invocations)
+                "#,
+            )?,
+        })
+    }
+
     /// Vacuum the database to reclaim space.
     pub fn vacuum(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -1349,9 +3593,25 @@ impl InteractionStore {
         Ok(())
     }
 
+    /// Copy the whole database to `path` with SQLite's online backup API,
+    /// replacing any file already there. Safe while the store is in use.
+    pub fn backup_to(&self, path: &Path) -> Result<()> {
+        // Copied from a reader in a single step, so the backup is one
+        // consistent snapshot and (in WAL mode) writers carry on meanwhile
+        let conn = self.reader();
+        let mut dest = Connection::open(path)?;
+        let backup = rusqlite::backup::Backup::new(&conn, &mut dest)?;
+        loop {
+            match backup.step(-1)? {
+                rusqlite::backup::StepResult::Done => return Ok(()),
+                _ => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    }
+
     /// Get storage statistics.
     pub fn get_storage_stats(&self) -> Result<StorageStats> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
 
         let interaction_count: i64 = conn.query_row(
             "SELECT COUNT(*) FROM interactions",
@@ -1403,6 +3663,88 @@ impl InteractionStore {
         })
     }
 
+    /// Storage used by each session, largest compressed size first.
+    ///
+    /// File contents are deduplicated across sessions, so each content's size
+    /// is split evenly between the snapshots referencing it; the per-session
+    /// sizes add up to the stored total.
+    pub fn storage_by_session(&self, limit: usize) -> Result<Vec<SessionStorageEntry>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            WITH refs AS (
+                SELECT content_hash, COUNT(*) AS n FROM file_snapshots GROUP BY content_hash
+            ),
+            shares AS (
+                SELECT i.session_id,
+                       COUNT(*) AS snapshot_count,
+                       SUM(fc.original_size * 1.0 / refs.n) AS content_size,
+                       SUM(LENGTH(fc.compressed_content) * 1.0 / refs.n) AS compressed_size
+                FROM file_snapshots fs
+                JOIN interactions i ON i.id = fs.interaction_id
+                JOIN file_contents fc ON fc.content_hash = fs.content_hash
+                JOIN refs ON refs.content_hash = fs.content_hash
+                GROUP BY i.session_id
+            ),
+            counts AS (
+                SELECT i.session_id,
+                       COUNT(DISTINCT i.id) AS interaction_count,
+                       COUNT(t.id) AS tool_count
+                FROM interactions i
+                LEFT JOIN tool_invocations t ON t.interaction_id = i.id
+                GROUP BY i.session_id
+            )
+            SELECT c.session_id, c.interaction_count, c.tool_count,
+                   COALESCE(s.snapshot_count, 0),
+                   ROUND(COALESCE(s.content_size, 0)),
+                   ROUND(COALESCE(s.compressed_size, 0))
+            FROM counts c
+            LEFT JOIN shares s ON s.session_id = c.session_id
+            ORDER BY COALESCE(s.compressed_size, 0) DESC, c.session_id
+            LIMIT ?1
+            "#,
+        )?;
+        let entries = stmt
+            .query_map(params![limit as i64], |row| {
+                let session_id: String = row.get(0)?;
+                Ok(SessionStorageEntry {
+                    session_id: Uuid::parse_str(&session_id).unwrap_or_default(),
+                    interaction_count: row.get::<_, i64>(1)? as u64,
+                    tool_count: row.get::<_, i64>(2)? as u64,
+                    snapshot_count: row.get::<_, i64>(3)? as u64,
+                    content_size: row.get::<_, f64>(4)? as u64,
+                    compressed_size: row.get::<_, f64>(5)? as u64,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Compressed bytes deleting a session would free: file contents whose
+    /// every reference is a snapshot of this session. Content shared with
+    /// other sessions stays stored and is not counted.
+    pub fn reclaimable_bytes(&self, session_id: Uuid) -> Result<u64> {
+        let conn = self.reader();
+        let bytes: i64 = conn.query_row(
+            r#"
+            WITH session_refs AS (
+                SELECT fs.content_hash, COUNT(*) AS n
+                FROM file_snapshots fs
+                JOIN interactions i ON i.id = fs.interaction_id
+                WHERE i.session_id = ?1
+                GROUP BY fs.content_hash
+            )
+            SELECT COALESCE(SUM(LENGTH(fc.compressed_content)), 0)
+            FROM file_contents fc
+            JOIN session_refs r ON r.content_hash = fc.content_hash
+            WHERE fc.reference_count <= r.n
+            "#,
+            params![session_id.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(bytes as u64)
+    }
+
     // =========================================================================
     // Full-Text Search
     // =========================================================================
@@ -1417,23 +3759,34 @@ impl InteractionStore {
     /// - "Re" → "\"Re\"*" → matches "Read", "Return", "Rebuild"
     /// - "describe project" → "\"describe\"* AND \"project\"*"
     /// - "package.json" → "\"package.json\"*" → handles special chars
-    fn escape_fts5_query(query: &str) -> String {
+    ///
+    /// Tokens without letters or digits are dropped, since the FTS5 tokenizer
+    /// discards punctuation and they could never match. Terms past `limits`
+    /// are dropped too. Returns `None` when nothing searchable is left.
+    fn escape_fts5_query(query: &str, limits: FtsQueryLimits) -> Option<String> {
+        let mut remaining_chars = limits.max_chars;
         let tokens: Vec<String> = query
             .split_whitespace()
-            .filter(|t| !t.is_empty())
-            .map(|t| {
+            .filter(|t| t.chars().any(char::is_alphanumeric))
+            .take(limits.max_tokens)
+            .map_while(|t| {
+                let kept: String = t.chars().take(remaining_chars).collect();
+                if !kept.chars().any(char::is_alphanumeric) {
+                    return None;
+                }
+                remaining_chars -= kept.chars().count();
                 // Escape internal double quotes and wrap in quotes with wildcard
-                let escaped = t.replace('"', "\"\"");
-                format!("\"{}\"*", escaped)
+                let escaped = kept.replace('"', "\"\"");
+                Some(format!("\"{}\"*", escaped))
             })
             .collect();
 
         if tokens.is_empty() {
-            return String::new();
+            return None;
         }
 
         // Join with AND - all tokens must match as prefixes
-        tokens.join(" AND ")
+        Some(tokens.join(" AND "))
     }
 
     /// Search interactions using full-text search.
@@ -1447,10 +3800,83 @@ impl InteractionStore {
         limit: usize,
         offset: usize,
     ) -> Result<Vec<SearchResult>> {
-        let conn = self.conn.lock().unwrap();
-        let escaped_query = Self::escape_fts5_query(query);
-
         let mut results = Vec::new();
+        self.each_interaction_match(query, session_id, limit, offset, &mut |result| {
+            results.push(result);
+            true
+        })?;
+        Ok(results)
+    }
+
+    /// Search interactions whose prompt or summary matches `query` and that
+    /// changed a file matching `file_pattern`, a glob such as `*.sql` (`*`
+    /// also matches across directories), in relevance order.
+    pub fn search_interactions_with_file_filter(
+        &self,
+        query: &str,
+        file_pattern: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let Some(escaped_query) = Self::escape_fts5_query(query, self.fts_query_limits) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT i.*, bm25(interactions_fts) as rank
+            FROM interactions_fts fts
+            JOIN interactions i ON i.rowid = fts.rowid
+            WHERE interactions_fts MATCH ?1
+            AND EXISTS (
+                SELECT 1 FROM file_snapshots fs
+                WHERE fs.interaction_id = i.id AND fs.file_path GLOB ?2
+            )
+            ORDER BY rank
+            LIMIT ?3 OFFSET ?4
+            "#,
+        )?;
+
+        let results = stmt
+            .query_map(
+                params![escaped_query, file_pattern, limit as i64, offset as i64],
+                |row| {
+                    let rank: f64 = row.get("rank")?;
+                    Ok(SearchResult {
+                        interaction: self.row_to_interaction(row)?,
+                        relevance_score: -rank,
+                        matched_field: SearchField::Prompt,
+                    })
+                },
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(results)
+    }
+
+    /// Visit interactions matching a full-text query, in relevance order.
+    /// Stops early when `visit` returns false.
+    fn each_interaction_match(
+        &self,
+        query: &str,
+        session_id: Option<Uuid>,
+        limit: usize,
+        offset: usize,
+        visit: &mut dyn FnMut(SearchResult) -> bool,
+    ) -> Result<()> {
+        let Some(escaped_query) = Self::escape_fts5_query(query, self.fts_query_limits) else {
+            return Ok(());
+        };
+        let conn = self.reader();
+
+        let to_result = |row: &rusqlite::Row| {
+            let interaction = self.row_to_interaction(row)?;
+            let rank: f64 = row.get("rank")?;
+            Ok(SearchResult {
+                interaction,
+                relevance_score: -rank,
+                matched_field: SearchField::Prompt,
+            })
+        };
 
         if let Some(sid) = session_id {
             let mut stmt = conn.prepare(
@@ -1467,19 +3893,13 @@ impl InteractionStore {
 
             let rows = stmt.query_map(
                 params![&escaped_query, sid.to_string(), limit as i64, offset as i64],
-                |row| {
-                    let interaction = self.row_to_interaction(row)?;
-                    let rank: f64 = row.get("rank")?;
-                    Ok(SearchResult {
-                        interaction,
-                        relevance_score: -rank,
-                        matched_field: SearchField::Prompt,
-                    })
-                },
+                to_result,
             )?;
 
             for result in rows {
-                results.push(result?);
+                if !visit(result?) {
+                    break;
+                }
             }
         } else {
             let mut stmt = conn.prepare(
@@ -1493,22 +3913,16 @@ impl InteractionStore {
                 "#,
             )?;
 
-            let rows = stmt.query_map(params![&escaped_query, limit as i64, offset as i64], |row| {
-                let interaction = self.row_to_interaction(row)?;
-                let rank: f64 = row.get("rank")?;
-                Ok(SearchResult {
-                    interaction,
-                    relevance_score: -rank,
-                    matched_field: SearchField::Prompt,
-                })
-            })?;
+            let rows = stmt.query_map(params![&escaped_query, limit as i64, offset as i64], to_result)?;
 
             for result in rows {
-                results.push(result?);
+                if !visit(result?) {
+                    break;
+                }
             }
         }
 
-        Ok(results)
+        Ok(())
     }
 
     /// Search tool invocations by file path or input content.
@@ -1519,10 +3933,28 @@ impl InteractionStore {
         limit: usize,
         offset: usize,
     ) -> Result<Vec<ToolInvocation>> {
-        let conn = self.conn.lock().unwrap();
-        let escaped_query = Self::escape_fts5_query(query);
-
         let mut results = Vec::new();
+        self.each_tool_invocation_match(query, interaction_id, limit, offset, &mut |invocation| {
+            results.push(invocation);
+            true
+        })?;
+        Ok(results)
+    }
+
+    /// Visit tool invocations matching a full-text query, in relevance order.
+    /// Stops early when `visit` returns false.
+    fn each_tool_invocation_match(
+        &self,
+        query: &str,
+        interaction_id: Option<Uuid>,
+        limit: usize,
+        offset: usize,
+        visit: &mut dyn FnMut(ToolInvocation) -> bool,
+    ) -> Result<()> {
+        let Some(escaped_query) = Self::escape_fts5_query(query, self.fts_query_limits) else {
+            return Ok(());
+        };
+        let conn = self.reader();
 
         if let Some(iid) = interaction_id {
             let mut stmt = conn.prepare(
@@ -1543,7 +3975,9 @@ impl InteractionStore {
             )?;
 
             for result in rows {
-                results.push(result?);
+                if !visit(result?) {
+                    break;
+                }
             }
         } else {
             let mut stmt = conn.prepare(
@@ -1562,11 +3996,133 @@ impl InteractionStore {
             })?;
 
             for result in rows {
-                results.push(result?);
+                if !visit(result?) {
+                    break;
+                }
             }
         }
 
-        Ok(results)
+        Ok(())
+    }
+
+    /// Every file path that has a snapshot, with snapshot and session counts,
+    /// most recently modified first.
+    pub fn all_touched_files(&self, limit: usize, offset: usize) -> Result<Vec<GlobalFileEntry>> {
+        let conn = self.reader();
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                fs.file_path,
+                COUNT(*) as snapshot_count,
+                COUNT(DISTINCT i.session_id) as session_count,
+                MAX(fs.created_at) as last_modified_at
+            FROM file_snapshots fs INDEXED BY idx_file_snapshots_file_path
+            JOIN interactions i ON fs.interaction_id = i.id
+            GROUP BY fs.file_path
+            ORDER BY last_modified_at DESC, fs.file_path
+            LIMIT ?1 OFFSET ?2
+            "#,
+        )?;
+
+        let rows = stmt.query_map(params![limit as i64, offset as i64], |row| {
+            Ok(GlobalFileEntry {
+                file_path: PathBuf::from(row.get::<_, String>(0)?),
+                snapshot_count: row.get::<_, i64>(1)? as u32,
+                session_count: row.get::<_, i64>(2)? as u32,
+                last_modified_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_default(),
+            })
+        })?;
+
+        Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// Run an ad-hoc SQL query that can only read, returning at most
+    /// `max_rows` rows.
+    ///
+    /// Anything but a single SELECT is rejected with
+    /// [`ClausetError::QueryRejected`]: the statement must start with SELECT
+    /// or WITH and be read-only, and an authorizer denies every action other
+    /// than reads and function calls while it is prepared. A query still
+    /// running after `timeout` is interrupted with
+    /// [`ClausetError::QueryTimeout`], so a runaway `WITH RECURSIVE` can't pin
+    /// the reader connection.
+    pub fn query_readonly(&self, sql: &str, max_rows: usize, timeout: Duration) -> Result<QueryResult> {
+        let keyword = sql
+            .trim_start()
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        if keyword != "SELECT" && keyword != "WITH" {
+            return Err(ClausetError::QueryRejected("only SELECT statements are allowed".to_string()));
+        }
+
+        let conn = self.reader();
+        conn.authorizer(Some(|context: AuthContext<'_>| match context.action {
+            AuthAction::Select | AuthAction::Read { .. } | AuthAction::Function { .. } | AuthAction::Recursive => {
+                Authorization::Allow
+            }
+            _ => Authorization::Deny,
+        }));
+        let deadline = Instant::now() + timeout;
+        conn.progress_handler(QUERY_PROGRESS_OPS, Some(move || Instant::now() >= deadline));
+        let result = Self::run_readonly_query(&conn, sql, max_rows);
+        // The connection is shared with the store's own queries
+        conn.authorizer(None::<fn(AuthContext<'_>) -> Authorization>);
+        conn.progress_handler(0, None::<fn() -> bool>);
+        match result {
+            Err(ClausetError::DatabaseError(rusqlite::Error::SqliteFailure(e, _)))
+                if e.code == rusqlite::ErrorCode::OperationInterrupted =>
+            {
+                Err(ClausetError::QueryTimeout(timeout))
+            }
+            result => result,
+        }
+    }
+
+    fn run_readonly_query(conn: &Connection, sql: &str, max_rows: usize) -> Result<QueryResult> {
+        use base64::Engine;
+
+        let rejected = |e: rusqlite::Error| ClausetError::QueryRejected(e.to_string());
+        let mut batch = Batch::new(conn, sql);
+        let mut stmt = batch
+            .next()
+            .map_err(rejected)?
+            .ok_or_else(|| ClausetError::QueryRejected("empty query".to_string()))?;
+        if !stmt.readonly() {
+            return Err(ClausetError::QueryRejected("statement is not read-only".to_string()));
+        }
+        if batch.next().map_err(rejected)?.is_some() {
+            return Err(ClausetError::QueryRejected("only a single statement is allowed".to_string()));
+        }
+
+        let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        let mut result_rows = stmt.query([])?;
+        while let Some(row) = result_rows.next()? {
+            if rows.len() == max_rows {
+                truncated = true;
+                break;
+            }
+            let values = (0..columns.len())
+                .map(|i| {
+                    Ok(match row.get_ref(i)? {
+                        ValueRef::Null => serde_json::Value::Null,
+                        ValueRef::Integer(n) => n.into(),
+                        ValueRef::Real(x) => x.into(),
+                        ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+                        ValueRef::Blob(blob) => base64::engine::general_purpose::STANDARD.encode(blob).into(),
+                    })
+                })
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows.push(values);
+        }
+
+        Ok(QueryResult { columns, rows, truncated })
     }
 
     /// Search for files by path pattern.
@@ -1577,7 +4133,23 @@ impl InteractionStore {
         path_pattern: &str,
         limit: usize,
     ) -> Result<Vec<FilePathMatch>> {
-        let conn = self.conn.lock().unwrap();
+        let mut results = Vec::new();
+        self.each_file_path_match(path_pattern, limit, &mut |file_match| {
+            results.push(file_match);
+            true
+        })?;
+        Ok(results)
+    }
+
+    /// Visit file snapshots whose path contains `path_pattern`, newest first.
+    /// Stops early when `visit` returns false.
+    fn each_file_path_match(
+        &self,
+        path_pattern: &str,
+        limit: usize,
+        visit: &mut dyn FnMut(FilePathMatch) -> bool,
+    ) -> Result<()> {
+        let conn = self.reader();
 
         let mut stmt = conn.prepare(
             r#"
@@ -1597,38 +4169,70 @@ impl InteractionStore {
         )?;
 
         let pattern = format!("%{}%", path_pattern);
-        let results = stmt
-            .query_map(params![pattern, limit as i64], |row| {
-                Ok(FilePathMatch {
-                    file_path: PathBuf::from(row.get::<_, String>(0)?),
-                    interaction_id: Uuid::parse_str(&row.get::<_, String>(1)?).unwrap_or_default(),
-                    session_id: Uuid::parse_str(&row.get::<_, String>(2)?).unwrap_or_default(),
-                    modified_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_default(),
-                    snapshot_count: row.get::<_, i64>(4)? as u32,
-                })
-            })?
-            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let rows = stmt.query_map(params![pattern, limit as i64], |row| {
+            Ok(FilePathMatch {
+                file_path: PathBuf::from(row.get::<_, String>(0)?),
+                interaction_id: Uuid::parse_str(&row.get::<_, String>(1)?).unwrap_or_default(),
+                session_id: Uuid::parse_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                modified_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_default(),
+                snapshot_count: row.get::<_, i64>(4)? as u32,
+            })
+        })?;
 
-        Ok(results)
+        for result in rows {
+            if !visit(result?) {
+                break;
+            }
+        }
+
+        Ok(())
     }
 
-    /// Global search across prompts, files, and tool inputs.
+    /// Global search across prompts, files, and tool inputs, with each
+    /// category capped by `options` and all results merged into `ranking`.
     pub fn global_search(
         &self,
         query: &str,
-        limit: usize,
+        options: &GlobalSearchOptions,
     ) -> Result<GlobalSearchResults> {
-        let interactions = self.search_interactions(query, None, limit, 0)?;
-        let tools = self.search_tool_invocations(query, None, limit, 0)?;
-        let files = self.search_files_by_path(query, limit)?;
+        let interactions = self.search_interactions(query, None, options.interaction_limit, 0)?;
+        let tools = self.search_tool_invocations(query, None, options.tool_limit, 0)?;
+        let files = self.search_files_by_path(query, options.file_limit)?;
 
-        Ok(GlobalSearchResults {
-            interactions,
-            tool_invocations: tools,
-            file_matches: files,
-        })
+        let mut results = GlobalSearchResults::grouped(interactions, tools, files);
+        results.rank();
+        Ok(results)
+    }
+
+    /// Global search that hands each result to `emit` as soon as it is read.
+    ///
+    /// Emits interaction matches first, then tool invocations, then file
+    /// matches. Stops early when `emit` returns false.
+    pub fn global_search_each(
+        &self,
+        query: &str,
+        limit: usize,
+        mut emit: impl FnMut(GlobalSearchItem) -> bool,
+    ) -> Result<()> {
+        let mut open = true;
+        self.each_interaction_match(query, None, limit, 0, &mut |result| {
+            open = emit(GlobalSearchItem::Interaction(result));
+            open
+        })?;
+        if open {
+            self.each_tool_invocation_match(query, None, limit, 0, &mut |invocation| {
+                open = emit(GlobalSearchItem::ToolInvocation(invocation));
+                open
+            })?;
+        }
+        if open {
+            self.each_file_path_match(query, limit, &mut |file_match| {
+                emit(GlobalSearchItem::File(file_match))
+            })?;
+        }
+        Ok(())
     }
 
     // =========================================================================
@@ -1637,7 +4241,7 @@ impl InteractionStore {
 
     /// Get total cost and token usage for a session.
     pub fn get_session_analytics(&self, session_id: Uuid) -> Result<SessionAnalytics> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
 
         let row = conn.query_row(
             r#"
@@ -1646,6 +4250,7 @@ impl InteractionStore {
                 COALESCE(SUM(cost_usd_delta), 0.0) as total_cost_usd,
                 COALESCE(SUM(input_tokens_delta), 0) as total_input_tokens,
                 COALESCE(SUM(output_tokens_delta), 0) as total_output_tokens,
+                COALESCE(SUM(thinking_tokens_delta), 0) as total_thinking_tokens,
                 MIN(started_at) as first_interaction_at,
                 MAX(started_at) as last_interaction_at
             FROM interactions
@@ -1659,12 +4264,13 @@ impl InteractionStore {
                     total_cost_usd: row.get(1)?,
                     total_input_tokens: row.get::<_, i64>(2)? as u64,
                     total_output_tokens: row.get::<_, i64>(3)? as u64,
+                    total_thinking_tokens: row.get::<_, i64>(4)? as u64,
                     first_interaction_at: row
-                        .get::<_, Option<String>>(4)?
+                        .get::<_, Option<String>>(5)?
                         .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
                         .map(|dt| dt.with_timezone(&Utc)),
                     last_interaction_at: row
-                        .get::<_, Option<String>>(5)?
+                        .get::<_, Option<String>>(6)?
                         .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
                         .map(|dt| dt.with_timezone(&Utc)),
                 })
@@ -1674,9 +4280,245 @@ impl InteractionStore {
         Ok(row)
     }
 
+    /// Interactions and file changes per hour, and average turn duration, over
+    /// a session's active span.
+    pub fn session_velocity(&self, session_id: Uuid) -> Result<SessionVelocity> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT i.started_at, i.ended_at,
+                   (SELECT COUNT(DISTINCT f.file_path) FROM file_snapshots f WHERE f.interaction_id = i.id)
+            FROM interactions i
+            WHERE i.session_id = ?1
+            "#,
+        )?;
+        let rows = stmt
+            .query_map(params![session_id.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, i64>(2)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let parse = |s: &str| DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc));
+        let mut span: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+        let mut files_changed = 0;
+        let mut durations = Vec::new();
+        for (started_at, ended_at, files) in &rows {
+            files_changed += *files as u32;
+            let Some(start) = parse(started_at) else { continue };
+            let end = ended_at.as_deref().and_then(parse);
+            if let Some(end) = end {
+                durations.push((end - start).num_milliseconds() as f64 / 1000.0);
+            }
+            let end = end.unwrap_or(start).max(start);
+            span = Some(match span {
+                Some((first, last)) => (first.min(start), last.max(end)),
+                None => (start, end),
+            });
+        }
+
+        let active_span_secs = span.map_or(0.0, |(first, last)| (last - first).num_milliseconds() as f64 / 1000.0);
+        let per_hour = |count: u32| (active_span_secs > 0.0).then(|| count as f64 * 3600.0 / active_span_secs);
+        Ok(SessionVelocity {
+            session_id,
+            interaction_count: rows.len() as u32,
+            files_changed,
+            active_span_secs,
+            interactions_per_hour: per_hour(rows.len() as u32),
+            files_changed_per_hour: per_hour(files_changed),
+            avg_turn_duration_secs: (!durations.is_empty())
+                .then(|| durations.iter().sum::<f64>() / durations.len() as f64),
+        })
+    }
+
+    /// Number of distinct changed files per language in a session, most
+    /// common first. Files with an unrecognized extension are left out.
+    pub fn language_breakdown(&self, session_id: Uuid) -> Result<Vec<(String, u32)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT DISTINCT f.file_path
+            FROM file_snapshots f
+            JOIN interactions i ON f.interaction_id = i.id
+            WHERE i.session_id = ?1
+            "#,
+        )?;
+        let paths = stmt
+            .query_map(params![session_id.to_string()], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let mut counts: HashMap<&'static str, u32> = HashMap::new();
+        for path in &paths {
+            if let Some(language) = detect_language(Path::new(path)) {
+                *counts.entry(language).or_default() += 1;
+            }
+        }
+        let mut breakdown: Vec<(String, u32)> =
+            counts.into_iter().map(|(language, count)| (language.to_string(), count)).collect();
+        breakdown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(breakdown)
+    }
+
+    /// Running total of a session's cost: each interaction's start time with
+    /// the cumulative cost up to and including it, in interaction order.
+    pub fn session_cumulative_cost(&self, session_id: Uuid) -> Result<Vec<(DateTime<Utc>, f64)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT started_at, SUM(cost_usd_delta) OVER (ORDER BY sequence_number)
+            FROM interactions
+            WHERE session_id = ?1
+            ORDER BY sequence_number
+            "#,
+        )?;
+        let rows = stmt
+            .query_map(params![session_id.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(started_at, total)| {
+                let started_at = DateTime::parse_from_rfc3339(&started_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now());
+                (started_at, total)
+            })
+            .collect())
+    }
+
+    /// Compose a one-line summary of a session from its interactions.
+    ///
+    /// Combines the first prompt (truncated) with the number of files changed
+    /// and tool calls made. Returns an empty string if the session has no
+    /// interactions.
+    pub fn generate_session_summary(&self, session_id: Uuid) -> Result<String> {
+        let conn = self.conn.lock().unwrap();
+
+        let first_prompt: Option<String> = conn
+            .query_row(
+                r#"
+                SELECT user_prompt FROM interactions
+                WHERE session_id = ?1
+                ORDER BY sequence_number ASC
+                LIMIT 1
+                "#,
+                params![session_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(first_prompt) = first_prompt else {
+            return Ok(String::new());
+        };
+
+        let (files_changed, tool_calls): (i64, i64) = conn.query_row(
+            r#"
+            SELECT
+                COUNT(DISTINCT CASE
+                    WHEN t.tool_name IN ('Write', 'Edit', 'MultiEdit', 'NotebookEdit')
+                    THEN t.file_path
+                END),
+                COUNT(t.id)
+            FROM tool_invocations t
+            JOIN interactions i ON i.id = t.interaction_id
+            WHERE i.session_id = ?1
+            "#,
+            params![session_id.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(format!(
+            "{} · {} · {}",
+            summary_snippet(&first_prompt),
+            plural(files_changed, "file changed", "files changed"),
+            plural(tool_calls, "tool call", "tool calls"),
+        ))
+    }
+
+    /// Fill in missing assistant summaries from the session's Claude transcript.
+    ///
+    /// Returns the number of interactions updated; 0 if the session or its
+    /// transcript can't be found.
+    pub fn backfill_summaries(&self, session_id: Uuid) -> Result<u32> {
+        let session: Option<(String, String)> = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                "SELECT claude_session_id, project_path FROM sessions WHERE id = ?1",
+                params![session_id.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+        };
+        let Some(transcript) = session.and_then(|(claude_id, project)| {
+            crate::get_transcript_path(&claude_id, Path::new(&project))
+        }) else {
+            return Ok(0);
+        };
+        self.backfill_summaries_from_transcript(session_id, &transcript)
+    }
+
+    /// Fill in missing assistant summaries from a transcript file.
+    ///
+    /// Each interaction is matched to the transcript turn with the same prompt
+    /// and summarized as the first sentence or line of the assistant's reply.
+    /// Existing summaries are never overwritten.
+    pub fn backfill_summaries_from_transcript(&self, session_id: Uuid, transcript: &Path) -> Result<u32> {
+        let turns = crate::chat_processor::read_transcript_turns(transcript)?;
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, user_prompt, assistant_summary IS NULL
+            FROM interactions
+            WHERE session_id = ?1
+            ORDER BY sequence_number ASC
+            "#,
+        )?;
+        let interactions = stmt
+            .query_map(params![session_id.to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, bool>(2)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        // Turns are matched in order so repeated prompts pair up correctly
+        let mut next_turn = 0;
+        let mut updated = 0;
+        for (id, prompt, missing) in interactions {
+            let prompt = prompt.trim();
+            let Some(offset) = turns[next_turn..]
+                .iter()
+                .position(|turn| !prompt.is_empty() && turn.prompt.trim().starts_with(prompt))
+            else {
+                continue;
+            };
+            let turn = &turns[next_turn + offset];
+            next_turn += offset + 1;
+
+            if !missing {
+                continue;
+            }
+            if let Some(summary) = response_summary(&turn.response) {
+                updated += conn.execute(
+                    "UPDATE interactions SET assistant_summary = ?1 WHERE id = ?2 AND assistant_summary IS NULL",
+                    params![summary, id],
+                )? as u32;
+            }
+        }
+
+        if updated > 0 {
+            tracing::info!(
+                target: "clauset::db",
+                "Backfilled {} assistant summaries for session {}",
+                updated,
+                session_id
+            );
+        }
+        Ok(updated)
+    }
+
     /// Get all session IDs that have interactions.
     pub fn get_all_session_ids(&self) -> Result<Vec<Uuid>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
 
         let mut stmt = conn.prepare(
             r#"
@@ -1697,35 +4539,162 @@ impl InteractionStore {
         Ok(rows)
     }
 
-    /// Get daily cost breakdown for the last N days.
-    pub fn get_daily_cost_breakdown(&self, days: u32) -> Result<Vec<DailyCostEntry>> {
-        let conn = self.conn.lock().unwrap();
+    /// Get daily cost breakdown for the last N days, newest first.
+    ///
+    /// With `min_duration_ms`, finished interactions shorter than that are
+    /// left out (see [`get_analytics_summary`](Self::get_analytics_summary)).
+    pub fn get_daily_cost_breakdown(&self, days: u32, min_duration_ms: Option<u64>) -> Result<Vec<DailyCostEntry>> {
+        let now = Utc::now();
+        let today = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let from = today - chrono::Duration::days(days as i64);
+
+        let mut buckets = self.bucket_costs(Granularity::Day, (from, now), None, min_duration_ms)?;
+        buckets.reverse();
+
+        Ok(buckets
+            .into_iter()
+            .map(|bucket| DailyCostEntry {
+                date: bucket.bucket_start.format("%Y-%m-%d").to_string(),
+                interaction_count: bucket.interaction_count,
+                total_cost_usd: bucket.total_cost_usd,
+                input_tokens: bucket.input_tokens,
+                output_tokens: bucket.output_tokens,
+            })
+            .collect())
+    }
 
+    /// Count distinct sessions with at least one interaction on each of the
+    /// last N days, as `(YYYY-MM-DD, session_count)` newest first. Days
+    /// without interactions are left out.
+    pub fn daily_active_sessions(&self, days: u32) -> Result<Vec<(String, u32)>> {
+        let today = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let from = today - chrono::Duration::days(days as i64);
+
+        let conn = self.reader();
         let mut stmt = conn.prepare(
+            r#"
+            SELECT DATE(started_at) as day, COUNT(DISTINCT session_id)
+            FROM interactions
+            WHERE started_at >= ?1
+            GROUP BY day
+            ORDER BY day DESC
+            "#,
+        )?;
+        let rows = stmt.query_map(params![from.to_rfc3339()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u32))
+        })?;
+
+        Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    /// Aggregate interaction costs into time buckets over `[from, to)`, oldest first.
+    ///
+    /// Only buckets containing at least one interaction are returned.
+    pub fn cost_timeseries(
+        &self,
+        granularity: Granularity,
+        range: (DateTime<Utc>, DateTime<Utc>),
+        session_id: Option<Uuid>,
+    ) -> Result<Vec<CostBucket>> {
+        self.bucket_costs(granularity, range, session_id, None)
+    }
+
+    /// Count tool invocations per `(time bucket, tool)` over `[from, to)`,
+    /// oldest bucket first and the most used tool first within a bucket.
+    ///
+    /// Only non-empty buckets are returned.
+    pub fn tool_histogram(
+        &self,
+        granularity: Granularity,
+        range: (DateTime<Utc>, DateTime<Utc>),
+        session_id: Option<Uuid>,
+    ) -> Result<Vec<ToolBucket>> {
+        let conn = self.reader();
+        let (from, to) = range;
+
+        let sql = format!(
+            r#"
+            SELECT
+                {bucket} as bucket_start,
+                tool_name,
+                COUNT(*) as invocation_count
+            FROM tool_invocations
+            WHERE started_at >= ?1 AND started_at < ?2
+              AND (?3 IS NULL OR interaction_id IN (SELECT id FROM interactions WHERE session_id = ?3))
+            GROUP BY bucket_start, tool_name
+            ORDER BY bucket_start ASC, invocation_count DESC, tool_name ASC
+            "#,
+            bucket = granularity.bucket_start_sql(),
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                params![from.to_rfc3339(), to.to_rfc3339(), session_id.map(|id| id.to_string())],
+                |row| {
+                    Ok(ToolBucket {
+                        bucket_start: DateTime::parse_from_rfc3339(&row.get::<_, String>(0)?)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_default(),
+                        tool_name: row.get(1)?,
+                        count: row.get::<_, i64>(2)? as u32,
+                    })
+                },
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    fn bucket_costs(
+        &self,
+        granularity: Granularity,
+        range: (DateTime<Utc>, DateTime<Utc>),
+        session_id: Option<Uuid>,
+        min_duration_ms: Option<u64>,
+    ) -> Result<Vec<CostBucket>> {
+        let conn = self.reader();
+        let (from, to) = range;
+
+        let sql = format!(
             r#"
             SELECT
-                DATE(started_at) as date,
+                {bucket} as bucket_start,
                 COUNT(*) as interaction_count,
                 COALESCE(SUM(cost_usd_delta), 0.0) as total_cost_usd,
                 COALESCE(SUM(input_tokens_delta), 0) as input_tokens,
                 COALESCE(SUM(output_tokens_delta), 0) as output_tokens
             FROM interactions
-            WHERE started_at >= DATE('now', '-' || ?1 || ' days')
-            GROUP BY DATE(started_at)
-            ORDER BY date DESC
+            WHERE started_at >= ?1 AND started_at < ?2
+              AND (?3 IS NULL OR session_id = ?3)
+              AND {long_enough}
+            GROUP BY bucket_start
+            ORDER BY bucket_start ASC
             "#,
-        )?;
+            bucket = granularity.bucket_start_sql(),
+            long_enough = min_duration_sql("?4"),
+        );
 
+        let mut stmt = conn.prepare(&sql)?;
         let rows = stmt
-            .query_map(params![days as i64], |row| {
-                Ok(DailyCostEntry {
-                    date: row.get(0)?,
-                    interaction_count: row.get::<_, i64>(1)? as u32,
-                    total_cost_usd: row.get(2)?,
-                    input_tokens: row.get::<_, i64>(3)? as u64,
-                    output_tokens: row.get::<_, i64>(4)? as u64,
-                })
-            })?
+            .query_map(
+                params![
+                    from.to_rfc3339(),
+                    to.to_rfc3339(),
+                    session_id.map(|id| id.to_string()),
+                    min_duration_ms.map(|ms| ms as i64),
+                ],
+                |row| {
+                    Ok(CostBucket {
+                        bucket_start: DateTime::parse_from_rfc3339(&row.get::<_, String>(0)?)
+                            .map(|dt| dt.with_timezone(&Utc))
+                            .unwrap_or_default(),
+                        interaction_count: row.get::<_, i64>(1)? as u32,
+                        total_cost_usd: row.get(2)?,
+                        input_tokens: row.get::<_, i64>(3)? as u64,
+                        output_tokens: row.get::<_, i64>(4)? as u64,
+                    })
+                },
+            )?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
         Ok(rows)
@@ -1733,7 +4702,7 @@ impl InteractionStore {
 
     /// Get cost breakdown by tool type.
     pub fn get_tool_cost_breakdown(&self, session_id: Option<Uuid>) -> Result<Vec<ToolCostEntry>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
 
         let mut results = Vec::new();
 
@@ -1792,44 +4761,137 @@ impl InteractionStore {
         Ok(results)
     }
 
-    /// Get overall analytics summary.
-    pub fn get_analytics_summary(&self) -> Result<AnalyticsSummary> {
-        let conn = self.conn.lock().unwrap();
-
-        let row = conn.query_row(
+    /// Per-tool invocation and error counts, most used first, optionally for
+    /// a single session.
+    pub fn tool_reliability(&self, session_id: Option<Uuid>) -> Result<Vec<ToolReliability>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
             r#"
             SELECT
-                COUNT(DISTINCT session_id) as session_count,
-                COUNT(*) as interaction_count,
-                COALESCE(SUM(cost_usd_delta), 0.0) as total_cost_usd,
-                COALESCE(SUM(input_tokens_delta), 0) as total_input_tokens,
-                COALESCE(SUM(output_tokens_delta), 0) as total_output_tokens,
-                AVG(cost_usd_delta) as avg_cost_per_interaction,
-                (SELECT COUNT(*) FROM tool_invocations) as total_tool_invocations,
-                (SELECT COUNT(*) FROM file_snapshots) as total_file_changes
-            FROM interactions
+                t.tool_name,
+                COUNT(*) as invocation_count,
+                SUM(t.is_error) as error_count,
+                AVG(t.duration_ms) as avg_duration_ms
+            FROM tool_invocations t
+            JOIN interactions i ON t.interaction_id = i.id
+            WHERE ?1 IS NULL OR i.session_id = ?1
+            GROUP BY t.tool_name
+            ORDER BY invocation_count DESC, t.tool_name
             "#,
-            [],
-            |row| {
-                Ok(AnalyticsSummary {
-                    session_count: row.get::<_, i64>(0)? as u32,
-                    interaction_count: row.get::<_, i64>(1)? as u32,
-                    total_cost_usd: row.get(2)?,
-                    total_input_tokens: row.get::<_, i64>(3)? as u64,
-                    total_output_tokens: row.get::<_, i64>(4)? as u64,
-                    avg_cost_per_interaction: row.get::<_, Option<f64>>(5)?.unwrap_or(0.0),
-                    total_tool_invocations: row.get::<_, i64>(6)? as u32,
-                    total_file_changes: row.get::<_, i64>(7)? as u32,
-                })
-            },
         )?;
-
-        Ok(row)
-    }
+        let rows = stmt
+            .query_map(params![session_id.map(|id| id.to_string())], |row| {
+                let invocation_count = row.get::<_, i64>(1)? as u32;
+                let error_count = row.get::<_, i64>(2)? as u32;
+                Ok(ToolReliability {
+                    tool_name: row.get(0)?,
+                    invocation_count,
+                    error_count,
+                    error_rate: error_count as f64 / invocation_count as f64,
+                    avg_duration_ms: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Count finished interactions by [`TurnOutcome`], optionally for a
+    /// single session. Every outcome is listed, in [`TurnOutcome::ALL`] order.
+    pub fn outcome_breakdown(&self, session_id: Option<Uuid>) -> Result<Vec<(TurnOutcome, u32)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT
+                i.status,
+                (SELECT COUNT(*) FROM tool_invocations t
+                 WHERE t.interaction_id = i.id AND t.is_error = 1) as tool_errors
+            FROM interactions i
+            WHERE ?1 IS NULL OR i.session_id = ?1
+            "#,
+        )?;
+        let mut counts: Vec<(TurnOutcome, u32)> = TurnOutcome::ALL.iter().map(|&o| (o, 0)).collect();
+        let rows = stmt.query_map(params![session_id.map(|id| id.to_string())], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        for row in rows {
+            let (status, tool_errors) = row?;
+            if let Some(outcome) = TurnOutcome::from_status(string_to_status(&status), tool_errors as u32)
+                && let Some(entry) = counts.iter_mut().find(|(o, _)| *o == outcome)
+            {
+                entry.1 += 1;
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Get overall analytics summary.
+    ///
+    /// With `min_duration_ms`, finished interactions shorter than that
+    /// (accidental double-enters, empty prompts) are left out, along with
+    /// their tool calls and file changes. In-progress interactions are kept.
+    /// With `range`, only interactions started within `[from, to)` count.
+    pub fn get_analytics_summary(
+        &self,
+        min_duration_ms: Option<u64>,
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> Result<AnalyticsSummary> {
+        let conn = self.reader();
+
+        // Interactions in scope: long enough and started within [from, to)
+        let in_scope = format!(
+            "{} AND (?2 IS NULL OR (started_at >= ?2 AND started_at < ?3))",
+            min_duration_sql("?1")
+        );
+        let sql = format!(
+            r#"
+            SELECT
+                COUNT(DISTINCT session_id) as session_count,
+                COUNT(*) as interaction_count,
+                COALESCE(SUM(cost_usd_delta), 0.0) as total_cost_usd,
+                COALESCE(SUM(input_tokens_delta), 0) as total_input_tokens,
+                COALESCE(SUM(output_tokens_delta), 0) as total_output_tokens,
+                COALESCE(SUM(thinking_tokens_delta), 0) as total_thinking_tokens,
+                AVG(cost_usd_delta) as avg_cost_per_interaction,
+                (SELECT COUNT(*) FROM tool_invocations
+                 WHERE (?1 IS NULL AND ?2 IS NULL)
+                    OR interaction_id IN (SELECT id FROM interactions WHERE {in_scope}))
+                    as total_tool_invocations,
+                (SELECT COUNT(*) FROM file_snapshots
+                 WHERE (?1 IS NULL AND ?2 IS NULL)
+                    OR interaction_id IN (SELECT id FROM interactions WHERE {in_scope}))
+                    as total_file_changes
+            FROM interactions
+            WHERE {in_scope}
+            "#
+        );
+        let row = conn.query_row(
+            &sql,
+            params![
+                min_duration_ms.map(|ms| ms as i64),
+                range.map(|(from, _)| from.to_rfc3339()),
+                range.map(|(_, to)| to.to_rfc3339()),
+            ],
+            |row| {
+                Ok(AnalyticsSummary {
+                    session_count: row.get::<_, i64>(0)? as u32,
+                    interaction_count: row.get::<_, i64>(1)? as u32,
+                    total_cost_usd: row.get(2)?,
+                    total_input_tokens: row.get::<_, i64>(3)? as u64,
+                    total_output_tokens: row.get::<_, i64>(4)? as u64,
+                    total_thinking_tokens: row.get::<_, i64>(5)? as u64,
+                    avg_cost_per_interaction: row.get::<_, Option<f64>>(6)?.unwrap_or(0.0),
+                    total_tool_invocations: row.get::<_, i64>(7)? as u32,
+                    total_file_changes: row.get::<_, i64>(8)? as u32,
+                })
+            },
+        )?;
+
+        Ok(row)
+    }
 
     /// Get top N most expensive interactions.
     pub fn get_most_expensive_interactions(&self, limit: usize) -> Result<Vec<Interaction>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
 
         let mut stmt = conn.prepare(
             r#"
@@ -1848,6 +4910,30 @@ impl InteractionStore {
         Ok(rows)
     }
 
+    /// List interactions costing at least `threshold` USD, most expensive first.
+    pub fn interactions_over_cost(
+        &self,
+        threshold: f64,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<Interaction>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT * FROM interactions
+            WHERE cost_usd_delta >= ?1
+            ORDER BY cost_usd_delta DESC, started_at DESC
+            LIMIT ?2 OFFSET ?3
+            "#,
+        )?;
+        let interactions = stmt
+            .query_map(params![threshold, limit, offset], |row| {
+                self.row_to_interaction(row)
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(interactions)
+    }
+
     // =========================================================================
     // Chat Message CRUD (for chat view persistence)
     // =========================================================================
@@ -1880,12 +4966,13 @@ impl InteractionStore {
 
         conn.execute(
             r#"
-            INSERT INTO chat_messages (id, session_id, sequence_number, role, content, is_streaming, is_complete, timestamp)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            INSERT INTO chat_messages (id, session_id, sequence_number, role, content, is_streaming, is_complete, timestamp, first_token_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
             ON CONFLICT(id) DO UPDATE SET
                 content = excluded.content,
                 is_streaming = excluded.is_streaming,
-                is_complete = excluded.is_complete
+                is_complete = excluded.is_complete,
+                first_token_at = COALESCE(chat_messages.first_token_at, excluded.first_token_at)
             "#,
             params![
                 &msg.id,
@@ -1896,6 +4983,7 @@ impl InteractionStore {
                 msg.is_streaming as i32,
                 msg.is_complete as i32,
                 msg.timestamp as i64,
+                msg.first_token_at.map(|ms| ms as i64),
             ],
         )?;
 
@@ -1954,14 +5042,15 @@ impl InteractionStore {
         // Get all messages
         let mut stmt = conn.prepare(
             r#"
-            SELECT id, session_id, role, content, is_streaming, is_complete, timestamp
+            SELECT id, session_id, role, content, is_streaming, is_complete, timestamp, first_token_at
             FROM chat_messages
             WHERE session_id = ?1
             ORDER BY sequence_number ASC
             "#,
         )?;
 
-        let messages: Vec<(String, String, String, i32, i32, i64)> = stmt
+        type MessageRow = (String, String, String, i32, i32, i64, Option<i64>);
+        let messages: Vec<MessageRow> = stmt
             .query_map(params![session_id.to_string()], |row| {
                 Ok((
                     row.get::<_, String>("id")?,
@@ -1970,6 +5059,7 @@ impl InteractionStore {
                     row.get::<_, i32>("is_streaming")?,
                     row.get::<_, i32>("is_complete")?,
                     row.get::<_, i64>("timestamp")?,
+                    row.get::<_, Option<i64>>("first_token_at")?,
                 ))
             })?
             .filter_map(|r| r.ok())
@@ -1979,7 +5069,7 @@ impl InteractionStore {
 
         // Build ChatMessage objects with tool calls
         let mut result = Vec::new();
-        for (id, role, content, is_streaming, is_complete, timestamp) in messages {
+        for (id, role, content, is_streaming, is_complete, timestamp, first_token_at) in messages {
             // Get tool calls for this message
             let tool_calls = self.get_chat_tool_calls_internal(&conn, &id)?;
 
@@ -1997,6 +5087,7 @@ impl InteractionStore {
                 is_streaming: is_streaming != 0,
                 is_complete: is_complete != 0,
                 timestamp: timestamp as u64,
+                first_token_at: first_token_at.map(|ms| ms as u64),
             });
         }
 
@@ -2046,6 +5137,33 @@ impl InteractionStore {
         Ok(tool_calls)
     }
 
+    /// Time from an interaction's prompt submission to the first response
+    /// text or tool call, taken from the assistant chat message started
+    /// with it (the session's assistant message created nearest its start).
+    /// None when no response content arrived.
+    pub fn response_latency(&self, interaction_id: Uuid) -> Result<Option<Duration>> {
+        let interaction = self
+            .get_interaction(interaction_id)?
+            .ok_or(ClausetError::InteractionNotFound(interaction_id))?;
+        let started_ms = interaction.started_at.timestamp_millis();
+
+        let conn = self.reader();
+        let first_token_at: Option<i64> = conn
+            .query_row(
+                r#"
+                SELECT first_token_at FROM chat_messages
+                WHERE session_id = ?1 AND role = 'assistant'
+                ORDER BY ABS(timestamp - ?2) ASC
+                LIMIT 1
+                "#,
+                params![interaction.session_id.to_string(), started_ms],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        Ok(first_token_at.map(|ms| Duration::from_millis(ms.saturating_sub(started_ms).max(0) as u64)))
+    }
+
     /// Delete all chat messages for a session.
     pub fn delete_chat_messages(&self, session_id: Uuid) -> Result<u32> {
         let conn = self.conn.lock().unwrap();
@@ -2072,16 +5190,24 @@ impl InteractionStore {
     // =========================================================================
 
     /// Insert a prompt into the library.
-    /// Uses UPSERT to handle deduplication by content_hash + session_id.
-    pub fn insert_prompt(&self, prompt: &clauset_types::Prompt) -> Result<()> {
+    ///
+    /// Uses UPSERT to handle deduplication by content_hash + session_id: a
+    /// duplicate bumps the stored prompt's use count instead. Returns false
+    /// for duplicates.
+    ///
+    /// Prompts keep their own copy of the text and aren't linked to
+    /// interactions, so they outlive interaction retention; see
+    /// [`Self::prune_prompts`] for the library's own lifecycle.
+    pub fn insert_prompt(&self, prompt: &clauset_types::Prompt) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
         let content_hash = prompt.content_hash();
 
-        conn.execute(
+        let use_count: i64 = conn.query_row(
             r#"
             INSERT INTO prompts (id, claude_session_id, project_path, content, preview, timestamp, word_count, char_count, content_hash)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-            ON CONFLICT(claude_session_id, content_hash) DO NOTHING
+            ON CONFLICT(claude_session_id, content_hash) DO UPDATE SET use_count = use_count + 1
+            RETURNING use_count
             "#,
             params![
                 prompt.id.to_string(),
@@ -2094,16 +5220,29 @@ impl InteractionStore {
                 prompt.char_count as i64,
                 content_hash,
             ],
+            |row| row.get(0),
         )?;
-        Ok(())
+        Ok(use_count == 1)
+    }
+
+    /// Delete library prompts submitted before `older_than`.
+    ///
+    /// Returns the number of prompts deleted.
+    pub fn prune_prompts(&self, older_than: DateTime<Utc>) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let deleted = conn.execute(
+            "DELETE FROM prompts WHERE timestamp < ?1",
+            params![older_than.timestamp_millis()],
+        )?;
+        Ok(deleted)
     }
 
     /// List prompts with pagination, ordered by timestamp descending (newest first).
     pub fn list_prompts(&self, limit: u32, offset: u32) -> Result<Vec<clauset_types::PromptSummary>> {
-        let conn = self.conn.lock().unwrap();
+        let conn = self.reader();
         let mut stmt = conn.prepare(
             r#"
-            SELECT id, preview, project_path, timestamp, word_count
+            SELECT id, preview, project_path, timestamp, word_count, use_count
             FROM prompts
             ORDER BY timestamp DESC
             LIMIT ?1 OFFSET ?2
@@ -2116,6 +5255,7 @@ impl InteractionStore {
             let project_path: String = row.get(2)?;
             let timestamp: i64 = row.get(3)?;
             let word_count: i64 = row.get(4)?;
+            let use_count: i64 = row.get(5)?;
 
             // Extract project name from path
             let project_name = std::path::Path::new(&project_path)
@@ -2130,6 +5270,7 @@ impl InteractionStore {
                 project_name,
                 timestamp: timestamp as u64,
                 word_count: word_count as u32,
+                use_count: use_count as u32,
             })
         })?;
 
@@ -2142,7 +5283,7 @@ impl InteractionStore {
         let conn = self.conn.lock().unwrap();
         let result = conn.query_row(
             r#"
-            SELECT id, claude_session_id, project_path, content, preview, timestamp, word_count, char_count
+            SELECT id, claude_session_id, project_path, content, preview, timestamp, word_count, char_count, use_count
             FROM prompts
             WHERE id = ?1
             "#,
@@ -2156,6 +5297,7 @@ impl InteractionStore {
                 let timestamp: i64 = row.get(5)?;
                 let word_count: i64 = row.get(6)?;
                 let char_count: i64 = row.get(7)?;
+                let use_count: i64 = row.get(8)?;
 
                 Ok(clauset_types::Prompt {
                     id: Uuid::parse_str(&id).unwrap_or_default(),
@@ -2166,6 +5308,7 @@ impl InteractionStore {
                     timestamp: timestamp as u64,
                     word_count: word_count as u32,
                     char_count: char_count as u32,
+                    use_count: use_count as u32,
                 })
             },
         );
@@ -2184,6 +5327,27 @@ impl InteractionStore {
         Ok(count as u64)
     }
 
+    /// Distinct prompt contents used at least `min_uses` times across all
+    /// sessions, with their total uses, most used first.
+    pub fn frequent_prompts(&self, min_uses: u32) -> Result<Vec<(String, u32)>> {
+        let conn = self.reader();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT content, SUM(use_count) AS uses
+            FROM prompts
+            GROUP BY content_hash
+            HAVING uses >= ?1
+            ORDER BY uses DESC, MIN(timestamp) ASC
+            "#,
+        )?;
+        let prompts = stmt
+            .query_map(params![min_uses as i64], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u32))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(prompts)
+    }
+
     /// Check if the prompts table is empty (for backfill detection).
     pub fn is_prompts_empty(&self) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
@@ -2195,6 +5359,59 @@ impl InteractionStore {
         Ok(count == 0)
     }
 
+    /// Save a prompt template.
+    pub fn insert_prompt_template(&self, template: &clauset_types::PromptTemplate) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            r#"
+            INSERT INTO prompt_templates (id, interaction_id, template, slots, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![
+                template.id.to_string(),
+                template.interaction_id.to_string(),
+                template.template,
+                serde_json::to_string(&template.slots)?,
+                template.created_at as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get a single prompt template by ID.
+    pub fn get_prompt_template(&self, id: Uuid) -> Result<Option<clauset_types::PromptTemplate>> {
+        let conn = self.conn.lock().unwrap();
+        let row = conn
+            .query_row(
+                r#"
+                SELECT id, interaction_id, template, slots, created_at
+                FROM prompt_templates
+                WHERE id = ?1
+                "#,
+                params![id.to_string()],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, i64>(4)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        row.map(|(interaction_id, template, slots, created_at)| {
+            Ok(clauset_types::PromptTemplate {
+                id,
+                interaction_id: Uuid::parse_str(&interaction_id).unwrap_or_default(),
+                template,
+                slots: serde_json::from_str(&slots)?,
+                created_at: created_at as u64,
+            })
+        })
+        .transpose()
+    }
+
     // =========================================================================
     // Row conversion helpers
     // =========================================================================
@@ -2204,12 +5421,15 @@ impl InteractionStore {
         let session_id: String = row.get("session_id")?;
         let sequence_number: i64 = row.get("sequence_number")?;
         let user_prompt: String = row.get("user_prompt")?;
+        let prompt_truncated: bool = row.get("prompt_truncated")?;
         let assistant_summary: Option<String> = row.get("assistant_summary")?;
         let started_at: String = row.get("started_at")?;
         let ended_at: Option<String> = row.get("ended_at")?;
         let cost_usd_delta: f64 = row.get("cost_usd_delta")?;
         let input_tokens_delta: i64 = row.get("input_tokens_delta")?;
         let output_tokens_delta: i64 = row.get("output_tokens_delta")?;
+        let thinking_tokens_delta: i64 = row.get("thinking_tokens_delta")?;
+        let context_percent: Option<i64> = row.get("context_percent")?;
         let status: String = row.get("status")?;
         let error_message: Option<String> = row.get("error_message")?;
 
@@ -2218,6 +5438,7 @@ impl InteractionStore {
             session_id: Uuid::parse_str(&session_id).unwrap_or_default(),
             sequence_number: sequence_number as u32,
             user_prompt,
+            prompt_truncated,
             assistant_summary,
             started_at: DateTime::parse_from_rfc3339(&started_at)
                 .map(|dt| dt.with_timezone(&Utc))
@@ -2230,6 +5451,8 @@ impl InteractionStore {
             cost_usd_delta,
             input_tokens_delta: input_tokens_delta as u64,
             output_tokens_delta: output_tokens_delta as u64,
+            thinking_tokens_delta: thinking_tokens_delta as u64,
+            context_percent: context_percent.map(|p| p.clamp(0, 100) as u8),
             status: string_to_status(&status),
             error_message,
         })
@@ -2246,6 +5469,7 @@ impl InteractionStore {
         let file_path: Option<String> = row.get("file_path")?;
         let is_error: i32 = row.get("is_error")?;
         let error_message: Option<String> = row.get("error_message")?;
+        let exit_code: Option<i32> = row.get("exit_code")?;
         let started_at: String = row.get("started_at")?;
         let ended_at: Option<String> = row.get("ended_at")?;
         let duration_ms: Option<i64> = row.get("duration_ms")?;
@@ -2261,6 +5485,7 @@ impl InteractionStore {
             file_path: file_path.map(|s| s.into()),
             is_error: is_error != 0,
             error_message,
+            exit_code,
             started_at: DateTime::parse_from_rfc3339(&started_at)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_default(),
@@ -2300,12 +5525,70 @@ impl InteractionStore {
 }
 
 /// Statistics from a cleanup operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct CleanupStats {
     pub interactions_deleted: u32,
     pub contents_deleted: u32,
 }
 
+/// Who wrote a line of a file, per [`InteractionStore::file_authorship`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineAuthor {
+    /// Added by Claude's edits during the session
+    Claude,
+    /// Present before Claude first touched the file
+    PreExisting,
+}
+
+/// One tool call when comparing two interactions' tool sequences.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolSeqDiff {
+    /// `add` if only in B, `remove` if only in A, `context` if in both
+    pub change_type: DiffChangeType,
+    pub tool_name: String,
+    /// File path, command or pattern identifying the call
+    pub key_input: Option<String>,
+    /// Sequence number in interaction A
+    pub sequence_a: Option<u32>,
+    /// Sequence number in interaction B
+    pub sequence_b: Option<u32>,
+}
+
+/// Attribution of one line in a file's final version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineAuthorship {
+    /// 1-based line number in the final version
+    pub line_number: u32,
+    pub content: String,
+    pub author: LineAuthor,
+}
+
+/// Rows referencing a parent that no longer exists.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct IntegrityReport {
+    /// Whether the connection enforces foreign keys (`PRAGMA foreign_keys`)
+    pub foreign_keys_enabled: bool,
+    /// Interactions whose session is missing
+    pub orphaned_interactions: u64,
+    /// Tool invocations whose interaction is missing
+    pub orphaned_tool_invocations: u64,
+    /// File snapshots whose interaction is missing
+    pub orphaned_snapshots: u64,
+    /// File snapshots linked to a missing tool invocation
+    pub dangling_snapshot_tool_links: u64,
+}
+
+impl IntegrityReport {
+    /// Whether no orphans were found.
+    pub fn is_clean(&self) -> bool {
+        self.orphaned_interactions == 0
+            && self.orphaned_tool_invocations == 0
+            && self.orphaned_snapshots == 0
+            && self.dangling_snapshot_tool_links == 0
+    }
+}
+
 /// Storage usage statistics.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct StorageStats {
@@ -2317,6 +5600,19 @@ pub struct StorageStats {
     pub total_compressed_size: u64,
 }
 
+/// Storage attributed to one session.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionStorageEntry {
+    pub session_id: Uuid,
+    pub interaction_count: u64,
+    pub tool_count: u64,
+    pub snapshot_count: u64,
+    /// Original size of the session's share of file contents
+    pub content_size: u64,
+    /// Compressed (on-disk) size of the session's share of file contents
+    pub compressed_size: u64,
+}
+
 impl StorageStats {
     /// Calculate compression ratio.
     pub fn compression_ratio(&self) -> f64 {
@@ -2330,11 +5626,103 @@ impl StorageStats {
 
 // Helper functions
 
+/// First line of a prompt with whitespace collapsed, truncated for summaries.
+fn summary_snippet(prompt: &str) -> String {
+    let line = prompt.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+    let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > SUMMARY_PROMPT_CHARS {
+        let truncated: String = line.chars().take(SUMMARY_PROMPT_CHARS).collect();
+        format!("{}…", truncated.trim_end())
+    } else if line.is_empty() {
+        "(empty prompt)".to_string()
+    } else {
+        line
+    }
+}
+
+/// First sentence (or line) of an assistant response, or None if it has no text.
+fn response_summary(response: &str) -> Option<String> {
+    let line = response
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())?;
+    let sentence = line
+        .char_indices()
+        .find(|&(i, c)| matches!(c, '.' | '!' | '?') && line[i + 1..].starts_with(' '))
+        .map_or(line, |(i, _)| &line[..=i]);
+    if sentence.chars().count() > SUMMARY_RESPONSE_CHARS {
+        let truncated: String = sentence.chars().take(SUMMARY_RESPONSE_CHARS).collect();
+        Some(format!("{}…", truncated.trim_end()))
+    } else {
+        Some(sentence.to_string())
+    }
+}
+
+/// Serialize a tool input within `max_bytes` by cutting its longest string
+/// fields, each ending in a marker with the number of bytes dropped. Short
+/// fields such as `file_path` or `command` are left whole.
+fn truncate_tool_input(input: &serde_json::Value, max_bytes: usize) -> String {
+    let mut value = input.clone();
+    loop {
+        let serialized = value.to_string();
+        let excess = serialized.len().saturating_sub(max_bytes);
+        if excess == 0 {
+            return serialized;
+        }
+        let Some(longest) = longest_string_mut(&mut value) else {
+            break;
+        };
+        let old_len = longest.len();
+        let marker_len = format!("… [truncated {old_len} bytes]").len();
+        let mut keep = old_len.saturating_sub(excess + marker_len);
+        while !longest.is_char_boundary(keep) {
+            keep -= 1;
+        }
+        let truncated = format!("{}… [truncated {} bytes]", &longest[..keep], old_len - keep);
+        if truncated.len() >= old_len {
+            break;
+        }
+        *longest = truncated;
+    }
+
+    // No string left to shorten: keep a marked prefix of the serialized input
+    let serialized = input.to_string();
+    let mut keep = max_bytes / 2;
+    while !serialized.is_char_boundary(keep) {
+        keep -= 1;
+    }
+    serde_json::json!({
+        "truncated": serialized.len() - keep,
+        "preview": &serialized[..keep],
+    })
+    .to_string()
+}
+
+fn longest_string_mut(value: &mut serde_json::Value) -> Option<&mut String> {
+    match value {
+        serde_json::Value::String(s) => Some(s),
+        serde_json::Value::Array(items) => items
+            .iter_mut()
+            .filter_map(longest_string_mut)
+            .max_by_key(|s| s.len()),
+        serde_json::Value::Object(map) => map
+            .values_mut()
+            .filter_map(longest_string_mut)
+            .max_by_key(|s| s.len()),
+        _ => None,
+    }
+}
+
+fn plural(count: i64, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
 fn status_to_string(status: InteractionStatus) -> &'static str {
     match status {
         InteractionStatus::Active => "active",
         InteractionStatus::Completed => "completed",
         InteractionStatus::Failed => "failed",
+        InteractionStatus::Interrupted => "interrupted",
     }
 }
 
@@ -2343,6 +5731,7 @@ fn string_to_status(s: &str) -> InteractionStatus {
         "active" => InteractionStatus::Active,
         "completed" => InteractionStatus::Completed,
         "failed" => InteractionStatus::Failed,
+        "interrupted" => InteractionStatus::Interrupted,
         _ => InteractionStatus::Active,
     }
 }
@@ -2362,6 +5751,16 @@ fn string_to_snapshot_type(s: &str) -> SnapshotType {
     }
 }
 
+/// SQL predicate keeping interactions that ran for at least the millisecond
+/// count bound to `param` (NULL keeps everything). Interactions that haven't
+/// ended yet always pass.
+fn min_duration_sql(param: &str) -> String {
+    format!(
+        "({param} IS NULL OR ended_at IS NULL \
+         OR (julianday(ended_at) - julianday(started_at)) * 86400000.0 >= {param})"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2436,71 +5835,2709 @@ mod tests {
     }
 
     #[test]
-    fn test_tool_invocation_crud() {
+    fn test_turn_timeline_orders_events() {
         let (store, _dir) = create_test_store();
         let session_id = Uuid::new_v4();
         create_test_session(&store, session_id);
-        let interaction = Interaction::new(session_id, 1, "Test".to_string());
+        let start = Utc::now() - chrono::Duration::seconds(60);
+        let at = |secs: i64| start + chrono::Duration::seconds(secs);
+
+        let mut interaction = Interaction::new(session_id, 1, "Refactor the parser".to_string());
+        interaction.started_at = at(0);
         store.insert_interaction(&interaction).unwrap();
 
-        // Create tool invocation
-        let invocation = ToolInvocation::new(
-            interaction.id,
-            1,
-            "Read".to_string(),
-            serde_json::json!({"file_path": "/test.rs"}),
-            Some("toolu_123".to_string()),
-        );
-        store.insert_tool_invocation(&invocation).unwrap();
+        // The Grep starts while the Read is still running
+        let mut read = ToolInvocation::new(interaction.id, 1, "Read".to_string(), serde_json::json!({"file_path": "/p/src/parser.rs"}), None);
+        read.started_at = at(1);
+        let mut grep = ToolInvocation::new(interaction.id, 2, "Grep".to_string(), serde_json::json!({"pattern": "parse"}), None);
+        grep.started_at = at(2);
+        store.insert_tool_invocation(&read).unwrap();
+        store.insert_tool_invocation(&grep).unwrap();
+        read.ended_at = Some(at(4));
+        read.duration_ms = Some(3000);
+        grep.ended_at = Some(at(3));
+        grep.duration_ms = Some(1000);
+        store.update_tool_invocation(&read).unwrap();
+        store.update_tool_invocation(&grep).unwrap();
+
+        interaction.assistant_summary = Some("Split the parser into modules".to_string());
+        interaction.ended_at = Some(at(5));
+        interaction.cost_usd_delta = 0.25;
+        store.update_interaction(&interaction).unwrap();
+
+        let timeline = store.turn_timeline(interaction.id).unwrap();
+        let kinds: Vec<_> = timeline
+            .iter()
+            .map(|entry| match entry {
+                TimelineEntry::Prompt { .. } => "prompt".to_string(),
+                TimelineEntry::ToolStart { tool_name, .. } => format!("start {tool_name}"),
+                TimelineEntry::ToolEnd { tool_name, .. } => format!("end {tool_name}"),
+                TimelineEntry::AssistantResponse { .. } => "response".to_string(),
+            })
+            .collect();
+        assert_eq!(kinds, ["prompt", "start Read", "start Grep", "end Grep", "end Read", "response"]);
+        assert!(timeline.windows(2).all(|w| w[0].timestamp() <= w[1].timestamp()));
+        assert!(matches!(
+            &timeline[3],
+            TimelineEntry::ToolEnd { duration_ms: Some(1000), .. }
+        ));
+        assert!(matches!(
+            timeline.last(),
+            Some(TimelineEntry::AssistantResponse { cost_usd, summary: Some(_), .. }) if *cost_usd == 0.25
+        ));
+
+        assert!(matches!(
+            store.turn_timeline(Uuid::new_v4()),
+            Err(ClausetError::InteractionNotFound(_))
+        ));
+    }
 
-        // Read by ID
-        let loaded = store.get_tool_invocation(invocation.id).unwrap().unwrap();
-        assert_eq!(loaded.tool_name, "Read");
+    #[test]
+    fn test_prompts_outlive_interactions() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
 
-        // Read by tool_use_id
-        let loaded = store
-            .get_tool_invocation_by_tool_use_id("toolu_123")
-            .unwrap()
-            .unwrap();
-        assert_eq!(loaded.id, invocation.id);
+        let text = "Add pagination to the prompts endpoint";
+        let interaction = Interaction::new(session_id, 1, text.to_string());
+        store.insert_interaction(&interaction).unwrap();
+        let prompt = clauset_types::Prompt::new("claude-1".to_string(), PathBuf::from("/p/app"), text.to_string(), 1_000);
+        assert!(store.insert_prompt(&prompt).unwrap());
+        // Submitting the same prompt again counts a use instead of duplicating it
+        let again = clauset_types::Prompt::new("claude-1".to_string(), PathBuf::from("/p/app"), text.to_string(), 2_000);
+        assert!(!store.insert_prompt(&again).unwrap());
+
+        store.truncate_session_history(session_id, 0).unwrap();
+        assert!(store.get_interaction(interaction.id).unwrap().is_none());
+
+        let kept = store.get_prompt(prompt.id).unwrap().unwrap();
+        assert_eq!(kept.content, text);
+        assert_eq!(kept.use_count, 2);
+        assert_eq!(store.list_prompts(10, 0).unwrap()[0].use_count, 2);
+
+        // The library has its own retention
+        let cutoff = DateTime::from_timestamp_millis(1_500).unwrap();
+        assert_eq!(store.prune_prompts(cutoff).unwrap(), 1);
+        assert_eq!(store.get_prompt_count().unwrap(), 0);
     }
 
     #[test]
-    fn test_file_content_deduplication() {
+    fn test_interaction_context_roundtrip() {
         let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Plan it".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        assert!(store.interaction_context(interaction.id).unwrap().is_none());
 
-        let content = b"Hello, world!";
+        let context = InteractionContext {
+            model: Some("claude-opus-4-5".to_string()),
+            permission_mode: Some(clauset_types::PermissionMode::Plan),
+            cwd: Some(PathBuf::from("/repo")),
+        };
+        store.set_interaction_context(interaction.id, &context).unwrap();
+        assert_eq!(store.interaction_context(interaction.id).unwrap(), Some(context));
+        assert!(store.interaction_context(Uuid::new_v4()).unwrap().is_none());
+    }
 
-        // Store content first time
-        let (hash1, is_new1) = store.store_file_content(content).unwrap();
-        assert!(is_new1);
+    #[test]
+    fn test_update_interaction_costs_targets_interaction() {
+        let (store, _temp_dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let older = Interaction::new(session_id, 1, "First turn".to_string());
+        store.insert_interaction(&older).unwrap();
+        store.complete_interaction_with_costs(older.id, 0.01, 1000, 100, 0).unwrap();
+
+        // A newer turn starts before the older turn's final cost arrives
+        let newer = Interaction::new(session_id, 2, "Second turn".to_string());
+        store.insert_interaction(&newer).unwrap();
+        assert!(store
+            .update_interaction_costs(session_id, Some(older.id), 0.05, 4000, 400)
+            .unwrap());
+
+        let older_row = store.get_interaction(older.id).unwrap().unwrap();
+        assert_eq!(older_row.cost_usd_delta, 0.05);
+        assert_eq!(older_row.input_tokens_delta, 4000);
+        assert_eq!(older_row.output_tokens_delta, 400);
+        assert_eq!(store.get_interaction(newer.id).unwrap().unwrap().cost_usd_delta, 0.0);
+
+        // Without a known interaction, the latest one is updated
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, other_session);
+        let foreign = Interaction::new(other_session, 1, "Elsewhere".to_string());
+        store.insert_interaction(&foreign).unwrap();
+        assert!(store
+            .update_interaction_costs(session_id, Some(foreign.id), 0.02, 10, 10)
+            .unwrap());
+        assert!(store.update_latest_interaction_costs(session_id, 0.03, 20, 20).unwrap());
+        assert_eq!(store.get_interaction(newer.id).unwrap().unwrap().cost_usd_delta, 0.03);
+        assert_eq!(store.get_interaction(foreign.id).unwrap().unwrap().cost_usd_delta, 0.0);
+        assert!(!store
+            .update_interaction_costs(Uuid::new_v4(), None, 0.01, 1, 1)
+            .unwrap());
+    }
 
-        // Store same content again
-        let (hash2, is_new2) = store.store_file_content(content).unwrap();
-        assert!(!is_new2);
-        assert_eq!(hash1, hash2);
+    #[test]
+    fn test_session_cumulative_cost() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        create_test_session(&store, other_session);
 
-        // Retrieve content
-        let loaded = store.get_file_content(&hash1).unwrap().unwrap();
-        assert_eq!(loaded, content);
+        // Inserted out of order; the series follows sequence_number
+        for (seq, delta) in [(2, 0.25), (1, 0.5), (3, 0.0), (4, 1.25)] {
+            let mut interaction = Interaction::new(session_id, seq, format!("Step {seq}"));
+            interaction.cost_usd_delta = delta;
+            store.insert_interaction(&interaction).unwrap();
+        }
+        let mut other = Interaction::new(other_session, 1, "Elsewhere".to_string());
+        other.cost_usd_delta = 9.0;
+        store.insert_interaction(&other).unwrap();
+
+        let series = store.session_cumulative_cost(session_id).unwrap();
+        let totals: Vec<f64> = series.iter().map(|(_, total)| *total).collect();
+        assert_eq!(totals, [0.5, 0.75, 0.75, 2.0]);
+        assert!(totals.windows(2).all(|w| w[0] <= w[1]));
+        let analytics = store.get_session_analytics(session_id).unwrap();
+        assert_eq!(*totals.last().unwrap(), analytics.total_cost_usd);
+
+        assert!(store.session_cumulative_cost(Uuid::new_v4()).unwrap().is_empty());
     }
 
     #[test]
-    fn test_sequence_numbers() {
+    fn test_backup_to() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Back me up".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let backup_path = dir.path().join("backup.db");
+        store.backup_to(&backup_path).unwrap();
+        // Writes after the backup don't show up in it
+        store.insert_interaction(&Interaction::new(session_id, 2, "Later".to_string())).unwrap();
+
+        let restored = InteractionStore::open(&backup_path).unwrap();
+        let interactions = restored.list_interactions(session_id, 10, 0).unwrap();
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].id, interaction.id);
+        assert_eq!(interactions[0].user_prompt, "Back me up");
+    }
+
+    #[test]
+    fn test_outcome_breakdown() {
         let (store, _dir) = create_test_store();
         let session_id = Uuid::new_v4();
         create_test_session(&store, session_id);
 
-        // First interaction should be 1
-        let seq = store.next_sequence_number(session_id).unwrap();
-        assert_eq!(seq, 1);
+        let mut clean = Interaction::new(session_id, 1, "Clean".to_string());
+        clean.complete();
+        store.insert_interaction(&clean).unwrap();
 
-        // Insert interaction
-        let interaction = Interaction::new(session_id, 1, "First".to_string());
-        store.insert_interaction(&interaction).unwrap();
+        let mut partial = Interaction::new(session_id, 2, "Flaky".to_string());
+        partial.complete();
+        store.insert_interaction(&partial).unwrap();
+        let mut invocation =
+            ToolInvocation::new(partial.id, 1, "Bash".to_string(), serde_json::json!({}), None);
+        invocation.is_error = true;
+        store.insert_tool_invocation(&invocation).unwrap();
 
-        // Next should be 2
-        let seq = store.next_sequence_number(session_id).unwrap();
-        assert_eq!(seq, 2);
+        let mut failed = Interaction::new(session_id, 3, "Broken".to_string());
+        failed.fail("API error".to_string());
+        store.insert_interaction(&failed).unwrap();
+
+        // Still running, so not counted
+        store.insert_interaction(&Interaction::new(session_id, 4, "Running".to_string())).unwrap();
+
+        let breakdown = store.outcome_breakdown(Some(session_id)).unwrap();
+        assert_eq!(
+            breakdown,
+            [
+                (TurnOutcome::Succeeded, 1),
+                (TurnOutcome::PartialError, 1),
+                (TurnOutcome::Failed, 1),
+                (TurnOutcome::Interrupted, 0),
+            ]
+        );
+        assert_eq!(store.outcome_breakdown(Some(Uuid::new_v4())).unwrap()[0], (TurnOutcome::Succeeded, 0));
+    }
+
+    #[test]
+    fn test_tool_reliability() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        create_test_session(&store, other_session);
+
+        let seed = |session_id: Uuid, tools: &[(&str, bool)]| {
+            let interaction = Interaction::new(session_id, 1, "Run things".to_string());
+            store.insert_interaction(&interaction).unwrap();
+            for (seq, (tool_name, is_error)) in tools.iter().enumerate() {
+                let mut invocation = ToolInvocation::new(
+                    interaction.id,
+                    seq as u32 + 1,
+                    tool_name.to_string(),
+                    serde_json::json!({}),
+                    None,
+                );
+                invocation.is_error = *is_error;
+                invocation.duration_ms = Some(100 * (seq as i64 + 1));
+                store.insert_tool_invocation(&invocation).unwrap();
+            }
+        };
+        seed(
+            session_id,
+            &[("Bash", false), ("Bash", true), ("Bash", false), ("Bash", true), ("Read", false), ("Read", false)],
+        );
+        seed(other_session, &[("Read", true)]);
+
+        let session = store.tool_reliability(Some(session_id)).unwrap();
+        assert_eq!(session.len(), 2);
+        assert_eq!(session[0].tool_name, "Bash");
+        assert_eq!((session[0].invocation_count, session[0].error_count), (4, 2));
+        assert!((session[0].error_rate - 0.5).abs() < 1e-9);
+        assert_eq!(session[0].avg_duration_ms, Some(250.0));
+        assert_eq!(session[1].tool_name, "Read");
+        assert_eq!(session[1].error_count, 0);
+        assert_eq!(session[1].error_rate, 0.0);
+
+        let all = store.tool_reliability(None).unwrap();
+        let read = all.iter().find(|t| t.tool_name == "Read").unwrap();
+        assert_eq!((read.invocation_count, read.error_count), (3, 1));
+        assert!((read.error_rate - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_neighbor_interactions() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        create_test_session(&store, other_session);
+
+        let ids: Vec<Uuid> = (1..=3)
+            .map(|seq| {
+                let interaction = Interaction::new(session_id, seq, format!("Prompt {seq}"));
+                store.insert_interaction(&interaction).unwrap();
+                interaction.id
+            })
+            .collect();
+        store
+            .insert_interaction(&Interaction::new(other_session, 2, "Elsewhere".to_string()))
+            .unwrap();
+
+        let ids_of = |id| {
+            let (prev, next) = store.neighbor_interactions(id).unwrap();
+            (prev.map(|i| i.id), next.map(|i| i.id))
+        };
+        assert_eq!(ids_of(ids[1]), (Some(ids[0]), Some(ids[2])));
+        assert_eq!(ids_of(ids[0]), (None, Some(ids[1])));
+        assert_eq!(ids_of(ids[2]), (Some(ids[1]), None));
+        assert_eq!(ids_of(Uuid::new_v4()), (None, None));
+    }
+
+    #[test]
+    fn test_interactions_over_cost() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        for (seq, cost) in [(1, 0.25), (2, 1.5), (3, 1.0), (4, 0.99), (5, 3.2)] {
+            let mut interaction = Interaction::new(session_id, seq, format!("Prompt {seq}"));
+            interaction.cost_usd_delta = cost;
+            store.insert_interaction(&interaction).unwrap();
+        }
+
+        let costs = |limit, offset| -> Vec<f64> {
+            store
+                .interactions_over_cost(1.0, limit, offset)
+                .unwrap()
+                .iter()
+                .map(|i| i.cost_usd_delta)
+                .collect()
+        };
+        assert_eq!(costs(10, 0), vec![3.2, 1.5, 1.0]);
+        assert_eq!(costs(2, 1), vec![1.5, 1.0]);
+        assert!(store.interactions_over_cost(5.0, 10, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_interaction_at_timestamp() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // 14:00-14:10, 14:30-14:40, then 15:00 still running
+        let mut ids = Vec::new();
+        for (seq, started, ended) in [
+            (1, "2026-01-05T14:00:00Z", Some("2026-01-05T14:10:00Z")),
+            (2, "2026-01-05T14:30:00Z", Some("2026-01-05T14:40:00Z")),
+            (3, "2026-01-05T15:00:00Z", None),
+        ] {
+            let mut interaction = Interaction::new(session_id, seq, format!("Prompt {seq}"));
+            interaction.started_at = at(started);
+            interaction.ended_at = ended.map(at);
+            store.insert_interaction(&interaction).unwrap();
+            ids.push(interaction.id);
+        }
+
+        let found = |ts: &str| store.interaction_at(session_id, at(ts)).unwrap().map(|i| i.id);
+        assert_eq!(found("2026-01-05T14:05:00Z"), Some(ids[0]));
+        assert_eq!(found("2026-01-05T14:32:00Z"), Some(ids[1]));
+        assert_eq!(found("2026-01-05T14:40:00Z"), Some(ids[1]));
+        assert_eq!(found("2026-01-05T17:00:00Z"), Some(ids[2]));
+        assert_eq!(found("2026-01-05T14:20:00Z"), None);
+        assert_eq!(found("2026-01-05T13:00:00Z"), None);
+        assert!(store.interaction_at(Uuid::new_v4(), at("2026-01-05T14:05:00Z")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_tool_invocation_crud() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Test".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        // Create tool invocation
+        let invocation = ToolInvocation::new(
+            interaction.id,
+            1,
+            "Read".to_string(),
+            serde_json::json!({"file_path": "/test.rs"}),
+            Some("toolu_123".to_string()),
+        );
+        assert!(store.insert_tool_invocation(&invocation).unwrap());
+
+        // Read by ID
+        let loaded = store.get_tool_invocation(invocation.id).unwrap().unwrap();
+        assert_eq!(loaded.tool_name, "Read");
+
+        // Read by tool_use_id
+        let loaded = store
+            .get_tool_invocation_by_tool_use_id("toolu_123")
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.id, invocation.id);
+    }
+
+    #[test]
+    fn test_hook_events_listed_in_order() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        create_test_session(&store, other_session);
+
+        let payload = |session: Uuid, event: &str| -> HookEventPayload {
+            serde_json::from_value(serde_json::json!({
+                "clauset_session_id": session,
+                "session_id": "claude-1",
+                "hook_event_name": event,
+            }))
+            .unwrap()
+        };
+        let usage = HookUsage {
+            cost_usd: 0.25,
+            input_tokens: 1200,
+            output_tokens: 300,
+            thinking_tokens: 50,
+            context_percent: 12,
+        };
+        let first = store
+            .record_hook_event(&payload(session_id, "UserPromptSubmit"), &HookUsage::default())
+            .unwrap();
+        store.record_hook_event(&payload(other_session, "Stop"), &usage).unwrap();
+        let last = store.record_hook_event(&payload(session_id, "Stop"), &usage).unwrap();
+        assert!(first < last);
+
+        let events = store.list_hook_events(session_id).unwrap();
+        let names: Vec<_> = events.iter().map(|e| e.payload.hook_event_name.as_str()).collect();
+        assert_eq!(names, ["UserPromptSubmit", "Stop"]);
+        assert_eq!(events[0].sequence, first);
+        assert_eq!(events[1].usage, usage);
+        assert_eq!(events[1].payload.clauset_session_id, session_id);
+    }
+
+    #[test]
+    fn test_custom_hook_events_listed_in_order() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        create_test_session(&store, other_session);
+
+        let deploy = serde_json::json!({"hook_event_name": "DeployFinished", "env": "staging"});
+        let first = store.record_custom_hook_event(session_id, "DeployFinished", &deploy).unwrap();
+        store
+            .record_custom_hook_event(other_session, "LintRan", &serde_json::json!({}))
+            .unwrap();
+        let last = store
+            .record_custom_hook_event(session_id, "LintRan", &serde_json::json!({"warnings": 3}))
+            .unwrap();
+
+        let events = store.list_custom_hook_events(session_id).unwrap();
+        let ids: Vec<_> = events.iter().map(|e| (e.id, e.event_name.as_str())).collect();
+        assert_eq!(ids, [(first, "DeployFinished"), (last, "LintRan")]);
+        assert_eq!(events[0].payload, deploy);
+        assert_eq!(events[1].payload["warnings"], 3);
+        assert!(events.iter().all(|e| e.session_id == session_id));
+    }
+
+    #[test]
+    fn test_duplicate_tool_use_id_recorded_once() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Test".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let input = serde_json::json!({"command": "ls"});
+        let first = ToolInvocation::new(
+            interaction.id,
+            1,
+            "Bash".to_string(),
+            input.clone(),
+            Some("toolu_dup".to_string()),
+        );
+        let replay = ToolInvocation::new(
+            interaction.id,
+            2,
+            "Bash".to_string(),
+            input.clone(),
+            Some("toolu_dup".to_string()),
+        );
+        assert!(store.insert_tool_invocation(&first).unwrap());
+        assert!(!store.insert_tool_invocation(&replay).unwrap());
+
+        // Invocations without a tool_use_id are never treated as duplicates
+        let anon_a = ToolInvocation::new(interaction.id, 3, "Bash".to_string(), input.clone(), None);
+        let anon_b = ToolInvocation::new(interaction.id, 4, "Bash".to_string(), input, None);
+        assert!(store.insert_tool_invocation(&anon_a).unwrap());
+        assert!(store.insert_tool_invocation(&anon_b).unwrap());
+
+        let invocations = store.list_tool_invocations(interaction.id).unwrap();
+        assert_eq!(invocations.len(), 3);
+        let dup: Vec<_> = invocations
+            .iter()
+            .filter(|t| t.tool_use_id.as_deref() == Some("toolu_dup"))
+            .collect();
+        assert_eq!(dup.len(), 1);
+        assert_eq!(dup[0].id, first.id);
+    }
+
+    #[test]
+    fn test_migration_removes_duplicate_tool_invocations() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Test".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let original = ToolInvocation::new(
+            interaction.id,
+            1,
+            "Read".to_string(),
+            serde_json::json!({"file_path": "/a.rs"}),
+            Some("toolu_replayed".to_string()),
+        );
+        store.insert_tool_invocation(&original).unwrap();
+
+        // Simulate a database from before the unique index existed
+        {
+            let conn = store.conn.lock().unwrap();
+            conn.execute_batch("DROP INDEX idx_tool_invocations_tool_use_id")
+                .unwrap();
+            for seq in 2..=3 {
+                conn.execute(
+                    r#"
+                    INSERT INTO tool_invocations (
+                        id, interaction_id, tool_use_id, sequence_number, tool_name,
+                        tool_input, started_at
+                    )
+                    SELECT ?1, interaction_id, tool_use_id, ?2, tool_name, tool_input, started_at
+                    FROM tool_invocations WHERE id = ?3
+                    "#,
+                    params![Uuid::new_v4().to_string(), seq, original.id.to_string()],
+                )
+                .unwrap();
+            }
+        }
+        assert_eq!(store.list_tool_invocations(interaction.id).unwrap().len(), 3);
+
+        store.migrate().unwrap();
+
+        let remaining = store.list_tool_invocations(interaction.id).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, original.id);
+
+        let replay = ToolInvocation::new(
+            interaction.id,
+            4,
+            "Read".to_string(),
+            serde_json::json!({"file_path": "/a.rs"}),
+            Some("toolu_replayed".to_string()),
+        );
+        assert!(!store.insert_tool_invocation(&replay).unwrap());
+    }
+
+    #[test]
+    fn test_file_content_deduplication() {
+        let (store, _dir) = create_test_store();
+
+        let content = b"Hello, world!";
+
+        // Store content first time
+        let (hash1, is_new1) = store.store_file_content(content).unwrap();
+        assert!(is_new1);
+
+        // Store same content again
+        let (hash2, is_new2) = store.store_file_content(content).unwrap();
+        assert!(!is_new2);
+        assert_eq!(hash1, hash2);
+
+        // Retrieve content
+        let loaded = store.get_file_content(&hash1).unwrap().unwrap();
+        assert_eq!(loaded, content);
+    }
+
+    #[test]
+    fn test_sequence_numbers() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        // First interaction should be 1
+        let seq = store.next_sequence_number(session_id).unwrap();
+        assert_eq!(seq, 1);
+
+        // Insert interaction
+        let interaction = Interaction::new(session_id, 1, "First".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        // Next should be 2
+        let seq = store.next_sequence_number(session_id).unwrap();
+        assert_eq!(seq, 2);
+    }
+
+    #[test]
+    fn test_thinking_tokens_tracked() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let first = Interaction::new(session_id, 1, "Think hard".to_string());
+        store.insert_interaction(&first).unwrap();
+        store
+            .complete_interaction_with_costs(first.id, 0.25, 1000, 500, 300)
+            .unwrap();
+        let second = Interaction::new(session_id, 2, "Quick one".to_string());
+        store.insert_interaction(&second).unwrap();
+        store
+            .complete_interaction_with_costs(second.id, 0.05, 200, 50, 0)
+            .unwrap();
+
+        let loaded = store.get_interaction(first.id).unwrap().unwrap();
+        assert_eq!(loaded.thinking_tokens_delta, 300);
+        assert_eq!(loaded.output_tokens_delta, 500);
+
+        let summary = store.get_analytics_summary(None, None).unwrap();
+        assert_eq!(summary.total_thinking_tokens, 300);
+        assert_eq!(summary.total_output_tokens, 550);
+
+        let session = store.get_session_analytics(session_id).unwrap();
+        assert_eq!(session.total_thinking_tokens, 300);
+    }
+
+    #[test]
+    fn test_min_duration_excludes_short_interactions() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let now = Utc::now();
+        let seed = |seq: u32, duration: chrono::Duration, cost: f64| {
+            let mut interaction = Interaction::new(session_id, seq, format!("Prompt {seq}"));
+            interaction.started_at = now - chrono::Duration::minutes(5);
+            interaction.ended_at = Some(interaction.started_at + duration);
+            interaction.cost_usd_delta = cost;
+            store.insert_interaction(&interaction).unwrap();
+            interaction
+        };
+        let blip = seed(1, chrono::Duration::milliseconds(100), 0.01);
+        seed(2, chrono::Duration::seconds(30), 0.5);
+        let mut running = Interaction::new(session_id, 3, "Still going".to_string());
+        running.started_at = now - chrono::Duration::minutes(1);
+        store.insert_interaction(&running).unwrap();
+        let tool = ToolInvocation::new(blip.id, 1, "Read".to_string(), serde_json::json!({}), None);
+        store.insert_tool_invocation(&tool).unwrap();
+
+        let all = store.get_analytics_summary(None, None).unwrap();
+        assert_eq!(all.interaction_count, 3);
+        assert_eq!(all.total_tool_invocations, 1);
+
+        let filtered = store.get_analytics_summary(Some(1000), None).unwrap();
+        assert_eq!(filtered.interaction_count, 2);
+        assert_eq!(filtered.total_cost_usd, 0.5);
+        assert_eq!(filtered.total_tool_invocations, 0);
+
+        let daily = store.get_daily_cost_breakdown(1, Some(1000)).unwrap();
+        let counted: u32 = daily.iter().map(|d| d.interaction_count).sum();
+        assert_eq!(counted, 2);
+        let daily = store.get_daily_cost_breakdown(1, None).unwrap();
+        assert_eq!(daily.iter().map(|d| d.interaction_count).sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn test_analytics_delta_between_weeks() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let week_start = Utc::now() - chrono::Duration::days(14);
+        let mut seq = 0;
+        let mut seed = |day: i64, cost: f64, input_tokens: u64, tools: u32| {
+            seq += 1;
+            let mut interaction = Interaction::new(session_id, seq, format!("Prompt {seq}"));
+            interaction.started_at = week_start + chrono::Duration::days(day);
+            interaction.ended_at = Some(interaction.started_at + chrono::Duration::seconds(30));
+            interaction.cost_usd_delta = cost;
+            interaction.input_tokens_delta = input_tokens;
+            store.insert_interaction(&interaction).unwrap();
+            for n in 1..=tools {
+                let tool = ToolInvocation::new(interaction.id, n, "Read".to_string(), serde_json::json!({}), None);
+                store.insert_tool_invocation(&tool).unwrap();
+            }
+        };
+        // Last week: $2.00, 2 interactions, 1000 input tokens, 4 tools
+        seed(1, 1.5, 600, 3);
+        seed(3, 0.5, 400, 1);
+        // This week: $3.00, 1 interaction, 1500 input tokens, 2 tools
+        seed(9, 3.0, 1500, 2);
+
+        let last_week = (week_start, week_start + chrono::Duration::days(7));
+        let this_week = (last_week.1, last_week.1 + chrono::Duration::days(7));
+        let a = store.get_analytics_summary(None, Some(last_week)).unwrap();
+        let b = store.get_analytics_summary(None, Some(this_week)).unwrap();
+        assert_eq!((a.interaction_count, a.total_tool_invocations), (2, 4));
+        assert_eq!((b.interaction_count, b.total_tool_invocations), (1, 2));
+
+        let delta = AnalyticsDelta::between(&a, &b);
+        assert!((delta.cost_usd.absolute - 1.0).abs() < 1e-9);
+        assert!((delta.cost_usd.percent.unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(delta.interactions.percent, Some(-50.0));
+        assert_eq!(delta.input_tokens.percent, Some(50.0));
+        assert_eq!(delta.tool_invocations.percent, Some(-50.0));
+        assert_eq!(delta.output_tokens.percent, None);
+    }
+
+    #[test]
+    fn test_context_history_in_sequence_order() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        // Usage climbs, then drops after a compaction
+        for (seq, percent) in [(1, 20), (2, 55), (3, 90), (4, 15)] {
+            let interaction = Interaction::new(session_id, seq, format!("Prompt {seq}"));
+            store.insert_interaction(&interaction).unwrap();
+            store
+                .complete_interaction_with_costs(interaction.id, 0.0, 0, 0, 0)
+                .unwrap();
+            store
+                .set_interaction_context_percent(interaction.id, percent)
+                .unwrap();
+        }
+        // An interaction without a snapshot is left out
+        let active = Interaction::new(session_id, 5, "Still running".to_string());
+        store.insert_interaction(&active).unwrap();
+
+        let history = store.context_history(session_id).unwrap();
+        assert_eq!(history, vec![(1, 20), (2, 55), (3, 90), (4, 15)]);
+
+        let loaded = store.get_interaction(active.id).unwrap().unwrap();
+        assert_eq!(loaded.context_percent, None);
+        assert!(store.context_history(Uuid::new_v4()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_interrupt_active_interactions() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let done = Interaction::new(session_id, 1, "First".to_string());
+        store.insert_interaction(&done).unwrap();
+        store.complete_interaction(done.id).unwrap();
+        let active = Interaction::new(session_id, 2, "Second".to_string());
+        store.insert_interaction(&active).unwrap();
+
+        assert_eq!(store.interrupt_active_interactions(session_id).unwrap(), 1);
+
+        let loaded = store.get_interaction(active.id).unwrap().unwrap();
+        assert_eq!(loaded.status, InteractionStatus::Interrupted);
+        assert_ne!(loaded.status, InteractionStatus::Failed);
+        assert!(loaded.ended_at.is_some());
+        assert!(loaded.error_message.is_none());
+        assert!(store.get_active_interaction(session_id).unwrap().is_none());
+
+        let loaded = store.get_interaction(done.id).unwrap().unwrap();
+        assert_eq!(loaded.status, InteractionStatus::Completed);
+
+        // Completing an interrupted interaction leaves it interrupted
+        store.complete_interaction(active.id).unwrap();
+        store.complete_interaction_with_costs(active.id, 0.5, 10, 10, 0).unwrap();
+        let loaded = store.get_interaction(active.id).unwrap().unwrap();
+        assert_eq!(loaded.status, InteractionStatus::Interrupted);
+    }
+
+    #[test]
+    fn test_dedupe_session_interactions() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        // Two "active" interactions for the same turn (hook glitch)
+        let first = Interaction::new(session_id, 1, "Fix the bug".to_string());
+        store.insert_interaction(&first).unwrap();
+        let second = Interaction::new(session_id, 2, "Fix the bug".to_string());
+        store.insert_interaction(&second).unwrap();
+        // A later, distinct turn must be left alone
+        let third = Interaction::new(session_id, 3, "Now add tests".to_string());
+        store.insert_interaction(&third).unwrap();
+
+        let read = ToolInvocation::new(first.id, 1, "Read".to_string(), serde_json::json!({}), None);
+        store.insert_tool_invocation(&read).unwrap();
+        let edit = ToolInvocation::new(second.id, 1, "Edit".to_string(), serde_json::json!({}), None);
+        store.insert_tool_invocation(&edit).unwrap();
+        let bash = ToolInvocation::new(second.id, 2, "Bash".to_string(), serde_json::json!({}), None);
+        store.insert_tool_invocation(&bash).unwrap();
+
+        let (hash, _) = store.store_file_content(b"after").unwrap();
+        let snapshot = FileSnapshot::new(
+            second.id,
+            Some(edit.id),
+            PathBuf::from("/test/src/lib.rs"),
+            hash,
+            SnapshotType::After,
+            5,
+        );
+        store.insert_file_snapshot(&snapshot).unwrap();
+
+        let merged = store.dedupe_session_interactions(session_id).unwrap();
+        assert_eq!(merged, 1);
+
+        assert!(store.get_interaction(second.id).unwrap().is_none());
+        let remaining = store.list_interactions(session_id, 10, 0).unwrap();
+        assert_eq!(remaining.len(), 2);
+
+        let tools = store.list_tool_invocations(first.id).unwrap();
+        let names: Vec<_> = tools.iter().map(|t| t.tool_name.as_str()).collect();
+        assert_eq!(names, vec!["Read", "Edit", "Bash"]);
+        let seqs: Vec<_> = tools.iter().map(|t| t.sequence_number).collect();
+        assert_eq!(seqs, vec![1, 2, 3]);
+
+        let snapshot = store.get_file_snapshot(snapshot.id).unwrap().unwrap();
+        assert_eq!(snapshot.interaction_id, first.id);
+
+        // Running again is a no-op
+        assert_eq!(store.dedupe_session_interactions(session_id).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_dedupe_keeps_same_prompt_turns_far_apart() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        // The first turn never ended; the same prompt was sent again an hour later
+        let mut first = Interaction::new(session_id, 1, "Run the tests".to_string());
+        first.started_at = Utc::now() - chrono::Duration::hours(1);
+        store.insert_interaction(&first).unwrap();
+        let second = Interaction::new(session_id, 2, "Run the tests".to_string());
+        store.insert_interaction(&second).unwrap();
+
+        assert_eq!(store.dedupe_session_interactions(session_id).unwrap(), 0);
+        assert_eq!(store.list_interactions(session_id, 10, 0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_daily_active_sessions() {
+        let (store, _dir) = create_test_store();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        create_test_session(&store, first);
+        create_test_session(&store, second);
+
+        let now = Utc::now();
+        let yesterday = now - chrono::Duration::days(1);
+        for (session_id, seq, started_at) in [
+            (first, 1, yesterday),
+            (first, 2, now),
+            (first, 3, now),
+            (second, 1, now),
+        ] {
+            let mut interaction = Interaction::new(session_id, seq, format!("prompt {seq}"));
+            interaction.started_at = started_at;
+            store.insert_interaction(&interaction).unwrap();
+        }
+
+        let days = store.daily_active_sessions(7).unwrap();
+        assert_eq!(
+            days,
+            vec![
+                (now.format("%Y-%m-%d").to_string(), 2),
+                (yesterday.format("%Y-%m-%d").to_string(), 1),
+            ]
+        );
+
+        // Only today is in range
+        assert_eq!(store.daily_active_sessions(0).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recover_stale_active() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let mut stale = Interaction::new(session_id, 1, "before the crash".to_string());
+        stale.started_at = Utc::now() - chrono::Duration::hours(2);
+        store.insert_interaction(&stale).unwrap();
+        let fresh = Interaction::new(session_id, 2, "just started".to_string());
+        store.insert_interaction(&fresh).unwrap();
+
+        let recovered = store.recover_stale_active(std::time::Duration::from_secs(600)).unwrap();
+        assert_eq!(recovered, 1);
+
+        let stale = store.get_interaction(stale.id).unwrap().unwrap();
+        assert_eq!(stale.status, InteractionStatus::Failed);
+        assert_eq!(stale.error_message.as_deref(), Some("Recovered after restart"));
+        assert!(stale.ended_at.is_some());
+        let fresh = store.get_interaction(fresh.id).unwrap().unwrap();
+        assert_eq!(fresh.status, InteractionStatus::Active);
+
+        // Already-recovered interactions aren't counted again
+        assert_eq!(store.recover_stale_active(std::time::Duration::from_secs(600)).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_list_failed_interactions_and_tool_errors() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let ok = Interaction::new(session_id, 1, "Works fine".to_string());
+        store.insert_interaction(&ok).unwrap();
+        store.complete_interaction(ok.id).unwrap();
+
+        let failed = Interaction::new(session_id, 2, "Crashes".to_string());
+        store.insert_interaction(&failed).unwrap();
+        store.fail_interaction(failed.id, "process exited").unwrap();
+
+        let tool_error = Interaction::new(session_id, 3, "Runs a bad command".to_string());
+        store.insert_interaction(&tool_error).unwrap();
+        let mut bash = ToolInvocation::new(
+            tool_error.id,
+            1,
+            "Bash".to_string(),
+            serde_json::json!({ "command": "false" }),
+            None,
+        );
+        bash.is_error = true;
+        store.insert_tool_invocation(&bash).unwrap();
+        store.complete_interaction(tool_error.id).unwrap();
+
+        let rows = store.list_failed_interactions(10, 0, None).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, failed.id);
+        assert_eq!(rows[0].error_message.as_deref(), Some("process exited"));
+
+        let rows = store.list_interactions_with_tool_errors(10, 0, None).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, tool_error.id);
+
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(store.list_failed_interactions(10, 0, Some(future)).unwrap().is_empty());
+        assert!(store.list_interactions_with_tool_errors(10, 0, Some(future)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_generate_session_summary() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        assert_eq!(store.generate_session_summary(session_id).unwrap(), "");
+
+        let first = Interaction::new(
+            session_id,
+            1,
+            "Refactor the websocket reconnect logic so it backs off exponentially\nand logs".to_string(),
+        );
+        let second = Interaction::new(session_id, 2, "Now add tests".to_string());
+        store.insert_interaction(&first).unwrap();
+        store.insert_interaction(&second).unwrap();
+
+        let tools = [
+            (first.id, "Edit", "/repo/src/ws.ts"),
+            (first.id, "Read", "/repo/src/api.ts"),
+            (second.id, "Edit", "/repo/src/ws.ts"),
+            (second.id, "Write", "/repo/src/ws.test.ts"),
+        ];
+        for (seq, (interaction_id, tool, path)) in tools.into_iter().enumerate() {
+            let invocation = ToolInvocation::new(
+                interaction_id,
+                seq as u32 + 1,
+                tool.to_string(),
+                serde_json::json!({ "file_path": path }),
+                None,
+            );
+            store.insert_tool_invocation(&invocation).unwrap();
+        }
+
+        let summary = store.generate_session_summary(session_id).unwrap();
+        assert_eq!(
+            summary,
+            "Refactor the websocket reconnect logic so it backs off expon… · 2 files changed · 4 tool calls"
+        );
+    }
+
+    #[test]
+    fn test_backfill_summaries_from_transcript() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let first = Interaction::new(session_id, 1, "Why is the build failing?".to_string());
+        let mut second = Interaction::new(session_id, 2, "Fix it".to_string());
+        second.assistant_summary = Some("Existing summary".to_string());
+        let third = Interaction::new(session_id, 3, "Now add tests".to_string());
+        for interaction in [&first, &second, &third] {
+            store.insert_interaction(interaction).unwrap();
+        }
+
+        let transcript = dir.path().join("transcript.jsonl");
+        std::fs::write(
+            &transcript,
+            [
+                r#"{"type":"user","message":{"role":"user","content":"Why is the build failing?"}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"Let me look"},{"type":"tool_use","name":"Bash"}]}}"#,
+                r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","content":"error[E0425]"}]}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"\nThe build fails because `foo` is undefined. I'll check where it was removed.\nMore detail."}]}}"#,
+                r#"{"type":"user","message":{"role":"user","content":"Fix it"}}"#,
+                r#"{"type":"assistant","message":{"role":"assistant","content":"Fixed by restoring the import."}}"#,
+                r#"{"type":"user","message":{"role":"user","content":"Now add tests"}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let updated = store.backfill_summaries_from_transcript(session_id, &transcript).unwrap();
+        assert_eq!(updated, 1);
+
+        let summary = |id| store.get_interaction(id).unwrap().unwrap().assistant_summary;
+        assert_eq!(
+            summary(first.id).as_deref(),
+            Some("The build fails because `foo` is undefined.")
+        );
+        assert_eq!(summary(second.id).as_deref(), Some("Existing summary"));
+        // The last turn has no assistant reply yet
+        assert_eq!(summary(third.id), None);
+
+        // Already-filled summaries are left alone on a second run
+        assert_eq!(store.backfill_summaries_from_transcript(session_id, &transcript).unwrap(), 0);
+        // Sessions whose transcript can't be located are a no-op
+        assert_eq!(store.backfill_summaries(session_id).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_content_references_lists_every_snapshot() {
+        let (store, _dir) = create_test_store();
+        let first_session = Uuid::new_v4();
+        let second_session = Uuid::new_v4();
+        create_test_session(&store, first_session);
+        create_test_session(&store, second_session);
+
+        let first = Interaction::new(first_session, 1, "Add the README".to_string());
+        let second = Interaction::new(second_session, 1, "Copy the README".to_string());
+        store.insert_interaction(&first).unwrap();
+        store.insert_interaction(&second).unwrap();
+
+        let content = b"# Project\n";
+        let (hash, _) = store.store_file_content(content).unwrap();
+        for (interaction, path, snapshot_type) in [
+            (&first, "/repo/README.md", SnapshotType::After),
+            (&second, "/fork/README.md", SnapshotType::Before),
+        ] {
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    PathBuf::from(path),
+                    hash.clone(),
+                    snapshot_type,
+                    content.len() as u64,
+                ))
+                .unwrap();
+        }
+
+        let refs = store.content_references(&hash).unwrap();
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].interaction_id, first.id);
+        assert_eq!(refs[0].session_id, first_session);
+        assert_eq!(refs[0].file_path, PathBuf::from("/repo/README.md"));
+        assert_eq!(refs[0].snapshot_type, SnapshotType::After);
+        assert_eq!(refs[1].session_id, second_session);
+        assert_eq!(refs[1].snapshot_type, SnapshotType::Before);
+        assert_eq!(store.content_reference_count(&hash).unwrap(), Some(2));
+
+        assert!(store.content_references("missing").unwrap().is_empty());
+        assert_eq!(store.content_reference_count("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_all_touched_files_counts_sessions() {
+        let (store, _dir) = create_test_store();
+        let (hash, _) = store.store_file_content(b"fn main() {}\n").unwrap();
+        let touch = |session_id: Uuid, seq: u32, file_path: &str| {
+            let interaction = Interaction::new(session_id, seq, "edit".to_string());
+            store.insert_interaction(&interaction).unwrap();
+            for snapshot_type in [SnapshotType::Before, SnapshotType::After] {
+                store
+                    .insert_file_snapshot(&FileSnapshot::new(
+                        interaction.id,
+                        None,
+                        file_path.into(),
+                        hash.clone(),
+                        snapshot_type,
+                        13,
+                    ))
+                    .unwrap();
+            }
+        };
+
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        create_test_session(&store, first);
+        create_test_session(&store, second);
+        touch(first, 1, "/repo/src/main.rs");
+        touch(first, 2, "/repo/README.md");
+        touch(second, 1, "/repo/src/main.rs");
+
+        let files = store.all_touched_files(50, 0).unwrap();
+        assert_eq!(files.len(), 2);
+        // Most recently touched first
+        assert_eq!(files[0].file_path, PathBuf::from("/repo/src/main.rs"));
+        assert_eq!(files[0].session_count, 2);
+        assert_eq!(files[0].snapshot_count, 4);
+        assert_eq!(files[1].file_path, PathBuf::from("/repo/README.md"));
+        assert_eq!(files[1].session_count, 1);
+        assert_eq!(files[1].snapshot_count, 2);
+
+        let page = store.all_touched_files(1, 1).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].file_path, PathBuf::from("/repo/README.md"));
+    }
+
+    #[test]
+    fn test_file_authorship_marks_added_lines() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let file_path = "/repo/src/retry.rs";
+
+        let edit = |seq: u32, before: &[u8], after: &[u8]| {
+            let interaction = Interaction::new(session_id, seq, format!("edit {seq}"));
+            store.insert_interaction(&interaction).unwrap();
+            for (content, snapshot_type) in [(before, SnapshotType::Before), (after, SnapshotType::After)] {
+                let (hash, _) = store.store_file_content(content).unwrap();
+                store
+                    .insert_file_snapshot(&FileSnapshot::new(
+                        interaction.id,
+                        None,
+                        file_path.into(),
+                        hash,
+                        snapshot_type,
+                        content.len() as u64,
+                    ))
+                    .unwrap();
+            }
+        };
+        let original = b"fn retry() {\n    attempt();\n}\n";
+        let first = b"fn retry() {\n    attempt();\n    sleep(100);\n}\n";
+        let last = b"use std::time;\n\nfn retry() {\n    attempt();\n    sleep(100);\n}\n";
+        edit(1, original, first);
+        edit(2, first, last);
+
+        let lines = store.file_authorship(session_id, file_path).unwrap();
+        let claude: Vec<_> = lines
+            .iter()
+            .filter(|l| l.author == LineAuthor::Claude)
+            .map(|l| (l.line_number, l.content.as_str()))
+            .collect();
+        assert_eq!(claude, [(1, "use std::time;"), (2, ""), (5, "    sleep(100);")]);
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2].author, LineAuthor::PreExisting);
+
+        // Untouched files have no attribution
+        assert!(store.file_authorship(session_id, "/repo/other.rs").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_file_authorship_attributes_created_file_to_claude() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let file_path = "/repo/src/backoff.rs";
+
+        let snapshot = |seq: u32, snapshots: &[(&[u8], SnapshotType)]| {
+            let interaction = Interaction::new(session_id, seq, format!("edit {seq}"));
+            store.insert_interaction(&interaction).unwrap();
+            for (content, snapshot_type) in snapshots {
+                let (hash, _) = store.store_file_content(content).unwrap();
+                store
+                    .insert_file_snapshot(&FileSnapshot::new(
+                        interaction.id,
+                        None,
+                        file_path.into(),
+                        hash,
+                        *snapshot_type,
+                        content.len() as u64,
+                    ))
+                    .unwrap();
+            }
+        };
+        let created = b"fn backoff() {}\n";
+        let edited = b"fn backoff() {}\nfn jitter() {}\n";
+        // Claude writes the file, then edits it in a later interaction
+        snapshot(1, &[(created, SnapshotType::After)]);
+        snapshot(2, &[(created, SnapshotType::Before), (edited, SnapshotType::After)]);
+
+        let lines = store.file_authorship(session_id, file_path).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.author == LineAuthor::Claude));
+    }
+
+    #[test]
+    fn test_export_interaction_full_inlines_snapshots() {
+        use base64::Engine;
+
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let mut interaction = Interaction::new(session_id, 1, "Add a retry".to_string());
+        interaction.assistant_summary = Some("Wrapped the call in a retry".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        store.update_interaction(&interaction).unwrap();
+
+        let mut edit = ToolInvocation::new(
+            interaction.id,
+            1,
+            "Edit".to_string(),
+            serde_json::json!({"file_path": "/repo/src/net.rs", "old_string": "call()", "new_string": "retry(call)"}),
+            None,
+        );
+        edit.tool_output_preview = Some("Edited /repo/src/net.rs".to_string());
+        store.insert_tool_invocation(&edit).unwrap();
+        store.update_tool_invocation(&edit).unwrap();
+
+        let before = b"fn main() { call(); }\n";
+        let after = b"fn main() { retry(call); }\n";
+        for (content, snapshot_type) in [(&before[..], SnapshotType::Before), (&after[..], SnapshotType::After)] {
+            let (hash, _) = store.store_file_content(content).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    Some(edit.id),
+                    "/repo/src/net.rs".into(),
+                    hash,
+                    snapshot_type,
+                    content.len() as u64,
+                ))
+                .unwrap();
+        }
+
+        let export = store.export_interaction_full(interaction.id).unwrap();
+        assert_eq!(export["interaction"]["user_prompt"], "Add a retry");
+        assert_eq!(export["interaction"]["assistant_summary"], "Wrapped the call in a retry");
+        assert_eq!(export["tool_invocations"][0]["tool_input"]["new_string"], "retry(call)");
+        assert_eq!(export["tool_invocations"][0]["tool_output_preview"], "Edited /repo/src/net.rs");
+
+        let change = &export["file_changes"][0];
+        assert_eq!(change["change_type"], "modified");
+        let decode = |value: &serde_json::Value| {
+            base64::engine::general_purpose::STANDARD
+                .decode(value["content_base64"].as_str().unwrap())
+                .unwrap()
+        };
+        assert_eq!(decode(&change["before"]), before);
+        assert_eq!(decode(&change["after"]), after);
+
+        assert!(matches!(
+            store.export_interaction_full(Uuid::new_v4()),
+            Err(ClausetError::InteractionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_file_across_sessions() {
+        let (store, _dir) = create_test_store();
+        let (session_a, session_b, session_c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let file_path = "/repo/src/retry.rs";
+
+        for session_id in [session_a, session_b, session_c] {
+            create_test_session(&store, session_id);
+        }
+
+        let snapshot = |session_id: Uuid, seq: u32, content: &[u8]| {
+            let interaction = Interaction::new(session_id, seq, format!("attempt {seq}"));
+            store.insert_interaction(&interaction).unwrap();
+            let (hash, _) = store.store_file_content(content).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    file_path.into(),
+                    hash,
+                    SnapshotType::After,
+                    content.len() as u64,
+                ))
+                .unwrap();
+        };
+        // Session A's second edit is its latest version
+        snapshot(session_a, 1, b"fn retry() {\n    todo!()\n}\n");
+        snapshot(session_a, 2, b"fn retry() {\n    sleep(100);\n}\n");
+        snapshot(session_b, 1, b"fn retry() {\n    backoff(2);\n    log();\n}\n");
+
+        let diff = store
+            .diff_file_across_sessions(session_a, session_b, file_path, 3)
+            .unwrap()
+            .unwrap();
+        assert!(!diff.is_identical);
+        assert_eq!((diff.lines_added, diff.lines_removed), (2, 1));
+
+        // Absent from one session: the whole file is added or removed
+        let diff = store
+            .diff_file_across_sessions(session_c, session_b, file_path, 3)
+            .unwrap()
+            .unwrap();
+        assert_eq!((diff.lines_added, diff.lines_removed), (4, 0));
+        let diff = store
+            .diff_file_across_sessions(session_a, session_c, file_path, 3)
+            .unwrap()
+            .unwrap();
+        assert_eq!((diff.lines_added, diff.lines_removed), (0, 3));
+
+        assert!(store
+            .diff_file_across_sessions(session_a, session_b, "/repo/other.rs", 3)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_diff_against_disk_reports_drift() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let interaction = Interaction::new(session_id, 1, "Edit the config".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let file_path = dir.path().join("config.toml");
+        let written = b"port = 8080\nhost = \"localhost\"\n";
+        std::fs::write(&file_path, written).unwrap();
+        let (hash, _) = store.store_file_content(written).unwrap();
+        store
+            .insert_file_snapshot(&FileSnapshot::new(
+                interaction.id,
+                None,
+                file_path.clone(),
+                hash,
+                SnapshotType::After,
+                written.len() as u64,
+            ))
+            .unwrap();
+
+        let diff = store.diff_against_disk(interaction.id, &file_path, 3).unwrap().unwrap();
+        assert!(diff.is_identical);
+        let stored = store
+            .get_snapshot_content(interaction.id, &file_path.to_string_lossy(), SnapshotType::After)
+            .unwrap();
+        assert_eq!(stored.as_deref(), Some(&written[..]));
+
+        // Edited outside of Claude
+        std::fs::write(&file_path, "port = 9090\nhost = \"localhost\"\n").unwrap();
+        let diff = store.diff_against_disk(interaction.id, &file_path, 3).unwrap().unwrap();
+        assert!(!diff.is_identical);
+        assert_eq!((diff.lines_added, diff.lines_removed), (1, 1));
+
+        // Deleted from disk
+        std::fs::remove_file(&file_path).unwrap();
+        let diff = store.diff_against_disk(interaction.id, &file_path, 3).unwrap().unwrap();
+        assert_eq!((diff.lines_added, diff.lines_removed), (0, 2));
+
+        // No snapshot for this file
+        let other = dir.path().join("other.toml");
+        assert!(store.diff_against_disk(interaction.id, &other, 3).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_degrades_diff_listing() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Edit two files".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let snapshot = |name: &str, content: &[u8], snapshot_type: SnapshotType| {
+            let (hash, _) = store.store_file_content(content).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    dir.path().join(name),
+                    hash.clone(),
+                    snapshot_type,
+                    content.len() as u64,
+                ))
+                .unwrap();
+            hash
+        };
+        snapshot("good.txt", b"one\n", SnapshotType::Before);
+        snapshot("good.txt", b"one\ntwo\n", SnapshotType::After);
+        snapshot("bad.txt", b"old\n", SnapshotType::Before);
+        let corrupt_hash = snapshot("bad.txt", b"new\n", SnapshotType::After);
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE file_contents SET compressed_content = ?1 WHERE content_hash = ?2",
+                params![b"not zstd at all".to_vec(), corrupt_hash],
+            )
+            .unwrap();
+
+        match store.get_file_content(&corrupt_hash) {
+            Err(ClausetError::CorruptSnapshot { content_hash }) => assert_eq!(content_hash, corrupt_hash),
+            other => panic!("expected CorruptSnapshot, got {other:?}"),
+        }
+
+        let mut changes = store
+            .get_file_changes_with_diffs(interaction.id, 3, &crate::diff::DiffOptions::default())
+            .unwrap();
+        changes.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].file_path.ends_with("bad.txt"));
+        assert!(changes[0].diff.decode_error);
+        assert!(changes[0].diff.hunks.is_empty());
+        assert!(changes[1].file_path.ends_with("good.txt"));
+        assert!(!changes[1].diff.decode_error);
+        assert_eq!((changes[1].diff.lines_added, changes[1].diff.lines_removed), (1, 0));
+    }
+
+    #[test]
+    fn test_multi_edit_file_diffs_first_before_against_last_after() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Edit a file twice".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let t0 = Utc::now();
+        // Ids are chosen so that picking by id would select the intermediate states
+        for (id, content, snapshot_type, secs) in [
+            ("00000000-0000-0000-0000-000000000001", &b"one
+"[..], SnapshotType::Before, 0),
+            ("00000000-0000-0000-0000-000000000003", b"one
+two
+", SnapshotType::After, 1),
+            ("00000000-0000-0000-0000-000000000004", b"one
+two
+", SnapshotType::Before, 2),
+            ("00000000-0000-0000-0000-000000000002", b"one
+two
+three
+", SnapshotType::After, 3),
+        ] {
+            let (hash, _) = store.store_file_content(content).unwrap();
+            let mut snapshot = FileSnapshot::new(
+                interaction.id,
+                None,
+                dir.path().join("notes.txt"),
+                hash,
+                snapshot_type,
+                content.len() as u64,
+            );
+            snapshot.id = Uuid::parse_str(id).unwrap();
+            snapshot.created_at = t0 + chrono::Duration::seconds(secs);
+            store.insert_file_snapshot(&snapshot).unwrap();
+        }
+
+        let changes = store.list_file_changes(interaction.id).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].before_snapshot_id, Some(Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap()));
+        assert_eq!(changes[0].after_snapshot_id, Some(Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap()));
+
+        let diffs = store
+            .get_file_changes_with_diffs(interaction.id, 3, &crate::diff::DiffOptions::default())
+            .unwrap();
+        assert_eq!((diffs[0].diff.lines_added, diffs[0].diff.lines_removed), (2, 0));
+    }
+
+    #[test]
+    fn test_repeated_diff_is_served_from_cache() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Edit a file".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        for (content, snapshot_type) in [(&b"one\n"[..], SnapshotType::Before), (b"one\ntwo\n", SnapshotType::After)] {
+            let (hash, _) = store.store_file_content(content).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    dir.path().join("notes.txt"),
+                    hash,
+                    snapshot_type,
+                    content.len() as u64,
+                ))
+                .unwrap();
+        }
+
+        let computed = AtomicUsize::new(0);
+        let counting = |before: Option<&[u8]>, after: Option<&[u8]>, context: usize, options: &DiffOptions| {
+            computed.fetch_add(1, Ordering::SeqCst);
+            crate::diff::compute_diff_with_options(before, after, context, options)
+        };
+        let options = DiffOptions::default();
+
+        let first = store.file_changes_with_diffs_using(interaction.id, 3, &options, counting).unwrap();
+        let second = store.file_changes_with_diffs_using(interaction.id, 3, &options, counting).unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+        assert_eq!(first[0].diff.lines_added, 1);
+        assert_eq!(second[0].diff.lines_added, 1);
+
+        // Different context is a different diff
+        store.file_changes_with_diffs_using(interaction.id, 0, &options, counting).unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_copy_interaction_shares_snapshot_content() {
+        let (store, _dir) = create_test_store();
+        let source_session = Uuid::new_v4();
+        let target_session = Uuid::new_v4();
+        create_test_session(&store, source_session);
+        create_test_session(&store, target_session);
+
+        let mut original = Interaction::new(source_session, 1, "Write the parser".to_string());
+        original.cost_usd_delta = 0.5;
+        store.insert_interaction(&original).unwrap();
+        let edit = ToolInvocation::new(
+            original.id,
+            1,
+            "Edit".to_string(),
+            serde_json::json!({ "file_path": "/test/src/parser.rs" }),
+            Some("toolu_123".to_string()),
+        );
+        store.insert_tool_invocation(&edit).unwrap();
+
+        let (hash, _) = store.store_file_content(b"fn parse() {}").unwrap();
+        let snapshot = FileSnapshot::new(
+            original.id,
+            Some(edit.id),
+            PathBuf::from("/test/src/parser.rs"),
+            hash.clone(),
+            SnapshotType::After,
+            13,
+        );
+        store.insert_file_snapshot(&snapshot).unwrap();
+
+        let ref_count = |store: &InteractionStore| -> i64 {
+            let conn = store.conn.lock().unwrap();
+            conn.query_row(
+                "SELECT reference_count FROM file_contents WHERE content_hash = ?1",
+                params![hash],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(ref_count(&store), 1);
+
+        // The target already has one interaction, so the copy comes next
+        store
+            .insert_interaction(&Interaction::new(target_session, 1, "Existing".to_string()))
+            .unwrap();
+
+        let copy_id = store.copy_interaction(original.id, target_session).unwrap();
+        assert_ne!(copy_id, original.id);
+
+        let copy = store.get_interaction(copy_id).unwrap().unwrap();
+        assert_eq!(copy.session_id, target_session);
+        assert_eq!(copy.sequence_number, 2);
+        assert_eq!(copy.user_prompt, "Write the parser");
+        assert_eq!(copy.cost_usd_delta, 0.0);
+
+        let tools = store.list_tool_invocations(copy_id).unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_ne!(tools[0].id, edit.id);
+        assert_eq!(tools[0].tool_use_id, None);
+
+        let (_, after) = store.get_tool_snapshots(tools[0].id).unwrap();
+        let after = after.unwrap();
+        assert_eq!(after.interaction_id, copy_id);
+        assert_eq!(after.content_hash, hash);
+        let content = store.get_file_content(&after.content_hash).unwrap();
+        assert_eq!(content.as_deref(), Some(&b"fn parse() {}"[..]));
+        assert_eq!(ref_count(&store), 2);
+
+        // The source is untouched
+        assert_eq!(store.list_tool_invocations(original.id).unwrap().len(), 1);
+        assert!(matches!(
+            store.copy_interaction(Uuid::new_v4(), target_session),
+            Err(ClausetError::InteractionNotFound(_))
+        ));
+    }
+
+    fn insert_costed_interaction(
+        store: &InteractionStore,
+        session_id: Uuid,
+        sequence_number: u32,
+        started_at: &str,
+        cost: f64,
+    ) {
+        let mut interaction = Interaction::new(session_id, sequence_number, "prompt".to_string());
+        interaction.started_at = DateTime::parse_from_rfc3339(started_at).unwrap().with_timezone(&Utc);
+        interaction.cost_usd_delta = cost;
+        interaction.input_tokens_delta = 100;
+        interaction.output_tokens_delta = 10;
+        store.insert_interaction(&interaction).unwrap();
+    }
+
+    fn seed_cost_timeseries(store: &InteractionStore) -> (Uuid, Uuid) {
+        let session_a = Uuid::new_v4();
+        let session_b = Uuid::new_v4();
+        create_test_session(store, session_a);
+        create_test_session(store, session_b);
+
+        // Sunday 2025-03-02 (end of ISO week starting Mon 2025-02-24)
+        insert_costed_interaction(store, session_a, 1, "2025-03-02T23:59:59+00:00", 1.0);
+        // Monday 2025-03-03 (start of new week)
+        insert_costed_interaction(store, session_a, 2, "2025-03-03T00:00:00+00:00", 2.0);
+        insert_costed_interaction(store, session_b, 1, "2025-03-03T12:30:00+00:00", 4.0);
+        // Sunday 2025-03-09 (same week as Monday 03-03)
+        insert_costed_interaction(store, session_a, 3, "2025-03-09T08:00:00+00:00", 8.0);
+        // Outside the queried range
+        insert_costed_interaction(store, session_a, 4, "2025-04-01T00:00:00+00:00", 16.0);
+
+        (session_a, session_b)
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_cost_timeseries_day_buckets() {
+        let (store, _dir) = create_test_store();
+        let (session_a, _) = seed_cost_timeseries(&store);
+        let range = (utc("2025-03-01T00:00:00Z"), utc("2025-03-31T00:00:00Z"));
+
+        let buckets = store.cost_timeseries(Granularity::Day, range, None).unwrap();
+        let summary: Vec<_> = buckets
+            .iter()
+            .map(|b| (b.bucket_start, b.interaction_count, b.total_cost_usd))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (utc("2025-03-02T00:00:00Z"), 1, 1.0),
+                (utc("2025-03-03T00:00:00Z"), 2, 6.0),
+                (utc("2025-03-09T00:00:00Z"), 1, 8.0),
+            ]
+        );
+        assert_eq!(buckets[1].input_tokens, 200);
+        assert_eq!(buckets[1].output_tokens, 20);
+
+        // Scoped to one session
+        let buckets = store.cost_timeseries(Granularity::Day, range, Some(session_a)).unwrap();
+        let totals: Vec<_> = buckets.iter().map(|b| b.total_cost_usd).collect();
+        assert_eq!(totals, vec![1.0, 2.0, 8.0]);
+    }
+
+    #[test]
+    fn test_tool_histogram_hour_buckets() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        create_test_session(&store, other_session);
+
+        let seed = |session_id: Uuid, tools: &[(&str, &str)]| {
+            let interaction = Interaction::new(session_id, 1, "Work".to_string());
+            store.insert_interaction(&interaction).unwrap();
+            for (seq, (tool_name, at)) in tools.iter().enumerate() {
+                let mut invocation = ToolInvocation::new(
+                    interaction.id,
+                    seq as u32 + 1,
+                    tool_name.to_string(),
+                    serde_json::json!({}),
+                    None,
+                );
+                invocation.started_at = utc(at);
+                store.insert_tool_invocation(&invocation).unwrap();
+            }
+        };
+        seed(
+            session_id,
+            &[
+                ("Read", "2025-03-02T10:05:00Z"),
+                ("Bash", "2025-03-02T10:20:00Z"),
+                ("Read", "2025-03-02T10:59:59Z"),
+                ("Edit", "2025-03-02T11:00:00Z"),
+                ("Read", "2025-03-02T11:30:00Z"),
+            ],
+        );
+        seed(other_session, &[("Bash", "2025-03-02T11:10:00Z")]);
+        let range = (utc("2025-03-02T00:00:00Z"), utc("2025-03-03T00:00:00Z"));
+
+        let histogram = store.tool_histogram(Granularity::Hour, range, None).unwrap();
+        let summary: Vec<_> = histogram
+            .iter()
+            .map(|b| (b.bucket_start, b.tool_name.as_str(), b.count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (utc("2025-03-02T10:00:00Z"), "Read", 2),
+                (utc("2025-03-02T10:00:00Z"), "Bash", 1),
+                (utc("2025-03-02T11:00:00Z"), "Bash", 1),
+                (utc("2025-03-02T11:00:00Z"), "Edit", 1),
+                (utc("2025-03-02T11:00:00Z"), "Read", 1),
+            ]
+        );
+
+        // Scoped to one session
+        let histogram = store.tool_histogram(Granularity::Hour, range, Some(session_id)).unwrap();
+        assert_eq!(histogram.len(), 4);
+        assert!(!histogram.iter().any(|b| b.bucket_start == utc("2025-03-02T11:00:00Z") && b.tool_name == "Bash"));
+    }
+
+    #[test]
+    fn test_cost_timeseries_week_buckets() {
+        let (store, _dir) = create_test_store();
+        seed_cost_timeseries(&store);
+        let range = (utc("2025-03-01T00:00:00Z"), utc("2025-03-31T00:00:00Z"));
+
+        let buckets = store.cost_timeseries(Granularity::Week, range, None).unwrap();
+        let summary: Vec<_> = buckets
+            .iter()
+            .map(|b| (b.bucket_start, b.interaction_count, b.total_cost_usd))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (utc("2025-02-24T00:00:00Z"), 1, 1.0),
+                (utc("2025-03-03T00:00:00Z"), 3, 14.0),
+            ]
+        );
+
+        // The range end is exclusive
+        let range = (utc("2025-03-01T00:00:00Z"), utc("2025-03-03T00:00:00Z"));
+        let buckets = store.cost_timeseries(Granularity::Week, range, None).unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].total_cost_usd, 1.0);
+    }
+
+    #[test]
+    fn test_interaction_patch() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Refactor".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let snapshot = |path: &str, content: &[u8], snapshot_type| {
+            let (hash, _) = store.store_file_content(content).unwrap();
+            let snap = FileSnapshot::new(
+                interaction.id,
+                None,
+                PathBuf::from(path),
+                hash,
+                snapshot_type,
+                content.len() as u64,
+            );
+            store.insert_file_snapshot(&snap).unwrap();
+        };
+
+        // Modified file
+        snapshot("/test/path/src/lib.rs", b"fn a() {}\nfn b() {}\n", SnapshotType::Before);
+        snapshot("/test/path/src/lib.rs", b"fn a() {}\nfn c() {}\n", SnapshotType::After);
+        // Newly created file
+        snapshot("/test/path/README.md", b"# Title\n", SnapshotType::After);
+
+        let patch = store.interaction_patch(interaction.id, 3).unwrap();
+
+        assert!(patch.contains(
+            "diff --git a/README.md b/README.md\nnew file mode 100644\n--- /dev/null\n+++ b/README.md\n"
+        ));
+        assert!(patch.contains("+# Title"));
+        assert!(patch.contains("diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ "));
+        assert!(patch.contains("-fn b() {}\n+fn c() {}\n"));
+        // Sections are ordered by path and every section starts with a git header
+        assert!(patch.find("a/README.md").unwrap() < patch.find("a/src/lib.rs").unwrap());
+        assert!(patch.starts_with("diff --git "));
+        assert_eq!(patch.matches("diff --git ").count(), 2);
+    }
+
+    #[test]
+    fn test_interaction_patch_applies_to_before_content() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Rename b to c".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let snapshot = |path: &str, content: &[u8], snapshot_type| {
+            let (hash, _) = store.store_file_content(content).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    PathBuf::from(path),
+                    hash.clone(),
+                    snapshot_type,
+                    content.len() as u64,
+                ))
+                .unwrap();
+            hash
+        };
+        let before = "fn a() {}\nfn b() {}\n".repeat(6);
+        let after = before.replacen("fn b() {}", "fn c() {}", 2);
+        snapshot("/test/path/src/lib.rs", before.as_bytes(), SnapshotType::Before);
+        snapshot("/test/path/src/lib.rs", after.as_bytes(), SnapshotType::After);
+        snapshot("/test/path/src/new.rs", b"pub mod new;\n", SnapshotType::After);
+        // A file whose content is corrupt is skipped, not silently dropped
+        snapshot("/test/path/src/bad.rs", b"old\n", SnapshotType::Before);
+        let corrupt_hash = snapshot("/test/path/src/bad.rs", b"new\n", SnapshotType::After);
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE file_contents SET compressed_content = ?1 WHERE content_hash = ?2",
+                params![b"not zstd at all".to_vec(), corrupt_hash],
+            )
+            .unwrap();
+
+        let patch = store.interaction_patch(interaction.id, 3).unwrap();
+        assert!(patch.contains(&format!("# skipped src/bad.rs: Corrupt snapshot content: {corrupt_hash}\n")), "{patch}");
+
+        let project = TempDir::new().unwrap();
+        std::fs::create_dir_all(project.path().join("src")).unwrap();
+        std::fs::write(project.path().join("src/lib.rs"), &before).unwrap();
+        std::fs::write(project.path().join("src/bad.rs"), "old\n").unwrap();
+        let patch_path = project.path().join("change.patch");
+        std::fs::write(&patch_path, &patch).unwrap();
+        let status = std::process::Command::new("git")
+            .arg("apply")
+            .arg(&patch_path)
+            .current_dir(project.path())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git apply rejected:\n{patch}");
+
+        assert_eq!(std::fs::read_to_string(project.path().join("src/lib.rs")).unwrap(), after);
+        assert_eq!(std::fs::read_to_string(project.path().join("src/new.rs")).unwrap(), "pub mod new;\n");
+        assert_eq!(std::fs::read_to_string(project.path().join("src/bad.rs")).unwrap(), "old\n");
+    }
+
+    #[test]
+    fn test_integrity_check_and_repair_orphaned_tool_invocation() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Read the file".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        store
+            .insert_tool_invocation(&ToolInvocation::new(
+                interaction.id,
+                1,
+                "Read".to_string(),
+                serde_json::json!({"file_path": "/src/lib.rs"}),
+                Some("toolu_kept".to_string()),
+            ))
+            .unwrap();
+        assert!(store.check_integrity().unwrap().is_clean());
+
+        // Simulate an import made without foreign key enforcement
+        store.conn.lock().unwrap().execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+        let orphan = ToolInvocation::new(
+            Uuid::new_v4(),
+            1,
+            "Bash".to_string(),
+            serde_json::json!({"command": "ls"}),
+            Some("toolu_orphan".to_string()),
+        );
+        store.insert_tool_invocation(&orphan).unwrap();
+
+        let report = store.check_integrity().unwrap();
+        assert!(!report.foreign_keys_enabled);
+        assert_eq!(report.orphaned_tool_invocations, 1);
+        assert_eq!(report.orphaned_interactions, 0);
+        assert!(!report.is_clean());
+
+        assert_eq!(store.repair_integrity().unwrap(), report);
+        assert!(store.get_tool_invocation(orphan.id).unwrap().is_none());
+        assert!(store.get_tool_invocation_by_tool_use_id("toolu_kept").unwrap().is_some());
+
+        let after = store.check_integrity().unwrap();
+        assert!(after.is_clean());
+        assert!(after.foreign_keys_enabled);
+    }
+
+    #[test]
+    fn test_truncate_session_history_keeps_newest() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        for seq in 1..=10u32 {
+            let interaction = Interaction::new(session_id, seq, format!("Prompt {seq}"));
+            store.insert_interaction(&interaction).unwrap();
+            store
+                .insert_tool_invocation(&ToolInvocation::new(
+                    interaction.id,
+                    1,
+                    "Read".to_string(),
+                    serde_json::json!({"file_path": format!("/src/{seq}.rs")}),
+                    Some(format!("toolu_{seq}")),
+                ))
+                .unwrap();
+            let content = format!("fn v{seq}() {{}}\n");
+            let (hash, _) = store.store_file_content(content.as_bytes()).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    PathBuf::from(format!("/src/{seq}.rs")),
+                    hash,
+                    SnapshotType::After,
+                    content.len() as u64,
+                ))
+                .unwrap();
+        }
+
+        let stats = store.truncate_session_history(session_id, 3).unwrap();
+        assert_eq!(stats.interactions_deleted, 7);
+        assert_eq!(stats.contents_deleted, 7);
+
+        let remaining = store.list_interactions(session_id, 100, 0).unwrap();
+        let sequences: Vec<_> = remaining.iter().map(|i| i.sequence_number).collect();
+        assert_eq!(sequences, [10, 9, 8]);
+        for interaction in &remaining {
+            let tools = store.list_tool_invocations(interaction.id).unwrap();
+            assert_eq!(tools.len(), 1);
+            assert_eq!(
+                tools[0].tool_use_id.as_deref(),
+                Some(format!("toolu_{}", interaction.sequence_number).as_str())
+            );
+        }
+        assert_eq!(store.get_storage_stats().unwrap().tool_count, 3);
+        assert_eq!(store.get_storage_stats().unwrap().content_count, 3);
+    }
+
+    #[test]
+    fn test_escape_fts5_query_limits() {
+        let limits = FtsQueryLimits::default();
+
+        // A normal query is unchanged
+        assert_eq!(
+            InteractionStore::escape_fts5_query("describe package.json", limits).as_deref(),
+            Some("\"describe\"* AND \"package.json\"*")
+        );
+
+        // An over-long query keeps only the first terms
+        let long_query = vec!["term"; 5000].join(" ");
+        let escaped = InteractionStore::escape_fts5_query(&long_query, limits).unwrap();
+        assert_eq!(escaped.matches("\"term\"*").count(), limits.max_tokens);
+
+        let tight = FtsQueryLimits { max_tokens: 10, max_chars: 6 };
+        assert_eq!(
+            InteractionStore::escape_fts5_query("alpha beta gamma", tight).as_deref(),
+            Some("\"alpha\"* AND \"b\"*")
+        );
+        let huge_token = "x".repeat(10_000);
+        let escaped = InteractionStore::escape_fts5_query(&huge_token, limits).unwrap();
+        assert_eq!(escaped.len(), limits.max_chars + 3);
+
+        // Nothing searchable left
+        assert_eq!(InteractionStore::escape_fts5_query("!!! ?? \"\"", limits), None);
+        assert_eq!(InteractionStore::escape_fts5_query("   ", limits), None);
+    }
+
+    #[test]
+    fn test_oversized_tool_input_stored_truncated() {
+        let (store, _temp_dir) = create_test_store();
+        let store = store.with_tool_input_limits(ToolInputLimits { max_bytes: 1024, store_full: true });
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Write the file".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        let content = format!("zephyrhead {} quasartail", "filler text ".repeat(2000));
+        let input = serde_json::json!({ "file_path": "/src/big.rs", "content": content });
+        let invocation = ToolInvocation::new(interaction.id, 1, "Write".to_string(), input.clone(), None);
+        store.insert_tool_invocation(&invocation).unwrap();
+
+        // Stored within the cap, keeping the short fields whole
+        let stored = store.get_tool_invocation(invocation.id).unwrap().unwrap();
+        assert!(stored.tool_input.to_string().len() <= 1024);
+        assert_eq!(stored.tool_input["file_path"], "/src/big.rs");
+        let stored_content = stored.tool_input["content"].as_str().unwrap();
+        assert!(stored_content.starts_with("zephyrhead filler"));
+        assert!(stored_content.ends_with("bytes]"));
+
+        // The index only covers the stored prefix
+        assert_eq!(store.search_tool_invocations("zephyrhead", None, 10, 0).unwrap().len(), 1);
+        assert!(store.search_tool_invocations("quasartail", None, 10, 0).unwrap().is_empty());
+
+        // The full input is kept as referenced content
+        assert_eq!(store.full_tool_input(invocation.id).unwrap(), Some(input.clone()));
+        let hash = format!("{:x}", Sha256::digest(input.to_string().as_bytes()));
+        assert_eq!(store.content_reference_count(&hash).unwrap(), Some(1));
+
+        // Without full copies, only the truncated input remains
+        let store = store.with_tool_input_limits(ToolInputLimits { max_bytes: 1024, store_full: false });
+        let invocation = ToolInvocation::new(interaction.id, 2, "Write".to_string(), input, None);
+        store.insert_tool_invocation(&invocation).unwrap();
+        let full = store.full_tool_input(invocation.id).unwrap().unwrap();
+        assert!(full.to_string().len() <= 1024);
+        assert_eq!(store.full_tool_input(Uuid::new_v4()).unwrap(), None);
+
+        // Small inputs are stored unchanged
+        assert_eq!(truncate_tool_input(&serde_json::json!({ "command": "ls" }), 1024), r#"{"command":"ls"}"#);
+        // With no strings to cut, a marked prefix is kept instead
+        let numbers = serde_json::json!(vec![12345; 1000]);
+        let fallback: serde_json::Value = serde_json::from_str(&truncate_tool_input(&numbers, 100)).unwrap();
+        assert!(fallback["truncated"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_search_with_unsearchable_query_returns_nothing() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let interaction = Interaction::new(session_id, 1, "Fix the login bug".to_string());
+        store.insert_interaction(&interaction).unwrap();
+
+        for query in ["", "!!! ??? ...", "(-)"] {
+            assert!(store.search_interactions(query, None, 10, 0).unwrap().is_empty());
+            assert!(store.search_tool_invocations(query, None, 10, 0).unwrap().is_empty());
+            assert!(store
+                .search_interactions_with_file_filter(query, "*", 10, 0)
+                .unwrap()
+                .is_empty());
+        }
+
+        let long_query = format!("login {}", vec!["bug"; 5000].join(" "));
+        let results = store.search_interactions(&long_query, None, 10, 0).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].interaction.id, interaction.id);
+    }
+
+    #[test]
+    fn test_diff_tool_sequences_flags_inserted_step() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let attempt = |seq: u32, tools: &[(&str, serde_json::Value)]| {
+            let interaction = Interaction::new(session_id, seq, "Fix the failing test".to_string());
+            store.insert_interaction(&interaction).unwrap();
+            for (i, (name, input)) in tools.iter().enumerate() {
+                let tool = ToolInvocation::new(interaction.id, i as u32 + 1, name.to_string(), input.clone(), None);
+                store.insert_tool_invocation(&tool).unwrap();
+            }
+            interaction.id
+        };
+        let read = ("Read", serde_json::json!({"file_path": "/app/src/lib.rs"}));
+        let edit = ("Edit", serde_json::json!({"file_path": "/app/src/lib.rs"}));
+        let test = ("Bash", serde_json::json!({"command": "cargo test"}));
+        let a = attempt(1, &[read.clone(), edit.clone()]);
+        let b = attempt(2, &[read, test, edit]);
+
+        let diff = store.diff_tool_sequences(a, b).unwrap();
+        let steps: Vec<_> = diff
+            .iter()
+            .map(|d| (d.change_type, d.tool_name.as_str(), d.sequence_a, d.sequence_b))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                (DiffChangeType::Context, "Read", Some(1), Some(1)),
+                (DiffChangeType::Add, "Bash", None, Some(2)),
+                (DiffChangeType::Context, "Edit", Some(2), Some(3)),
+            ]
+        );
+        assert_eq!(diff[1].key_input.as_deref(), Some("cargo test"));
+
+        assert!(matches!(
+            store.diff_tool_sequences(a, Uuid::new_v4()),
+            Err(ClausetError::InteractionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_interactions_with_file_filter() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let seed = |seq: u32, prompt: &str, file: &str| {
+            let interaction = Interaction::new(session_id, seq, prompt.to_string());
+            store.insert_interaction(&interaction).unwrap();
+            let (hash, _) = store.store_file_content(file.as_bytes()).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    PathBuf::from(file),
+                    hash,
+                    SnapshotType::After,
+                    file.len() as u64,
+                ))
+                .unwrap();
+            interaction.id
+        };
+        let wanted = seed(1, "Write the users migration", "/app/db/migrations/001_users.sql");
+        seed(2, "Document the migration process", "/app/docs/migrations.md");
+        seed(3, "Tune the report query", "/app/queries/report.sql");
+
+        let results = store
+            .search_interactions_with_file_filter("migration", "*.sql", 10, 0)
+            .unwrap();
+        let ids: Vec<_> = results.iter().map(|r| r.interaction.id).collect();
+        assert_eq!(ids, [wanted]);
+
+        assert_eq!(
+            store
+                .search_interactions_with_file_filter("migration", "*", 10, 0)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(store
+            .search_interactions_with_file_filter("migration", "*.sql", 10, 1)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_global_search_per_category_limits() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let prompt_match = Interaction::new(session_id, 1, "Fix the tokenizer crash".to_string());
+        store.insert_interaction(&prompt_match).unwrap();
+        for seq in 2..32 {
+            let interaction = Interaction::new(session_id, seq, "Update the module".to_string());
+            store.insert_interaction(&interaction).unwrap();
+            let path = format!("/src/tokenizer_{}.rs", seq);
+            let (hash, _) = store.store_file_content(path.as_bytes()).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    PathBuf::from(&path),
+                    hash,
+                    SnapshotType::After,
+                    path.len() as u64,
+                ))
+                .unwrap();
+        }
+
+        let options = GlobalSearchOptions { interaction_limit: 5, tool_limit: 5, file_limit: 10 };
+        let results = store.global_search("tokenizer", &options).unwrap();
+        assert_eq!(results.interactions.len(), 1);
+        assert_eq!(results.interactions[0].interaction.id, prompt_match.id);
+        assert_eq!(results.file_matches.len(), 10);
+
+        // The prompt match leads the merged ranking despite the file flood
+        assert_eq!(results.ranking.len(), 11);
+        assert_eq!(results.ranking[0].kind, GlobalSearchKind::Interaction);
+        assert!(matches!(
+            &results.merged()[0],
+            GlobalSearchItem::Interaction(result) if result.interaction.id == prompt_match.id
+        ));
+        assert!(results.ranking.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn test_rebuild_all_fts_restores_search() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let interaction = Interaction::new(session_id, 1, "Refactor the tokenizer".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        store
+            .insert_tool_invocation(&ToolInvocation::new(
+                interaction.id,
+                1,
+                "Read".to_string(),
+                serde_json::json!({"file_path": "/src/tokenizer.rs"}),
+                None,
+            ))
+            .unwrap();
+        assert_eq!(store.search_interactions("tokenizer", None, 10, 0).unwrap().len(), 1);
+
+        // Drift: the indexes lose their rows while the base tables keep theirs
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                r#"
+                INSERT INTO interactions_fts(interactions_fts) VALUES('delete-all');
+                INSERT INTO tool_invocations_fts(tool_invocations_fts) VALUES('delete-all');
+                "#,
+            )
+            .unwrap();
+        assert!(store.search_interactions("tokenizer", None, 10, 0).unwrap().is_empty());
+        assert!(store.search_tool_invocations("tokenizer", None, 10, 0).unwrap().is_empty());
+
+        store.rebuild_all_fts().unwrap();
+        let results = store.search_interactions("tokenizer", None, 10, 0).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].interaction.id, interaction.id);
+        assert_eq!(store.search_tool_invocations("tokenizer", None, 10, 0).unwrap().len(), 1);
+
+        // Rebuilding a healthy index doesn't duplicate entries
+        store.rebuild_all_fts().unwrap();
+        assert_eq!(store.search_interactions("tokenizer", None, 10, 0).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_storage_by_session_splits_shared_content() {
+        let (store, _dir) = create_test_store();
+        let content = |lines: usize| -> Vec<u8> {
+            (0..lines).map(|i| format!("line {i:04}\n")).collect::<String>().into_bytes()
+        };
+        let big = content(400);
+        let small = content(100);
+        let shared = content(10);
+
+        let snapshot = |session_id: Uuid, seq: u32, contents: &[&[u8]]| {
+            let interaction = Interaction::new(session_id, seq, "Edit files".to_string());
+            store.insert_interaction(&interaction).unwrap();
+            for (i, data) in contents.iter().enumerate() {
+                let (hash, _) = store.store_file_content(data).unwrap();
+                store
+                    .insert_file_snapshot(&FileSnapshot::new(
+                        interaction.id,
+                        None,
+                        PathBuf::from(format!("/src/{i}.rs")),
+                        hash,
+                        SnapshotType::After,
+                        data.len() as u64,
+                    ))
+                    .unwrap();
+            }
+        };
+        let (large, medium, tiny, empty) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        for session_id in [large, medium, tiny, empty] {
+            create_test_session(&store, session_id);
+        }
+        snapshot(large, 1, &[&big, &shared]);
+        snapshot(large, 2, &[]);
+        snapshot(medium, 1, &[&small]);
+        snapshot(tiny, 1, &[&shared]);
+        let idle = Interaction::new(empty, 1, "Just ask".to_string());
+        store.insert_interaction(&idle).unwrap();
+        store
+            .insert_tool_invocation(&ToolInvocation::new(idle.id, 1, "Grep".to_string(), serde_json::json!({}), None))
+            .unwrap();
+
+        let entries = store.storage_by_session(10).unwrap();
+        let order: Vec<_> = entries.iter().map(|e| e.session_id).collect();
+        assert_eq!(order, [large, medium, tiny, empty]);
+
+        // The shared content is split between its two snapshots
+        let half_shared = shared.len() as u64 / 2;
+        assert_eq!(entries[0].content_size, big.len() as u64 + half_shared);
+        assert_eq!(entries[0].interaction_count, 2);
+        assert_eq!(entries[0].snapshot_count, 2);
+        assert_eq!(entries[1].content_size, small.len() as u64);
+        assert_eq!(entries[2].content_size, half_shared);
+        assert_eq!(entries[3].content_size, 0);
+        assert_eq!(entries[3].compressed_size, 0);
+        assert_eq!(entries[3].tool_count, 1);
+
+        let stats = store.get_storage_stats().unwrap();
+        let content_total: u64 = entries.iter().map(|e| e.content_size).sum();
+        assert_eq!(content_total, stats.total_content_size);
+        let compressed_total: u64 = entries.iter().map(|e| e.compressed_size).sum();
+        assert!(compressed_total.abs_diff(stats.total_compressed_size) <= 1);
+
+        assert_eq!(store.storage_by_session(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_reclaimable_bytes_excludes_shared_content() {
+        let (store, _dir) = create_test_store();
+        let (owner, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let unique: Vec<u8> = (0..200).map(|i| format!("unique {i:04}\n")).collect::<String>().into_bytes();
+        let shared: Vec<u8> = (0..50).map(|i| format!("shared {i:04}\n")).collect::<String>().into_bytes();
+
+        let snapshot = |session_id: Uuid, data: &[u8]| -> String {
+            let interaction = Interaction::new(session_id, 1, "Edit".to_string());
+            store.insert_interaction(&interaction).unwrap();
+            let (hash, _) = store.store_file_content(data).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    PathBuf::from("/src/lib.rs"),
+                    hash.clone(),
+                    SnapshotType::After,
+                    data.len() as u64,
+                ))
+                .unwrap();
+            hash
+        };
+        create_test_session(&store, owner);
+        create_test_session(&store, other);
+        let unique_hash = snapshot(owner, &unique);
+        // Snapshotted twice by the owner, once by the other session
+        let interaction = Interaction::new(owner, 2, "Edit again".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        for _ in 0..2 {
+            let (hash, _) = store.store_file_content(&shared).unwrap();
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    PathBuf::from("/src/shared.rs"),
+                    hash,
+                    SnapshotType::After,
+                    shared.len() as u64,
+                ))
+                .unwrap();
+        }
+        snapshot(other, &shared);
+
+        let unique_compressed: i64 = store
+            .reader()
+            .query_row(
+                "SELECT LENGTH(compressed_content) FROM file_contents WHERE content_hash = ?1",
+                params![unique_hash],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(store.reclaimable_bytes(owner).unwrap(), unique_compressed as u64);
+        assert_eq!(store.reclaimable_bytes(other).unwrap(), 0);
+        assert_eq!(store.reclaimable_bytes(Uuid::new_v4()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_long_read_does_not_block_writes() {
+        use std::sync::mpsc;
+
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        store.insert_interaction(&Interaction::new(session_id, 1, "first".to_string())).unwrap();
+
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let (written_tx, written_rx) = mpsc::channel();
+        let store = &store;
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                // Hold a read transaction on the interactions table open until released
+                let conn = store.reader();
+                conn.execute_batch("BEGIN").unwrap();
+                let count = |conn: &Connection| -> i64 {
+                    conn.query_row("SELECT count(*) FROM interactions", [], |row| row.get(0)).unwrap()
+                };
+                assert_eq!(count(&conn), 1);
+                started_tx.send(()).unwrap();
+                release_rx.recv().unwrap();
+                // Still the snapshot from before the write
+                assert_eq!(count(&conn), 1);
+                conn.execute_batch("COMMIT").unwrap();
+            });
+
+            started_rx.recv().unwrap();
+            scope.spawn(move || {
+                store.insert_interaction(&Interaction::new(session_id, 2, "second".to_string())).unwrap();
+                written_tx.send(()).unwrap();
+            });
+            // Fails rather than hangs if the write waits on the open read
+            let written = written_rx.recv_timeout(Duration::from_secs(10));
+            release_tx.send(()).unwrap();
+            assert!(written.is_ok(), "write did not complete while the read was open");
+        });
+
+        assert_eq!(store.list_interactions(session_id, 10, 0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_extract_bash_script() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let first = Interaction::new(session_id, 1, "build it".to_string());
+        let second = Interaction::new(session_id, 2, "test it".to_string());
+        store.insert_interaction(&first).unwrap();
+        store.insert_interaction(&second).unwrap();
+
+        let bash = |interaction: &Interaction, seq: u32, command: &str| {
+            ToolInvocation::new(interaction.id, seq, "Bash".to_string(), serde_json::json!({"command": command}), None)
+        };
+        store.insert_tool_invocation(&bash(&first, 1, "cargo build")).unwrap();
+        let mut blocked = bash(&first, 2, "rm -rf target");
+        blocked.is_error = true;
+        store.insert_tool_invocation(&blocked).unwrap();
+        store
+            .insert_tool_invocation(&ToolInvocation::new(
+                second.id,
+                1,
+                "Read".to_string(),
+                serde_json::json!({"file_path": "/p/Cargo.toml"}),
+                None,
+            ))
+            .unwrap();
+        let mut failing = bash(&second, 2, "cargo test");
+        failing.is_error = true;
+        failing.exit_code = Some(101);
+        store.insert_tool_invocation(&failing).unwrap();
+
+        let script = store.extract_bash_script(session_id).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        let build = script.find("cargo build").unwrap();
+        let test = script.find("cargo test").unwrap();
+        assert!(build < test);
+        assert!(script.find(&format!("# Interaction 1 ({})", first.id)).unwrap() < build);
+        assert!(script.find(&format!("# Interaction 2 ({})", second.id)).unwrap() < test);
+        assert!(!script.contains("rm -rf"));
+    }
+
+    #[test]
+    fn test_reconcile_costs_from_transcript() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        let first = Interaction::new(session_id, 1, "read the config".to_string());
+        let second = Interaction::new(session_id, 2, "now fix it".to_string());
+        store.insert_interaction(&first).unwrap();
+        store.insert_interaction(&second).unwrap();
+
+        let assistant = |id: &str, input: u64, output: u64| {
+            serde_json::json!({
+                "type": "assistant",
+                "message": {
+                    "id": id,
+                    "model": "claude-sonnet-4-5",
+                    "content": [{"type": "text", "text": "ok"}],
+                    "usage": {"input_tokens": input, "output_tokens": output, "cache_read_input_tokens": 1000}
+                }
+            })
+        };
+        let user = |content: serde_json::Value| {
+            serde_json::json!({"type": "user", "message": {"role": "user", "content": content}})
+        };
+        let transcript = [
+            user(serde_json::json!("read the config")),
+            assistant("msg_1", 100, 50),
+            // Same message again for another content block: counted once
+            assistant("msg_1", 100, 50),
+            user(serde_json::json!([{"type": "tool_result", "tool_use_id": "t1", "content": "..."}])),
+            assistant("msg_2", 200, 100),
+            user(serde_json::json!("now fix it")),
+            assistant("msg_3", 1_000_000, 0),
+        ]
+        .iter()
+        .map(|entry| entry.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+        let path = dir.path().join("transcript.jsonl");
+        std::fs::write(&path, transcript).unwrap();
+
+        assert_eq!(store.reconcile_costs_from_transcript(session_id, &path).unwrap(), 2);
+
+        let first = store.get_interaction(first.id).unwrap().unwrap();
+        assert_eq!(first.status, InteractionStatus::Completed);
+        assert!(first.ended_at.is_some());
+        assert_eq!(first.input_tokens_delta, 300 + 2 * 1000);
+        assert_eq!(first.output_tokens_delta, 150);
+        // Sonnet: $3/M input, $15/M output, $0.30/M cache reads
+        let expected = (300.0 * 3.0 + 150.0 * 15.0 + 2000.0 * 0.3) / 1_000_000.0;
+        assert!((first.cost_usd_delta - expected).abs() < 1e-12);
+
+        let second = store.get_interaction(second.id).unwrap().unwrap();
+        assert_eq!(second.status, InteractionStatus::Completed);
+        assert!((second.cost_usd_delta - (3.0 + 0.0003)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_session_velocity() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let t0 = Utc::now() - chrono::Duration::hours(3);
+        let minutes = |m: i64| t0 + chrono::Duration::minutes(m);
+        let mut ids = Vec::new();
+        for (seq, start, end) in [(1, 0, 10), (2, 30, 40), (3, 90, 120)] {
+            let mut interaction = Interaction::new(session_id, seq, format!("prompt {seq}"));
+            interaction.started_at = minutes(start);
+            interaction.ended_at = Some(minutes(end));
+            store.insert_interaction(&interaction).unwrap();
+            ids.push(interaction.id);
+        }
+        let (hash, _) = store.store_file_content(b"x\n").unwrap();
+        // Before and after of one file count once
+        for (interaction_id, name, snapshot_type) in [
+            (ids[0], "a.rs", SnapshotType::Before),
+            (ids[0], "a.rs", SnapshotType::After),
+            (ids[2], "b.rs", SnapshotType::After),
+            (ids[2], "c.rs", SnapshotType::After),
+        ] {
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction_id,
+                    None,
+                    dir.path().join(name),
+                    hash.clone(),
+                    snapshot_type,
+                    2,
+                ))
+                .unwrap();
+        }
+
+        let velocity = store.session_velocity(session_id).unwrap();
+        assert_eq!(velocity.interaction_count, 3);
+        assert_eq!(velocity.files_changed, 3);
+        assert_eq!(velocity.active_span_secs, 7200.0);
+        assert_eq!(velocity.interactions_per_hour, Some(1.5));
+        assert_eq!(velocity.files_changed_per_hour, Some(1.5));
+        assert_eq!(velocity.avg_turn_duration_secs, Some(1000.0));
+
+        // A single unfinished interaction has no span to divide by
+        let lone = Uuid::new_v4();
+        create_test_session(&store, lone);
+        store.insert_interaction(&Interaction::new(lone, 1, "just started".to_string())).unwrap();
+        let velocity = store.session_velocity(lone).unwrap();
+        assert_eq!(velocity.interaction_count, 1);
+        assert_eq!(velocity.active_span_secs, 0.0);
+        assert!(velocity.interactions_per_hour.is_none());
+        assert!(velocity.avg_turn_duration_secs.is_none());
+    }
+
+    #[test]
+    fn test_session_transcript_text() {
+        let (store, _temp_dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        assert_eq!(store.session_transcript_text(session_id).unwrap(), "");
+
+        // Inserted out of order to check turns follow the sequence number
+        let mut second = Interaction::new(session_id, 2, "Now add a test".to_string());
+        second.assistant_summary = Some("Added a regression test".to_string());
+        store.insert_interaction(&second).unwrap();
+        let mut first = Interaction::new(session_id, 1, "Fix the login redirect\n".to_string());
+        first.assistant_summary = Some("Fixed the redirect check".to_string());
+        store.insert_interaction(&first).unwrap();
+
+        let read = ToolInvocation::new(
+            first.id,
+            1,
+            "Read".to_string(),
+            serde_json::json!({"file_path": "src/auth.rs"}),
+            Some("src/auth.rs".into()),
+        );
+        store.insert_tool_invocation(&read).unwrap();
+        let todo = ToolInvocation::new(second.id, 1, "TodoWrite".to_string(), serde_json::json!({}), None);
+        store.insert_tool_invocation(&todo).unwrap();
+
+        // Another session's turns are left out
+        let other_session = Uuid::new_v4();
+        create_test_session(&store, other_session);
+        store
+            .insert_interaction(&Interaction::new(other_session, 1, "Unrelated".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            store.session_transcript_text(session_id).unwrap(),
+            "User: Fix the login redirect\n\
+             Tool: Read src/auth.rs\n\
+             Assistant: Fixed the redirect check\n\
+             \n\
+             User: Now add a test\n\
+             Tool: TodoWrite\n\
+             Assistant: Added a regression test\n"
+        );
+    }
+
+    #[test]
+    fn test_list_interactions_by_change() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let edit = Interaction::new(session_id, 1, "Fix the parser".to_string());
+        store.insert_interaction(&edit).unwrap();
+        let question = Interaction::new(session_id, 2, "What does the parser do?".to_string());
+        store.insert_interaction(&question).unwrap();
+        let (hash, _) = store.store_file_content(b"fn parse() {}\n").unwrap();
+        store
+            .insert_file_snapshot(&FileSnapshot::new(
+                edit.id,
+                None,
+                dir.path().join("parser.rs"),
+                hash,
+                SnapshotType::After,
+                14,
+            ))
+            .unwrap();
+
+        let changed = store.list_interactions_by_change(session_id, true, 50, 0).unwrap();
+        assert_eq!(changed.iter().map(|s| s.id).collect::<Vec<_>>(), vec![edit.id]);
+        assert_eq!(changed[0].files_changed, 1);
+
+        let unchanged = store.list_interactions_by_change(session_id, false, 50, 0).unwrap();
+        assert_eq!(unchanged.iter().map(|s| s.id).collect::<Vec<_>>(), vec![question.id]);
+    }
+
+    #[test]
+    fn test_language_breakdown() {
+        let (store, dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+
+        let interaction = Interaction::new(session_id, 1, "edit".to_string());
+        store.insert_interaction(&interaction).unwrap();
+        let (hash, _) = store.store_file_content(b"x\n").unwrap();
+        for (name, snapshot_type) in [
+            ("main.rs", SnapshotType::Before),
+            ("main.rs", SnapshotType::After),
+            ("lib.rs", SnapshotType::After),
+            ("tool.py", SnapshotType::After),
+            ("Makefile", SnapshotType::After),
+        ] {
+            store
+                .insert_file_snapshot(&FileSnapshot::new(
+                    interaction.id,
+                    None,
+                    dir.path().join(name),
+                    hash.clone(),
+                    snapshot_type,
+                    2,
+                ))
+                .unwrap();
+        }
+
+        let breakdown = store.language_breakdown(session_id).unwrap();
+        assert_eq!(breakdown, vec![("Rust".to_string(), 2), ("Python".to_string(), 1)]);
+        assert!(store.language_breakdown(Uuid::new_v4()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extract_file_refs() {
+        assert_eq!(
+            extract_file_refs("@README.md explain, then fix @src/lib.rs. Also @src/lib.rs and mail bob@example.com or @team"),
+            vec!["README.md".to_string(), "src/lib.rs".to_string()]
+        );
+        assert!(extract_file_refs("no references here").is_empty());
+    }
+
+    #[test]
+    fn test_query_readonly() {
+        let (store, _dir) = create_test_store();
+        let session_id = Uuid::new_v4();
+        create_test_session(&store, session_id);
+        for seq in 1..=3 {
+            store.insert_interaction(&Interaction::new(session_id, seq, format!("prompt {seq}"))).unwrap();
+        }
+
+        let result = store
+            .query_readonly(
+                "SELECT sequence_number, user_prompt FROM interactions ORDER BY sequence_number",
+                2,
+                Duration::from_secs(5),
+            )
+            .unwrap();
+        assert_eq!(result.columns, vec!["sequence_number", "user_prompt"]);
+        assert_eq!(result.rows, vec![vec![serde_json::json!(1), serde_json::json!("prompt 1")], vec![
+            serde_json::json!(2),
+            serde_json::json!("prompt 2")
+        ]]);
+        assert!(result.truncated);
+
+        for sql in [
+            "INSERT INTO interactions (id) VALUES ('x')",
+            "UPDATE interactions SET user_prompt = 'changed'",
+            "PRAGMA journal_mode = DELETE",
+            "WITH doomed AS (SELECT id FROM interactions) DELETE FROM interactions WHERE id IN doomed",
+            "SELECT 1; DELETE FROM interactions",
+            "ATTACH DATABASE ':memory:' AS other",
+        ] {
+            match store.query_readonly(sql, 10, Duration::from_secs(5)) {
+                Err(ClausetError::QueryRejected(_)) => {}
+                other => panic!("{sql}: expected rejection, got {other:?}"),
+            }
+        }
+
+        // Nothing was written and the store still works normally
+        let interactions = store.list_interactions(session_id, 10, 0).unwrap();
+        assert_eq!(interactions.len(), 3);
+        assert!(interactions.iter().all(|i| i.user_prompt.starts_with("prompt")));
+        store.insert_interaction(&Interaction::new(session_id, 4, "prompt 4".to_string())).unwrap();
+    }
+
+    #[test]
+    fn test_query_readonly_times_out() {
+        let (store, _dir) = create_test_store();
+        let runaway = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT count(*) FROM n";
+
+        match store.query_readonly(runaway, 10, Duration::from_millis(50)) {
+            Err(ClausetError::QueryTimeout(_)) => {}
+            other => panic!("expected timeout, got {other:?}"),
+        }
+
+        // The reader is released and usable without the deadline
+        let result = store.query_readonly("SELECT 1", 10, Duration::from_secs(5)).unwrap();
+        assert_eq!(result.rows, vec![vec![serde_json::json!(1)]]);
+    }
+
+    #[test]
+    fn test_cleanup_keeps_pinned_session_interactions() {
+        let (store, _temp) = create_test_store();
+        let pinned = Uuid::new_v4();
+        let unpinned = Uuid::new_v4();
+        create_test_session(&store, pinned);
+        create_test_session(&store, unpinned);
+        {
+            let conn = store.conn.lock().unwrap();
+            conn.execute("ALTER TABLE sessions ADD COLUMN is_pinned INTEGER NOT NULL DEFAULT 0", []).unwrap();
+            conn.execute("UPDATE sessions SET is_pinned = 1 WHERE id = ?1", params![pinned.to_string()]).unwrap();
+        }
+
+        for session_id in [pinned, unpinned] {
+            let mut interaction = Interaction::new(session_id, 1, "stale prompt".to_string());
+            interaction.started_at = Utc::now() - chrono::Duration::days(60);
+            store.insert_interaction(&interaction).unwrap();
+        }
+
+        let stats = store.cleanup_old_data(30).unwrap();
+        assert_eq!(stats.interactions_deleted, 1);
+        assert_eq!(store.list_interactions(pinned, 10, 0).unwrap().len(), 1);
+        assert!(store.list_interactions(unpinned, 10, 0).unwrap().is_empty());
     }
 }
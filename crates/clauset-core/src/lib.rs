@@ -1,5 +1,6 @@
 //! Core session and process management for Clauset.
 
+mod ansi;
 mod buffer;
 mod chat_processor;
 mod claude_sessions;
@@ -7,39 +8,55 @@ mod command_discovery;
 mod db;
 mod diff;
 mod error;
+mod glob;
 mod history;
 mod interaction_store;
 mod parser;
+mod permission_prompt_parser;
+mod pricing;
 mod process;
 mod prompt_indexer;
+mod quiet_hours;
 mod session;
 mod sizing;
 mod transcript_watcher;
 mod tui_menu_parser;
 
-pub use buffer::{AppendResult, RecentAction, SequencedChunk, SessionActivity, SessionBuffers};
-pub use chat_processor::ChatProcessor;
+pub use ansi::{ansi_to_html, render_ansi, strip_ansi, AnsiMode};
+pub use buffer::{compact_buffer, dry_parse, ActionSource, ActivityDelta, ActivityState, ActivityKeywords, AppendResult, BufferIntegrity, BufferMetrics, ChangeThreshold, BufferSlice, ByteRange, Compaction, EvictionPolicy, McpStatus, OversizedChunkPolicy, ParseDiagnostics, ParsedStatus, ReadyDetection, DEFAULT_HOOK_ACTION_WINDOW, RecentAction, SequencedChunk, SessionActivity, SessionBuffers, STALLED_AFTER};
+pub use chat_processor::{ChatProcessor, OutputPreview};
 pub use command_discovery::CommandDiscovery;
-pub use claude_sessions::{ClaudeSession, ClaudeSessionReader, TranscriptMessage};
-pub use db::{SessionStore, TerminalBufferData};
-pub use diff::{compute_diff, generate_unified_diff, DiffChangeType, DiffHunk, DiffLine, FileDiff};
+pub use claude_sessions::{read_latest_todos, ClaudeSession, ClaudeSessionReader, TranscriptMessage};
+pub use db::{PermissionModeChange, RegisteredProject, SessionNameChange, SessionStore, TerminalBufferData, DEFAULT_BUFFER_COMPRESSION_LEVEL};
+pub use diff::{
+    compute_diff, compute_diff_with_options, detect_language, diff_sequences, generate_unified_diff, DiffChangeType,
+    DiffHunk, DiffLine, DiffOptions, FileDiff, SequenceStep,
+};
 pub use error::ClausetError;
-pub use history::HistoryWatcher;
+pub use glob::{segments_match, wildcard_match};
+pub use history::{
+    parse_history_line, HistoryCommand, HistoryEntry, HistoryWatcher,
+    SourcedHistoryEntry,
+};
 pub use interaction_store::{
-    AnalyticsSummary, CleanupStats, DailyCostEntry, FileChangeWithDiff, FilePathMatch,
-    GlobalSearchResults, InteractionStore, SearchField, SearchResult, SessionAnalytics,
-    StorageStats, ToolCostEntry, DEFAULT_RETENTION_DAYS, MAX_SNAPSHOT_SIZE,
+    AnalyticsDelta, AnalyticsSummary, CleanupStats, ContentRef, CostBucket, CustomHookEvent, DailyCostEntry, FileChangeWithDiff, FilePathMatch, FtsQueryLimits,
+    GlobalFileEntry, GlobalSearchItem, GlobalSearchKind, GlobalSearchOptions, GlobalSearchResults, Granularity, HookUsage, IntegrityReport, InteractionStore, LineAuthor, LineAuthorship, MetricDelta, QueryResult, RankedMatch, RecordedHookEvent,
+    SearchField, SearchResult, SessionAnalytics, SessionStorageEntry, SessionVelocity, StorageStats, ToolBucket, ToolCostEntry, ToolInputLimits, ToolReliability, ToolSeqDiff, DEFAULT_RETENTION_DAYS, MAX_SNAPSHOT_SIZE,
+    extract_file_refs,
 };
 pub use parser::OutputParser;
-pub use process::{ProcessEvent, ProcessManager, SpawnOptions};
+pub use permission_prompt_parser::{parse_permission_prompt, PermissionPromptParser};
+pub use pricing::{ModelRates, PricingTable};
+pub use process::{ProcessEvent, ProcessManager, PtyReadOptions, SpawnOptions};
 pub use prompt_indexer::{BackfillStats, PromptIndexer};
+pub use quiet_hours::{QuietHours, QuietWindow};
 pub use session::{CreateSessionOptions, SessionManager, SessionManagerConfig};
 pub use sizing::{
-    validate_dimensions, ConfidenceLevel, DeviceHint, DimensionError, DimensionSource,
-    ValidatedDimensions,
+    reconcile_dimensions, unwrap_lines, validate_dimensions, ConfidenceLevel, DeviceHint, DimensionError,
+    DimensionSource, ValidatedDimensions,
 };
 pub use transcript_watcher::{
-    compute_session_usage, get_transcript_path, transcript_event_to_chat_event, SessionUsage,
+    compute_session_usage, compute_turn_usage, get_transcript_path, transcript_event_to_chat_event, SessionUsage,
     TranscriptEvent, TranscriptUsage, TranscriptWatcher, TranscriptWatcherHandle,
 };
 pub use tui_menu_parser::TuiMenuParser;
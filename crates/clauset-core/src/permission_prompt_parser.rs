@@ -0,0 +1,279 @@
+//! Permission prompt detection for terminal output.
+//!
+//! Claude Code asks for confirmation before running tools ("Do you want to make
+//! this edit to main.rs?"). These prompts are answered with a number or y/n
+//! rather than arrow-key navigation, so they are detected separately from
+//! generic TUI menus and surfaced as a structured [`PermissionPrompt`].
+
+use crate::tui_menu_parser::{normalize_unicode_escapes, strip_ansi_codes};
+use clauset_types::{PermissionPrompt, PermissionPromptOption};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tracing::debug;
+
+/// Maximum bytes of recent output kept while waiting for a complete prompt
+const MAX_PENDING_BYTES: usize = 4096;
+
+/// Number of lines above the question searched for a tool header
+const TOOL_HEADER_LOOKBACK: usize = 30;
+
+/// Box headers Claude Code draws above a permission prompt, and their tool.
+const TOOL_HEADERS: &[(&str, &str)] = &[
+    ("Bash command", "Bash"),
+    ("Edit file", "Edit"),
+    ("Create file", "Write"),
+    ("Write file", "Write"),
+    ("Read file", "Read"),
+    ("Fetch", "WebFetch"),
+    ("Web search", "WebSearch"),
+];
+
+/// Regex for a numbered-answer question ("Do you want to proceed?")
+static QUESTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(Do you want to .+\?|Allow .+\?)$").expect("Invalid question regex")
+});
+
+/// Regex for a y/n question ("Allow this edit? (y/n)")
+static YES_NO_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(.+?)\s*[(\[]y/n[)\]]\s*:?$").expect("Invalid y/n regex")
+});
+
+/// Regex for numbered answers ("❯ 1. Yes", "2. No, and tell Claude...")
+static ANSWER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[❯›>▸]?\s*(\d+)\.\s+(.+)$").expect("Invalid answer regex")
+});
+
+/// Tracks the permission prompt currently awaiting an answer.
+#[derive(Default)]
+pub struct PermissionPromptParser {
+    /// Clean output seen since the last prompt was detected
+    pending: String,
+    /// Prompt awaiting an answer
+    active: Option<PermissionPrompt>,
+}
+
+impl PermissionPromptParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a terminal output chunk. Returns the prompt if one was just detected.
+    ///
+    /// While a prompt is awaiting an answer, redraws of it are ignored.
+    pub fn process(&mut self, data: &[u8]) -> Option<PermissionPrompt> {
+        if self.active.is_some() {
+            return None;
+        }
+
+        let raw_text = String::from_utf8_lossy(data);
+        self.pending
+            .push_str(&normalize_unicode_escapes(&strip_ansi_codes(&raw_text)));
+        if self.pending.len() > MAX_PENDING_BYTES {
+            let mut cut = self.pending.len() - MAX_PENDING_BYTES;
+            while !self.pending.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.pending.drain(..cut);
+        }
+
+        let prompt = parse_permission_prompt(&self.pending)?;
+        debug!(target: "clauset::tui_parser", "Detected permission prompt: {}", prompt.question);
+        self.pending.clear();
+        self.active = Some(prompt.clone());
+        Some(prompt)
+    }
+
+    /// Note terminal input. Returns the prompt if the input answered it.
+    ///
+    /// An answer key, Enter, Escape or Ctrl+C closes the prompt.
+    pub fn answer(&mut self, input: &[u8]) -> Option<PermissionPrompt> {
+        let prompt = self.active.as_ref()?;
+        let text = String::from_utf8_lossy(input);
+        let answered = input == b"\x1b"
+            || input.iter().any(|&b| matches!(b, b'\r' | b'\n' | 0x03))
+            || prompt
+                .options
+                .iter()
+                .any(|opt| opt.key.eq_ignore_ascii_case(text.trim()));
+        if answered { self.active.take() } else { None }
+    }
+
+    /// Get the prompt awaiting an answer, if any.
+    pub fn active_prompt(&self) -> Option<&PermissionPrompt> {
+        self.active.as_ref()
+    }
+
+    /// Forget any pending output and active prompt.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.active = None;
+    }
+}
+
+/// Parse the last complete permission prompt in ANSI-stripped terminal text.
+pub fn parse_permission_prompt(text: &str) -> Option<PermissionPrompt> {
+    let lines: Vec<&str> = text.lines().map(clean_line).collect();
+
+    let (index, question, options) = lines.iter().enumerate().rev().find_map(|(i, line)| {
+        if let Some(caps) = YES_NO_RE.captures(line) {
+            let question = caps.get(1)?.as_str().to_string();
+            let options = vec![
+                answer("Yes", "y", true),
+                answer("No", "n", false),
+            ];
+            return Some((i, question, options));
+        }
+        if QUESTION_RE.is_match(line) {
+            let options = parse_numbered_answers(&lines[i + 1..])?;
+            return Some((i, line.to_string(), options));
+        }
+        None
+    })?;
+
+    let tool = lines[index.saturating_sub(TOOL_HEADER_LOOKBACK)..index]
+        .iter()
+        .rev()
+        .find_map(|line| {
+            TOOL_HEADERS
+                .iter()
+                .find(|(header, _)| line.starts_with(header))
+                .map(|(_, tool)| tool.to_string())
+        })
+        .or_else(|| infer_tool_from_question(&question));
+
+    Some(PermissionPrompt::new(question, options, tool))
+}
+
+/// Parse numbered answers following a question.
+///
+/// The list is only complete once a denying answer ("No, ...") has arrived,
+/// since Claude Code always lists it last.
+fn parse_numbered_answers(lines: &[&str]) -> Option<Vec<PermissionPromptOption>> {
+    let mut options = Vec::new();
+    for line in lines {
+        if line.is_empty() {
+            continue;
+        }
+        let Some(caps) = ANSWER_RE.captures(line) else {
+            break;
+        };
+        let key = caps.get(1)?.as_str();
+        let label = caps.get(2)?.as_str().trim();
+        let allows = !label.to_lowercase().starts_with("no");
+        options.push(answer(label, key, allows));
+    }
+
+    let complete = options.len() >= 2 && options.last().is_some_and(|opt| !opt.allows);
+    complete.then_some(options)
+}
+
+fn answer(label: &str, key: &str, allows: bool) -> PermissionPromptOption {
+    PermissionPromptOption {
+        label: label.to_string(),
+        key: key.to_string(),
+        allows,
+    }
+}
+
+/// Guess the tool from the wording of the question.
+fn infer_tool_from_question(question: &str) -> Option<String> {
+    let lower = question.to_lowercase();
+    let tool = if lower.contains("edit") {
+        "Edit"
+    } else if lower.contains("create") || lower.contains("write") {
+        "Write"
+    } else if lower.contains("fetch") {
+        "WebFetch"
+    } else if lower.contains("run") || lower.contains("command") {
+        "Bash"
+    } else {
+        return None;
+    };
+    Some(tool.to_string())
+}
+
+/// Trim whitespace and box-drawing borders from a line.
+fn clean_line(line: &str) -> &str {
+    line.trim().trim_matches(['│', '|']).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDIT_PROMPT: &str = "\
+╭──────────────────────────────────────────────╮
+│ Edit file                                    │
+│ ╭──────────────────────────────────────────╮ │
+│ │ src/main.rs                              │ │
+│ │  1 - fn main() {}                        │ │
+│ │  1 + fn main() { run(); }                │ │
+│ ╰──────────────────────────────────────────╯ │
+│ Do you want to make this edit to main.rs?    │
+│ ❯ 1. Yes                                     │
+│   2. Yes, and don't ask again this session   │
+│   3. No, and tell Claude what to do differently (esc) │
+╰──────────────────────────────────────────────╯
+";
+
+    #[test]
+    fn test_parses_numbered_permission_prompt() {
+        let prompt = parse_permission_prompt(EDIT_PROMPT).unwrap();
+        assert_eq!(prompt.question, "Do you want to make this edit to main.rs?");
+        assert_eq!(prompt.tool.as_deref(), Some("Edit"));
+
+        let labels: Vec<_> = prompt.options.iter().map(|o| o.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Yes",
+                "Yes, and don't ask again this session",
+                "No, and tell Claude what to do differently (esc)",
+            ]
+        );
+        let keys: Vec<_> = prompt.options.iter().map(|o| o.key.as_str()).collect();
+        assert_eq!(keys, vec!["1", "2", "3"]);
+        let allows: Vec<_> = prompt.options.iter().map(|o| o.allows).collect();
+        assert_eq!(allows, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_parses_yes_no_prompt() {
+        let prompt = parse_permission_prompt("Bash command\n  rm -rf target\nAllow this command? (y/n)\n").unwrap();
+        assert_eq!(prompt.question, "Allow this command?");
+        assert_eq!(prompt.tool.as_deref(), Some("Bash"));
+        assert_eq!(prompt.options.len(), 2);
+        assert_eq!((prompt.options[0].key.as_str(), prompt.options[0].allows), ("y", true));
+        assert_eq!((prompt.options[1].key.as_str(), prompt.options[1].allows), ("n", false));
+    }
+
+    #[test]
+    fn test_incomplete_prompt_not_detected() {
+        let partial = "Do you want to proceed?\n❯ 1. Yes\n  2. Yes, and don't";
+        assert!(parse_permission_prompt(partial).is_none());
+        assert!(parse_permission_prompt("Regular output\n1. First\n2. Second\n").is_none());
+    }
+
+    #[test]
+    fn test_parser_detects_across_chunks_and_clears_on_answer() {
+        let mut parser = PermissionPromptParser::new();
+        let (first, second) = EDIT_PROMPT.split_at(EDIT_PROMPT.find("2. Yes").unwrap());
+
+        assert!(parser.process(format!("\x1b[1m{first}\x1b[0m").as_bytes()).is_none());
+        let prompt = parser.process(second.as_bytes()).unwrap();
+        assert_eq!(parser.active_prompt().unwrap().id, prompt.id);
+
+        // Redraws while waiting don't re-emit
+        assert!(parser.process(EDIT_PROMPT.as_bytes()).is_none());
+
+        // Non-answer keys leave it open, an answer key closes it
+        assert!(parser.answer(b"\x1b[B").is_none());
+        assert!(parser.answer(b"x").is_none());
+        assert_eq!(parser.answer(b"1").unwrap().id, prompt.id);
+        assert!(parser.active_prompt().is_none());
+
+        // A new prompt can be detected afterwards
+        assert!(parser.process(EDIT_PROMPT.as_bytes()).is_some());
+        assert!(parser.answer(b"\r").is_some());
+    }
+}
@@ -0,0 +1,134 @@
+//! Per-model token pricing.
+//!
+//! Cost normally comes from Claude's status line. When a session never shows
+//! one (e.g. proxied models), cost is estimated from accumulated token usage
+//! using these rates instead of staying at $0.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// USD rates per million tokens for one model.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelRates {
+    pub input: f64,
+    pub output: f64,
+    /// Rate for tokens read from the prompt cache
+    pub cache_read: f64,
+    /// Rate for tokens written to the prompt cache
+    pub cache_write: f64,
+}
+
+impl ModelRates {
+    pub const fn new(input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read,
+            cache_write,
+        }
+    }
+}
+
+/// Published Claude rates (5-minute cache writes), keyed by model name fragment.
+const DEFAULT_RATES: &[(&str, ModelRates)] = &[
+    ("opus", ModelRates::new(15.0, 75.0, 1.5, 18.75)),
+    ("opus-4-5", ModelRates::new(5.0, 25.0, 0.5, 6.25)),
+    ("sonnet", ModelRates::new(3.0, 15.0, 0.3, 3.75)),
+    ("haiku", ModelRates::new(1.0, 5.0, 0.1, 1.25)),
+    ("3-5-haiku", ModelRates::new(0.8, 4.0, 0.08, 1.0)),
+    ("3-haiku", ModelRates::new(0.25, 1.25, 0.03, 0.3)),
+];
+
+/// Pricing table mapping model names to rates.
+///
+/// Keys match a model name exactly or as a fragment ("sonnet" matches
+/// "claude-sonnet-4-5-20250929"); the longest matching key wins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingTable {
+    rates: HashMap<String, ModelRates>,
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self {
+            rates: DEFAULT_RATES
+                .iter()
+                .map(|(model, rates)| (model.to_string(), *rates))
+                .collect(),
+        }
+    }
+}
+
+impl PricingTable {
+    /// A table with no rates; every cost computes to zero.
+    pub fn empty() -> Self {
+        Self {
+            rates: HashMap::new(),
+        }
+    }
+
+    /// Register (or replace) the rates for a model name or fragment.
+    pub fn set(&mut self, model: &str, rates: ModelRates) {
+        self.rates.insert(model.to_lowercase(), rates);
+    }
+
+    /// Rates for a model, if any key matches.
+    pub fn rates_for(&self, model: &str) -> Option<ModelRates> {
+        let model = model.to_lowercase();
+        if let Some(rates) = self.rates.get(&model) {
+            return Some(*rates);
+        }
+        self.rates
+            .iter()
+            .filter(|(key, _)| model.contains(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, rates)| *rates)
+    }
+
+    /// USD cost of the given token counts, or 0.0 for an unpriced model.
+    pub fn compute_cost(
+        &self,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+        cache_creation_tokens: u64,
+    ) -> f64 {
+        let Some(rates) = self.rates_for(model) else {
+            return 0.0;
+        };
+        (input_tokens as f64 * rates.input
+            + output_tokens as f64 * rates.output
+            + cache_read_tokens as f64 * rates.cache_read
+            + cache_creation_tokens as f64 * rates.cache_write)
+            / 1_000_000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rates_match_model_families() {
+        let table = PricingTable::default();
+        assert_eq!(table.rates_for("claude-sonnet-4-5-20250929").unwrap().input, 3.0);
+        assert_eq!(table.rates_for("claude-opus-4-1").unwrap().output, 75.0);
+        assert_eq!(table.rates_for("claude-opus-4-5-20251101").unwrap().output, 25.0);
+        assert_eq!(table.rates_for("claude-3-5-haiku-latest").unwrap().input, 0.8);
+        assert!(table.rates_for("gpt-4o").is_none());
+
+        // 1M input + 100K output on Sonnet
+        let cost = table.compute_cost("sonnet", 1_000_000, 100_000, 0, 0);
+        assert!((cost - 4.5).abs() < 1e-9);
+        assert_eq!(table.compute_cost("gpt-4o", 1_000_000, 0, 0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_registered_rates_take_precedence() {
+        let mut table = PricingTable::empty();
+        table.set("my-proxy/sonnet", ModelRates::new(2.0, 10.0, 0.2, 2.5));
+        let cost = table.compute_cost("My-Proxy/Sonnet", 500_000, 100_000, 1_000_000, 200_000);
+        assert!((cost - (1.0 + 1.0 + 0.2 + 0.5)).abs() < 1e-9);
+    }
+}
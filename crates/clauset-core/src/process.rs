@@ -109,6 +109,13 @@ pub enum ProcessEvent {
     /// TUI menu event for native UI rendering.
     /// Sent when a TUI selection menu is detected in terminal output.
     TuiMenu(clauset_types::TuiMenuEvent),
+    /// Periodic liveness check of a terminal session's process.
+    /// Together with the buffer's busy flag this tells an idle session from a
+    /// hung one. The last heartbeat for a process that died has `alive: false`.
+    Heartbeat { session_id: Uuid, alive: bool },
+    /// The terminal buffer was rewritten (e.g. compacted), so clients must
+    /// resync in full from `new_start_seq`.
+    BufferReset { session_id: Uuid, new_start_seq: u64 },
 }
 
 /// Options for spawning a Claude process.
@@ -123,18 +130,51 @@ pub struct SpawnOptions {
     pub resume: bool,
     /// URL for hooks to send events back to
     pub clauset_url: String,
+    /// How terminal output is read from the PTY
+    pub pty_read: PtyReadOptions,
+}
+
+/// How terminal output is read from the PTY and batched into chunks.
+///
+/// Each chunk becomes a buffered `SequencedChunk` and a WebSocket frame, so
+/// small reads mean more overhead while coalescing adds up to `coalesce_ms`
+/// of latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(default)]
+pub struct PtyReadOptions {
+    /// Bytes requested per PTY read
+    pub buffer_size: usize,
+    /// Merge reads arriving within this many milliseconds of the first
+    /// pending one into a single chunk (0 sends every read as it arrives)
+    pub coalesce_ms: u64,
+    /// Largest coalesced chunk in bytes; a full batch is sent right away
+    pub coalesce_max_bytes: usize,
+}
+
+impl Default for PtyReadOptions {
+    fn default() -> Self {
+        Self {
+            buffer_size: 4096,
+            coalesce_ms: 0,
+            coalesce_max_bytes: 64 * 1024,
+        }
+    }
 }
 
 /// Manages Claude CLI processes.
 pub struct ProcessManager {
     claude_path: PathBuf,
     processes: Arc<RwLock<HashMap<Uuid, ManagedProcess>>>,
+    /// How often terminal sessions emit heartbeats (None disables them)
+    heartbeat_interval: Option<Duration>,
 }
 
 enum ManagedProcess {
     StreamJson {
         handle: tokio::task::JoinHandle<()>,
         stdin_tx: mpsc::Sender<String>,
+        /// OS process ID, captured at spawn
+        pid: Option<u32>,
     },
     Terminal {
         handle: std::thread::JoinHandle<()>,
@@ -144,37 +184,44 @@ enum ManagedProcess {
         shutdown: Arc<AtomicBool>,
         /// Child process for proper termination
         child: Arc<std::sync::Mutex<Box<dyn PtyChild + Send + Sync>>>,
+        /// OS process ID of the PTY child, captured at spawn
+        pid: Option<u32>,
     },
 }
 
+impl ManagedProcess {
+    fn pid(&self) -> Option<u32> {
+        match self {
+            ManagedProcess::StreamJson { pid, .. } | ManagedProcess::Terminal { pid, .. } => *pid,
+        }
+    }
+}
+
 impl ProcessManager {
     /// Create a new process manager.
     pub fn new(claude_path: PathBuf) -> Self {
         Self {
             claude_path,
             processes: Arc::new(RwLock::new(HashMap::new())),
+            heartbeat_interval: None,
         }
     }
 
-    /// Spawn a new Claude process.
-    pub async fn spawn(
-        &self,
-        opts: SpawnOptions,
-        event_tx: broadcast::Sender<ProcessEvent>,
-    ) -> Result<()> {
-        match opts.mode {
-            SessionMode::StreamJson => self.spawn_stream_json(opts, event_tx).await,
-            SessionMode::Terminal => self.spawn_terminal(opts, event_tx).await,
-        }
+    /// Emit a [`ProcessEvent::Heartbeat`] for each terminal session every `interval`.
+    pub fn with_heartbeat_interval(mut self, interval: Option<Duration>) -> Self {
+        self.heartbeat_interval = interval;
+        self
     }
 
-    async fn spawn_stream_json(
+    /// Spawn a new Claude process.
+    pub async fn spawn(
         &self,
         opts: SpawnOptions,
         event_tx: broadcast::Sender<ProcessEvent>,
     ) -> Result<()> {
-        // Validate project path exists
-        if !opts.project_path.exists() {
+        // Validate project path is a directory (the PTY would otherwise fall
+        // back to another working directory instead of failing)
+        if !opts.project_path.is_dir() {
             error!(target: "clauset::process", "Project path does not exist: {:?}", opts.project_path);
             return Err(ClausetError::ProcessSpawnFailed(format!(
                 "Project path does not exist: {:?}",
@@ -191,6 +238,17 @@ impl ProcessManager {
             )));
         }
 
+        match opts.mode {
+            SessionMode::StreamJson => self.spawn_stream_json(opts, event_tx).await,
+            SessionMode::Terminal => self.spawn_terminal(opts, event_tx).await,
+        }
+    }
+
+    async fn spawn_stream_json(
+        &self,
+        opts: SpawnOptions,
+        event_tx: broadcast::Sender<ProcessEvent>,
+    ) -> Result<()> {
         let mut cmd = tokio::process::Command::new(&self.claude_path);
 
         // Build arguments - use print mode with streaming JSON output
@@ -227,6 +285,7 @@ impl ProcessManager {
             ClausetError::ProcessSpawnFailed(format!("Failed to spawn: {}", e))
         })?;
 
+        let pid = child.id();
         let stdout = child.stdout.take().unwrap();
         let stderr = child.stderr.take();
         let session_id = opts.session_id;
@@ -298,6 +357,7 @@ impl ProcessManager {
             ManagedProcess::StreamJson {
                 handle,
                 stdin_tx,
+                pid,
             },
         );
 
@@ -368,6 +428,7 @@ impl ProcessManager {
             .slave
             .spawn_command(cmd)
             .map_err(|e| ClausetError::ProcessSpawnFailed(e.to_string()))?;
+        let pid = child.process_id();
 
         // Wrap child for shared access (needed for termination)
         let child: Arc<std::sync::Mutex<Box<dyn PtyChild + Send + Sync>>> =
@@ -376,6 +437,7 @@ impl ProcessManager {
         // Create shutdown signal for clean termination
         let shutdown = Arc::new(AtomicBool::new(false));
         let shutdown_for_thread = shutdown.clone();
+        let child_for_thread = child.clone();
 
         let mut reader = pair
             .master
@@ -391,13 +453,30 @@ impl ProcessManager {
 
         let session_id = opts.session_id;
         let tx = event_tx.clone();
+        let pty_read = opts.pty_read;
 
         // Reader thread (PTY reading is blocking)
         // Note: Initial prompt is now passed as CLI argument, no PTY-based prompt sending needed
         let handle = std::thread::spawn(move || {
-            let mut buf = [0u8; 4096];
+            let mut buf = vec![0u8; pty_read.buffer_size.max(1)];
             debug!(target: "clauset::process", "PTY reader thread started for session {}", session_id);
 
+            // With coalescing, reads go through a batching thread instead of
+            // straight to the event channel
+            let (raw_tx, coalescer) = if pty_read.coalesce_ms > 0 {
+                let (raw_tx, raw_rx) = std::sync::mpsc::channel::<Vec<u8>>();
+                let tx = tx.clone();
+                let window = Duration::from_millis(pty_read.coalesce_ms);
+                let coalescer = std::thread::spawn(move || {
+                    coalesce_output(raw_rx, window, pty_read.coalesce_max_bytes, |data| {
+                        let _ = tx.send(ProcessEvent::TerminalOutput { session_id, data });
+                    });
+                });
+                (Some(raw_tx), Some(coalescer))
+            } else {
+                (None, None)
+            };
+
             loop {
                 // Check shutdown signal before each read
                 if shutdown_for_thread.load(Ordering::SeqCst) {
@@ -415,10 +494,17 @@ impl ProcessManager {
                         trace!(target: "clauset::process", "PTY output ({} bytes): {}", n,
                               output_str.chars().take(200).collect::<String>());
 
-                        let _ = tx.send(ProcessEvent::TerminalOutput {
-                            session_id,
-                            data: buf[..n].to_vec(),
-                        });
+                        match &raw_tx {
+                            Some(raw_tx) => {
+                                let _ = raw_tx.send(buf[..n].to_vec());
+                            }
+                            None => {
+                                let _ = tx.send(ProcessEvent::TerminalOutput {
+                                    session_id,
+                                    data: buf[..n].to_vec(),
+                                });
+                            }
+                        }
                     }
                     Err(e) => {
                         // Don't log error if we're shutting down (expected)
@@ -430,11 +516,32 @@ impl ProcessManager {
                 }
             }
 
-            // Don't wait for process here - that's handled by terminate()
-            // Just signal that the reader thread is done
+            // Flush whatever the coalescer still holds
+            drop(raw_tx);
+            if let Some(coalescer) = coalescer {
+                let _ = coalescer.join();
+            }
+
+            // Without a shutdown signal the PTY closed because Claude exited
+            // on its own (/exit, a crash); terminate() reaps killed processes
+            if !shutdown_for_thread.load(Ordering::SeqCst) {
+                let exit_code = wait_for_exit_code(&child_for_thread);
+                let _ = tx.send(ProcessEvent::Exited { session_id, exit_code });
+            }
             debug!(target: "clauset::process", "PTY reader thread exiting for session {}", session_id);
         });
 
+        if let Some(interval) = self.heartbeat_interval {
+            tokio::spawn(run_heartbeat(
+                session_id,
+                interval,
+                child.clone(),
+                writer.clone(),
+                shutdown.clone(),
+                event_tx,
+            ));
+        }
+
         self.processes.write().await.insert(
             opts.session_id,
             ManagedProcess::Terminal {
@@ -443,6 +550,7 @@ impl ProcessManager {
                 master: Arc::new(std::sync::Mutex::new(pair.master)),
                 shutdown,
                 child,
+                pid,
             },
         );
 
@@ -523,6 +631,17 @@ impl ProcessManager {
         Ok(())
     }
 
+    /// Current `(cols, rows)` of a session's PTY, or None if the session has
+    /// no terminal process.
+    pub async fn current_dimensions(&self, session_id: Uuid) -> Option<(u16, u16)> {
+        let processes = self.processes.read().await;
+        let Some(ManagedProcess::Terminal { master, .. }) = processes.get(&session_id) else {
+            return None;
+        };
+        let size = master.lock().unwrap().get_size().ok()?;
+        Some((size.cols, size.rows))
+    }
+
     /// Terminate a process gracefully.
     ///
     /// For Terminal mode: send "exit" → wait → close PTY → join thread → kill process group
@@ -538,6 +657,7 @@ impl ProcessManager {
                     child,
                     master,
                     writer,
+                    ..
                 } => {
                     info!(target: "clauset::process", "Terminating terminal session {}", session_id);
 
@@ -641,4 +761,308 @@ impl ProcessManager {
     pub async fn is_active(&self, session_id: Uuid) -> bool {
         self.processes.read().await.contains_key(&session_id)
     }
+
+    /// OS process ID of a session's Claude process, if it is running.
+    pub async fn session_pid(&self, session_id: Uuid) -> Option<u32> {
+        self.processes.read().await.get(&session_id)?.pid()
+    }
+
+    /// Every session with a running process and its OS process ID, ordered
+    /// by session ID.
+    pub async fn sessions_with_pids(&self) -> Vec<(Uuid, u32)> {
+        let mut pids: Vec<_> = self
+            .processes
+            .read()
+            .await
+            .iter()
+            .filter_map(|(session_id, process)| Some((*session_id, process.pid()?)))
+            .collect();
+        pids.sort();
+        pids
+    }
+}
+
+/// Send a heartbeat for a terminal session every `interval` until its process
+/// dies (reported once with `alive: false`) or the session is terminated.
+async fn run_heartbeat(
+    session_id: Uuid,
+    interval: Duration,
+    child: Arc<std::sync::Mutex<Box<dyn PtyChild + Send + Sync>>>,
+    writer: Arc<std::sync::Mutex<Box<dyn Write + Send>>>,
+    shutdown: Arc<AtomicBool>,
+    event_tx: broadcast::Sender<ProcessEvent>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    // The first tick completes immediately
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        let alive = terminal_alive(&child, &writer);
+        if !alive {
+            warn!(target: "clauset::process", "Terminal process for session {} is no longer alive", session_id);
+        }
+        let _ = event_tx.send(ProcessEvent::Heartbeat { session_id, alive });
+        if !alive {
+            break;
+        }
+    }
+}
+
+/// Whether a terminal session's process is still running and its PTY still
+/// accepts writes. Flushing checks the PTY without sending the process input.
+fn terminal_alive(
+    child: &std::sync::Mutex<Box<dyn PtyChild + Send + Sync>>,
+    writer: &std::sync::Mutex<Box<dyn Write + Send>>,
+) -> bool {
+    let running = child
+        .lock()
+        .map(|mut c| matches!(c.try_wait(), Ok(None)))
+        .unwrap_or(false);
+    running && writer.lock().map(|mut w| w.flush().is_ok()).unwrap_or(false)
+}
+
+/// Exit code of a PTY child whose terminal has closed, waiting briefly for
+/// it to be reaped. None if it is still running after that.
+fn wait_for_exit_code(child: &std::sync::Mutex<Box<dyn PtyChild + Send + Sync>>) -> Option<i32> {
+    for _ in 0..20 {
+        let status = child.lock().ok()?.try_wait();
+        match status {
+            Ok(Some(status)) => return i32::try_from(status.exit_code()).ok(),
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+/// Forward PTY reads from `rx` to `emit`, merging reads that arrive within
+/// `window` of the first pending one into chunks of at most `max_bytes`.
+///
+/// A batch is emitted as soon as its window elapses or it would overflow, so
+/// output is never held back longer than `window`. Returns once `rx` is
+/// closed and everything pending has been emitted.
+fn coalesce_output(
+    rx: std::sync::mpsc::Receiver<Vec<u8>>,
+    window: Duration,
+    max_bytes: usize,
+    mut emit: impl FnMut(Vec<u8>),
+) {
+    let mut carry: Option<Vec<u8>> = None;
+    loop {
+        let mut batch = match carry.take() {
+            Some(data) => data,
+            None => match rx.recv() {
+                Ok(data) => data,
+                Err(_) => return,
+            },
+        };
+        let deadline = std::time::Instant::now() + window;
+        while batch.len() < max_bytes {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match rx.recv_timeout(remaining) {
+                Ok(more) if batch.len() + more.len() > max_bytes => {
+                    carry = Some(more);
+                    break;
+                }
+                Ok(more) => batch.extend_from_slice(&more),
+                // Window elapsed, or the reader is gone
+                Err(_) => break,
+            }
+        }
+        emit(batch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed a burst of 10-byte reads through `coalesce_output` and return
+    /// each emitted chunk with how long its oldest byte waited.
+    fn coalesce_burst(window: Duration, max_bytes: usize) -> Vec<(Vec<u8>, Duration)> {
+        let (raw_tx, raw_rx) = std::sync::mpsc::channel();
+        let sent_at = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let producer = {
+            let sent_at = sent_at.clone();
+            std::thread::spawn(move || {
+                for i in 0..200u8 {
+                    sent_at.lock().unwrap().push(std::time::Instant::now());
+                    raw_tx.send(vec![i; 10]).unwrap();
+                    if i % 20 == 19 {
+                        std::thread::sleep(Duration::from_millis(2));
+                    }
+                }
+            })
+        };
+
+        let mut emitted = Vec::new();
+        let mut offset = 0;
+        coalesce_output(raw_rx, window, max_bytes, |data| {
+            let first_sent = sent_at.lock().unwrap()[offset / 10];
+            offset += data.len();
+            emitted.push((data, first_sent.elapsed()));
+        });
+        producer.join().unwrap();
+        emitted
+    }
+
+    #[test]
+    fn test_coalescing_batches_bursts_within_window() {
+        let unbatched = coalesce_burst(Duration::ZERO, 64 * 1024);
+        assert_eq!(unbatched.len(), 200);
+
+        let window = Duration::from_millis(25);
+        let coalesced = coalesce_burst(window, 64 * 1024);
+        assert!(coalesced.len() < unbatched.len() / 4, "{} chunks", coalesced.len());
+        let data: Vec<u8> = coalesced.iter().flat_map(|(chunk, _)| chunk.clone()).collect();
+        assert_eq!(data, (0..200u8).flat_map(|i| [i; 10]).collect::<Vec<_>>());
+        // Nothing waits much past the window (slack for scheduling)
+        for (_, waited) in &coalesced {
+            assert!(*waited < window + Duration::from_millis(50), "waited {waited:?}");
+        }
+
+        // Batches never exceed the size cap
+        let capped = coalesce_burst(window, 50);
+        assert!(capped.iter().all(|(chunk, _)| chunk.len() <= 50));
+        assert_eq!(capped.iter().map(|(chunk, _)| chunk.len()).sum::<usize>(), 2000);
+    }
+
+    #[tokio::test]
+    async fn test_current_dimensions_reflect_resize() {
+        let project = tempfile::TempDir::new().unwrap();
+        let manager = ProcessManager::new(PathBuf::from("/bin/sleep"));
+        let (event_tx, _events) = broadcast::channel(64);
+        let session_id = Uuid::new_v4();
+        manager
+            .spawn(
+                SpawnOptions {
+                    session_id,
+                    claude_session_id: Uuid::new_v4(),
+                    project_path: project.path().to_path_buf(),
+                    prompt: "30".to_string(),
+                    model: None,
+                    mode: SessionMode::Terminal,
+                    resume: false,
+                    clauset_url: String::new(),
+                    pty_read: PtyReadOptions::default(),
+                },
+                event_tx,
+            )
+            .await
+            .unwrap();
+
+        manager.resize_terminal(session_id, 30, 100).await.unwrap();
+        let pty = manager.current_dimensions(session_id).await.unwrap();
+        assert_eq!(pty, (100, 30));
+
+        // A client still drawing at the old size is flagged for a resize
+        let mismatch = crate::reconcile_dimensions((80, 24), pty, 1).unwrap_err();
+        assert_eq!((mismatch.suggested_cols, mismatch.suggested_rows), (80, 24));
+        assert!(crate::reconcile_dimensions((100, 30), pty, 1).is_ok());
+
+        assert_eq!(manager.current_dimensions(Uuid::new_v4()).await, None);
+        manager.terminate(session_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_pid_captured_at_spawn() {
+        let project = tempfile::TempDir::new().unwrap();
+        let manager = ProcessManager::new(PathBuf::from("/bin/sleep"));
+        let (event_tx, _events) = broadcast::channel(64);
+        let session_id = Uuid::new_v4();
+        assert_eq!(manager.session_pid(session_id).await, None);
+        manager
+            .spawn(
+                SpawnOptions {
+                    session_id,
+                    claude_session_id: Uuid::new_v4(),
+                    project_path: project.path().to_path_buf(),
+                    prompt: "30".to_string(),
+                    model: None,
+                    mode: SessionMode::Terminal,
+                    resume: false,
+                    clauset_url: String::new(),
+                    pty_read: PtyReadOptions::default(),
+                },
+                event_tx,
+            )
+            .await
+            .unwrap();
+
+        let pid = manager.session_pid(session_id).await.unwrap();
+        assert!(pid > 0);
+        assert_eq!(manager.sessions_with_pids().await, [(session_id, pid)]);
+
+        manager.terminate(session_id).await.unwrap();
+        assert_eq!(manager.session_pid(session_id).await, None);
+        assert!(manager.sessions_with_pids().await.is_empty());
+    }
+
+    /// Wait for the session's next heartbeat and return its `alive` flag.
+    async fn next_heartbeat(events: &mut broadcast::Receiver<ProcessEvent>, session_id: Uuid) -> bool {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+                .await
+                .expect("no heartbeat")
+                .unwrap();
+            if let ProcessEvent::Heartbeat { session_id: id, alive } = event {
+                assert_eq!(id, session_id);
+                return alive;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_reports_process_death() {
+        let project = tempfile::TempDir::new().unwrap();
+        let manager = ProcessManager::new(PathBuf::from("/bin/sleep"))
+            .with_heartbeat_interval(Some(Duration::from_millis(50)));
+        let (event_tx, mut events) = broadcast::channel(64);
+        let session_id = Uuid::new_v4();
+        manager
+            .spawn(
+                SpawnOptions {
+                    session_id,
+                    claude_session_id: Uuid::new_v4(),
+                    project_path: project.path().to_path_buf(),
+                    // Passed as the positional argument: `sleep 30`
+                    prompt: "30".to_string(),
+                    model: None,
+                    mode: SessionMode::Terminal,
+                    resume: false,
+                    clauset_url: String::new(),
+                    pty_read: PtyReadOptions::default(),
+                },
+                event_tx,
+            )
+            .await
+            .unwrap();
+
+        assert!(next_heartbeat(&mut events, session_id).await);
+        assert!(next_heartbeat(&mut events, session_id).await);
+
+        // Ctrl+C through the PTY kills the sleep
+        manager.send_terminal_input(session_id, b"\x03").await.unwrap();
+        while next_heartbeat(&mut events, session_id).await {}
+        assert!(
+            tokio::time::timeout(Duration::from_millis(300), async {
+                loop {
+                    if let Ok(ProcessEvent::Heartbeat { .. }) = events.recv().await {
+                        break;
+                    }
+                }
+            })
+            .await
+            .is_err(),
+            "heartbeats continued after the process died"
+        );
+
+        manager.terminate(session_id).await.unwrap();
+    }
 }
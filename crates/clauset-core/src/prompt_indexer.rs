@@ -5,11 +5,33 @@
 
 use crate::claude_sessions::ClaudeSessionReader;
 use crate::interaction_store::InteractionStore;
-use crate::Result;
-use clauset_types::Prompt;
-use std::path::PathBuf;
+use crate::quiet_hours::QuietHours;
+use crate::{ClausetError, Result};
+use clauset_types::{Prompt, PromptTemplate, TemplateSlot, ToolInvocation};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Tool input keys whose values are file paths.
+const PATH_KEYS: &[&str] = &["file_path", "path", "notebook_path"];
+
+/// Prompts used fewer times than this aren't exported as commands.
+const COMMAND_MIN_USES: u32 = 2;
+
+/// Longest command name generated from a prompt.
+const COMMAND_NAME_MAX_LEN: usize = 40;
+
+/// Longest command description generated from a prompt.
+const COMMAND_DESCRIPTION_MAX_CHARS: usize = 80;
+
+/// Words too common to make a phrase on their own.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "can", "do", "for", "from", "i", "if",
+    "in", "is", "it", "its", "me", "my", "of", "on", "or", "so", "that", "the", "then", "this", "to",
+    "we", "with", "you", "your",
+];
 
 /// Statistics from a backfill operation.
 #[derive(Debug, Clone, Default)]
@@ -22,12 +44,18 @@ pub struct BackfillStats {
     pub prompts_skipped: u32,
     /// Number of errors encountered.
     pub errors: u32,
+    /// Whether the backfill was cancelled before scanning every session.
+    pub cancelled: bool,
 }
 
 /// Indexes prompts from Claude Code transcript files.
 pub struct PromptIndexer {
     claude_reader: ClaudeSessionReader,
     store: Arc<InteractionStore>,
+    /// Backfill pauses during these hours
+    quiet_hours: Option<Arc<QuietHours>>,
+    /// Backfill skips prompts sent before this
+    retain_since: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl PromptIndexer {
@@ -36,9 +64,34 @@ impl PromptIndexer {
         Self {
             claude_reader: ClaudeSessionReader::new(),
             store,
+            quiet_hours: None,
+            retain_since: None,
+        }
+    }
+
+    /// Create a prompt indexer that reads from a specific Claude directory.
+    pub fn with_claude_dir(store: Arc<InteractionStore>, claude_dir: PathBuf) -> Self {
+        Self {
+            claude_reader: ClaudeSessionReader::with_dir(claude_dir),
+            store,
+            quiet_hours: None,
+            retain_since: None,
         }
     }
 
+    /// Pause backfill between sessions while `quiet_hours` is in effect.
+    pub fn with_quiet_hours(mut self, quiet_hours: Arc<QuietHours>) -> Self {
+        self.quiet_hours = Some(quiet_hours);
+        self
+    }
+
+    /// Skip backfilling prompts sent before `cutoff`, which prompt retention
+    /// would prune again.
+    pub fn with_retain_since(mut self, cutoff: chrono::DateTime<chrono::Utc>) -> Self {
+        self.retain_since = Some(cutoff);
+        self
+    }
+
     /// Check if backfill is needed (prompts table is empty).
     pub fn needs_backfill(&self) -> bool {
         match self.store.is_prompts_empty() {
@@ -52,7 +105,16 @@ impl PromptIndexer {
 
     /// Backfill prompts from all Claude transcript files.
     /// This is called on server startup if the prompts table is empty.
-    pub async fn backfill(&self) -> Result<BackfillStats> {
+    ///
+    /// `cancel` is checked between sessions so a long backfill can be stopped,
+    /// and the backfill waits out quiet hours there if configured;
+    /// `on_progress` is called after each session with the stats so far and
+    /// the total number of sessions.
+    pub async fn backfill(
+        &self,
+        cancel: &CancellationToken,
+        on_progress: impl Fn(&BackfillStats, u32),
+    ) -> Result<BackfillStats> {
         let mut stats = BackfillStats::default();
 
         info!(target: "clauset::prompt_indexer", "Starting prompt backfill from ~/.claude");
@@ -72,7 +134,22 @@ impl PromptIndexer {
             sessions.len()
         );
 
+        let total = sessions.len() as u32;
+
         for session in sessions {
+            if let Some(quiet_hours) = &self.quiet_hours {
+                quiet_hours.wait(cancel).await;
+            }
+            if cancel.is_cancelled() {
+                stats.cancelled = true;
+                info!(
+                    target: "clauset::prompt_indexer",
+                    "Backfill cancelled after {} of {} sessions",
+                    stats.sessions_scanned, total
+                );
+                return Ok(stats);
+            }
+
             stats.sessions_scanned += 1;
 
             let project_path = session.project_path.clone();
@@ -87,6 +164,7 @@ impl PromptIndexer {
                         session.session_id, e
                     );
                     stats.errors += 1;
+                    on_progress(&stats, total);
                     continue;
                 }
             };
@@ -102,6 +180,11 @@ impl PromptIndexer {
                     continue;
                 }
 
+                if self.retain_since.is_some_and(|cutoff| message.timestamp < cutoff) {
+                    stats.prompts_skipped += 1;
+                    continue;
+                }
+
                 let timestamp = message.timestamp.timestamp_millis() as u64;
 
                 let prompt = Prompt::new(
@@ -112,9 +195,9 @@ impl PromptIndexer {
                 );
 
                 match self.store.insert_prompt(&prompt) {
-                    Ok(_) => stats.prompts_indexed += 1,
+                    Ok(true) => stats.prompts_indexed += 1,
+                    Ok(false) => stats.prompts_skipped += 1,
                     Err(e) => {
-                        // Duplicates are handled silently by the UPSERT
                         debug!(
                             target: "clauset::prompt_indexer",
                             "Failed to insert prompt: {}",
@@ -125,6 +208,8 @@ impl PromptIndexer {
                 }
             }
 
+            on_progress(&stats, total);
+
             // Yield to allow other tasks to run
             if stats.sessions_scanned % 100 == 0 {
                 tokio::task::yield_now().await;
@@ -167,18 +252,477 @@ impl PromptIndexer {
             timestamp,
         );
 
-        self.store.insert_prompt(&prompt)
+        self.store.insert_prompt(&prompt).map(|_| ())
+    }
+
+    /// Turn an interaction's prompt into a reusable template and save it.
+    ///
+    /// File paths and identifiers that also appear in the interaction's tool
+    /// inputs, plus quoted paths and identifiers, become `{{name}}` slots.
+    pub fn templatize(&self, interaction_id: Uuid) -> Result<PromptTemplate> {
+        let interaction = self
+            .store
+            .get_interaction(interaction_id)?
+            .ok_or(ClausetError::InteractionNotFound(interaction_id))?;
+        let invocations = self.store.list_tool_invocations(interaction_id)?;
+        let (template, slots) = propose_template(&interaction.user_prompt, &invocations);
+
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let template = PromptTemplate {
+            id: Uuid::new_v4(),
+            interaction_id,
+            template,
+            slots,
+            created_at,
+        };
+        self.store.insert_prompt_template(&template)?;
+        Ok(template)
+    }
+
+    /// Write each frequently used prompt to `dir` as a Claude Code command
+    /// file, named after its opening words with a description frontmatter.
+    ///
+    /// Prompts are de-duplicated across sessions. A command file that already
+    /// holds the same prompt is rewritten in place; other existing files are
+    /// left alone and the new command gets a numbered name. Returns the number
+    /// of commands written.
+    pub fn export_as_commands(&self, dir: &Path) -> Result<u32> {
+        std::fs::create_dir_all(dir)?;
+        let mut used_names = std::collections::HashSet::new();
+        let mut written = 0;
+        for (content, _uses) in self.store.frequent_prompts(COMMAND_MIN_USES)? {
+            let file = format!(
+                "---\ndescription: {}\n---\n\n{}\n",
+                serde_json::to_string(&command_description(&content))?,
+                content.trim()
+            );
+            let base = command_name(&content);
+            let path = (1..)
+                .map(|n| if n == 1 { base.clone() } else { format!("{}-{}", base, n) })
+                .filter(|name| !used_names.contains(name))
+                .map(|name| dir.join(format!("{}.md", name)))
+                .find(|path| match std::fs::read_to_string(path) {
+                    Ok(existing) => existing == file,
+                    // Unreadable files (not UTF-8, no permission) still exist
+                    Err(e) => e.kind() == std::io::ErrorKind::NotFound,
+                })
+                .expect("unbounded name candidates");
+            std::fs::write(&path, &file)?;
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                used_names.insert(stem.to_string());
+            }
+            written += 1;
+        }
+        info!(target: "clauset::prompts", "Exported {} prompts as commands to {}", written, dir.display());
+        Ok(written)
+    }
+
+    /// The `n` most common `ngram`-word phrases across indexed prompts, with
+    /// how often each was used.
+    ///
+    /// Words are lowercased and stripped of punctuation. A phrase counts once
+    /// per prompt, weighted by the prompt's use count; phrases made only of
+    /// stopwords are skipped. Ties are broken alphabetically.
+    pub fn top_phrases(&self, n: usize, ngram: usize) -> Result<Vec<(String, u32)>> {
+        if ngram == 0 {
+            return Ok(Vec::new());
+        }
+        let mut counts: std::collections::HashMap<String, u32> = std::collections::HashMap::new();
+        for (content, uses) in self.store.frequent_prompts(1)? {
+            let words = phrase_words(&content);
+            let phrases: std::collections::HashSet<String> = words
+                .windows(ngram)
+                .filter(|window| !window.iter().all(|word| STOPWORDS.contains(&word.as_str())))
+                .map(|window| window.join(" "))
+                .collect();
+            for phrase in phrases {
+                *counts.entry(phrase).or_default() += uses;
+            }
+        }
+
+        let mut phrases: Vec<_> = counts.into_iter().collect();
+        phrases.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        phrases.truncate(n);
+        Ok(phrases)
+    }
+}
+
+/// A prompt's lowercased words, without surrounding punctuation.
+fn phrase_words(prompt: &str) -> Vec<String> {
+    prompt
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// A filename-safe command name from a prompt's opening words.
+fn command_name(prompt: &str) -> String {
+    let mut name = String::new();
+    for c in prompt.chars().flat_map(char::to_lowercase) {
+        if name.len() >= COMMAND_NAME_MAX_LEN {
+            break;
+        }
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    let name = name.trim_end_matches('-');
+    if name.is_empty() { "prompt".to_string() } else { name.to_string() }
+}
+
+/// A one-line command description from a prompt's first line.
+fn command_description(prompt: &str) -> String {
+    let first_line = prompt.trim().lines().next().unwrap_or_default();
+    let words = first_line.split_whitespace().collect::<Vec<_>>().join(" ");
+    if words.chars().count() <= COMMAND_DESCRIPTION_MAX_CHARS {
+        return words;
+    }
+    let truncated: String = words.chars().take(COMMAND_DESCRIPTION_MAX_CHARS - 3).collect();
+    format!("{}...", truncated.trim_end())
+}
+
+/// Replace likely variable spans of `prompt` with `{{name}}` placeholders.
+fn propose_template(prompt: &str, invocations: &[ToolInvocation]) -> (String, Vec<TemplateSlot>) {
+    let mut candidates: Vec<(String, &'static str)> = Vec::new();
+    for invocation in invocations {
+        let Some(fields) = invocation.tool_input.as_object() else {
+            continue;
+        };
+        for (key, value) in fields {
+            let Some(value) = value.as_str() else {
+                continue;
+            };
+            if PATH_KEYS.contains(&key.as_str()) {
+                // Prompts usually name files relative to the project, so
+                // use the longest trailing part of the path that appears
+                if let Some(suffix) = path_suffixes(value).find(|s| !find_spans(prompt, s).is_empty()) {
+                    candidates.push((suffix.to_string(), "file"));
+                }
+            } else if is_identifier(value) && value.chars().skip(1).any(|c| c == '_' || c.is_uppercase()) {
+                // Only snake_case/camelCase values, not ordinary words
+                candidates.push((value.to_string(), "identifier"));
+            }
+        }
+    }
+    for quote in ['`', '"'] {
+        for quoted in prompt.split(quote).skip(1).step_by(2) {
+            if quoted.is_empty() || quoted.contains(char::is_whitespace) {
+                continue;
+            }
+            if quoted.contains('/') || quoted.contains('.') {
+                candidates.push((quoted.to_string(), "file"));
+            } else if is_identifier(quoted) {
+                candidates.push((quoted.to_string(), "identifier"));
+            }
+        }
+    }
+
+    // Longer values win overlaps, so `src/lib.rs` beats `lib.rs`
+    candidates.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+    candidates.dedup_by(|a, b| a.0 == b.0);
+    let mut spans: Vec<(usize, usize, usize)> = Vec::new();
+    for (index, (value, _)) in candidates.iter().enumerate() {
+        for (start, end) in find_spans(prompt, value) {
+            if spans.iter().all(|&(s, e, _)| end <= s || start >= e) {
+                spans.push((start, end, index));
+            }
+        }
+    }
+    spans.sort();
+
+    let mut slots: Vec<TemplateSlot> = Vec::new();
+    let mut template = String::with_capacity(prompt.len());
+    let mut cursor = 0;
+    for (start, end, index) in spans {
+        let (value, kind) = &candidates[index];
+        let name = match slots.iter().find(|slot| &slot.default_value == value) {
+            Some(slot) => slot.name.clone(),
+            None => {
+                let taken = slots.iter().filter(|slot| slot.name.split('_').next() == Some(kind)).count();
+                let name = if taken == 0 { kind.to_string() } else { format!("{}_{}", kind, taken + 1) };
+                slots.push(TemplateSlot {
+                    name: name.clone(),
+                    default_value: value.clone(),
+                });
+                name
+            }
+        };
+        template.push_str(&prompt[cursor..start]);
+        template.push_str(&format!("{{{{{}}}}}", name));
+        cursor = end;
     }
+    template.push_str(&prompt[cursor..]);
+    (template, slots)
+}
+
+/// A path followed by each shorter trailing part of it, e.g.
+/// `/repo/src/lib.rs`, `repo/src/lib.rs`, `src/lib.rs`, `lib.rs`.
+fn path_suffixes(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(path).chain(
+        path.match_indices('/')
+            .map(move |(i, _)| &path[i + 1..])
+            .filter(|s| !s.is_empty()),
+    )
+}
+
+/// Byte spans where `needle` occurs in `text` as a whole word.
+fn find_spans(text: &str, needle: &str) -> Vec<(usize, usize)> {
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    text.match_indices(needle)
+        .map(|(start, _)| (start, start + needle.len()))
+        .filter(|&(start, end)| !is_word(text[..start].chars().next_back()) && !is_word(text[end..].chars().next()))
+        .collect()
+}
+
+/// Whether `value` is shaped like a code identifier.
+fn is_identifier(value: &str) -> bool {
+    value.len() >= 3
+        && value.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && value.chars().all(|c| c.is_alphanumeric() || c == '_')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use clauset_types::Interaction;
+    use tempfile::TempDir;
+
+    fn create_indexer() -> (PromptIndexer, Arc<InteractionStore>, Uuid, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        // Interactions reference a session, normally created by SessionStore
+        let session_id = Uuid::new_v4();
+        crate::SessionStore::open(&db_path).unwrap();
+        rusqlite::Connection::open(&db_path)
+            .unwrap()
+            .execute(
+                "INSERT INTO sessions (id, claude_session_id, project_path, model, status, mode, created_at, last_activity_at) VALUES (?1, 'claude', '/repo', 'haiku', 'active', 'terminal', ?2, ?2)",
+                rusqlite::params![session_id.to_string(), chrono::Utc::now().to_rfc3339()],
+            )
+            .unwrap();
+        let store = Arc::new(InteractionStore::open(&db_path).unwrap());
+        let indexer = PromptIndexer::with_claude_dir(store.clone(), temp_dir.path().to_path_buf());
+        (indexer, store, session_id, temp_dir)
+    }
+
+    #[test]
+    fn test_templatize_replaces_tool_file_path() {
+        let (indexer, store, session_id, _dir) = create_indexer();
+        let interaction = Interaction::new(
+            session_id,
+            1,
+            "Fix the panic in src/config.rs when `load_settings` gets an empty file".to_string(),
+        );
+        store.insert_interaction(&interaction).unwrap();
+        for (seq, input) in [
+            serde_json::json!({"file_path": "/repo/src/config.rs"}),
+            serde_json::json!({"pattern": "load_settings", "path": "/repo"}),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let invocation = ToolInvocation::new(interaction.id, seq as u32 + 1, "Read".to_string(), input, None);
+            store.insert_tool_invocation(&invocation).unwrap();
+        }
+
+        let template = indexer.templatize(interaction.id).unwrap();
+        assert_eq!(
+            template.template,
+            "Fix the panic in {{file}} when `{{identifier}}` gets an empty file"
+        );
+        assert_eq!(
+            template.slots,
+            vec![
+                TemplateSlot { name: "file".to_string(), default_value: "src/config.rs".to_string() },
+                TemplateSlot { name: "identifier".to_string(), default_value: "load_settings".to_string() },
+            ]
+        );
+
+        let saved = store.get_prompt_template(template.id).unwrap().unwrap();
+        assert_eq!(saved.template, template.template);
+        assert_eq!(saved.slots, template.slots);
+        assert_eq!(saved.render(&Default::default()), interaction.user_prompt);
+    }
+
+    #[test]
+    fn test_templatize_unknown_interaction() {
+        let (indexer, _store, _session_id, _dir) = create_indexer();
+        assert!(matches!(
+            indexer.templatize(Uuid::new_v4()),
+            Err(ClausetError::InteractionNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_export_as_commands() {
+        let (indexer, _store, _session_id, dir) = create_indexer();
+        let review = "Review the diff for bugs: check error handling & edge cases\nThen summarize.";
+        for session in ["a", "b"] {
+            indexer.index_prompt(session, "/repo", review).unwrap();
+        }
+        indexer.index_prompt("a", "/repo", "Run the tests").unwrap();
+        indexer.index_prompt("a", "/repo", "Run the tests").unwrap();
+        // Used once, so not exported
+        indexer.index_prompt("a", "/repo", "What does this function do?").unwrap();
+
+        let commands_dir = dir.path().join("claude").join("commands");
+        assert_eq!(indexer.export_as_commands(&commands_dir).unwrap(), 2);
+
+        let mut names: Vec<_> = std::fs::read_dir(&commands_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["review-the-diff-for-bugs-check-error-han.md", "run-the-tests.md"]);
+        let written = std::fs::read_to_string(commands_dir.join("run-the-tests.md")).unwrap();
+        assert_eq!(written, "---\ndescription: \"Run the tests\"\n---\n\nRun the tests\n");
+
+        // Claude Code's command discovery reads the frontmatter back
+        let mut discovery = crate::CommandDiscovery::with_claude_dir(dir.path().join("claude"));
+        let commands = discovery.discover_all().unwrap().commands;
+        let review_command = commands
+            .iter()
+            .find(|c| c.name == "review-the-diff-for-bugs-check-error-han")
+            .unwrap();
+        assert_eq!(
+            review_command.description,
+            "Review the diff for bugs: check error handling & edge cases"
+        );
+
+        // Re-exporting rewrites the same files; unrelated commands are kept
+        std::fs::write(commands_dir.join("run-the-tests.md"), "My own command").unwrap();
+        assert_eq!(indexer.export_as_commands(&commands_dir).unwrap(), 2);
+        assert_eq!(std::fs::read_dir(&commands_dir).unwrap().count(), 3);
+        assert_eq!(
+            std::fs::read_to_string(commands_dir.join("run-the-tests.md")).unwrap(),
+            "My own command"
+        );
+        assert!(commands_dir.join("run-the-tests-2.md").exists());
+
+        // A file that can't be read as text is never overwritten either
+        std::fs::write(commands_dir.join("run-the-tests-2.md"), b"\xff\xfe binary").unwrap();
+        assert_eq!(indexer.export_as_commands(&commands_dir).unwrap(), 2);
+        assert_eq!(
+            std::fs::read(commands_dir.join("run-the-tests-2.md")).unwrap(),
+            b"\xff\xfe binary"
+        );
+        assert!(commands_dir.join("run-the-tests-3.md").exists());
+    }
+
+    #[test]
+    fn test_command_name_sanitizes() {
+        assert_eq!(command_name("Fix ../../etc/passwd NOW!"), "fix-etc-passwd-now");
+        assert_eq!(command_name("!!!"), "prompt");
+    }
+
+    #[test]
+    fn test_top_phrases() {
+        let (indexer, _store, _session_id, _dir) = create_indexer();
+        for prompt in [
+            "Add a test for the parser",
+            "add a test covering empty input.",
+            "Please add a test for retries",
+            "Fix the login redirect",
+        ] {
+            indexer.index_prompt("a", "/repo", prompt).unwrap();
+        }
+        // Repeated use counts each time
+        indexer.index_prompt("a", "/repo", "Fix the login redirect").unwrap();
+
+        let phrases = indexer.top_phrases(3, 2).unwrap();
+        assert_eq!(phrases[0], ("a test".to_string(), 3));
+        assert_eq!(phrases[1], ("add a".to_string(), 3));
+        assert_eq!(phrases[2], ("fix the".to_string(), 2));
+
+        let trigrams = indexer.top_phrases(1, 3).unwrap();
+        assert_eq!(trigrams, [("add a test".to_string(), 3)]);
+
+        // Stopwords alone never rank
+        let words = indexer.top_phrases(10, 1).unwrap();
+        assert_eq!(words[0], ("add".to_string(), 3));
+        assert!(words.iter().all(|(word, _)| word != "a" && word != "the"));
+        assert!(indexer.top_phrases(5, 0).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_skips_prompts_before_retention_cutoff() {
+        let (indexer, store, _session_id, dir) = create_indexer();
+        let now = chrono::Utc::now();
+        let old = now - chrono::Duration::days(60);
+        std::fs::write(
+            dir.path().join("history.jsonl"),
+            format!(
+                "{{\"display\":\"x\",\"timestamp\":{},\"project\":\"/repo\",\"sessionId\":\"s1\"}}\n",
+                now.timestamp_millis()
+            ),
+        )
+        .unwrap();
+        let transcript_dir = dir.path().join("projects").join("-repo");
+        std::fs::create_dir_all(&transcript_dir).unwrap();
+        let line = |at: chrono::DateTime<chrono::Utc>, text: &str| {
+            serde_json::json!({
+                "type": "user",
+                "message": {"role": "user", "content": text},
+                "timestamp": at.to_rfc3339(),
+            })
+            .to_string()
+        };
+        std::fs::write(
+            transcript_dir.join("s1.jsonl"),
+            format!("{}\n{}\n", line(old, "An ancient prompt"), line(now, "A recent prompt")),
+        )
+        .unwrap();
+
+        let indexer = indexer.with_retain_since(now - chrono::Duration::days(30));
+        let stats = indexer.backfill(&CancellationToken::new(), |_, _| {}).await.unwrap();
+        assert_eq!((stats.prompts_indexed, stats.prompts_skipped), (1, 1));
+        let prompts = store.list_prompts(10, 0).unwrap();
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].preview, "A recent prompt");
+    }
+
+    #[tokio::test]
+    async fn test_backfill_stops_at_next_session_once_cancelled() {
+        let (indexer, store, _session_id, dir) = create_indexer();
+        let transcript_dir = dir.path().join("projects").join("-repo");
+        std::fs::create_dir_all(&transcript_dir).unwrap();
+        let mut history = String::new();
+        for i in 0..3 {
+            history.push_str(&format!(
+                "{{\"display\":\"x\",\"timestamp\":{},\"project\":\"/repo\",\"sessionId\":\"s{i}\"}}\n",
+                1_700_000_000_000i64 + i
+            ));
+            let message = serde_json::json!({
+                "type": "user",
+                "message": {"role": "user", "content": format!("Prompt number {i}")},
+                "timestamp": "2025-01-01T00:00:00Z",
+            });
+            std::fs::write(transcript_dir.join(format!("s{i}.jsonl")), format!("{message}\n")).unwrap();
+        }
+        std::fs::write(dir.path().join("history.jsonl"), history).unwrap();
+
+        // Cancel as soon as the first session is reported
+        let cancel = CancellationToken::new();
+        let stats = indexer.backfill(&cancel, |_, _| cancel.cancel()).await.unwrap();
+        assert!(stats.cancelled);
+        assert_eq!(stats.sessions_scanned, 1);
+        assert_eq!(store.get_prompt_count().unwrap(), 1);
+    }
 
     #[test]
     fn test_backfill_stats_default() {
         let stats = BackfillStats::default();
         assert_eq!(stats.sessions_scanned, 0);
         assert_eq!(stats.prompts_indexed, 0);
+        assert!(!stats.cancelled);
     }
 }
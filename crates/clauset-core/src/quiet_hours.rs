@@ -0,0 +1,156 @@
+//! Quiet hours for heavy background work.
+//!
+//! Long-running tasks like the prompt backfill hammer the database, so they
+//! wait on a shared [`QuietHours`] gate before each batch and only proceed
+//! outside the configured daily windows.
+
+use chrono::{Local, NaiveTime};
+use serde::Deserialize;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// How often a paused task re-checks the clock.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A daily window in local time, e.g. `{ start = "09:00", end = "18:00" }`.
+/// A window whose end is before its start spans midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct QuietWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietWindow {
+    /// Whether `time` falls inside the window (start inclusive, end exclusive).
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Shared gate that background task loops wait on before each batch.
+/// The windows can be replaced at runtime, which wakes paused tasks.
+#[derive(Debug, Default)]
+pub struct QuietHours {
+    windows: RwLock<Vec<QuietWindow>>,
+    changed: Notify,
+}
+
+impl QuietHours {
+    pub fn new(windows: Vec<QuietWindow>) -> Self {
+        Self {
+            windows: RwLock::new(windows),
+            changed: Notify::new(),
+        }
+    }
+
+    /// Replace the quiet windows and wake any paused tasks to re-check them.
+    pub fn set_windows(&self, windows: Vec<QuietWindow>) {
+        *self.windows.write().unwrap() = windows;
+        self.changed.notify_waiters();
+    }
+
+    /// Whether `time` falls inside any quiet window.
+    pub fn is_quiet_at(&self, time: NaiveTime) -> bool {
+        self.windows.read().unwrap().iter().any(|window| window.contains(time))
+    }
+
+    /// Whether it is currently quiet hours (local time).
+    pub fn is_quiet_now(&self) -> bool {
+        self.is_quiet_at(Local::now().time())
+    }
+
+    /// Wait until outside quiet hours. Returns false if `cancel` fired first.
+    pub async fn wait(&self, cancel: &CancellationToken) -> bool {
+        let mut logged = false;
+        loop {
+            // Register for changes before checking, so a concurrent
+            // `set_windows` can't slip in between
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if !self.is_quiet_now() {
+                if logged {
+                    info!(target: "clauset::tasks", "Quiet hours over, resuming background work");
+                }
+                return true;
+            }
+            if !logged {
+                info!(target: "clauset::tasks", "Quiet hours, pausing background work");
+                logged = true;
+            }
+
+            tokio::select! {
+                _ = cancel.cancelled() => return false,
+                _ = &mut changed => {}
+                _ = tokio::time::sleep(RECHECK_INTERVAL) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn time(s: &str) -> NaiveTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_window_contains() {
+        let day = QuietWindow { start: time("09:00"), end: time("18:00") };
+        assert!(day.contains(time("09:00")));
+        assert!(day.contains(time("12:30")));
+        assert!(!day.contains(time("18:00")));
+        assert!(!day.contains(time("08:59")));
+
+        let night = QuietWindow { start: time("22:00"), end: time("06:00") };
+        assert!(night.contains(time("23:15")));
+        assert!(night.contains(time("00:00")));
+        assert!(night.contains(time("05:59")));
+        assert!(!night.contains(time("06:00")));
+        assert!(!night.contains(time("12:00")));
+    }
+
+    #[tokio::test]
+    async fn test_wait_resumes_when_windows_lifted() {
+        let now = Local::now().time();
+        let window = QuietWindow {
+            start: now - chrono::Duration::hours(1),
+            end: now + chrono::Duration::hours(1),
+        };
+        let gate = Arc::new(QuietHours::new(vec![window]));
+        assert!(gate.is_quiet_now());
+
+        let waiter = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.wait(&CancellationToken::new()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        gate.set_windows(Vec::new());
+        assert!(waiter.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_wait_returns_false_when_cancelled() {
+        let now = Local::now().time();
+        let gate = QuietHours::new(vec![QuietWindow {
+            start: now - chrono::Duration::hours(1),
+            end: now + chrono::Duration::hours(1),
+        }]);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(!gate.wait(&cancel).await);
+    }
+}
@@ -1,9 +1,13 @@
 //! Session manager orchestrating processes and persistence.
 
-use crate::{AppendResult, ClausetError, ProcessEvent, ProcessManager, Result, SessionActivity, SessionBuffers, SessionStore, SpawnOptions};
+use crate::{render_ansi, ActionSource, ActivityKeywords, AnsiMode, AppendResult, ChangeThreshold, ClausetError, EvictionPolicy, OversizedChunkPolicy, PermissionModeChange, ProcessEvent, ProcessManager, PricingTable, PtyReadOptions, ReadyDetection, RegisteredProject, Result, SessionActivity, SessionBuffers, SessionNameChange, SessionStore, SpawnOptions};
 use clauset_types::{Session, SessionMode, SessionStatus, SessionSummary};
-use std::path::PathBuf;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{broadcast, RwLock};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
@@ -17,6 +21,40 @@ pub struct SessionManagerConfig {
     pub default_model: String,
     /// URL for hooks to send events back to (e.g., "http://localhost:8080")
     pub clauset_url: String,
+    /// Which signal drives the busy → Ready transition
+    pub ready_detection: ReadyDetection,
+    /// Rates used to estimate cost when Claude doesn't report it
+    pub pricing: PricingTable,
+    /// How old terminal output is evicted from session buffers
+    pub eviction_policy: EvictionPolicy,
+    /// How single writes larger than a session buffer are stored
+    pub oversized_chunk_policy: OversizedChunkPolicy,
+    /// Spinner words recognized as thinking/planning status lines
+    pub activity_keywords: ActivityKeywords,
+    /// Activity changes recorded per session for debugging (0 disables)
+    pub activity_history_size: usize,
+    /// Where recent actions come from
+    pub action_source: ActionSource,
+    /// How long a hook-sourced action suppresses terminal-parsed ones
+    pub hook_action_window: Duration,
+    /// Status-line deltas too small to count as an activity change
+    pub change_threshold: ChangeThreshold,
+    /// zstd level for persisted terminal buffers (0 stores them raw)
+    pub buffer_compression_level: i32,
+    /// How often terminal sessions report process liveness (None disables)
+    pub heartbeat_interval: Option<Duration>,
+    /// How terminal output is read from the PTY and batched
+    pub pty_read: PtyReadOptions,
+    /// Busy sessions that receive no output for this long are forced Ready (None disables)
+    pub idle_input_timeout: Option<Duration>,
+    /// Leave the terminal's echo of typed input out of activity parsing
+    pub echo_suppression: bool,
+    /// Directory where terminated sessions' scrollback is written as `<id>.log`
+    pub archive_dir: Option<PathBuf>,
+    /// Strip ANSI escape sequences from archived scrollback
+    pub archive_strip_ansi: bool,
+    /// Claude's data directory, where transcripts live (None uses `~/.claude`)
+    pub claude_dir: Option<PathBuf>,
 }
 
 impl Default for SessionManagerConfig {
@@ -30,6 +68,23 @@ impl Default for SessionManagerConfig {
             max_concurrent_sessions: 10,
             default_model: "haiku".to_string(),
             clauset_url: "http://localhost:8080".to_string(),
+            ready_detection: ReadyDetection::default(),
+            pricing: PricingTable::default(),
+            eviction_policy: EvictionPolicy::default(),
+            oversized_chunk_policy: OversizedChunkPolicy::default(),
+            activity_keywords: ActivityKeywords::default(),
+            activity_history_size: 0,
+            action_source: ActionSource::default(),
+            hook_action_window: crate::DEFAULT_HOOK_ACTION_WINDOW,
+            change_threshold: ChangeThreshold::default(),
+            buffer_compression_level: crate::DEFAULT_BUFFER_COMPRESSION_LEVEL,
+            heartbeat_interval: None,
+            pty_read: PtyReadOptions::default(),
+            idle_input_timeout: None,
+            echo_suppression: false,
+            archive_dir: None,
+            archive_strip_ansi: false,
+            claude_dir: None,
         }
     }
 }
@@ -52,15 +107,33 @@ pub struct SessionManager {
     event_tx: broadcast::Sender<ProcessEvent>,
     active_sessions: Arc<RwLock<Vec<Uuid>>>,
     buffers: Arc<SessionBuffers>,
+    /// Sessions whose scrollback was archived since their process started
+    archived_sessions: std::sync::Mutex<HashSet<Uuid>>,
 }
 
 impl SessionManager {
     /// Create a new session manager.
     pub fn new(config: SessionManagerConfig) -> Result<Self> {
-        let db = Arc::new(SessionStore::open(&config.db_path)?);
-        let process_manager = Arc::new(ProcessManager::new(config.claude_path.clone()));
+        let db = Arc::new(
+            SessionStore::open(&config.db_path)?.with_buffer_compression_level(config.buffer_compression_level),
+        );
+        let process_manager = Arc::new(
+            ProcessManager::new(config.claude_path.clone())
+                .with_heartbeat_interval(config.heartbeat_interval),
+        );
         let (event_tx, _) = broadcast::channel(256);
-        let buffers = Arc::new(SessionBuffers::new());
+        let buffers = Arc::new(
+            SessionBuffers::with_ready_detection(config.ready_detection)
+                .with_pricing(config.pricing.clone())
+                .with_eviction_policy(config.eviction_policy)
+                .with_oversized_chunk_policy(config.oversized_chunk_policy)
+                .with_activity_keywords(config.activity_keywords.clone())
+                .with_activity_history(config.activity_history_size)
+                .with_action_source(config.action_source, config.hook_action_window)
+                .with_change_threshold(config.change_threshold)
+                .with_idle_input_timeout(config.idle_input_timeout)
+                .with_echo_suppression(config.echo_suppression),
+        );
 
         let manager = Self {
             config,
@@ -68,6 +141,7 @@ impl SessionManager {
             process_manager,
             event_tx,
             active_sessions: Arc::new(RwLock::new(Vec::new())),
+            archived_sessions: std::sync::Mutex::new(HashSet::new()),
             buffers,
         };
 
@@ -131,6 +205,7 @@ impl SessionManager {
             output_tokens: 0,
             context_percent: 0,
             preview: truncate_preview(&opts.prompt),
+            claude_version: None,
         };
 
         // Persist to database
@@ -152,32 +227,18 @@ impl SessionManager {
         self.db.update_status(session_id, SessionStatus::Starting)?;
 
         // Spawn process - prompt is passed as CLI argument (like: claude "prompt")
-        let spawn_result = self
-            .process_manager
-            .spawn(
-                SpawnOptions {
-                    session_id,
-                    claude_session_id: session.claude_session_id,
-                    project_path: session.project_path,
-                    prompt: prompt.to_string(),
-                    model: Some(session.model),
-                    mode: session.mode,
-                    resume: false,
-                    clauset_url: self.config.clauset_url.clone(),
-                },
-                self.event_tx.clone(),
-            )
-            .await;
-
-        // Handle spawn failure
-        if let Err(e) = spawn_result {
-            error!(target: "clauset::session", "Failed to spawn Claude process for session {}: {}", session_id, e);
-            // Update status to Error
-            if let Err(db_err) = self.db.update_status(session_id, SessionStatus::Error) {
-                warn!(target: "clauset::session", "Failed to update session {} status to Error in DB: {}", session_id, db_err);
-            }
-            return Err(e);
-        }
+        self.spawn_process(SpawnOptions {
+            session_id,
+            claude_session_id: session.claude_session_id,
+            project_path: session.project_path,
+            prompt: prompt.to_string(),
+            model: Some(session.model),
+            mode: session.mode,
+            resume: false,
+            clauset_url: self.config.clauset_url.clone(),
+            pty_read: self.config.pty_read,
+        })
+        .await?;
 
         // Track as active
         self.active_sessions.write().await.push(session_id);
@@ -195,8 +256,27 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Reader for Claude's own session data (transcripts, history).
+    pub fn claude_reader(&self) -> crate::ClaudeSessionReader {
+        match &self.config.claude_dir {
+            Some(dir) => crate::ClaudeSessionReader::with_dir(dir.clone()),
+            None => crate::ClaudeSessionReader::new(),
+        }
+    }
+
     /// Resume an existing session.
     pub async fn resume_session(&self, session_id: Uuid) -> Result<()> {
+        self.resume_session_in(session_id, None).await
+    }
+
+    /// Resume an existing session, optionally in a different working directory.
+    ///
+    /// The Claude session ID, transcript and interaction history stay attached:
+    /// the transcript is copied to where Claude looks for it in the new
+    /// directory, and the move is rejected with
+    /// [`ClausetError::TranscriptNotFound`] if there is none to copy. Once the
+    /// process is running the new directory is saved on the session.
+    pub async fn resume_session_in(&self, session_id: Uuid, working_dir: Option<PathBuf>) -> Result<()> {
         let session = self
             .db
             .get(session_id)?
@@ -212,6 +292,20 @@ impl SessionManager {
             return Err(ClausetError::SessionNotResumable(session_id));
         }
 
+        // Claude keys transcripts by working directory, so a moved session
+        // needs its transcript where Claude will look for it
+        let project_path = working_dir.unwrap_or(session.project_path.clone());
+        if project_path != session.project_path {
+            let carried = self.claude_reader().carry_transcript(
+                &session.claude_session_id.to_string(),
+                &session.project_path,
+                &project_path,
+            )?;
+            if !carried {
+                return Err(ClausetError::TranscriptNotFound(session_id));
+            }
+        }
+
         // Update status
         self.db.update_status(session_id, SessionStatus::Starting)?;
 
@@ -223,27 +317,37 @@ impl SessionManager {
                 session_id,
                 buffer_data.data.len()
             );
-            self.buffers
+            if self.buffers
                 .restore_buffer(session_id, buffer_data.data, buffer_data.start_seq, buffer_data.end_seq)
-                .await;
+                .await
+            {
+                // Show the last-known status line stats instead of a blank activity
+                self.buffers.reparse_activity(session_id).await;
+            }
         }
 
         // Spawn process in resume mode
-        self.process_manager
-            .spawn(
-                SpawnOptions {
-                    session_id,
-                    claude_session_id: session.claude_session_id,
-                    project_path: session.project_path,
-                    prompt: String::new(),
-                    model: Some(session.model),
-                    mode: session.mode,
-                    resume: true,
-                    clauset_url: self.config.clauset_url.clone(),
-                },
-                self.event_tx.clone(),
-            )
-            .await?;
+        self.spawn_process(SpawnOptions {
+            session_id,
+            claude_session_id: session.claude_session_id,
+            project_path: project_path.clone(),
+            prompt: String::new(),
+            model: Some(session.model),
+            mode: session.mode,
+            resume: true,
+            clauset_url: self.config.clauset_url.clone(),
+            pty_read: self.config.pty_read,
+        })
+        .await?;
+
+        if project_path != session.project_path {
+            info!(
+                target: "clauset::session",
+                "Session {} moved from {:?} to {:?}",
+                session_id, session.project_path, project_path
+            );
+            self.db.update_project_path(session_id, &project_path)?;
+        }
 
         // Track as active
         self.active_sessions.write().await.push(session_id);
@@ -258,6 +362,27 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Spawn the Claude process for a session whose row is already persisted.
+    ///
+    /// On failure the row is marked Error so it never lingers as a
+    /// Starting/Active session that can't run.
+    async fn spawn_process(&self, opts: SpawnOptions) -> Result<()> {
+        let session_id = opts.session_id;
+        self.archived_sessions.lock().unwrap().remove(&session_id);
+        if let Err(e) = self.process_manager.spawn(opts, self.event_tx.clone()).await {
+            error!(target: "clauset::session", "Failed to spawn Claude process for session {}: {}", session_id, e);
+            if let Err(db_err) = self.db.update_status(session_id, SessionStatus::Error) {
+                warn!(target: "clauset::session", "Failed to update session {} status to Error in DB: {}", session_id, db_err);
+            }
+            self.active_sessions.write().await.retain(|&id| id != session_id);
+            return Err(ClausetError::SessionSpawnFailed {
+                session_id,
+                reason: e.to_string(),
+            });
+        }
+        Ok(())
+    }
+
     /// Send input to a session.
     pub async fn send_input(&self, session_id: Uuid, input: &str) -> Result<()> {
         self.process_manager.send_input(session_id, input).await
@@ -265,12 +390,26 @@ impl SessionManager {
 
     /// Send terminal input to a PTY session.
     pub async fn send_terminal_input(&self, session_id: Uuid, data: &[u8]) -> Result<()> {
-        self.process_manager.send_terminal_input(session_id, data).await
+        // Noted before writing so the echo can't arrive first
+        self.buffers.note_input(session_id, data).await;
+        self.process_manager.send_terminal_input(session_id, data).await?;
+        self.buffers.handle_terminal_input(session_id, data).await;
+        if let Some(prompt) = self.buffers.answer_permission_prompt(session_id, data).await {
+            let _ = self.event_tx.send(ProcessEvent::TuiMenu(
+                clauset_types::TuiMenuEvent::PermissionPromptAnswered {
+                    session_id,
+                    prompt_id: prompt.id,
+                },
+            ));
+        }
+        Ok(())
     }
 
     /// Resize terminal for a PTY session.
     pub async fn resize_terminal(&self, session_id: Uuid, rows: u16, cols: u16) -> Result<()> {
-        self.process_manager.resize_terminal(session_id, rows, cols).await
+        self.process_manager.resize_terminal(session_id, rows, cols).await?;
+        self.buffers.set_terminal_size(session_id, rows, cols).await;
+        Ok(())
     }
 
     /// Terminate a session.
@@ -280,6 +419,9 @@ impl SessionManager {
         // Remove from active list
         self.active_sessions.write().await.retain(|&id| id != session_id);
 
+        // Archive the full scrollback before persistence compacts it
+        self.archive_session_buffer(session_id).await;
+
         // Persist activity data before updating status
         self.persist_session_activity(session_id).await;
 
@@ -289,17 +431,84 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Write a session's scrollback to `<archive_dir>/<session_id>.log`, if
+    /// archiving is configured. Failures are logged, not returned.
+    ///
+    /// Call before persisting the buffer, which compacts it. Only the first
+    /// call after the process started writes the archive, so a session that
+    /// is terminated and then reported exited is archived once.
+    pub async fn archive_session_buffer(&self, session_id: Uuid) {
+        let Some(archive_dir) = &self.config.archive_dir else {
+            return;
+        };
+        if !self.archived_sessions.lock().unwrap().insert(session_id) {
+            return;
+        }
+        let Some((data, _, _)) = self.buffers.get_buffer_for_persistence(session_id).await else {
+            return;
+        };
+        let data = if self.config.archive_strip_ansi {
+            render_ansi(&data, AnsiMode::Strip).into_bytes()
+        } else {
+            data
+        };
+
+        let path = archive_dir.join(format!("{}.log", session_id));
+        let result = match tokio::fs::create_dir_all(archive_dir).await {
+            Ok(()) => tokio::fs::write(&path, &data).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => info!(
+                target: "clauset::session",
+                "Archived terminal buffer for session {} to {:?} ({} bytes)",
+                session_id,
+                path,
+                data.len()
+            ),
+            Err(e) => warn!(target: "clauset::session", "Failed to archive session {} buffer to {:?}: {}", session_id, path, e),
+        }
+    }
+
     /// Get a session by ID.
     pub fn get_session(&self, session_id: Uuid) -> Result<Option<Session>> {
         self.db.get(session_id)
     }
 
+    /// Register a project directory by canonical path.
+    ///
+    /// Registering an already-known path returns the existing project.
+    pub fn register_project(&self, path: &Path, name: &str) -> Result<RegisteredProject> {
+        if self.db.register_project(path, name)? {
+            info!(target: "clauset::session", "Registered project {} at {:?}", name, path);
+        }
+        self.db
+            .get_project(path)?
+            .ok_or_else(|| ClausetError::ParseError(format!("Project {:?} was not stored", path)))
+    }
+
+    /// List projects registered by path.
+    pub fn list_registered_projects(&self) -> Result<Vec<RegisteredProject>> {
+        self.db.list_projects()
+    }
+
     /// List all sessions with current activity data.
     pub async fn list_sessions(&self) -> Result<Vec<SessionSummary>> {
         let mut sessions = self.db.list()?;
+        self.enrich_with_activity(&mut sessions).await;
+        Ok(sessions)
+    }
 
-        // Enrich active sessions with current activity data from buffers
-        for session in &mut sessions {
+    /// List sessions carrying a tag, with current activity data.
+    pub async fn list_sessions_by_tag(&self, tag: &str) -> Result<Vec<SessionSummary>> {
+        let mut sessions = self.db.list_sessions_by_tag(tag.trim())?;
+        self.enrich_with_activity(&mut sessions).await;
+        Ok(sessions)
+    }
+
+    /// Enrich active sessions with current activity data from buffers.
+    async fn enrich_with_activity(&self, sessions: &mut [SessionSummary]) {
+        for session in sessions {
             if matches!(
                 session.status,
                 SessionStatus::Active | SessionStatus::Starting
@@ -337,8 +546,6 @@ impl SessionManager {
                 }
             }
         }
-
-        Ok(sessions)
     }
 
     /// Update session status.
@@ -380,6 +587,14 @@ impl SessionManager {
         }
 
         // Persist terminal buffer for resume
+        if let Some(new_start_seq) = self
+            .buffers
+            .compact(session_id)
+            .await
+            .and_then(|compaction| compaction.new_start_seq)
+        {
+            let _ = self.event_tx.send(ProcessEvent::BufferReset { session_id, new_start_seq });
+        }
         if let Some((data, start_seq, end_seq)) = self.buffers.get_buffer_for_persistence(session_id).await {
             if let Err(e) = self.db.save_terminal_buffer(session_id, &data, start_seq, end_seq) {
                 warn!(target: "clauset::session", "Failed to persist session {} terminal buffer: {}", session_id, e);
@@ -399,11 +614,21 @@ impl SessionManager {
         self.db.update_cost(session_id, cost)
     }
 
+    /// Record terminal activity without rewriting the rest of the session row.
+    pub fn touch_activity(&self, session_id: Uuid) -> Result<()> {
+        self.db.touch_activity(session_id, chrono::Utc::now())
+    }
+
     /// Check if a session is active.
     pub async fn is_active(&self, session_id: Uuid) -> bool {
         self.process_manager.is_active(session_id).await
     }
 
+    /// Running sessions with the OS process ID behind each.
+    pub async fn sessions_with_pids(&self) -> Vec<(Uuid, u32)> {
+        self.process_manager.sessions_with_pids().await
+    }
+
     /// Get the event sender for external use.
     pub fn event_sender(&self) -> broadcast::Sender<ProcessEvent> {
         self.event_tx.clone()
@@ -431,13 +656,127 @@ impl SessionManager {
         Ok(())
     }
 
-    /// Rename a session (update its preview/name).
+    /// List sessions that never recorded an interaction and have been idle
+    /// for longer than `older_than`. Sessions with a running process are
+    /// never listed.
+    pub async fn list_empty_sessions(&self, older_than: Duration) -> Result<Vec<SessionSummary>> {
+        let cutoff = chrono::Duration::from_std(older_than)
+            .ok()
+            .and_then(|age| chrono::Utc::now().checked_sub_signed(age))
+            .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC);
+        let mut sessions = self.db.list_empty_sessions(cutoff)?;
+        let active = self.active_sessions.read().await;
+        sessions.retain(|s| !active.contains(&s.id));
+        Ok(sessions)
+    }
+
+    /// Delete every session [`list_empty_sessions`](Self::list_empty_sessions)
+    /// returns, returning the deleted ids.
+    pub async fn delete_empty_sessions(&self, older_than: Duration) -> Result<Vec<Uuid>> {
+        let mut deleted = Vec::new();
+        for session in self.list_empty_sessions(older_than).await? {
+            self.delete_session(session.id).await?;
+            deleted.push(session.id);
+        }
+        Ok(deleted)
+    }
+
+    /// Pin or unpin a session. Pinned sessions are skipped by the empty-session
+    /// cleanup and interaction retention.
+    pub fn set_session_pinned(&self, session_id: Uuid, pinned: bool) -> Result<()> {
+        self.db.set_pinned(session_id, pinned)?;
+        info!(target: "clauset::session", "Session {} {}", session_id, if pinned { "pinned" } else { "unpinned" });
+        Ok(())
+    }
+
+    /// Record the Claude Code version reported for a session.
+    pub fn set_claude_version(&self, session_id: Uuid, version: &str) -> Result<()> {
+        self.db.set_claude_version(session_id, version)?;
+        debug!(target: "clauset::session", "Session {} runs Claude Code {}", session_id, version);
+        Ok(())
+    }
+
+    /// Rename a session (update its preview/name), keeping a history of names.
     pub fn rename_session(&self, session_id: Uuid, name: &str) -> Result<()> {
-        self.db.update_preview(session_id, name)?;
+        self.db.rename(session_id, name)?;
         info!(target: "clauset::session", "Session {} renamed to: {}", session_id, name);
         Ok(())
     }
 
+    /// Correct a session's model and/or project path (e.g. after an import).
+    ///
+    /// A new model also resets the activity buffer's context window to that
+    /// model's default.
+    pub async fn update_session_metadata(
+        &self,
+        session_id: Uuid,
+        model: Option<&str>,
+        project_path: Option<&Path>,
+    ) -> Result<Session> {
+        self.db.update_metadata(session_id, model, project_path)?;
+        if let Some(model) = model {
+            self.buffers.set_model(session_id, model).await;
+        }
+        info!(
+            target: "clauset::session",
+            "Session {} metadata updated (model: {:?}, project: {:?})",
+            session_id, model, project_path
+        );
+        self.db
+            .get(session_id)?
+            .ok_or(ClausetError::SessionNotFound(session_id))
+    }
+
+    /// Get a session's rename history, oldest first.
+    pub fn session_name_history(&self, session_id: Uuid) -> Result<Vec<SessionNameChange>> {
+        self.db.session_name_history(session_id)
+    }
+
+    /// Tag a session. Tags are trimmed and deduped per session.
+    /// Returns false if the session already had the tag.
+    pub fn add_session_tag(&self, session_id: Uuid, tag: &str) -> Result<bool> {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(ClausetError::ParseError("Tag must not be empty".to_string()));
+        }
+        self.db.add_session_tag(session_id, tag)
+    }
+
+    /// Remove a tag from a session. Returns false if the session didn't have it.
+    pub fn remove_session_tag(&self, session_id: Uuid, tag: &str) -> Result<bool> {
+        self.db.remove_session_tag(session_id, tag.trim())
+    }
+
+    /// List a session's tags, alphabetically.
+    pub fn session_tags(&self, session_id: Uuid) -> Result<Vec<String>> {
+        self.db.list_session_tags(session_id)
+    }
+
+    /// Set a metadata key on a session (e.g. `jira` = `PROJ-123`). Keys are
+    /// trimmed and must not be empty.
+    pub fn set_session_metadata(&self, session_id: Uuid, key: &str, value: &str) -> Result<()> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ClausetError::ParseError("Metadata key must not be empty".to_string()));
+        }
+        self.db.set_session_metadata(session_id, key, value)
+    }
+
+    /// Get a session's metadata, ordered by key.
+    pub fn session_metadata(&self, session_id: Uuid) -> Result<BTreeMap<String, String>> {
+        self.db.get_session_metadata(session_id)
+    }
+
+    /// Remove a metadata key from a session. Returns false if it wasn't set.
+    pub fn delete_session_metadata(&self, session_id: Uuid, key: &str) -> Result<bool> {
+        self.db.delete_session_metadata(session_id, key.trim())
+    }
+
+    /// List the ids of sessions whose metadata has `key` set to `value`.
+    pub fn session_ids_with_metadata(&self, key: &str, value: &str) -> Result<Vec<Uuid>> {
+        self.db.session_ids_with_metadata(key.trim(), value)
+    }
+
     /// Update session stats from Claude's status line.
     pub fn update_session_stats(
         &self,
@@ -469,13 +808,29 @@ impl SessionManager {
     /// - activity is Some if it changed
     /// - tui_menu is Some if a new TUI menu was detected
     pub async fn append_terminal_output(&self, session_id: Uuid, data: &[u8]) -> (AppendResult, Option<SessionActivity>, Option<clauset_types::TuiMenu>) {
-        let (append_result, activity, tui_menu, mode_change) = self.buffers.append(session_id, data).await;
+        let (append_result, activity, tui_menu, mode_change, permission_prompt) = self.buffers.append(session_id, data).await;
+
+        // Fall back to the startup banner for the Claude Code version when
+        // no SessionStart hook reported it
+        if append_result.seq < BANNER_SCAN_CHUNKS
+            && let Some(version) = parse_claude_version(&render_ansi(data, AnsiMode::Strip))
+        {
+            match self.db.fill_claude_version(session_id, &version) {
+                Ok(true) => debug!(target: "clauset::session", "Session {} banner shows Claude Code {}", session_id, version),
+                Ok(false) => {}
+                Err(e) => warn!(target: "clauset::session", "Failed to record session {} Claude version: {}", session_id, e),
+            }
+        }
 
         if let Some(mode) = mode_change {
-            let _ = self.event_tx.send(ProcessEvent::ModeChange {
-                session_id,
-                mode,
-            });
+            self.permission_mode_changed(session_id, mode);
+        }
+
+        if let Some(prompt) = permission_prompt {
+            info!(target: "clauset::session", "Permission prompt detected for session {}: {}", session_id, prompt.question);
+            let _ = self.event_tx.send(ProcessEvent::TuiMenu(
+                clauset_types::TuiMenuEvent::PermissionPromptPresented { session_id, prompt },
+            ));
         }
 
         // If activity changed, update the database with new stats
@@ -508,11 +863,30 @@ impl SessionManager {
         self.buffers.get_buffer(session_id).await
     }
 
+    /// Get a session's terminal output, falling back to the copy last
+    /// persisted when none is in memory (e.g. after a restart).
+    pub async fn get_terminal_buffer_or_persisted(&self, session_id: Uuid) -> Result<Option<Vec<u8>>> {
+        if let Some((data, _, _)) = self.buffers.get_buffer_for_persistence(session_id).await {
+            return Ok(Some(data));
+        }
+        Ok(self.db.get_terminal_buffer(session_id)?.map(|buffer| buffer.data))
+    }
+
     /// Get current activity for a session.
     pub async fn get_activity(&self, session_id: Uuid) -> Option<SessionActivity> {
         self.buffers.get_activity(session_id).await
     }
 
+    /// Get the TUI menu currently open in a session, if any.
+    pub async fn get_tui_menu(&self, session_id: Uuid) -> Option<clauset_types::TuiMenu> {
+        self.buffers.get_tui_menu(session_id).await
+    }
+
+    /// Get the permission prompt awaiting an answer in a session, if any.
+    pub async fn get_permission_prompt(&self, session_id: Uuid) -> Option<clauset_types::PermissionPrompt> {
+        self.buffers.get_permission_prompt(session_id).await
+    }
+
     /// Get the session buffers for external use.
     pub fn buffers(&self) -> Arc<SessionBuffers> {
         self.buffers.clone()
@@ -545,6 +919,40 @@ impl SessionManager {
         }
     }
 
+    /// Force sessions that never answered their input back to Ready and
+    /// broadcast the new activity. Returns the IDs of the expired sessions.
+    pub async fn expire_unanswered_input(&self) -> Vec<Uuid> {
+        let expired = self.buffers.expire_unanswered_input().await;
+        self.broadcast_expired(expired)
+    }
+
+    /// Clear busy sessions idle at the prompt whose Stop hook was lost and
+    /// broadcast the new activity. Returns the IDs of the cleared sessions.
+    pub async fn expire_lost_hook_stops(&self) -> Vec<Uuid> {
+        let expired = self.buffers.expire_lost_hook_stops().await;
+        self.broadcast_expired(expired)
+    }
+
+    fn broadcast_expired(&self, expired: Vec<(Uuid, SessionActivity)>) -> Vec<Uuid> {
+        expired
+            .into_iter()
+            .map(|(session_id, activity)| {
+                let _ = self.event_tx.send(ProcessEvent::ActivityUpdate {
+                    session_id,
+                    model: activity.model,
+                    cost: activity.cost,
+                    input_tokens: activity.input_tokens,
+                    output_tokens: activity.output_tokens,
+                    context_percent: activity.context_percent,
+                    current_activity: activity.current_activity,
+                    current_step: activity.current_step,
+                    recent_actions: activity.recent_actions,
+                });
+                session_id
+            })
+            .collect()
+    }
+
     /// Mark a session as ready (Claude finished responding).
     pub async fn mark_session_ready(&self, session_id: Uuid) {
         self.buffers.mark_ready(session_id).await;
@@ -676,6 +1084,13 @@ impl SessionManager {
         }
     }
 
+    /// Record extended thinking tokens for a session (from transcript usage).
+    pub async fn add_thinking_tokens(&self, session_id: Uuid, thinking_tokens: u64) {
+        if thinking_tokens > 0 {
+            self.buffers.add_thinking_tokens(session_id, thinking_tokens).await;
+        }
+    }
+
     /// Reset the context usage percentage (e.g., after /clear) without changing totals.
     pub async fn reset_context_percent(&self, session_id: Uuid) {
         let Some(mut activity) = self.buffers.reset_context_percent(session_id).await else {
@@ -778,13 +1193,26 @@ impl SessionManager {
                 "Broadcasting ModeChange event for session {}: {:?}",
                 session_id, mode
             );
-            let _ = self.event_tx.send(ProcessEvent::ModeChange {
-                session_id,
-                mode,
-            });
+            self.permission_mode_changed(session_id, mode);
         }
     }
 
+    /// Record a permission mode change in the session's timeline and broadcast it.
+    fn permission_mode_changed(&self, session_id: Uuid, mode: clauset_types::PermissionMode) {
+        if let Err(e) = self.db.record_permission_mode(session_id, mode) {
+            warn!(target: "clauset::session::mode", "Failed to record session {} mode change: {}", session_id, e);
+        }
+        let _ = self.event_tx.send(ProcessEvent::ModeChange {
+            session_id,
+            mode,
+        });
+    }
+
+    /// Get a session's permission mode changes, oldest first.
+    pub fn permission_mode_timeline(&self, session_id: Uuid) -> Result<Vec<PermissionModeChange>> {
+        self.db.permission_mode_timeline(session_id)
+    }
+
     /// Update usage from transcript data (authoritative source).
     ///
     /// This is called when the TranscriptWatcher emits a Usage event.
@@ -844,6 +1272,17 @@ impl SessionManager {
     }
 }
 
+/// Output chunks at the start of a session searched for the version banner.
+const BANNER_SCAN_CHUNKS: u64 = 32;
+
+static CLAUDE_VERSION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Claude Code v(\d+\.\d+\.\d+(?:-[0-9A-Za-z.]+)?)").unwrap());
+
+/// Find the version in Claude Code's startup banner ("Claude Code v2.0.14").
+fn parse_claude_version(text: &str) -> Option<String> {
+    CLAUDE_VERSION_RE.captures(text).map(|c| c[1].to_string())
+}
+
 fn truncate_preview(s: &str) -> String {
     const MAX_LEN: usize = 100;
     if s.len() <= MAX_LEN {
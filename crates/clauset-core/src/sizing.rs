@@ -218,6 +218,55 @@ pub fn validate_dimensions(
     })
 }
 
+/// Check a client's reported size against the PTY's actual size.
+///
+/// Both sizes are `(cols, rows)`. When either dimension differs by more
+/// than `tolerance`, returns an error suggesting the client's size, which
+/// the PTY should be resized to so both sides redraw at the same width.
+pub fn reconcile_dimensions(
+    client: (u16, u16),
+    pty: (u16, u16),
+    tolerance: u16,
+) -> Result<(), DimensionError> {
+    let (client_cols, client_rows) = client;
+    let (pty_cols, pty_rows) = pty;
+    if client_cols.abs_diff(pty_cols) <= tolerance && client_rows.abs_diff(pty_rows) <= tolerance {
+        return Ok(());
+    }
+    Err(DimensionError {
+        reason: format!(
+            "client is {}x{} but the PTY is {}x{}",
+            client_cols, client_rows, pty_cols, pty_rows
+        ),
+        suggested_cols: client_cols,
+        suggested_rows: client_rows,
+    })
+}
+
+/// Rejoin physical rows that the terminal wrapped at `cols` into logical lines.
+///
+/// A row that fills the full width continues on the next row, so it is joined
+/// with it. Expects ANSI-stripped text.
+pub fn unwrap_lines(text: &str, cols: u16) -> String {
+    let cols = cols as usize;
+    if cols == 0 {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut wrapped = false;
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 && !wrapped {
+            result.push('\n');
+        }
+        // PTY rows end in "\r\n"; a wrapped row's "\r" would split the line
+        let row = line.trim_end_matches('\r');
+        wrapped = row.chars().count() == cols;
+        result.push_str(if wrapped { row } else { line });
+    }
+    result
+}
+
 /// Get safe default dimensions for a device type.
 fn get_device_defaults(device: &DeviceHint) -> (u16, u16) {
     match device {
@@ -231,6 +280,35 @@ fn get_device_defaults(device: &DeviceHint) -> (u16, u16) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_unwrap_lines_joins_full_width_rows() {
+        let text = "output\nOpus 4.5 | $0.68 | 29.\n2K/22.5K | ctx:11%\n> ";
+        assert_eq!(
+            unwrap_lines(text, 22),
+            "output\nOpus 4.5 | $0.68 | 29.2K/22.5K | ctx:11%\n> "
+        );
+        // PTY output ends rows with "\r\n"
+        let text = "output\r\nOpus 4.5 | $0.68 | 29.\r\n2K/22.5K | ctx:11%\r\n> ";
+        assert_eq!(
+            unwrap_lines(text, 22),
+            "output\r\nOpus 4.5 | $0.68 | 29.2K/22.5K | ctx:11%\r\n> "
+        );
+        // Rows shorter than the width are left alone
+        assert_eq!(unwrap_lines("a\nb\n", 40), "a\nb\n");
+        assert_eq!(unwrap_lines("abc", 0), "abc");
+    }
+
+    #[test]
+    fn test_reconcile_dimensions() {
+        assert!(reconcile_dimensions((80, 24), (80, 24), 0).is_ok());
+        assert!(reconcile_dimensions((81, 24), (80, 24), 1).is_ok());
+
+        let err = reconcile_dimensions((120, 40), (80, 24), 1).unwrap_err();
+        assert_eq!((err.suggested_cols, err.suggested_rows), (120, 40));
+        assert!(err.reason.contains("80x24"));
+        assert!(reconcile_dimensions((80, 26), (80, 24), 1).is_err());
+    }
+
     #[test]
     fn test_valid_dimensions() {
         let result = validate_dimensions(80, 24, None, None, None);
@@ -6,6 +6,7 @@
 //! **The transcript file is the authoritative source for token usage data.**
 //! Each assistant message contains accurate API usage including cache tokens.
 
+use crate::buffer::context_tokens;
 use crate::Result;
 use clauset_types::{ChatEvent, ChatToolCall};
 use notify::{
@@ -14,6 +15,7 @@ use notify::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
@@ -36,6 +38,10 @@ pub struct TranscriptUsage {
     pub cache_creation_input_tokens: u64,
     /// Tokens read from cache
     pub cache_read_input_tokens: u64,
+    /// Extended thinking tokens, estimated from the length of the message's
+    /// `thinking` blocks (transcripts only report them within `output_tokens`)
+    #[serde(default)]
+    pub thinking_tokens: u64,
     /// Model used for this call
     pub model: String,
 }
@@ -43,7 +49,7 @@ pub struct TranscriptUsage {
 impl TranscriptUsage {
     /// Total input tokens including cache (for context window calculation)
     pub fn total_input(&self) -> u64 {
-        self.input_tokens + self.cache_read_input_tokens + self.cache_creation_input_tokens
+        context_tokens(self.input_tokens, self.cache_creation_input_tokens, self.cache_read_input_tokens)
     }
 
     /// Merge another usage into this one (for cumulative totals)
@@ -52,6 +58,7 @@ impl TranscriptUsage {
         self.output_tokens += other.output_tokens;
         self.cache_creation_input_tokens += other.cache_creation_input_tokens;
         self.cache_read_input_tokens += other.cache_read_input_tokens;
+        self.thinking_tokens += other.thinking_tokens;
         // Keep the most recent model
         if !other.model.is_empty() {
             self.model = other.model.clone();
@@ -70,6 +77,10 @@ pub struct SessionUsage {
     pub total_cache_read_tokens: u64,
     /// Total cache creation tokens across all turns
     pub total_cache_creation_tokens: u64,
+    /// Total extended thinking tokens across all turns (an estimate, see
+    /// [`TranscriptUsage::thinking_tokens`])
+    #[serde(default)]
+    pub total_thinking_tokens: u64,
     /// Model from most recent message
     pub model: String,
     /// Number of assistant messages processed
@@ -83,6 +94,7 @@ impl SessionUsage {
         self.total_output_tokens += usage.output_tokens;
         self.total_cache_read_tokens += usage.cache_read_input_tokens;
         self.total_cache_creation_tokens += usage.cache_creation_input_tokens;
+        self.total_thinking_tokens += usage.thinking_tokens;
         if !usage.model.is_empty() {
             self.model = usage.model.clone();
         }
@@ -90,9 +102,12 @@ impl SessionUsage {
     }
 
     /// Total tokens for context window percentage calculation.
-    /// Uses input + cache_read as that's what's in the context.
     pub fn context_tokens(&self) -> u64 {
-        self.total_input_tokens + self.total_cache_read_tokens + self.total_cache_creation_tokens
+        context_tokens(
+            self.total_input_tokens,
+            self.total_cache_creation_tokens,
+            self.total_cache_read_tokens,
+        )
     }
 }
 
@@ -214,6 +229,25 @@ struct TranscriptMessageEntry {
     usage: Option<TranscriptMessageUsage>,
 }
 
+/// Characters of thinking text per estimated token.
+const THINKING_CHARS_PER_TOKEN: usize = 4;
+
+impl TranscriptMessageEntry {
+    /// Rough token count of this entry's `thinking` blocks.
+    fn estimated_thinking_tokens(&self) -> u64 {
+        let Value::Array(blocks) = &self.content else {
+            return 0;
+        };
+        let chars: usize = blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(|t| t.as_str()) == Some("thinking"))
+            .filter_map(|block| block.get("thinking").and_then(|t| t.as_str()))
+            .map(|thinking| thinking.chars().count())
+            .sum();
+        chars.div_ceil(THINKING_CHARS_PER_TOKEN) as u64
+    }
+}
+
 /// Watches a Claude Code transcript file and emits content events in real-time.
 pub struct TranscriptWatcher {
     path: PathBuf,
@@ -507,6 +541,7 @@ impl TranscriptWatcher {
                 output_tokens: usage.output_tokens,
                 cache_creation_input_tokens: usage.cache_creation_input_tokens,
                 cache_read_input_tokens: usage.cache_read_input_tokens,
+                thinking_tokens: message.estimated_thinking_tokens(),
                 model: message.model.clone().unwrap_or_default(),
             };
             let _ = self.event_tx.send(TranscriptEvent::Usage {
@@ -793,12 +828,13 @@ pub fn compute_session_usage(transcript_path: &Path) -> Option<SessionUsage> {
         }
 
         if let Some(message) = entry.message {
-            if let Some(usage) = message.usage {
+            if let Some(ref usage) = message.usage {
                 let transcript_usage = TranscriptUsage {
                     input_tokens: usage.input_tokens,
                     output_tokens: usage.output_tokens,
                     cache_creation_input_tokens: usage.cache_creation_input_tokens,
                     cache_read_input_tokens: usage.cache_read_input_tokens,
+                    thinking_tokens: message.estimated_thinking_tokens(),
                     model: message.model.unwrap_or_default(),
                 };
                 session_usage.add_message(&transcript_usage);
@@ -821,6 +857,58 @@ pub fn compute_session_usage(transcript_path: &Path) -> Option<SessionUsage> {
     }
 }
 
+/// Usage per prompt turn, by reading an entire transcript file.
+///
+/// A turn starts at each user message with text (tool results don't count)
+/// and covers the assistant messages up to the next one. Usage repeated
+/// across the entries of one assistant message is counted once.
+///
+/// Fails only if the file can't be opened; unparseable lines are skipped.
+pub fn compute_turn_usage(transcript_path: &Path) -> Result<Vec<SessionUsage>> {
+    let file = File::open(transcript_path)?;
+    let mut turns: Vec<SessionUsage> = Vec::new();
+    let mut counted_ids: HashSet<String> = HashSet::new();
+
+    for line in BufReader::new(file).lines() {
+        let Ok(line) = line else { continue };
+        let Ok(entry) = serde_json::from_str::<TranscriptEntry>(&line) else {
+            continue;
+        };
+        let Some(message) = entry.message else { continue };
+
+        match entry.entry_type.as_str() {
+            "user" if !extract_text_content(&message.content).is_empty() => {
+                turns.push(SessionUsage::default());
+                counted_ids.clear();
+            }
+            "assistant" => {
+                let Some(turn) = turns.last_mut() else { continue };
+                // Each entry carries its own thinking blocks, even when it
+                // repeats the message's usage
+                turn.total_thinking_tokens += message.estimated_thinking_tokens();
+                let Some(usage) = message.usage else { continue };
+                if let Some(id) = message.id
+                    && !counted_ids.insert(id)
+                {
+                    continue;
+                }
+                turn.add_message(&TranscriptUsage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                    cache_creation_input_tokens: usage.cache_creation_input_tokens,
+                    cache_read_input_tokens: usage.cache_read_input_tokens,
+                    // Counted above
+                    thinking_tokens: 0,
+                    model: message.model.unwrap_or_default(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(turns)
+}
+
 /// Get the transcript file path for a Claude session.
 ///
 /// The path format is: `~/.claude/projects/<encoded-project-path>/<session-id>.jsonl`
@@ -1016,4 +1104,26 @@ mod tests {
         let chat_event = transcript_event_to_chat_event(session_id, event).unwrap();
         assert!(matches!(chat_event, ChatEvent::ToolCallStart { .. }));
     }
+
+    #[test]
+    fn test_turn_usage_estimates_thinking_from_blocks() {
+        let usage = serde_json::json!({"input_tokens": 10, "output_tokens": 500});
+        let lines = [
+            serde_json::json!({"type": "user", "message": {"content": "Explain the bug"}}),
+            serde_json::json!({"type": "assistant", "message": {"id": "m1", "usage": usage,
+                "content": [{"type": "thinking", "thinking": "x".repeat(398)}]}}),
+            serde_json::json!({"type": "assistant", "message": {"id": "m1", "usage": usage,
+                "content": [{"type": "text", "text": "It's an off-by-one."}]}}),
+        ];
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("transcript.jsonl");
+        let content: String = lines.iter().map(|line| format!("{line}\n")).collect();
+        std::fs::write(&path, content).unwrap();
+
+        let turns = compute_turn_usage(&path).unwrap();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].total_output_tokens, 500);
+        // 398 characters at four per token, rounded up
+        assert_eq!(turns[0].total_thinking_tokens, 100);
+    }
 }
@@ -350,7 +350,7 @@ impl TuiMenuParser {
 }
 
 /// Strip ANSI escape codes from text.
-fn strip_ansi_codes(text: &str) -> String {
+pub(crate) fn strip_ansi_codes(text: &str) -> String {
     static ANSI_RE: Lazy<Regex> = Lazy::new(|| {
         // Comprehensive ANSI escape sequence matching:
         // - CSI sequences: ESC [ ... letter (includes DEC private sequences with ?)
@@ -374,7 +374,7 @@ fn strip_ansi_codes(text: &str) -> String {
 
 /// Normalize literal Unicode escape sequences (e.g., "\u2191") to actual Unicode characters.
 /// Claude Code CLI sometimes outputs these as literal text in the terminal.
-fn normalize_unicode_escapes(text: &str) -> String {
+pub(crate) fn normalize_unicode_escapes(text: &str) -> String {
     static UNICODE_ESCAPE_RE: Lazy<Regex> = Lazy::new(|| {
         // Match literal \uXXXX patterns (not actual escape sequences, but the text representation)
         Regex::new(r"\\u([0-9a-fA-F]{4})").expect("Invalid unicode escape regex")
@@ -0,0 +1,85 @@
+//! Session export bundles.
+//!
+//! A bundle is one zip holding everything needed to share a session or attach
+//! it to a bug report: its interactions, the changes it made, cost analytics,
+//! and the terminal log.
+
+use clauset_core::{render_ansi, AnsiMode, ClausetError, InteractionStore};
+use clauset_types::{Interaction, ToolInvocation};
+use serde::Serialize;
+use std::io::{Cursor, Write};
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+/// One interaction per line, oldest first, with its tool invocations.
+pub const INTERACTIONS_ENTRY: &str = "interactions.jsonl";
+/// Every file change as a git-style patch, in interaction order.
+pub const PATCH_ENTRY: &str = "changes.patch";
+pub const ANALYTICS_ENTRY: &str = "analytics.json";
+/// Terminal output with ANSI escapes stripped.
+pub const TERMINAL_LOG_ENTRY: &str = "terminal.log";
+
+/// Context lines around each hunk in the patch.
+const PATCH_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("store error: {0}")]
+    Store(#[from] ClausetError),
+    #[error("serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Serialize)]
+struct ExportedInteraction<'a> {
+    #[serde(flatten)]
+    interaction: &'a Interaction,
+    tool_invocations: Vec<ToolInvocation>,
+}
+
+/// Build the bundle zip for a session in memory.
+///
+/// `terminal` is the raw terminal output, live or as last persisted; the
+/// log entry is empty otherwise.
+pub fn build_bundle(
+    store: &InteractionStore,
+    session_id: Uuid,
+    terminal: Option<&[u8]>,
+) -> Result<Vec<u8>, BundleError> {
+    let mut interactions = store.list_interactions(session_id, u32::MAX, 0)?;
+    interactions.reverse();
+
+    let mut jsonl = String::new();
+    let mut patch = String::new();
+    for interaction in &interactions {
+        let exported = ExportedInteraction {
+            interaction,
+            tool_invocations: store.list_tool_invocations(interaction.id)?,
+        };
+        jsonl.push_str(&serde_json::to_string(&exported)?);
+        jsonl.push('\n');
+        patch.push_str(&store.interaction_patch(interaction.id, PATCH_CONTEXT_LINES)?);
+    }
+
+    let analytics = serde_json::to_string_pretty(&store.get_session_analytics(session_id)?)?;
+    let terminal_log = terminal
+        .map(|data| render_ansi(data, AnsiMode::Strip))
+        .unwrap_or_default();
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    for (name, contents) in [
+        (INTERACTIONS_ENTRY, jsonl),
+        (PATCH_ENTRY, patch),
+        (ANALYTICS_ENTRY, analytics),
+        (TERMINAL_LOG_ENTRY, terminal_log),
+    ] {
+        zip.start_file(name, options)?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
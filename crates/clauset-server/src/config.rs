@@ -1,10 +1,14 @@
 //! Server configuration.
 
+use crate::interaction_processor::DEFAULT_MAX_PROMPT_LENGTH;
+use crate::webhook::WebhookConfig;
 use anyhow::Result;
-use serde::Deserialize;
-use std::path::PathBuf;
+use clauset_core::{segments_match, wildcard_match, ActionSource, ActivityKeywords, ChangeThreshold, EvictionPolicy, FtsQueryLimits, ModelRates, OutputPreview, OversizedChunkPolicy, PricingTable, PtyReadOptions, QuietWindow, ReadyDetection, ToolInputLimits};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Config {
     #[serde(default = "default_host")]
     pub host: String,
@@ -12,16 +16,283 @@ pub struct Config {
     pub port: u16,
     #[serde(default = "default_static_dir")]
     pub static_dir: PathBuf,
+    /// Refuse to start when `static_dir` has no built frontend, instead of
+    /// serving a placeholder page.
+    #[serde(default)]
+    pub require_static_dir: bool,
     #[serde(default = "default_claude_path")]
     pub claude_path: PathBuf,
     #[serde(default = "default_db_path")]
     pub db_path: PathBuf,
+    /// Claude's data directory (transcripts, history, commands, hooks). Unset
+    /// uses `~/.claude`.
+    #[serde(default)]
+    pub claude_dir: Option<PathBuf>,
     #[serde(default = "default_max_sessions")]
     pub max_concurrent_sessions: usize,
     #[serde(default = "default_model")]
     pub default_model: String,
     #[serde(default = "default_projects_root")]
     pub projects_root: PathBuf,
+    /// Which tool invocations are persisted to the interaction timeline.
+    #[serde(default = "default_tracked_tools")]
+    pub tracked_tools: TrackedTools,
+    /// How a busy session is detected as Ready again ("hook_preferred" or "terminal").
+    #[serde(default = "default_ready_detection")]
+    pub ready_detection: ReadyDetection,
+    /// Prompts longer than this (in characters) are stored truncated.
+    #[serde(default = "default_max_prompt_length")]
+    pub max_prompt_length: usize,
+    /// Delete prompt library entries older than this many days at startup.
+    /// Independent of interaction retention; unset keeps prompts forever.
+    #[serde(default)]
+    pub prompt_retention_days: Option<u64>,
+    /// Caps on full-text search query terms; the rest of a query is ignored.
+    #[serde(default)]
+    pub search_query_limits: FtsQueryLimits,
+    /// Size cap on stored tool inputs; larger inputs are saved truncated.
+    #[serde(default)]
+    pub tool_input_limits: ToolInputLimits,
+    /// Largest accepted request body, in bytes. Hook payloads carry full tool
+    /// inputs and outputs, so this is well above axum's 2 MB default.
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Files matching these globs are never snapshotted.
+    #[serde(default)]
+    pub snapshot_exclude: SnapshotExclude,
+    /// How tool output is shortened in chat tool calls and stored previews.
+    #[serde(default)]
+    pub tool_output_preview: OutputPreview,
+    /// Per-tool preview modes for stored tool output, overriding
+    /// `tool_output_preview` (by default `Bash` keeps the tail).
+    #[serde(default = "OutputPreview::tool_defaults")]
+    pub tool_output_preview_overrides: HashMap<String, OutputPreview>,
+    /// Per-million-token rates by model name, added to (or overriding) the
+    /// built-in Claude rates. Used when Claude doesn't report a cost.
+    #[serde(default)]
+    pub model_pricing: HashMap<String, ModelRates>,
+    /// How old terminal output is evicted ("byte_budget" or "screen_boundary").
+    #[serde(default)]
+    pub eviction_policy: EvictionPolicy,
+    /// Single terminal writes larger than the buffer ("keep" or "split").
+    #[serde(default)]
+    pub oversized_chunk_policy: OversizedChunkPolicy,
+    /// Spinner words that mark thinking/planning status lines, for
+    /// non-English Claude UIs. Each list replaces the English default.
+    #[serde(default)]
+    pub activity_keywords: ActivityKeywords,
+    /// Keep each session's last N activity changes in memory with timestamps,
+    /// for debugging activity detection. 0 (the default) disables recording.
+    #[serde(default)]
+    pub activity_history_size: usize,
+    /// Where recent actions come from ("hook_preferred" or "hooks").
+    /// "hook_preferred" falls back to actions parsed from terminal output for
+    /// sessions without hooks.
+    #[serde(default)]
+    pub action_source: ActionSource,
+    /// Seconds a hook-sourced action suppresses terminal-parsed actions.
+    #[serde(default = "default_hook_action_window_secs")]
+    pub hook_action_window_secs: u64,
+    /// Status-line cost/token deltas at or below these values don't count as
+    /// activity changes, so they aren't broadcast or persisted.
+    #[serde(default)]
+    pub change_threshold: ChangeThreshold,
+    /// PTY read size and optional coalescing of reads into larger chunks,
+    /// trading output latency against per-chunk overhead.
+    #[serde(default)]
+    pub pty_read: PtyReadOptions,
+    /// zstd level (1-22) for terminal buffers persisted for resume; 0 stores
+    /// them uncompressed.
+    #[serde(default = "default_buffer_compression_level")]
+    pub buffer_compression_level: i32,
+    /// Seconds between liveness heartbeats for terminal sessions, so clients
+    /// can tell an idle session from a hung one. Unset disables heartbeats.
+    #[serde(default)]
+    pub heartbeat_interval_secs: Option<u64>,
+    /// Compact the terminal buffers of sessions that have been idle for this
+    /// many seconds, dropping superseded screen redraws to save memory.
+    /// Unset disables background compaction.
+    #[serde(default)]
+    pub idle_compaction_secs: Option<u64>,
+    /// Force a busy session back to Ready when it has received no output or
+    /// activity for this many seconds after input (e.g. Claude errored before
+    /// hooks fired). Unset leaves such sessions "Thinking".
+    #[serde(default)]
+    pub idle_input_timeout_secs: Option<u64>,
+    /// Ignore the terminal's echo of typed input when detecting activity, so
+    /// text typed into the prompt doesn't look like Claude finishing.
+    #[serde(default)]
+    pub suppress_input_echo: bool,
+    /// Write each terminated session's scrollback to `<archive_dir>/<id>.log`.
+    #[serde(default)]
+    pub archive_dir: Option<PathBuf>,
+    /// Strip ANSI escape sequences from archived scrollback.
+    #[serde(default)]
+    pub archive_strip_ansi: bool,
+    /// Named interaction databases that API requests reading or maintaining
+    /// interactions can select with the `X-Clauset-Profile` header. Hook
+    /// events of sessions in a profile's `projects` are captured into its
+    /// database; all others go to the default one.
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
+    /// Daily local-time windows during which heavy background tasks (prompt
+    /// backfill) pause, resuming once the window ends.
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietWindow>,
+    /// Mirror received hook events (optionally only some event types) to an
+    /// external webhook, without delaying hook processing.
+    #[serde(default)]
+    pub hook_webhook: Option<WebhookConfig>,
+    /// Enable `POST /api/query` for ad-hoc read-only SQL over the interaction
+    /// database, authorized by a bearer token. Disabled when unset.
+    #[serde(default)]
+    pub query_api: Option<QueryApiConfig>,
+    /// Enable diagnostic endpoints under `/api/debug`, such as running the
+    /// terminal parsers over a posted snippet.
+    #[serde(default)]
+    pub debug_endpoints: bool,
+}
+
+/// Fields that `POST /api/admin/config/reload` applies to a running server.
+/// Any other changed field only takes effect after a restart.
+pub const RELOADABLE_FIELDS: &[&str] = &[
+    "projects_root",
+    "tracked_tools",
+    "max_prompt_length",
+    "snapshot_exclude",
+    "prompt_retention_days",
+    "quiet_hours",
+];
+
+/// Outcome of reloading the config file into a running server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigReload {
+    /// Changed fields now in effect.
+    pub applied: Vec<String>,
+    /// Changed fields that were ignored because they need a restart.
+    pub restart_required: Vec<String>,
+}
+
+/// Settings for the ad-hoc SQL query endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct QueryApiConfig {
+    /// Bearer token required in the `Authorization` header.
+    pub token: String,
+    /// Rows returned per query; further rows are dropped.
+    #[serde(default = "default_query_max_rows")]
+    pub max_rows: usize,
+    /// Milliseconds a query may run before it is interrupted.
+    #[serde(default = "default_query_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_query_max_rows() -> usize {
+    1000
+}
+
+fn default_query_timeout_ms() -> u64 {
+    5000
+}
+
+/// A named work context with its own interaction database.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ProfileConfig {
+    pub db_path: PathBuf,
+    /// Project directories whose sessions are captured into this profile
+    #[serde(default)]
+    pub projects: Vec<PathBuf>,
+}
+
+/// Allow/deny configuration for tools recorded in interactions.
+///
+/// In TOML:
+/// ```toml
+/// [tracked_tools]
+/// mode = "deny"
+/// tools = ["Read", "Glob"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "mode", content = "tools", rename_all = "snake_case")]
+pub enum TrackedTools {
+    /// Track every tool (default).
+    #[default]
+    All,
+    /// Track only the listed tools.
+    Allow(Vec<String>),
+    /// Track every tool except the listed ones.
+    Deny(Vec<String>),
+}
+
+impl TrackedTools {
+    /// Whether invocations of `tool_name` should be persisted.
+    pub fn is_tracked(&self, tool_name: &str) -> bool {
+        match self {
+            TrackedTools::All => true,
+            TrackedTools::Allow(tools) => tools.iter().any(|t| t == tool_name),
+            TrackedTools::Deny(tools) => !tools.iter().any(|t| t == tool_name),
+        }
+    }
+}
+
+/// Gitignore-style globs for files whose contents are never snapshotted.
+///
+/// A pattern without a `/` matches any path component (`node_modules/`,
+/// `*.min.js`); one containing a `/` is matched from the working directory
+/// (`src/generated/**`). A trailing `/` only matches directories, and a
+/// matching directory excludes everything beneath it. `*` and `?` match
+/// within a component, `**` across any number of components.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct SnapshotExclude(pub Vec<String>);
+
+impl Default for SnapshotExclude {
+    fn default() -> Self {
+        Self(
+            ["node_modules/", "target/", ".git/", ".venv/", "__pycache__/"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        )
+    }
+}
+
+impl SnapshotExclude {
+    /// Whether `path` is excluded. Relative patterns are matched against the
+    /// part of `path` under `cwd` when it lies there.
+    pub fn is_excluded(&self, path: &Path, cwd: Option<&Path>) -> bool {
+        let relative = cwd.and_then(|cwd| path.strip_prefix(cwd).ok()).unwrap_or(path);
+        let components: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        let components: Vec<&str> = components.iter().map(String::as_str).collect();
+
+        self.0.iter().any(|pattern| pattern_matches(pattern, &components))
+    }
+}
+
+fn pattern_matches(pattern: &str, components: &[&str]) -> bool {
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    if pattern.is_empty() {
+        return false;
+    }
+    // A directory pattern must match above the final (file) component
+    let candidates = if dir_only {
+        &components[..components.len().saturating_sub(1)]
+    } else {
+        components
+    };
+
+    if !pattern.contains('/') {
+        return candidates.iter().any(|name| wildcard_match(pattern, name));
+    }
+
+    let parts: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+    (1..=candidates.len()).any(|len| segments_match(&parts, &candidates[..len]))
 }
 
 fn default_projects_root() -> PathBuf {
@@ -30,6 +301,30 @@ fn default_projects_root() -> PathBuf {
         .join("Downloads/projects")
 }
 
+fn default_tracked_tools() -> TrackedTools {
+    TrackedTools::All
+}
+
+fn default_ready_detection() -> ReadyDetection {
+    ReadyDetection::HookPreferred
+}
+
+fn default_hook_action_window_secs() -> u64 {
+    clauset_core::DEFAULT_HOOK_ACTION_WINDOW.as_secs()
+}
+
+fn default_buffer_compression_level() -> i32 {
+    clauset_core::DEFAULT_BUFFER_COMPRESSION_LEVEL
+}
+
+fn default_max_prompt_length() -> usize {
+    DEFAULT_MAX_PROMPT_LENGTH
+}
+
+fn default_max_body_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
 fn default_host() -> String {
     "0.0.0.0".to_string()
 }
@@ -67,16 +362,123 @@ impl Default for Config {
             host: default_host(),
             port: default_port(),
             static_dir: default_static_dir(),
+            require_static_dir: false,
             claude_path: default_claude_path(),
             db_path: default_db_path(),
+            claude_dir: None,
             max_concurrent_sessions: default_max_sessions(),
             default_model: default_model(),
             projects_root: default_projects_root(),
+            tracked_tools: default_tracked_tools(),
+            ready_detection: default_ready_detection(),
+            max_prompt_length: default_max_prompt_length(),
+            prompt_retention_days: None,
+            search_query_limits: FtsQueryLimits::default(),
+            tool_input_limits: ToolInputLimits::default(),
+            max_body_bytes: default_max_body_bytes(),
+            snapshot_exclude: SnapshotExclude::default(),
+            tool_output_preview: OutputPreview::default(),
+            tool_output_preview_overrides: OutputPreview::tool_defaults(),
+            model_pricing: HashMap::new(),
+            eviction_policy: EvictionPolicy::default(),
+            oversized_chunk_policy: OversizedChunkPolicy::default(),
+            activity_keywords: ActivityKeywords::default(),
+            activity_history_size: 0,
+            action_source: ActionSource::default(),
+            hook_action_window_secs: default_hook_action_window_secs(),
+            change_threshold: ChangeThreshold::default(),
+            pty_read: PtyReadOptions::default(),
+            buffer_compression_level: default_buffer_compression_level(),
+            heartbeat_interval_secs: None,
+            idle_compaction_secs: None,
+            idle_input_timeout_secs: None,
+            suppress_input_echo: false,
+            archive_dir: None,
+            archive_strip_ansi: false,
+            profiles: HashMap::new(),
+            quiet_hours: Vec::new(),
+            hook_webhook: None,
+            query_api: None,
+            debug_endpoints: false,
         }
     }
 }
 
 impl Config {
+    /// Claude's data directory: `claude_dir`, or `~/.claude` when unset.
+    pub fn claude_data_dir(&self) -> PathBuf {
+        self.claude_dir
+            .clone()
+            .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".claude"))
+    }
+
+    /// The built-in pricing table with `model_pricing` entries applied.
+    pub fn pricing_table(&self) -> PricingTable {
+        let mut table = PricingTable::default();
+        for (model, rates) in &self.model_pricing {
+            table.set(model, *rates);
+        }
+        table
+    }
+
+    /// Names of the fields whose values differ between `self` and `other`.
+    pub fn changed_fields(&self, other: &Config) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        macro_rules! compare {
+            ($($field:ident),* $(,)?) => {
+                // Destructure so a new field can't be forgotten here
+                let Config { $($field: _),* } = self;
+                $(if self.$field != other.$field {
+                    changed.push(stringify!($field));
+                })*
+            };
+        }
+        compare!(
+            host,
+            port,
+            static_dir,
+            require_static_dir,
+            claude_path,
+            db_path,
+            claude_dir,
+            max_concurrent_sessions,
+            default_model,
+            projects_root,
+            tracked_tools,
+            ready_detection,
+            max_prompt_length,
+            prompt_retention_days,
+            search_query_limits,
+            tool_input_limits,
+            max_body_bytes,
+            snapshot_exclude,
+            tool_output_preview,
+            tool_output_preview_overrides,
+            model_pricing,
+            eviction_policy,
+            oversized_chunk_policy,
+            activity_keywords,
+            activity_history_size,
+            action_source,
+            hook_action_window_secs,
+            change_threshold,
+            pty_read,
+            buffer_compression_level,
+            heartbeat_interval_secs,
+            idle_compaction_secs,
+            idle_input_timeout_secs,
+            suppress_input_echo,
+            archive_dir,
+            archive_strip_ansi,
+            profiles,
+            quiet_hours,
+            hook_webhook,
+            query_api,
+            debug_endpoints,
+        );
+        changed
+    }
+
     /// Load config from a specific file path.
     pub fn load_from(path: &std::path::Path) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
@@ -84,11 +486,15 @@ impl Config {
         Ok(config)
     }
 
+    /// The default config file (config/default.toml), if it exists.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(PathBuf::from("config/default.toml")).filter(|path| path.exists())
+    }
+
     /// Load config from default location (config/default.toml) or fall back to defaults.
     pub fn load() -> Result<Self> {
         // Try to load from config file
-        let config_path = PathBuf::from("config/default.toml");
-        if config_path.exists() {
+        if let Some(config_path) = Self::default_path() {
             return Self::load_from(&config_path);
         }
 
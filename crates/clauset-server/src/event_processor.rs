@@ -8,8 +8,9 @@ use crate::state::AppState;
 use clauset_core::ProcessEvent;
 use clauset_types::TuiMenuEvent;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
-use tracing::{error, info, instrument, warn};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Spawns a background task that processes all session events.
 /// This ensures terminal output is buffered and activity is tracked
@@ -38,34 +39,103 @@ pub fn spawn_event_processor(state: Arc<AppState>) {
     });
 }
 
+/// Spawns a background task that periodically compacts the terminal buffers
+/// of sessions idle for at least `idle_for`. Busy sessions are skipped, so
+/// a session is never compacted mid-stream.
+pub fn spawn_idle_compaction(state: Arc<AppState>, idle_for: Duration) {
+    tokio::spawn(async move {
+        info!(target: "clauset::events", "Idle buffer compaction started ({}s threshold)", idle_for.as_secs());
+        let mut ticker = tokio::time::interval(idle_for.max(Duration::from_secs(1)));
+        loop {
+            ticker.tick().await;
+            for (session_id, compaction) in state.session_manager.buffers().compact_idle(idle_for).await {
+                debug!(
+                    target: "clauset::events",
+                    "Compacted idle buffer for session {}: {} -> {} bytes",
+                    session_id,
+                    compaction.before,
+                    compaction.after
+                );
+                if let Some(new_start_seq) = compaction.new_start_seq {
+                    let _ = state
+                        .session_manager
+                        .event_sender()
+                        .send(ProcessEvent::BufferReset { session_id, new_start_seq });
+                }
+            }
+        }
+    });
+}
+
+/// Spawns a background task that forces busy sessions back to Ready once
+/// they have gone `timeout` without any output after input.
+pub fn spawn_idle_input_timeout(state: Arc<AppState>, timeout: Duration) {
+    tokio::spawn(async move {
+        info!(target: "clauset::events", "Idle input timeout started ({}s)", timeout.as_secs());
+        let mut ticker = tokio::time::interval((timeout / 4).max(Duration::from_secs(1)));
+        loop {
+            ticker.tick().await;
+            for session_id in state.session_manager.expire_unanswered_input().await {
+                debug!(target: "clauset::events", "Session {} timed out waiting for output", session_id);
+            }
+        }
+    });
+}
+
+/// Spawns a background task that clears hook-driven sessions left busy at
+/// a quiet prompt after their Stop hook was lost.
+pub fn spawn_hook_stop_fallback(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(30));
+        loop {
+            ticker.tick().await;
+            for session_id in state.session_manager.expire_lost_hook_stops().await {
+                debug!(target: "clauset::events", "Session {} never got its Stop hook", session_id);
+            }
+        }
+    });
+}
+
 #[instrument(skip(state, event), fields(event_type = ?std::mem::discriminant(&event)))]
 async fn process_event(state: &AppState, event: ProcessEvent) {
     match event {
         ProcessEvent::TerminalOutput { session_id, ref data } => {
+            // The interaction this output belongs to, taken before a hook
+            // processed while it is being parsed can start the next one
+            let interaction_id = state.interaction_processor_for(session_id).current_interaction(session_id);
+
             // Store terminal output in buffer and get sequence number for reliable streaming
             let (append_result, activity, tui_menu) = state
                 .session_manager
                 .append_terminal_output(session_id, data)
                 .await;
 
-            // Broadcast sequenced output for reliable streaming protocol
+            // Broadcast sequenced output for reliable streaming protocol, one
+            // message per stored chunk so sequence numbers match the buffer.
             // WebSocket handlers will convert this to TerminalChunk messages
-            let _ = state.session_manager.event_sender().send(
-                ProcessEvent::SequencedTerminalOutput {
-                    session_id,
-                    seq: append_result.seq,
-                    data: data.clone(),
-                    timestamp: append_result.timestamp,
-                },
-            );
+            for (seq, piece) in (append_result.seq..).zip(append_result.pieces) {
+                let _ = state.session_manager.event_sender().send(
+                    ProcessEvent::SequencedTerminalOutput {
+                        session_id,
+                        seq,
+                        data: data[piece].to_vec(),
+                        timestamp: append_result.timestamp,
+                    },
+                );
+            }
+
+            if let Err(e) = state.session_manager.touch_activity(session_id) {
+                warn!(target: "clauset::events", "Failed to touch session {} activity: {}", session_id, e);
+            }
 
             // NOTE: Terminal output parsing disabled - too noisy (spinners, ANSI codes, status lines)
             // Claude's response is now read from transcript file on Stop hook instead
 
             if let Some(activity) = activity {
                 // Update interaction costs if they changed (handles late terminal output after Stop hook)
-                state.interaction_processor.update_costs_from_session(
+                state.interaction_processor_for(session_id).update_costs_from_session(
                     session_id,
+                    interaction_id,
                     activity.cost,
                     activity.input_tokens,
                     activity.output_tokens,
@@ -100,6 +170,8 @@ async fn process_event(state: &AppState, event: ProcessEvent) {
         }
         ProcessEvent::Exited { session_id, exit_code } => {
             info!(target: "clauset::session", "Session {} exited with code {:?}", session_id, exit_code);
+            // Archive the full scrollback before persistence compacts it
+            state.session_manager.archive_session_buffer(session_id).await;
             // Persist activity data before updating status
             state.session_manager.persist_session_activity(session_id).await;
             // Update session status to stopped
@@ -136,5 +208,9 @@ async fn process_event(state: &AppState, event: ProcessEvent) {
         ProcessEvent::ModeChange { .. } => {}
         // TUI menu events are handled by WebSocket handlers for native UI rendering
         ProcessEvent::TuiMenu(_) => {}
+        // Heartbeats are forwarded by WebSocket handlers
+        ProcessEvent::Heartbeat { .. } => {}
+        // Buffer resets are forwarded by WebSocket handlers
+        ProcessEvent::BufferReset { .. } => {}
     }
 }
@@ -1,6 +1,7 @@
 //! Global WebSocket for dashboard real-time updates.
 
 use crate::state::AppState;
+use crate::ws_protocol::WsProtocol;
 use anyhow::Result;
 use axum::extract::ws::{Message, WebSocket};
 use clauset_core::ProcessEvent;
@@ -10,11 +11,11 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 
 /// Handle global WebSocket connection for dashboard updates.
-pub async fn handle_global_websocket(socket: WebSocket, state: Arc<AppState>) -> Result<()> {
+pub async fn handle_global_websocket(socket: WebSocket, state: Arc<AppState>, protocol: WsProtocol) -> Result<()> {
     let (mut ws_tx, mut ws_rx) = socket.split();
 
     // Channel for recv_task to request sending messages (like pong responses)
-    let (pong_tx, mut pong_rx) = mpsc::channel::<String>(16);
+    let (pong_tx, mut pong_rx) = mpsc::channel::<WsServerMessage>(16);
 
     // Subscribe to all session events
     let mut event_rx = state.session_manager.subscribe();
@@ -38,8 +39,8 @@ pub async fn handle_global_websocket(socket: WebSocket, state: Arc<AppState>) ->
                     current_step: session.current_step.clone(),
                     recent_actions: session.recent_actions.clone(),
                 };
-                if let Ok(json) = serde_json::to_string(&msg) {
-                    if ws_tx.send(Message::Text(json.into())).await.is_err() {
+                if let Ok(frame) = protocol.encode(&msg) {
+                    if ws_tx.send(frame).await.is_err() {
                         tracing::debug!(target: "clauset::ws", "Failed to send initial activity state");
                         return Ok(());
                     }
@@ -53,8 +54,12 @@ pub async fn handle_global_websocket(socket: WebSocket, state: Arc<AppState>) ->
         loop {
             tokio::select! {
                 // Handle pong responses from recv_task
-                Some(pong_json) = pong_rx.recv() => {
-                    if ws_tx.send(Message::Text(pong_json.into())).await.is_err() {
+                Some(pong) = pong_rx.recv() => {
+                    let frame = match protocol.encode(&pong) {
+                        Ok(frame) => frame,
+                        Err(_) => continue,
+                    };
+                    if ws_tx.send(frame).await.is_err() {
                         tracing::debug!(target: "clauset::ws", "Failed to send pong, client disconnected");
                         break;
                     }
@@ -129,11 +134,11 @@ pub async fn handle_global_websocket(socket: WebSocket, state: Arc<AppState>) ->
                     };
 
                     if let Some(msg) = msg {
-                        let json = match serde_json::to_string(&msg) {
-                            Ok(j) => j,
+                        let frame = match protocol.encode(&msg) {
+                            Ok(frame) => frame,
                             Err(_) => continue,
                         };
-                        if ws_tx.send(Message::Text(json.into())).await.is_err() {
+                        if ws_tx.send(frame).await.is_err() {
                             tracing::debug!(target: "clauset::ws", "Global WebSocket client disconnected");
                             break;
                         }
@@ -156,11 +161,8 @@ pub async fn handle_global_websocket(socket: WebSocket, state: Arc<AppState>) ->
                     if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
                         if json.get("type").and_then(|v| v.as_str()) == Some("ping") {
                             // Respond with pong
-                            let pong = serde_json::json!({
-                                "type": "pong",
-                                "timestamp": json.get("timestamp").cloned().unwrap_or(serde_json::Value::Null)
-                            });
-                            if pong_tx.send(pong.to_string()).await.is_err() {
+                            let timestamp = json.get("timestamp").and_then(|v| v.as_u64()).unwrap_or(0);
+                            if pong_tx.send(WsServerMessage::Pong { timestamp }).await.is_err() {
                                 tracing::debug!(target: "clauset::ws", "Failed to queue pong response");
                                 break;
                             }
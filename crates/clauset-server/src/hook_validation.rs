@@ -0,0 +1,395 @@
+//! Validation and normalization of raw hook payloads.
+//!
+//! Hook scripts post loosely-structured JSON. Before anything reaches the
+//! processors, the payload is normalized (camelCase aliases, `null`s, blank
+//! identifiers), checked against per-event rules, and given defaults for the
+//! optional fields each event relies on. Problems are reported per field so a
+//! buggy hook script gets an actionable 400 instead of a generic parse error.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use clauset_types::HookEventPayload;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+/// Expected JSON type of a payload field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    String,
+    Bool,
+    Object,
+    /// Any JSON value (tool inputs and responses).
+    Any,
+}
+
+impl FieldKind {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            FieldKind::String => value.is_string(),
+            FieldKind::Bool => value.is_boolean(),
+            FieldKind::Object => value.is_object(),
+            FieldKind::Any => true,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            FieldKind::String => "a string",
+            FieldKind::Bool => "a boolean",
+            FieldKind::Object => "an object",
+            FieldKind::Any => "any value",
+        }
+    }
+}
+
+/// Known payload fields and their types, after alias normalization.
+const FIELD_KINDS: &[(&str, FieldKind)] = &[
+    ("clauset_session_id", FieldKind::String),
+    ("session_id", FieldKind::String),
+    ("hook_event_name", FieldKind::String),
+    ("cwd", FieldKind::String),
+    ("transcript_path", FieldKind::String),
+    ("permission_mode", FieldKind::String),
+    ("tool_name", FieldKind::String),
+    ("tool_input", FieldKind::Any),
+    ("tool_response", FieldKind::Any),
+    ("tool_use_id", FieldKind::String),
+    ("prompt", FieldKind::String),
+    ("source", FieldKind::String),
+    ("reason", FieldKind::String),
+    ("stop_hook_active", FieldKind::Bool),
+    ("message", FieldKind::String),
+    ("notification_type", FieldKind::String),
+    ("context_window", FieldKind::Object),
+    ("model", FieldKind::Object),
+    ("workspace", FieldKind::Object),
+    ("output_style", FieldKind::Object),
+    ("version", FieldKind::String),
+    ("agent_id", FieldKind::String),
+    ("agent_type", FieldKind::String),
+    ("error", FieldKind::String),
+    ("error_type", FieldKind::String),
+    ("is_timeout", FieldKind::Bool),
+    ("is_interrupt", FieldKind::Bool),
+    ("trigger", FieldKind::String),
+];
+
+/// camelCase spellings accepted for snake_case fields.
+const FIELD_ALIASES: &[(&str, &str)] = &[
+    ("hookEventName", "hook_event_name"),
+    ("transcriptPath", "transcript_path"),
+    ("permissionMode", "permission_mode"),
+    ("contextWindow", "context_window"),
+    ("outputStyle", "output_style"),
+    ("agentId", "agent_id"),
+    ("agentType", "agent_type"),
+    ("isInterrupt", "is_interrupt"),
+];
+
+/// Optional identifier fields where a blank string means "not provided".
+const BLANK_AS_MISSING: &[&str] = &["cwd", "transcript_path", "permission_mode", "tool_use_id"];
+
+/// Fields every hook event must carry.
+const COMMON_REQUIRED: &[&str] = &["clauset_session_id", "session_id", "hook_event_name"];
+
+/// Produces the default value of an optional field.
+type FieldDefault = (&'static str, fn() -> Value);
+
+/// Per-event rules: fields that must be present and defaults for optional ones.
+struct EventRule {
+    event: &'static str,
+    required: &'static [&'static str],
+    defaults: &'static [FieldDefault],
+}
+
+const EVENT_RULES: &[EventRule] = &[
+    EventRule {
+        event: "SessionStart",
+        required: &[],
+        defaults: &[("source", || Value::from("startup"))],
+    },
+    EventRule {
+        event: "SessionEnd",
+        required: &[],
+        defaults: &[("reason", || Value::from("unknown"))],
+    },
+    EventRule {
+        event: "UserPromptSubmit",
+        required: &[],
+        defaults: &[("prompt", || Value::from(""))],
+    },
+    EventRule {
+        event: "PreToolUse",
+        required: &["tool_name"],
+        defaults: &[("tool_input", || Value::Object(Map::new()))],
+    },
+    EventRule {
+        event: "PostToolUse",
+        required: &["tool_name"],
+        defaults: &[("tool_input", || Value::Object(Map::new()))],
+    },
+    EventRule {
+        event: "PostToolUseFailure",
+        required: &["tool_name"],
+        defaults: &[
+            ("tool_input", || Value::Object(Map::new())),
+            ("is_timeout", || Value::Bool(false)),
+            ("is_interrupt", || Value::Bool(false)),
+        ],
+    },
+    EventRule {
+        event: "Stop",
+        required: &[],
+        defaults: &[("stop_hook_active", || Value::Bool(false))],
+    },
+    EventRule {
+        event: "SubagentStart",
+        required: &[],
+        defaults: &[("agent_type", || Value::from("unknown"))],
+    },
+    EventRule {
+        event: "SubagentStop",
+        required: &[],
+        defaults: &[("stop_hook_active", || Value::Bool(false))],
+    },
+    EventRule {
+        event: "Notification",
+        required: &[],
+        defaults: &[],
+    },
+    EventRule {
+        event: "PreCompact",
+        required: &[],
+        defaults: &[("trigger", || Value::from("unknown"))],
+    },
+    EventRule {
+        event: "PermissionRequest",
+        required: &["tool_name"],
+        defaults: &[("tool_input", || Value::Object(Map::new()))],
+    },
+];
+
+/// A problem with a single payload field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// A hook payload that failed validation, reported as a 400.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookValidationError {
+    pub error: &'static str,
+    pub fields: Vec<FieldError>,
+}
+
+impl HookValidationError {
+    fn new(fields: Vec<FieldError>) -> Self {
+        Self {
+            error: "invalid hook payload",
+            fields,
+        }
+    }
+}
+
+impl std::fmt::Display for HookValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect();
+        write!(f, "{} ({})", self.error, fields.join("; "))
+    }
+}
+
+impl IntoResponse for HookValidationError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, Json(self)).into_response()
+    }
+}
+
+/// Normalize and validate a raw hook payload.
+pub fn normalize_hook_payload(raw: Value) -> Result<HookEventPayload, HookValidationError> {
+    let Value::Object(mut fields) = raw else {
+        return Err(HookValidationError::new(vec![FieldError::new(
+            "$",
+            "expected a JSON object",
+        )]));
+    };
+
+    normalize_fields(&mut fields);
+
+    let mut errors = Vec::new();
+    for name in COMMON_REQUIRED {
+        if !fields.contains_key(*name) {
+            errors.push(FieldError::new(name, "missing required field"));
+        }
+    }
+    for (name, kind) in FIELD_KINDS {
+        if let Some(value) = fields.get(*name)
+            && !kind.accepts(value)
+        {
+            errors.push(FieldError::new(name, format!("expected {}", kind.name())));
+        }
+    }
+    if let Some(Value::String(id)) = fields.get("clauset_session_id")
+        && Uuid::parse_str(id).is_err()
+    {
+        errors.push(FieldError::new("clauset_session_id", "expected a UUID"));
+    }
+
+    if let Some(Value::String(event)) = fields.get("hook_event_name") {
+        match EVENT_RULES.iter().find(|rule| rule.event == event) {
+            Some(rule) => {
+                for name in rule.required {
+                    match fields.get(*name) {
+                        None => errors.push(FieldError::new(
+                            name,
+                            format!("missing required field for {event}"),
+                        )),
+                        Some(Value::String(s)) if s.trim().is_empty() => {
+                            errors.push(FieldError::new(name, "must not be empty"))
+                        }
+                        _ => {}
+                    }
+                }
+                for (name, default) in rule.defaults {
+                    fields.entry(*name).or_insert_with(default);
+                }
+            }
+            None => errors.push(FieldError::new(
+                "hook_event_name",
+                format!("unknown hook event type '{event}'"),
+            )),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(HookValidationError::new(errors));
+    }
+
+    // Nested objects (context_window, model, ...) are checked by their serde types
+    serde_json::from_value(Value::Object(fields))
+        .map_err(|e| HookValidationError::new(vec![FieldError::new("$", e.to_string())]))
+}
+
+/// Identify a payload from a user's custom hook: its event name is not one
+/// Clauset knows, but it names a valid Clauset session. Returns the session
+/// and event name; anything else goes through [`normalize_hook_payload`].
+pub fn custom_hook_event(raw: &Value) -> Option<(Uuid, &str)> {
+    let fields = raw.as_object()?;
+    let event = ["hook_event_name", "hookEventName"]
+        .iter()
+        .find_map(|name| fields.get(*name)?.as_str())
+        .filter(|event| !event.trim().is_empty())?;
+    if EVENT_RULES.iter().any(|rule| rule.event == event) {
+        return None;
+    }
+    let session_id = Uuid::parse_str(fields.get("clauset_session_id")?.as_str()?).ok()?;
+    Some((session_id, event))
+}
+
+/// Canonicalize aliases and drop values that mean "not provided".
+fn normalize_fields(fields: &mut Map<String, Value>) {
+    for (alias, name) in FIELD_ALIASES {
+        if let Some(value) = fields.remove(*alias) {
+            fields.entry(*name).or_insert(value);
+        }
+    }
+    fields.retain(|name, value| !is_unset(name, value));
+}
+
+fn is_unset(name: &str, value: &Value) -> bool {
+    value.is_null()
+        || (BLANK_AS_MISSING.contains(&name) && value.as_str().is_some_and(|s| s.trim().is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn base(event: &str) -> Value {
+        json!({
+            "clauset_session_id": Uuid::new_v4().to_string(),
+            "session_id": "claude-session",
+            "hook_event_name": event,
+        })
+    }
+
+    fn field_names(err: &HookValidationError) -> Vec<&str> {
+        err.fields.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn test_reports_every_missing_and_mistyped_field() {
+        let err = normalize_hook_payload(json!({
+            "clauset_session_id": "not-a-uuid",
+            "hookEventName": "PreToolUse",
+            "stop_hook_active": "yes",
+        }))
+        .unwrap_err();
+        assert_eq!(
+            field_names(&err),
+            vec!["session_id", "stop_hook_active", "clauset_session_id", "tool_name"]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_event_and_non_object() {
+        let err = normalize_hook_payload(base("Teleport")).unwrap_err();
+        assert_eq!(field_names(&err), vec!["hook_event_name"]);
+        assert!(err.fields[0].message.contains("Teleport"));
+
+        let err = normalize_hook_payload(json!([1, 2])).unwrap_err();
+        assert_eq!(field_names(&err), vec!["$"]);
+    }
+
+    #[test]
+    fn test_normalizes_aliases_nulls_and_defaults() {
+        let mut raw = base("PostToolUseFailure");
+        raw["tool_name"] = json!("Bash");
+        raw["cwd"] = json!("  ");
+        raw["error"] = Value::Null;
+        raw["isInterrupt"] = json!(true);
+
+        let payload = normalize_hook_payload(raw).unwrap();
+        assert_eq!(payload.cwd, None);
+        assert_eq!(payload.error, None);
+        assert_eq!(payload.is_interrupt, Some(true));
+        assert_eq!(payload.is_timeout, Some(false));
+        assert_eq!(payload.tool_input, Some(json!({})));
+    }
+
+    #[test]
+    fn test_custom_hook_event() {
+        let raw = base("DeployFinished");
+        let session_id = Uuid::parse_str(raw["clauset_session_id"].as_str().unwrap()).unwrap();
+        assert_eq!(custom_hook_event(&raw), Some((session_id, "DeployFinished")));
+
+        let mut aliased = raw.clone();
+        let name = aliased.as_object_mut().unwrap().remove("hook_event_name").unwrap();
+        aliased["hookEventName"] = name;
+        assert_eq!(custom_hook_event(&aliased), Some((session_id, "DeployFinished")));
+
+        assert_eq!(custom_hook_event(&base("Stop")), None);
+        assert_eq!(custom_hook_event(&base(" ")), None);
+        let mut bad_session = raw;
+        bad_session["clauset_session_id"] = json!("not-a-uuid");
+        assert_eq!(custom_hook_event(&bad_session), None);
+    }
+}
@@ -3,24 +3,35 @@
 //! This module captures Claude interactions (user prompts + tool invocations)
 //! and persists them to the database for timeline, search, and analytics features.
 
-use clauset_core::InteractionStore;
-use clauset_types::{FileSnapshot, HookEvent, Interaction, SnapshotType, ToolInvocation};
+use crate::config::{SnapshotExclude, TrackedTools};
+use clauset_core::{extract_file_refs, InteractionStore, OutputPreview};
+use clauset_types::{
+    FileSnapshot, HookEvent, Interaction, InteractionContext, SnapshotType, TodoItem, ToolInvocation,
+};
 use dashmap::DashMap;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 /// Maximum file size for snapshots (1 MB).
 const MAX_SNAPSHOT_SIZE: u64 = 1_048_576;
 
+/// Default maximum stored prompt length, in characters.
+pub const DEFAULT_MAX_PROMPT_LENGTH: usize = 100_000;
+
 /// Snapshot of session costs at interaction start.
 #[derive(Debug, Clone, Copy)]
 struct CostSnapshot {
+    /// Interaction the snapshot was taken for, so late costs land on it
+    interaction_id: Uuid,
     cost_usd: f64,
     input_tokens: u64,
     output_tokens: u64,
+    thinking_tokens: u64,
 }
 
 /// Captures interactions and tool invocations from hook events.
@@ -32,6 +43,21 @@ pub struct InteractionProcessor {
     pending_tool_invocations: DashMap<String, (Uuid, Uuid, Option<String>)>,
     /// Maps session_id -> cost snapshot at interaction start (for computing deltas)
     starting_costs: DashMap<Uuid, CostSnapshot>,
+    /// Maps session_id -> starting snapshot of the interaction before the
+    /// current one, so its late costs can still land on it
+    previous_costs: DashMap<Uuid, CostSnapshot>,
+    /// Which tools are persisted (others are skipped entirely)
+    tracked_tools: RwLock<TrackedTools>,
+    /// Prompts longer than this many characters are stored truncated
+    max_prompt_length: AtomicUsize,
+    /// Files whose contents are never snapshotted
+    snapshot_exclude: RwLock<SnapshotExclude>,
+    /// How stored tool output previews are shortened
+    output_preview: OutputPreview,
+    /// Per-tool overrides of `output_preview`
+    tool_output_previews: HashMap<String, OutputPreview>,
+    /// Maps session_id -> todo list from the latest TodoWrite invocation
+    todos: DashMap<Uuid, Vec<TodoItem>>,
 }
 
 impl InteractionProcessor {
@@ -41,9 +67,61 @@ impl InteractionProcessor {
             active_interactions: DashMap::new(),
             pending_tool_invocations: DashMap::new(),
             starting_costs: DashMap::new(),
+            previous_costs: DashMap::new(),
+            tracked_tools: RwLock::new(TrackedTools::All),
+            max_prompt_length: AtomicUsize::new(DEFAULT_MAX_PROMPT_LENGTH),
+            snapshot_exclude: RwLock::new(SnapshotExclude::default()),
+            output_preview: OutputPreview::default(),
+            tool_output_previews: OutputPreview::tool_defaults(),
+            todos: DashMap::new(),
         }
     }
 
+    /// Restrict which tool invocations are persisted.
+    pub fn with_tracked_tools(self, tracked_tools: TrackedTools) -> Self {
+        self.set_tracked_tools(tracked_tools);
+        self
+    }
+
+    /// Skip file snapshots for paths matching these globs.
+    pub fn with_snapshot_exclude(self, snapshot_exclude: SnapshotExclude) -> Self {
+        self.set_snapshot_exclude(snapshot_exclude);
+        self
+    }
+
+    /// Cap the number of prompt characters stored and indexed per interaction.
+    pub fn with_max_prompt_length(self, max_prompt_length: usize) -> Self {
+        self.set_max_prompt_length(max_prompt_length);
+        self
+    }
+
+    /// Replace the tracked tools of a running processor (config reload).
+    pub fn set_tracked_tools(&self, tracked_tools: TrackedTools) {
+        *self.tracked_tools.write().unwrap() = tracked_tools;
+    }
+
+    /// Replace the snapshot exclusions of a running processor (config reload).
+    pub fn set_snapshot_exclude(&self, snapshot_exclude: SnapshotExclude) {
+        *self.snapshot_exclude.write().unwrap() = snapshot_exclude;
+    }
+
+    /// Replace the prompt length cap of a running processor (config reload).
+    pub fn set_max_prompt_length(&self, max_prompt_length: usize) {
+        self.max_prompt_length.store(max_prompt_length, Ordering::Relaxed);
+    }
+
+    /// Set how stored tool output previews are shortened.
+    pub fn with_output_preview(mut self, output_preview: OutputPreview) -> Self {
+        self.output_preview = output_preview;
+        self
+    }
+
+    /// Shorten stored output previews of these tools with their own mode.
+    pub fn with_tool_output_previews(mut self, tool_output_previews: HashMap<String, OutputPreview>) -> Self {
+        self.tool_output_previews = tool_output_previews;
+        self
+    }
+
     /// Process a hook event and update the interaction tracking state.
     /// Requires current session costs for proper delta calculation.
     pub async fn process_event(
@@ -52,9 +130,18 @@ impl InteractionProcessor {
         cost_usd: f64,
         input_tokens: u64,
         output_tokens: u64,
+        thinking_tokens: u64,
+        context_percent: u8,
     ) {
         if let Err(e) = self
-            .process_event_inner(event, cost_usd, input_tokens, output_tokens)
+            .process_event_inner(
+                event,
+                cost_usd,
+                input_tokens,
+                output_tokens,
+                thinking_tokens,
+                context_percent,
+            )
             .await
         {
             error!(target: "clauset::interactions", "Failed to process hook event: {}", e);
@@ -67,13 +154,22 @@ impl InteractionProcessor {
         cost_usd: f64,
         input_tokens: u64,
         output_tokens: u64,
+        thinking_tokens: u64,
+        context_percent: u8,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         match event {
             HookEvent::UserPromptSubmit {
                 session_id, prompt, ..
             } => {
-                self.handle_user_prompt(*session_id, prompt, cost_usd, input_tokens, output_tokens)
-                    .await?;
+                self.handle_user_prompt(
+                    *session_id,
+                    prompt,
+                    cost_usd,
+                    input_tokens,
+                    output_tokens,
+                    thinking_tokens,
+                )
+                .await?;
             }
 
             HookEvent::PreToolUse {
@@ -84,6 +180,10 @@ impl InteractionProcessor {
                 cwd,
                 ..
             } => {
+                // The todo list is tracked even when TodoWrite isn't persisted
+                if tool_name == "TodoWrite" {
+                    self.record_todos(*session_id, tool_input);
+                }
                 self.handle_pre_tool_use(
                     *session_id,
                     tool_name,
@@ -115,18 +215,43 @@ impl InteractionProcessor {
             HookEvent::Stop {
                 session_id,
                 stop_hook_active,
+                transcript_path,
                 ..
             } => {
                 if !stop_hook_active {
-                    self.handle_stop(*session_id, cost_usd, input_tokens, output_tokens)
-                        .await?;
+                    self.handle_stop(
+                        *session_id,
+                        cost_usd,
+                        input_tokens,
+                        output_tokens,
+                        thinking_tokens,
+                        context_percent,
+                    )
+                    .await?;
+
+                    // The finished turn is now in the transcript
+                    if let Some(path) = transcript_path
+                        && let Err(e) = self
+                            .store
+                            .backfill_summaries_from_transcript(*session_id, Path::new(path))
+                    {
+                        warn!(target: "clauset::interactions",
+                            "Failed to backfill summaries for session {}: {}", session_id, e);
+                    }
                 }
             }
 
             HookEvent::SessionEnd { session_id, .. } => {
                 // Complete any active interaction when session ends
-                self.handle_stop(*session_id, cost_usd, input_tokens, output_tokens)
-                    .await?;
+                self.handle_stop(
+                    *session_id,
+                    cost_usd,
+                    input_tokens,
+                    output_tokens,
+                    thinking_tokens,
+                    context_percent,
+                )
+                .await?;
             }
 
             _ => {
@@ -145,6 +270,7 @@ impl InteractionProcessor {
         cost_usd: f64,
         input_tokens: u64,
         output_tokens: u64,
+        thinking_tokens: u64,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Complete any existing interaction first (with costs from stored snapshot)
         if let Some((_, existing_id)) = self.active_interactions.remove(&session_id) {
@@ -157,23 +283,50 @@ impl InteractionProcessor {
             }
         }
 
-        // Store starting costs for delta calculation when interaction completes
-        self.starting_costs.insert(session_id, CostSnapshot {
-            cost_usd,
-            input_tokens,
-            output_tokens,
-        });
-
         // Get next sequence number
         let seq_num = self.store.next_sequence_number(session_id)?;
 
         // Create new interaction
-        let interaction = Interaction::new(session_id, seq_num, prompt.to_string());
+        let mut interaction = Interaction::new(session_id, seq_num, prompt.to_string());
+        let max_prompt_length = self.max_prompt_length.load(Ordering::Relaxed);
+        if let Some((cut, _)) = prompt.char_indices().nth(max_prompt_length) {
+            warn!(target: "clauset::interactions",
+                "Truncating {}-byte prompt for session {} to {} characters",
+                prompt.len(), session_id, max_prompt_length);
+            interaction.user_prompt.truncate(cut);
+            interaction.prompt_truncated = true;
+        }
         let interaction_id = interaction.id;
 
+        // Store starting costs for delta calculation when interaction completes,
+        // keeping the previous turn's so its late costs can still land on it
+        let previous = self.starting_costs.insert(session_id, CostSnapshot {
+            interaction_id,
+            cost_usd,
+            input_tokens,
+            output_tokens,
+            thinking_tokens,
+        });
+        match previous {
+            Some(previous) => {
+                self.previous_costs.insert(session_id, previous);
+            }
+            None => {
+                self.previous_costs.remove(&session_id);
+            }
+        }
+
         self.store.insert_interaction(&interaction)?;
         self.active_interactions.insert(session_id, interaction_id);
 
+        let file_refs = extract_file_refs(prompt);
+        if !file_refs.is_empty()
+            && let Err(e) = self.store.insert_prompt_file_refs(interaction_id, &file_refs)
+        {
+            warn!(target: "clauset::interactions",
+                "Failed to record file references for interaction {}: {}", interaction_id, e);
+        }
+
         info!(target: "clauset::interactions",
             "Started interaction {} (seq {}) for session {} (start: ${:.4}, {}K/{}K)",
             interaction_id, seq_num, session_id, cost_usd, input_tokens/1000, output_tokens/1000);
@@ -190,6 +343,12 @@ impl InteractionProcessor {
         tool_use_id: &str,
         cwd: Option<String>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.tracked_tools.read().unwrap().is_tracked(tool_name) {
+            debug!(target: "clauset::interactions",
+                "Skipping untracked tool {} for session {}", tool_name, session_id);
+            return Ok(());
+        }
+
         // Get or create active interaction
         let interaction_id = match self.active_interactions.get(&session_id) {
             Some(id) => *id,
@@ -235,7 +394,11 @@ impl InteractionProcessor {
         // Store invocation with file_path
         let mut inv = invocation;
         inv.file_path = file_path.clone();
-        self.store.insert_tool_invocation(&inv)?;
+        if !self.store.insert_tool_invocation(&inv)? {
+            debug!(target: "clauset::interactions",
+                "Ignoring duplicate PreToolUse for tool_use_id {}", tool_use_id);
+            return Ok(());
+        }
 
         // Store pending invocation for PostToolUse
         self.pending_tool_invocations.insert(
@@ -251,6 +414,7 @@ impl InteractionProcessor {
                     interaction_id,
                     Some(invocation_id),
                     &abs_path,
+                    cwd.as_deref(),
                     SnapshotType::Before,
                 )
                 .await;
@@ -273,6 +437,10 @@ impl InteractionProcessor {
         tool_response: &Value,
         tool_use_id: &str,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.tracked_tools.read().unwrap().is_tracked(tool_name) {
+            return Ok(());
+        }
+
         // Look up pending invocation
         let (invocation_id, interaction_id, cwd) =
             match self.pending_tool_invocations.remove(tool_use_id) {
@@ -313,11 +481,17 @@ impl InteractionProcessor {
         };
 
         // Extract preview from response
-        let preview = self.extract_response_preview(tool_response);
+        let preview = self.extract_response_preview(tool_name, tool_response);
+
+        let exit_code = if tool_name == "Bash" {
+            ToolInvocation::exit_code_from_response(tool_response)
+        } else {
+            None
+        };
 
         // Complete the tool invocation
         self.store
-            .complete_tool_invocation(invocation_id, preview, is_error, error_message)?;
+            .complete_tool_invocation(invocation_id, preview, is_error, error_message, exit_code)?;
 
         // Capture after snapshot for Write/Edit tools
         if matches!(tool_name, "Write" | "Edit") {
@@ -329,6 +503,7 @@ impl InteractionProcessor {
                         interaction_id,
                         Some(invocation_id),
                         &abs_path,
+                        cwd.as_deref(),
                         SnapshotType::After,
                     )
                     .await;
@@ -343,26 +518,30 @@ impl InteractionProcessor {
         Ok(())
     }
 
-    /// Handle Stop: Complete the current interaction with cost deltas.
+    /// Handle Stop: Complete the current interaction with cost deltas and
+    /// snapshot the session's context usage.
     async fn handle_stop(
         &self,
         session_id: Uuid,
         cost_usd: f64,
         input_tokens: u64,
         output_tokens: u64,
+        thinking_tokens: u64,
+        context_percent: u8,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         if let Some((_, interaction_id)) = self.active_interactions.remove(&session_id) {
             // Calculate deltas from stored starting costs (don't remove - keep for late updates)
-            let (cost_delta, input_delta, output_delta) =
+            let (cost_delta, input_delta, output_delta, thinking_delta) =
                 if let Some(snapshot) = self.starting_costs.get(&session_id) {
                     (
                         (cost_usd - snapshot.cost_usd).max(0.0),
                         input_tokens.saturating_sub(snapshot.input_tokens),
                         output_tokens.saturating_sub(snapshot.output_tokens),
+                        thinking_tokens.saturating_sub(snapshot.thinking_tokens),
                     )
                 } else {
                     // No starting snapshot - use current values as total
-                    (cost_usd, input_tokens, output_tokens)
+                    (cost_usd, input_tokens, output_tokens, thinking_tokens)
                 };
 
             self.store.complete_interaction_with_costs(
@@ -370,7 +549,10 @@ impl InteractionProcessor {
                 cost_delta,
                 input_delta,
                 output_delta,
+                thinking_delta,
             )?;
+            self.store
+                .set_interaction_context_percent(interaction_id, context_percent)?;
             info!(target: "clauset::interactions",
                 "Completed interaction {} for session {} (delta: ${:.4}, {}K/{}K)",
                 interaction_id, session_id, cost_delta, input_delta/1000, output_delta/1000);
@@ -404,8 +586,15 @@ impl InteractionProcessor {
         interaction_id: Uuid,
         tool_invocation_id: Option<Uuid>,
         file_path: &Path,
+        cwd: Option<&str>,
         snapshot_type: SnapshotType,
     ) {
+        if self.snapshot_exclude.read().unwrap().is_excluded(file_path, cwd.map(Path::new)) {
+            debug!(target: "clauset::interactions",
+                "Skipping snapshot for excluded file {:?}", file_path);
+            return;
+        }
+
         // Check if file exists and is readable
         let metadata = match tokio::fs::metadata(file_path).await {
             Ok(m) => m,
@@ -463,23 +652,26 @@ impl InteractionProcessor {
         }
     }
 
-    /// Extract a preview from the tool response.
-    fn extract_response_preview(&self, tool_response: &Value) -> Option<String> {
+    /// Extract a preview from the tool response, shortened with the tool's
+    /// preview mode.
+    fn extract_response_preview(&self, tool_name: &str, tool_response: &Value) -> Option<String> {
+        let preview = self.tool_output_previews.get(tool_name).copied().unwrap_or(self.output_preview);
+
         // Try common response fields
         if let Some(s) = tool_response.as_str() {
-            return Some(truncate(s, 500));
+            return Some(preview.apply(s));
         }
 
         if let Some(content) = tool_response.get("content").and_then(|v| v.as_str()) {
-            return Some(truncate(content, 500));
+            return Some(preview.apply(content));
         }
 
         if let Some(output) = tool_response.get("output").and_then(|v| v.as_str()) {
-            return Some(truncate(output, 500));
+            return Some(preview.apply(output));
         }
 
         if let Some(result) = tool_response.get("result").and_then(|v| v.as_str()) {
-            return Some(truncate(result, 500));
+            return Some(preview.apply(result));
         }
 
         // For arrays or objects, just note the type
@@ -499,48 +691,180 @@ impl InteractionProcessor {
         None
     }
 
+    /// Replace a session's todo list with the one in a TodoWrite input.
+    fn record_todos(&self, session_id: Uuid, tool_input: &Value) {
+        match TodoItem::list_from_tool_input(tool_input) {
+            Some(todos) => {
+                debug!(target: "clauset::interactions",
+                    "Session {} todo list updated ({} items)", session_id, todos.len());
+                self.todos.insert(session_id, todos);
+            }
+            None => warn!(target: "clauset::interactions",
+                "Ignoring malformed TodoWrite input for session {}", session_id),
+        }
+    }
+
+    /// Current todo list for a session, falling back to the last recorded
+    /// TodoWrite invocation (e.g. after a restart). Empty if there is none.
+    pub fn todos(&self, session_id: Uuid) -> Result<Vec<TodoItem>, clauset_core::ClausetError> {
+        if let Some(todos) = self.todos.get(&session_id) {
+            return Ok(todos.clone());
+        }
+        Ok(self
+            .store
+            .latest_tool_input(session_id, "TodoWrite")?
+            .and_then(|input| TodoItem::list_from_tool_input(&input))
+            .unwrap_or_default())
+    }
+
+    /// Re-run a session's recorded hook events into `into` with this
+    /// processor's settings, reproducing its interactions and tool invocations.
+    /// File snapshots are read from disk as it is now.
+    ///
+    /// Returns the number of events replayed.
+    pub async fn replay_hook_events(
+        &self,
+        session_id: Uuid,
+        into: Arc<InteractionStore>,
+    ) -> Result<u32, clauset_core::ClausetError> {
+        let events = self.store.list_hook_events(session_id)?;
+        let replayer = InteractionProcessor::new(into)
+            .with_tracked_tools(self.tracked_tools.read().unwrap().clone())
+            .with_max_prompt_length(self.max_prompt_length.load(Ordering::Relaxed))
+            .with_snapshot_exclude(self.snapshot_exclude.read().unwrap().clone());
+
+        let mut replayed = 0;
+        for recorded in events {
+            let event = match HookEvent::try_from(recorded.payload) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!(target: "clauset::interactions",
+                        "Skipping unparseable hook event #{} for session {}: {}",
+                        recorded.sequence, session_id, e);
+                    continue;
+                }
+            };
+            let usage = recorded.usage;
+            replayer
+                .process_event(
+                    &event,
+                    usage.cost_usd,
+                    usage.input_tokens,
+                    usage.output_tokens,
+                    usage.thinking_tokens,
+                    usage.context_percent,
+                )
+                .await;
+            replayed += 1;
+        }
+        info!(target: "clauset::interactions",
+            "Replayed {} hook events for session {}", replayed, session_id);
+        Ok(replayed)
+    }
+
+    /// Record the session settings the session's active interaction started under.
+    pub fn record_context(&self, session_id: Uuid, context: &InteractionContext) {
+        let Some(interaction_id) = self.active_interactions.get(&session_id).map(|id| *id) else {
+            return;
+        };
+        if let Err(e) = self.store.set_interaction_context(interaction_id, context) {
+            warn!(target: "clauset::interactions",
+                "Failed to record context for interaction {}: {}", interaction_id, e);
+        }
+    }
+
+    /// Mark the session's active interaction as interrupted by the user, so a
+    /// later Stop or prompt doesn't complete it.
+    pub fn interrupt(&self, session_id: Uuid) -> Result<u32, clauset_core::ClausetError> {
+        self.active_interactions.remove(&session_id);
+        self.store.interrupt_active_interactions(session_id)
+    }
+
     /// Get a reference to the underlying store.
     pub fn store(&self) -> &Arc<InteractionStore> {
         &self.store
     }
 
+    /// The interaction that terminal output for a session currently belongs
+    /// to: the active one, or the most recently completed one.
+    pub fn current_interaction(&self, session_id: Uuid) -> Option<Uuid> {
+        self.active_interactions
+            .get(&session_id)
+            .map(|id| *id)
+            .or_else(|| self.starting_costs.get(&session_id).map(|s| s.interaction_id))
+    }
+
     /// Update interaction costs when session costs change (from terminal parsing).
     /// This handles the case where terminal output with final costs arrives after
     /// the Stop hook has already fired.
+    ///
+    /// `interaction_id` is the interaction the costs belong to (see
+    /// [`Self::current_interaction`]); None means the latest one. Costs for
+    /// the active interaction are captured on Stop instead.
     pub fn update_costs_from_session(
         &self,
         session_id: Uuid,
+        interaction_id: Option<Uuid>,
         cost_usd: f64,
         input_tokens: u64,
         output_tokens: u64,
     ) {
-        // Only update if there's no active interaction (i.e., the interaction just completed)
-        if self.active_interactions.contains_key(&session_id) {
-            // Interaction is still active, costs will be captured on Stop
+        let Some(current) = self.starting_costs.get(&session_id).map(|s| *s) else {
+            return;
+        };
+        let snapshot = match interaction_id {
+            None => current,
+            Some(id) if id == current.interaction_id => current,
+            Some(id) => match self.previous_costs.get(&session_id).map(|s| *s) {
+                Some(previous) if previous.interaction_id == id => previous,
+                _ => return,
+            },
+        };
+        let active = self.active_interactions.get(&session_id).map(|id| *id);
+        if active == Some(snapshot.interaction_id) {
             return;
         }
 
-        // Check if we have a starting snapshot - if so, the interaction was recently completed
-        // and we can update its costs with the new values (don't remove - next prompt will replace)
-        if let Some(snapshot) = self.starting_costs.get(&session_id) {
-            let cost_delta = (cost_usd - snapshot.cost_usd).max(0.0);
-            let input_delta = input_tokens.saturating_sub(snapshot.input_tokens);
-            let output_delta = output_tokens.saturating_sub(snapshot.output_tokens);
-
-            if cost_delta > 0.0 || input_delta > 0 || output_delta > 0 {
-                if let Err(e) = self.store.update_latest_interaction_costs(
-                    session_id,
-                    cost_delta,
-                    input_delta,
-                    output_delta,
-                ) {
-                    warn!(target: "clauset::interactions",
-                        "Failed to update interaction costs from terminal: {}", e);
-                } else {
-                    debug!(target: "clauset::interactions",
-                        "Updated interaction costs for session {} from terminal (delta: ${:.4}, {}K/{}K)",
-                        session_id, cost_delta, input_delta/1000, output_delta/1000);
-                }
+        self.apply_late_costs(session_id, &snapshot, cost_usd, input_tokens, output_tokens);
+
+        // Costs up to here were the previous turn's, so the current turn
+        // starts counting after them
+        if snapshot.interaction_id != current.interaction_id
+            && let Some(mut baseline) = self.starting_costs.get_mut(&session_id)
+        {
+            baseline.cost_usd = baseline.cost_usd.max(cost_usd);
+            baseline.input_tokens = baseline.input_tokens.max(input_tokens);
+            baseline.output_tokens = baseline.output_tokens.max(output_tokens);
+        }
+    }
+
+    /// Set an interaction's cost deltas from session totals and its starting snapshot.
+    fn apply_late_costs(
+        &self,
+        session_id: Uuid,
+        snapshot: &CostSnapshot,
+        cost_usd: f64,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) {
+        let cost_delta = (cost_usd - snapshot.cost_usd).max(0.0);
+        let input_delta = input_tokens.saturating_sub(snapshot.input_tokens);
+        let output_delta = output_tokens.saturating_sub(snapshot.output_tokens);
+
+        if cost_delta > 0.0 || input_delta > 0 || output_delta > 0 {
+            if let Err(e) = self.store.update_interaction_costs(
+                session_id,
+                Some(snapshot.interaction_id),
+                cost_delta,
+                input_delta,
+                output_delta,
+            ) {
+                warn!(target: "clauset::interactions",
+                    "Failed to update interaction costs from terminal: {}", e);
+            } else {
+                debug!(target: "clauset::interactions",
+                    "Updated interaction {} costs for session {} from terminal (delta: ${:.4}, {}K/{}K)",
+                    snapshot.interaction_id, session_id, cost_delta, input_delta/1000, output_delta/1000);
             }
         }
     }
@@ -558,12 +882,3 @@ impl InteractionProcessor {
         self.store.cleanup_old_data(retention_days)
     }
 }
-
-/// Truncate a string to a maximum length.
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
-    }
-}
@@ -3,11 +3,18 @@
 //! This library provides the HTTP routes, WebSocket handlers, and application state
 //! for the Clauset dashboard server. It's separated from main.rs to enable integration testing.
 
+pub mod bundle;
 pub mod config;
 pub mod event_processor;
 pub mod global_ws;
+pub mod hook_validation;
 pub mod interaction_processor;
 pub mod logging;
+pub mod preflight;
 pub mod routes;
 pub mod state;
+pub mod tasks;
+pub mod webhook;
 pub mod websocket;
+pub mod ws_protocol;
+pub mod ws_queue;
@@ -1,23 +1,24 @@
 //! Clauset server - HTTP/WebSocket server for Claude Code session management.
 
 use anyhow::Result;
-use clauset_server::{config, event_processor, global_ws, logging, routes, state};
+use clauset_server::{config, event_processor, global_ws, logging, routes, state, tasks, ws_protocol};
 use axum::{
     extract::{
         ws::{WebSocket, WebSocketUpgrade},
         State,
     },
-    response::Response,
-    routing::{delete, get, post, put},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use clap::Parser;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::{
     cors::{Any, CorsLayer},
-    services::ServeDir,
     trace::TraceLayer,
 };
 
@@ -66,16 +67,25 @@ struct Cli {
 use config::Config;
 use state::AppState;
 
+/// Interactions still active this long after starting are treated as
+/// orphaned by a previous run when the server starts.
+const STALE_INTERACTION_GRACE: Duration = Duration::from_secs(5 * 60);
+
 /// Handler for global events WebSocket upgrade.
 async fn global_events_ws(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_global_events(socket, state))
+    let (ws, protocol) = match ws_protocol::negotiate_upgrade(ws, &headers) {
+        Ok(negotiated) => negotiated,
+        Err(rejection) => return rejection.into_response(),
+    };
+    ws.on_upgrade(move |socket| handle_global_events(socket, state, protocol))
 }
 
-async fn handle_global_events(socket: WebSocket, state: Arc<AppState>) {
-    if let Err(e) = global_ws::handle_global_websocket(socket, state).await {
+async fn handle_global_events(socket: WebSocket, state: Arc<AppState>, protocol: ws_protocol::WsProtocol) {
+    if let Err(e) = global_ws::handle_global_websocket(socket, state, protocol).await {
         tracing::error!(target: "clauset::ws", "Global WebSocket error: {}", e);
     }
 }
@@ -97,9 +107,10 @@ async fn main() -> Result<()> {
     logging::init(&log_config);
 
     // Load configuration
-    let mut config = match &cli.config {
+    let config_path = cli.config.clone().or_else(Config::default_path);
+    let mut config = match &config_path {
         Some(path) => Config::load_from(path)?,
-        None => Config::load()?,
+        None => Config::default(),
     };
 
     // Apply CLI overrides
@@ -110,15 +121,43 @@ async fn main() -> Result<()> {
     tracing::info!(target: "clauset::startup", "Loaded configuration (port: {})", config.port);
 
     // Initialize application state
-    let state = Arc::new(AppState::new(config.clone())?);
+    let mut state = AppState::new(config.clone())?;
+    if let Some(path) = config_path {
+        state = state.with_config_path(path);
+    }
+    let state = Arc::new(state);
     tracing::info!(target: "clauset::startup", "Initialized application state");
 
     // Start background event processor for continuous terminal buffering
     event_processor::spawn_event_processor(state.clone());
     tracing::info!(target: "clauset::startup", "Started background event processor");
 
-    // Start prompt indexer backfill if needed (runs async, doesn't block startup)
-    spawn_prompt_backfill(state.clone());
+    if let Some(secs) = config.idle_compaction_secs {
+        event_processor::spawn_idle_compaction(state.clone(), Duration::from_secs(secs));
+    }
+
+    if let Some(secs) = config.idle_input_timeout_secs {
+        event_processor::spawn_idle_input_timeout(state.clone(), Duration::from_secs(secs));
+    }
+
+    if config.ready_detection == clauset_core::ReadyDetection::HookPreferred {
+        event_processor::spawn_hook_stop_fallback(state.clone());
+    }
+
+    // Decide on the backfill before pruning, so a fully pruned library isn't
+    // mistaken for a fresh one (runs async, doesn't block startup)
+    spawn_prompt_backfill(state.clone(), config.prompt_retention_days);
+
+    if let Some(days) = config.prompt_retention_days {
+        state.prune_prompt_library(days);
+    }
+
+    // Interactions a crash left active will never get their Stop hook
+    match state.interaction_processor.store().recover_stale_active(STALE_INTERACTION_GRACE) {
+        Ok(0) => {}
+        Ok(count) => tracing::warn!(target: "clauset::startup", "Recovered {} interactions left active by a previous run", count),
+        Err(e) => tracing::warn!(target: "clauset::startup", "Failed to recover stale interactions: {}", e),
+    }
 
     // Build router
     let api_routes = Router::new()
@@ -127,44 +166,146 @@ async fn main() -> Result<()> {
         .route("/sessions", post(routes::sessions::create))
         .route("/sessions/{id}", get(routes::sessions::get))
         .route("/sessions/{id}", delete(routes::sessions::terminate))
+        .route("/sessions/{id}", patch(routes::sessions::update))
         .route("/sessions/{id}/delete", delete(routes::sessions::delete))
         .route("/sessions/{id}/name", put(routes::sessions::rename))
+        .route("/sessions/{id}/pin", put(routes::sessions::pin))
+        .route(
+            "/sessions/{id}/tags",
+            get(routes::sessions::list_tags)
+                .post(routes::sessions::add_tag)
+                .delete(routes::sessions::remove_tag),
+        )
+        .route(
+            "/sessions/{id}/metadata",
+            get(routes::sessions::get_metadata)
+                .put(routes::sessions::set_metadata)
+                .delete(routes::sessions::delete_metadata),
+        )
         .route("/sessions/{id}/start", post(routes::sessions::start))
         .route("/sessions/{id}/resume", post(routes::sessions::resume))
         .route("/sessions/{id}/input", post(routes::sessions::send_input))
+        .route("/sessions/{id}/menu-select", post(routes::sessions::menu_select))
+        .route("/sessions/{id}/buffer", get(routes::sessions::download_buffer))
+        .route("/sessions/{id}/todos", get(routes::sessions::get_todos))
+        .route("/sessions/{id}/plan", get(routes::sessions::get_plan))
+        .route("/sessions/{id}/mode-history", get(routes::sessions::get_mode_history))
+        .route("/sessions/{id}/transcript.txt", get(routes::sessions::get_transcript_text))
+        .route("/sessions/{id}/bundle", get(routes::sessions::download_bundle))
+        .route("/sessions/{id}/custom-events", get(routes::sessions::list_custom_events))
         // Claude sessions from ~/.claude
         .route("/claude-sessions", get(routes::sessions::list_claude_sessions))
         .route("/claude-sessions/{id}/transcript", get(routes::sessions::get_claude_transcript))
         .route("/sessions/import", post(routes::sessions::import_session))
+        .route("/sessions/empty", get(routes::sessions::list_empty))
+        .route("/sessions/empty/cleanup", post(routes::sessions::cleanup_empty))
         // Interaction timeline
         .route(
             "/sessions/{id}/interactions",
             get(routes::interactions::list_session_interactions),
         )
+        .route(
+            "/sessions/{id}/truncate",
+            post(routes::interactions::truncate_session_history),
+        )
         .route(
             "/sessions/{id}/files-changed",
             get(routes::interactions::get_session_files_changed),
         )
+        .route(
+            "/sessions/{id}/authorship",
+            get(routes::interactions::get_file_authorship),
+        )
+        .route(
+            "/sessions/{id}/cost-timeline",
+            get(routes::interactions::get_session_cost_timeline),
+        )
+        .route(
+            "/sessions/{id}/velocity",
+            get(routes::interactions::get_session_velocity),
+        )
+        .route(
+            "/sessions/{id}/languages",
+            get(routes::interactions::get_session_languages),
+        )
+        .route(
+            "/sessions/{id}/repro.sh",
+            get(routes::interactions::get_session_repro_script),
+        )
+        .route(
+            "/interactions/failed",
+            get(routes::interactions::list_failed_interactions),
+        )
+        .route(
+            "/interactions/referencing",
+            get(routes::interactions::list_interactions_referencing_file),
+        )
+        .route(
+            "/interactions/compare",
+            get(routes::interactions::compare_interactions),
+        )
         .route(
             "/interactions/{id}",
             get(routes::interactions::get_interaction),
         )
+        .route(
+            "/interactions/{id}/patch",
+            get(routes::interactions::get_interaction_patch),
+        )
+        .route(
+            "/interactions/{id}/export",
+            get(routes::interactions::export_interaction),
+        )
+        .route(
+            "/interactions/{id}/timeline",
+            get(routes::interactions::get_turn_timeline),
+        )
+        .route("/files", get(routes::interactions::list_touched_files))
         // Diff computation
         .route("/diff", get(routes::interactions::get_diff))
+        .route("/diff/cross-session", get(routes::interactions::get_cross_session_diff))
         // Cross-session search
         .route("/search", get(routes::interactions::search))
+        .route("/search/stream", get(routes::interactions::search_stream))
         // Cost analytics
         .route("/analytics", get(routes::interactions::get_analytics))
+        .route("/analytics/compare", get(routes::interactions::compare_analytics))
+        .route(
+            "/analytics/active-sessions",
+            get(routes::interactions::get_daily_active_sessions),
+        )
         .route(
             "/analytics/expensive",
             get(routes::interactions::get_expensive_interactions),
         )
+        .route(
+            "/analytics/tools/reliability",
+            get(routes::interactions::get_tool_reliability),
+        )
+        .route(
+            "/analytics/tools/histogram",
+            get(routes::interactions::get_tool_histogram),
+        )
+        .route(
+            "/analytics/outcomes",
+            get(routes::interactions::get_outcome_breakdown),
+        )
+        .route(
+            "/analytics/timeseries",
+            get(routes::interactions::get_cost_timeseries),
+        )
         .route(
             "/analytics/storage",
             get(routes::interactions::get_storage_stats),
         )
+        .route(
+            "/analytics/storage/sessions",
+            get(routes::interactions::get_storage_by_session),
+        )
         // Prompt Library
         .route("/prompts", get(routes::prompts::list_prompts))
+        .route("/prompts/export-commands", post(routes::prompts::export_commands))
+        .route("/prompts/phrases", get(routes::prompts::list_phrases))
         .route("/prompts/{id}", get(routes::prompts::get_prompt))
         // Command discovery
         .route("/commands", get(routes::commands::list_commands))
@@ -172,16 +313,30 @@ async fn main() -> Result<()> {
         .route("/history", get(routes::history::list))
         .route("/projects", get(routes::projects::list).post(routes::projects::create))
         .route("/hooks", post(routes::hooks::receive))
-        .route("/health", get(routes::health));
+        // Background task management
+        .route("/admin/tasks", get(routes::admin::list_tasks))
+        .route("/admin/tasks/{id}/cancel", post(routes::admin::cancel_task))
+        .route("/admin/processes", get(routes::admin::list_processes))
+        .route("/admin/reindex-fts", post(routes::admin::reindex_fts))
+        .route("/admin/integrity", get(routes::admin::check_integrity))
+        .route("/admin/integrity/repair", post(routes::admin::repair_integrity))
+        .route("/admin/backup", post(routes::admin::backup_database))
+        .route("/admin/config/reload", post(routes::admin::reload_config))
+        // Diagnostics (enabled by debug_endpoints)
+        .route("/debug/parse", post(routes::debug::parse))
+        // Ad-hoc read-only SQL, when enabled
+        .route("/query", post(routes::interactions::run_query))
+        .route("/health", get(routes::health))
+        .route("/preflight", get(routes::preflight));
 
     let ws_routes = Router::new()
         .route("/sessions/{id}", get(routes::ws::upgrade))
         .route("/events", get(global_events_ws));
 
     let app = Router::new()
-        .nest("/api", api_routes)
-        .nest("/ws", ws_routes)
-        .fallback_service(ServeDir::new(&config.static_dir))
+        .nest("/api", routes::limit_body_size(api_routes, config.max_body_bytes))
+        .nest("/ws", ws_routes);
+    let app = routes::serve_static(app, &config.static_dir, config.require_static_dir)?
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
@@ -189,44 +344,40 @@ async fn main() -> Result<()> {
                 .allow_headers(Any),
         )
         .layer(TraceLayer::new_for_http())
-        .with_state(state);
+        .with_state(state.clone());
 
     // Start server
     let addr: SocketAddr = format!("{}:{}", config.host, config.port).parse()?;
     tracing::info!(target: "clauset::startup", "Starting server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    let flushed = state.chat_processor.flush_all().await;
+    tracing::info!(target: "clauset::startup", "Shutting down, flushed {} in-progress chat messages", flushed);
 
     Ok(())
 }
 
-/// Spawn prompt backfill task if needed.
-/// Runs asynchronously and doesn't block server startup.
-fn spawn_prompt_backfill(state: Arc<AppState>) {
-    tokio::spawn(async move {
-        use clauset_core::PromptIndexer;
-
-        let store = state.interaction_processor.store().clone();
-        let indexer = PromptIndexer::new(store);
-
-        if indexer.needs_backfill() {
-            tracing::info!(target: "clauset::startup", "Starting prompt library backfill...");
-            match indexer.backfill().await {
-                Ok(stats) => {
-                    tracing::info!(
-                        target: "clauset::startup",
-                        "Prompt backfill complete: {} prompts indexed from {} sessions",
-                        stats.prompts_indexed,
-                        stats.sessions_scanned
-                    );
-                }
-                Err(e) => {
-                    tracing::warn!(target: "clauset::startup", "Prompt backfill failed: {}", e);
-                }
-            }
-        } else {
-            tracing::debug!(target: "clauset::startup", "Prompt library already populated, skipping backfill");
-        }
-    });
+/// Spawn prompt backfill as a registered background task if the library is
+/// empty, skipping prompts older than `retention_days`.
+fn spawn_prompt_backfill(state: Arc<AppState>, retention_days: Option<u64>) {
+    use clauset_core::PromptIndexer;
+
+    let store = state.interaction_processor.store().clone();
+    let mut indexer = PromptIndexer::with_claude_dir(store, state.config().claude_data_dir()).with_quiet_hours(state.quiet_hours.clone());
+    if let Some(days) = retention_days {
+        indexer = indexer.with_retain_since(clauset_server::state::prompt_retention_cutoff(days));
+    }
+
+    if indexer.needs_backfill() {
+        tracing::info!(target: "clauset::startup", "Starting prompt library backfill...");
+        tasks::spawn_prompt_backfill(state.tasks.clone(), indexer);
+    } else {
+        tracing::debug!(target: "clauset::startup", "Prompt library already populated, skipping backfill");
+    }
 }
@@ -0,0 +1,193 @@
+//! Pre-flight checks of the Claude Code environment.
+//!
+//! Sessions fail in confusing ways when `claude` can't be found, `~/.claude`
+//! is missing, or the Clauset hooks aren't installed. These checks run before
+//! anything is spawned and report a remediation step for each failure.
+
+use crate::config::Config;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+}
+
+/// Result of one pre-flight check.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// How to fix a failed check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl PreflightCheck {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            remediation: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+}
+
+/// Full pre-flight report.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    /// Whether every check passed
+    pub ok: bool,
+    pub checks: Vec<PreflightCheck>,
+}
+
+/// Paths the checks inspect.
+#[derive(Debug, Clone)]
+pub struct PreflightEnv {
+    /// Configured `claude` binary; PATH is searched when it doesn't exist
+    pub claude_path: PathBuf,
+    /// Claude Code config directory (normally `~/.claude`)
+    pub claude_dir: PathBuf,
+    pub static_dir: PathBuf,
+    /// URL hooks post back to
+    pub hook_url: String,
+}
+
+impl PreflightEnv {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            claude_path: config.claude_path.clone(),
+            claude_dir: config.claude_data_dir(),
+            static_dir: config.static_dir.clone(),
+            hook_url: format!("http://localhost:{}/api/hooks", config.port),
+        }
+    }
+}
+
+/// Hook script installed by `scripts/setup-hooks.sh`.
+const HOOK_SCRIPT: &str = "hooks/clauset-hook.sh";
+
+/// Run every check. `probe_version` runs the binary and returns its version.
+pub fn run_checks(
+    env: &PreflightEnv,
+    probe_version: impl Fn(&Path) -> Result<String, String>,
+) -> PreflightReport {
+    let checks = vec![
+        check_claude_binary(env, probe_version),
+        check_claude_dir(env),
+        check_hooks(env),
+        check_static_dir(env),
+    ];
+    PreflightReport {
+        ok: checks.iter().all(|c| c.status == CheckStatus::Pass),
+        checks,
+    }
+}
+
+/// Run `<binary> --version` and return the first line of its output.
+pub fn claude_version(binary: &Path) -> Result<String, String> {
+    let output = std::process::Command::new(binary)
+        .arg("--version")
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+fn check_claude_binary(
+    env: &PreflightEnv,
+    probe_version: impl Fn(&Path) -> Result<String, String>,
+) -> PreflightCheck {
+    const NAME: &str = "claude_binary";
+    let Some(binary) = find_binary(&env.claude_path) else {
+        return PreflightCheck::fail(
+            NAME,
+            format!("{} not found and `claude` is not on PATH", env.claude_path.display()),
+            "Install Claude Code (npm install -g @anthropic-ai/claude-code) or set claude_path in the config",
+        );
+    };
+    match probe_version(&binary) {
+        Ok(version) => PreflightCheck::pass(NAME, format!("{} ({version})", binary.display())),
+        Err(e) => PreflightCheck::fail(
+            NAME,
+            format!("{} --version failed: {e}", binary.display()),
+            "Reinstall Claude Code and check that `claude --version` works",
+        ),
+    }
+}
+
+/// The configured binary if it exists, otherwise `claude` from PATH.
+fn find_binary(configured: &Path) -> Option<PathBuf> {
+    if configured.is_file() {
+        return Some(configured.to_path_buf());
+    }
+    let name = configured.file_name()?;
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+fn check_claude_dir(env: &PreflightEnv) -> PreflightCheck {
+    const NAME: &str = "claude_dir";
+    if env.claude_dir.is_dir() {
+        PreflightCheck::pass(NAME, env.claude_dir.display().to_string())
+    } else {
+        PreflightCheck::fail(
+            NAME,
+            format!("{} does not exist", env.claude_dir.display()),
+            "Run `claude` once to sign in and create its config directory",
+        )
+    }
+}
+
+fn check_hooks(env: &PreflightEnv) -> PreflightCheck {
+    const NAME: &str = "hooks";
+    const REMEDIATION: &str = "Run scripts/setup-hooks.sh to install the Clauset hooks";
+
+    let script = env.claude_dir.join(HOOK_SCRIPT);
+    if !script.is_file() {
+        return PreflightCheck::fail(NAME, format!("{} is missing", script.display()), REMEDIATION);
+    }
+    let settings = env.claude_dir.join("settings.json");
+    let registered = std::fs::read_to_string(&settings)
+        .map(|content| content.contains("clauset-hook.sh"))
+        .unwrap_or(false);
+    if !registered {
+        return PreflightCheck::fail(
+            NAME,
+            format!("hook script is not registered in {}", settings.display()),
+            REMEDIATION,
+        );
+    }
+    PreflightCheck::pass(NAME, format!("installed, posting to {}", env.hook_url))
+}
+
+fn check_static_dir(env: &PreflightEnv) -> PreflightCheck {
+    const NAME: &str = "static_dir";
+    if env.static_dir.join("index.html").is_file() {
+        PreflightCheck::pass(NAME, env.static_dir.display().to_string())
+    } else {
+        PreflightCheck::fail(
+            NAME,
+            format!("{} has no index.html", env.static_dir.display()),
+            "Build the frontend (cd frontend && npm run build) or set static_dir in the config",
+        )
+    }
+}
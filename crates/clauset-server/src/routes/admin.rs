@@ -0,0 +1,138 @@
+//! Administrative routes for background task management, database
+//! maintenance and config reloads.
+
+use crate::config::ConfigReload;
+use crate::state::AppState;
+use crate::tasks::TaskInfo;
+use clauset_core::IntegrityReport;
+use crate::routes::{profile_store, reject_profile};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+/// Response for listing background tasks.
+#[derive(Serialize)]
+pub struct TaskListResponse {
+    pub tasks: Vec<TaskInfo>,
+}
+
+/// GET /api/admin/tasks - List background tasks with progress and status.
+pub async fn list_tasks(State(state): State<Arc<AppState>>) -> Json<TaskListResponse> {
+    Json(TaskListResponse {
+        tasks: state.tasks.list(),
+    })
+}
+
+/// POST /api/admin/tasks/{id}/cancel - Request cancellation of a background task.
+pub async fn cancel_task(
+    State(state): State<Arc<AppState>>,
+    Path(task_id): Path<Uuid>,
+) -> Result<Json<TaskInfo>, (StatusCode, String)> {
+    state
+        .tasks
+        .cancel(task_id)
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "Task not found".to_string()))
+}
+
+/// A running session and the OS process behind it.
+#[derive(Serialize)]
+pub struct SessionProcess {
+    pub session_id: Uuid,
+    pub pid: u32,
+}
+
+/// GET /api/admin/processes - Running sessions with their process IDs, for
+/// attaching a debugger or checking resource usage from the OS.
+pub async fn list_processes(State(state): State<Arc<AppState>>) -> Json<Vec<SessionProcess>> {
+    Json(
+        state
+            .session_manager
+            .sessions_with_pids()
+            .await
+            .into_iter()
+            .map(|(session_id, pid)| SessionProcess { session_id, pid })
+            .collect(),
+    )
+}
+
+/// POST /api/admin/reindex-fts - Rebuild the search indexes from the interaction tables.
+pub async fn reindex_fts(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    profile_store(&state, &headers)?
+        .rebuild_all_fts()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/admin/integrity - Count orphaned interactions, tool invocations and snapshots.
+pub async fn check_integrity(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<IntegrityReport>, (StatusCode, String)> {
+    profile_store(&state, &headers)?
+        .check_integrity()
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// POST /api/admin/integrity/repair - Remove orphans and enforce foreign keys.
+/// Returns what was found before the repair.
+pub async fn repair_integrity(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<IntegrityReport>, (StatusCode, String)> {
+    profile_store(&state, &headers)?
+        .repair_integrity()
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Response for a database backup.
+#[derive(Serialize)]
+pub struct BackupResponse {
+    pub path: PathBuf,
+}
+
+/// POST /api/admin/backup - Hot-copy the database into a timestamped file in
+/// a `backups` directory next to it. Returns the backup's path.
+pub async fn backup_database(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<BackupResponse>, (StatusCode, String)> {
+    reject_profile(&headers)?;
+    let db_path = state.config().db_path.clone();
+    let dir = db_path.parent().unwrap_or(std::path::Path::new(".")).join("backups");
+    std::fs::create_dir_all(&dir).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let path = dir.join(format!("clauset-{}.db", chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f")));
+
+    let store = state.interaction_processor.store().clone();
+    let target = path.clone();
+    tokio::task::spawn_blocking(move || store.backup_to(&target))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    info!(target: "clauset::db", "Database backed up to {}", path.display());
+    Ok(Json(BackupResponse { path }))
+}
+
+/// POST /api/admin/config/reload - Re-read the config file and apply the
+/// fields that can change without a restart.
+pub async fn reload_config(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ConfigReload>, (StatusCode, String)> {
+    state
+        .reload_config()
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
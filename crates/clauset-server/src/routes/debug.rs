@@ -0,0 +1,33 @@
+//! Diagnostic routes, enabled by the `debug_endpoints` config flag.
+
+use crate::state::AppState;
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use clauset_core::{dry_parse, ParseDiagnostics};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Terminal size to parse a snippet at; unknown when omitted.
+#[derive(Deserialize)]
+pub struct ParseQuery {
+    pub rows: Option<u16>,
+    pub cols: Option<u16>,
+}
+
+/// POST /api/debug/parse - Run the terminal output parsers over the raw
+/// request body, for diagnosing mis-detected status, mode or activity.
+pub async fn parse(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ParseQuery>,
+    body: Bytes,
+) -> Result<Json<ParseDiagnostics>, (StatusCode, String)> {
+    let config = state.config();
+    if !config.debug_endpoints {
+        return Err((StatusCode::NOT_FOUND, "Debug endpoints are disabled".to_string()));
+    }
+    Ok(Json(dry_parse(&body, (query.rows, query.cols), &config.activity_keywords)))
+}
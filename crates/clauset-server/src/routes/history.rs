@@ -13,6 +13,12 @@ use std::sync::Arc;
 pub struct HistoryQuery {
     #[serde(default)]
     pub limit: Option<usize>,
+    /// Only entries at or after this time (Unix milliseconds).
+    #[serde(default)]
+    pub since: Option<i64>,
+    /// Only entries before this time (Unix milliseconds).
+    #[serde(default)]
+    pub until: Option<i64>,
 }
 
 #[derive(Serialize)]
@@ -25,6 +31,8 @@ pub struct HistoryEntryResponse {
     pub display: String,
     pub timestamp: i64,
     pub project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
 }
 
 pub async fn list(
@@ -37,7 +45,9 @@ pub async fn list(
         .reload()
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    let entries = state.history_watcher.get_entries(query.limit);
+    let entries = state
+        .history_watcher
+        .get_in_range(query.since, query.until, query.limit);
 
     let response_entries: Vec<HistoryEntryResponse> = entries
         .into_iter()
@@ -45,6 +55,7 @@ pub async fn list(
             display: e.display,
             timestamp: e.timestamp,
             project: e.project.to_string_lossy().to_string(),
+            duration_ms: e.duration_ms,
         })
         .collect();
 
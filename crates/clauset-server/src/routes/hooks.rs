@@ -3,18 +3,25 @@
 //! This module handles HTTP POST requests from the Claude Code hooks,
 //! providing real-time activity tracking for the Clauset dashboard.
 
+use crate::hook_validation::{custom_hook_event, normalize_hook_payload};
 use crate::state::AppState;
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
 use clauset_core::{
-    transcript_event_to_chat_event, ChatProcessor, ProcessEvent, RecentAction, TranscriptEvent,
+    transcript_event_to_chat_event, ChatProcessor, HookUsage, ProcessEvent, RecentAction, TranscriptEvent,
 };
 use clauset_types::{
-    ChatEvent, HookActivityUpdate, HookEvent, HookEventPayload, HookEventType,
+    ChatEvent, HookActivityUpdate, HookEvent, HookEventType, InteractionContext,
     InteractiveEvent, InteractivePrompt, InteractiveQuestion, PermissionMode, QuestionOption,
     SessionStatus,
 };
 use serde::Serialize;
 use serde_json::Value;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, info, warn};
@@ -28,11 +35,40 @@ pub struct HookResponse {
 /// POST /api/hooks - Receive Claude Code hook events.
 ///
 /// This endpoint is called by the clauset-hook.sh script whenever
-/// Claude Code fires a hook event (PreToolUse, Stop, etc.).
+/// Claude Code fires a hook event (PreToolUse, Stop, etc.). Payloads are
+/// validated first; invalid ones get a 400 listing the offending fields.
+/// Events with names Clauset doesn't recognize (custom hooks) are stored
+/// verbatim and listed by `GET /api/sessions/{id}/custom-events`.
 pub async fn receive(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<HookEventPayload>,
-) -> Result<Json<HookResponse>, (StatusCode, String)> {
+    Json(raw): Json<Value>,
+) -> Result<Json<HookResponse>, Response> {
+    // Events from custom hooks are stored as-is rather than rejected
+    if let Some((session_id, event_name)) = custom_hook_event(&raw) {
+        debug!(
+            target: "clauset::hooks",
+            "Received custom hook event: {} for session {}",
+            event_name, session_id
+        );
+        if let Err(e) = state
+            .interaction_processor_for(session_id)
+            .store()
+            .record_custom_hook_event(session_id, event_name, &raw)
+        {
+            warn!(target: "clauset::hooks", "Failed to record custom hook event for session {}: {}", session_id, e);
+        }
+        if let Some(webhook) = &state.hook_webhook
+            && webhook.matches(event_name)
+        {
+            webhook.forward(event_name, raw.clone());
+        }
+        return Ok(Json(HookResponse { status: "ok" }));
+    }
+
+    let payload = normalize_hook_payload(raw).map_err(|err| {
+        warn!(target: "clauset::hooks", "Rejected hook payload: {}", err);
+        err.into_response()
+    })?;
     let session_id = payload.clauset_session_id;
     debug!(
         target: "clauset::hooks",
@@ -40,6 +76,13 @@ pub async fn receive(
         payload.hook_event_name, session_id
     );
 
+    if let Some(webhook) = &state.hook_webhook
+        && webhook.matches(&payload.hook_event_name)
+        && let Ok(value) = serde_json::to_value(&payload)
+    {
+        webhook.forward(&payload.hook_event_name, value);
+    }
+
     let permission_mode = payload.permission_mode.clone();
     let model_display = payload.model.as_ref().and_then(|model| {
         if !model.display_name.is_empty() {
@@ -52,11 +95,11 @@ pub async fn receive(
     });
 
     // Parse into typed event
-    let event = match HookEvent::try_from(payload) {
+    let event = match HookEvent::try_from(payload.clone()) {
         Ok(e) => e,
         Err(err) => {
             warn!(target: "clauset::hooks", "Failed to parse hook event: {}", err);
-            return Err((StatusCode::BAD_REQUEST, err.to_string()));
+            return Err((StatusCode::BAD_REQUEST, err.to_string()).into_response());
         }
     };
 
@@ -90,20 +133,56 @@ pub async fn receive(
         }
     }
 
-    // Get current session costs for interaction delta calculation
-    let (cost_usd, input_tokens, output_tokens) =
-        if let Some(activity) = state.session_manager.get_activity(session_id).await {
-            (activity.cost, activity.input_tokens, activity.output_tokens)
+    // Get current session costs and context usage for interaction tracking
+    let activity = state.session_manager.get_activity(session_id).await;
+    let (cost_usd, input_tokens, output_tokens, thinking_tokens, context_percent) =
+        if let Some(activity) = &activity {
+            (
+                activity.cost,
+                activity.input_tokens,
+                activity.output_tokens,
+                activity.thinking_tokens,
+                activity.context_percent,
+            )
         } else {
-            (0.0, 0, 0)
+            (0.0, 0, 0, 0, 0)
         };
 
+    // Log the raw event so the session can be replayed later
+    let usage = HookUsage {
+        cost_usd,
+        input_tokens,
+        output_tokens,
+        thinking_tokens,
+        context_percent,
+    };
+    let interaction_processor = state.interaction_processor_for(session_id);
+    if let Err(e) = interaction_processor.store().record_hook_event(&payload, &usage) {
+        warn!(target: "clauset::hooks", "Failed to record hook event for session {}: {}", session_id, e);
+    }
+
     // Capture interaction data for persistence (runs concurrently with activity update)
-    state
-        .interaction_processor
-        .process_event(&event, cost_usd, input_tokens, output_tokens)
+    interaction_processor
+        .process_event(
+            &event,
+            cost_usd,
+            input_tokens,
+            output_tokens,
+            thinking_tokens,
+            context_percent,
+        )
         .await;
 
+    // Snapshot the settings a new interaction starts under
+    if let HookEvent::UserPromptSubmit { cwd, .. } = &event {
+        let context = InteractionContext {
+            model: activity.as_ref().map(|a| a.model.clone()).filter(|m| !m.is_empty()),
+            permission_mode: activity.as_ref().and_then(|a| a.permission_mode),
+            cwd: cwd.as_ref().map(PathBuf::from),
+        };
+        interaction_processor.record_context(session_id, &context);
+    }
+
     // Process the event for chat mode messages
     let chat_events = state.chat_processor.process_hook_event(&event).await;
     for chat_event in chat_events {
@@ -151,10 +230,16 @@ async fn process_hook_event(
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     match event {
         HookEvent::SessionStart {
-            session_id, source, transcript_path, context_window, ..
+            session_id, source, transcript_path, context_window, version, ..
         } => {
             info!(target: "clauset::hooks", "Session {} started (source: {})", session_id, source);
 
+            if let Some(ref version) = version
+                && let Err(e) = state.session_manager.set_claude_version(session_id, version)
+            {
+                warn!(target: "clauset::hooks", "Failed to record Claude version for session {}: {}", session_id, e);
+            }
+
             let should_reset_context =
                 source == "clear" && context_window.as_ref().and_then(|ctx| ctx.current_usage.as_ref()).is_none();
 
@@ -195,6 +280,7 @@ async fn process_hook_event(
                                             usage.cache_creation_input_tokens,
                                             &usage.model,
                                         ).await;
+                                        session_manager.add_thinking_tokens(session_id, usage.thinking_tokens).await;
                                     }
                                     // Context compaction events - broadcast to frontend
                                     TranscriptEvent::ContextCompacted { timestamp, metadata } => {
@@ -333,7 +419,7 @@ async fn process_hook_event(
                     now_ms(),
                 );
 
-                if let Err(e) = state.interaction_processor.store().insert_prompt(&prompt_entry) {
+                if let Err(e) = state.interaction_processor_for(session_id).store().insert_prompt(&prompt_entry) {
                     warn!(target: "clauset::hooks", "Failed to index prompt: {}", e);
                 }
 
@@ -1,26 +1,38 @@
 //! Interaction tracking API routes.
 //!
 //! Provides endpoints for:
-//! - Listing session interactions (timeline)
+//! - Listing session interactions (timeline) and truncating old history
 //! - Getting interaction details
-//! - Computing file diffs
+//! - Computing file diffs and exporting interaction patches
 //! - Cross-session search
 //! - Cost analytics
+//! - Ad-hoc read-only SQL (when enabled)
+//!
+//! Every endpoint uses the default interaction database unless the request
+//! names a configured profile in the `X-Clauset-Profile` header.
 
+use crate::routes::profile_store;
 use crate::state::AppState;
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
     Json,
 };
 use chrono::{DateTime, Utc};
 use clauset_core::{
-    compute_diff, generate_unified_diff, AnalyticsSummary, DailyCostEntry, FileChangeWithDiff,
-    FileDiff, GlobalSearchResults, SessionAnalytics, StorageStats, ToolCostEntry,
+    compute_diff, generate_unified_diff, AnalyticsDelta, AnalyticsSummary, ClausetError, CleanupStats, CostBucket, DailyCostEntry, DiffOptions,
+    FileChangeWithDiff, FileDiff, GlobalFileEntry, GlobalSearchOptions, GlobalSearchResults, Granularity, LineAuthor, LineAuthorship, QueryResult, SessionAnalytics, SessionVelocity,
+    SessionStorageEntry, StorageStats, ToolBucket, ToolCostEntry, ToolReliability, ToolSeqDiff,
 };
-use clauset_types::{Interaction, ToolInvocation};
+use clauset_types::{Interaction, InteractionContext, TimelineEntry, ToolInvocation, TurnOutcome};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::warn;
 use uuid::Uuid;
 
 // ============================================================================
@@ -54,19 +66,36 @@ pub struct InteractionSummary {
 pub struct InteractionListQuery {
     pub limit: Option<u32>,
     pub offset: Option<u32>,
+    /// Hide finished interactions shorter than this many milliseconds
+    pub min_duration_ms: Option<u64>,
+    /// Only interactions that did (`true`) or did not (`false`) change files
+    pub changed: Option<bool>,
 }
 
 /// List all interactions for a session.
 pub async fn list_session_interactions(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Path(session_id): Path<Uuid>,
     Query(query): Query<InteractionListQuery>,
 ) -> Result<Json<InteractionListResponse>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
 
-    let interactions = store
+    let mut interactions = store
         .list_interactions(session_id, 1000, 0)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if let Some(min) = query.min_duration_ms {
+        interactions.retain(|i| i.duration_ms().is_none_or(|ms| ms >= min as i64));
+    }
+    if let Some(changed) = query.changed {
+        let matching: HashSet<Uuid> = store
+            .list_interactions_by_change(session_id, changed, 1000, 0)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        interactions.retain(|i| matching.contains(&i.id));
+    }
 
     let limit = query.limit.unwrap_or(50) as usize;
     let offset = query.offset.unwrap_or(0) as usize;
@@ -83,7 +112,7 @@ pub async fn list_session_interactions(
                 .unwrap_or(0);
 
             let files_changed: Vec<String> = store
-                .get_file_changes_with_diffs(i.id, 3)
+                .get_file_changes_with_diffs(i.id, 3, &DiffOptions::default())
                 .map(|changes| {
                     changes
                         .into_iter()
@@ -120,41 +149,157 @@ pub async fn list_session_interactions(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct TruncateHistoryRequest {
+    /// Number of most recent interactions to keep
+    pub keep_last: u32,
+}
+
+/// Delete all but the most recent interactions of a session.
+pub async fn truncate_session_history(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+    Json(req): Json<TruncateHistoryRequest>,
+) -> Result<Json<CleanupStats>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let stats = store
+        .truncate_session_history(session_id, req.keep_last)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(stats))
+}
+
 /// Full interaction detail response.
 #[derive(Serialize)]
 pub struct InteractionDetailResponse {
     pub interaction: Interaction,
+    /// Session settings the interaction started under, if recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<InteractionContext>,
     pub tool_invocations: Vec<ToolInvocation>,
     pub file_changes: Vec<FileChangeWithDiff>,
+    /// Paths referenced with `@` in the prompt
+    pub file_refs: Vec<String>,
+    /// Previous interaction in the session, by sequence number
+    pub prev_id: Option<Uuid>,
+    /// Next interaction in the session, by sequence number
+    pub next_id: Option<Uuid>,
+    /// Milliseconds from prompt submission to the first response text or tool call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_latency_ms: Option<u64>,
 }
 
 /// Get full details for a single interaction.
+///
+/// `?ignore_whitespace=true` and `?ignore_blank_lines=true` hide cosmetic-only
+/// changes from the file diffs.
 pub async fn get_interaction(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Path(interaction_id): Path<Uuid>,
+    Query(diff_options): Query<DiffOptions>,
 ) -> Result<Json<InteractionDetailResponse>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
 
     let interaction = store
         .get_interaction(interaction_id)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or((StatusCode::NOT_FOUND, "Interaction not found".to_string()))?;
 
+    let context = store
+        .interaction_context(interaction_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
     let tool_invocations = store
         .list_tool_invocations(interaction_id)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     let file_changes = store
-        .get_file_changes_with_diffs(interaction_id, 3)
+        .get_file_changes_with_diffs(interaction_id, 3, &diff_options)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let file_refs = store
+        .prompt_file_refs(interaction_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let (prev, next) = store
+        .neighbor_interactions(interaction_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let response_latency = store
+        .response_latency(interaction_id)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     Ok(Json(InteractionDetailResponse {
         interaction,
+        context,
         tool_invocations,
         file_changes,
+        file_refs,
+        prev_id: prev.map(|i| i.id),
+        next_id: next.map(|i| i.id),
+        response_latency_ms: response_latency.map(|latency| latency.as_millis() as u64),
     }))
 }
 
+#[derive(Deserialize)]
+pub struct FileRefQuery {
+    /// Path as referenced, or its trailing components (`config.rs`)
+    pub path: String,
+    pub limit: Option<u32>,
+}
+
+/// List interactions whose prompt referenced a file with `@`, newest first.
+pub async fn list_interactions_referencing_file(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<FileRefQuery>,
+) -> Result<Json<Vec<Interaction>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let interactions = store
+        .interactions_referencing_file(&query.path, query.limit.unwrap_or(50))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(interactions))
+}
+
+#[derive(Deserialize)]
+pub struct FailedInteractionsQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+    /// Only interactions started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// List interactions with an errored tool call instead of failed ones
+    #[serde(default)]
+    pub tool_errors: bool,
+}
+
+/// List failed interactions across all sessions, newest first.
+///
+/// `?tool_errors=true` lists interactions containing at least one errored
+/// tool invocation instead.
+pub async fn list_failed_interactions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<FailedInteractionsQuery>,
+) -> Result<Json<Vec<Interaction>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+
+    let interactions = if query.tool_errors {
+        store.list_interactions_with_tool_errors(limit, offset, query.since)
+    } else {
+        store.list_failed_interactions(limit, offset, query.since)
+    }
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(interactions))
+}
+
 // ============================================================================
 // Diff Endpoints
 // ============================================================================
@@ -184,9 +329,10 @@ pub struct DiffResponse {
 /// Compute diff between two interaction snapshots for a specific file.
 pub async fn get_diff(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(query): Query<DiffQuery>,
 ) -> Result<Json<DiffResponse>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
     let context_lines = query.context.unwrap_or(3);
 
     // Get 'after' snapshot from the 'from' interaction
@@ -222,6 +368,181 @@ pub async fn get_diff(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct CrossSessionDiffQuery {
+    /// Session to diff FROM (its latest 'after' snapshot)
+    pub session_a: Uuid,
+    /// Session to diff TO (its latest 'after' snapshot)
+    pub session_b: Uuid,
+    /// File path to diff
+    pub file: String,
+    /// Number of context lines (default: 3)
+    pub context: Option<usize>,
+}
+
+/// Response for a cross-session diff.
+#[derive(Serialize)]
+pub struct CrossSessionDiffResponse {
+    pub file_path: String,
+    pub session_a: Uuid,
+    pub session_b: Uuid,
+    pub diff: FileDiff,
+}
+
+/// Compare the latest versions of a file written in two different sessions.
+pub async fn get_cross_session_diff(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<CrossSessionDiffQuery>,
+) -> Result<Json<CrossSessionDiffResponse>, (StatusCode, String)> {
+    let diff = profile_store(&state, &headers)?
+        .diff_file_across_sessions(
+            query.session_a,
+            query.session_b,
+            &query.file,
+            query.context.unwrap_or(3),
+        )
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((
+            StatusCode::NOT_FOUND,
+            format!("No snapshots of {} in either session", query.file),
+        ))?;
+
+    Ok(Json(CrossSessionDiffResponse {
+        file_path: query.file,
+        session_a: query.session_a,
+        session_b: query.session_b,
+        diff,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct CompareInteractionsQuery {
+    pub a: Uuid,
+    pub b: Uuid,
+}
+
+/// Diff the tool-call sequences of two interactions.
+pub async fn compare_interactions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<CompareInteractionsQuery>,
+) -> Result<Json<Vec<ToolSeqDiff>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    match store.diff_tool_sequences(query.a, query.b) {
+        Ok(diff) => Ok(Json(diff)),
+        Err(ClausetError::InteractionNotFound(id)) => {
+            Err((StatusCode::NOT_FOUND, format!("Interaction {} not found", id)))
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AuthorshipQuery {
+    /// File path to attribute
+    pub path: String,
+}
+
+/// Line-level attribution of a file's final version in a session.
+#[derive(Serialize)]
+pub struct AuthorshipResponse {
+    pub file_path: String,
+    /// Number of lines Claude added
+    pub claude_lines: usize,
+    pub lines: Vec<LineAuthorship>,
+}
+
+/// Blame-style view of which lines of a file Claude wrote in a session.
+pub async fn get_file_authorship(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+    Query(query): Query<AuthorshipQuery>,
+) -> Result<Json<AuthorshipResponse>, (StatusCode, String)> {
+    let lines = profile_store(&state, &headers)?
+        .file_authorship(session_id, &query.path)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(AuthorshipResponse {
+        file_path: query.path,
+        claude_lines: lines.iter().filter(|l| l.author == LineAuthor::Claude).count(),
+        lines,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct PatchQuery {
+    /// Number of context lines (default: 3)
+    pub context: Option<usize>,
+}
+
+/// Export every file change in an interaction as a single `git apply`-able patch.
+pub async fn get_interaction_patch(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(interaction_id): Path<Uuid>,
+    Query(query): Query<PatchQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    store
+        .get_interaction(interaction_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Interaction not found".to_string()))?;
+
+    let patch = store
+        .interaction_patch(interaction_id, query.context.unwrap_or(3))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(([(header::CONTENT_TYPE, "text/x-patch; charset=utf-8")], patch))
+}
+
+/// Download one interaction as self-contained JSON, with the before/after
+/// contents of every changed file inlined (unlike the session bundle).
+pub async fn export_interaction(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(interaction_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let export = match store.export_interaction_full(interaction_id) {
+        Ok(export) => export,
+        Err(ClausetError::InteractionNotFound(_)) => {
+            return Err((StatusCode::NOT_FOUND, "Interaction not found".to_string()));
+        }
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"interaction-{}.json\"", interaction_id),
+        )],
+        Json(export),
+    ))
+}
+
+/// Merged chronological feed of an interaction (prompt, tool starts/ends,
+/// assistant response) for replaying a turn.
+pub async fn get_turn_timeline(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(interaction_id): Path<Uuid>,
+) -> Result<Json<Vec<TimelineEntry>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    match store.turn_timeline(interaction_id) {
+        Ok(entries) => Ok(Json(entries)),
+        Err(ClausetError::InteractionNotFound(_)) => {
+            Err((StatusCode::NOT_FOUND, "Interaction not found".to_string()))
+        }
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
 /// Response for files changed in a session.
 #[derive(Serialize)]
 pub struct FilesChangedResponse {
@@ -238,9 +559,10 @@ pub struct FileChangeSummary {
 /// List all files changed in a session with counts.
 pub async fn get_session_files_changed(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Path(session_id): Path<Uuid>,
 ) -> Result<Json<FilesChangedResponse>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
 
     let interactions = store
         .list_interactions(session_id, 1000, 0)
@@ -251,7 +573,9 @@ pub async fn get_session_files_changed(
         std::collections::HashMap::new();
 
     for interaction in interactions {
-        let changes = store.get_file_changes_with_diffs(interaction.id, 0).unwrap_or_default();
+        let changes = store
+            .get_file_changes_with_diffs(interaction.id, 0, &DiffOptions::default())
+            .unwrap_or_default();
 
         for change in changes {
             let path_str = change.file_path.display().to_string();
@@ -273,6 +597,33 @@ pub async fn get_session_files_changed(
     Ok(Json(FilesChangedResponse { files }))
 }
 
+#[derive(Deserialize)]
+pub struct TouchedFilesQuery {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// Response for the global touched-files index.
+#[derive(Serialize)]
+pub struct TouchedFilesResponse {
+    pub files: Vec<GlobalFileEntry>,
+}
+
+/// List every file touched across all sessions, most recently modified first.
+pub async fn list_touched_files(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<TouchedFilesQuery>,
+) -> Result<Json<TouchedFilesResponse>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let files = store
+        .all_touched_files(query.limit.unwrap_or(100), query.offset.unwrap_or(0))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(TouchedFilesResponse { files }))
+}
+
 // ============================================================================
 // Search Endpoints
 // ============================================================================
@@ -289,14 +640,21 @@ pub struct SearchQuery {
     pub limit: Option<usize>,
     /// Offset for pagination
     pub offset: Option<usize>,
+    /// Maximum prompt matches for scope "all" (default: `limit`)
+    pub interaction_limit: Option<usize>,
+    /// Maximum tool matches for scope "all" (default: `limit`)
+    pub tool_limit: Option<usize>,
+    /// Maximum file matches for scope "all" (default: `limit`)
+    pub file_limit: Option<usize>,
 }
 
 /// Search across sessions.
 pub async fn search(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(query): Query<SearchQuery>,
 ) -> Result<Json<GlobalSearchResults>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
     let limit = query.limit.unwrap_or(50);
     let offset = query.offset.unwrap_or(0);
 
@@ -307,36 +665,29 @@ pub async fn search(
             let interactions = store
                 .search_interactions(&query.q, query.session_id, limit, offset)
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-            GlobalSearchResults {
-                interactions,
-                tool_invocations: Vec::new(),
-                file_matches: Vec::new(),
-            }
+            GlobalSearchResults::grouped(interactions, Vec::new(), Vec::new())
         }
         "files" => {
             let file_matches = store
                 .search_files_by_path(&query.q, limit)
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-            GlobalSearchResults {
-                interactions: Vec::new(),
-                tool_invocations: Vec::new(),
-                file_matches,
-            }
+            GlobalSearchResults::grouped(Vec::new(), Vec::new(), file_matches)
         }
         "tools" => {
             let tool_invocations = store
                 .search_tool_invocations(&query.q, None, limit, offset)
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-            GlobalSearchResults {
-                interactions: Vec::new(),
-                tool_invocations,
-                file_matches: Vec::new(),
-            }
+            GlobalSearchResults::grouped(Vec::new(), tool_invocations, Vec::new())
         }
         _ => {
             // "all" - combined search
+            let options = GlobalSearchOptions {
+                interaction_limit: query.interaction_limit.unwrap_or(limit),
+                tool_limit: query.tool_limit.unwrap_or(limit),
+                file_limit: query.file_limit.unwrap_or(limit),
+            };
             store
-                .global_search(&query.q, limit)
+                .global_search(&query.q, &options)
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         }
     };
@@ -344,6 +695,51 @@ pub async fn search(
     Ok(Json(results))
 }
 
+#[derive(Deserialize)]
+pub struct SearchStreamQuery {
+    /// Search query string
+    pub q: String,
+    /// Maximum results per category
+    pub limit: Option<usize>,
+}
+
+/// Search across sessions, streaming results as NDJSON.
+///
+/// Each line is one result tagged with its `kind` (`interaction`,
+/// `tool_invocation`, or `file`), in that category order. A failure part-way
+/// through is reported as a final `{"kind":"error"}` line.
+pub async fn search_stream(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SearchStreamQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+    let limit = query.limit.unwrap_or(50);
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, std::convert::Infallible>>(32);
+
+    tokio::task::spawn_blocking(move || {
+        let result = store.global_search_each(&query.q, limit, |item| {
+            match serde_json::to_string(&item) {
+                // A failed send means the client went away
+                Ok(line) => tx.blocking_send(Ok(line + "\n")).is_ok(),
+                Err(e) => {
+                    warn!(target: "clauset::interactions", "Failed to serialize search result: {}", e);
+                    true
+                }
+            }
+        });
+        if let Err(e) = result {
+            let line = serde_json::json!({ "kind": "error", "message": e.to_string() });
+            let _ = tx.blocking_send(Ok(format!("{}\n", line)));
+        }
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(ReceiverStream::new(rx)),
+    ))
+}
+
 // ============================================================================
 // Analytics Endpoints
 // ============================================================================
@@ -352,6 +748,8 @@ pub async fn search(
 pub struct AnalyticsQuery {
     /// Number of days to include in daily breakdown (default: 30)
     pub days: Option<u32>,
+    /// Leave out finished interactions shorter than this many milliseconds
+    pub min_duration_ms: Option<u64>,
 }
 
 /// Combined analytics response.
@@ -366,17 +764,18 @@ pub struct AnalyticsResponse {
 /// Get analytics summary.
 pub async fn get_analytics(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(query): Query<AnalyticsQuery>,
 ) -> Result<Json<AnalyticsResponse>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
     let days = query.days.unwrap_or(30);
 
     let summary = store
-        .get_analytics_summary()
+        .get_analytics_summary(query.min_duration_ms, None)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     let daily_costs = store
-        .get_daily_cost_breakdown(days)
+        .get_daily_cost_breakdown(days, query.min_duration_ms)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     let tool_costs = store
@@ -402,22 +801,376 @@ pub async fn get_analytics(
     }))
 }
 
+#[derive(Deserialize)]
+pub struct ActiveSessionsQuery {
+    /// Number of days to include (default: 30)
+    pub days: Option<u32>,
+}
+
+/// Distinct sessions active on one day.
+#[derive(Serialize)]
+pub struct DailyActiveSessions {
+    pub date: String,
+    pub session_count: u32,
+}
+
+#[derive(Serialize)]
+pub struct ActiveSessionsResponse {
+    pub days: Vec<DailyActiveSessions>,
+}
+
+/// Get how many distinct sessions were active each day, newest first.
+pub async fn get_daily_active_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ActiveSessionsQuery>,
+) -> Result<Json<ActiveSessionsResponse>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let days = store
+        .daily_active_sessions(query.days.unwrap_or(30))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .map(|(date, session_count)| DailyActiveSessions { date, session_count })
+        .collect();
+
+    Ok(Json(ActiveSessionsResponse { days }))
+}
+
+#[derive(Deserialize)]
+pub struct AnalyticsCompareQuery {
+    /// Baseline period as `from..to` (RFC 3339 timestamps or `YYYY-MM-DD` dates; `to` is exclusive)
+    pub period_a: String,
+    /// Compared period, same format
+    pub period_b: String,
+    /// Leave out finished interactions shorter than this many milliseconds
+    pub min_duration_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct AnalyticsPeriod {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub summary: AnalyticsSummary,
+}
+
+#[derive(Serialize)]
+pub struct AnalyticsCompareResponse {
+    pub period_a: AnalyticsPeriod,
+    pub period_b: AnalyticsPeriod,
+    /// Changes from period A to period B
+    pub delta: AnalyticsDelta,
+}
+
+/// Parse one end of a period: an RFC 3339 timestamp or a date (midnight UTC).
+fn parse_period_bound(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        })
+}
+
+/// Parse a `from..to` period.
+fn parse_period(name: &str, value: &str) -> Result<(DateTime<Utc>, DateTime<Utc>), (StatusCode, String)> {
+    let invalid = || {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("{name} must be `from..to` with RFC 3339 timestamps or YYYY-MM-DD dates"),
+        )
+    };
+    let (from, to) = value.split_once("..").ok_or_else(invalid)?;
+    let from = parse_period_bound(from.trim()).ok_or_else(invalid)?;
+    let to = parse_period_bound(to.trim()).ok_or_else(invalid)?;
+    if from >= to {
+        return Err((StatusCode::BAD_REQUEST, format!("{name} must start before it ends")));
+    }
+    Ok((from, to))
+}
+
+/// Compare analytics between two periods (e.g. this week vs last week).
+pub async fn compare_analytics(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<AnalyticsCompareQuery>,
+) -> Result<Json<AnalyticsCompareResponse>, (StatusCode, String)> {
+    let range_a = parse_period("period_a", &query.period_a)?;
+    let range_b = parse_period("period_b", &query.period_b)?;
+    let store = profile_store(&state, &headers)?;
+
+    let summarize = |(from, to)| {
+        store
+            .get_analytics_summary(query.min_duration_ms, Some((from, to)))
+            .map(|summary| AnalyticsPeriod { from, to, summary })
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+    };
+    let period_a = summarize(range_a)?;
+    let period_b = summarize(range_b)?;
+    let delta = AnalyticsDelta::between(&period_a.summary, &period_b.summary);
+
+    Ok(Json(AnalyticsCompareResponse {
+        period_a,
+        period_b,
+        delta,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ToolReliabilityQuery {
+    /// Restrict to a single session
+    pub session_id: Option<Uuid>,
+}
+
+/// Get per-tool invocation and error counts.
+pub async fn get_tool_reliability(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ToolReliabilityQuery>,
+) -> Result<Json<Vec<ToolReliability>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let tools = store
+        .tool_reliability(query.session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(tools))
+}
+
+#[derive(Deserialize)]
+pub struct OutcomeBreakdownQuery {
+    /// Restrict to a single session
+    pub session_id: Option<Uuid>,
+}
+
+/// Number of interactions that ended with one outcome.
+#[derive(Serialize)]
+pub struct OutcomeCount {
+    pub outcome: TurnOutcome,
+    pub count: u32,
+}
+
+/// Count finished interactions by outcome (succeeded, partial error, failed, interrupted).
+pub async fn get_outcome_breakdown(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<OutcomeBreakdownQuery>,
+) -> Result<Json<Vec<OutcomeCount>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let breakdown = store
+        .outcome_breakdown(query.session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        breakdown
+            .into_iter()
+            .map(|(outcome, count)| OutcomeCount { outcome, count })
+            .collect(),
+    ))
+}
+
+/// One point of a session's running cost total.
+#[derive(Serialize)]
+pub struct CostTimelinePoint {
+    pub timestamp: DateTime<Utc>,
+    pub cumulative_cost_usd: f64,
+}
+
+/// Get a session's cumulative cost after each interaction, for spend charts.
+pub async fn get_session_cost_timeline(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<Vec<CostTimelinePoint>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let series = store
+        .session_cumulative_cost(session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        series
+            .into_iter()
+            .map(|(timestamp, cumulative_cost_usd)| CostTimelinePoint {
+                timestamp,
+                cumulative_cost_usd,
+            })
+            .collect(),
+    ))
+}
+
+/// Download the session's Bash commands as a replayable shell script.
+pub async fn get_session_repro_script(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let script = store
+        .extract_bash_script(session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/x-shellscript; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"session-{}-repro.sh\"", session_id),
+            ),
+        ],
+        script,
+    ))
+}
+
+/// Interactions and file changes per hour, and average turn duration.
+pub async fn get_session_velocity(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<SessionVelocity>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let velocity = store
+        .session_velocity(session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(velocity))
+}
+
+#[derive(Serialize)]
+pub struct LanguageCount {
+    pub language: String,
+    pub files: u32,
+}
+
+/// Changed files per programming language in a session, most common first.
+pub async fn get_session_languages(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<Vec<LanguageCount>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+
+    let breakdown = store
+        .language_breakdown(session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        breakdown
+            .into_iter()
+            .map(|(language, files)| LanguageCount { language, files })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct TimeseriesQuery {
+    /// Bucket size: hour, day, week or month (default: day)
+    #[serde(default)]
+    pub granularity: Granularity,
+    /// Range start, inclusive (default: 30 days before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// Range end, exclusive (default: now)
+    pub to: Option<DateTime<Utc>>,
+    /// Restrict to a single session
+    pub session_id: Option<Uuid>,
+}
+
+#[derive(Serialize)]
+pub struct TimeseriesResponse {
+    pub granularity: Granularity,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub buckets: Vec<CostBucket>,
+}
+
+/// Get cost aggregated into time buckets.
+pub async fn get_cost_timeseries(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<TimeseriesQuery>,
+) -> Result<Json<TimeseriesResponse>, (StatusCode, String)> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(30));
+    if from >= to {
+        return Err((StatusCode::BAD_REQUEST, "from must be before to".to_string()));
+    }
+
+    let buckets = profile_store(&state, &headers)?
+        .cost_timeseries(query.granularity, (from, to), query.session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(TimeseriesResponse {
+        granularity: query.granularity,
+        from,
+        to,
+        buckets,
+    }))
+}
+
+#[derive(Serialize)]
+pub struct ToolHistogramResponse {
+    pub granularity: Granularity,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub buckets: Vec<ToolBucket>,
+}
+
+/// Get tool invocation counts per time bucket and tool, for usage heatmaps.
+pub async fn get_tool_histogram(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<TimeseriesQuery>,
+) -> Result<Json<ToolHistogramResponse>, (StatusCode, String)> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(30));
+    if from >= to {
+        return Err((StatusCode::BAD_REQUEST, "from must be before to".to_string()));
+    }
+
+    let buckets = profile_store(&state, &headers)?
+        .tool_histogram(query.granularity, (from, to), query.session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ToolHistogramResponse {
+        granularity: query.granularity,
+        from,
+        to,
+        buckets,
+    }))
+}
+
 /// Get most expensive interactions.
 #[derive(Deserialize)]
 pub struct ExpensiveInteractionsQuery {
     pub limit: Option<usize>,
+    /// Return every interaction costing at least this many USD (paged with
+    /// `limit`/`offset`) instead of the top N
+    pub min_cost: Option<f64>,
+    pub offset: Option<u32>,
 }
 
 pub async fn get_expensive_interactions(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(query): Query<ExpensiveInteractionsQuery>,
 ) -> Result<Json<Vec<Interaction>>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
     let limit = query.limit.unwrap_or(10);
 
-    let interactions = store
-        .get_most_expensive_interactions(limit)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let interactions = match query.min_cost {
+        Some(min_cost) => store.interactions_over_cost(
+            min_cost,
+            limit.min(u32::MAX as usize) as u32,
+            query.offset.unwrap_or(0),
+        ),
+        None => store.get_most_expensive_interactions(limit),
+    }
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     Ok(Json(interactions))
 }
@@ -425,8 +1178,9 @@ pub async fn get_expensive_interactions(
 /// Get storage statistics.
 pub async fn get_storage_stats(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
 ) -> Result<Json<StorageStats>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
 
     let stats = store
         .get_storage_stats()
@@ -434,3 +1188,70 @@ pub async fn get_storage_stats(
 
     Ok(Json(stats))
 }
+
+#[derive(Deserialize)]
+pub struct SessionStorageQuery {
+    pub limit: Option<usize>,
+}
+
+/// Get storage usage per session, largest first.
+pub async fn get_storage_by_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SessionStorageQuery>,
+) -> Result<Json<Vec<SessionStorageEntry>>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+    let limit = query.limit.unwrap_or(20);
+
+    let entries = store
+        .storage_by_session(limit)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(entries))
+}
+
+// ============================================================================
+// Ad-hoc Query Endpoint
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct QueryRequest {
+    pub sql: String,
+}
+
+/// Run a read-only SQL query against the interaction database. Disabled
+/// unless `query_api` is configured, and requires its bearer token.
+pub async fn run_query(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<QueryRequest>,
+) -> Result<Json<QueryResult>, (StatusCode, String)> {
+    let Some(query_api) = state.config().query_api.clone() else {
+        return Err((StatusCode::NOT_FOUND, "Query API is disabled".to_string()));
+    };
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !token.is_some_and(|token| tokens_match(token, &query_api.token)) {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid or missing query token".to_string()));
+    }
+
+    let store = profile_store(&state, &headers)?;
+    let timeout = Duration::from_millis(query_api.timeout_ms);
+    let result = tokio::task::spawn_blocking(move || store.query_readonly(&request.sql, query_api.max_rows, timeout))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match result {
+        Ok(result) => Ok(Json(result)),
+        Err(ClausetError::QueryRejected(reason)) => Err((StatusCode::BAD_REQUEST, reason)),
+        Err(e @ ClausetError::QueryTimeout(_)) => Err((StatusCode::REQUEST_TIMEOUT, e.to_string())),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// Compare tokens without returning early on the first differing byte.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
@@ -1,6 +1,8 @@
 //! HTTP route handlers.
 
+pub mod admin;
 pub mod commands;
+pub mod debug;
 pub mod history;
 pub mod hooks;
 pub mod interactions;
@@ -9,8 +11,128 @@ pub mod prompts;
 pub mod sessions;
 pub mod ws;
 
-use axum::Json;
+use crate::preflight::{self, PreflightEnv, PreflightReport};
+use crate::state::{AppState, PROFILE_HEADER};
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use clauset_core::InteractionStore;
 use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use tower_http::services::ServeDir;
+
+/// The profile named in the request's `X-Clauset-Profile` header, if any.
+fn requested_profile(headers: &HeaderMap) -> Result<Option<&str>, (StatusCode, String)> {
+    headers
+        .get(PROFILE_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid profile header".to_string()))
+        })
+        .transpose()
+}
+
+/// The interaction store selected by the request's profile header. Reads and
+/// writes through it both go to that profile's database.
+pub(crate) fn profile_store(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Arc<InteractionStore>, (StatusCode, String)> {
+    let profile = requested_profile(headers)?;
+    state.interaction_store(profile).cloned().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Unknown profile: {}", profile.unwrap_or_default()),
+        )
+    })
+}
+
+/// Reject a profile header on routes that only write to the default store,
+/// such as those that create sessions.
+pub(crate) fn reject_profile(headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    match requested_profile(headers)? {
+        Some(profile) => Err((
+            StatusCode::BAD_REQUEST,
+            format!("This route writes to the default profile and can't target profile {profile}"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Served at `/` when `static_dir` has no built frontend.
+const PLACEHOLDER_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Clauset</title></head>
+<body>
+<h1>Clauset is running</h1>
+<p>The web frontend isn't available: the configured <code>static_dir</code> has no
+<code>index.html</code>. Build it with <code>cd frontend &amp;&amp; npm run build</code>
+or point <code>static_dir</code> at the built files. The API is served under
+<code>/api</code>.</p>
+</body>
+</html>
+"#;
+
+/// Reject request bodies over `max_body_bytes` with a 413 that states the limit.
+pub fn limit_body_size<S>(router: Router<S>, max_body_bytes: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router
+        .layer(middleware::map_response(move |response: Response| async move {
+            if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+                return response;
+            }
+            (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Request body exceeds the {max_body_bytes} byte limit (max_body_bytes)"),
+            )
+                .into_response()
+        }))
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+}
+
+/// Serve the frontend from `static_dir` for every path the router doesn't
+/// handle.
+///
+/// Without an `index.html` there, startup fails when `require` is set;
+/// otherwise a warning is logged, `/` serves a page explaining what's
+/// missing and other paths get a 404 saying the same.
+pub fn serve_static<S>(router: Router<S>, static_dir: &Path, require: bool) -> anyhow::Result<Router<S>>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if static_dir.join("index.html").is_file() {
+        return Ok(router.fallback_service(ServeDir::new(static_dir)));
+    }
+    if require {
+        anyhow::bail!(
+            "static_dir {} has no index.html; build the frontend (cd frontend && npm run build) \
+             or unset require_static_dir",
+            static_dir.display()
+        );
+    }
+
+    tracing::warn!(
+        target: "clauset::startup",
+        "static_dir {} has no index.html, serving a placeholder page instead of the frontend",
+        static_dir.display()
+    );
+    let message = format!(
+        "Not found (the web frontend is not built: {} has no index.html)",
+        static_dir.display()
+    );
+    Ok(router
+        .route("/", get(|| async { Html(PLACEHOLDER_PAGE) }))
+        .fallback(move || async move { (StatusCode::NOT_FOUND, message) }))
+}
 
 #[derive(Serialize)]
 pub struct HealthResponse {
@@ -24,3 +146,14 @@ pub async fn health() -> Json<HealthResponse> {
         version: env!("CARGO_PKG_VERSION"),
     })
 }
+
+/// GET /api/preflight - Check the Claude Code environment before spawning sessions.
+pub async fn preflight(State(state): State<Arc<AppState>>) -> Json<PreflightReport> {
+    let env = PreflightEnv::from_config(&state.config());
+    // Probing the binary runs a subprocess
+    let report =
+        tokio::task::spawn_blocking(move || preflight::run_checks(&env, preflight::claude_version))
+            .await
+            .expect("preflight checks panicked");
+    Json(report)
+}
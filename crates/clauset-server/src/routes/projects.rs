@@ -7,6 +7,8 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -26,14 +28,15 @@ pub struct ProjectsResponse {
     pub projects_root: String,
 }
 
-/// List available projects in the projects root directory.
+/// List available projects: directories in the projects root plus projects
+/// registered by path, de-duplicated by canonical path.
 pub async fn list(State(state): State<Arc<AppState>>) -> Json<ProjectsResponse> {
-    let projects_root = &state.config.projects_root;
+    let projects_root = state.config().projects_root.clone();
     let mut projects = Vec::new();
 
     debug!("Scanning projects in {:?}", projects_root);
 
-    if let Ok(entries) = std::fs::read_dir(projects_root) {
+    if let Ok(entries) = std::fs::read_dir(&projects_root) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_dir() {
@@ -52,6 +55,24 @@ pub async fn list(State(state): State<Arc<AppState>>) -> Json<ProjectsResponse>
         warn!("Failed to read projects directory: {:?}", projects_root);
     }
 
+    let mut seen: HashSet<PathBuf> = projects
+        .iter()
+        .map(|p| canonical_or_self(Path::new(&p.path)))
+        .collect();
+    match state.session_manager.list_registered_projects() {
+        Ok(registered) => {
+            for project in registered {
+                if seen.insert(project.path.clone()) {
+                    projects.push(Project {
+                        name: project.name,
+                        path: project.path.to_string_lossy().to_string(),
+                    });
+                }
+            }
+        }
+        Err(e) => warn!("Failed to list registered projects: {}", e),
+    }
+
     // Sort by name
     projects.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
 
@@ -63,14 +84,25 @@ pub async fn list(State(state): State<Arc<AppState>>) -> Json<ProjectsResponse>
 
 #[derive(Deserialize)]
 pub struct CreateProjectRequest {
-    pub name: String,
+    /// Name of a new directory to create in the projects root, or the display
+    /// name when registering an existing `path`.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Existing directory to register. May be relative to the projects root or
+    /// start with `~`.
+    #[serde(default)]
+    pub path: Option<String>,
 }
 
 /// Error type for project creation.
+#[derive(Debug)]
 pub enum CreateProjectError {
     InvalidName(String),
     AlreadyExists,
+    NotFound(PathBuf),
+    NotADirectory(PathBuf),
     IoError(std::io::Error),
+    StoreError(clauset_core::ClausetError),
 }
 
 impl IntoResponse for CreateProjectError {
@@ -82,20 +114,113 @@ impl IntoResponse for CreateProjectError {
             CreateProjectError::AlreadyExists => {
                 (StatusCode::CONFLICT, "A project with this name already exists".to_string())
             }
+            CreateProjectError::NotFound(path) => {
+                (StatusCode::BAD_REQUEST, format!("Project path does not exist: {}", path.display()))
+            }
+            CreateProjectError::NotADirectory(path) => {
+                (StatusCode::BAD_REQUEST, format!("Project path is not a directory: {}", path.display()))
+            }
             CreateProjectError::IoError(e) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create project: {}", e))
             }
+            CreateProjectError::StoreError(e) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to save project: {}", e))
+            }
         };
         (status, message).into_response()
     }
 }
 
-/// Create a new project directory.
+/// Resolve a user-supplied project path to its canonical form.
+///
+/// Expands a leading `~` to `home`, resolves relative paths against `base`,
+/// follows symlinks, and requires the result to be an existing directory.
+pub fn normalize_project_path(
+    raw: &str,
+    base: &Path,
+    home: Option<&Path>,
+) -> Result<PathBuf, CreateProjectError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(CreateProjectError::InvalidName("path cannot be empty".to_string()));
+    }
+
+    let expanded = match (raw.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        (Some(_), None) => {
+            return Err(CreateProjectError::InvalidName(
+                "cannot expand '~' without a home directory".to_string(),
+            ));
+        }
+        _ => PathBuf::from(raw),
+    };
+    let absolute = if expanded.is_absolute() { expanded } else { base.join(expanded) };
+
+    let canonical = std::fs::canonicalize(&absolute).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => CreateProjectError::NotFound(absolute.clone()),
+        _ => CreateProjectError::IoError(e),
+    })?;
+    if !canonical.is_dir() {
+        return Err(CreateProjectError::NotADirectory(canonical));
+    }
+    Ok(canonical)
+}
+
+pub(crate) fn canonical_or_self(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Whether `path` is a known project: a visible directory in the projects
+/// root or one registered by path.
+pub(crate) fn is_project(state: &AppState, path: &Path) -> bool {
+    let Ok(path) = std::fs::canonicalize(path) else {
+        return false;
+    };
+    let in_root = path.parent() == Some(canonical_or_self(&state.config().projects_root).as_path())
+        && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| !n.starts_with('.'));
+    in_root
+        || state
+            .session_manager
+            .list_registered_projects()
+            .is_ok_and(|registered| registered.iter().any(|p| p.path == path))
+}
+
+/// Register an existing directory as a project, storing its canonical path.
+fn register_existing(state: &AppState, raw_path: &str, name: Option<&str>) -> Result<Project, CreateProjectError> {
+    let base = canonical_or_self(&state.config().projects_root);
+    let path = normalize_project_path(raw_path, &base, dirs::home_dir().as_deref())?;
+
+    let name = match name.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => name.to_string(),
+        None => path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string()),
+    };
+
+    let project = state
+        .session_manager
+        .register_project(&path, &name)
+        .map_err(CreateProjectError::StoreError)?;
+
+    Ok(Project {
+        name: project.name,
+        path: project.path.to_string_lossy().to_string(),
+    })
+}
+
+/// Create a new project directory, or register an existing one by path.
 pub async fn create(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateProjectRequest>,
 ) -> Result<Json<Project>, CreateProjectError> {
-    let name = req.name.trim();
+    if let Some(path) = &req.path {
+        return register_existing(&state, path, req.name.as_deref()).map(Json);
+    }
+
+    let name = req.name.as_deref().unwrap_or_default().trim();
 
     // Validate project name
     if name.is_empty() {
@@ -111,7 +236,7 @@ pub async fn create(
         return Err(CreateProjectError::InvalidName("name cannot start with '.'".to_string()));
     }
 
-    let project_path = state.config.projects_root.join(name);
+    let project_path = state.config().projects_root.join(name);
 
     // Check if already exists
     if project_path.exists() {
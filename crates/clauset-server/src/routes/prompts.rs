@@ -1,13 +1,19 @@
 //! Prompt Library routes.
+//!
+//! Prompts are read from the default interaction database unless the request
+//! names a configured profile in the `X-Clauset-Profile` header.
 
+use crate::routes::{profile_store, projects};
 use crate::state::AppState;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     Json,
 };
+use clauset_core::PromptIndexer;
 use clauset_types::{Prompt, PromptSummary};
 use serde::{Deserialize, Serialize};
+use std::path::{Path as FsPath, PathBuf};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -35,9 +41,10 @@ pub struct PromptsListResponse {
 /// GET /api/prompts - List prompts with pagination.
 pub async fn list_prompts(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(query): Query<ListPromptsQuery>,
 ) -> Result<Json<PromptsListResponse>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
 
     let prompts = store
         .list_prompts(query.limit, query.offset)
@@ -59,9 +66,10 @@ pub async fn list_prompts(
 /// GET /api/prompts/{id} - Get a single prompt by ID.
 pub async fn get_prompt(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Path(id): Path<Uuid>,
 ) -> Result<Json<Prompt>, (StatusCode, String)> {
-    let store = state.interaction_processor.store();
+    let store = profile_store(&state, &headers)?;
 
     let prompt = store
         .get_prompt(id)
@@ -70,3 +78,123 @@ pub async fn get_prompt(
 
     Ok(Json(prompt))
 }
+
+/// Query parameters for listing common prompt phrases.
+#[derive(Deserialize)]
+pub struct PhrasesQuery {
+    /// Number of phrases to return
+    #[serde(default = "default_phrase_limit")]
+    pub limit: usize,
+    /// Words per phrase
+    #[serde(default = "default_ngram")]
+    pub ngram: usize,
+}
+
+fn default_phrase_limit() -> usize {
+    20
+}
+
+fn default_ngram() -> usize {
+    2
+}
+
+/// A phrase and how many prompt uses contain it.
+#[derive(Serialize)]
+pub struct PhraseCount {
+    pub phrase: String,
+    pub count: u32,
+}
+
+/// GET /api/prompts/phrases - Most common phrases across indexed prompts.
+pub async fn list_phrases(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<PhrasesQuery>,
+) -> Result<Json<Vec<PhraseCount>>, (StatusCode, String)> {
+    let indexer = PromptIndexer::with_claude_dir(profile_store(&state, &headers)?, state.config().claude_data_dir());
+    let phrases = indexer
+        .top_phrases(query.limit, query.ngram)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(
+        phrases
+            .into_iter()
+            .map(|(phrase, count)| PhraseCount { phrase, count })
+            .collect(),
+    ))
+}
+
+/// Request body for exporting prompts as commands.
+#[derive(Deserialize)]
+pub struct ExportCommandsRequest {
+    /// Directory to write command files to: `~/.claude/commands` (the
+    /// default) or a project's `.claude/commands`
+    pub dir: Option<PathBuf>,
+}
+
+/// Resolve a requested export directory, which must be the `.claude/commands`
+/// directory of the home directory or of a known project.
+fn commands_dir(state: &AppState, dir: &FsPath, home: Option<&FsPath>) -> Result<PathBuf, (StatusCode, String)> {
+    let rejected = || {
+        (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Commands can only be exported to ~/.claude/commands or a project's .claude/commands, not {}",
+                dir.display()
+            ),
+        )
+    };
+    let root = Some(dir)
+        .filter(|dir| dir.ends_with(".claude/commands"))
+        .and_then(FsPath::parent)
+        .and_then(FsPath::parent)
+        .filter(|root| !root.as_os_str().is_empty())
+        .ok_or_else(rejected)?;
+    let root = std::fs::canonicalize(root).map_err(|_| rejected())?;
+    let is_home = home.is_some_and(|home| projects::canonical_or_self(home) == root);
+    if !is_home && !projects::is_project(state, &root) {
+        return Err(rejected());
+    }
+
+    // Follow whatever part of .claude/commands exists, so a symlinked
+    // `.claude` or `commands` can't point the export outside the root
+    let dir = root.join(".claude").join("commands");
+    let existing = dir
+        .ancestors()
+        .find(|path| path.symlink_metadata().is_ok())
+        .ok_or_else(rejected)?;
+    let resolved = std::fs::canonicalize(existing).map_err(|_| rejected())?;
+    if !resolved.starts_with(&root) {
+        return Err(rejected());
+    }
+    Ok(resolved.join(dir.strip_prefix(existing).map_err(|_| rejected())?))
+}
+
+/// Response for exporting prompts as commands.
+#[derive(Serialize)]
+pub struct ExportCommandsResponse {
+    pub dir: PathBuf,
+    pub exported: u32,
+}
+
+/// POST /api/prompts/export-commands - Write frequently used prompts as
+/// Claude Code slash commands. The request body is optional.
+pub async fn export_commands(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<ExportCommandsRequest>>,
+) -> Result<Json<ExportCommandsResponse>, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+    let home = dirs::home_dir();
+    let dir = match body.and_then(|Json(req)| req.dir) {
+        Some(dir) => commands_dir(&state, &dir, home.as_deref())?,
+        None => state.config().claude_data_dir().join("commands"),
+    };
+
+    let indexer = PromptIndexer::with_claude_dir(store, state.config().claude_data_dir());
+    let exported = indexer
+        .export_as_commands(&dir)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ExportCommandsResponse { dir, exported }))
+}
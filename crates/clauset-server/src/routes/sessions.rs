@@ -1,14 +1,20 @@
 //! Session management routes.
 
+use crate::bundle;
+use crate::routes::{profile_store, projects, reject_profile};
 use crate::state::AppState;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use clauset_core::{ClaudeSessionReader, CreateSessionOptions};
-use clauset_types::{SessionMode, SessionStatus, SessionSummary};
+use clauset_core::{
+    ActivityState, ByteRange, ClausetError, CreateSessionOptions, CustomHookEvent, PermissionModeChange, SessionNameChange,
+};
+use clauset_types::{Plan, Session, SessionMode, SessionStatus, SessionSummary, TodoItem};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tracing::{error, info, warn};
@@ -20,14 +26,61 @@ pub struct SessionListResponse {
     pub active_count: usize,
 }
 
+#[derive(Deserialize)]
+pub struct SessionListQuery {
+    /// Only list sessions carrying this tag
+    pub tag: Option<String>,
+    /// Only list sessions whose metadata has this key set to `meta_value`
+    pub meta_key: Option<String>,
+    pub meta_value: Option<String>,
+    /// Only list sessions whose live activity is in this state
+    pub state: Option<ActivityState>,
+}
+
 pub async fn list(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SessionListQuery>,
 ) -> Result<Json<SessionListResponse>, (StatusCode, String)> {
-    let sessions = state
-        .session_manager
-        .list_sessions()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let store = profile_store(&state, &headers)?;
+    let mut sessions = match query.tag {
+        Some(tag) => state.session_manager.list_sessions_by_tag(&tag).await,
+        None => state.session_manager.list_sessions().await,
+    }
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if let Some(key) = query.meta_key {
+        let value = query.meta_value.ok_or((
+            StatusCode::BAD_REQUEST,
+            "meta_value is required with meta_key".to_string(),
+        ))?;
+        let ids: HashSet<Uuid> = state
+            .session_manager
+            .session_ids_with_metadata(&key, &value)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .into_iter()
+            .collect();
+        sessions.retain(|s| ids.contains(&s.id));
+    }
+
+    if let Some(activity_state) = query.state {
+        let ids: HashSet<Uuid> = state
+            .session_manager
+            .buffers()
+            .sessions_by_state(activity_state)
+            .await
+            .into_iter()
+            .collect();
+        sessions.retain(|s| ids.contains(&s.id));
+    }
+
+    for session in &mut sessions {
+        match store.generate_session_summary(session.id) {
+            Ok(summary) if !summary.is_empty() => session.subtitle = Some(summary),
+            Ok(_) => {}
+            Err(e) => warn!(target: "clauset::session", "Failed to summarize session {}: {}", session.id, e),
+        }
+    }
 
     let active_count = sessions
         .iter()
@@ -94,16 +147,113 @@ pub async fn create(
     }))
 }
 
+/// Session detail, including its rename history.
+#[derive(Serialize)]
+pub struct SessionDetailResponse {
+    #[serde(flatten)]
+    pub session: clauset_types::Session,
+    pub name_history: Vec<SessionNameChange>,
+}
+
 pub async fn get(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<clauset_types::Session>, (StatusCode, String)> {
+) -> Result<Json<SessionDetailResponse>, (StatusCode, String)> {
     let session = state
         .session_manager
         .get_session(id)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or((StatusCode::NOT_FOUND, "Session not found".to_string()))?;
 
+    let name_history = state
+        .session_manager
+        .session_name_history(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(SessionDetailResponse {
+        session,
+        name_history,
+    }))
+}
+
+/// When a session's permission mode changed, oldest first.
+pub async fn get_mode_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<PermissionModeChange>>, (StatusCode, String)> {
+    state
+        .session_manager
+        .get_session(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Session not found".to_string()))?;
+
+    let timeline = state
+        .session_manager
+        .permission_mode_timeline(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(timeline))
+}
+
+/// GET /api/sessions/{id}/transcript.txt - The session's interactions as
+/// plain text, for piping into other tools.
+pub async fn get_transcript_text(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Response, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+    state
+        .session_manager
+        .get_session(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Session not found".to_string()))?;
+
+    let transcript = store
+        .session_transcript_text(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], transcript).into_response())
+}
+
+#[derive(Deserialize)]
+pub struct UpdateSessionRequest {
+    #[serde(default)]
+    pub model: Option<String>,
+    /// May be relative to the projects root or start with `~`.
+    #[serde(default)]
+    pub project_path: Option<String>,
+}
+
+/// Correct a session's model and/or project path.
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateSessionRequest>,
+) -> Result<Json<Session>, Response> {
+    let model = match req.model.as_deref().map(str::trim) {
+        Some("") => {
+            return Err((StatusCode::BAD_REQUEST, "Model cannot be empty".to_string()).into_response());
+        }
+        model => model,
+    };
+    let project_path = match req.project_path {
+        Some(raw) => {
+            let base = projects::canonical_or_self(&state.config().projects_root);
+            let path = projects::normalize_project_path(&raw, &base, dirs::home_dir().as_deref())
+                .map_err(IntoResponse::into_response)?;
+            Some(path)
+        }
+        None => None,
+    };
+
+    let session = state
+        .session_manager
+        .update_session_metadata(id, model, project_path.as_deref())
+        .await
+        .map_err(|e| match e {
+            ClausetError::SessionNotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }.into_response())?;
+
     Ok(Json(session))
 }
 
@@ -147,15 +297,46 @@ pub async fn start(
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+pub struct ResumeSessionRequest {
+    /// Directory to resume in instead of the session's project path. May be
+    /// relative to the projects root or start with `~`.
+    #[serde(default)]
+    pub working_dir: Option<String>,
+}
+
+/// Resume a session. The request body is optional.
 pub async fn resume(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
+    req: Option<Json<ResumeSessionRequest>>,
+) -> Result<StatusCode, Response> {
+    let working_dir = match req.and_then(|Json(req)| req.working_dir) {
+        Some(raw) => {
+            let base = projects::canonical_or_self(&state.config().projects_root);
+            let path = projects::normalize_project_path(&raw, &base, dirs::home_dir().as_deref())
+                .map_err(IntoResponse::into_response)?;
+            if !projects::is_project(&state, &path) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "working_dir must be a project under the projects root or a registered project".to_string(),
+                )
+                    .into_response());
+            }
+            Some(path)
+        }
+        None => None,
+    };
+
     state
         .session_manager
-        .resume_session(id)
+        .resume_session_in(id, working_dir)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| match e {
+            ClausetError::SessionNotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
+            ClausetError::TranscriptNotFound(_) => (StatusCode::CONFLICT, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }.into_response())?;
 
     Ok(StatusCode::OK)
 }
@@ -179,6 +360,47 @@ pub async fn send_input(
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+pub struct MenuSelectRequest {
+    /// 0-based index of the option to select.
+    pub index: usize,
+    /// Menu the selection was made in; rejected if a different menu is open.
+    #[serde(default)]
+    pub menu_id: Option<String>,
+}
+
+/// Select an option in the TUI menu currently open in a session.
+pub async fn menu_select(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<MenuSelectRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let menu = state
+        .session_manager
+        .get_tui_menu(id)
+        .await
+        .ok_or((StatusCode::NOT_FOUND, "No menu is open".to_string()))?;
+
+    if let Some(menu_id) = &req.menu_id
+        && menu_id != &menu.id
+    {
+        return Err((StatusCode::CONFLICT, format!("Menu {} is no longer open", menu_id)));
+    }
+
+    let input = menu.input_for_selection(req.index).ok_or((
+        StatusCode::BAD_REQUEST,
+        format!("Option {} out of range (menu has {})", req.index, menu.options.len()),
+    ))?;
+
+    state
+        .session_manager
+        .send_terminal_input(id, &input)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(StatusCode::OK)
+}
+
 /// Delete a session permanently.
 pub async fn delete(
     State(state): State<Arc<AppState>>,
@@ -193,6 +415,58 @@ pub async fn delete(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Default idle age before an empty session is eligible for cleanup.
+const DEFAULT_EMPTY_SESSION_AGE_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Deserialize)]
+pub struct EmptySessionsQuery {
+    /// Only include sessions idle for at least this many seconds (default: one day)
+    pub older_than_secs: Option<u64>,
+}
+
+impl EmptySessionsQuery {
+    fn older_than(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.older_than_secs.unwrap_or(DEFAULT_EMPTY_SESSION_AGE_SECS))
+    }
+}
+
+#[derive(Serialize)]
+pub struct EmptySessionsResponse {
+    pub sessions: Vec<SessionSummary>,
+}
+
+/// List sessions that were spawned but never recorded an interaction.
+pub async fn list_empty(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EmptySessionsQuery>,
+) -> Result<Json<EmptySessionsResponse>, (StatusCode, String)> {
+    let sessions = state
+        .session_manager
+        .list_empty_sessions(query.older_than())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(EmptySessionsResponse { sessions }))
+}
+
+#[derive(Serialize)]
+pub struct CleanupEmptySessionsResponse {
+    pub deleted: Vec<Uuid>,
+}
+
+/// Delete all sessions that were spawned but never recorded an interaction.
+pub async fn cleanup_empty(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EmptySessionsQuery>,
+) -> Result<Json<CleanupEmptySessionsResponse>, (StatusCode, String)> {
+    let deleted = state
+        .session_manager
+        .delete_empty_sessions(query.older_than())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    info!(target: "clauset::session", "Cleaned up {} empty sessions", deleted.len());
+    Ok(Json(CleanupEmptySessionsResponse { deleted }))
+}
+
 #[derive(Deserialize)]
 pub struct RenameSessionRequest {
     pub name: String,
@@ -207,11 +481,320 @@ pub async fn rename(
     state
         .session_manager
         .rename_session(id, &req.name)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| match e {
+            ClausetError::SessionNotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+
+    Ok(StatusCode::OK)
+}
 
+#[derive(Deserialize)]
+pub struct PinSessionRequest {
+    pub pinned: bool,
+}
+
+/// Pin or unpin a session, exempting it from automatic cleanup and retention.
+pub async fn pin(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<PinSessionRequest>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    state.session_manager.set_session_pinned(id, req.pinned).map_err(annotation_error)?;
     Ok(StatusCode::OK)
 }
 
+// === Session Tags ===
+
+#[derive(Deserialize)]
+pub struct SessionTagRequest {
+    pub tag: String,
+}
+
+#[derive(Serialize)]
+pub struct SessionTagsResponse {
+    pub tags: Vec<String>,
+}
+
+/// Map tag and metadata errors to HTTP statuses.
+fn annotation_error(e: ClausetError) -> (StatusCode, String) {
+    match e {
+        ClausetError::SessionNotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
+        ClausetError::ParseError(_) => (StatusCode::BAD_REQUEST, e.to_string()),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+fn session_tags(state: &AppState, id: Uuid) -> Result<Json<SessionTagsResponse>, (StatusCode, String)> {
+    let tags = state.session_manager.session_tags(id).map_err(annotation_error)?;
+    Ok(Json(SessionTagsResponse { tags }))
+}
+
+/// List a session's tags.
+pub async fn list_tags(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SessionTagsResponse>, (StatusCode, String)> {
+    session_tags(&state, id)
+}
+
+/// Tag a session. Returns the session's tags afterwards.
+pub async fn add_tag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SessionTagRequest>,
+) -> Result<Json<SessionTagsResponse>, (StatusCode, String)> {
+    state.session_manager.add_session_tag(id, &req.tag).map_err(annotation_error)?;
+    session_tags(&state, id)
+}
+
+/// Remove a tag from a session. Returns the session's tags afterwards.
+pub async fn remove_tag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SessionTagRequest>,
+) -> Result<Json<SessionTagsResponse>, (StatusCode, String)> {
+    state.session_manager.remove_session_tag(id, &req.tag).map_err(annotation_error)?;
+    session_tags(&state, id)
+}
+
+// === Session Metadata ===
+
+#[derive(Deserialize)]
+pub struct SetSessionMetadataRequest {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteSessionMetadataRequest {
+    pub key: String,
+}
+
+#[derive(Serialize)]
+pub struct SessionMetadataResponse {
+    pub metadata: BTreeMap<String, String>,
+}
+
+fn session_metadata(state: &AppState, id: Uuid) -> Result<Json<SessionMetadataResponse>, (StatusCode, String)> {
+    let metadata = state.session_manager.session_metadata(id).map_err(annotation_error)?;
+    Ok(Json(SessionMetadataResponse { metadata }))
+}
+
+/// Get a session's metadata.
+pub async fn get_metadata(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SessionMetadataResponse>, (StatusCode, String)> {
+    session_metadata(&state, id)
+}
+
+/// Set a metadata key on a session. Returns the session's metadata afterwards.
+pub async fn set_metadata(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetSessionMetadataRequest>,
+) -> Result<Json<SessionMetadataResponse>, (StatusCode, String)> {
+    state
+        .session_manager
+        .set_session_metadata(id, &req.key, &req.value)
+        .map_err(annotation_error)?;
+    session_metadata(&state, id)
+}
+
+/// Remove a metadata key from a session. Returns the session's metadata afterwards.
+pub async fn delete_metadata(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<DeleteSessionMetadataRequest>,
+) -> Result<Json<SessionMetadataResponse>, (StatusCode, String)> {
+    state.session_manager.delete_session_metadata(id, &req.key).map_err(annotation_error)?;
+    session_metadata(&state, id)
+}
+
+#[derive(Serialize)]
+pub struct SessionTodosResponse {
+    pub session_id: Uuid,
+    pub todos: Vec<TodoItem>,
+}
+
+/// Get the session's current todo list (from Claude's latest TodoWrite call).
+///
+/// Sessions without TodoWrite hooks (e.g. imported ones) fall back to the
+/// latest TodoWrite in Claude's transcript.
+pub async fn get_todos(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SessionTodosResponse>, (StatusCode, String)> {
+    let mut todos = state
+        .interaction_processor_for(id)
+        .todos(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if todos.is_empty()
+        && let Ok(Some(session)) = state.session_manager.get_session(id)
+    {
+        match state.session_manager.claude_reader()
+            .latest_todos(&session.claude_session_id.to_string(), &session.project_path)
+        {
+            Ok(transcript_todos) => todos = transcript_todos.unwrap_or_default(),
+            Err(e) => warn!(target: "clauset::session", "Failed to read todos from transcript for session {}: {}", id, e),
+        }
+    }
+    Ok(Json(SessionTodosResponse { session_id: id, todos }))
+}
+
+#[derive(Serialize)]
+pub struct SessionPlanResponse {
+    pub session_id: Uuid,
+    /// None until Claude has proposed a plan
+    pub plan: Option<Plan>,
+}
+
+/// Get the plan Claude last proposed in plan mode (its latest ExitPlanMode call).
+pub async fn get_plan(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SessionPlanResponse>, (StatusCode, String)> {
+    let plan = profile_store(&state, &headers)?
+        .latest_tool_input(id, "ExitPlanMode")
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .and_then(|input| Plan::from_tool_input(&input));
+    Ok(Json(SessionPlanResponse { session_id: id, plan }))
+}
+
+/// Response for a session's custom hook events.
+#[derive(Serialize)]
+pub struct CustomHookEventsResponse {
+    pub session_id: Uuid,
+    pub events: Vec<CustomHookEvent>,
+}
+
+/// List the session's hook events with unrecognized names, oldest first.
+pub async fn list_custom_events(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CustomHookEventsResponse>, (StatusCode, String)> {
+    let events = profile_store(&state, &headers)?
+        .list_custom_hook_events(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(CustomHookEventsResponse { session_id: id, events }))
+}
+
+/// Download a zip bundle of the session's interactions, changes, analytics,
+/// and terminal log (see [`bundle`]).
+pub async fn download_bundle(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Response, (StatusCode, String)> {
+    let store = profile_store(&state, &headers)?;
+    state
+        .session_manager
+        .get_session(id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Session not found".to_string()))?;
+
+    let terminal = state
+        .session_manager
+        .get_terminal_buffer_or_persisted(id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let zip = tokio::task::spawn_blocking(move || bundle::build_bundle(&store, id, terminal.as_deref()))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"session-{}.zip\"", id),
+            ),
+        ],
+        zip,
+    )
+        .into_response())
+}
+
+/// Download a session's terminal buffer as raw bytes.
+///
+/// Honors a single `Range: bytes=…` header with `206 Partial Content`, so
+/// clients can fetch the tail of a large buffer or resume a download.
+/// Multi-range requests are served the whole buffer. Offsets move when old
+/// output is evicted, so the `ETag` changes with them; a resumed download
+/// sends it as `If-Range` and gets the whole buffer again if it no longer
+/// matches.
+pub async fn download_buffer(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let mut requested = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_range_header);
+    let if_range = headers.get(header::IF_RANGE).and_then(|value| value.to_str().ok());
+
+    let buffers = state.session_manager.buffers();
+    let Some(mut slice) = buffers.read_byte_range(id, requested.unwrap_or(ByteRange::From(0))).await else {
+        return (StatusCode::NOT_FOUND, "No terminal buffer for session".to_string()).into_response();
+    };
+    if requested.is_some() && if_range.is_some_and(|validator| validator != buffer_etag(slice.start_seq)) {
+        requested = None;
+        match buffers.read_byte_range(id, ByteRange::From(0)).await {
+            Some(full) => slice = full,
+            None => return (StatusCode::NOT_FOUND, "No terminal buffer for session".to_string()).into_response(),
+        }
+    }
+
+    let common = [
+        (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+        (header::ACCEPT_RANGES, "bytes".to_string()),
+        (header::ETAG, buffer_etag(slice.start_seq)),
+    ];
+    match (requested, slice.range) {
+        (Some(_), Some((start, end))) => (
+            StatusCode::PARTIAL_CONTENT,
+            common,
+            [(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, slice.total_len))],
+            slice.data,
+        )
+            .into_response(),
+        (Some(_), None) => (
+            StatusCode::RANGE_NOT_SATISFIABLE,
+            common,
+            [(header::CONTENT_RANGE, format!("bytes */{}", slice.total_len))],
+        )
+            .into_response(),
+        // No (usable) Range header: the whole buffer, possibly empty
+        (None, _) => (StatusCode::OK, common, slice.data).into_response(),
+    }
+}
+
+/// Validator for buffer byte offsets, which hold while the start is unchanged.
+fn buffer_etag(start_seq: u64) -> String {
+    format!("\"{}\"", start_seq)
+}
+
+/// Parse a single-range `bytes=` header value.
+fn parse_range_header(value: &str) -> Option<ByteRange> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    match (start.is_empty(), end.is_empty()) {
+        (false, true) => start.parse().ok().map(ByteRange::From),
+        (false, false) => Some(ByteRange::FromTo(start.parse().ok()?, end.parse().ok()?)),
+        (true, false) => end.parse().ok().map(ByteRange::Suffix),
+        (true, true) => None,
+    }
+}
+
 // === Claude Sessions from ~/.claude ===
 
 #[derive(Deserialize)]
@@ -259,7 +842,7 @@ pub async fn list_claude_sessions(
     State(state): State<Arc<AppState>>,
     Query(query): Query<ClaudeSessionsQuery>,
 ) -> Result<Json<ClaudeSessionsListResponse>, (StatusCode, String)> {
-    let reader = ClaudeSessionReader::new();
+    let reader = state.session_manager.claude_reader();
 
     let claude_sessions = reader
         .list_sessions_for_project(&query.project_path)
@@ -294,10 +877,11 @@ pub async fn list_claude_sessions(
 
 /// Read the full transcript for a Claude session.
 pub async fn get_claude_transcript(
+    State(state): State<Arc<AppState>>,
     Path(session_id): Path<String>,
     Query(query): Query<ClaudeTranscriptQuery>,
 ) -> Result<Json<ClaudeTranscriptResponse>, (StatusCode, String)> {
-    let reader = ClaudeSessionReader::new();
+    let reader = state.session_manager.claude_reader();
     let messages = reader
         .read_transcript(&session_id, &query.project_path)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
@@ -332,10 +916,12 @@ pub struct ImportSessionResponse {
 /// imports the chat history from the transcript, and sets status to Stopped.
 pub async fn import_session(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(req): Json<ImportSessionRequest>,
 ) -> Result<Json<ImportSessionResponse>, (StatusCode, String)> {
+    reject_profile(&headers)?;
     // Verify the session exists in Claude's storage
-    let reader = ClaudeSessionReader::new();
+    let reader = state.session_manager.claude_reader();
     let claude_session = reader
         .get_session(&req.claude_session_id)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
@@ -390,6 +976,7 @@ pub async fn import_session(
             is_streaming: false,
             is_complete: true,
             timestamp: msg.timestamp.timestamp_millis() as u64,
+            first_token_at: None,
         };
 
         if let Err(e) = store.save_chat_message(&chat_msg) {
@@ -2,12 +2,14 @@
 
 use crate::state::AppState;
 use crate::websocket::handle_websocket;
+use crate::ws_protocol::{negotiate_upgrade, WsProtocol};
 use axum::{
     extract::{
         ws::{WebSocket, WebSocketUpgrade},
         Path, State,
     },
-    response::Response,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
 };
 use std::sync::Arc;
 use uuid::Uuid;
@@ -15,13 +17,18 @@ use uuid::Uuid;
 pub async fn upgrade(
     State(state): State<Arc<AppState>>,
     Path(session_id): Path<Uuid>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_connection(socket, state, session_id))
+    let (ws, protocol) = match negotiate_upgrade(ws, &headers) {
+        Ok(negotiated) => negotiated,
+        Err(rejection) => return rejection.into_response(),
+    };
+    ws.on_upgrade(move |socket| handle_connection(socket, state, session_id, protocol))
 }
 
-async fn handle_connection(socket: WebSocket, state: Arc<AppState>, session_id: Uuid) {
-    if let Err(e) = handle_websocket(socket, state, session_id).await {
+async fn handle_connection(socket: WebSocket, state: Arc<AppState>, session_id: Uuid, protocol: WsProtocol) {
+    if let Err(e) = handle_websocket(socket, state, session_id, protocol).await {
         tracing::error!(target: "clauset::ws", "WebSocket error for session {}: {}", session_id, e);
     }
 }
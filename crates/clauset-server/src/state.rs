@@ -1,21 +1,51 @@
 //! Shared application state.
 
-use crate::config::Config;
+use crate::config::{Config, ConfigReload, RELOADABLE_FIELDS};
 use crate::interaction_processor::InteractionProcessor;
+use crate::tasks::TaskRegistry;
+use crate::webhook::WebhookMirror;
 use clauset_core::{
-    ChatProcessor, CommandDiscovery, HistoryWatcher, InteractionStore, SessionManager,
-    SessionManagerConfig,
+    ChatProcessor, CommandDiscovery, HistoryWatcher, InteractionStore, QuietHours, SessionManager,
+    SessionManagerConfig, SessionStore,
 };
-use std::sync::{Arc, Mutex};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Request header naming the profile whose interaction database to read.
+pub const PROFILE_HEADER: &str = "x-clauset-profile";
+
+/// A profile's interaction database and the projects captured into it.
+pub struct Profile {
+    pub processor: Arc<InteractionProcessor>,
+    /// Session rows in the profile database, which its interactions reference
+    sessions: SessionStore,
+    /// Sessions in these directories (or below) are captured into the profile
+    projects: Vec<PathBuf>,
+}
 
 /// Shared application state.
 pub struct AppState {
     pub session_manager: Arc<SessionManager>,
     pub history_watcher: Arc<HistoryWatcher>,
     pub interaction_processor: Arc<InteractionProcessor>,
+    /// Interaction databases of the configured profiles, by name
+    pub profiles: HashMap<String, Profile>,
+    /// Profile each session's hook events are captured into (None: default)
+    session_profiles: DashMap<Uuid, Option<String>>,
     pub chat_processor: Arc<ChatProcessor>,
     pub command_discovery: Mutex<CommandDiscovery>,
-    pub config: Config,
+    pub tasks: Arc<TaskRegistry>,
+    /// Gate heavy background tasks wait on during quiet hours
+    pub quiet_hours: Arc<QuietHours>,
+    /// External webhook that hook events are mirrored to, if configured
+    pub hook_webhook: Option<Arc<WebhookMirror>>,
+    config: RwLock<Config>,
+    /// File the config was loaded from, re-read on reload
+    config_path: Option<PathBuf>,
 }
 
 impl AppState {
@@ -29,22 +59,212 @@ impl AppState {
             max_concurrent_sessions: config.max_concurrent_sessions,
             default_model: config.default_model.clone(),
             clauset_url,
+            ready_detection: config.ready_detection,
+            pricing: config.pricing_table(),
+            eviction_policy: config.eviction_policy,
+            oversized_chunk_policy: config.oversized_chunk_policy,
+            activity_keywords: config.activity_keywords.clone(),
+            activity_history_size: config.activity_history_size,
+            action_source: config.action_source,
+            hook_action_window: Duration::from_secs(config.hook_action_window_secs),
+            change_threshold: config.change_threshold,
+            pty_read: config.pty_read,
+            buffer_compression_level: config.buffer_compression_level,
+            heartbeat_interval: config.heartbeat_interval_secs.map(Duration::from_secs),
+            idle_input_timeout: config.idle_input_timeout_secs.map(Duration::from_secs),
+            echo_suppression: config.suppress_input_echo,
+            archive_dir: config.archive_dir.clone(),
+            archive_strip_ansi: config.archive_strip_ansi,
+            claude_dir: config.claude_dir.clone(),
         };
 
         let session_manager = Arc::new(SessionManager::new(session_config)?);
-        let history_watcher = Arc::new(HistoryWatcher::default());
-        let interaction_store = Arc::new(InteractionStore::open(&config.db_path)?);
-        let interaction_processor = Arc::new(InteractionProcessor::new(interaction_store.clone()));
-        let chat_processor = Arc::new(ChatProcessor::with_store(interaction_store));
-        let command_discovery = Mutex::new(CommandDiscovery::new());
+        let history_watcher = Arc::new(
+            HistoryWatcher::with_paths(vec![config.claude_data_dir().join("history.jsonl")])
+                .unwrap_or_default(),
+        );
+        let interaction_store = Arc::new(
+            InteractionStore::open(&config.db_path)?
+                .with_fts_query_limits(config.search_query_limits)
+                .with_tool_input_limits(config.tool_input_limits),
+        );
+        let new_processor = |store: Arc<InteractionStore>| {
+            Arc::new(
+                InteractionProcessor::new(store)
+                    .with_tracked_tools(config.tracked_tools.clone())
+                    .with_max_prompt_length(config.max_prompt_length)
+                    .with_snapshot_exclude(config.snapshot_exclude.clone())
+                    .with_output_preview(config.tool_output_preview)
+                    .with_tool_output_previews(config.tool_output_preview_overrides.clone()),
+            )
+        };
+        let interaction_processor = new_processor(interaction_store.clone());
+        let chat_processor = Arc::new(
+            ChatProcessor::with_store(interaction_store).with_output_preview(config.tool_output_preview),
+        );
+        let profiles = config
+            .profiles
+            .iter()
+            .map(|(name, profile)| {
+                // Interactions reference the sessions table, so set up the full schema
+                let sessions = SessionStore::open(&profile.db_path)?;
+                let store = InteractionStore::open(&profile.db_path)?
+                    .with_fts_query_limits(config.search_query_limits)
+                    .with_tool_input_limits(config.tool_input_limits);
+                let profile = Profile {
+                    processor: new_processor(Arc::new(store)),
+                    sessions,
+                    projects: profile.projects.clone(),
+                };
+                Ok((name.clone(), profile))
+            })
+            .collect::<clauset_core::Result<_>>()?;
+        let command_discovery = Mutex::new(CommandDiscovery::with_claude_dir(config.claude_data_dir()));
+        let hook_webhook = config.hook_webhook.clone().and_then(|webhook| match WebhookMirror::new(webhook) {
+            Ok(mirror) => Some(Arc::new(mirror)),
+            Err(e) => {
+                tracing::warn!(target: "clauset::startup", "Hook webhook disabled: {}", e);
+                None
+            }
+        });
 
         Ok(Self {
             session_manager,
             history_watcher,
             interaction_processor,
+            profiles,
+            session_profiles: DashMap::new(),
             chat_processor,
             command_discovery,
-            config,
+            tasks: Arc::new(TaskRegistry::new()),
+            quiet_hours: Arc::new(QuietHours::new(config.quiet_hours.clone())),
+            hook_webhook,
+            config: RwLock::new(config),
+            config_path: None,
         })
     }
+
+    /// Record the file the config came from so it can be reloaded.
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// The live configuration. Reloadable fields reflect the last reload.
+    pub fn config(&self) -> RwLockReadGuard<'_, Config> {
+        self.config.read().unwrap()
+    }
+
+    /// Re-read the config file and apply the changed `RELOADABLE_FIELDS`.
+    /// Other changed fields are reported as needing a restart and left as-is.
+    pub fn reload_config(&self) -> anyhow::Result<ConfigReload> {
+        let path = self
+            .config_path
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Server was not started from a config file"))?;
+        let new = Config::load_from(path)?;
+
+        let mut reload = ConfigReload::default();
+        let mut config = self.config.write().unwrap();
+        for field in config.changed_fields(&new) {
+            if RELOADABLE_FIELDS.contains(&field) {
+                reload.applied.push(field.to_string());
+            } else {
+                reload.restart_required.push(field.to_string());
+            }
+        }
+
+        config.projects_root = new.projects_root;
+        config.tracked_tools = new.tracked_tools;
+        config.max_prompt_length = new.max_prompt_length;
+        config.snapshot_exclude = new.snapshot_exclude;
+        config.prompt_retention_days = new.prompt_retention_days;
+        config.quiet_hours = new.quiet_hours;
+        self.quiet_hours.set_windows(config.quiet_hours.clone());
+        let processors = std::iter::once(&self.interaction_processor)
+            .chain(self.profiles.values().map(|profile| &profile.processor));
+        for processor in processors {
+            processor.set_tracked_tools(config.tracked_tools.clone());
+            processor.set_max_prompt_length(config.max_prompt_length);
+            processor.set_snapshot_exclude(config.snapshot_exclude.clone());
+        }
+        let retention_days = config.prompt_retention_days;
+        drop(config);
+
+        if reload.applied.iter().any(|field| field == "prompt_retention_days")
+            && let Some(days) = retention_days
+        {
+            self.prune_prompt_library(days);
+        }
+
+        tracing::info!(target: "clauset::api",
+            "Reloaded config from {:?} (applied: {:?}, restart required: {:?})",
+            path, reload.applied, reload.restart_required);
+        Ok(reload)
+    }
+
+    /// Delete prompt library entries older than `days`.
+    pub fn prune_prompt_library(&self, days: u64) {
+        match self.interaction_processor.store().prune_prompts(prompt_retention_cutoff(days)) {
+            Ok(deleted) => {
+                tracing::info!(target: "clauset::startup", "Pruned {} prompts older than {} days", deleted, days)
+            }
+            Err(e) => tracing::warn!(target: "clauset::startup", "Failed to prune prompt library: {}", e),
+        }
+    }
+
+    /// The interaction store for `profile`, or the default store for `None`.
+    /// Returns `None` for an unknown profile.
+    pub fn interaction_store(&self, profile: Option<&str>) -> Option<&Arc<InteractionStore>> {
+        match profile {
+            None => Some(self.interaction_processor.store()),
+            Some(name) => self.profiles.get(name).map(|profile| profile.processor.store()),
+        }
+    }
+
+    /// The interaction processor that captures a session's hook events: that
+    /// of the profile listing the session's project, or the default one.
+    pub fn interaction_processor_for(&self, session_id: Uuid) -> &Arc<InteractionProcessor> {
+        if self.profiles.values().all(|profile| profile.projects.is_empty()) {
+            return &self.interaction_processor;
+        }
+        let name = match self.session_profiles.get(&session_id) {
+            Some(name) => name.clone(),
+            None => {
+                let name = self.assign_profile(session_id);
+                self.session_profiles.insert(session_id, name.clone());
+                name
+            }
+        };
+        name.and_then(|name| self.profiles.get(&name))
+            .map_or(&self.interaction_processor, |profile| &profile.processor)
+    }
+
+    /// Find the profile listing a session's project, copying the session row
+    /// into the profile database so its interactions can reference it.
+    fn assign_profile(&self, session_id: Uuid) -> Option<String> {
+        let session = self.session_manager.get_session(session_id).ok().flatten()?;
+        let (name, profile) = self.profiles.iter().find(|(_, profile)| {
+            profile.projects.iter().any(|project| session.project_path.starts_with(project))
+        })?;
+        let copied = match profile.sessions.get(session_id) {
+            Ok(Some(_)) => Ok(()),
+            _ => profile.sessions.insert(&session),
+        };
+        if let Err(e) = copied {
+            tracing::warn!(target: "clauset::api",
+                "Failed to add session {} to profile {}: {}", session_id, name, e);
+            return None;
+        }
+        Some(name.clone())
+    }
+}
+
+/// The oldest prompt timestamp kept when prompts are retained for `days`.
+pub fn prompt_retention_cutoff(days: u64) -> chrono::DateTime<chrono::Utc> {
+    i64::try_from(days)
+        .ok()
+        .and_then(chrono::Duration::try_days)
+        .and_then(|age| chrono::Utc::now().checked_sub_signed(age))
+        .unwrap_or(chrono::DateTime::<chrono::Utc>::MIN_UTC)
 }
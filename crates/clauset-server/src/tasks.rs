@@ -0,0 +1,195 @@
+//! Registry of long-running background tasks (backfill, maintenance).
+//!
+//! Background work is spawned detached from any request, so this registry
+//! gives the admin API visibility into what is running and a way to stop it
+//! cooperatively via a `CancellationToken`.
+
+use chrono::{DateTime, Utc};
+use clauset_core::PromptIndexer;
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// What a background task does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    /// Indexing historical prompts from ~/.claude into the Prompt Library.
+    PromptBackfill,
+}
+
+/// Lifecycle state of a background task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Progress counters for a background task.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct TaskProgress {
+    /// Units of work completed so far.
+    pub done: u64,
+    /// Total units of work, if known.
+    pub total: Option<u64>,
+}
+
+/// Snapshot of a background task for the admin API.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub id: Uuid,
+    pub kind: TaskKind,
+    pub status: TaskStatus,
+    pub progress: TaskProgress,
+    /// Whether cancellation has been requested (the task may still be winding down).
+    pub cancel_requested: bool,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Completion summary or error message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancel: CancellationToken,
+}
+
+/// Tracks background tasks and their cancellation tokens.
+#[derive(Default)]
+pub struct TaskRegistry {
+    tasks: DashMap<Uuid, TaskEntry>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new running task. The returned token is cancelled when the
+    /// task is cancelled through the registry.
+    pub fn register(&self, kind: TaskKind) -> (Uuid, CancellationToken) {
+        let id = Uuid::new_v4();
+        let cancel = CancellationToken::new();
+        self.tasks.insert(
+            id,
+            TaskEntry {
+                info: TaskInfo {
+                    id,
+                    kind,
+                    status: TaskStatus::Running,
+                    progress: TaskProgress::default(),
+                    cancel_requested: false,
+                    started_at: Utc::now(),
+                    finished_at: None,
+                    message: None,
+                },
+                cancel: cancel.clone(),
+            },
+        );
+        (id, cancel)
+    }
+
+    /// Update the progress counters of a task.
+    pub fn set_progress(&self, id: Uuid, done: u64, total: Option<u64>) {
+        if let Some(mut entry) = self.tasks.get_mut(&id) {
+            entry.info.progress = TaskProgress { done, total };
+        }
+    }
+
+    /// Record the final status of a task.
+    pub fn finish(&self, id: Uuid, status: TaskStatus, message: Option<String>) {
+        if let Some(mut entry) = self.tasks.get_mut(&id) {
+            entry.info.status = status;
+            entry.info.finished_at = Some(Utc::now());
+            entry.info.message = message;
+        }
+    }
+
+    /// Request cancellation of a task. Returns None if the task is unknown.
+    pub fn cancel(&self, id: Uuid) -> Option<TaskInfo> {
+        let mut entry = self.tasks.get_mut(&id)?;
+        if entry.info.status == TaskStatus::Running {
+            entry.cancel.cancel();
+            entry.info.cancel_requested = true;
+        }
+        Some(entry.info.clone())
+    }
+
+    /// Get a single task.
+    pub fn get(&self, id: Uuid) -> Option<TaskInfo> {
+        self.tasks.get(&id).map(|entry| entry.info.clone())
+    }
+
+    /// List all tasks, newest first.
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let mut tasks: Vec<TaskInfo> = self.tasks.iter().map(|entry| entry.info.clone()).collect();
+        tasks.sort_by_key(|task| std::cmp::Reverse(task.started_at));
+        tasks
+    }
+}
+
+/// Run a prompt library backfill as a registered, cancellable task.
+pub fn spawn_prompt_backfill(registry: Arc<TaskRegistry>, indexer: PromptIndexer) -> Uuid {
+    let (task_id, cancel) = registry.register(TaskKind::PromptBackfill);
+
+    let progress_registry = registry.clone();
+    // The backfill reads transcripts and writes the database synchronously,
+    // so it runs on a blocking thread; quiet hours still wait on the runtime
+    let runtime = tokio::runtime::Handle::current();
+    let backfill = tokio::task::spawn_blocking(move || {
+        runtime.block_on(indexer.backfill(&cancel, |stats, total| {
+            progress_registry.set_progress(task_id, stats.sessions_scanned as u64, Some(total as u64));
+        }))
+    });
+
+    tokio::spawn(async move {
+        let result = match backfill.await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match result {
+            Ok(stats) if stats.cancelled => {
+                info!(
+                    target: "clauset::tasks",
+                    "Prompt backfill cancelled after {} sessions ({} prompts indexed)",
+                    stats.sessions_scanned, stats.prompts_indexed
+                );
+                registry.finish(
+                    task_id,
+                    TaskStatus::Cancelled,
+                    Some(format!("{} prompts indexed before cancel", stats.prompts_indexed)),
+                );
+            }
+            Ok(stats) => {
+                info!(
+                    target: "clauset::tasks",
+                    "Prompt backfill complete: {} prompts indexed from {} sessions",
+                    stats.prompts_indexed, stats.sessions_scanned
+                );
+                registry.finish(
+                    task_id,
+                    TaskStatus::Completed,
+                    Some(format!(
+                        "{} prompts indexed from {} sessions",
+                        stats.prompts_indexed, stats.sessions_scanned
+                    )),
+                );
+            }
+            Err(e) => {
+                warn!(target: "clauset::tasks", "Prompt backfill failed: {}", e);
+                registry.finish(task_id, TaskStatus::Failed, Some(e));
+            }
+        }
+    });
+
+    task_id
+}
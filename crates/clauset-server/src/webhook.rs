@@ -0,0 +1,160 @@
+//! Mirroring of received hook events to an external webhook.
+//!
+//! Each matching event is POSTed as JSON from a detached task, with retries
+//! and a per-attempt timeout, so a slow or failing receiver never holds up
+//! hook ingestion. Deliveries share one HTTP client, and at most
+//! [`MAX_IN_FLIGHT`] run at once; events beyond that are dropped.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+
+/// Deliveries (including their retries) that may be pending at once.
+pub const MAX_IN_FLIGHT: usize = 32;
+
+/// Where and which hook events to mirror.
+///
+/// In TOML:
+/// ```toml
+/// [hook_webhook]
+/// url = "http://dashboard.local:9000/clauset"
+/// events = ["Stop", "Notification"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WebhookConfig {
+    /// `http://` or `https://` URL to POST events to.
+    pub url: String,
+    /// Hook event names to forward; empty forwards every event.
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Delivery attempts per event before giving up.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Seconds each attempt may take.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_timeout_secs() -> u64 {
+    5
+}
+
+/// Forwards hook events to the configured webhook.
+#[derive(Debug)]
+pub struct WebhookMirror {
+    config: WebhookConfig,
+    url: reqwest::Url,
+    client: reqwest::Client,
+    in_flight: Arc<Semaphore>,
+}
+
+impl WebhookMirror {
+    /// Validate the webhook URL and build the client deliveries share.
+    pub fn new(config: WebhookConfig) -> Result<Self, String> {
+        let url = reqwest::Url::parse(&config.url).map_err(|e| format!("invalid webhook URL {:?}: {}", config.url, e))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host().is_none() {
+            return Err(format!("webhook URL {:?} must be an http:// or https:// URL with a host", config.url));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| format!("failed to build webhook client: {}", e))?;
+        Ok(Self {
+            config,
+            url,
+            client,
+            in_flight: Arc::new(Semaphore::new(MAX_IN_FLIGHT)),
+        })
+    }
+
+    /// Whether events named `event_name` are forwarded.
+    pub fn matches(&self, event_name: &str) -> bool {
+        self.config.events.is_empty() || self.config.events.iter().any(|e| e == event_name)
+    }
+
+    /// Forward `payload` in the background if `event_name` matches. Dropped
+    /// with a warning while [`MAX_IN_FLIGHT`] deliveries are already pending.
+    pub fn forward(self: &Arc<Self>, event_name: &str, payload: Value) {
+        if !self.matches(event_name) {
+            return;
+        }
+        let Ok(permit) = self.in_flight.clone().try_acquire_owned() else {
+            warn!(target: "clauset::hooks",
+                "Webhook has {} deliveries pending, dropping {} event", MAX_IN_FLIGHT, event_name);
+            return;
+        };
+        let mirror = self.clone();
+        let event_name = event_name.to_string();
+        tokio::spawn(async move {
+            let _permit = permit;
+            let body = payload.to_string();
+            let attempts = mirror.config.max_attempts.max(1);
+            for attempt in 1..=attempts {
+                match mirror.post(body.clone()).await {
+                    Ok(()) => {
+                        debug!(target: "clauset::hooks", "Mirrored {} event to webhook", event_name);
+                        return;
+                    }
+                    Err(e) if e.is_timeout() => warn!(target: "clauset::hooks",
+                        "Webhook delivery of {} event timed out (attempt {}/{})", event_name, attempt, attempts),
+                    Err(e) => warn!(target: "clauset::hooks",
+                        "Webhook delivery of {} event failed (attempt {}/{}): {}", event_name, attempt, attempts, e),
+                }
+                if attempt < attempts {
+                    tokio::time::sleep(Duration::from_millis(250) * 2u32.pow(attempt - 1)).await;
+                }
+            }
+        });
+    }
+
+    /// POST `body` once, treating any non-2xx response as a failure.
+    async fn post(&self, body: String) -> reqwest::Result<()> {
+        self.client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(url: &str, events: &[&str]) -> WebhookConfig {
+        WebhookConfig {
+            url: url.to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            max_attempts: 3,
+            timeout_secs: 5,
+        }
+    }
+
+    #[test]
+    fn test_webhook_url_must_be_http_or_https() {
+        assert!(WebhookMirror::new(config("http://localhost:9000/hooks", &[])).is_ok());
+        assert!(WebhookMirror::new(config("https://hooks.example.com/x", &[])).is_ok());
+        assert!(WebhookMirror::new(config("ftp://hooks.example.com/x", &[])).is_err());
+        assert!(WebhookMirror::new(config("not a url", &[])).is_err());
+    }
+
+    #[test]
+    fn test_webhook_event_filter() {
+        let all = WebhookMirror::new(config("http://localhost/", &[])).unwrap();
+        assert!(all.matches("PreToolUse"));
+
+        let some = WebhookMirror::new(config("http://localhost/", &["Stop", "Notification"])).unwrap();
+        assert!(some.matches("Stop"));
+        assert!(!some.matches("PreToolUse"));
+    }
+}
@@ -1,6 +1,8 @@
 //! WebSocket connection handling.
 
 use crate::state::AppState;
+use crate::ws_protocol::WsProtocol;
+use crate::ws_queue::OutboundQueue;
 use anyhow::Result;
 use axum::extract::ws::{Message, WebSocket};
 use clauset_core::ProcessEvent;
@@ -16,10 +18,15 @@ const MAX_INPUT_SIZE: usize = 10 * 1024;
 /// Maximum size for terminal input data (64KB - generous for paste operations)
 const MAX_TERMINAL_INPUT_SIZE: usize = 64 * 1024;
 
+/// Terminal chunks queued for a client before new ones are dropped (and
+/// reported as a gap for the client to re-request)
+const MAX_QUEUED_TERMINAL_CHUNKS: usize = 256;
+
 pub async fn handle_websocket(
     socket: WebSocket,
     state: Arc<AppState>,
     session_id: Uuid,
+    protocol: WsProtocol,
 ) -> Result<()> {
     let (mut ws_tx, mut ws_rx) = socket.split();
 
@@ -41,8 +48,7 @@ pub async fn handle_websocket(
             tools: vec![],
             cwd: session.project_path,
         };
-        let json = serde_json::to_string(&init_msg)?;
-        ws_tx.send(Message::Text(json.into())).await?;
+        ws_tx.send(protocol.encode(&init_msg)?).await?;
     }
 
     // Always send initial mode - default if not set
@@ -63,27 +69,62 @@ pub async fn handle_websocket(
         session_id,
         mode: initial_mode,
     };
-    let json = serde_json::to_string(&mode_msg)?;
-    ws_tx.send(Message::Text(json.into())).await?;
+    ws_tx.send(protocol.encode(&mode_msg)?).await?;
+
+    // Re-present any menu or permission prompt that is still open so a
+    // reconnecting client can render it
+    if let Some(menu) = state.session_manager.get_tui_menu(session_id).await {
+        let menu_msg = WsServerMessage::TuiMenu {
+            event: clauset_types::TuiMenuEvent::MenuPresented { session_id, menu },
+        };
+        ws_tx.send(protocol.encode(&menu_msg)?).await?;
+    }
+    if let Some(prompt) = state.session_manager.get_permission_prompt(session_id).await {
+        let prompt_msg = WsServerMessage::TuiMenu {
+            event: clauset_types::TuiMenuEvent::PermissionPromptPresented { session_id, prompt },
+        };
+        ws_tx.send(protocol.encode(&prompt_msg)?).await?;
+    }
 
     // NOTE: Terminal buffer is NOT sent here on connect.
     // The client must first send a Resize message so tmux can be resized to match.
     // Then the client sends RequestBuffer, and we send the buffer formatted for the correct size.
     // This prevents text from being formatted for 80 columns but displayed in a narrower terminal.
 
-    // Spawn task to forward events to WebSocket
+    // Outgoing messages are queued so a slow client can't stall the broadcast
+    let queue = Arc::new(OutboundQueue::new(MAX_QUEUED_TERMINAL_CHUNKS));
+
+    // Spawn task to drain the queue into the WebSocket
+    let write_queue = queue.clone();
+    let mut write_task = tokio::spawn(async move {
+        while let Some(msg) = write_queue.pop().await {
+            let frame = match protocol.encode(&msg) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    warn!(target: "clauset::ws", "Failed to serialize WebSocket message for session {}: {}", session_id, e);
+                    continue;
+                }
+            };
+            if let Err(e) = ws_tx.send(frame).await {
+                debug!(
+                    target: "clauset::ws",
+                    "WebSocket send failed for session {} (client likely disconnected): {}",
+                    session_id, e
+                );
+                break;
+            }
+        }
+    });
+
+    // Spawn task to forward events to the queue
     let state_clone = state.clone();
-    let mut send_task = tokio::spawn(async move {
+    let send_queue = queue.clone();
+    let send_task = tokio::spawn(async move {
         loop {
             tokio::select! {
                 // Handle outgoing messages from recv_task (sync responses, chunk batches, etc.)
                 Some(msg) = outgoing_rx.recv() => {
-                    if let Ok(json) = serde_json::to_string(&msg) {
-                        if let Err(e) = ws_tx.send(Message::Text(json.into())).await {
-                            debug!(target: "clauset::ws", "WebSocket send failed for session {}: {}", session_id, e);
-                            break;
-                        }
-                    }
+                    send_queue.push(msg);
                 }
                 // Handle buffer request from recv_task (legacy)
                 Some(()) = buffer_rx.recv() => {
@@ -91,13 +132,7 @@ pub async fn handle_websocket(
                     if let Some(buffer) = state_clone.session_manager.get_terminal_buffer(session_id).await {
                         if !buffer.is_empty() {
                             debug!(target: "clauset::ws", "Sending terminal buffer for session {}: {} bytes", session_id, buffer.len());
-                            let buffer_msg = WsServerMessage::TerminalBuffer { data: buffer };
-                            if let Ok(json) = serde_json::to_string(&buffer_msg) {
-                                if let Err(e) = ws_tx.send(Message::Text(json.into())).await {
-                                    debug!(target: "clauset::ws", "WebSocket send failed for session {}: {}", session_id, e);
-                                    break;
-                                }
-                            }
+                            send_queue.push(WsServerMessage::TerminalBuffer { data: buffer });
                         }
                     }
                 }
@@ -244,6 +279,7 @@ pub async fn handle_websocket(
                             let event_session_id = match &chat_event {
                                 clauset_types::ChatEvent::Message { session_id, .. } => *session_id,
                                 clauset_types::ChatEvent::ContentDelta { session_id, .. } => *session_id,
+                                clauset_types::ChatEvent::ContentReplaced { session_id, .. } => *session_id,
                                 clauset_types::ChatEvent::ThinkingDelta { session_id, .. } => *session_id,
                                 clauset_types::ChatEvent::ToolCallStart { session_id, .. } => *session_id,
                                 clauset_types::ChatEvent::ToolCallComplete { session_id, .. } => *session_id,
@@ -366,13 +402,29 @@ pub async fn handle_websocket(
                                 None
                             }
                         }
+                        ProcessEvent::BufferReset { session_id: event_session_id, new_start_seq } => {
+                            if *event_session_id == session_id {
+                                Some(WsServerMessage::BufferOverflow {
+                                    new_start_seq: *new_start_seq,
+                                    requires_resync: true,
+                                })
+                            } else {
+                                None
+                            }
+                        }
+                        ProcessEvent::Heartbeat { session_id: event_session_id, alive } => {
+                            if *event_session_id == session_id {
+                                Some(WsServerMessage::Heartbeat {
+                                    session_id: *event_session_id,
+                                    alive: *alive,
+                                })
+                            } else {
+                                None
+                            }
+                        }
                         ProcessEvent::TuiMenu(tui_event) => {
                             // Forward TUI menu events for native UI rendering
-                            let event_session_id = match &tui_event {
-                                clauset_types::TuiMenuEvent::MenuPresented { session_id, .. } => *session_id,
-                                clauset_types::TuiMenuEvent::MenuDismissed { session_id, .. } => *session_id,
-                            };
-                            if event_session_id == session_id {
+                            if tui_event.session_id() == session_id {
                                 Some(WsServerMessage::TuiMenu { event: tui_event.clone() })
                             } else {
                                 None
@@ -381,22 +433,10 @@ pub async fn handle_websocket(
                         _ => None,
                     };
 
-                    if let Some(msg) = msg {
-                        let json = match serde_json::to_string(&msg) {
-                            Ok(j) => j,
-                            Err(e) => {
-                                warn!(target: "clauset::ws", "Failed to serialize WebSocket message for session {}: {}", session_id, e);
-                                continue;
-                            }
-                        };
-                        if let Err(e) = ws_tx.send(Message::Text(json.into())).await {
-                            debug!(
-                                target: "clauset::ws",
-                                "WebSocket send failed for session {} (client likely disconnected): {}",
-                                session_id, e
-                            );
-                            break;
-                        }
+                    if let Some(msg) = msg
+                        && !send_queue.push(msg)
+                    {
+                        debug!(target: "clauset::ws", "Client for session {} is behind, dropped terminal chunk", session_id);
                     }
                 }
             }
@@ -482,6 +522,43 @@ pub async fn handle_websocket(
                         WsClientMessage::GetState => {
                             // TODO: Send current state
                         }
+                        WsClientMessage::GetActivity => {
+                            let update = match state_clone.session_manager.get_activity(session_id).await {
+                                Some(activity) => Some(WsServerMessage::ActivityUpdate {
+                                    session_id,
+                                    model: activity.model,
+                                    cost: activity.cost,
+                                    input_tokens: activity.input_tokens,
+                                    output_tokens: activity.output_tokens,
+                                    context_percent: activity.context_percent,
+                                    current_activity: activity.current_activity,
+                                    current_step: activity.current_step,
+                                    recent_actions: activity.recent_actions.into_iter().map(|a| clauset_types::RecentAction {
+                                        action_type: a.action_type,
+                                        summary: a.summary,
+                                        detail: a.detail,
+                                        timestamp: a.timestamp,
+                                    }).collect(),
+                                }),
+                                // No live activity yet (e.g. after a restart): fall back to stored stats
+                                None => state_clone.session_manager.get_session(session_id).ok().flatten().map(|session| {
+                                    WsServerMessage::ActivityUpdate {
+                                        session_id,
+                                        model: session.model,
+                                        cost: session.total_cost_usd,
+                                        input_tokens: session.input_tokens,
+                                        output_tokens: session.output_tokens,
+                                        context_percent: session.context_percent,
+                                        current_activity: session.preview,
+                                        current_step: None,
+                                        recent_actions: Vec::new(),
+                                    }
+                                }),
+                            };
+                            if let Some(update) = update {
+                                let _ = outgoing_tx_clone.send(update).await;
+                            }
+                        }
                         WsClientMessage::StatusUpdate {
                             model,
                             cost,
@@ -511,22 +588,45 @@ pub async fn handle_websocket(
 
                             // Get buffer info to determine what the client needs
                             let buffers = state_clone.session_manager.buffers();
-                            let (buffer_start_seq, buffer_end_seq, full_buffer) = if let Some((start, end, data)) = buffers.get_full_buffer(session_id).await {
-                                // Client needs full buffer if:
-                                // - Fresh connection (last_seq == 0)
-                                // - Client is behind the buffer start (missed too many chunks)
-                                let needs_full = last_seq == 0 || last_seq < start;
-                                if needs_full {
+                            let (mut buffer_start_seq, mut buffer_end_seq) =
+                                buffers.get_buffer_info(session_id).await.unwrap_or((0, 0));
+                            let mut delta = None;
+                            let mut needs_full = false;
+                            match plan_resync(last_seq, buffer_start_seq, buffer_end_seq) {
+                                Resync::UpToDate => {
+                                    debug!(target: "clauset::ws", "SyncResponse: client up to date (last_seq={}, buffer {}..{})", last_seq, buffer_start_seq, buffer_end_seq);
+                                }
+                                Resync::Missing { start, end } => {
+                                    let chunks = buffers.get_chunk_range(session_id, start, end).await.unwrap_or_default();
+                                    // Chunks may have been evicted since the buffer info was read
+                                    if chunks.first().map(|c| c.seq) == Some(start) {
+                                        let data: Vec<u8> = chunks.iter().flat_map(|c| c.data.iter().copied()).collect();
+                                        debug!(target: "clauset::ws", "SyncResponse: resuming client at seq {} ({} chunks, {} bytes)", start, chunks.len(), data.len());
+                                        delta = Some(WsServerMessage::ChunkBatch {
+                                            start_seq: start,
+                                            data,
+                                            chunk_count: chunks.len() as u32,
+                                            is_complete: true,
+                                        });
+                                    } else {
+                                        needs_full = true;
+                                    }
+                                }
+                                Resync::Full => needs_full = true,
+                            }
+                            let full_buffer = if needs_full {
+                                if let Some((start, end, data)) = buffers.get_full_buffer(session_id).await {
                                     debug!(target: "clauset::ws", "SyncResponse: sending full buffer ({} bytes, seq {}..{})", data.len(), start, end);
-                                    (start, end, Some(data))
+                                    buffer_start_seq = start;
+                                    buffer_end_seq = end;
+                                    Some(data)
                                 } else {
-                                    debug!(target: "clauset::ws", "SyncResponse: client up to date (last_seq={}, buffer {}..{})", last_seq, start, end);
-                                    (start, end, None)
+                                    // No buffer yet - fresh session
+                                    debug!(target: "clauset::ws", "SyncResponse: no buffer yet for session {}", session_id);
+                                    None
                                 }
                             } else {
-                                // No buffer yet - fresh session
-                                debug!(target: "clauset::ws", "SyncResponse: no buffer yet for session {}", session_id);
-                                (0, 0, None)
+                                None
                             };
 
                             // Send SyncResponse
@@ -539,6 +639,10 @@ pub async fn handle_websocket(
                                 full_buffer_start_seq: if buffer_start_seq > 0 { Some(buffer_start_seq) } else { None },
                             };
                             let _ = outgoing_tx_clone.send(response).await;
+                            // Then just the chunks the client missed while disconnected
+                            if let Some(delta) = delta {
+                                let _ = outgoing_tx_clone.send(delta).await;
+                            }
                         }
                         WsClientMessage::Ack { ack_seq } => {
                             // Track client acknowledgment for flow control
@@ -581,11 +685,16 @@ pub async fn handle_websocket(
                         WsClientMessage::RequestChatHistory => {
                             debug!(target: "clauset::ws", "RequestChatHistory for session {}", session_id);
 
-                            // Get chat history from database via chat processor
-                            let messages = state_clone.chat_processor.get_chat_history(session_id);
-                            debug!(target: "clauset::ws", "Sending {} chat messages for session {}", messages.len(), session_id);
+                            // Finalized messages plus the live partial, so a mid-stream
+                            // reconnect renders the exact current state
+                            let snapshot = state_clone.chat_processor.get_session_snapshot(session_id).await;
+                            debug!(target: "clauset::ws", "Sending {} chat messages for session {} (streaming: {})",
+                                snapshot.messages.len(), session_id, snapshot.in_progress.is_some());
 
-                            let response = WsServerMessage::ChatHistory { messages };
+                            let response = WsServerMessage::ChatHistory {
+                                messages: snapshot.messages,
+                                in_progress: snapshot.in_progress,
+                            };
                             let _ = outgoing_tx_clone.send(response).await;
                         }
                         // === Interactive Prompt Protocol ===
@@ -722,6 +831,11 @@ pub async fn handle_websocket(
                                 .await
                             {
                                 warn!(target: "clauset::ws", "Failed to send interrupt for session {}: {}", session_id, e);
+                            } else if let Err(e) = state_clone
+                                .interaction_processor_for(session_id)
+                                .interrupt(session_id)
+                            {
+                                warn!(target: "clauset::ws", "Failed to mark interactions interrupted for session {}: {}", session_id, e);
                             }
                         }
 
@@ -731,18 +845,29 @@ pub async fn handle_websocket(
 
                             // TUI menus use arrow keys for navigation and Enter to confirm
                             // Options are 0-indexed internally
-                            // To select option N, we need to send N Down arrows, then Enter
+                            // To select option N, we move the highlight with Up/Down arrows, then Enter
                             //
                             // ANSI escape codes:
                             // Down arrow: ESC [ B  (0x1B 0x5B 0x42)
+                            // Up arrow: ESC [ A  (0x1B 0x5B 0x41)
                             // Enter: CR (0x0D or \r)
 
-                            let mut nav_bytes: Vec<u8> = Vec::new();
-
-                            // Navigate down to the selected option
-                            for _ in 0..selected_index {
-                                nav_bytes.extend_from_slice(b"\x1b[B"); // Down arrow
-                            }
+                            // Navigate relative to the tracked highlight when we know the menu,
+                            // otherwise assume the highlight starts on the first option
+                            let nav_bytes: Vec<u8> = match state_clone
+                                .session_manager
+                                .get_tui_menu(session_id)
+                                .await
+                                .filter(|menu| menu.id == menu_id)
+                                .and_then(|menu| menu.input_for_selection(selected_index))
+                            {
+                                // Enter is sent separately below
+                                Some(mut input) => {
+                                    input.pop();
+                                    input
+                                }
+                                None => b"\x1b[B".repeat(selected_index),
+                            };
 
                             // Send navigation keys first (if any)
                             if !nav_bytes.is_empty() {
@@ -855,15 +980,40 @@ pub async fn handle_websocket(
         }
     });
 
-    // Wait for either task to finish
+    // Wait for the writer or reader to finish, then stop the rest
     tokio::select! {
-        _ = &mut send_task => {
+        _ = &mut write_task => {
             recv_task.abort();
         }
         _ = &mut recv_task => {
-            send_task.abort();
+            write_task.abort();
         }
     }
+    send_task.abort();
+    queue.close();
 
     Ok(())
 }
+
+/// What a reconnecting client needs to catch up with the server buffer.
+#[derive(Debug, PartialEq, Eq)]
+enum Resync {
+    /// The client already has every chunk
+    UpToDate,
+    /// The client missed chunks `start..=end`, all still buffered
+    Missing { start: u64, end: u64 },
+    /// Fresh connection, or the client's position was evicted
+    Full,
+}
+
+/// Decide how a client that last saw `last_seq` resyncs against a buffer
+/// holding chunks `start..=end`.
+fn plan_resync(last_seq: u64, start: u64, end: u64) -> Resync {
+    if last_seq == 0 || last_seq.saturating_add(1) < start {
+        Resync::Full
+    } else if last_seq >= end {
+        Resync::UpToDate
+    } else {
+        Resync::Missing { start: last_seq + 1, end }
+    }
+}
@@ -0,0 +1,134 @@
+//! WebSocket subprotocol negotiation for message format versioning.
+//!
+//! Clients name the message formats they speak in `Sec-WebSocket-Protocol`
+//! (`clauset.v1`, ...). The server picks the newest one it supports, echoes
+//! it back, and serializes every message for that version, so the wire
+//! format can evolve without breaking older frontends. Clients that offer no
+//! subprotocol at all get v1; offering only unknown versions is rejected.
+
+use axum::{
+    extract::ws::{Message, WebSocketUpgrade},
+    http::{header, HeaderMap, StatusCode},
+};
+use clauset_types::WsServerMessage;
+
+/// A message format version, negotiated per connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WsProtocol {
+    /// One JSON text frame per message.
+    V1,
+}
+
+impl WsProtocol {
+    /// Every supported version, oldest first.
+    pub const SUPPORTED: &[WsProtocol] = &[WsProtocol::V1];
+
+    /// The `Sec-WebSocket-Protocol` token for this version.
+    pub fn name(self) -> &'static str {
+        match self {
+            WsProtocol::V1 => "clauset.v1",
+        }
+    }
+
+    /// Parse a `Sec-WebSocket-Protocol` token.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::SUPPORTED.iter().copied().find(|p| p.name() == name)
+    }
+
+    /// Serialize a message for this version.
+    pub fn encode(self, msg: &WsServerMessage) -> serde_json::Result<Message> {
+        match self {
+            WsProtocol::V1 => Ok(Message::Text(serde_json::to_string(msg)?.into())),
+        }
+    }
+
+    /// Pick the version for a connection from the client's offered
+    /// subprotocols. `Ok(None)` means the client offered none (legacy client,
+    /// served as v1 without echoing a protocol).
+    pub fn negotiate(headers: &HeaderMap) -> Result<Option<Self>, String> {
+        let offered: Vec<&str> = headers
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+        if offered.is_empty() {
+            return Ok(None);
+        }
+        offered
+            .iter()
+            .filter_map(|name| Self::from_name(name))
+            .max()
+            .map(Some)
+            .ok_or_else(|| {
+                let supported: Vec<_> = Self::SUPPORTED.iter().map(|p| p.name()).collect();
+                format!(
+                    "Unsupported WebSocket protocol {}; supported: {}",
+                    offered.join(", "),
+                    supported.join(", ")
+                )
+            })
+    }
+}
+
+/// Negotiate the subprotocol for an upgrade request, echoing the chosen
+/// version back to the client. Unknown versions get a 400.
+pub fn negotiate_upgrade(
+    ws: WebSocketUpgrade,
+    headers: &HeaderMap,
+) -> Result<(WebSocketUpgrade, WsProtocol), (StatusCode, String)> {
+    match WsProtocol::negotiate(headers) {
+        Ok(Some(protocol)) => Ok((ws.protocols([protocol.name()]), protocol)),
+        Ok(None) => Ok((ws, WsProtocol::V1)),
+        Err(message) => {
+            tracing::warn!(target: "clauset::ws", "Rejected WebSocket upgrade: {}", message);
+            Err((StatusCode::BAD_REQUEST, message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn offering(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(header::SEC_WEBSOCKET_PROTOCOL, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(WsProtocol::negotiate(&offering(&[])), Ok(None));
+        assert_eq!(WsProtocol::negotiate(&offering(&["clauset.v1"])), Ok(Some(WsProtocol::V1)));
+        assert_eq!(
+            WsProtocol::negotiate(&offering(&["clauset.v9, clauset.v1"])),
+            Ok(Some(WsProtocol::V1))
+        );
+        assert_eq!(
+            WsProtocol::negotiate(&offering(&["graphql-ws", "clauset.v1"])),
+            Ok(Some(WsProtocol::V1))
+        );
+
+        let err = WsProtocol::negotiate(&offering(&["clauset.v9"])).unwrap_err();
+        assert!(err.contains("clauset.v9"), "{err}");
+        assert!(err.contains("supported: clauset.v1"), "{err}");
+    }
+
+    #[test]
+    fn test_v1_encodes_json_text() {
+        let msg = WsServerMessage::Pong { timestamp: 7 };
+        match WsProtocol::V1.encode(&msg).unwrap() {
+            Message::Text(text) => {
+                let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+                assert_eq!(value["type"], "pong");
+            }
+            other => panic!("expected a text frame, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Bounded per-connection send queue for session WebSockets.
+//!
+//! Events are queued by a forwarding task and drained by a writer task, so a
+//! slow client never stalls the broadcast channel. Terminal chunks are the
+//! only high-volume traffic: once too many are queued, new chunks are dropped
+//! and their sequence range is reported with a `TerminalGap` so the client can
+//! fetch them with a `RangeRequest`. Every other message is always delivered.
+
+use clauset_types::WsServerMessage;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct QueueState {
+    messages: VecDeque<WsServerMessage>,
+    /// Terminal chunks currently in `messages`
+    queued_chunks: usize,
+    /// Sequence range of chunks dropped since the last gap marker
+    gap: Option<(u64, u64)>,
+    closed: bool,
+}
+
+impl QueueState {
+    fn flush_gap(&mut self) {
+        if let Some((start_seq, end_seq)) = self.gap.take() {
+            self.messages
+                .push_back(WsServerMessage::TerminalGap { start_seq, end_seq });
+        }
+    }
+}
+
+/// Outgoing messages for one WebSocket connection.
+pub struct OutboundQueue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    max_chunks: usize,
+}
+
+impl OutboundQueue {
+    /// Create a queue holding at most `max_chunks` terminal chunks.
+    pub fn new(max_chunks: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            notify: Notify::new(),
+            max_chunks,
+        }
+    }
+
+    /// Queue a message. Returns false if it was a terminal chunk dropped for backpressure.
+    pub fn push(&self, msg: WsServerMessage) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return false;
+        }
+
+        if let WsServerMessage::TerminalChunk { seq, .. } = &msg {
+            if state.queued_chunks >= self.max_chunks {
+                let seq = *seq;
+                state.gap = Some(match state.gap {
+                    Some((start, end)) => (start.min(seq), end.max(seq)),
+                    None => (seq, seq),
+                });
+                return false;
+            }
+            // Report the gap before any chunk that follows it
+            state.flush_gap();
+            state.queued_chunks += 1;
+        }
+
+        state.messages.push_back(msg);
+        drop(state);
+        self.notify.notify_one();
+        true
+    }
+
+    /// Wait for the next message. Returns None once the queue is closed.
+    pub async fn pop(&self) -> Option<WsServerMessage> {
+        loop {
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.messages.is_empty() {
+                    state.flush_gap();
+                }
+                if let Some(msg) = state.messages.pop_front() {
+                    if matches!(msg, WsServerMessage::TerminalChunk { .. }) {
+                        state.queued_chunks -= 1;
+                    }
+                    return Some(msg);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.notify.notified().await;
+        }
+    }
+
+    /// Stop accepting messages and wake the writer.
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.notify.notify_one();
+    }
+
+    /// Number of messages waiting to be sent.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    fn chunk(seq: u64) -> WsServerMessage {
+        WsServerMessage::TerminalChunk {
+            seq,
+            data: vec![b'x'; 1024],
+            timestamp: 0,
+        }
+    }
+
+    fn mode_change() -> WsServerMessage {
+        WsServerMessage::ModeChange {
+            session_id: Uuid::nil(),
+            mode: clauset_types::PermissionMode::Plan,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_chunks_and_reports_gap() {
+        let queue = OutboundQueue::new(4);
+        for seq in 1..=10 {
+            queue.push(chunk(seq));
+        }
+        assert!(queue.push(mode_change()), "non-terminal messages are never dropped");
+        assert_eq!(queue.len(), 5);
+
+        let mut seqs = Vec::new();
+        for _ in 0..5 {
+            match queue.pop().await.unwrap() {
+                WsServerMessage::TerminalChunk { seq, .. } => seqs.push(seq),
+                WsServerMessage::ModeChange { .. } => {}
+                other => panic!("unexpected message {other:?}"),
+            }
+        }
+        assert_eq!(seqs, vec![1, 2, 3, 4]);
+
+        // Once drained, the dropped range is reported for resync
+        match queue.pop().await.unwrap() {
+            WsServerMessage::TerminalGap { start_seq, end_seq } => {
+                assert_eq!((start_seq, end_seq), (5, 10))
+            }
+            other => panic!("expected gap marker, got {other:?}"),
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_gap_marker_precedes_later_chunks() {
+        let queue = OutboundQueue::new(1);
+        queue.push(chunk(1));
+        assert!(!queue.push(chunk(2)));
+        queue.pop().await.unwrap();
+        assert!(queue.push(chunk(3)));
+
+        assert!(matches!(
+            queue.pop().await.unwrap(),
+            WsServerMessage::TerminalGap { start_seq: 2, end_seq: 2 }
+        ));
+        assert!(matches!(
+            queue.pop().await.unwrap(),
+            WsServerMessage::TerminalChunk { seq: 3, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slow_consumer_stays_bounded() {
+        let queue = Arc::new(OutboundQueue::new(8));
+        let consumer_queue = queue.clone();
+        let consumer = tokio::spawn(async move {
+            let mut received = Vec::new();
+            while let Some(msg) = consumer_queue.pop().await {
+                received.push(msg);
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            received
+        });
+
+        let mut max_len = 0;
+        for seq in 1..=2000 {
+            queue.push(chunk(seq));
+            if seq % 100 == 0 {
+                queue.push(mode_change());
+            }
+            max_len = max_len.max(queue.len());
+        }
+        queue.close();
+        let received = consumer.await.unwrap();
+
+        // 8 chunks plus a few undroppable messages and gap markers at most
+        assert!(max_len <= 8 + 20 + 20, "queue grew to {max_len}");
+        assert_eq!(
+            received
+                .iter()
+                .filter(|m| matches!(m, WsServerMessage::ModeChange { .. }))
+                .count(),
+            20
+        );
+
+        // Every chunk was either delivered or covered by a gap marker
+        let mut covered = vec![false; 2001];
+        for msg in &received {
+            match msg {
+                WsServerMessage::TerminalChunk { seq, .. } => covered[*seq as usize] = true,
+                WsServerMessage::TerminalGap { start_seq, end_seq } => {
+                    for seq in *start_seq..=*end_seq {
+                        covered[seq as usize] = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        assert!(covered[1..].iter().all(|&c| c));
+        assert!(received
+            .iter()
+            .any(|m| matches!(m, WsServerMessage::TerminalGap { .. })));
+    }
+}
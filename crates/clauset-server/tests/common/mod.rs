@@ -0,0 +1,46 @@
+//! Fixtures shared by the integration tests.
+
+// Each test binary compiles this module and uses only some of it
+#![allow(dead_code)]
+
+use clauset_core::CreateSessionOptions;
+use clauset_server::{config::Config, state::AppState};
+use clauset_types::SessionMode;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+/// Config keeping all server files in `temp_dir`, with a `claude` that exits
+/// immediately.
+pub fn test_config(temp_dir: &TempDir) -> Config {
+    Config {
+        port: 0,
+        host: "127.0.0.1".to_string(),
+        db_path: temp_dir.path().join("test.db"),
+        static_dir: temp_dir.path().join("static"),
+        claude_path: PathBuf::from("/usr/bin/true"),
+        projects_root: temp_dir.path().join("projects"),
+        ..Config::default()
+    }
+}
+
+pub fn test_state(config: Config) -> Arc<AppState> {
+    Arc::new(AppState::new(config).expect("Failed to create AppState"))
+}
+
+/// Create a terminal session in `temp_dir` and return its id.
+pub async fn create_session(state: &AppState, temp_dir: &TempDir) -> Uuid {
+    state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Test prompt".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap()
+        .id
+}
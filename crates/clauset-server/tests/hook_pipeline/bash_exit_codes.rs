@@ -0,0 +1,93 @@
+//! Integration tests for recording Bash exit codes from PostToolUse hooks.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::SessionMode;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn send_hook(app: &Router, session_id: Uuid, event: serde_json::Value) {
+    let mut payload = serde_json::json!({
+        "clauset_session_id": session_id,
+        "session_id": "test-claude-session",
+    });
+    payload.as_object_mut().unwrap().extend(event.as_object().unwrap().clone());
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn run_bash(app: &Router, session_id: Uuid, tool_use_id: &str, command: &str, response: serde_json::Value) {
+    send_hook(app, session_id, serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": "Bash",
+        "tool_use_id": tool_use_id,
+        "tool_input": {"command": command},
+    }))
+    .await;
+    send_hook(app, session_id, serde_json::json!({
+        "hook_event_name": "PostToolUse",
+        "tool_name": "Bash",
+        "tool_use_id": tool_use_id,
+        "tool_input": {"command": command},
+        "tool_response": response,
+    }))
+    .await;
+}
+
+#[tokio::test]
+async fn test_bash_exit_codes_are_stored_and_queryable() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Run the tests".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+
+    send_hook(&app, session.id, serde_json::json!({"hook_event_name": "UserPromptSubmit", "prompt": "Run the tests"})).await;
+    run_bash(&app, session.id, "toolu_build", "cargo build", serde_json::json!({"stdout": "ok", "exit_code": 0})).await;
+    run_bash(&app, session.id, "toolu_test", "cargo test", serde_json::json!({"stderr": "1 failed", "exit_code": 101})).await;
+    run_bash(&app, session.id, "toolu_ls", "ls", serde_json::json!({"stdout": "Cargo.toml"})).await;
+
+    let store = state.interaction_processor.store();
+    let build = store.get_tool_invocation_by_tool_use_id("toolu_build").unwrap().unwrap();
+    assert_eq!(build.exit_code, Some(0));
+    let ls = store.get_tool_invocation_by_tool_use_id("toolu_ls").unwrap().unwrap();
+    assert_eq!(ls.exit_code, None);
+
+    let failed = store.list_failed_commands(session.id).unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].tool_use_id.as_deref(), Some("toolu_test"));
+    assert_eq!(failed[0].exit_code, Some(101));
+    assert_eq!(failed[0].tool_input["command"], "cargo test");
+}
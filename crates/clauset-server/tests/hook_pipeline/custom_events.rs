@@ -0,0 +1,121 @@
+//! Integration tests for hook events with names Clauset doesn't recognize.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::SessionMode;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .route("/api/sessions/{id}/custom-events", get(routes::sessions::list_custom_events))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn send_hook(app: &Router, session_id: Uuid, event: serde_json::Value) -> StatusCode {
+    let mut payload = serde_json::json!({
+        "clauset_session_id": session_id,
+        "session_id": "test-claude-session",
+    });
+    payload.as_object_mut().unwrap().extend(event.as_object().unwrap().clone());
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+async fn get_custom_events(app: &Router, session_id: Uuid) -> serde_json::Value {
+    let request = Request::builder()
+        .uri(format!("/api/sessions/{session_id}/custom-events"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_unrecognized_hook_event_is_stored() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Ship it".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+
+    let status = send_hook(
+        &app,
+        session.id,
+        serde_json::json!({"hook_event_name": "UserPromptSubmit", "prompt": "Ship it"}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let status = send_hook(
+        &app,
+        session.id,
+        serde_json::json!({"hook_event_name": "DeployFinished", "env": "staging", "ok": true}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let status = send_hook(&app, session.id, serde_json::json!({"hook_event_name": "Stop"})).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let body = get_custom_events(&app, session.id).await;
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["event_name"], "DeployFinished");
+    assert_eq!(events[0]["payload"]["env"], "staging");
+    assert_eq!(events[0]["payload"]["ok"], true);
+
+    // Standard events still drive interaction tracking
+    let store = state.interaction_processor.store();
+    let interactions = store.list_interactions(session.id, 10, 0).unwrap();
+    assert_eq!(interactions.len(), 1);
+    assert_eq!(interactions[0].user_prompt, "Ship it");
+    let names: Vec<_> = store
+        .list_hook_events(session.id)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.payload.hook_event_name)
+        .collect();
+    assert_eq!(names, ["UserPromptSubmit", "Stop"]);
+}
+
+#[tokio::test]
+async fn test_unknown_event_without_session_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, _) = create_test_app(&temp_dir);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"session_id": "s", "hook_event_name": "DeployFinished"}"#))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
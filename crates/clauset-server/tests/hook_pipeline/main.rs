@@ -0,0 +1,1041 @@
+//! Integration tests for the hook → WebSocket pipeline.
+//!
+//! These tests verify that hook events received from the Claude Code CLI
+//! are properly parsed, processed, and would be broadcast to WebSocket clients.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_server::{
+    config::{Config, SnapshotExclude, TrackedTools},
+    routes,
+    state::AppState,
+};
+use clauset_types::{
+    ContextWindow, CurrentUsage, HookEvent, HookEventPayload, HookEventType, InteractionStatus,
+};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[path = "../common/mod.rs"]
+mod common;
+mod bash_exit_codes;
+mod custom_events;
+mod replay;
+mod tool_output_previews;
+mod webhook;
+
+/// Create a minimal test app state for integration testing.
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    create_test_app_with(|_| {}).await
+}
+
+/// Create a test app state, letting the caller adjust the config first.
+async fn create_test_app_with(configure: impl FnOnce(&mut Config)) -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("static")).unwrap();
+
+    let mut config = Config {
+        max_concurrent_sessions: 5,
+        default_model: "haiku".to_string(),
+        ..common::test_config(&temp_dir)
+    };
+    configure(&mut config);
+
+    let state = common::test_state(config);
+
+    let app = Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .route("/api/sessions/{id}/todos", get(routes::sessions::get_todos))
+        .route("/api/sessions/{id}/plan", get(routes::sessions::get_plan))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+/// Create a minimal hook payload with required fields.
+fn create_hook_payload(event_name: &str, clauset_session_id: Uuid) -> HookEventPayload {
+    HookEventPayload {
+        clauset_session_id,
+        session_id: "test-claude-session".to_string(),
+        hook_event_name: event_name.to_string(),
+        cwd: None,
+        transcript_path: None,
+        permission_mode: None,
+        tool_name: None,
+        tool_input: None,
+        tool_response: None,
+        tool_use_id: None,
+        prompt: None,
+        source: None,
+        reason: None,
+        stop_hook_active: None,
+        message: None,
+        notification_type: None,
+        context_window: None,
+        model: None,
+        workspace: None,
+        output_style: None,
+        version: None,
+        agent_id: None,
+        agent_type: None,
+        error: None,
+        error_type: None,
+        is_timeout: None,
+        is_interrupt: None,
+        trigger: None,
+    }
+}
+
+/// Send a hook event to the test app and return the response status.
+async fn send_hook_event(app: &Router, payload: &HookEventPayload) -> StatusCode {
+    let body = serde_json::to_string(payload).unwrap();
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    response.status()
+}
+
+#[tokio::test]
+async fn test_hook_endpoint_returns_ok_for_valid_payload() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Hello world".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_hook_endpoint_returns_ok_for_unknown_session() {
+    let (app, _, _temp) = create_test_app().await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", Uuid::new_v4());
+    payload.prompt = Some("Hello".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_hook_endpoint_accepts_all_event_types() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let event_types = vec![
+        "SessionStart",
+        "SessionEnd",
+        "UserPromptSubmit",
+        "PreToolUse",
+        "PostToolUse",
+        "PostToolUseFailure",
+        "Stop",
+        "SubagentStart",
+        "SubagentStop",
+        "Notification",
+        "PreCompact",
+        "PermissionRequest",
+    ];
+
+    for event_name in event_types {
+        let mut payload = create_hook_payload(event_name, session_id);
+        // Some events require specific fields
+        match event_name {
+            "PreToolUse" | "PostToolUse" | "PostToolUseFailure" | "PermissionRequest" => {
+                payload.tool_name = Some("TestTool".to_string());
+                payload.tool_use_id = Some("test_tool_id".to_string());
+            }
+            _ => {}
+        }
+        let status = send_hook_event(&app, &payload).await;
+        assert_eq!(status, StatusCode::OK, "Event type {} should return OK", event_name);
+    }
+}
+
+#[tokio::test]
+async fn test_hook_event_type_parsing() {
+    let test_cases = vec![
+        ("SessionStart", HookEventType::SessionStart),
+        ("SessionEnd", HookEventType::SessionEnd),
+        ("UserPromptSubmit", HookEventType::UserPromptSubmit),
+        ("PreToolUse", HookEventType::PreToolUse),
+        ("PostToolUse", HookEventType::PostToolUse),
+        ("PostToolUseFailure", HookEventType::PostToolUseFailure),
+        ("Stop", HookEventType::Stop),
+        ("SubagentStart", HookEventType::SubagentStart),
+        ("SubagentStop", HookEventType::SubagentStop),
+        ("Notification", HookEventType::Notification),
+        ("PreCompact", HookEventType::PreCompact),
+        ("PermissionRequest", HookEventType::PermissionRequest),
+    ];
+
+    for (name, expected) in test_cases {
+        let result = HookEventType::from_str(name);
+        assert!(result.is_some(), "Should parse '{}'", name);
+        assert_eq!(result.unwrap(), expected, "Parsed type for '{}'", name);
+    }
+}
+
+#[tokio::test]
+async fn test_context_window_extraction() {
+    let context_window = ContextWindow {
+        total_input_tokens: 15000,
+        total_output_tokens: 5000,
+        context_window_size: 200000,
+        current_usage: Some(CurrentUsage {
+            input_tokens: 15000,
+            output_tokens: 5000,
+            cache_creation_input_tokens: 1000,
+            cache_read_input_tokens: 500,
+        }),
+    };
+
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Hello".to_string());
+    payload.context_window = Some(context_window.clone());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(context_window.total_input_tokens, 15000);
+    assert_eq!(context_window.total_output_tokens, 5000);
+    let usage = context_window.current_usage.unwrap();
+    assert_eq!(usage.input_tokens, 15000);
+    assert_eq!(usage.output_tokens, 5000);
+}
+
+#[tokio::test]
+async fn test_tool_use_events_capture_tool_details() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let tools = vec![
+        ("Read", serde_json::json!({"path": "/test/file.txt"})),
+        ("Write", serde_json::json!({"path": "/test/new.txt", "content": "hello"})),
+        ("Edit", serde_json::json!({"path": "/test/file.txt", "old": "a", "new": "b"})),
+        ("Bash", serde_json::json!({"command": "ls -la"})),
+        ("mcp__greptile__list_pull_requests", serde_json::json!({"limit": 10})),
+    ];
+
+    for (tool_name, tool_input) in tools {
+        let mut payload = create_hook_payload("PreToolUse", session_id);
+        payload.tool_name = Some(tool_name.to_string());
+        payload.tool_input = Some(tool_input);
+        payload.tool_use_id = Some(format!("tool_{}", tool_name));
+
+        let status = send_hook_event(&app, &payload).await;
+        assert_eq!(status, StatusCode::OK, "PreToolUse for {} should succeed", tool_name);
+    }
+}
+
+#[tokio::test]
+async fn test_todos_endpoint_tracks_latest_todo_write() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let todo_lists = [
+        serde_json::json!({"todos": [
+            {"content": "Write parser", "status": "in_progress", "activeForm": "Writing parser"},
+            {"content": "Add tests", "status": "pending", "activeForm": "Adding tests"},
+        ]}),
+        serde_json::json!({"todos": [
+            {"content": "Write parser", "status": "completed", "activeForm": "Writing parser"},
+            {"content": "Add tests", "status": "in_progress", "activeForm": "Adding tests"},
+            {"content": "Update docs", "status": "pending", "activeForm": "Updating docs"},
+        ]}),
+    ];
+    for (i, input) in todo_lists.into_iter().enumerate() {
+        let mut payload = create_hook_payload("PreToolUse", session_id);
+        payload.tool_name = Some("TodoWrite".to_string());
+        payload.tool_input = Some(input);
+        payload.tool_use_id = Some(format!("todo_{i}"));
+        assert_eq!(send_hook_event(&app, &payload).await, StatusCode::OK);
+    }
+
+    let request = Request::builder()
+        .uri(format!("/api/sessions/{session_id}/todos"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let todos = json["todos"].as_array().unwrap();
+    let items: Vec<(&str, &str)> = todos
+        .iter()
+        .map(|t| (t["content"].as_str().unwrap(), t["status"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            ("Write parser", "completed"),
+            ("Add tests", "in_progress"),
+            ("Update docs", "pending"),
+        ]
+    );
+    assert_eq!(todos[1]["active_form"], "Adding tests");
+
+    // After a restart the list is recovered from the stored invocation
+    let todos = clauset_server::interaction_processor::InteractionProcessor::new(
+        state.interaction_processor.store().clone(),
+    )
+    .todos(session_id)
+    .unwrap();
+    assert_eq!(todos.len(), 3);
+    assert_eq!(todos[0].status, clauset_types::TodoStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_plan_endpoint_extracts_exit_plan_mode_steps() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+    let get_plan = || async {
+        let request = Request::builder()
+            .uri(format!("/api/sessions/{session_id}/plan"))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    };
+    assert!(get_plan().await["plan"].is_null());
+
+    let mut prompt = create_hook_payload("UserPromptSubmit", session_id);
+    prompt.prompt = Some("Plan the migration".to_string());
+    assert_eq!(send_hook_event(&app, &prompt).await, StatusCode::OK);
+    let mut payload = create_hook_payload("PreToolUse", session_id);
+    payload.tool_name = Some("ExitPlanMode".to_string());
+    payload.tool_input = Some(serde_json::json!({
+        "plan": "## Migration\n1. Add the column\n2. Backfill existing rows\n3. Drop the old table"
+    }));
+    payload.tool_use_id = Some("plan_1".to_string());
+    assert_eq!(send_hook_event(&app, &payload).await, StatusCode::OK);
+
+    let json = get_plan().await;
+    assert_eq!(
+        json["plan"]["steps"],
+        serde_json::json!(["Add the column", "Backfill existing rows", "Drop the old table"])
+    );
+}
+
+#[tokio::test]
+async fn test_post_tool_use_failure_event() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let failures = vec![
+        (true, false, "Command timed out"),
+        (false, true, "User interrupted"),
+        (false, false, "Permission denied"),
+    ];
+
+    for (is_timeout, is_interrupt, error_msg) in failures {
+        let mut payload = create_hook_payload("PostToolUseFailure", session_id);
+        payload.tool_name = Some("Bash".to_string());
+        payload.tool_use_id = Some("tool_1".to_string());
+        payload.error = Some(error_msg.to_string());
+        payload.is_timeout = Some(is_timeout);
+        payload.is_interrupt = Some(is_interrupt);
+
+        let status = send_hook_event(&app, &payload).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_subagent_lifecycle_events() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut start_payload = create_hook_payload("SubagentStart", session_id);
+    start_payload.agent_id = Some("agent_123".to_string());
+    start_payload.agent_type = Some("code-reviewer".to_string());
+    assert_eq!(send_hook_event(&app, &start_payload).await, StatusCode::OK);
+
+    let mut stop_payload = create_hook_payload("SubagentStop", session_id);
+    stop_payload.agent_id = Some("agent_123".to_string());
+    assert_eq!(send_hook_event(&app, &stop_payload).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_interactive_prompt_event() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("PreToolUse", session_id);
+    payload.tool_name = Some("AskUserQuestion".to_string());
+    payload.tool_input = Some(serde_json::json!({
+        "questions": [{
+            "question": "Which model to use?",
+            "header": "Model",
+            "options": [
+                {"label": "opus", "description": "Claude 4 Opus"},
+                {"label": "sonnet", "description": "Claude 4 Sonnet"}
+            ],
+            "multiSelect": false
+        }]
+    }));
+    payload.tool_use_id = Some("ask_1".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_permission_request_event() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("PermissionRequest", session_id);
+    payload.tool_name = Some("Write".to_string());
+    payload.tool_input = Some(serde_json::json!({"path": "/etc/passwd"}));
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_pre_compact_event() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("PreCompact", session_id);
+    payload.trigger = Some("auto".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_multiple_sessions_isolated() {
+    let (app, state, temp) = create_test_app().await;
+
+    let session1 = common::create_session(&state, &temp).await;
+    let session2 = common::create_session(&state, &temp).await;
+
+    let mut payload1 = create_hook_payload("UserPromptSubmit", session1);
+    payload1.prompt = Some("Hello 1".to_string());
+    assert_eq!(send_hook_event(&app, &payload1).await, StatusCode::OK);
+
+    let mut payload2 = create_hook_payload("UserPromptSubmit", session2);
+    payload2.prompt = Some("Hello 2".to_string());
+    assert_eq!(send_hook_event(&app, &payload2).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_stop_event_with_transcript_path() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("Stop", session_id);
+    payload.transcript_path = Some("/home/user/.claude/projects/test/123.jsonl".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_hooks_are_idempotent() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Hello".to_string());
+
+    for _ in 0..5 {
+        let status = send_hook_event(&app, &payload).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_json_returns_error() {
+    let (app, _, _temp) = create_test_app().await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from("not valid json"))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert!(response.status().is_client_error(), "Malformed JSON should return client error");
+}
+
+#[tokio::test]
+async fn test_missing_required_fields_returns_error() {
+    let (app, _, _temp) = create_test_app().await;
+
+    let body = serde_json::json!({
+        "clauset_session_id": Uuid::new_v4().to_string(),
+        "session_id": "test"
+    });
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert!(response.status().is_client_error(), "Missing required field should return client error");
+}
+
+/// Post a raw JSON body to the hooks endpoint, returning status and JSON response.
+async fn send_raw_hook(app: &Router, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+#[tokio::test]
+async fn test_missing_session_id_reports_field_error() {
+    let (app, _, _temp) = create_test_app().await;
+
+    let (status, body) = send_raw_hook(
+        &app,
+        serde_json::json!({
+            "clauset_session_id": Uuid::new_v4().to_string(),
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+        }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "invalid hook payload");
+    assert_eq!(
+        body["fields"],
+        serde_json::json!([{ "field": "session_id", "message": "missing required field" }])
+    );
+}
+
+#[tokio::test]
+async fn test_validated_payload_with_aliases_and_nulls_succeeds() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let (status, body) = send_raw_hook(
+        &app,
+        serde_json::json!({
+            "clauset_session_id": session_id.to_string(),
+            "session_id": "claude-session",
+            "hookEventName": "UserPromptSubmit",
+            "prompt": "Validate me",
+            "cwd": null,
+            "permissionMode": "",
+        }),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    let interactions = state
+        .interaction_processor
+        .store()
+        .list_interactions(session_id, 10, 0)
+        .unwrap();
+    assert_eq!(interactions[0].user_prompt, "Validate me");
+}
+
+#[tokio::test]
+async fn test_hook_processing_performance() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Hello".to_string());
+
+    let start = std::time::Instant::now();
+    for _ in 0..100 {
+        let _ = send_hook_event(&app, &payload).await;
+    }
+    let elapsed = start.elapsed();
+
+    assert!(elapsed.as_secs() < 1, "100 hook events took {:?}, expected < 1s", elapsed);
+}
+
+#[tokio::test]
+async fn test_user_prompt_submit_with_all_fields() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Explain this code".to_string());
+    payload.cwd = Some("/home/user/project".to_string());
+    payload.context_window = Some(ContextWindow {
+        total_input_tokens: 10000,
+        total_output_tokens: 2000,
+        context_window_size: 200000,
+        current_usage: Some(CurrentUsage {
+            input_tokens: 10000,
+            output_tokens: 2000,
+            cache_creation_input_tokens: 500,
+            cache_read_input_tokens: 200,
+        }),
+    });
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_session_start_with_source() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let sources = vec!["startup", "resume", "clear", "compact"];
+
+    for source in sources {
+        let mut payload = create_hook_payload("SessionStart", session_id);
+        payload.source = Some(source.to_string());
+
+        let status = send_hook_event(&app, &payload).await;
+        assert_eq!(status, StatusCode::OK, "SessionStart with source '{}' should succeed", source);
+    }
+}
+
+#[tokio::test]
+async fn test_notification_event() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("Notification", session_id);
+    payload.message = Some("Build completed successfully".to_string());
+    payload.notification_type = Some("success".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_session_end_event() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("SessionEnd", session_id);
+    payload.reason = Some("clear".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_stop_hook_active_flag() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("Stop", session_id);
+    payload.stop_hook_active = Some(true);
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+
+    payload.stop_hook_active = Some(false);
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_stop_snapshots_context_percent() {
+    let (_app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+    let processor = &state.interaction_processor;
+
+    for (i, percent) in [30u8, 70, 10].into_iter().enumerate() {
+        let prompt = HookEvent::UserPromptSubmit {
+            session_id,
+            claude_session_id: "test-claude-session".to_string(),
+            prompt: format!("Prompt {i}"),
+            cwd: None,
+            context_window: None,
+        };
+        processor.process_event(&prompt, 0.0, 0, 0, 0, 0).await;
+
+        let stop = HookEvent::Stop {
+            session_id,
+            claude_session_id: "test-claude-session".to_string(),
+            stop_hook_active: false,
+            transcript_path: None,
+            context_window: None,
+        };
+        processor.process_event(&stop, 0.0, 0, 0, 0, percent).await;
+    }
+
+    let history = processor.store().context_history(session_id).unwrap();
+    assert_eq!(history, vec![(1, 30), (2, 70), (3, 10)]);
+}
+
+#[tokio::test]
+async fn test_interrupt_survives_later_stop_and_prompt() {
+    let (_app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+    let processor = &state.interaction_processor;
+
+    let prompt = |text: &str| HookEvent::UserPromptSubmit {
+        session_id,
+        claude_session_id: "test-claude-session".to_string(),
+        prompt: text.to_string(),
+        cwd: None,
+        context_window: None,
+    };
+    let stop = HookEvent::Stop {
+        session_id,
+        claude_session_id: "test-claude-session".to_string(),
+        stop_hook_active: false,
+        transcript_path: None,
+        context_window: None,
+    };
+
+    processor.process_event(&prompt("Long task"), 0.0, 0, 0, 0, 0).await;
+    assert_eq!(processor.interrupt(session_id).unwrap(), 1);
+    // Claude still fires Stop after Ctrl+C
+    processor.process_event(&stop, 0.2, 100, 50, 0, 40).await;
+    processor.process_event(&prompt("Something else"), 0.2, 100, 50, 0, 0).await;
+
+    let interactions = processor.store().list_interactions(session_id, 10, 0).unwrap();
+    let interrupted = interactions.iter().find(|i| i.user_prompt == "Long task").unwrap();
+    assert_eq!(interrupted.status, InteractionStatus::Interrupted);
+    let next = interactions.iter().find(|i| i.user_prompt == "Something else").unwrap();
+    assert_eq!(next.status, InteractionStatus::Active);
+}
+
+#[tokio::test]
+async fn test_late_cost_lands_on_previous_interaction() {
+    let (_app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+    let processor = &state.interaction_processor;
+
+    let prompt = |text: &str| HookEvent::UserPromptSubmit {
+        session_id,
+        claude_session_id: "test-claude-session".to_string(),
+        prompt: text.to_string(),
+        cwd: None,
+        context_window: None,
+    };
+    let stop = HookEvent::Stop {
+        session_id,
+        claude_session_id: "test-claude-session".to_string(),
+        stop_hook_active: false,
+        transcript_path: None,
+        context_window: None,
+    };
+
+    processor.process_event(&prompt("First"), 0.0, 0, 0, 0, 0).await;
+    processor.process_event(&stop, 0.1, 1000, 100, 0, 0).await;
+    // The first turn's final status line was read, but the next prompt
+    // starts before it is parsed
+    let first_id = processor.current_interaction(session_id);
+    processor.process_event(&prompt("Second"), 0.1, 1000, 100, 0, 0).await;
+    processor.update_costs_from_session(session_id, first_id, 0.3, 3000, 300);
+    processor.process_event(&stop, 0.5, 5000, 500, 0, 0).await;
+
+    let interactions = processor.store().list_interactions(session_id, 10, 0).unwrap();
+    let first = interactions.iter().find(|i| i.user_prompt == "First").unwrap();
+    assert!((first.cost_usd_delta - 0.3).abs() < 1e-9);
+    assert_eq!((first.input_tokens_delta, first.output_tokens_delta), (3000, 300));
+    let second = interactions.iter().find(|i| i.user_prompt == "Second").unwrap();
+    assert!((second.cost_usd_delta - 0.2).abs() < 1e-9);
+    assert_eq!((second.input_tokens_delta, second.output_tokens_delta), (2000, 200));
+}
+
+#[tokio::test]
+async fn test_fast_reply_keeps_its_own_cost() {
+    let (_app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+    let processor = &state.interaction_processor;
+
+    let prompt = |text: &str| HookEvent::UserPromptSubmit {
+        session_id,
+        claude_session_id: "test-claude-session".to_string(),
+        prompt: text.to_string(),
+        cwd: None,
+        context_window: None,
+    };
+    let stop = HookEvent::Stop {
+        session_id,
+        claude_session_id: "test-claude-session".to_string(),
+        stop_hook_active: false,
+        transcript_path: None,
+        context_window: None,
+    };
+
+    processor.process_event(&prompt("First"), 0.0, 0, 0, 0, 0).await;
+    processor.process_event(&stop, 0.1, 1000, 100, 0, 0).await;
+    // A quick text-only reply: its status line arrives right after its
+    // prompt, before any tool use
+    processor.process_event(&prompt("Second"), 0.1, 1000, 100, 0, 0).await;
+    let second_id = processor.current_interaction(session_id);
+    processor.update_costs_from_session(session_id, second_id, 0.3, 3000, 300);
+    processor.process_event(&stop, 0.3, 3000, 300, 0, 0).await;
+
+    let interactions = processor.store().list_interactions(session_id, 10, 0).unwrap();
+    let first = interactions.iter().find(|i| i.user_prompt == "First").unwrap();
+    assert!((first.cost_usd_delta - 0.1).abs() < 1e-9);
+    assert_eq!((first.input_tokens_delta, first.output_tokens_delta), (1000, 100));
+    let second = interactions.iter().find(|i| i.user_prompt == "Second").unwrap();
+    assert!((second.cost_usd_delta - 0.2).abs() < 1e-9);
+    assert_eq!((second.input_tokens_delta, second.output_tokens_delta), (2000, 200));
+}
+
+#[tokio::test]
+async fn test_oversized_prompt_is_truncated_and_searchable() {
+    let (app, state, temp) = create_test_app_with(|config| config.max_prompt_length = 1000).await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some(format!("zanzibarquokka please review {}", "log line ".repeat(50_000)));
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let store = state.interaction_processor.store();
+    let interactions = store.list_interactions(session_id, 10, 0).unwrap();
+    assert_eq!(interactions.len(), 1);
+    assert!(interactions[0].prompt_truncated);
+    assert_eq!(interactions[0].user_prompt.chars().count(), 1000);
+
+    let results = store.search_interactions("zanzibarquokka", Some(session_id), 10, 0).unwrap();
+    assert_eq!(results.len(), 1);
+}
+
+#[tokio::test]
+async fn test_mcp_tool_detection() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mcp_tools = vec![
+        "mcp__greptile__list_pull_requests",
+        "mcp__browser_playwright__navigate",
+        "mcp__filesystem__read_file",
+    ];
+
+    for tool_name in mcp_tools {
+        let mut payload = create_hook_payload("PreToolUse", session_id);
+        payload.tool_name = Some(tool_name.to_string());
+        payload.tool_input = Some(serde_json::json!({}));
+        payload.tool_use_id = Some("mcp_tool_1".to_string());
+
+        let status = send_hook_event(&app, &payload).await;
+        assert_eq!(status, StatusCode::OK, "MCP tool {} should succeed", tool_name);
+    }
+}
+
+#[tokio::test]
+async fn test_tool_response_with_error() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("PostToolUse", session_id);
+    payload.tool_name = Some("Bash".to_string());
+    payload.tool_input = Some(serde_json::json!({"command": "cat /nonexistent"}));
+    payload.tool_response = Some(serde_json::json!({
+        "error": "No such file or directory",
+        "exit_code": 1
+    }));
+    payload.tool_use_id = Some("tool_err_1".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_concurrent_hook_events() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let handles: Vec<_> = (0..10)
+        .map(|i| {
+            let app = app.clone();
+            let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+            payload.prompt = Some(format!("Message {}", i));
+            tokio::spawn(async move { send_hook_event(&app, &payload).await })
+        })
+        .collect();
+
+    for handle in handles {
+        let status = handle.await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_large_tool_input() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let large_content = "x".repeat(100_000);
+    let mut payload = create_hook_payload("PostToolUse", session_id);
+    payload.tool_name = Some("Write".to_string());
+    payload.tool_input = Some(serde_json::json!({
+        "path": "/test/large_file.txt",
+        "content": large_content
+    }));
+    payload.tool_response = Some(serde_json::json!({"success": true}));
+    payload.tool_use_id = Some("large_write_1".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_empty_string_fields() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("".to_string());
+
+    let status = send_hook_event(&app, &payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_untracked_tools_are_not_persisted() {
+    let (app, state, temp) = create_test_app_with(|config| {
+        config.tracked_tools = TrackedTools::Deny(vec!["Read".to_string()]);
+    })
+    .await;
+    let session_id = common::create_session(&state, &temp).await;
+    let file_path = temp.path().join("main.rs");
+    std::fs::write(&file_path, "fn main() {}\n").unwrap();
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Tidy up main".to_string());
+    send_hook_event(&app, &payload).await;
+
+    let mut payload = create_hook_payload("PreToolUse", session_id);
+    payload.tool_name = Some("Read".to_string());
+    payload.tool_use_id = Some("toolu_read".to_string());
+    payload.tool_input = Some(serde_json::json!({ "file_path": file_path }));
+    send_hook_event(&app, &payload).await;
+
+    let mut payload = create_hook_payload("PreToolUse", session_id);
+    payload.tool_name = Some("Edit".to_string());
+    payload.tool_use_id = Some("toolu_edit".to_string());
+    payload.tool_input = Some(serde_json::json!({ "file_path": file_path }));
+    send_hook_event(&app, &payload).await;
+
+    let store = state.interaction_processor.store();
+    let interactions = store.list_interactions(session_id, 10, 0).unwrap();
+    assert_eq!(interactions.len(), 1);
+
+    let tools = store.list_tool_invocations(interactions[0].id).unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].tool_name, "Edit");
+    assert!(store.get_tool_invocation_by_tool_use_id("toolu_read").unwrap().is_none());
+
+    // The tracked Edit still gets its before snapshot
+    let (before, _) = store.get_tool_snapshots(tools[0].id).unwrap();
+    assert!(before.is_some());
+}
+
+#[test]
+fn test_tracked_tools_matching() {
+    assert!(TrackedTools::All.is_tracked("Read"));
+
+    let allow = TrackedTools::Allow(vec!["Edit".to_string(), "Write".to_string()]);
+    assert!(allow.is_tracked("Edit"));
+    assert!(!allow.is_tracked("Read"));
+
+    let deny = TrackedTools::Deny(vec!["Read".to_string()]);
+    assert!(!deny.is_tracked("Read"));
+    assert!(deny.is_tracked("Bash"));
+}
+
+#[tokio::test]
+async fn test_excluded_paths_are_not_snapshotted() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+    let package_dir = temp.path().join("node_modules").join("pkg");
+    std::fs::create_dir_all(&package_dir).unwrap();
+    std::fs::write(package_dir.join("index.js"), "module.exports = {};\n").unwrap();
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Patch the dependency".to_string());
+    send_hook_event(&app, &payload).await;
+
+    let mut payload = create_hook_payload("PreToolUse", session_id);
+    payload.cwd = Some(temp.path().to_string_lossy().to_string());
+    payload.tool_name = Some("Edit".to_string());
+    payload.tool_use_id = Some("toolu_vendored".to_string());
+    payload.tool_input = Some(serde_json::json!({ "file_path": "node_modules/pkg/index.js" }));
+    send_hook_event(&app, &payload).await;
+
+    // The invocation is recorded, its file contents are not
+    let store = state.interaction_processor.store();
+    let tool = store
+        .get_tool_invocation_by_tool_use_id("toolu_vendored")
+        .unwrap()
+        .unwrap();
+    let (before, after) = store.get_tool_snapshots(tool.id).unwrap();
+    assert!(before.is_none() && after.is_none());
+}
+
+#[test]
+fn test_snapshot_exclude_matching() {
+    let defaults = SnapshotExclude::default();
+    let cwd = Path::new("/work/app");
+    assert!(defaults.is_excluded(Path::new("/work/app/node_modules/pkg/index.js"), Some(cwd)));
+    assert!(defaults.is_excluded(Path::new("/work/app/target/debug/build.rs"), Some(cwd)));
+    assert!(!defaults.is_excluded(Path::new("/work/app/src/main.rs"), Some(cwd)));
+    // Directory patterns don't match files of the same name
+    assert!(!defaults.is_excluded(Path::new("/work/app/target"), Some(cwd)));
+
+    let custom = SnapshotExclude(vec!["*.min.js".to_string(), "src/generated/**".to_string()]);
+    assert!(custom.is_excluded(Path::new("/work/app/static/app.min.js"), Some(cwd)));
+    assert!(custom.is_excluded(Path::new("/work/app/src/generated/api/types.rs"), Some(cwd)));
+    assert!(!custom.is_excluded(Path::new("/work/app/lib/src/generated/types.rs"), Some(cwd)));
+    assert!(!custom.is_excluded(Path::new("/work/app/src/main.rs"), Some(cwd)));
+}
+
+#[tokio::test]
+async fn test_prompt_file_refs_are_recorded() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+
+    let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+    payload.prompt = Some("Compare @src/config.rs with @docs/setup.md, then ask @alice".to_string());
+    assert_eq!(send_hook_event(&app, &payload).await, StatusCode::OK);
+
+    let store = state.interaction_processor.store();
+    let interaction = store.list_interactions(session_id, 10, 0).unwrap().remove(0);
+    assert_eq!(
+        store.prompt_file_refs(interaction.id).unwrap(),
+        vec!["src/config.rs".to_string(), "docs/setup.md".to_string()]
+    );
+
+    let referencing = store.interactions_referencing_file("config.rs", 10).unwrap();
+    assert_eq!(referencing.len(), 1);
+    assert_eq!(referencing[0].id, interaction.id);
+    assert_eq!(store.interactions_referencing_file("setup.md", 10).unwrap().len(), 1);
+    assert!(store.interactions_referencing_file("fig.rs", 10).unwrap().is_empty());
+}
@@ -0,0 +1,167 @@
+//! Integration tests for recording hook events and replaying them into a
+//! fresh store.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_core::{CreateSessionOptions, InteractionStore, SessionStore};
+use clauset_server::{routes, state::AppState};
+use clauset_types::{Interaction, SessionMode, ToolInvocation};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn send_hook(app: &Router, session_id: Uuid, event: serde_json::Value) {
+    let mut payload = serde_json::json!({
+        "clauset_session_id": session_id,
+        "session_id": "test-claude-session",
+    });
+    payload.as_object_mut().unwrap().extend(event.as_object().unwrap().clone());
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// The parts of an interaction that replay must reproduce (ids and
+/// timestamps are new on every run).
+fn interaction_key(i: &Interaction) -> (u32, String, Option<String>, String) {
+    (
+        i.sequence_number,
+        i.user_prompt.clone(),
+        i.assistant_summary.clone(),
+        format!("{:?}", i.status),
+    )
+}
+
+fn tool_key(t: &ToolInvocation) -> (u32, String, Option<String>, String, Option<PathBuf>, bool) {
+    (
+        t.sequence_number,
+        t.tool_name.clone(),
+        t.tool_use_id.clone(),
+        t.tool_input.to_string(),
+        t.file_path.clone(),
+        t.is_error,
+    )
+}
+
+fn snapshot(store: &InteractionStore, session_id: Uuid) -> Vec<(Interaction, Vec<ToolInvocation>)> {
+    let mut interactions = store.list_interactions(session_id, 100, 0).unwrap();
+    interactions.reverse();
+    interactions
+        .into_iter()
+        .map(|i| {
+            let tools = store.list_tool_invocations(i.id).unwrap();
+            (i, tools)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_replayed_hook_events_reproduce_interactions() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Add a test".to_string(),
+            model: Some("haiku".to_string()),
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+    let file_path = temp_dir.path().join("lib.rs");
+    std::fs::write(&file_path, "pub fn add() {}\n").unwrap();
+
+    let hooks = [
+        serde_json::json!({"hook_event_name": "UserPromptSubmit", "prompt": "Add a test"}),
+        serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Read",
+            "tool_use_id": "toolu_read",
+            "tool_input": {"file_path": file_path},
+        }),
+        serde_json::json!({
+            "hook_event_name": "PostToolUse",
+            "tool_name": "Read",
+            "tool_use_id": "toolu_read",
+            "tool_input": {"file_path": file_path},
+            "tool_response": {"content": "pub fn add() {}"},
+        }),
+        serde_json::json!({
+            "hook_event_name": "PreToolUse",
+            "tool_name": "Bash",
+            "tool_use_id": "toolu_bash",
+            "tool_input": {"command": "cargo test"},
+        }),
+        serde_json::json!({
+            "hook_event_name": "PostToolUse",
+            "tool_name": "Bash",
+            "tool_use_id": "toolu_bash",
+            "tool_input": {"command": "cargo test"},
+            "tool_response": {"stdout": "1 passed"},
+        }),
+        serde_json::json!({"hook_event_name": "Stop", "stop_hook_active": false}),
+        serde_json::json!({"hook_event_name": "UserPromptSubmit", "prompt": "Now run clippy"}),
+        serde_json::json!({"hook_event_name": "Stop", "stop_hook_active": false}),
+    ];
+    for hook in hooks.clone() {
+        send_hook(&app, session.id, hook).await;
+    }
+
+    let store = state.interaction_processor.store();
+    let recorded = store.list_hook_events(session.id).unwrap();
+    let names: Vec<_> = recorded.iter().map(|e| e.payload.hook_event_name.as_str()).collect();
+    let expected: Vec<_> = hooks.iter().map(|h| h["hook_event_name"].as_str().unwrap()).collect();
+    assert_eq!(names, expected);
+
+    // Replay into a fresh database holding only the session row
+    let replay_dir = TempDir::new().unwrap();
+    let replay_db = replay_dir.path().join("replay.db");
+    SessionStore::open(&replay_db)
+        .unwrap()
+        .insert(&state.session_manager.get_session(session.id).unwrap().unwrap())
+        .unwrap();
+    let replay_store = Arc::new(InteractionStore::open(&replay_db).unwrap());
+
+    let replayed = state
+        .interaction_processor
+        .replay_hook_events(session.id, replay_store.clone())
+        .await
+        .unwrap();
+    assert_eq!(replayed, hooks.len() as u32);
+
+    let original = snapshot(store, session.id);
+    let replay = snapshot(&replay_store, session.id);
+    assert_eq!(original.len(), 2);
+    assert_eq!(replay.len(), original.len());
+    for ((orig, orig_tools), (copy, copy_tools)) in original.iter().zip(&replay) {
+        assert_eq!(interaction_key(copy), interaction_key(orig));
+        assert_ne!(copy.id, orig.id);
+        let orig_tools: Vec<_> = orig_tools.iter().map(tool_key).collect();
+        let copy_tools: Vec<_> = copy_tools.iter().map(tool_key).collect();
+        assert_eq!(copy_tools, orig_tools);
+    }
+    assert_eq!(original[0].1.len(), 2);
+}
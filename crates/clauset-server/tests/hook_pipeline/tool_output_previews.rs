@@ -0,0 +1,98 @@
+//! Integration tests for per-tool output preview modes.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::SessionMode;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn send_hook(app: &Router, session_id: Uuid, event: serde_json::Value) {
+    let mut payload = serde_json::json!({
+        "clauset_session_id": session_id,
+        "session_id": "test-claude-session",
+    });
+    payload.as_object_mut().unwrap().extend(event.as_object().unwrap().clone());
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn run_tool(app: &Router, session_id: Uuid, tool_name: &str, tool_use_id: &str, output: &str) {
+    let tool_input = serde_json::json!({"command": "cargo test"});
+    send_hook(app, session_id, serde_json::json!({
+        "hook_event_name": "PreToolUse",
+        "tool_name": tool_name,
+        "tool_use_id": tool_use_id,
+        "tool_input": tool_input,
+    }))
+    .await;
+    send_hook(app, session_id, serde_json::json!({
+        "hook_event_name": "PostToolUse",
+        "tool_name": tool_name,
+        "tool_use_id": tool_use_id,
+        "tool_input": tool_input,
+        "tool_response": {"output": output},
+    }))
+    .await;
+}
+
+#[tokio::test]
+async fn test_bash_preview_keeps_tail_and_read_preview_keeps_head() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Run the tests".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+    let output = format!(
+        "running 200 tests\n{}test result: FAILED. 199 passed; 1 failed",
+        "test parser::tests::ok ... ok\n".repeat(200)
+    );
+
+    send_hook(&app, session.id, serde_json::json!({"hook_event_name": "UserPromptSubmit", "prompt": "Run the tests"})).await;
+    run_tool(&app, session.id, "Bash", "toolu_bash", &output).await;
+    run_tool(&app, session.id, "Read", "toolu_read", &output).await;
+
+    let store = state.interaction_processor.store();
+    let bash = store.get_tool_invocation_by_tool_use_id("toolu_bash").unwrap().unwrap();
+    let bash_preview = bash.tool_output_preview.unwrap();
+    assert!(bash_preview.starts_with("..."));
+    assert!(bash_preview.ends_with("test result: FAILED. 199 passed; 1 failed"));
+    assert!(!bash_preview.contains("running 200 tests"));
+
+    let read = store.get_tool_invocation_by_tool_use_id("toolu_read").unwrap().unwrap();
+    let read_preview = read.tool_output_preview.unwrap();
+    assert!(read_preview.starts_with("running 200 tests"));
+    assert!(read_preview.ends_with("..."));
+    assert!(!read_preview.contains("FAILED"));
+}
@@ -0,0 +1,198 @@
+//! Integration tests for mirroring hook events to an external webhook.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use clauset_server::{
+    config::Config,
+    routes,
+    webhook::{WebhookConfig, MAX_IN_FLIGHT},
+};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir, webhook: WebhookConfig) -> Router {
+    let config = Config {
+        hook_webhook: Some(webhook),
+        ..common::test_config(temp_dir)
+    };
+    let state = common::test_state(config);
+    Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .with_state(state)
+}
+
+/// Start a receiver that answers `status` and reports every body it gets.
+async fn start_receiver(status: StatusCode) -> (String, mpsc::UnboundedReceiver<Value>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    start_receiver_on(listener, status)
+}
+
+fn start_receiver_on(listener: tokio::net::TcpListener, status: StatusCode) -> (String, mpsc::UnboundedReceiver<Value>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new()
+        .route(
+            "/events",
+            post(move |State(tx): State<mpsc::UnboundedSender<Value>>, Json(body): Json<Value>| async move {
+                let _ = tx.send(body);
+                status
+            }),
+        )
+        .with_state(tx);
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}/events"), rx)
+}
+
+fn webhook(url: String, events: &[&str]) -> WebhookConfig {
+    WebhookConfig {
+        url,
+        events: events.iter().map(|e| e.to_string()).collect(),
+        max_attempts: 3,
+        timeout_secs: 2,
+    }
+}
+
+fn hook(event_name: &str) -> Value {
+    json!({
+        "clauset_session_id": uuid::Uuid::new_v4(),
+        "session_id": "claude-session",
+        "hook_event_name": event_name,
+        "stop_hook_active": false,
+        "tool_name": "Read",
+        "tool_input": {"file_path": "/tmp/x"},
+        "tool_use_id": "toolu_1",
+        "cwd": "/tmp",
+    })
+}
+
+async fn post_hook(app: &Router, payload: &Value) -> StatusCode {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_matching_hook_is_mirrored() {
+    let temp_dir = TempDir::new().unwrap();
+    let (url, mut received) = start_receiver(StatusCode::OK).await;
+    let app = create_test_app(&temp_dir, webhook(url, &["Stop"]));
+
+    assert_eq!(post_hook(&app, &hook("PreToolUse")).await, StatusCode::OK);
+    let stop = hook("Stop");
+    assert_eq!(post_hook(&app, &stop).await, StatusCode::OK);
+
+    let body = tokio::time::timeout(Duration::from_secs(5), received.recv())
+        .await
+        .expect("webhook should receive the Stop event")
+        .unwrap();
+    assert_eq!(body["hook_event_name"], "Stop");
+    assert_eq!(body["clauset_session_id"], stop["clauset_session_id"]);
+    assert_eq!(body["session_id"], "claude-session");
+
+    // The filtered-out PreToolUse event was never sent
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(received.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_failing_webhook_is_retried_without_affecting_response() {
+    let temp_dir = TempDir::new().unwrap();
+    let (url, mut received) = start_receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+    let app = create_test_app(&temp_dir, webhook(url, &[]));
+
+    assert_eq!(post_hook(&app, &hook("Stop")).await, StatusCode::OK);
+
+    // Every attempt reaches the receiver, then delivery gives up
+    for _ in 0..3 {
+        tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .expect("failed deliveries should be retried")
+            .unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+    assert!(received.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_unreachable_webhook_does_not_block_hooks() {
+    let temp_dir = TempDir::new().unwrap();
+    // Bind and drop a listener to get a port nothing listens on
+    let addr = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+    let app = create_test_app(&temp_dir, webhook(format!("http://{addr}/events"), &[]));
+
+    let status = tokio::time::timeout(Duration::from_millis(500), post_hook(&app, &hook("Stop")))
+        .await
+        .expect("hook response must not wait on the webhook");
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_ipv6_literal_webhook_is_mirrored() {
+    // Hosts without IPv6 loopback can't run this
+    let Ok(listener) = tokio::net::TcpListener::bind("[::1]:0").await else {
+        return;
+    };
+    let temp_dir = TempDir::new().unwrap();
+    let (url, mut received) = start_receiver_on(listener, StatusCode::OK);
+    assert!(url.starts_with("http://[::1]:"));
+    let app = create_test_app(&temp_dir, webhook(url, &[]));
+
+    assert_eq!(post_hook(&app, &hook("Stop")).await, StatusCode::OK);
+    let body = tokio::time::timeout(Duration::from_secs(5), received.recv())
+        .await
+        .expect("webhook on an IPv6 literal should receive the event")
+        .unwrap();
+    assert_eq!(body["hook_event_name"], "Stop");
+}
+
+#[tokio::test]
+async fn test_pending_deliveries_are_capped() {
+    // A receiver that takes every request but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let receiver = Router::new()
+        .route(
+            "/events",
+            post(move |State(tx): State<mpsc::UnboundedSender<Value>>, Json(body): Json<Value>| async move {
+                let _ = tx.send(body);
+                std::future::pending::<StatusCode>().await
+            }),
+        )
+        .with_state(tx);
+    tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+    let temp_dir = TempDir::new().unwrap();
+    let config = WebhookConfig {
+        max_attempts: 1,
+        timeout_secs: 30,
+        ..webhook(format!("http://{addr}/events"), &[])
+    };
+    let app = create_test_app(&temp_dir, config);
+
+    for _ in 0..MAX_IN_FLIGHT + 5 {
+        assert_eq!(post_hook(&app, &hook("Stop")).await, StatusCode::OK);
+    }
+    for _ in 0..MAX_IN_FLIGHT {
+        tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .expect("deliveries up to the cap should be sent")
+            .unwrap();
+    }
+    // Events past the cap were dropped rather than queued
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(received.try_recv().is_err());
+}
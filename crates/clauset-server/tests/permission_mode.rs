@@ -9,34 +9,29 @@ use axum::{
     routing::post,
     Router,
 };
-use clauset_core::{CreateSessionOptions, ProcessEvent};
+use clauset_core::ProcessEvent;
 use clauset_server::{config::Config, routes, state::AppState};
-use clauset_types::{HookEventPayload, PermissionMode, SessionMode};
+use clauset_types::{HookEventPayload, PermissionMode};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempfile::TempDir;
 use tower::ServiceExt;
 use uuid::Uuid;
 
+mod common;
+
 /// Create a minimal test app state for integration testing.
 async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
     let temp_dir = TempDir::new().unwrap();
-    let db_path = temp_dir.path().join("test.db");
-    let static_dir = temp_dir.path().join("static");
-    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("static")).unwrap();
 
     let config = Config {
-        port: 0,
-        host: "127.0.0.1".to_string(),
-        db_path: db_path.clone(),
-        static_dir,
-        claude_path: PathBuf::from("/usr/bin/true"),
         max_concurrent_sessions: 5,
         default_model: "haiku".to_string(),
-        projects_root: temp_dir.path().join("projects"),
+        ..common::test_config(&temp_dir)
     };
 
-    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let state = common::test_state(config);
 
     let app = Router::new()
         .route("/api/hooks", post(routes::hooks::receive))
@@ -45,19 +40,6 @@ async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
     (app, state, temp_dir)
 }
 
-/// Helper to create a test session with minimal options.
-async fn create_test_session(state: &AppState, temp_dir: &TempDir) -> Uuid {
-    let opts = CreateSessionOptions {
-        project_path: temp_dir.path().to_path_buf(),
-        prompt: "Test prompt".to_string(),
-        model: Some("haiku".to_string()),
-        mode: SessionMode::Terminal,
-        resume_session_id: None,
-    };
-    let session = state.session_manager.create_session(opts).await.unwrap();
-    session.id
-}
-
 /// Create a minimal hook payload with required fields.
 fn create_hook_payload(event_name: &str, clauset_session_id: Uuid) -> HookEventPayload {
     HookEventPayload {
@@ -206,7 +188,7 @@ fn test_permission_mode_from_hook_value_whitespace() {
 #[tokio::test]
 async fn test_hook_with_permission_mode_updates_activity() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // Initially, no permission mode should be set
     let activity = state.session_manager.get_activity(session_id).await;
@@ -229,7 +211,7 @@ async fn test_hook_with_permission_mode_updates_activity() {
 #[tokio::test]
 async fn test_hook_with_camelcase_permission_mode() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // Send hook with camelCase mode (as Claude Code sends it)
     let mut payload = create_hook_payload("UserPromptSubmit", session_id);
@@ -250,7 +232,7 @@ async fn test_hook_with_camelcase_permission_mode() {
 #[tokio::test]
 async fn test_hook_with_bypass_permissions_mode() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     let mut payload = create_hook_payload("PreToolUse", session_id);
     payload.permission_mode = Some("bypassPermissions".to_string());
@@ -271,7 +253,7 @@ async fn test_hook_with_bypass_permissions_mode() {
 #[tokio::test]
 async fn test_mode_change_event_broadcast_on_change() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // Subscribe to events before sending
     let mut event_rx = state.session_manager.subscribe();
@@ -309,7 +291,7 @@ async fn test_mode_change_event_broadcast_on_change() {
 #[tokio::test]
 async fn test_mode_change_event_not_broadcast_when_same() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // First, set the mode to "plan"
     let mut payload = create_hook_payload("SessionStart", session_id);
@@ -356,7 +338,7 @@ async fn test_mode_change_event_not_broadcast_when_same() {
 #[tokio::test]
 async fn test_mode_change_broadcast_on_different_mode() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // First, set mode to "default"
     let mut payload = create_hook_payload("SessionStart", session_id);
@@ -404,7 +386,7 @@ async fn test_mode_change_broadcast_on_different_mode() {
 #[tokio::test]
 async fn test_hook_without_permission_mode_keeps_existing() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // First, set mode to "plan"
     let mut payload = create_hook_payload("SessionStart", session_id);
@@ -431,7 +413,7 @@ async fn test_hook_without_permission_mode_keeps_existing() {
 #[tokio::test]
 async fn test_get_activity_returns_permission_mode() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // Set mode via hook
     let mut payload = create_hook_payload("SessionStart", session_id);
@@ -454,7 +436,7 @@ async fn test_get_activity_returns_permission_mode() {
 #[tokio::test]
 async fn test_mode_cycling_default_to_plan() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     // Start with default
     let mut payload = create_hook_payload("SessionStart", session_id);
@@ -475,7 +457,7 @@ async fn test_mode_cycling_default_to_plan() {
 #[tokio::test]
 async fn test_mode_cycling_through_all_modes() {
     let (app, state, temp) = create_test_app().await;
-    let session_id = create_test_session(&state, &temp).await;
+    let session_id = common::create_session(&state, &temp).await;
 
     let modes = [
         ("default", PermissionMode::Default),
@@ -502,3 +484,29 @@ async fn test_mode_cycling_through_all_modes() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_interactions_record_active_permission_mode() {
+    let (app, state, temp) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp).await;
+    let cwd = temp.path().to_string_lossy().to_string();
+
+    for (raw_mode, prompt) in [("plan", "Plan the refactor"), ("acceptEdits", "Do it")] {
+        let mut payload = create_hook_payload("UserPromptSubmit", session_id);
+        payload.permission_mode = Some(raw_mode.to_string());
+        payload.prompt = Some(prompt.to_string());
+        payload.cwd = Some(cwd.clone());
+        assert_eq!(send_hook_event(&app, &payload).await, StatusCode::OK);
+    }
+
+    let store = state.interaction_processor.store();
+    let mut interactions = store.list_interactions(session_id, 10, 0).unwrap();
+    interactions.reverse();
+    let contexts: Vec<_> = interactions
+        .iter()
+        .map(|i| store.interaction_context(i.id).unwrap().unwrap())
+        .collect();
+    assert_eq!(contexts[0].permission_mode, Some(PermissionMode::Plan));
+    assert_eq!(contexts[1].permission_mode, Some(PermissionMode::AcceptEdits));
+    assert_eq!(contexts[1].cwd, Some(PathBuf::from(&cwd)));
+}
@@ -0,0 +1,189 @@
+//! Integration tests for the background task registry and admin endpoints.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_core::{PromptIndexer, QuietHours, QuietWindow};
+use clauset_server::{
+    routes,
+    state::AppState,
+    tasks::{self, TaskStatus},
+};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+/// Create a minimal test app with the admin routes mounted.
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/admin/tasks", get(routes::admin::list_tasks))
+        .route("/api/admin/tasks/{id}/cancel", post(routes::admin::cancel_task))
+        .route("/api/admin/reindex-fts", post(routes::admin::reindex_fts))
+        .route("/api/admin/integrity", get(routes::admin::check_integrity))
+        .route("/api/admin/integrity/repair", post(routes::admin::repair_integrity))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+/// Write a fake ~/.claude directory with `count` sessions, one prompt each.
+fn write_claude_history(claude_dir: &Path, count: usize) {
+    let project = "/tmp/backfill-project";
+    let transcripts = claude_dir.join("projects").join(project.replace('/', "-"));
+    std::fs::create_dir_all(&transcripts).unwrap();
+
+    let mut history = std::fs::File::create(claude_dir.join("history.jsonl")).unwrap();
+    for i in 0..count {
+        let session_id = format!("session-{i}");
+        let entry = serde_json::json!({
+            "display": format!("prompt {i}"),
+            "timestamp": 1_700_000_000_000i64 + i as i64,
+            "project": project,
+            "sessionId": session_id,
+        });
+        writeln!(history, "{}", entry).unwrap();
+
+        let message = serde_json::json!({
+            "type": "user",
+            "message": { "role": "user", "content": format!("Please do task number {i}") },
+            "timestamp": "2025-01-01T00:00:00Z",
+        });
+        std::fs::write(transcripts.join(format!("{session_id}.jsonl")), format!("{}\n", message))
+            .unwrap();
+    }
+}
+
+async fn send(app: &Router, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+/// Quiet hours covering the current time, so a backfill holds before its
+/// first session until cancelled or the window is lifted.
+fn quiet_now() -> Arc<QuietHours> {
+    let now = chrono::Local::now().time();
+    Arc::new(QuietHours::new(vec![QuietWindow {
+        start: now - chrono::Duration::hours(1),
+        end: now + chrono::Duration::hours(1),
+    }]))
+}
+
+/// Poll until the task stops running.
+async fn wait_until_finished(state: &AppState, task_id: uuid::Uuid) -> tasks::TaskInfo {
+    let mut info = state.tasks.get(task_id).unwrap();
+    for _ in 0..500 {
+        if info.status != TaskStatus::Running {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        info = state.tasks.get(task_id).unwrap();
+    }
+    info
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_cancel_backfill_through_admin_api() {
+    let (app, state, temp) = create_test_app().await;
+    let claude_dir = temp.path().join("claude");
+    write_claude_history(&claude_dir, 3);
+
+    let indexer = PromptIndexer::with_claude_dir(state.interaction_processor.store().clone(), claude_dir)
+        .with_quiet_hours(quiet_now());
+    let task_id = tasks::spawn_prompt_backfill(state.tasks.clone(), indexer);
+
+    let (status, body) = send(&app, "GET", "/api/admin/tasks").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tasks"][0]["id"], task_id.to_string());
+    assert_eq!(body["tasks"][0]["kind"], "prompt_backfill");
+    assert_eq!(body["tasks"][0]["status"], "running");
+
+    let (status, body) = send(&app, "POST", &format!("/api/admin/tasks/{task_id}/cancel")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["cancel_requested"], true);
+
+    // The task notices the token while paused and stops before any session
+    let info = wait_until_finished(&state, task_id).await;
+    assert_eq!(info.status, TaskStatus::Cancelled);
+    assert!(info.finished_at.is_some());
+    assert_eq!(info.progress.done, 0);
+    assert_eq!(state.interaction_processor.store().get_prompt_count().unwrap(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_backfill_pauses_during_quiet_hours() {
+    let (_app, state, temp) = create_test_app().await;
+    let claude_dir = temp.path().join("claude");
+    write_claude_history(&claude_dir, 20);
+
+    let quiet_hours = quiet_now();
+    let indexer = PromptIndexer::with_claude_dir(state.interaction_processor.store().clone(), claude_dir)
+        .with_quiet_hours(quiet_hours.clone());
+    let task_id = tasks::spawn_prompt_backfill(state.tasks.clone(), indexer);
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let info = state.tasks.get(task_id).unwrap();
+    assert_eq!(info.status, TaskStatus::Running);
+    assert_eq!(info.progress.done, 0, "backfill should not run during quiet hours");
+    assert_eq!(state.interaction_processor.store().get_prompt_count().unwrap(), 0);
+
+    // Lifting the window resumes the paused backfill
+    quiet_hours.set_windows(Vec::new());
+    let info = wait_until_finished(&state, task_id).await;
+    assert_eq!(info.status, TaskStatus::Completed);
+    assert_eq!(info.progress.done, 20);
+    assert_eq!(state.interaction_processor.store().get_prompt_count().unwrap(), 20);
+}
+
+#[tokio::test]
+async fn test_cancel_unknown_task_returns_404() {
+    let (app, _state, _temp) = create_test_app().await;
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        &format!("/api/admin/tasks/{}/cancel", uuid::Uuid::new_v4()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_reindex_fts() {
+    let (app, _state, _temp) = create_test_app().await;
+
+    let (status, _) = send(&app, "POST", "/api/admin/reindex-fts").await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_integrity_endpoints() {
+    let (app, _state, _temp) = create_test_app().await;
+
+    let (status, body) = send(&app, "GET", "/api/admin/integrity").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["foreign_keys_enabled"], true);
+    assert_eq!(body["orphaned_tool_invocations"], 0);
+
+    let (status, body) = send(&app, "POST", "/api/admin/integrity/repair").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["orphaned_interactions"], 0);
+}
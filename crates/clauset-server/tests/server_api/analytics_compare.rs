@@ -0,0 +1,73 @@
+//! Integration tests for comparing analytics between two periods.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use chrono::{TimeZone, Utc};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::{Interaction, SessionMode};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/analytics/compare", get(routes::interactions::compare_analytics))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn get_json(app: &Router, uri: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_compare_weeks() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Refactor".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+
+    let store = state.interaction_processor.store();
+    for (seq, day, cost) in [(1, 2, 1.0), (2, 4, 1.0), (3, 6, 1.0), (4, 10, 1.5), (5, 12, 4.5)] {
+        let mut interaction = Interaction::new(session.id, seq, format!("Prompt {seq}"));
+        interaction.started_at = Utc.with_ymd_and_hms(2026, 9, day, 12, 0, 0).unwrap();
+        interaction.cost_usd_delta = cost;
+        store.insert_interaction(&interaction).unwrap();
+    }
+
+    let (status, body) = get_json(
+        &app,
+        "/api/analytics/compare?period_a=2026-09-01..2026-09-08&period_b=2026-09-08..2026-09-15",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["period_a"]["summary"]["interaction_count"], 3);
+    assert_eq!(body["period_b"]["summary"]["interaction_count"], 2);
+    assert_eq!(body["delta"]["cost_usd"]["absolute"], 3.0);
+    assert_eq!(body["delta"]["cost_usd"]["percent"], 100.0);
+    assert_eq!(body["delta"]["interactions"]["percent"].as_f64().unwrap().round(), -33.0);
+    assert!(body["delta"]["output_tokens"]["percent"].is_null());
+
+    let (status, _) = get_json(&app, "/api/analytics/compare?period_a=last-week&period_b=2026-09-08..2026-09-15").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
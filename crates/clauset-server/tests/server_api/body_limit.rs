@@ -0,0 +1,77 @@
+//! Integration tests for the request body size limit.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{config::Config, routes, state::AppState};
+use clauset_types::SessionMode;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+const LIMIT: usize = 4096;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let config = Config {
+        max_body_bytes: LIMIT,
+        ..common::test_config(temp_dir)
+    };
+    let state = common::test_state(config);
+    let api = Router::new().route("/hooks", post(routes::hooks::receive));
+    let app = Router::new()
+        .nest("/api", routes::limit_body_size(api, state.config().max_body_bytes))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn post_hook(app: &Router, session_id: Uuid, output: &str) -> (StatusCode, String) {
+    let payload = serde_json::json!({
+        "clauset_session_id": session_id,
+        "session_id": "test-claude-session",
+        "hook_event_name": "PostToolUse",
+        "tool_name": "Bash",
+        "tool_use_id": "toolu_build",
+        "tool_input": {"command": "cargo build"},
+        "tool_response": {"stdout": output},
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&bytes).to_string())
+}
+
+#[tokio::test]
+async fn test_body_limit() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Build it".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+
+    let (status, _) = post_hook(&app, session.id, "Finished dev profile").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = post_hook(&app, session.id, &"x".repeat(LIMIT * 2)).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert!(body.contains("4096 byte limit"), "{body}");
+}
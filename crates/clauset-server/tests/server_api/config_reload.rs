@@ -0,0 +1,85 @@
+//! Integration tests for reloading the config file into a running server.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_server::{config::Config, routes, state::AppState};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+fn write_config(path: &Path, temp_dir: &TempDir, projects_root: &Path, port: u16) {
+    let toml = format!(
+        "host = \"127.0.0.1\"\nport = {port}\ndb_path = {:?}\nstatic_dir = {:?}\nclaude_path = \"/usr/bin/true\"\nprojects_root = {:?}\n",
+        temp_dir.path().join("test.db"),
+        temp_dir.path().join("static"),
+        projects_root,
+    );
+    std::fs::write(path, toml).unwrap();
+}
+
+fn create_test_app(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/projects", get(routes::projects::list))
+        .route("/api/admin/config/reload", post(routes::admin::reload_config))
+        .with_state(state)
+}
+
+async fn send(app: &Router, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().method(method).uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+}
+
+#[tokio::test]
+async fn test_reload_applies_reloadable_fields_and_reports_restart_required() {
+    let temp_dir = TempDir::new().unwrap();
+    let old_root = temp_dir.path().join("old-projects");
+    let new_root = temp_dir.path().join("new-projects");
+    std::fs::create_dir_all(old_root.join("alpha")).unwrap();
+    std::fs::create_dir_all(new_root.join("beta")).unwrap();
+
+    let config_path = temp_dir.path().join("clauset.toml");
+    write_config(&config_path, &temp_dir, &old_root, 9100);
+    let config = Config::load_from(&config_path).unwrap();
+    let state = Arc::new(AppState::new(config).unwrap().with_config_path(&config_path));
+    let app = create_test_app(state.clone());
+
+    let (_, projects) = send(&app, "GET", "/api/projects").await;
+    assert_eq!(projects["projects"][0]["name"], "alpha");
+
+    write_config(&config_path, &temp_dir, &new_root, 9200);
+    let (status, reload) = send(&app, "POST", "/api/admin/config/reload").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reload["applied"], serde_json::json!(["projects_root"]));
+    assert_eq!(reload["restart_required"], serde_json::json!(["port"]));
+
+    assert_eq!(state.config().projects_root, new_root);
+    assert_eq!(state.config().port, 9100);
+    let (_, projects) = send(&app, "GET", "/api/projects").await;
+    assert_eq!(projects["projects"][0]["name"], "beta");
+
+    // Reloading an unchanged file reports nothing
+    let (_, reload) = send(&app, "POST", "/api/admin/config/reload").await;
+    assert_eq!(reload["applied"], serde_json::json!([]));
+    assert_eq!(reload["restart_required"], serde_json::json!(["port"]));
+}
+
+#[tokio::test]
+async fn test_reload_without_config_file_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = create_test_app(common::test_state(common::test_config(&temp_dir)));
+
+    let (status, _) = send(&app, "POST", "/api/admin/config/reload").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,77 @@
+//! Integration tests for the terminal parser diagnostic endpoint.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_server::{config::Config, routes};
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+fn create_test_app(debug_endpoints: bool) -> (Router, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = Config {
+        debug_endpoints,
+        ..common::test_config(&temp_dir)
+    };
+
+    let state = common::test_state(config);
+
+    let app = Router::new()
+        .route("/api/debug/parse", post(routes::debug::parse))
+        .with_state(state);
+
+    (app, temp_dir)
+}
+
+async fn post_snippet(app: &Router, uri: &str, snippet: &[u8]) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/octet-stream")
+        .body(Body::from(snippet.to_vec()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+const STATUS_SNIPPET: &[u8] = "\x1b[1m● Read(src/main.rs)\x1b[0m\n\
+    ✻ Thinking… (esc to interrupt)\n\
+    \x1b[2mHaiku 4.5 | $0.07 |\x1b[0m\n\
+    2.4K/1.2K | ctx:21%\n\
+    ⏸ plan mode on (shift+tab to cycle)\n"
+    .as_bytes();
+
+#[tokio::test]
+async fn test_parse_returns_status_line() {
+    let (app, _temp_dir) = create_test_app(true);
+    let (status, body) = post_snippet(&app, "/api/debug/parse", STATUS_SNIPPET).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(!body["stripped_text"].as_str().unwrap().contains('\x1b'));
+    assert_eq!(body["status"]["model"], "Haiku 4.5");
+    assert_eq!(body["status"]["cost"], 0.07);
+    assert_eq!(body["status"]["input_tokens"], 2400);
+    assert_eq!(body["status"]["output_tokens"], 1200);
+    assert_eq!(body["status"]["context_percent"], 21);
+    assert_eq!(body["permission_mode"], "plan");
+    assert!(body["activity"].is_string());
+
+    // A known width rejoins the wrapped status line the same way
+    let (status, body) = post_snippet(&app, "/api/debug/parse?rows=40&cols=20", STATUS_SNIPPET).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"]["model"], "Haiku 4.5");
+}
+
+#[tokio::test]
+async fn test_parse_disabled_by_default() {
+    let (app, _temp_dir) = create_test_app(false);
+    let (status, _) = post_snippet(&app, "/api/debug/parse", STATUS_SNIPPET).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,18 @@
+//! Integration tests for the server's REST and WebSocket endpoints.
+
+#[path = "../common/mod.rs"]
+mod common;
+mod admin_tasks;
+mod analytics_compare;
+mod body_limit;
+mod config_reload;
+mod debug_parse;
+mod preflight;
+mod profiles;
+mod projects;
+mod prompt_commands;
+mod query_api;
+mod search_stream;
+mod static_dir;
+mod ws_protocol;
+mod ws_resync;
@@ -0,0 +1,103 @@
+//! Tests for the environment pre-flight checks.
+
+use crate::common;
+use clauset_server::config::Config;
+use clauset_server::preflight::{run_checks, CheckStatus, PreflightEnv, PreflightReport};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// An environment where every check passes, with a fake `claude` binary.
+fn healthy_env(temp: &TempDir) -> PreflightEnv {
+    let root = temp.path();
+    let claude_path = root.join("bin/claude");
+    std::fs::create_dir_all(claude_path.parent().unwrap()).unwrap();
+    std::fs::write(&claude_path, "").unwrap();
+
+    let claude_dir = root.join("home/.claude");
+    std::fs::create_dir_all(claude_dir.join("hooks")).unwrap();
+    std::fs::write(claude_dir.join("hooks/clauset-hook.sh"), "#!/bin/bash\n").unwrap();
+    std::fs::write(
+        claude_dir.join("settings.json"),
+        r#"{"hooks":{"Stop":[{"hooks":[{"type":"command","command":"~/.claude/hooks/clauset-hook.sh"}]}]}}"#,
+    )
+    .unwrap();
+
+    let static_dir = root.join("static");
+    std::fs::create_dir_all(&static_dir).unwrap();
+    std::fs::write(static_dir.join("index.html"), "<html></html>").unwrap();
+
+    PreflightEnv {
+        claude_path,
+        claude_dir,
+        static_dir,
+        hook_url: "http://localhost:8080/api/hooks".to_string(),
+    }
+}
+
+fn fake_version(_: &Path) -> Result<String, String> {
+    Ok("2.0.14 (Claude Code)".to_string())
+}
+
+fn status_of(report: &PreflightReport, name: &str) -> CheckStatus {
+    report.checks.iter().find(|c| c.name == name).unwrap().status
+}
+
+#[test]
+fn test_healthy_environment_passes_every_check() {
+    let temp = TempDir::new().unwrap();
+    let report = run_checks(&healthy_env(&temp), fake_version);
+
+    assert!(report.ok);
+    let names: Vec<_> = report.checks.iter().map(|c| c.name).collect();
+    assert_eq!(names, vec!["claude_binary", "claude_dir", "hooks", "static_dir"]);
+    assert!(report.checks[0].detail.contains("2.0.14"));
+    assert!(report.checks.iter().all(|c| c.remediation.is_none()));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["checks"][0]["status"], "pass");
+    assert!(json["checks"][0].get("remediation").is_none());
+}
+
+#[test]
+fn test_missing_claude_dir_is_reported_as_failure() {
+    let temp = TempDir::new().unwrap();
+    let env = healthy_env(&temp);
+    std::fs::remove_dir_all(&env.claude_dir).unwrap();
+
+    let report = run_checks(&env, fake_version);
+    assert!(!report.ok);
+    assert_eq!(status_of(&report, "claude_binary"), CheckStatus::Pass);
+    assert_eq!(status_of(&report, "claude_dir"), CheckStatus::Fail);
+    // Hooks live in ~/.claude too
+    assert_eq!(status_of(&report, "hooks"), CheckStatus::Fail);
+
+    let check = report.checks.iter().find(|c| c.name == "claude_dir").unwrap();
+    assert!(check.detail.contains("does not exist"));
+    assert!(check.remediation.is_some());
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["ok"], false);
+    assert_eq!(json["checks"][1]["status"], "fail");
+}
+
+#[test]
+fn test_failing_binary_and_unregistered_hooks() {
+    let temp = TempDir::new().unwrap();
+    let env = healthy_env(&temp);
+    std::fs::write(env.claude_dir.join("settings.json"), "{}").unwrap();
+
+    let report = run_checks(&env, |_| Err("permission denied".to_string()));
+    assert_eq!(status_of(&report, "claude_binary"), CheckStatus::Fail);
+    assert!(report.checks[0].detail.contains("permission denied"));
+    assert_eq!(status_of(&report, "hooks"), CheckStatus::Fail);
+    assert_eq!(status_of(&report, "static_dir"), CheckStatus::Pass);
+}
+
+#[test]
+fn test_env_uses_configured_claude_dir() {
+    let temp = TempDir::new().unwrap();
+    let config = Config {
+        claude_dir: Some(temp.path().join("claude")),
+        ..common::test_config(&temp)
+    };
+    assert_eq!(PreflightEnv::from_config(&config).claude_dir, temp.path().join("claude"));
+}
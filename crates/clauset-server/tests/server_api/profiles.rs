@@ -0,0 +1,205 @@
+//! Integration tests for profile-scoped interaction databases.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_core::{CreateSessionOptions, SessionStore};
+use clauset_server::{
+    config::{Config, ProfileConfig},
+    routes,
+    state::{AppState, PROFILE_HEADER},
+};
+use clauset_types::{Interaction, Prompt, SessionMode};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let config = Config {
+        profiles: HashMap::from([(
+            "work".to_string(),
+            ProfileConfig {
+                db_path: temp_dir.path().join("work.db"),
+                projects: vec![temp_dir.path().join("projects").join("work")],
+            },
+        )]),
+        ..common::test_config(temp_dir)
+    };
+    let state = common::test_state(config);
+    let app = Router::new()
+        .route("/api/search", get(routes::interactions::search))
+        .route("/api/analytics", get(routes::interactions::get_analytics))
+        .route("/api/prompts", get(routes::prompts::list_prompts))
+        .route("/api/sessions/{id}/transcript.txt", get(routes::sessions::get_transcript_text))
+        .route("/api/sessions/import", post(routes::sessions::import_session))
+        .route("/api/hooks", post(routes::hooks::receive))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn get_json(app: &Router, uri: &str, profile: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder().uri(uri);
+    if let Some(profile) = profile {
+        request = request.header(PROFILE_HEADER, profile);
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+fn prompts(results: &serde_json::Value) -> Vec<String> {
+    results["interactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["interaction"]["user_prompt"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_profile_stores_are_isolated() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Refactor".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+    SessionStore::open(&temp_dir.path().join("work.db"))
+        .unwrap()
+        .insert(&session)
+        .unwrap();
+
+    let personal = Interaction::new(session.id, 1, "Refactor the parser at home".to_string());
+    state.interaction_store(None).unwrap().insert_interaction(&personal).unwrap();
+    let work = Interaction::new(session.id, 1, "Refactor the parser at work".to_string());
+    state.interaction_store(Some("work")).unwrap().insert_interaction(&work).unwrap();
+
+    let (status, results) = get_json(&app, "/api/search?q=parser&scope=prompts", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(prompts(&results), ["Refactor the parser at home"]);
+
+    let (status, results) = get_json(&app, "/api/search?q=parser&scope=prompts", Some("work")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(prompts(&results), ["Refactor the parser at work"]);
+
+    let (status, analytics) = get_json(&app, "/api/analytics", Some("work")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(analytics["summary"]["interaction_count"], 1);
+
+    // Session and prompt routes honor the header too
+    let uri = format!("/api/sessions/{}/transcript.txt", session.id);
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(&uri)
+                .header(PROFILE_HEADER, "work")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(String::from_utf8_lossy(&bytes), "User: Refactor the parser at work\n");
+
+    let prompt = Prompt::new("claude".to_string(), temp_dir.path().to_path_buf(), "Work prompt".to_string(), 1);
+    state.interaction_store(Some("work")).unwrap().insert_prompt(&prompt).unwrap();
+    let (_, listed) = get_json(&app, "/api/prompts", None).await;
+    assert_eq!(listed["total_count"], 0);
+    let (_, listed) = get_json(&app, "/api/prompts", Some("work")).await;
+    assert_eq!(listed["prompts"][0]["preview"], "Work prompt");
+}
+
+#[tokio::test]
+async fn test_default_only_writes_reject_profile() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, _) = create_test_app(&temp_dir);
+
+    let body = serde_json::json!({
+        "claude_session_id": uuid::Uuid::new_v4().to_string(),
+        "project_path": temp_dir.path(),
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/sessions/import")
+        .header("content-type", "application/json")
+        .header(PROFILE_HEADER, "work")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_unknown_profile_is_not_found() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, _) = create_test_app(&temp_dir);
+
+    let (status, _) = get_json(&app, "/api/search?q=parser", Some("missing")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_hooks_are_captured_into_the_project_profile() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+
+    let mut sessions = Vec::new();
+    for project in ["work", "home"] {
+        let project_path = temp_dir.path().join("projects").join(project);
+        std::fs::create_dir_all(&project_path).unwrap();
+        let session = state
+            .session_manager
+            .create_session(CreateSessionOptions {
+                project_path,
+                prompt: String::new(),
+                model: None,
+                mode: SessionMode::Terminal,
+                resume_session_id: None,
+            })
+            .await
+            .unwrap();
+        let hook = serde_json::json!({
+            "clauset_session_id": session.id,
+            "session_id": "claude-session",
+            "hook_event_name": "UserPromptSubmit",
+            "prompt": format!("Fix the {project} build"),
+        });
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/hooks")
+            .header("content-type", "application/json")
+            .body(Body::from(hook.to_string()))
+            .unwrap();
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::OK);
+        sessions.push(session.id);
+    }
+
+    let (_, results) = get_json(&app, "/api/search?q=build&scope=prompts", Some("work")).await;
+    assert_eq!(prompts(&results), ["Fix the work build"]);
+    let (_, results) = get_json(&app, "/api/search?q=build&scope=prompts", None).await;
+    assert_eq!(prompts(&results), ["Fix the home build"]);
+
+    let work = state.interaction_store(Some("work")).unwrap();
+    assert_eq!(work.list_interactions(sessions[0], 10, 0).unwrap().len(), 1);
+    assert!(work.list_interactions(sessions[1], 10, 0).unwrap().is_empty());
+}
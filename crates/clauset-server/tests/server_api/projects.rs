@@ -0,0 +1,129 @@
+//! Integration tests for project creation and registration.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_server::{routes, state::AppState};
+use routes::projects::{normalize_project_path, CreateProjectError};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::create_dir_all(temp_dir.path().join("projects")).unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/projects", get(routes::projects::list).post(routes::projects::create))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn send(app: &Router, method: &str, body: Option<serde_json::Value>) -> (StatusCode, serde_json::Value) {
+    let mut builder = Request::builder().method(method).uri("/api/projects");
+    let body = match body {
+        Some(json) => {
+            builder = builder.header("content-type", "application/json");
+            Body::from(json.to_string())
+        }
+        None => Body::empty(),
+    };
+    let response = app.clone().oneshot(builder.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+#[tokio::test]
+async fn test_register_relative_path_is_canonicalized_and_deduped() {
+    let (app, _state, temp) = create_test_app().await;
+    let external = temp.path().join("external").join("app");
+    std::fs::create_dir_all(&external).unwrap();
+    let canonical = std::fs::canonicalize(&external).unwrap();
+
+    // Relative to the projects root, with redundant components and a trailing slash
+    let (status, body) = send(&app, "POST", Some(serde_json::json!({ "path": "../external/./app/" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["path"], canonical.to_string_lossy().as_ref());
+    assert_eq!(body["name"], "app");
+
+    // Through a symlink: same canonical path, no duplicate
+    let link = temp.path().join("link");
+    std::os::unix::fs::symlink(&external, &link).unwrap();
+    let (status, body) = send(&app, "POST", Some(serde_json::json!({ "path": link, "name": "Other" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["path"], canonical.to_string_lossy().as_ref());
+    assert_eq!(body["name"], "app");
+
+    let (_, body) = send(&app, "GET", None).await;
+    let matching: Vec<_> = body["projects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|p| p["path"] == canonical.to_string_lossy().as_ref())
+        .collect();
+    assert_eq!(matching.len(), 1);
+}
+
+#[tokio::test]
+async fn test_register_missing_path_rejected() {
+    let (app, state, temp) = create_test_app().await;
+
+    let (status, _) = send(
+        &app,
+        "POST",
+        Some(serde_json::json!({ "path": temp.path().join("missing") })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let file = temp.path().join("file.txt");
+    std::fs::write(&file, "not a dir").unwrap();
+    let (status, _) = send(&app, "POST", Some(serde_json::json!({ "path": file }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    assert!(state.session_manager.list_registered_projects().unwrap().is_empty());
+}
+
+#[test]
+fn test_normalize_expands_home() {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir_all(home.path().join("code/site")).unwrap();
+    let canonical_home = std::fs::canonicalize(home.path()).unwrap();
+    let base = PathBuf::from("/nonexistent-base");
+
+    let path = normalize_project_path("~/code/site", &base, Some(home.path())).unwrap();
+    assert_eq!(path, canonical_home.join("code/site"));
+    assert_eq!(
+        normalize_project_path("~", &base, Some(home.path())).unwrap(),
+        canonical_home
+    );
+
+    assert!(matches!(
+        normalize_project_path("~/code/missing", &base, Some(home.path())),
+        Err(CreateProjectError::NotFound(_))
+    ));
+    assert!(matches!(
+        normalize_project_path("~/code", &base, None),
+        Err(CreateProjectError::InvalidName(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_create_by_name_still_supported() {
+    let (app, _state, temp) = create_test_app().await;
+
+    let (status, body) = send(&app, "POST", Some(serde_json::json!({ "name": "fresh" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "fresh");
+    assert!(temp.path().join("projects/fresh").is_dir());
+}
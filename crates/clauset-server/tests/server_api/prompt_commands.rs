@@ -0,0 +1,83 @@
+//! Integration tests for exporting frequent prompts as slash commands.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_server::routes;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> Router {
+    let state = common::test_state(common::test_config(temp_dir));
+    Router::new()
+        .route("/api/prompts/export-commands", post(routes::prompts::export_commands))
+        .with_state(state)
+}
+
+async fn export_to(app: &Router, dir: &std::path::Path) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/prompts/export-commands")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::json!({ "dir": dir }).to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_export_commands_only_into_project_commands_dirs() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = create_test_app(&temp_dir);
+    let project = temp_dir.path().join("projects").join("app");
+    std::fs::create_dir_all(&project).unwrap();
+
+    let commands = project.join(".claude").join("commands");
+    let (status, body) = export_to(&app, &commands).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["exported"], 0);
+    assert!(commands.is_dir());
+
+    // Arbitrary directories are refused
+    let elsewhere = temp_dir.path().join("elsewhere");
+    std::fs::create_dir_all(&elsewhere).unwrap();
+    let (status, _) = export_to(&app, &elsewhere).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    // ...as are commands dirs of directories that aren't projects
+    let (status, _) = export_to(&app, &elsewhere.join(".claude").join("commands")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(!elsewhere.join(".claude").exists());
+    let (status, _) = export_to(&app, &project.join("..").join(".claude").join("commands")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_export_commands_does_not_follow_symlinks_out_of_project() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = create_test_app(&temp_dir);
+    let project = temp_dir.path().join("projects").join("app");
+    std::fs::create_dir_all(&project).unwrap();
+    let outside = temp_dir.path().join("outside");
+    std::fs::create_dir_all(&outside).unwrap();
+
+    std::os::unix::fs::symlink(&outside, project.join(".claude")).unwrap();
+    let (status, _) = export_to(&app, &project.join(".claude").join("commands")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(!outside.join("commands").exists());
+
+    // A link that stays inside the project is fine
+    std::fs::remove_file(project.join(".claude")).unwrap();
+    std::fs::create_dir_all(project.join("config")).unwrap();
+    std::os::unix::fs::symlink(project.join("config"), project.join(".claude")).unwrap();
+    let (status, body) = export_to(&app, &project.join(".claude").join("commands")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["dir"].as_str().unwrap().ends_with("config/commands"));
+    assert!(project.join("config").join("commands").is_dir());
+}
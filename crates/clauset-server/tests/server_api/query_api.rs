@@ -0,0 +1,135 @@
+//! Integration tests for the ad-hoc read-only SQL endpoint.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_server::{
+    config::{Config, QueryApiConfig},
+    routes,
+    state::AppState,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_types::{Interaction, SessionMode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+const TOKEN: &str = "test-token";
+
+fn create_test_app(temp_dir: &TempDir, query_api: Option<QueryApiConfig>) -> (Router, Arc<AppState>) {
+    let config = Config {
+        query_api,
+        ..common::test_config(temp_dir)
+    };
+    let state = common::test_state(config);
+    let app = Router::new()
+        .route("/api/query", post(routes::interactions::run_query))
+        .with_state(state.clone());
+    (app, state)
+}
+
+fn enabled() -> Option<QueryApiConfig> {
+    Some(QueryApiConfig {
+        token: TOKEN.to_string(),
+        max_rows: 100,
+        timeout_ms: 200,
+    })
+}
+
+async fn query(app: &Router, token: Option<&str>, sql: &str) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/api/query")
+        .header("content-type", "application/json");
+    if let Some(token) = token {
+        request = request.header("authorization", format!("Bearer {token}"));
+    }
+    let request = request.body(Body::from(json!({ "sql": sql }).to_string())).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn seed_interaction(state: &AppState, temp_dir: &TempDir) {
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Explain".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+    state
+        .interaction_processor
+        .store()
+        .insert_interaction(&Interaction::new(session.id, 1, "explain the parser".to_string()))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_query_select_returns_rows() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir, enabled());
+    seed_interaction(&state, &temp_dir).await;
+
+    let (status, body) = query(&app, Some(TOKEN), "SELECT user_prompt, sequence_number FROM interactions").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["columns"], json!(["user_prompt", "sequence_number"]));
+    assert_eq!(body["rows"], json!([["explain the parser", 1]]));
+    assert_eq!(body["truncated"], json!(false));
+}
+
+#[tokio::test]
+async fn test_query_rejects_writes_and_pragmas() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir, enabled());
+    seed_interaction(&state, &temp_dir).await;
+
+    for sql in [
+        "INSERT INTO interactions (id) VALUES ('x')",
+        "UPDATE interactions SET user_prompt = 'changed'",
+        "PRAGMA user_version = 7",
+        "SELECT 1; DELETE FROM interactions",
+    ] {
+        let (status, _) = query(&app, Some(TOKEN), sql).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{sql}");
+    }
+
+    let (_, body) = query(&app, Some(TOKEN), "SELECT user_prompt FROM interactions").await;
+    assert_eq!(body["rows"], json!([["explain the parser"]]));
+}
+
+#[tokio::test]
+async fn test_query_runaway_times_out() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, _) = create_test_app(&temp_dir, enabled());
+
+    let runaway = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT count(*) FROM n";
+    let (status, _) = query(&app, Some(TOKEN), runaway).await;
+    assert_eq!(status, StatusCode::REQUEST_TIMEOUT);
+
+    let (status, body) = query(&app, Some(TOKEN), "SELECT 1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["rows"], json!([[1]]));
+}
+
+#[tokio::test]
+async fn test_query_requires_token_and_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, _) = create_test_app(&temp_dir, enabled());
+    assert_eq!(query(&app, None, "SELECT 1").await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(query(&app, Some("wrong"), "SELECT 1").await.0, StatusCode::UNAUTHORIZED);
+
+    let disabled_dir = TempDir::new().unwrap();
+    let (app, _) = create_test_app(&disabled_dir, None);
+    assert_eq!(query(&app, Some(TOKEN), "SELECT 1").await.0, StatusCode::NOT_FOUND);
+}
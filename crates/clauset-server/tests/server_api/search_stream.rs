@@ -0,0 +1,108 @@
+//! Integration tests for the streaming NDJSON search endpoint.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::{FileSnapshot, Interaction, SessionMode, SnapshotType, ToolInvocation};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/search/stream", get(routes::interactions::search_stream))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+/// Seed one interaction, tool invocation, and file snapshot mentioning "parser".
+async fn seed_search_data(state: &AppState, temp_dir: &TempDir) {
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Test prompt".to_string(),
+            model: None,
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+
+    let store = state.interaction_processor.store();
+    let interaction = Interaction::new(session.id, 1, "Refactor the parser module".to_string());
+    store.insert_interaction(&interaction).unwrap();
+
+    let tool = ToolInvocation::new(
+        interaction.id,
+        1,
+        "Edit".to_string(),
+        serde_json::json!({ "file_path": "/repo/src/parser.rs" }),
+        None,
+    );
+    store.insert_tool_invocation(&tool).unwrap();
+
+    let (hash, _) = store.store_file_content(b"fn parse() {}").unwrap();
+    let snapshot = FileSnapshot::new(
+        interaction.id,
+        Some(tool.id),
+        PathBuf::from("/repo/src/parser.rs"),
+        hash,
+        SnapshotType::After,
+        13,
+    );
+    store.insert_file_snapshot(&snapshot).unwrap();
+}
+
+#[tokio::test]
+async fn test_search_stream_emits_ndjson_per_category() {
+    let (app, state, temp) = create_test_app().await;
+    seed_search_data(&state, &temp).await;
+
+    let request = Request::builder()
+        .uri("/api/search/stream?q=parser")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = String::from_utf8(bytes.to_vec()).unwrap();
+    let lines: Vec<serde_json::Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    let kinds: Vec<&str> = lines.iter().map(|l| l["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, vec!["interaction", "tool_invocation", "file"]);
+    assert_eq!(lines[0]["interaction"]["user_prompt"], "Refactor the parser module");
+    assert_eq!(lines[1]["tool_name"], "Edit");
+    assert_eq!(lines[2]["file_path"], "/repo/src/parser.rs");
+}
+
+#[tokio::test]
+async fn test_search_stream_empty_results() {
+    let (app, state, temp) = create_test_app().await;
+    seed_search_data(&state, &temp).await;
+
+    let request = Request::builder()
+        .uri("/api/search/stream?q=nomatchanywhere")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(bytes.is_empty());
+}
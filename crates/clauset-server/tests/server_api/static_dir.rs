@@ -0,0 +1,69 @@
+//! Integration tests for serving the frontend when static_dir is missing.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_server::routes;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+fn api() -> Router {
+    Router::new().route("/api/health", get(routes::health))
+}
+
+async fn get_page(app: &Router, uri: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[tokio::test]
+async fn test_missing_static_dir_serves_placeholder() {
+    let temp_dir = TempDir::new().unwrap();
+    let static_dir = temp_dir.path().join("missing");
+    let app = routes::serve_static(api(), &static_dir, false).unwrap();
+
+    let (status, body) = get_page(&app, "/").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("static_dir"));
+    assert!(body.contains("npm run build"));
+
+    // Other paths say why they're missing rather than an empty 404
+    let (status, body) = get_page(&app, "/sessions/abc").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body.contains("frontend is not built"));
+    assert!(body.contains(&static_dir.display().to_string()));
+
+    // The API is unaffected
+    let (status, _) = get_page(&app, "/api/health").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_missing_static_dir_fails_when_required() {
+    let temp_dir = TempDir::new().unwrap();
+    let static_dir = temp_dir.path().join("missing");
+    let err = routes::serve_static(api(), &static_dir, true).unwrap_err();
+    assert!(err.to_string().contains(&static_dir.display().to_string()));
+
+    // An empty directory counts as missing
+    std::fs::create_dir_all(&static_dir).unwrap();
+    assert!(routes::serve_static(api(), &static_dir, true).is_err());
+}
+
+#[tokio::test]
+async fn test_built_frontend_is_served() {
+    let temp_dir = TempDir::new().unwrap();
+    let static_dir = temp_dir.path();
+    std::fs::write(static_dir.join("index.html"), "<html>app</html>").unwrap();
+    let app = routes::serve_static(api(), static_dir, true).unwrap();
+
+    let (status, body) = get_page(&app, "/").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "<html>app</html>");
+}
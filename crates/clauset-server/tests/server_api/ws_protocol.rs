@@ -0,0 +1,132 @@
+//! Integration tests for WebSocket subprotocol negotiation.
+
+use axum::{
+    extract::{ws::WebSocketUpgrade, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use clauset_server::{global_ws, routes, state::AppState, ws_protocol};
+use clauset_types::WsServerMessage;
+use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, http::header, Error, Message};
+use uuid::Uuid;
+use crate::common;
+
+async fn start_server(temp_dir: &TempDir) -> (SocketAddr, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/ws/sessions/{id}", get(routes::ws::upgrade))
+        .route("/ws/events", get(global_events))
+        .with_state(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (addr, state)
+}
+
+/// The global dashboard socket, as the server binary mounts it.
+async fn global_events(State(state): State<Arc<AppState>>, headers: HeaderMap, ws: WebSocketUpgrade) -> Response {
+    match ws_protocol::negotiate_upgrade(ws, &headers) {
+        Ok((ws, protocol)) => ws.on_upgrade(move |socket| async move {
+            let _ = global_ws::handle_global_websocket(socket, state, protocol).await;
+        }),
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+fn request(addr: SocketAddr, session_id: Uuid, protocol: Option<&str>) -> tokio_tungstenite::tungstenite::handshake::client::Request {
+    let mut request = format!("ws://{addr}/ws/sessions/{session_id}").into_client_request().unwrap();
+    if let Some(protocol) = protocol {
+        request
+            .headers_mut()
+            .insert(header::SEC_WEBSOCKET_PROTOCOL, protocol.parse().unwrap());
+    }
+    request
+}
+
+#[tokio::test]
+async fn test_v1_subprotocol_is_echoed() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, state) = start_server(&temp_dir).await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+
+    let (mut client, response) = tokio_tungstenite::connect_async(request(addr, session_id, Some("clauset.v1")))
+        .await
+        .unwrap();
+    assert_eq!(response.headers()[header::SEC_WEBSOCKET_PROTOCOL], "clauset.v1");
+
+    // v1 messages are JSON text frames
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let Message::Text(text) = frame else {
+        panic!("expected a text frame, got {frame:?}");
+    };
+    match serde_json::from_str(&text).unwrap() {
+        WsServerMessage::SessionInit { session_id: id, .. } => assert_eq!(id, session_id),
+        other => panic!("expected SessionInit, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_no_subprotocol_is_served_as_v1() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, state) = start_server(&temp_dir).await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+
+    let (mut client, response) = tokio_tungstenite::connect_async(request(addr, session_id, None))
+        .await
+        .unwrap();
+    assert!(response.headers().get(header::SEC_WEBSOCKET_PROTOCOL).is_none());
+    let frame = client.next().await.unwrap().unwrap();
+    assert!(matches!(frame, Message::Text(_)));
+}
+
+#[tokio::test]
+async fn test_unknown_subprotocol_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, state) = start_server(&temp_dir).await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+
+    match tokio_tungstenite::connect_async(request(addr, session_id, Some("clauset.v99"))).await {
+        Err(Error::Http(response)) => assert_eq!(response.status(), 400),
+        other => panic!("expected a 400, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_global_pong_uses_negotiated_protocol() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, _state) = start_server(&temp_dir).await;
+
+    let mut request = format!("ws://{addr}/ws/events").into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert(header::SEC_WEBSOCKET_PROTOCOL, "clauset.v1".parse().unwrap());
+    let (mut client, response) = tokio_tungstenite::connect_async(request).await.unwrap();
+    assert_eq!(response.headers()[header::SEC_WEBSOCKET_PROTOCOL], "clauset.v1");
+
+    client
+        .send(Message::Text(r#"{"type":"ping","timestamp":42}"#.into()))
+        .await
+        .unwrap();
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let Message::Text(text) = frame else {
+        panic!("expected a text frame, got {frame:?}");
+    };
+    match serde_json::from_str(&text).unwrap() {
+        WsServerMessage::Pong { timestamp } => assert_eq!(timestamp, 42),
+        other => panic!("expected Pong, got {other:?}"),
+    }
+}
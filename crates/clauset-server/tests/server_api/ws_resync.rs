@@ -0,0 +1,166 @@
+//! Integration tests for resuming a terminal stream over a reconnected
+//! WebSocket.
+
+use axum::{routing::get, Router};
+use clauset_server::{routes, state::AppState};
+use clauset_types::{WsClientMessage, WsServerMessage};
+use futures::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+use crate::common;
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn start_server(temp_dir: &TempDir) -> (SocketAddr, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/ws/sessions/{id}", get(routes::ws::upgrade))
+        .with_state(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (addr, state)
+}
+
+/// Connect and send a SyncRequest as a reconnecting client would.
+async fn reconnect(addr: SocketAddr, session_id: Uuid, last_seq: u64) -> Client {
+    let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws/sessions/{session_id}"))
+        .await
+        .unwrap();
+    let request = WsClientMessage::SyncRequest { last_seq, cols: 80, rows: 24 };
+    client
+        .send(Message::text(serde_json::to_string(&request).unwrap()))
+        .await
+        .unwrap();
+    client
+}
+
+async fn next_message(client: &mut Client) -> WsServerMessage {
+    loop {
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+            .await
+            .expect("timed out waiting for a message")
+            .unwrap()
+            .unwrap();
+        if let Message::Text(text) = frame {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+/// Skip the connect preamble and return the SyncResponse's
+/// (buffer_start_seq, buffer_end_seq, full_buffer).
+async fn sync_response(client: &mut Client) -> (u64, u64, Option<Vec<u8>>) {
+    loop {
+        if let WsServerMessage::SyncResponse {
+            buffer_start_seq,
+            buffer_end_seq,
+            full_buffer,
+            ..
+        } = next_message(client).await
+        {
+            return (buffer_start_seq, buffer_end_seq, full_buffer);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_reconnect_within_buffer_sends_only_missing_chunks() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, state) = start_server(&temp_dir).await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+    for line in ["zero\r\n", "one\r\n", "two\r\n", "three\r\n", "four\r\n"] {
+        state.session_manager.buffers().append(session_id, line.as_bytes()).await;
+    }
+
+    let mut client = reconnect(addr, session_id, 2).await;
+
+    let (start, end, full_buffer) = sync_response(&mut client).await;
+    assert_eq!((start, end), (0, 4));
+    assert!(full_buffer.is_none());
+    match next_message(&mut client).await {
+        WsServerMessage::ChunkBatch {
+            start_seq,
+            data,
+            chunk_count,
+            is_complete,
+        } => {
+            assert_eq!(start_seq, 3);
+            assert_eq!(chunk_count, 2);
+            assert_eq!(data, b"three\r\nfour\r\n");
+            assert!(is_complete);
+        }
+        other => panic!("expected ChunkBatch, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_reconnect_after_eviction_sends_full_buffer() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, state) = start_server(&temp_dir).await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+    // Five 200 KB chunks overflow the 500 KB buffer, evicting seq 0..=2
+    for _ in 0..5 {
+        state.session_manager.buffers().append(session_id, &[b'x'; 200 * 1024]).await;
+    }
+
+    let mut client = reconnect(addr, session_id, 1).await;
+
+    let (start, end, full_buffer) = sync_response(&mut client).await;
+    assert_eq!((start, end), (3, 4));
+    assert_eq!(full_buffer.unwrap().len(), 2 * 200 * 1024);
+}
+
+#[tokio::test]
+async fn test_reconnect_up_to_date_sends_nothing_more() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, state) = start_server(&temp_dir).await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+    for line in ["zero\r\n", "one\r\n", "two\r\n"] {
+        state.session_manager.buffers().append(session_id, line.as_bytes()).await;
+    }
+
+    let mut client = reconnect(addr, session_id, 2).await;
+
+    let (_, end, full_buffer) = sync_response(&mut client).await;
+    assert_eq!(end, 2);
+    assert!(full_buffer.is_none());
+    let more = tokio::time::timeout(std::time::Duration::from_millis(200), client.next()).await;
+    assert!(more.is_err(), "unexpected message after SyncResponse: {more:?}");
+}
+
+#[tokio::test]
+async fn test_get_activity_returns_current_snapshot() {
+    let temp_dir = TempDir::new().unwrap();
+    let (addr, state) = start_server(&temp_dir).await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+    state.session_manager.buffers().append(session_id, b"booting\r\n").await;
+    state.session_manager.mark_session_busy(session_id).await;
+
+    let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws/sessions/{session_id}"))
+        .await
+        .unwrap();
+    client
+        .send(Message::text(serde_json::to_string(&WsClientMessage::GetActivity).unwrap()))
+        .await
+        .unwrap();
+
+    loop {
+        if let WsServerMessage::ActivityUpdate {
+            session_id: sid,
+            current_activity,
+            current_step,
+            ..
+        } = next_message(&mut client).await
+        {
+            assert_eq!(sid, session_id);
+            assert_eq!(current_activity, "Thinking...");
+            assert_eq!(current_step.as_deref(), Some("Thinking"));
+            break;
+        }
+    }
+}
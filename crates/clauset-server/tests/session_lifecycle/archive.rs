@@ -0,0 +1,120 @@
+//! Integration tests for archiving terminal scrollback when a session ends.
+
+use clauset_core::CreateSessionOptions;
+use clauset_server::{config::Config, event_processor, state::AppState};
+use clauset_types::SessionMode;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use uuid::Uuid;
+use crate::common;
+
+const OUTPUT: &[u8] = b"\x1b[1m\xe2\x97\x8f Build\x1b[0m\r\n\x1b[32mok\x1b[0m 12 passed\r\n";
+
+fn create_state(temp_dir: &TempDir, archive_dir: Option<PathBuf>, strip_ansi: bool) -> AppState {
+    let config = Config {
+        archive_dir,
+        archive_strip_ansi: strip_ansi,
+        ..common::test_config(temp_dir)
+    };
+    AppState::new(config).expect("Failed to create AppState")
+}
+
+/// Create a session with terminal output, then terminate it.
+async fn terminate_with_output(state: &AppState, temp_dir: &TempDir) -> Uuid {
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Run the build".to_string(),
+            model: Some("haiku".to_string()),
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+    state.session_manager.buffers().append(session.id, OUTPUT).await;
+    state.session_manager.terminate_session(session.id).await.unwrap();
+    session.id
+}
+
+#[tokio::test]
+async fn test_terminated_session_is_archived_raw() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive_dir = temp_dir.path().join("archive");
+    let state = create_state(&temp_dir, Some(archive_dir.clone()), false);
+
+    let session_id = terminate_with_output(&state, &temp_dir).await;
+
+    let archived = std::fs::read(archive_dir.join(format!("{session_id}.log"))).unwrap();
+    assert_eq!(archived, OUTPUT);
+}
+
+#[tokio::test]
+async fn test_terminated_session_is_archived_stripped() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive_dir = temp_dir.path().join("archive");
+    let state = create_state(&temp_dir, Some(archive_dir.clone()), true);
+
+    let session_id = terminate_with_output(&state, &temp_dir).await;
+
+    let archived = std::fs::read_to_string(archive_dir.join(format!("{session_id}.log"))).unwrap();
+    assert_eq!(archived, "● Build\r\nok 12 passed\r\n");
+}
+
+#[tokio::test]
+async fn test_no_archive_without_archive_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = create_state(&temp_dir, None, false);
+
+    terminate_with_output(&state, &temp_dir).await;
+
+    let logs = std::fs::read_dir(temp_dir.path())
+        .unwrap()
+        .filter(|e| e.as_ref().unwrap().path().extension().is_some_and(|ext| ext == "log"))
+        .count();
+    assert_eq!(logs, 0);
+}
+
+/// Write a fake Claude binary that prints a line and exits on its own.
+#[cfg(unix)]
+fn write_exiting_claude(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("fake-claude");
+    std::fs::write(&script, "#!/bin/sh\necho 'all done here'\nsleep 0.2\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_session_exiting_on_its_own_is_archived() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive_dir = temp_dir.path().join("archive");
+    let config = Config {
+        claude_path: write_exiting_claude(temp_dir.path()),
+        archive_dir: Some(archive_dir.clone()),
+        archive_strip_ansi: true,
+        ..common::test_config(&temp_dir)
+    };
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    event_processor::spawn_event_processor(state.clone());
+
+    let session_id = common::create_session(&state, &temp_dir).await;
+    state.session_manager.start_session(session_id, "").await.unwrap();
+
+    let path = archive_dir.join(format!("{session_id}.log"));
+    let archived = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Ok(archived) = std::fs::read_to_string(&path) {
+                return archived;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("the exited session should be archived");
+    assert!(archived.contains("all done here"));
+}
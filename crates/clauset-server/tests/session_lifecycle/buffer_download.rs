@@ -0,0 +1,145 @@
+//! Integration tests for the terminal buffer download endpoint.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_server::{routes, state::AppState};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions/{id}/buffer", get(routes::sessions::download_buffer))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+/// Fill a session's buffer with three chunks, returning the concatenated bytes.
+async fn fill_buffer(state: &AppState, session_id: Uuid) -> Vec<u8> {
+    let chunks: Vec<Vec<u8>> = (0..3u8)
+        .map(|i| (0..100_000u32).map(|n| b'a' + ((n as u8).wrapping_add(i)) % 26).collect())
+        .collect();
+    let buffers = state.session_manager.buffers();
+    for chunk in &chunks {
+        buffers.append(session_id, chunk).await;
+    }
+    chunks.concat()
+}
+
+async fn get_buffer(
+    app: &Router,
+    session_id: Uuid,
+    range: Option<&str>,
+) -> (StatusCode, axum::http::HeaderMap, Vec<u8>) {
+    get_buffer_if_range(app, session_id, range, None).await
+}
+
+async fn get_buffer_if_range(
+    app: &Router,
+    session_id: Uuid,
+    range: Option<&str>,
+    if_range: Option<&str>,
+) -> (StatusCode, axum::http::HeaderMap, Vec<u8>) {
+    let mut request = Request::builder().uri(format!("/api/sessions/{session_id}/buffer"));
+    if let Some(range) = range {
+        request = request.header(header::RANGE, range);
+    }
+    if let Some(if_range) = if_range {
+        request = request.header(header::IF_RANGE, if_range);
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, headers, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_range_request_returns_partial_content() {
+    let (app, state, _temp) = create_test_app().await;
+    let session_id = Uuid::new_v4();
+    let buffer = fill_buffer(&state, session_id).await;
+    let total = buffer.len();
+
+    // A range spanning the first two chunks
+    let (status, headers, body) = get_buffer(&app, session_id, Some("bytes=99990-100009")).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(headers[header::CONTENT_RANGE], format!("bytes 99990-100009/{total}"));
+    assert_eq!(headers[header::CONTENT_LENGTH], "20");
+    assert_eq!(headers[header::ACCEPT_RANGES], "bytes");
+    assert_eq!(body, &buffer[99_990..=100_009]);
+
+    // The tail of the buffer
+    let (status, headers, body) = get_buffer(&app, session_id, Some("bytes=-500")).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        headers[header::CONTENT_RANGE],
+        format!("bytes {}-{}/{total}", total - 500, total - 1)
+    );
+    assert_eq!(body, &buffer[total - 500..]);
+
+    // Resuming from an offset
+    let (status, _, body) = get_buffer(&app, session_id, Some("bytes=250000-")).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(body, &buffer[250_000..]);
+}
+
+#[tokio::test]
+async fn test_full_and_unsatisfiable_downloads() {
+    let (app, state, _temp) = create_test_app().await;
+    let session_id = Uuid::new_v4();
+    let buffer = fill_buffer(&state, session_id).await;
+
+    let (status, headers, body) = get_buffer(&app, session_id, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::ACCEPT_RANGES], "bytes");
+    assert_eq!(body, buffer);
+
+    let (status, headers, _) = get_buffer(&app, session_id, Some("bytes=900000-")).await;
+    assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(headers[header::CONTENT_RANGE], format!("bytes */{}", buffer.len()));
+
+    let (status, _, _) = get_buffer(&app, Uuid::new_v4(), Some("bytes=0-10")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_resume_after_eviction_restarts_download() {
+    let (app, state, _temp) = create_test_app().await;
+    let session_id = Uuid::new_v4();
+    let buffer = fill_buffer(&state, session_id).await;
+
+    let (status, headers, _) = get_buffer(&app, session_id, Some("bytes=0-99")).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    let etag = headers[header::ETAG].to_str().unwrap().to_string();
+
+    // Still the same buffer start: the resume continues where it left off
+    let (status, headers, body) = get_buffer_if_range(&app, session_id, Some("bytes=100-"), Some(&etag)).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(headers[header::ETAG].to_str().unwrap(), etag);
+    assert_eq!(body, &buffer[100..]);
+
+    // Enough new output to evict the oldest chunk shifts every offset
+    let more = fill_buffer(&state, session_id).await;
+    let (status, headers, body) = get_buffer_if_range(&app, session_id, Some("bytes=100-"), Some(&etag)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(headers[header::ETAG].to_str().unwrap(), etag);
+    assert!(headers.get(header::CONTENT_RANGE).is_none());
+    assert!(body.ends_with(&more));
+    assert!(body.len() < buffer.len() + more.len());
+}
@@ -0,0 +1,173 @@
+//! Integration tests for the session export bundle.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{bundle, routes, state::AppState};
+use clauset_types::{HookEventPayload, SessionMode};
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .route("/api/sessions/{id}/bundle", get(routes::sessions::download_bundle))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn send_hook(app: &Router, session_id: Uuid, event: serde_json::Value) {
+    let mut payload = serde_json::json!({
+        "clauset_session_id": session_id,
+        "session_id": "test-claude-session",
+    });
+    payload.as_object_mut().unwrap().extend(event.as_object().unwrap().clone());
+    // Round-trip through the payload type so the test tracks its schema
+    let payload: HookEventPayload = serde_json::from_value(payload).unwrap();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_string(&payload).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn get_bundle(app: &Router, session_id: Uuid) -> axum::response::Response {
+    let request = Request::builder()
+        .uri(format!("/api/sessions/{session_id}/bundle"))
+        .body(Body::empty())
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap()
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+    let mut contents = String::new();
+    archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+    contents
+}
+
+#[tokio::test]
+async fn test_bundle_contains_session_export() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session = state
+        .session_manager
+        .create_session(CreateSessionOptions {
+            project_path: temp_dir.path().to_path_buf(),
+            prompt: "Fix the greeting".to_string(),
+            model: Some("haiku".to_string()),
+            mode: SessionMode::Terminal,
+            resume_session_id: None,
+        })
+        .await
+        .unwrap();
+    let file_path = temp_dir.path().join("greeting.txt");
+    std::fs::write(&file_path, "hello\n").unwrap();
+
+    send_hook(
+        &app,
+        session.id,
+        serde_json::json!({"hook_event_name": "UserPromptSubmit", "prompt": "Fix the greeting"}),
+    )
+    .await;
+    let tool = serde_json::json!({
+        "tool_name": "Edit",
+        "tool_use_id": "toolu_edit",
+        "tool_input": {"file_path": file_path, "old_string": "hello", "new_string": "hello, world"},
+    });
+    let mut pre = tool.clone();
+    pre["hook_event_name"] = "PreToolUse".into();
+    send_hook(&app, session.id, pre).await;
+    std::fs::write(&file_path, "hello, world\n").unwrap();
+    let mut post = tool;
+    post["hook_event_name"] = "PostToolUse".into();
+    post["tool_response"] = serde_json::json!({"success": true});
+    send_hook(&app, session.id, post).await;
+
+    state
+        .session_manager
+        .buffers()
+        .append(session.id, b"\x1b[32m\xe2\x9c\x93\x1b[0m edited greeting.txt\r\n")
+        .await;
+
+    let response = get_bundle(&app, session.id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/zip");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(body.to_vec())).unwrap();
+    let mut names: Vec<_> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            bundle::ANALYTICS_ENTRY,
+            bundle::PATCH_ENTRY,
+            bundle::INTERACTIONS_ENTRY,
+            bundle::TERMINAL_LOG_ENTRY,
+        ]
+    );
+
+    let jsonl = read_entry(&mut archive, bundle::INTERACTIONS_ENTRY);
+    let lines: Vec<serde_json::Value> = jsonl.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["user_prompt"], "Fix the greeting");
+    assert_eq!(lines[0]["tool_invocations"][0]["tool_name"], "Edit");
+
+    let patch = read_entry(&mut archive, bundle::PATCH_ENTRY);
+    assert!(patch.contains("diff --git a/greeting.txt b/greeting.txt"), "{patch}");
+    assert!(patch.contains("-hello\n+hello, world\n"), "{patch}");
+
+    let analytics: serde_json::Value =
+        serde_json::from_str(&read_entry(&mut archive, bundle::ANALYTICS_ENTRY)).unwrap();
+    assert_eq!(analytics["session_id"], session.id.to_string());
+    assert_eq!(analytics["interaction_count"], 1);
+
+    let log = read_entry(&mut archive, bundle::TERMINAL_LOG_ENTRY);
+    assert_eq!(log, "✓ edited greeting.txt\r\n");
+}
+
+#[tokio::test]
+async fn test_bundle_for_unknown_session_is_not_found() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, _) = create_test_app(&temp_dir);
+
+    let response = get_bundle(&app, Uuid::new_v4()).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_bundle_after_restart_uses_persisted_terminal_log() {
+    let temp_dir = TempDir::new().unwrap();
+    let (_, state) = create_test_app(&temp_dir);
+    let session_id = common::create_session(&state, &temp_dir).await;
+    state
+        .session_manager
+        .buffers()
+        .append(session_id, b"\x1b[1mbuilt\x1b[0m in 2s\r\n")
+        .await;
+    state.session_manager.persist_session_activity(session_id).await;
+    drop(state);
+
+    // A restarted server only has the persisted buffer
+    let (app, state) = create_test_app(&temp_dir);
+    assert!(state.session_manager.get_terminal_buffer(session_id).await.is_none());
+
+    let response = get_bundle(&app, session_id).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let mut archive = zip::ZipArchive::new(Cursor::new(body.to_vec())).unwrap();
+    assert_eq!(read_entry(&mut archive, bundle::TERMINAL_LOG_ENTRY), "built in 2s\r\n");
+}
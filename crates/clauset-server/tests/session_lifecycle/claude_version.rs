@@ -0,0 +1,82 @@
+//! Integration tests for recording the Claude Code version of a session.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_server::{routes, state::AppState};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/hooks", post(routes::hooks::receive))
+        .route("/api/sessions/{id}", get(routes::sessions::get))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn session_start(app: &Router, session_id: Uuid, version: &str) {
+    let payload = serde_json::json!({
+        "clauset_session_id": session_id,
+        "session_id": "test-claude-session",
+        "hook_event_name": "SessionStart",
+        "source": "startup",
+        "version": version,
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/hooks")
+        .header("content-type", "application/json")
+        .body(Body::from(payload.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn claude_version(app: &Router, session_id: Uuid) -> serde_json::Value {
+    let request = Request::builder()
+        .uri(format!("/api/sessions/{session_id}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let detail: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    detail["claude_version"].clone()
+}
+
+#[tokio::test]
+async fn test_session_start_hook_records_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let session_id = common::create_session(&state, &temp_dir).await;
+    assert!(claude_version(&app, session_id).await.is_null());
+
+    session_start(&app, session_id, "2.0.76").await;
+
+    assert_eq!(claude_version(&app, session_id).await, "2.0.76");
+}
+
+#[tokio::test]
+async fn test_terminal_banner_records_version_when_hook_did_not() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+    let banner = "\x1b[1m✻ Welcome to Claude Code!\x1b[0m\r\n\x1b[2mClaude Code v2.0.14\x1b[0m\r\n";
+
+    let session_id = common::create_session(&state, &temp_dir).await;
+    state.session_manager.append_terminal_output(session_id, banner.as_bytes()).await;
+    assert_eq!(claude_version(&app, session_id).await, "2.0.14");
+
+    // A version reported by the hook is not replaced by the banner
+    let hooked = common::create_session(&state, &temp_dir).await;
+    session_start(&app, hooked, "2.0.76").await;
+    state.session_manager.append_terminal_output(hooked, banner.as_bytes()).await;
+    assert_eq!(claude_version(&app, hooked).await, "2.0.76");
+}
@@ -0,0 +1,112 @@
+//! Integration tests for listing and cleaning up sessions that never
+//! recorded an interaction.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post, put},
+    Router,
+};
+use clauset_core::SessionStore;
+use clauset_server::{routes, state::AppState};
+use clauset_types::Interaction;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+fn create_test_app(temp_dir: &TempDir) -> (Router, Arc<AppState>) {
+    let state = common::test_state(common::test_config(temp_dir));
+    let app = Router::new()
+        .route("/api/sessions/empty", get(routes::sessions::list_empty))
+        .route("/api/sessions/empty/cleanup", post(routes::sessions::cleanup_empty))
+        .route("/api/sessions/{id}/pin", put(routes::sessions::pin))
+        .with_state(state.clone());
+    (app, state)
+}
+
+async fn send(app: &Router, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+async fn pin(app: &Router, id: Uuid) -> StatusCode {
+    let request = Request::builder()
+        .method("PUT")
+        .uri(format!("/api/sessions/{}/pin", id))
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"pinned":true}"#))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+fn listed_ids(body: &serde_json::Value) -> Vec<String> {
+    body["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_only_old_empty_sessions_are_cleaned_up() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+
+    let used = common::create_session(&state, &temp_dir).await;
+    let empty = common::create_session(&state, &temp_dir).await;
+    let recent_empty = common::create_session(&state, &temp_dir).await;
+    let interaction = Interaction::new(used, 1, "Refactor the parser".to_string());
+    state.interaction_processor.store().insert_interaction(&interaction).unwrap();
+
+    let store = SessionStore::open(&temp_dir.path().join("test.db")).unwrap();
+    let two_days_ago = chrono::Utc::now() - chrono::Duration::days(2);
+    store.touch_activity(used, two_days_ago).unwrap();
+    store.touch_activity(empty, two_days_ago).unwrap();
+
+    let (status, body) = send(&app, "GET", "/api/sessions/empty").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed_ids(&body), [empty.to_string()]);
+
+    let (status, body) = send(&app, "POST", "/api/sessions/empty/cleanup").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["deleted"], serde_json::json!([empty]));
+
+    assert!(state.session_manager.get_session(empty).unwrap().is_none());
+    assert!(state.session_manager.get_session(used).unwrap().is_some());
+    assert!(state.session_manager.get_session(recent_empty).unwrap().is_some());
+
+    // A zero threshold includes the just-created empty session
+    let (_, body) = send(&app, "GET", "/api/sessions/empty?older_than_secs=0").await;
+    assert_eq!(listed_ids(&body), [recent_empty.to_string()]);
+}
+
+#[tokio::test]
+async fn test_pinned_sessions_are_not_cleaned_up() {
+    let temp_dir = TempDir::new().unwrap();
+    let (app, state) = create_test_app(&temp_dir);
+
+    let pinned = common::create_session(&state, &temp_dir).await;
+    let unpinned = common::create_session(&state, &temp_dir).await;
+    let store = SessionStore::open(&temp_dir.path().join("test.db")).unwrap();
+    let two_days_ago = chrono::Utc::now() - chrono::Duration::days(2);
+    store.touch_activity(pinned, two_days_ago).unwrap();
+    store.touch_activity(unpinned, two_days_ago).unwrap();
+
+    assert_eq!(pin(&app, pinned).await, StatusCode::OK);
+
+    let (_, body) = send(&app, "GET", "/api/sessions/empty").await;
+    assert_eq!(listed_ids(&body), [unpinned.to_string()]);
+
+    let (status, body) = send(&app, "POST", "/api/sessions/empty/cleanup").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["deleted"], serde_json::json!([unpinned]));
+    assert!(state.session_manager.get_session(pinned).unwrap().is_some());
+    assert!(state.session_manager.get_session(unpinned).unwrap().is_none());
+    assert_eq!(pin(&app, Uuid::new_v4()).await, StatusCode::NOT_FOUND);
+}
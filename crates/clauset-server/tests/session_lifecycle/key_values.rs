@@ -0,0 +1,118 @@
+//! Integration tests for session key-value metadata.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_server::{routes, state::AppState};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions", get(routes::sessions::list))
+        .route(
+            "/api/sessions/{id}/metadata",
+            get(routes::sessions::get_metadata)
+                .put(routes::sessions::set_metadata)
+                .delete(routes::sessions::delete_metadata),
+        )
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<serde_json::Value>) -> (StatusCode, serde_json::Value) {
+    let body = match body {
+        Some(body) => Body::from(body.to_string()),
+        None => Body::empty(),
+    };
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+async fn listed_ids(app: &Router, query: &str) -> Vec<String> {
+    let (status, body) = send(app, "GET", &format!("/api/sessions?{query}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    body["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_session_metadata_crud_and_filter() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let first = common::create_session(&state, &temp_dir).await;
+    let second = common::create_session(&state, &temp_dir).await;
+
+    for (id, key, value) in [
+        (first, "jira", "PROJ-123"),
+        (first, "branch", "feature/x"),
+        (second, "jira", "PROJ-456"),
+    ] {
+        let uri = format!("/api/sessions/{id}/metadata");
+        let (status, _) = send(&app, "PUT", &uri, Some(serde_json::json!({"key": key, "value": value}))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, body) = send(&app, "GET", &format!("/api/sessions/{first}/metadata"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["metadata"],
+        serde_json::json!({"branch": "feature/x", "jira": "PROJ-123"})
+    );
+
+    assert_eq!(listed_ids(&app, "meta_key=jira&meta_value=PROJ-123").await, [first.to_string()]);
+    assert_eq!(listed_ids(&app, "meta_key=branch&meta_value=feature%2Fx").await, [first.to_string()]);
+    assert!(listed_ids(&app, "meta_key=jira&meta_value=PROJ-999").await.is_empty());
+    assert_eq!(listed_ids(&app, "").await.len(), 2);
+
+    // Setting a key again replaces its value
+    let uri = format!("/api/sessions/{second}/metadata");
+    let (_, body) = send(&app, "PUT", &uri, Some(serde_json::json!({"key": "jira", "value": "PROJ-123"}))).await;
+    assert_eq!(body["metadata"], serde_json::json!({"jira": "PROJ-123"}));
+    assert_eq!(listed_ids(&app, "meta_key=jira&meta_value=PROJ-123").await.len(), 2);
+
+    let (status, body) = send(&app, "DELETE", &uri, Some(serde_json::json!({"key": "jira"}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["metadata"], serde_json::json!({}));
+    assert_eq!(listed_ids(&app, "meta_key=jira&meta_value=PROJ-123").await, [first.to_string()]);
+}
+
+#[tokio::test]
+async fn test_session_metadata_validation() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+
+    let uri = format!("/api/sessions/{session_id}/metadata");
+    let (status, _) = send(&app, "PUT", &uri, Some(serde_json::json!({"key": " ", "value": "x"}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let uri = format!("/api/sessions/{}/metadata", Uuid::new_v4());
+    let (status, _) = send(&app, "PUT", &uri, Some(serde_json::json!({"key": "jira", "value": "x"}))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, "GET", "/api/sessions?meta_key=jira", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
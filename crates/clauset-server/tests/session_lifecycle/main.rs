@@ -0,0 +1,289 @@
+//! Integration tests for session start/resume handling.
+//!
+//! A session row is persisted before its process is spawned; when the spawn
+//! fails the row must not be left behind looking like a live session.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use clauset_core::{ClausetError, CreateSessionOptions};
+use clauset_server::{config::Config, routes, state::AppState};
+use clauset_types::{SessionMode, SessionStatus};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[path = "../common/mod.rs"]
+mod common;
+mod archive;
+mod buffer_download;
+mod bundle;
+mod claude_version;
+mod empty_sessions;
+mod key_values;
+mod metadata;
+mod mode_history;
+mod pids;
+mod rename;
+mod tags;
+mod transcript_text;
+mod tui_menu;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    create_test_app_in(temp_dir, PathBuf::from("/usr/bin/true"))
+}
+
+fn create_test_app_in(temp_dir: TempDir, claude_path: PathBuf) -> (Router, Arc<AppState>, TempDir) {
+    let config = Config {
+        claude_path,
+        claude_dir: Some(temp_dir.path().join("claude")),
+        ..common::test_config(&temp_dir)
+    };
+
+    let state = common::test_state(config);
+
+    let app = Router::new()
+        .route("/api/sessions/{id}/start", post(routes::sessions::start))
+        .route("/api/sessions/{id}/resume", post(routes::sessions::resume))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+/// Write a fake Claude binary that records the directory it was started in.
+fn write_cwd_recorder(dir: &Path, marker: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("fake-claude");
+    std::fs::write(&script, format!("#!/bin/sh\npwd > '{}'\n", marker.display())).unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+async fn post_resume(app: &Router, session_id: Uuid, working_dir: &Path) -> StatusCode {
+    let body = serde_json::json!({ "working_dir": working_dir }).to_string();
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/sessions/{session_id}/resume"))
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+/// Create a session whose working directory does not exist, so spawning fails.
+async fn create_unspawnable_session(
+    state: &AppState,
+    temp_dir: &TempDir,
+    resume_session_id: Option<Uuid>,
+) -> Uuid {
+    let opts = CreateSessionOptions {
+        project_path: temp_dir.path().join("does-not-exist"),
+        prompt: "Test prompt".to_string(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id,
+    };
+    state.session_manager.create_session(opts).await.unwrap().id
+}
+
+async fn assert_not_running(state: &AppState, session_id: Uuid) {
+    assert!(!state.session_manager.is_active(session_id).await);
+    let session = state.session_manager.get_session(session_id).unwrap().unwrap();
+    assert_eq!(session.status, SessionStatus::Error);
+    let summaries = state.session_manager.list_sessions().await.unwrap();
+    let summary = summaries.iter().find(|s| s.id == session_id).unwrap();
+    assert_eq!(summary.status, SessionStatus::Error);
+}
+
+#[tokio::test]
+async fn test_start_spawn_failure_marks_session_error() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let session_id = create_unspawnable_session(&state, &temp_dir, None).await;
+
+    let err = state
+        .session_manager
+        .start_session(session_id, "hello")
+        .await
+        .unwrap_err();
+    match err {
+        ClausetError::SessionSpawnFailed { session_id: failed_id, .. } => {
+            assert_eq!(failed_id, session_id)
+        }
+        other => panic!("expected SessionSpawnFailed, got {other:?}"),
+    }
+    assert_not_running(&state, session_id).await;
+
+    // The HTTP route reports the failure too
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/sessions/{session_id}/start"))
+        .header("content-type", "application/json")
+        .body(Body::from("{}"))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_not_running(&state, session_id).await;
+}
+
+#[tokio::test]
+async fn test_resume_spawn_failure_marks_session_error() {
+    let (_app, state, temp_dir) = create_test_app().await;
+    let session_id = create_unspawnable_session(&state, &temp_dir, Some(Uuid::new_v4())).await;
+
+    let err = state.session_manager.resume_session(session_id).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ClausetError::SessionSpawnFailed { session_id: failed_id, .. } if failed_id == session_id
+    ));
+    assert_not_running(&state, session_id).await;
+}
+
+/// Path of a session's transcript in the test Claude dir, as Claude keys it.
+fn transcript_path(temp_dir: &TempDir, claude_session_id: Uuid, project: &Path) -> PathBuf {
+    temp_dir
+        .path()
+        .join("claude")
+        .join("projects")
+        .join(project.to_string_lossy().replace('/', "-"))
+        .join(format!("{claude_session_id}.jsonl"))
+}
+
+#[tokio::test]
+async fn test_resume_in_new_working_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let marker = temp_dir.path().join("cwd.txt");
+    let claude_path = write_cwd_recorder(temp_dir.path(), &marker);
+    let (app, state, temp_dir) = create_test_app_in(temp_dir, claude_path);
+
+    let projects = temp_dir.path().join("projects");
+    let old_dir = projects.join("old-checkout");
+    let new_dir = projects.join("new-checkout");
+    std::fs::create_dir_all(&old_dir).unwrap();
+    std::fs::create_dir_all(&new_dir).unwrap();
+    let old_dir = std::fs::canonicalize(&old_dir).unwrap();
+    let new_dir = std::fs::canonicalize(&new_dir).unwrap();
+
+    let claude_session_id = Uuid::new_v4();
+    let opts = CreateSessionOptions {
+        project_path: old_dir.clone(),
+        prompt: "Test prompt".to_string(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id: Some(claude_session_id),
+    };
+    let session = state.session_manager.create_session(opts).await.unwrap();
+
+    let transcript = "{\"type\":\"user\",\"message\":{\"content\":\"hi\"}}\n";
+    let source = transcript_path(&temp_dir, claude_session_id, &old_dir);
+    std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+    std::fs::write(&source, transcript).unwrap();
+
+    assert_eq!(post_resume(&app, session.id, &new_dir).await, StatusCode::OK);
+
+    // The transcript is where Claude looks for it in the new directory
+    let carried = transcript_path(&temp_dir, claude_session_id, &new_dir);
+    assert_eq!(std::fs::read_to_string(&carried).unwrap(), transcript);
+
+    let updated = state.session_manager.get_session(session.id).unwrap().unwrap();
+    assert_eq!(updated.project_path, new_dir);
+    assert_eq!(updated.claude_session_id, claude_session_id);
+
+    // The spawned process ran in the new directory
+    for _ in 0..500 {
+        if std::fs::read_to_string(&marker).is_ok_and(|s| !s.is_empty()) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let cwd = std::fs::read_to_string(&marker).unwrap();
+    assert_eq!(Path::new(cwd.trim()), new_dir);
+}
+
+#[tokio::test]
+async fn test_resume_in_new_working_directory_requires_project_and_transcript() {
+    let temp_dir = TempDir::new().unwrap();
+    let marker = temp_dir.path().join("cwd.txt");
+    let claude_path = write_cwd_recorder(temp_dir.path(), &marker);
+    let (app, state, temp_dir) = create_test_app_in(temp_dir, claude_path);
+
+    let old_dir = temp_dir.path().join("projects").join("old-checkout");
+    let new_dir = temp_dir.path().join("projects").join("new-checkout");
+    let outside = temp_dir.path().join("elsewhere");
+    for dir in [&old_dir, &new_dir, &outside] {
+        std::fs::create_dir_all(dir).unwrap();
+    }
+
+    let opts = CreateSessionOptions {
+        project_path: old_dir.clone(),
+        prompt: "Test prompt".to_string(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id: Some(Uuid::new_v4()),
+    };
+    let session = state.session_manager.create_session(opts).await.unwrap();
+
+    // Only known project directories are accepted
+    assert_eq!(post_resume(&app, session.id, &outside).await, StatusCode::BAD_REQUEST);
+    // A project without the session's transcript would resume with no history
+    assert_eq!(post_resume(&app, session.id, &new_dir).await, StatusCode::CONFLICT);
+
+    let unchanged = state.session_manager.get_session(session.id).unwrap().unwrap();
+    assert_eq!(unchanged.project_path, old_dir);
+    assert_eq!(unchanged.status, SessionStatus::Created);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!marker.exists(), "no process may be spawned for a rejected resume");
+}
+
+#[tokio::test]
+async fn test_resume_rejects_missing_working_directory() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let opts = CreateSessionOptions {
+        project_path: temp_dir.path().to_path_buf(),
+        prompt: "Test prompt".to_string(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id: Some(Uuid::new_v4()),
+    };
+    let session = state.session_manager.create_session(opts).await.unwrap();
+
+    let missing = temp_dir.path().join("moved-away");
+    assert_eq!(post_resume(&app, session.id, &missing).await, StatusCode::BAD_REQUEST);
+
+    let unchanged = state.session_manager.get_session(session.id).unwrap().unwrap();
+    assert_eq!(unchanged.project_path, temp_dir.path());
+    assert_eq!(unchanged.status, SessionStatus::Created);
+}
+
+#[tokio::test]
+async fn test_claude_transcript_reads_configured_claude_dir() {
+    let (_, state, temp_dir) = create_test_app().await;
+    let app = Router::new()
+        .route("/api/claude-sessions/{id}/transcript", get(routes::sessions::get_claude_transcript))
+        .with_state(state);
+    let project = Path::new("/work/app");
+    let transcript = temp_dir.path().join("claude/projects/-work-app/s1.jsonl");
+    std::fs::create_dir_all(transcript.parent().unwrap()).unwrap();
+    std::fs::write(
+        &transcript,
+        "{\"type\":\"user\",\"message\":{\"role\":\"user\",\"content\":\"hello from the configured dir\"}}\n",
+    )
+    .unwrap();
+
+    let request = Request::builder()
+        .uri(format!("/api/claude-sessions/s1/transcript?project_path={}", project.display()))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["messages"][0]["content"], "hello from the configured dir");
+}
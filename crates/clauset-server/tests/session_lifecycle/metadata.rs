@@ -0,0 +1,89 @@
+//! Integration tests for correcting session metadata (model, project path).
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::patch,
+    Router,
+};
+use clauset_server::{routes, state::AppState};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions/{id}", patch(routes::sessions::update))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn patch_session(app: &Router, session_id: Uuid, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/api/sessions/{session_id}"))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+#[tokio::test]
+async fn test_patch_model_updates_row_and_context_window() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+
+    let (status, body) = patch_session(&app, session_id, serde_json::json!({ "model": "sonnet[1m]" })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["model"], "sonnet[1m]");
+
+    let session = state.session_manager.get_session(session_id).unwrap().unwrap();
+    assert_eq!(session.model, "sonnet[1m]");
+    assert_eq!(session.project_path, temp_dir.path());
+
+    let activity = state.session_manager.get_activity(session_id).await.unwrap();
+    assert_eq!(activity.model, "sonnet[1m]");
+    assert_eq!(activity.context_window_size, 1_000_000);
+
+    let (status, _) = patch_session(&app, session_id, serde_json::json!({ "model": "opus" })).await;
+    assert_eq!(status, StatusCode::OK);
+    let activity = state.session_manager.get_activity(session_id).await.unwrap();
+    assert_eq!(activity.context_window_size, 200_000);
+}
+
+#[tokio::test]
+async fn test_patch_project_path_is_validated() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+
+    let (status, _) = patch_session(
+        &app,
+        session_id,
+        serde_json::json!({ "project_path": temp_dir.path().join("missing") }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let moved = temp_dir.path().join("moved");
+    std::fs::create_dir_all(&moved).unwrap();
+    let (status, _) = patch_session(&app, session_id, serde_json::json!({ "project_path": moved })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let session = state.session_manager.get_session(session_id).unwrap().unwrap();
+    assert_eq!(session.project_path, std::fs::canonicalize(&moved).unwrap());
+    assert_eq!(session.model, "haiku");
+
+    let (status, _) = patch_session(&app, Uuid::new_v4(), serde_json::json!({ "model": "opus" })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
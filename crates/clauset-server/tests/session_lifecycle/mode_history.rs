@@ -0,0 +1,85 @@
+//! Integration tests for the permission mode timeline.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::{PermissionMode, SessionMode};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions/{id}/mode-history", get(routes::sessions::get_mode_history))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn get_history(app: &Router, session_id: Uuid) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .uri(format!("/api/sessions/{session_id}/mode-history"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null))
+}
+
+#[tokio::test]
+async fn test_mode_toggles_recorded_in_order() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let opts = CreateSessionOptions {
+        project_path: temp_dir.path().to_path_buf(),
+        prompt: "Initial prompt".to_string(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id: None,
+    };
+    let session = state.session_manager.create_session(opts).await.unwrap();
+
+    // Toggled in the terminal, then reported by a hook
+    state
+        .session_manager
+        .append_terminal_output(session.id, b"plan mode on (shift+tab to cycle)\r\n")
+        .await;
+    state
+        .session_manager
+        .update_permission_mode(session.id, PermissionMode::BypassPermissions)
+        .await;
+    // An unchanged mode is not recorded again
+    state
+        .session_manager
+        .update_permission_mode(session.id, PermissionMode::BypassPermissions)
+        .await;
+
+    let timeline = state.session_manager.permission_mode_timeline(session.id).unwrap();
+    let modes: Vec<_> = timeline.iter().map(|change| change.mode).collect();
+    assert_eq!(modes, [PermissionMode::Plan, PermissionMode::BypassPermissions]);
+    assert!(timeline[0].changed_at <= timeline[1].changed_at);
+
+    let (status, body) = get_history(&app, session.id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert_eq!(body[0]["mode"], "plan");
+    assert_eq!(body[1]["mode"], "bypass_permissions");
+}
+
+#[tokio::test]
+async fn test_mode_history_unknown_session_returns_404() {
+    let (app, _state, _temp_dir) = create_test_app().await;
+    let (status, _) = get_history(&app, Uuid::new_v4()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
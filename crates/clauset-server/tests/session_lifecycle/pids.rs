@@ -0,0 +1,102 @@
+//! Integration tests for reporting the OS process behind each session.
+#![cfg(unix)]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{config::Config, routes, state::AppState};
+use clauset_types::SessionMode;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use crate::common;
+
+/// Write a fake Claude binary that stays running.
+fn write_long_running_claude(dir: &Path) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let script = dir.join("fake-claude");
+    std::fs::write(&script, "#!/bin/sh\nexec sleep 30\n").unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config = Config {
+        claude_path: write_long_running_claude(temp_dir.path()),
+        ..common::test_config(&temp_dir)
+    };
+
+    let state = common::test_state(config);
+
+    let app = Router::new()
+        .route("/api/admin/processes", get(routes::admin::list_processes))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+/// Whether `pid` is a running (not exited or zombie) process.
+fn is_running(pid: u32) -> bool {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "stat=", "-p", &pid.to_string()])
+        .output()
+        .unwrap();
+    let stat = String::from_utf8_lossy(&output.stdout);
+    output.status.success() && !stat.trim().is_empty() && !stat.trim().starts_with('Z')
+}
+
+async fn list_processes(app: &Router) -> serde_json::Value {
+    let request = Request::builder()
+        .uri("/api/admin/processes")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_session_pid_is_live_until_terminated() {
+    let (app, state, temp_dir) = create_test_app();
+    let opts = CreateSessionOptions {
+        project_path: temp_dir.path().to_path_buf(),
+        prompt: String::new(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id: None,
+    };
+    let session = state.session_manager.create_session(opts).await.unwrap();
+    assert!(state.session_manager.sessions_with_pids().await.is_empty());
+
+    state.session_manager.start_session(session.id, "").await.unwrap();
+    let pids = state.session_manager.sessions_with_pids().await;
+    assert_eq!(pids.len(), 1);
+    let (session_id, pid) = pids[0];
+    assert_eq!(session_id, session.id);
+    assert!(is_running(pid));
+
+    let body = list_processes(&app).await;
+    assert_eq!(body[0]["session_id"], session.id.to_string());
+    assert_eq!(body[0]["pid"], pid);
+
+    state.session_manager.terminate_session(session.id).await.unwrap();
+    assert!(state.session_manager.sessions_with_pids().await.is_empty());
+    assert_eq!(list_processes(&app).await, serde_json::json!([]));
+    for _ in 0..500 {
+        if !is_running(pid) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(!is_running(pid));
+}
@@ -0,0 +1,86 @@
+//! Integration tests for session rename history.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::{get, put},
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::SessionMode;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions/{id}", get(routes::sessions::get))
+        .route("/api/sessions/{id}/name", put(routes::sessions::rename))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn rename(app: &Router, session_id: Uuid, name: &str) -> StatusCode {
+    let request = Request::builder()
+        .method("PUT")
+        .uri(format!("/api/sessions/{session_id}/name"))
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::json!({ "name": name }).to_string()))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_rename_twice_records_history_in_order() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let opts = CreateSessionOptions {
+        project_path: temp_dir.path().to_path_buf(),
+        prompt: "Initial prompt".to_string(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id: None,
+    };
+    let session = state.session_manager.create_session(opts).await.unwrap();
+    let original = session.preview.clone();
+
+    assert_eq!(rename(&app, session.id, "Refactor parser").await, StatusCode::OK);
+    assert_eq!(rename(&app, session.id, "Parser rewrite").await, StatusCode::OK);
+    // Renaming to the current name is not recorded
+    assert_eq!(rename(&app, session.id, "Parser rewrite").await, StatusCode::OK);
+
+    let history = state.session_manager.session_name_history(session.id).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].old_name, original);
+    assert_eq!(history[0].new_name, "Refactor parser");
+    assert_eq!(history[1].old_name, "Refactor parser");
+    assert_eq!(history[1].new_name, "Parser rewrite");
+    assert!(history[0].changed_at <= history[1].changed_at);
+
+    // The session detail route exposes the history alongside the session
+    let request = Request::builder()
+        .uri(format!("/api/sessions/{}", session.id))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["preview"], "Parser rewrite");
+    assert_eq!(body["name_history"].as_array().unwrap().len(), 2);
+    assert_eq!(body["name_history"][1]["old_name"], "Refactor parser");
+    assert_eq!(body["name_history"][1]["new_name"], "Parser rewrite");
+}
+
+#[tokio::test]
+async fn test_rename_unknown_session_returns_404() {
+    let (app, _state, _temp_dir) = create_test_app().await;
+    assert_eq!(rename(&app, Uuid::new_v4(), "Nope").await, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,105 @@
+//! Integration tests for session tags.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_server::{routes, state::AppState};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions", get(routes::sessions::list))
+        .route(
+            "/api/sessions/{id}/tags",
+            get(routes::sessions::list_tags)
+                .post(routes::sessions::add_tag)
+                .delete(routes::sessions::remove_tag),
+        )
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn send(app: &Router, method: &str, uri: &str, tag: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let body = match tag {
+        Some(tag) => Body::from(serde_json::json!({ "tag": tag }).to_string()),
+        None => Body::empty(),
+    };
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(body)
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+    (status, json)
+}
+
+async fn listed_ids(app: &Router, tag: &str) -> HashSet<String> {
+    let (status, body) = send(app, "GET", &format!("/api/sessions?tag={tag}"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    body["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_sessions_by_overlapping_tags() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let first = common::create_session(&state, &temp_dir).await;
+    let second = common::create_session(&state, &temp_dir).await;
+    let _untagged = common::create_session(&state, &temp_dir).await;
+
+    for (id, tag) in [(first, "bugfix"), (first, "parser"), (second, "parser"), (second, "experiment")] {
+        let (status, _) = send(&app, "POST", &format!("/api/sessions/{id}/tags"), Some(tag)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    // Tags are deduped per session
+    let (status, body) = send(&app, "POST", &format!("/api/sessions/{first}/tags"), Some(" parser ")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tags"], serde_json::json!(["bugfix", "parser"]));
+
+    assert_eq!(listed_ids(&app, "parser").await, HashSet::from([first.to_string(), second.to_string()]));
+    assert_eq!(listed_ids(&app, "bugfix").await, HashSet::from([first.to_string()]));
+    assert_eq!(listed_ids(&app, "experiment").await, HashSet::from([second.to_string()]));
+    assert!(listed_ids(&app, "unused").await.is_empty());
+
+    let (status, body) = send(&app, "DELETE", &format!("/api/sessions/{second}/tags"), Some("parser")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["tags"], serde_json::json!(["experiment"]));
+    assert_eq!(listed_ids(&app, "parser").await, HashSet::from([first.to_string()]));
+
+    let (status, body) = send(&app, "GET", "/api/sessions", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["sessions"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_tag_validation_and_unknown_session() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let session_id = common::create_session(&state, &temp_dir).await;
+
+    let (status, _) = send(&app, "POST", &format!("/api/sessions/{session_id}/tags"), Some("  ")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, "POST", &format!("/api/sessions/{}/tags", Uuid::new_v4()), Some("bugfix")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,85 @@
+//! Integration tests for the plain-text session transcript.
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get,
+    Router,
+};
+use clauset_core::CreateSessionOptions;
+use clauset_server::{routes, state::AppState};
+use clauset_types::{Interaction, SessionMode};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions/{id}/transcript.txt", get(routes::sessions::get_transcript_text))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn get_transcript(app: &Router, session_id: Uuid) -> (StatusCode, Option<String>, String) {
+    let request = Request::builder()
+        .uri(format!("/api/sessions/{session_id}/transcript.txt"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|value| value.to_str().unwrap().to_string());
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_transcript_text_lists_turns_in_order() {
+    let (app, state, temp_dir) = create_test_app().await;
+    let opts = CreateSessionOptions {
+        project_path: temp_dir.path().to_path_buf(),
+        prompt: "Initial prompt".to_string(),
+        model: Some("haiku".to_string()),
+        mode: SessionMode::Terminal,
+        resume_session_id: None,
+    };
+    let session = state.session_manager.create_session(opts).await.unwrap();
+
+    let store = state.interaction_processor.store();
+    for (seq, prompt, summary) in [
+        (1, "Explain the config loader", "It merges defaults with the file"),
+        (2, "Now simplify it", "Removed the duplicate merge"),
+    ] {
+        let mut interaction = Interaction::new(session.id, seq, prompt.to_string());
+        interaction.assistant_summary = Some(summary.to_string());
+        store.insert_interaction(&interaction).unwrap();
+    }
+
+    let (status, content_type, body) = get_transcript(&app, session.id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/plain; charset=utf-8"));
+    assert_eq!(
+        body,
+        "User: Explain the config loader\n\
+         Assistant: It merges defaults with the file\n\
+         \n\
+         User: Now simplify it\n\
+         Assistant: Removed the duplicate merge\n"
+    );
+}
+
+#[tokio::test]
+async fn test_transcript_text_unknown_session_returns_404() {
+    let (app, _state, _temp_dir) = create_test_app().await;
+    let (status, _, _) = get_transcript(&app, Uuid::new_v4()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,66 @@
+//! Integration tests for selecting TUI menu options over HTTP.
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Router,
+};
+use clauset_server::{routes, state::AppState};
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+use uuid::Uuid;
+use crate::common;
+
+const MENU_OUTPUT: &[u8] =
+    b"Select model\n  1. Default\n> 2. Opus\n  3. Haiku\nEnter to confirm \xc2\xb7 Esc to exit\n";
+
+async fn create_test_app() -> (Router, Arc<AppState>, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+
+    let state = common::test_state(common::test_config(&temp_dir));
+
+    let app = Router::new()
+        .route("/api/sessions/{id}/menu-select", post(routes::sessions::menu_select))
+        .with_state(state.clone());
+
+    (app, state, temp_dir)
+}
+
+async fn select(app: &Router, session_id: Uuid, body: serde_json::Value) -> StatusCode {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/sessions/{session_id}/menu-select"))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_menu_select_requires_open_menu() {
+    let (app, _state, _temp) = create_test_app().await;
+
+    let status = select(&app, Uuid::new_v4(), serde_json::json!({ "index": 0 })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_menu_select_validates_menu_and_index() {
+    let (app, state, _temp) = create_test_app().await;
+    let session_id = Uuid::new_v4();
+    state.session_manager.buffers().append(session_id, MENU_OUTPUT).await;
+    let menu = state.session_manager.get_tui_menu(session_id).await.unwrap();
+
+    let status = select(&app, session_id, serde_json::json!({ "index": 0, "menu_id": "stale" })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let status = select(&app, session_id, serde_json::json!({ "index": 3, "menu_id": menu.id })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A valid selection confirms with Enter, which closes the menu
+    let status = select(&app, session_id, serde_json::json!({ "index": 0, "menu_id": menu.id })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(state.session_manager.get_tui_menu(session_id).await.is_none());
+}
@@ -32,6 +32,20 @@ pub struct ChatMessage {
     pub is_complete: bool,
     /// Message timestamp (ms since Unix epoch)
     pub timestamp: u64,
+    /// When the first response text or tool call arrived (ms since Unix epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_token_at: Option<u64>,
+}
+
+/// A session's chat as a reconnecting client should render it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionChatSnapshot {
+    pub session_id: Uuid,
+    /// Finalized messages, in order
+    pub messages: Vec<ChatMessage>,
+    /// The assistant message still being built, with its partial content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_progress: Option<ChatMessage>,
 }
 
 /// Role of the message sender in chat mode.
@@ -77,6 +91,7 @@ impl ChatMessage {
             is_streaming: false,
             is_complete: true,
             timestamp: now_ms(),
+            first_token_at: None,
         }
     }
 
@@ -92,6 +107,7 @@ impl ChatMessage {
             is_streaming: true,
             is_complete: false,
             timestamp: now_ms(),
+            first_token_at: None,
         }
     }
 
@@ -108,6 +124,16 @@ impl ChatMessage {
         self.content.push_str(delta);
     }
 
+    /// Stamp when the response started, unless already stamped.
+    /// Returns whether this call stamped it.
+    pub fn mark_first_token(&mut self) -> bool {
+        if self.first_token_at.is_some() {
+            return false;
+        }
+        self.first_token_at = Some(now_ms());
+        true
+    }
+
     /// Add a tool call to this message.
     pub fn add_tool_call(&mut self, tool_call: ChatToolCall) {
         self.tool_calls.push(tool_call);
@@ -156,6 +182,12 @@ pub enum ChatEvent {
         message_id: String,
         delta: String,
     },
+    /// A message's content was rewritten as a whole
+    ContentReplaced {
+        session_id: Uuid,
+        message_id: String,
+        content: String,
+    },
     /// Thinking content was appended to a streaming message
     ThinkingDelta {
         session_id: Uuid,
@@ -220,6 +252,13 @@ mod tests {
         msg.append_content(" world");
         assert_eq!(msg.content, "Hello world");
 
+        assert!(msg.first_token_at.is_none());
+        assert!(msg.mark_first_token());
+        let first = msg.first_token_at.unwrap();
+        assert!(first >= msg.timestamp);
+        assert!(!msg.mark_first_token());
+        assert_eq!(msg.first_token_at, Some(first));
+
         msg.complete();
         assert!(!msg.is_streaming);
         assert!(msg.is_complete);
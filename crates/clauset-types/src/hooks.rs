@@ -279,6 +279,8 @@ pub enum HookEvent {
         model: Option<ModelInfo>,
         /// Path to the JSONL transcript file (for real-time content streaming)
         transcript_path: Option<String>,
+        /// Claude Code version, when the hook reports it
+        version: Option<String>,
     },
 
     /// Session ended
@@ -398,6 +400,7 @@ impl TryFrom<HookEventPayload> for HookEvent {
                 context_window: p.context_window,
                 model: p.model,
                 transcript_path: p.transcript_path,
+                version: p.version,
             }),
 
             "SessionEnd" => Ok(HookEvent::SessionEnd {
@@ -17,8 +17,10 @@ pub enum InteractionStatus {
     Active,
     /// Interaction completed successfully.
     Completed,
-    /// Interaction failed or was interrupted.
+    /// Interaction failed with an error.
     Failed,
+    /// Interaction was cancelled by the user.
+    Interrupted,
 }
 
 impl Default for InteractionStatus {
@@ -39,8 +41,11 @@ pub struct Interaction {
     pub session_id: Uuid,
     /// Sequence number within the session (1, 2, 3, ...).
     pub sequence_number: u32,
-    /// The user's prompt text.
+    /// The user's prompt text (cut short if `prompt_truncated`).
     pub user_prompt: String,
+    /// Whether `user_prompt` was truncated to the configured maximum length.
+    #[serde(default)]
+    pub prompt_truncated: bool,
     /// Generated summary of the assistant's response.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub assistant_summary: Option<String>,
@@ -55,6 +60,12 @@ pub struct Interaction {
     pub input_tokens_delta: u64,
     /// Output tokens generated in this interaction.
     pub output_tokens_delta: u64,
+    /// Extended thinking tokens in this interaction (0 when not reported).
+    #[serde(default)]
+    pub thinking_tokens_delta: u64,
+    /// Context window usage (0-100) when the interaction completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_percent: Option<u8>,
     /// Current status.
     pub status: InteractionStatus,
     /// Error message if status is Failed.
@@ -70,12 +81,15 @@ impl Interaction {
             session_id,
             sequence_number,
             user_prompt,
+            prompt_truncated: false,
             assistant_summary: None,
             started_at: Utc::now(),
             ended_at: None,
             cost_usd_delta: 0.0,
             input_tokens_delta: 0,
             output_tokens_delta: 0,
+            thinking_tokens_delta: 0,
+            context_percent: None,
             status: InteractionStatus::Active,
             error_message: None,
         }
@@ -94,12 +108,123 @@ impl Interaction {
         self.error_message = Some(error);
     }
 
+    /// Mark the interaction as interrupted by the user.
+    pub fn interrupt(&mut self) {
+        self.status = InteractionStatus::Interrupted;
+        self.ended_at = Some(Utc::now());
+    }
+
     /// Duration of the interaction in milliseconds.
     pub fn duration_ms(&self) -> Option<i64> {
         self.ended_at.map(|end| (end - self.started_at).num_milliseconds())
     }
 }
 
+/// How a finished interaction turned out, for analytics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnOutcome {
+    /// Completed without any failed tool calls.
+    Succeeded,
+    /// Completed, but at least one tool call failed.
+    PartialError,
+    /// The interaction failed.
+    Failed,
+    /// The user interrupted the interaction.
+    Interrupted,
+}
+
+impl TurnOutcome {
+    /// Every outcome, in reporting order.
+    pub const ALL: [TurnOutcome; 4] = [
+        TurnOutcome::Succeeded,
+        TurnOutcome::PartialError,
+        TurnOutcome::Failed,
+        TurnOutcome::Interrupted,
+    ];
+
+    /// Outcome for an interaction in `status` with `tool_error_count` failed
+    /// tool calls. None while the interaction is still active.
+    pub fn from_status(status: InteractionStatus, tool_error_count: u32) -> Option<Self> {
+        match status {
+            InteractionStatus::Active => None,
+            InteractionStatus::Completed if tool_error_count > 0 => Some(Self::PartialError),
+            InteractionStatus::Completed => Some(Self::Succeeded),
+            InteractionStatus::Failed => Some(Self::Failed),
+            InteractionStatus::Interrupted => Some(Self::Interrupted),
+        }
+    }
+}
+
+/// Classify a finished interaction given how many of its tool calls failed.
+/// Returns None for interactions that are still active.
+pub fn classify_outcome(interaction: &Interaction, tool_error_count: u32) -> Option<TurnOutcome> {
+    TurnOutcome::from_status(interaction.status, tool_error_count)
+}
+
+/// Session settings in effect when an interaction started.
+///
+/// Lets the timeline show where the model, permission mode or working
+/// directory changed between interactions.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InteractionContext {
+    /// Model in use (None if not known yet).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Permission mode (None if no hook has reported one yet).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<crate::PermissionMode>,
+    /// Claude's working directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+}
+
+/// One event in the merged, chronological replay of an interaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    /// The user's prompt, at the start of the interaction.
+    Prompt { timestamp: DateTime<Utc>, text: String },
+    /// A tool call began.
+    ToolStart {
+        timestamp: DateTime<Utc>,
+        tool_invocation_id: Uuid,
+        tool_name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_path: Option<PathBuf>,
+    },
+    /// A tool call finished.
+    ToolEnd {
+        timestamp: DateTime<Utc>,
+        tool_invocation_id: Uuid,
+        tool_name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        duration_ms: Option<i64>,
+        is_error: bool,
+    },
+    /// Claude's reply and what the turn cost, once the interaction ended.
+    AssistantResponse {
+        timestamp: DateTime<Utc>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary: Option<String>,
+        cost_usd: f64,
+        input_tokens: u64,
+        output_tokens: u64,
+    },
+}
+
+impl TimelineEntry {
+    /// When the event happened.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            TimelineEntry::Prompt { timestamp, .. }
+            | TimelineEntry::ToolStart { timestamp, .. }
+            | TimelineEntry::ToolEnd { timestamp, .. }
+            | TimelineEntry::AssistantResponse { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
 /// A single tool invocation within an interaction.
 ///
 /// Created from PreToolUse hook, completed by PostToolUse hook.
@@ -129,6 +254,9 @@ pub struct ToolInvocation {
     /// Error message if is_error is true.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    /// Exit code of a Bash command, when the response reported one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
     /// When the tool started.
     pub started_at: DateTime<Utc>,
     /// When the tool completed.
@@ -165,6 +293,7 @@ impl ToolInvocation {
             file_path,
             is_error: false,
             error_message: None,
+            exit_code: None,
             started_at: Utc::now(),
             ended_at: None,
             duration_ms: None,
@@ -191,6 +320,10 @@ impl ToolInvocation {
                 .map(|s| s.to_string());
         }
 
+        if self.tool_name == "Bash" {
+            self.exit_code = Self::exit_code_from_response(response);
+        }
+
         // Store preview of output (first 1KB)
         if let Some(output) = response.get("output").or_else(|| response.get("content")) {
             let output_str = output.to_string();
@@ -201,6 +334,27 @@ impl ToolInvocation {
             });
         }
     }
+
+    /// Read a command's exit code from a structured Bash tool response
+    /// (`exit_code`, `exitCode` or `returnCode`).
+    pub fn exit_code_from_response(response: &Value) -> Option<i32> {
+        ["exit_code", "exitCode", "returnCode"]
+            .iter()
+            .find_map(|key| response.get(key)?.as_i64())
+            .and_then(|code| i32::try_from(code).ok())
+    }
+
+    /// The input that identifies what this call did: its file path, else
+    /// its command, pattern or URL.
+    pub fn key_input(&self) -> Option<String> {
+        if let Some(path) = &self.file_path {
+            return Some(path.display().to_string());
+        }
+        ["command", "pattern", "url"]
+            .iter()
+            .find_map(|key| self.tool_input.get(key)?.as_str())
+            .map(str::to_string)
+    }
 }
 
 /// Type of file snapshot.
@@ -365,10 +519,128 @@ pub enum FileChangeType {
     Deleted,
 }
 
+/// Status of an item in Claude's todo list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+/// An item of the task list Claude maintains with the `TodoWrite` tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TodoItem {
+    /// What needs to be done.
+    pub content: String,
+    pub status: TodoStatus,
+    /// Present-tense form shown while the item is in progress ("Running tests").
+    #[serde(
+        default,
+        rename(deserialize = "activeForm"),
+        alias = "active_form",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_form: Option<String>,
+}
+
+impl TodoItem {
+    /// Parse the todo list from a `TodoWrite` tool input.
+    pub fn list_from_tool_input(tool_input: &Value) -> Option<Vec<TodoItem>> {
+        serde_json::from_value(tool_input.get("todos")?.clone()).ok()
+    }
+}
+
+/// The plan Claude proposes in plan mode with the `ExitPlanMode` tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    /// Steps of the plan, in order.
+    pub steps: Vec<String>,
+}
+
+impl Plan {
+    /// Parse the plan from an `ExitPlanMode` tool input.
+    ///
+    /// The plan is markdown; its list items (`1.`, `-`, `*`) become the
+    /// steps. A plan without any list uses its non-heading lines instead.
+    pub fn from_tool_input(tool_input: &Value) -> Option<Plan> {
+        let text = tool_input.get("plan")?.as_str()?;
+        let lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let list_item = |line: &str| {
+            let unnumbered = line.trim_start_matches(|c: char| c.is_ascii_digit());
+            let rest = if unnumbered.len() < line.len() {
+                unnumbered.strip_prefix(['.', ')'])?
+            } else {
+                line.strip_prefix(['-', '*', '+'])?
+            };
+            rest.strip_prefix(' ').map(|step| step.trim().to_string())
+        };
+
+        let mut steps: Vec<String> = lines.clone().filter_map(list_item).collect();
+        if steps.is_empty() {
+            steps = lines.filter(|line| !line.starts_with('#')).map(str::to_string).collect();
+        }
+        Some(Plan { steps })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_classify_outcome() {
+        let mut interaction = Interaction::new(Uuid::new_v4(), 1, "Fix it".to_string());
+        assert_eq!(classify_outcome(&interaction, 0), None);
+
+        interaction.complete();
+        assert_eq!(classify_outcome(&interaction, 0), Some(TurnOutcome::Succeeded));
+        assert_eq!(classify_outcome(&interaction, 2), Some(TurnOutcome::PartialError));
+
+        interaction.fail("boom".to_string());
+        assert_eq!(classify_outcome(&interaction, 2), Some(TurnOutcome::Failed));
+
+        interaction.interrupt();
+        assert_eq!(classify_outcome(&interaction, 0), Some(TurnOutcome::Interrupted));
+    }
+
+    #[test]
+    fn test_todo_list_from_tool_input() {
+        let input = serde_json::json!({
+            "todos": [
+                {"content": "Run tests", "status": "in_progress", "activeForm": "Running tests"},
+                {"content": "Ship it", "status": "pending"},
+            ]
+        });
+        let todos = TodoItem::list_from_tool_input(&input).unwrap();
+        assert_eq!(todos[0].status, TodoStatus::InProgress);
+        assert_eq!(todos[0].active_form.as_deref(), Some("Running tests"));
+        assert_eq!(todos[1].active_form, None);
+
+        assert!(TodoItem::list_from_tool_input(&serde_json::json!({"todos": "nope"})).is_none());
+    }
+
+    #[test]
+    fn test_plan_from_exit_plan_mode_input() {
+        let input = serde_json::json!({
+            "plan": "## Fix the flaky test\n\n1. Reproduce the failure locally\n2. Add a timeout to the watcher\n   - keep the default at 5s\n3) Re-run the suite\n"
+        });
+        let plan = Plan::from_tool_input(&input).unwrap();
+        assert_eq!(
+            plan.steps,
+            [
+                "Reproduce the failure locally",
+                "Add a timeout to the watcher",
+                "keep the default at 5s",
+                "Re-run the suite",
+            ]
+        );
+
+        let prose = serde_json::json!({"plan": "# Plan\nRename the module.\nUpdate imports."});
+        assert_eq!(Plan::from_tool_input(&prose).unwrap().steps, ["Rename the module.", "Update imports."]);
+        assert!(Plan::from_tool_input(&serde_json::json!({})).is_none());
+    }
+
     #[test]
     fn test_interaction_lifecycle() {
         let session_id = Uuid::new_v4();
@@ -435,4 +707,37 @@ mod tests {
         assert!(invocation.is_error);
         assert_eq!(invocation.error_message, Some("File not found".to_string()));
     }
+
+    #[test]
+    fn test_tool_invocation_key_input() {
+        let key = |tool: &str, input: serde_json::Value| {
+            ToolInvocation::new(Uuid::new_v4(), 1, tool.to_string(), input, None).key_input()
+        };
+        assert_eq!(
+            key("Edit", serde_json::json!({"file_path": "/app/src/main.rs", "old_string": "a"})),
+            Some("/app/src/main.rs".to_string())
+        );
+        assert_eq!(key("Bash", serde_json::json!({"command": "cargo test"})), Some("cargo test".to_string()));
+        assert_eq!(key("Grep", serde_json::json!({"pattern": "TODO"})), Some("TODO".to_string()));
+        assert_eq!(key("TodoWrite", serde_json::json!({"todos": []})), None);
+    }
+
+    #[test]
+    fn test_bash_exit_code_from_response() {
+        let mut invocation = ToolInvocation::new(
+            Uuid::new_v4(),
+            1,
+            "Bash".to_string(),
+            serde_json::json!({"command": "cargo test"}),
+            None,
+        );
+        invocation.complete(&serde_json::json!({"stdout": "", "exit_code": 101}));
+        assert_eq!(invocation.exit_code, Some(101));
+
+        let code = |response| ToolInvocation::exit_code_from_response(&response);
+        assert_eq!(code(serde_json::json!({"exitCode": 0})), Some(0));
+        assert_eq!(code(serde_json::json!({"returnCode": 2})), Some(2));
+        assert_eq!(code(serde_json::json!({"stdout": "ok"})), None);
+        assert_eq!(code(serde_json::json!({"exit_code": "1"})), None);
+    }
 }
@@ -25,6 +25,13 @@ pub struct Prompt {
     pub word_count: u32,
     /// Number of characters in the prompt.
     pub char_count: u32,
+    /// How many times this prompt was submitted in its session.
+    #[serde(default = "default_use_count")]
+    pub use_count: u32,
+}
+
+fn default_use_count() -> u32 {
+    1
 }
 
 impl Prompt {
@@ -48,6 +55,7 @@ impl Prompt {
             timestamp,
             word_count,
             char_count,
+            use_count: 1,
         }
     }
 
@@ -82,6 +90,9 @@ pub struct PromptSummary {
     pub timestamp: u64,
     /// Word count.
     pub word_count: u32,
+    /// How many times this prompt was submitted in its session.
+    #[serde(default = "default_use_count")]
+    pub use_count: u32,
 }
 
 impl From<&Prompt> for PromptSummary {
@@ -92,6 +103,7 @@ impl From<&Prompt> for PromptSummary {
             project_name: prompt.project_name(),
             timestamp: prompt.timestamp,
             word_count: prompt.word_count,
+            use_count: prompt.use_count,
         }
     }
 }
@@ -105,10 +117,45 @@ impl From<Prompt> for PromptSummary {
             project_name,
             timestamp: prompt.timestamp,
             word_count: prompt.word_count,
+            use_count: prompt.use_count,
         }
     }
 }
 
+/// A reusable prompt with `{{name}}` placeholders, derived from an interaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    /// Unique identifier for this template.
+    pub id: Uuid,
+    /// Interaction whose prompt the template was made from.
+    pub interaction_id: Uuid,
+    /// Prompt text with `{{name}}` placeholders.
+    pub template: String,
+    /// Named slots, in order of first appearance.
+    pub slots: Vec<TemplateSlot>,
+    /// Unix timestamp in milliseconds.
+    pub created_at: u64,
+}
+
+impl PromptTemplate {
+    /// Fill the placeholders, falling back to each slot's original value.
+    pub fn render(&self, values: &std::collections::HashMap<String, String>) -> String {
+        self.slots.iter().fold(self.template.clone(), |text, slot| {
+            let value = values.get(&slot.name).unwrap_or(&slot.default_value);
+            text.replace(&format!("{{{{{}}}}}", slot.name), value)
+        })
+    }
+}
+
+/// A named placeholder in a [`PromptTemplate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateSlot {
+    /// Placeholder name, as written between `{{` and `}}`.
+    pub name: String,
+    /// The text the placeholder replaced in the original prompt.
+    pub default_value: String,
+}
+
 /// Truncate text to a maximum number of characters, adding ellipsis if truncated.
 fn truncate_preview(text: &str, max_chars: usize) -> String {
     // Normalize whitespace first
@@ -175,6 +222,27 @@ mod tests {
         assert!(truncated.chars().count() <= 23); // 20 + "..."
     }
 
+    #[test]
+    fn test_prompt_template_render() {
+        let template = PromptTemplate {
+            id: Uuid::new_v4(),
+            interaction_id: Uuid::new_v4(),
+            template: "Fix {{file}} and {{file}} again".to_string(),
+            slots: vec![TemplateSlot {
+                name: "file".to_string(),
+                default_value: "src/main.rs".to_string(),
+            }],
+            created_at: 0,
+        };
+
+        assert_eq!(
+            template.render(&Default::default()),
+            "Fix src/main.rs and src/main.rs again"
+        );
+        let values = [("file".to_string(), "lib.rs".to_string())].into();
+        assert_eq!(template.render(&values), "Fix lib.rs and lib.rs again");
+    }
+
     #[test]
     fn test_prompt_summary_from() {
         let prompt = Prompt::new(
@@ -92,6 +92,9 @@ pub struct Session {
     pub context_percent: u8,
     /// Preview text (first prompt or last message).
     pub preview: String,
+    /// Claude Code version that ran the session, when known.
+    #[serde(default)]
+    pub claude_version: Option<String>,
 }
 
 /// Summary view of a session for listing.
@@ -110,12 +113,18 @@ pub struct SessionSummary {
     pub output_tokens: u64,
     pub context_percent: u8,
     pub preview: String,
+    /// Generated one-line summary of what the session did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
     /// Current step/activity (e.g., "Thinking", "Read", "Ready")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub current_step: Option<String>,
     /// Recent actions performed by Claude
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub recent_actions: Vec<crate::RecentAction>,
+    /// Pinned sessions are exempt from automatic cleanup and retention
+    #[serde(default)]
+    pub is_pinned: bool,
 }
 
 #[cfg(test)]
@@ -151,8 +160,10 @@ impl From<Session> for SessionSummary {
             output_tokens: s.output_tokens,
             context_percent: s.context_percent,
             preview: s.preview,
+            subtitle: None,
             current_step: None,
             recent_actions: Vec::new(),
+            is_pinned: false,
         }
     }
 }
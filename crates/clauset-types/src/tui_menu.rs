@@ -7,6 +7,13 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Down arrow key (ESC [ B).
+const KEY_DOWN: &[u8] = b"\x1b[B";
+/// Up arrow key (ESC [ A).
+const KEY_UP: &[u8] = b"\x1b[A";
+/// Enter key (CR).
+const KEY_ENTER: u8 = b'\r';
+
 /// A single option in a TUI selection menu.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TuiMenuOption {
@@ -54,6 +61,42 @@ pub struct TuiMenu {
     pub highlighted_index: usize,
 }
 
+/// An answer to a permission prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PermissionPromptOption {
+    /// Display label (e.g., "Yes, and don't ask again this session")
+    pub label: String,
+    /// Keystroke that picks this answer (e.g., "1" or "y")
+    pub key: String,
+    /// Whether this answer grants the permission
+    pub allows: bool,
+}
+
+/// A permission confirmation prompt ("Do you want to make this edit?").
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PermissionPrompt {
+    /// Unique identifier for this prompt instance
+    pub id: String,
+    /// The question being asked
+    pub question: String,
+    /// Available answers, in display order
+    pub options: Vec<PermissionPromptOption>,
+    /// Tool the permission is for, when it can be inferred (e.g., "Edit", "Bash")
+    pub tool: Option<String>,
+}
+
+impl PermissionPrompt {
+    /// Create a new permission prompt.
+    pub fn new(question: String, options: Vec<PermissionPromptOption>, tool: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            question,
+            options,
+            tool,
+        }
+    }
+}
+
 /// Events for TUI menu lifecycle.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -68,6 +111,28 @@ pub enum TuiMenuEvent {
         session_id: Uuid,
         menu_id: String,
     },
+    /// A permission confirmation prompt was detected in terminal output
+    PermissionPromptPresented {
+        session_id: Uuid,
+        prompt: PermissionPrompt,
+    },
+    /// The permission prompt was answered
+    PermissionPromptAnswered {
+        session_id: Uuid,
+        prompt_id: String,
+    },
+}
+
+impl TuiMenuEvent {
+    /// Session the event belongs to.
+    pub fn session_id(&self) -> Uuid {
+        match self {
+            Self::MenuPresented { session_id, .. }
+            | Self::MenuDismissed { session_id, .. }
+            | Self::PermissionPromptPresented { session_id, .. }
+            | Self::PermissionPromptAnswered { session_id, .. } => *session_id,
+        }
+    }
 }
 
 impl TuiMenuOption {
@@ -138,6 +203,26 @@ impl TuiMenu {
     pub fn highlighted_option(&self) -> Option<&TuiMenuOption> {
         self.options.get(self.highlighted_index)
     }
+
+    /// Terminal input that selects the option at `index`.
+    ///
+    /// Moves the highlight from `highlighted_index` with up/down arrows, then
+    /// presses Enter. Returns None if `index` is out of range.
+    pub fn input_for_selection(&self, index: usize) -> Option<Vec<u8>> {
+        if index >= self.options.len() {
+            return None;
+        }
+
+        let (key, steps) = if index >= self.highlighted_index {
+            (KEY_DOWN, index - self.highlighted_index)
+        } else {
+            (KEY_UP, self.highlighted_index - index)
+        };
+
+        let mut input = key.repeat(steps);
+        input.push(KEY_ENTER);
+        Some(input)
+    }
 }
 
 #[cfg(test)]
@@ -370,6 +455,44 @@ mod tests {
         assert_eq!(menu.menu_type, TuiMenuType::ModelSelect);
     }
 
+    fn navigation_menu(highlighted_index: usize) -> TuiMenu {
+        let options = (0..5)
+            .map(|i| TuiMenuOption::new(i, format!("Option {i}"), None, false))
+            .collect();
+        TuiMenu::with_details(
+            "Select option".to_string(),
+            None,
+            options,
+            TuiMenuType::Generic,
+            highlighted_index,
+        )
+    }
+
+    #[test]
+    fn test_input_for_selection_below_highlight() {
+        let menu = navigation_menu(1);
+        assert_eq!(
+            menu.input_for_selection(3).unwrap(),
+            b"\x1b[B\x1b[B\r".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_input_for_selection_above_highlight() {
+        let menu = navigation_menu(3);
+        assert_eq!(
+            menu.input_for_selection(0).unwrap(),
+            b"\x1b[A\x1b[A\x1b[A\r".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_input_for_selection_current_and_out_of_range() {
+        let menu = navigation_menu(2);
+        assert_eq!(menu.input_for_selection(2).unwrap(), b"\r".to_vec());
+        assert!(menu.input_for_selection(5).is_none());
+    }
+
     // ==================== TuiMenuEvent Tests ====================
 
     #[test]
@@ -22,6 +22,8 @@ pub enum WsClientMessage {
     Ping { timestamp: u64 },
     /// Request current session state.
     GetState,
+    /// Request the current activity snapshot, answered with an `ActivityUpdate`.
+    GetActivity,
     /// Request terminal buffer (after resize).
     /// DEPRECATED: Use SyncRequest instead for reliable streaming.
     RequestBuffer,
@@ -38,6 +40,9 @@ pub enum WsClientMessage {
 
     /// Request synchronization on connect/reconnect.
     /// Client sends this after connection to sync state and request missed data.
+    /// If every chunk after `last_seq` is still buffered, the SyncResponse is
+    /// followed by a ChunkBatch of just those chunks; otherwise the response
+    /// carries the full buffer.
     SyncRequest {
         /// Last sequence number the client received (0 if fresh connection)
         last_seq: u64,
@@ -233,6 +238,14 @@ pub enum WsServerMessage {
         /// True if client needs to resync (their ack_seq < new_start_seq)
         requires_resync: bool,
     },
+    /// Terminal chunks dropped because the client fell behind.
+    /// Client should recover them with a RangeRequest.
+    TerminalGap {
+        /// First dropped sequence number (inclusive)
+        start_seq: u64,
+        /// Last dropped sequence number (inclusive)
+        end_seq: u64,
+    },
 
     // === Dimension Negotiation Protocol ===
 
@@ -299,8 +312,11 @@ pub enum WsServerMessage {
     /// Full chat history for a session.
     /// Sent in response to RequestChatHistory.
     ChatHistory {
-        /// All chat messages for the session (ordered by sequence)
+        /// Finalized chat messages for the session (ordered by sequence)
         messages: Vec<crate::ChatMessage>,
+        /// The assistant message still streaming, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        in_progress: Option<crate::ChatMessage>,
     },
     /// A new prompt was indexed (for Prompt Library real-time updates).
     NewPrompt {
@@ -387,6 +403,15 @@ pub enum WsServerMessage {
         mode: crate::PermissionMode,
     },
 
+    // === Liveness ===
+
+    /// Periodic liveness check of the session's process. With the activity's
+    /// busy flag this distinguishes an idle session from an unresponsive one.
+    Heartbeat {
+        session_id: Uuid,
+        alive: bool,
+    },
+
     // === TUI Menu Protocol ===
 
     /// TUI menu event for native UI rendering.
@@ -1130,6 +1155,17 @@ mod serialization_tests {
         }
     }
 
+    #[test]
+    fn test_heartbeat_serialization() {
+        let msg = WsServerMessage::Heartbeat {
+            session_id: Uuid::nil(),
+            alive: false,
+        };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains(r#""type":"heartbeat""#));
+        assert!(json.contains(r#""alive":false"#));
+    }
+
     // ========================================================================
     // TUI MENU TESTS
     // ========================================================================
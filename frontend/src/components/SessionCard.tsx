@@ -71,6 +71,11 @@ function StatusIndicator(props: { status: Session['status']; preview?: string; c
       return { icon: '●', text: props.currentStep!, color: 'var(--color-warning, #d4a644)' };
     }
 
+    // Claude asked a question and is waiting for an answer
+    if (stepLower === 'awaitinginput') {
+      return { icon: '▸', text: 'Waiting for your input', color: 'var(--color-accent)' };
+    }
+
     // Check if current step is "Ready" - show green
     const isReady = stepLower === 'ready' || props.preview?.toLowerCase() === 'ready';
     if (isReady) {
@@ -184,7 +189,7 @@ export function SessionCard(props: SessionCardProps) {
     const toolNames = ['read', 'edit', 'write', 'bash', 'grep', 'glob', 'task', 'search', 'webfetch', 'websearch'];
     if (step && toolNames.includes(step)) return true;
     // Also actively working if status is active but step is NOT ready
-    if (props.session.status === 'active' && step && step !== 'ready') return true;
+    if (props.session.status === 'active' && step && step !== 'ready' && step !== 'awaitinginput') return true;
     return false;
   });
 
@@ -217,6 +222,11 @@ export function SessionCard(props: SessionCardProps) {
       return '#2c8f7a';
     }
 
+    // Accent when Claude asked a question and expects an answer
+    if (step === 'awaitinginput') {
+      return 'var(--color-accent)';
+    }
+
     switch (status) {
       case 'active':
       case 'starting':
@@ -288,6 +298,23 @@ export function SessionCard(props: SessionCardProps) {
           </button>
         </div>
 
+        {/* Generated summary of what the session did */}
+        <Show when={props.session.subtitle}>
+          <div
+            style={{
+              'font-size': '12px',
+              color: 'var(--color-text-secondary)',
+              'margin-top': '-4px',
+              'margin-bottom': '10px',
+              overflow: 'hidden',
+              'text-overflow': 'ellipsis',
+              'white-space': 'nowrap',
+            }}
+          >
+            {props.session.subtitle}
+          </div>
+        </Show>
+
         {/* Status indicator only when no actions to display */}
         <Show when={showStatusIndicatorOnly()}>
           <StatusIndicator
@@ -331,6 +358,7 @@ export function SessionCard(props: SessionCardProps) {
               >
                 {props.session.status === 'stopped' ? '✓' :
                  props.session.status === 'waiting_input' ? '▸' :
+                 props.session.current_step === 'AwaitingInput' ? '▸' :
                  props.session.status === 'error' ? '✕' :
                  isActivelyWorking() ? '●' :
                  props.session.current_step?.toLowerCase() === 'ready' ? '✓' : '✓'}
@@ -345,6 +373,7 @@ export function SessionCard(props: SessionCardProps) {
               >
                 {props.session.status === 'stopped' ? 'Session ended' :
                  props.session.status === 'waiting_input' ? 'Waiting for input' :
+                 props.session.current_step === 'AwaitingInput' ? 'Waiting for input' :
                  props.session.status === 'error' ? 'Error' :
                  isActivelyWorking() ? (
                    props.session.current_step?.toLowerCase() === 'thinking' ? 'Thinking' :
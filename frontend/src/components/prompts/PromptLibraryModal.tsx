@@ -314,6 +314,7 @@ export function PromptLibraryModal(props: PromptLibraryModalProps) {
                             </span>
                             <span>{formatRelativeTime(prompt.timestamp)}</span>
                             <span>{prompt.word_count} words</span>
+                            {prompt.use_count > 1 && <span>used {prompt.use_count}×</span>}
                           </div>
                         </div>
 
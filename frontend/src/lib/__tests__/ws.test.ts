@@ -908,6 +908,29 @@ describe('WebSocketManager', () => {
     });
   });
 
+  describe('Terminal Gap', () => {
+    it('requests dropped chunks with a range_request', () => {
+      const manager = createManager();
+      manager.connect();
+      simulateOpen();
+
+      simulateMessage({
+        type: 'sync_response',
+        buffer_start_seq: 0,
+        buffer_end_seq: 10,
+        cols: 80,
+        rows: 24,
+      });
+      mockWsSend.mockClear();
+
+      simulateMessage({ type: 'terminal_gap', start_seq: 11, end_seq: 40 });
+
+      expect(mockWsSend).toHaveBeenCalledWith(
+        JSON.stringify({ type: 'range_request', start_seq: 11, end_seq: 40 })
+      );
+    });
+  });
+
   describe('iOS Lifecycle', () => {
     it('suspend closes connection and sets suspended state', () => {
       const manager = createManager();
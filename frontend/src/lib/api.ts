@@ -21,10 +21,25 @@ export interface Session {
   output_tokens: number;
   context_percent: number;
   preview: string;
+  subtitle?: string;
   current_step?: string;
   recent_actions: RecentAction[];
 }
 
+export interface SessionNameChange {
+  old_name: string;
+  new_name: string;
+  changed_at: string;
+}
+
+export interface SessionDetail extends Session {
+  name_history: SessionNameChange[];
+}
+
+export interface SessionTagsResponse {
+  tags: string[];
+}
+
 export interface SessionListResponse {
   sessions: Session[];
   active_count: number;
@@ -48,7 +63,10 @@ export interface ProjectsResponse {
 }
 
 export interface CreateProjectRequest {
-  name: string;
+  /** New directory name, or display name when registering `path` */
+  name?: string;
+  /** Existing directory to register (relative to projects root, or `~`-prefixed) */
+  path?: string;
 }
 
 export interface CreateSessionResponse {
@@ -63,13 +81,16 @@ export interface Interaction {
   session_id: string;
   sequence_number: number;
   user_prompt: string;
+  prompt_truncated?: boolean;
   assistant_summary?: string;
   started_at: string;
   ended_at?: string;
   cost_usd_delta: number;
   input_tokens_delta: number;
   output_tokens_delta: number;
-  status: 'active' | 'completed' | 'error';
+  thinking_tokens_delta?: number;
+  context_percent?: number;
+  status: 'active' | 'completed' | 'failed' | 'interrupted';
   error_message?: string;
 }
 
@@ -99,6 +120,7 @@ export interface ToolInvocation {
   tool_input?: string;
   tool_output_preview?: string;
   is_error: boolean;
+  exit_code?: number;
   file_path?: string;
   duration_ms?: number;
   created_at: string;
@@ -125,6 +147,7 @@ export interface FileDiff {
   hunks: DiffHunk[];
   is_identical: boolean;
   is_binary: boolean;
+  decode_error?: boolean;
 }
 
 export interface FileChangeWithDiff {
@@ -299,6 +322,7 @@ export interface Prompt {
   timestamp: number;
   word_count: number;
   char_count: number;
+  use_count: number;
 }
 
 export interface PromptSummary {
@@ -307,6 +331,7 @@ export interface PromptSummary {
   project_name: string;
   timestamp: number;
   word_count: number;
+  use_count: number;
 }
 
 export interface PromptsListResponse {
@@ -336,9 +361,10 @@ async function fetchJSON<T>(url: string, options?: RequestInit): Promise<T> {
 
 export const api = {
   sessions: {
-    list: () => fetchJSON<SessionListResponse>('/sessions'),
+    list: (tag?: string) =>
+      fetchJSON<SessionListResponse>(tag ? `/sessions?tag=${encodeURIComponent(tag)}` : '/sessions'),
 
-    get: (id: string) => fetchJSON<Session>(`/sessions/${id}`),
+    get: (id: string) => fetchJSON<SessionDetail>(`/sessions/${id}`),
 
     create: (req: CreateSessionRequest) =>
       fetchJSON<CreateSessionResponse>('/sessions', {
@@ -353,8 +379,14 @@ export const api = {
         body: JSON.stringify({ prompt }),
       }),
 
-    resume: (id: string) =>
-      fetch(`${BASE_URL}/sessions/${id}/resume`, { method: 'POST' }),
+    resume: (id: string, workingDir?: string) =>
+      fetch(`${BASE_URL}/sessions/${id}/resume`, workingDir
+        ? {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ working_dir: workingDir }),
+          }
+        : { method: 'POST' }),
 
     terminate: (id: string) =>
       fetch(`${BASE_URL}/sessions/${id}`, { method: 'DELETE' }),
@@ -362,6 +394,12 @@ export const api = {
     delete: (id: string) =>
       fetch(`${BASE_URL}/sessions/${id}/delete`, { method: 'DELETE' }),
 
+    update: (id: string, changes: { model?: string; project_path?: string }) =>
+      fetchJSON<Session>(`/sessions/${id}`, {
+        method: 'PATCH',
+        body: JSON.stringify(changes),
+      }),
+
     rename: (id: string, name: string) =>
       fetch(`${BASE_URL}/sessions/${id}/name`, {
         method: 'PUT',
@@ -369,6 +407,20 @@ export const api = {
         body: JSON.stringify({ name }),
       }),
 
+    tags: (id: string) => fetchJSON<SessionTagsResponse>(`/sessions/${id}/tags`),
+
+    addTag: (id: string, tag: string) =>
+      fetchJSON<SessionTagsResponse>(`/sessions/${id}/tags`, {
+        method: 'POST',
+        body: JSON.stringify({ tag }),
+      }),
+
+    removeTag: (id: string, tag: string) =>
+      fetchJSON<SessionTagsResponse>(`/sessions/${id}/tags`, {
+        method: 'DELETE',
+        body: JSON.stringify({ tag }),
+      }),
+
     sendInput: (id: string, content: string) =>
       fetch(`${BASE_URL}/sessions/${id}/input`, {
         method: 'POST',
@@ -419,7 +471,23 @@ export const api = {
       );
     },
 
-    get: (id: string) => fetchJSON<InteractionDetailResponse>(`/interactions/${id}`),
+    get: (id: string, options?: { ignoreWhitespace?: boolean; ignoreBlankLines?: boolean }) => {
+      const params = new URLSearchParams();
+      if (options?.ignoreWhitespace) params.set('ignore_whitespace', 'true');
+      if (options?.ignoreBlankLines) params.set('ignore_blank_lines', 'true');
+      const query = params.toString();
+      return fetchJSON<InteractionDetailResponse>(`/interactions/${id}${query ? `?${query}` : ''}`);
+    },
+
+    failed: (options?: { since?: string; toolErrors?: boolean; limit?: number; offset?: number }) => {
+      const params = new URLSearchParams();
+      if (options?.since) params.set('since', options.since);
+      if (options?.toolErrors) params.set('tool_errors', 'true');
+      if (options?.limit) params.set('limit', options.limit.toString());
+      if (options?.offset) params.set('offset', options.offset.toString());
+      const query = params.toString();
+      return fetchJSON<Interaction[]>(`/interactions/failed${query ? `?${query}` : ''}`);
+    },
 
     filesChanged: (sessionId: string) =>
       fetchJSON<FilesChangedResponse>(`/sessions/${sessionId}/files-changed`),
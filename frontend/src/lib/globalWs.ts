@@ -5,6 +5,7 @@ import { createSignal } from 'solid-js';
 import { updateSessionFromActivity, updateSessionStatus } from '../stores/sessions';
 import { addNewPrompt } from '../stores/prompts';
 import type { Session, PromptSummary } from './api';
+import { WS_SUBPROTOCOL } from './ws';
 
 export type GlobalWsState = 'connecting' | 'connected' | 'disconnected' | 'reconnecting';
 
@@ -85,6 +86,7 @@ function handleMessage(event: MessageEvent) {
           project_name: data.prompt.project_name,
           timestamp: data.prompt.timestamp,
           word_count: data.prompt.word_count,
+          use_count: data.prompt.use_count ?? 1,
         };
         addNewPrompt(prompt);
         break;
@@ -123,7 +125,7 @@ export function connectGlobalWs() {
   try {
     const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
     const wsUrl = `${protocol}//${window.location.host}/ws/events`;
-    ws = new WebSocket(wsUrl);
+    ws = new WebSocket(wsUrl, WS_SUBPROTOCOL);
 
     ws.onopen = () => {
       setGlobalWsState('connected');
@@ -4,6 +4,9 @@
 
 import { getRecommendedFontSize } from './fonts';
 
+// Message format version negotiated via Sec-WebSocket-Protocol
+export const WS_SUBPROTOCOL = 'clauset.v1';
+
 export type ConnectionState =
   | 'initial'      // Never connected
   | 'connecting'   // Active connection attempt
@@ -50,6 +53,12 @@ export interface BufferOverflow {
   requires_resync: boolean;
 }
 
+// Terminal chunks the server dropped because this client fell behind
+export interface TerminalGap {
+  start_seq: number;
+  end_seq: number;
+}
+
 // Dimension negotiation response from server
 export interface DimensionsConfirmed {
   cols: number;
@@ -366,7 +375,7 @@ export function createWebSocketManager(options: WebSocketManagerOptions) {
     try {
       const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
       const wsUrl = options.url.startsWith('ws') ? options.url : `${protocol}//${window.location.host}${options.url}`;
-      ws = new WebSocket(wsUrl);
+      ws = new WebSocket(wsUrl, WS_SUBPROTOCOL);
 
       ws.onopen = () => {
         setState('connected');
@@ -450,6 +459,9 @@ export function createWebSocketManager(options: WebSocketManagerOptions) {
       case 'buffer_overflow':
         handleBufferOverflow(data as unknown as { type: string } & BufferOverflow);
         break;
+      case 'terminal_gap':
+        handleTerminalGap(data as unknown as { type: string } & TerminalGap);
+        break;
       case 'dimensions_confirmed':
         handleDimensionsConfirmed(data as unknown as { type: string } & DimensionsConfirmed);
         break;
@@ -577,6 +589,14 @@ export function createWebSocketManager(options: WebSocketManagerOptions) {
     }
   }
 
+  function handleTerminalGap(gap: { type: string } & TerminalGap) {
+    console.warn(`Terminal gap: server dropped seq ${gap.start_seq}..${gap.end_seq}`);
+
+    if (gap.end_seq > streamState.lastContiguousSeq) {
+      sendRangeRequest(Math.max(gap.start_seq, streamState.lastContiguousSeq + 1), gap.end_seq);
+    }
+  }
+
   function sendSyncRequest() {
     if (ws?.readyState === WebSocket.OPEN) {
       const syncRequest = {
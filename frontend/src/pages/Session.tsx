@@ -105,7 +105,7 @@ function getStatusDotColor(status: Session['status'], currentStep?: string): str
   }
   if (status === 'active' || status === 'starting' || status === 'waiting_input') {
     // Check if actively working (not just ready)
-    if (currentStep && currentStep !== 'Ready' && currentStep.length > 0) {
+    if (currentStep && currentStep !== 'Ready' && currentStep !== 'AwaitingInput' && currentStep.length > 0) {
       return '#c45b37'; // Orange - thinking/working
     }
     return '#2c8f7a'; // Green - ready
@@ -117,7 +117,7 @@ function getStatusDotColor(status: Session['status'], currentStep?: string): str
 function StatusDot(props: { status: Session['status']; currentStep?: string }) {
   const color = () => getStatusDotColor(props.status, props.currentStep);
   const isActive = () => props.status === 'active' || props.status === 'starting' || props.status === 'waiting_input';
-  const isWorking = () => isActive() && props.currentStep && props.currentStep !== 'Ready' && props.currentStep.length > 0;
+  const isWorking = () => isActive() && props.currentStep && props.currentStep !== 'Ready' && props.currentStep !== 'AwaitingInput' && props.currentStep.length > 0;
 
   return (
     <span
@@ -463,10 +463,14 @@ export default function SessionPage() {
         break;
       }
       case 'chat_history': {
-        // Full chat history from backend (on connect)
-        const chatMessages = (msg as unknown as { messages: ChatMessage[] }).messages;
+        // Full chat history from backend (on connect), plus any message still streaming
+        const history = msg as unknown as { messages: ChatMessage[]; in_progress?: ChatMessage };
+        const chatMessages = history.messages;
         if (chatMessages && Array.isArray(chatMessages)) {
-          handleChatHistory(params.id, chatMessages);
+          handleChatHistory(
+            params.id,
+            history.in_progress ? [...chatMessages, history.in_progress] : chatMessages,
+          );
           scrollToBottom();
         }
         break;
@@ -699,7 +703,7 @@ export default function SessionPage() {
     if (!s) return false;
     if (s.status === 'stopped' || s.status === 'error') return false;
     // Processing if has current step or messages are streaming
-    const hasCurrentStep = s.current_step && s.current_step !== 'Ready' && s.current_step.length > 0;
+    const hasCurrentStep = s.current_step && s.current_step !== 'Ready' && s.current_step !== 'AwaitingInput' && s.current_step.length > 0;
     const hasStreaming = currentStreamingId() !== null;
     return hasCurrentStep || hasStreaming;
   };
@@ -666,6 +666,25 @@ describe('Messages Store', () => {
       });
     });
 
+    describe('content_replaced event', () => {
+      it('replaces the streamed content', () => {
+        const sessionId = 'replaced-session';
+        const messageId = 'replaced-msg';
+
+        addMessage(sessionId, {
+          id: messageId,
+          role: 'assistant',
+          content: 'Helo wrld',
+          timestamp: Date.now(),
+        });
+
+        handleChatEvent({ type: 'content_replaced', session_id: sessionId, message_id: messageId, content: 'Hello world' });
+
+        const messages = getMessagesForSession(sessionId);
+        expect(messages[0].content).toBe('Hello world');
+      });
+    });
+
     describe('tool_call_start event', () => {
       it('adds tool call to message', () => {
         const sessionId = 'tool-start-session';
@@ -423,6 +423,7 @@ export interface ChatToolCall {
 export type ChatEvent =
   | { type: 'message'; session_id: string; message: ChatMessage }
   | { type: 'content_delta'; session_id: string; message_id: string; delta: string }
+  | { type: 'content_replaced'; session_id: string; message_id: string; content: string }
   | { type: 'thinking_delta'; session_id: string; message_id: string; delta: string }
   | { type: 'tool_call_start'; session_id: string; message_id: string; tool_call: ChatToolCall }
   | { type: 'tool_call_complete'; session_id: string; message_id: string; tool_call_id: string; output: string; is_error: boolean }
@@ -573,6 +574,21 @@ export function handleChatEvent(event: ChatEvent) {
       break;
     }
 
+    case 'content_replaced': {
+      // Replace the streamed content with the reconciled text
+      setMessages((prev) => {
+        const newMap = new Map(prev);
+        const sessionMessages = newMap.get(event.session_id) ?? [];
+        const updatedMessages = sessionMessages.map((msg) =>
+          msg.id === event.message_id ? { ...msg, content: event.content } : msg
+        );
+        newMap.set(event.session_id, updatedMessages);
+        saveToStorage(event.session_id, updatedMessages);
+        return newMap;
+      });
+      break;
+    }
+
     case 'thinking_delta': {
       // Update the message thinking content with the delta
       setMessages((prev) => {
@@ -26,9 +26,24 @@ export interface TuiMenu {
   highlighted_index: number;
 }
 
+export interface PermissionPromptOption {
+  label: string;
+  key: string;
+  allows: boolean;
+}
+
+export interface PermissionPrompt {
+  id: string;
+  question: string;
+  options: PermissionPromptOption[];
+  tool?: string;
+}
+
 export type TuiMenuEvent =
   | { type: 'menu_presented'; session_id: string; menu: TuiMenu }
-  | { type: 'menu_dismissed'; session_id: string; menu_id: string };
+  | { type: 'menu_dismissed'; session_id: string; menu_id: string }
+  | { type: 'permission_prompt_presented'; session_id: string; prompt: PermissionPrompt }
+  | { type: 'permission_prompt_answered'; session_id: string; prompt_id: string };
 
 export type TuiMenuState =
   | { type: 'idle' }